@@ -0,0 +1,150 @@
+"""
+User administration from the command line, for headless servers where
+nobody can sign in to the web app (no admin yet, or the only one locked
+out). Works on the user database directly (USER_DATABASE_URL), so run it
+with the same environment as the backend; it can run while the backend does.
+
+    python MainApp/admin_cli.py create-user alice --name "Alice" --email alice@example.org --role admin
+    python MainApp/admin_cli.py reset-password alice
+    python MainApp/admin_cli.py set-role alice onsite
+    python MainApp/admin_cli.py unlock alice
+    python MainApp/admin_cli.py audit --limit 20 --user alice
+
+Passwords are prompted for (or read from stdin with --password-stdin) and
+must meet the password policy. Every change is written to the audit log
+under --actor (default "admin-cli"); role changes and password resets sign
+the user out everywhere, as they do from the web app.
+"""
+
+import argparse
+import getpass
+import sys
+from typing import Any, List, Optional, get_args
+
+from pydantic import TypeAdapter, ValidationError
+
+import login
+from password_policy import is_breached
+from schemas import Email, Password, UserRole, Username
+
+ROLES = get_args(UserRole)
+
+
+class CliError(Exception):
+    pass
+
+
+def _valid(kind: Any, value: str) -> str:
+    """The value as the API would accept it; CliError with the API's message otherwise."""
+    try:
+        return TypeAdapter(kind).validate_python(value)
+    except ValidationError as e:
+        error = e.errors()[0]
+        raise CliError(str(error.get("ctx", {}).get("error") or error["msg"]))
+
+
+def _read_password(from_stdin: bool) -> str:
+    if from_stdin:
+        password = sys.stdin.readline().rstrip("\n")
+    else:
+        password = getpass.getpass("New password: ")
+        if getpass.getpass("Repeat it: ") != password:
+            raise CliError("The passwords don't match")
+    password = _valid(Password, password)
+    if is_breached(password):
+        raise CliError("This password has appeared in a data breach; choose a different one")
+    return password
+
+
+def _existing(username: str) -> tuple:
+    row = login.get_user_row(username)
+    if not row:
+        raise CliError(f"No user named {username}")
+    return row
+
+
+def create_user(args: argparse.Namespace) -> str:
+    username = _valid(Username, args.username)
+    email = _valid(Email, args.email)
+    if login.get_user_row(username):
+        raise CliError(f"{username} already exists")
+    if login.email_in_use(email):
+        raise CliError("Another account already uses that email")
+    password = _read_password(args.password_stdin)
+    if not login.add_user(username, args.name or username, password, args.role, email):
+        raise CliError(f"{username} already exists")
+    login.log_action(args.actor, f"Created {username} ({args.role}) from the command line")
+    return f"Created {username} ({args.role})"
+
+
+def reset_password(args: argparse.Namespace) -> str:
+    _existing(args.username)
+    login.reset_password(args.username, _read_password(args.password_stdin))
+    login.log_action(args.actor, f"Reset the password of {args.username} from the command line")
+    return f"Reset the password of {args.username}; their sessions were signed out"
+
+
+def set_role(args: argparse.Namespace) -> str:
+    row = _existing(args.username)
+    if row[2] == args.role:
+        return f"{args.username} is already {args.role}"
+    if row[2] == "admin" and row[5] and login.count_admins() <= 1:
+        raise CliError("Can't demote the last admin")
+    login.update_user(args.username, {"role": args.role}, args.actor)
+    return f"{args.username} is now {args.role} (was {row[2]}); their sessions were signed out"
+
+
+def unlock(args: argparse.Namespace) -> str:
+    if not login.unlock_user(args.username, args.actor):
+        raise CliError(f"No user named {args.username}")
+    return f"Unlocked {args.username}"
+
+
+def audit(args: argparse.Namespace) -> str:
+    rows = login.get_audit_logs(limit=args.limit, username=args.user)
+    return "\n".join(f"{timestamp}  {username:<20} {action}" for timestamp, username, action in reversed(rows)) or "No audit events"
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    parser = argparse.ArgumentParser(description="Manage users in the user database")
+    parser.add_argument("--actor", default="admin-cli", help='who the audit log credits with changes (default: "admin-cli")')
+    commands = parser.add_subparsers(dest="command", required=True)
+
+    create = commands.add_parser("create-user", help="create an account")
+    create.add_argument("username")
+    create.add_argument("--email", required=True)
+    create.add_argument("--name", help="display name (default: the username)")
+    create.add_argument("--role", choices=ROLES, default="user")
+    create.add_argument("--password-stdin", action="store_true", help="read the password from stdin instead of prompting")
+    create.set_defaults(run=create_user)
+
+    reset = commands.add_parser("reset-password", help="set a new password and sign the user out")
+    reset.add_argument("username")
+    reset.add_argument("--password-stdin", action="store_true", help="read the password from stdin instead of prompting")
+    reset.set_defaults(run=reset_password)
+
+    role = commands.add_parser("set-role", help="change a user's role and sign them out")
+    role.add_argument("username")
+    role.add_argument("role", choices=ROLES)
+    role.set_defaults(run=set_role)
+
+    unlock_parser = commands.add_parser("unlock", help="lift a lockout after failed logins")
+    unlock_parser.add_argument("username")
+    unlock_parser.set_defaults(run=unlock)
+
+    audit_parser = commands.add_parser("audit", help="recent audit events, oldest first")
+    audit_parser.add_argument("--limit", type=int, default=50, help="how many (default: 50)")
+    audit_parser.add_argument("--user", help="only this user's")
+    audit_parser.set_defaults(run=audit)
+
+    args = parser.parse_args(argv)
+    try:
+        print(args.run(args))
+    except CliError as e:
+        print(f"❌ {e}", file=sys.stderr)
+        return 1
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -7,6 +7,8 @@ from PIL import Image
 import time
 import logging
 
+from request_logging import REQUEST_ID_HEADER, request_id_var
+
 # Configure logging
 logging.basicConfig(level=logging.INFO)
 logger = logging.getLogger(__name__)
@@ -46,6 +48,9 @@ class OllamaClient:
         """Make a POST request with retry logic."""
         url = f"{self.endpoint}{path}"
         headers = {"Content-Type": "application/json"}
+        request_id = request_id_var.get()
+        if request_id:
+            headers[REQUEST_ID_HEADER] = request_id
 
         last_exception = None
         for attempt in range(self.max_retries):
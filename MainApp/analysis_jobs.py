@@ -0,0 +1,343 @@
+"""
+Image analysis by tier: each tier has its own worker pool and time budget.
+
+Tiers in ANALYSIS_INLINE_TIERS (by default just instant, which takes a
+second or two) are analysed during the request. The others are stored as
+jobs in the analysis_jobs table and picked up by that tier's workers, so
+a slow thorough analysis neither holds a request open nor delays quick
+ones. The queue is the table itself: jobs a stopped process left running
+are queued again on start, up to ANALYSIS_JOB_MAX_ATTEMPTS tries.
+
+While a job runs its stage moves through preprocessing (loading the image),
+inference (the provider call) and saving (recording the result), which
+GET /api/analyze/jobs/{id}/events streams to the client.
+
+Results are cached by image content hash and tier for ANALYSIS_CACHE_DAYS,
+so uploading the same photo again answers at once without another model
+call, unless the client asks for a refresh.
+
+A batch (submit_batch) is one job per image sharing a batch id. Batches are
+always queued, even for inline tiers, so every tier has a worker pool.
+
+When a queued job finishes or fails, the webhooks of whoever queued it are
+notified (see webhooks.py).
+
+Each stage is a tracing span (see tracing.py) and is timed for /metrics
+(see metrics.py), along with every provider call's outcome.
+"""
+
+import hashlib
+import logging
+import threading
+import time
+import uuid
+from contextlib import contextmanager
+from dataclasses import asdict
+from datetime import datetime, timedelta
+from typing import Any, Dict, Iterator, List, Optional, Tuple
+
+import image_embeddings
+import image_store
+import metrics
+import tracing
+import webhooks
+from analysis_providers import TIERS, check_configuration, provider_for
+from config import (
+    ANALYSIS_CACHE_DAYS,
+    ANALYSIS_INLINE_TIERS,
+    ANALYSIS_JOB_MAX_ATTEMPTS,
+    ANALYSIS_JOB_RETENTION_HOURS,
+    ANALYSIS_WORKERS,
+)
+from database import (
+    cache_analysis,
+    claim_analysis_job,
+    create_analysis_job,
+    delete_cached_analyses,
+    delete_finished_analysis_jobs,
+    finish_analysis_job,
+    get_analysis_batch,
+    get_analysis_job,
+    get_cached_analysis,
+    requeue_interrupted_jobs,
+    set_analysis_job_stage,
+)
+from request_logging import request_id_var
+
+logger = logging.getLogger(__name__)
+
+# Idle workers look for new jobs at least this often, in case a wake-up was missed
+IDLE_POLL_SECONDS = 5
+
+
+def normalize_tier(tier: Optional[str]) -> str:
+    """The API tier for a requested one; unknown tiers get "fast"."""
+    tier = (tier or "fast").lower()
+    return tier if tier in TIERS else "fast"
+
+
+def runs_inline(tier: str) -> bool:
+    return tier in ANALYSIS_INLINE_TIERS
+
+
+@contextmanager
+def _stage(tier: str, stage: str, **attributes: Any) -> Iterator[Optional[Any]]:
+    """Trace a stage of an analysis and time it for /metrics; yields the span."""
+    started = time.perf_counter()
+    try:
+        with tracing.span(f"analysis.{stage}", tier=tier, **attributes) as span:
+            yield span
+    finally:
+        metrics.ANALYSIS_STAGE_SECONDS.labels(tier, stage).observe(time.perf_counter() - started)
+
+
+def cached_result(image_bytes: bytes, tier: str) -> Optional[Dict[str, Any]]:
+    """An earlier result for the same image bytes and tier from the tier's current provider, or None."""
+    if ANALYSIS_CACHE_DAYS <= 0:
+        return None
+    result = get_cached_analysis(
+        hashlib.sha256(image_bytes).hexdigest(),
+        tier,
+        provider_for(tier).name,
+        datetime.utcnow() - timedelta(days=ANALYSIS_CACHE_DAYS),
+    )
+    return {**result, "cached": True} if result else None
+
+
+def analyze(image_bytes: bytes, tier: str, refresh: bool = False) -> Dict[str, Any]:
+    """
+    Analyse an image with the tier's provider and return the API result:
+    the best candidate, plus all of them. A cached result is returned unless
+    ``refresh`` is set; a new one replaces it. May raise RuntimeError when
+    the provider's service fails.
+    """
+    started = time.perf_counter()
+    with tracing.span("analysis", tier=tier, refresh=refresh) as span:
+        result = _analyze(image_bytes, tier, refresh)
+        if span is not None:
+            span.set_attribute("cached", result["cached"])
+    metrics.ANALYSIS_SECONDS.labels(tier, str(result["cached"]).lower()).observe(time.perf_counter() - started)
+    return result
+
+
+def _analyze(image_bytes: bytes, tier: str, refresh: bool) -> Dict[str, Any]:
+    if not refresh:
+        cached = cached_result(image_bytes, tier)
+        if cached:
+            return cached
+
+    provider = provider_for(tier)
+    started = time.time()
+    with _stage(tier, "inference", provider=provider.name):
+        try:
+            candidates = provider.analyze(image_bytes, tier)
+        except Exception:
+            metrics.PROVIDER_REQUESTS.labels(provider.name, tier, "error").inc()
+            raise
+        metrics.PROVIDER_REQUESTS.labels(provider.name, tier, "ok" if candidates else "empty").inc()
+    if not candidates:
+        raise RuntimeError(f"{provider.describe(tier)} could not identify the artifact")
+    best = candidates[0]
+    result = {
+        "name": best.name,
+        "description": best.description,
+        "confidence": best.confidence,
+        "method": provider.describe(tier),
+        "provider": provider.name,
+        "tier": tier,
+        "analysis_time": f"{time.time() - started:.1f}s",
+        "candidates": [asdict(candidate) for candidate in candidates],
+    }
+    try:
+        # For similarity search with this image (see image_embeddings.py)
+        with _stage(tier, "embedding"):
+            result["embedding"] = image_embeddings.embed(image_bytes).tolist()
+    except Exception as e:
+        logger.warning(f"Could not embed the analysed image: {e}")
+    if ANALYSIS_CACHE_DAYS > 0:
+        try:
+            with _stage(tier, "caching"):
+                cache_analysis(hashlib.sha256(image_bytes).hexdigest(), tier, provider.name, result)
+        except Exception as e:
+            logger.warning(f"Could not cache the {tier} analysis: {e}")
+    return {**result, "cached": False}
+
+
+def submit(image_bytes: bytes, tier: str, requested_by: Optional[str] = None) -> Dict[str, Any]:
+    """Queue an analysis for the tier's workers and return the new job."""
+    job = create_analysis_job(tier, image_bytes, request_id=request_id_var.get(), requested_by=requested_by)
+    pool = _pools.get(tier)
+    if pool:
+        pool.wake()
+    return job
+
+
+def get_job(job_id: str) -> Optional[Dict[str, Any]]:
+    return get_analysis_job(job_id)
+
+
+def submit_batch(
+    images: List[Tuple[Optional[str], bytes, Optional[str]]],
+    tier: str,
+    refresh: bool = False,
+    requested_by: Optional[str] = None,
+) -> Dict[str, Any]:
+    """
+    Queue a job per image and return the new batch. ``images`` are
+    (filename, bytes, problem) in upload order; an image with a problem
+    (e.g. not an image at all) is recorded as failed with that error, and
+    cached results are recorded as done at once unless ``refresh`` is set.
+    """
+    batch_id = uuid.uuid4().hex
+    request_id = request_id_var.get()
+    for index, (filename, image_bytes, problem) in enumerate(images):
+        job = {
+            "batch_id": batch_id,
+            "batch_index": index,
+            "filename": filename,
+            "request_id": request_id,
+            "requested_by": requested_by,
+        }
+        if problem:
+            create_analysis_job(tier, image_bytes, error=problem, **job)
+            continue
+        cached = None if refresh else cached_result(image_bytes, tier)
+        create_analysis_job(tier, image_bytes, result=cached, **job)
+    pool = _pools.get(tier)
+    if pool:
+        pool.wake_all()
+    return get_batch(batch_id)
+
+
+def get_batch(batch_id: str) -> Optional[Dict[str, Any]]:
+    """
+    A batch with each image's job: status is queued or running until every
+    item is done or failed, then done. None if there is no such batch.
+    """
+    jobs = get_analysis_batch(batch_id)
+    if not jobs:
+        return None
+    counts = {status: sum(1 for job in jobs if job["status"] == status) for status in ("queued", "running", "done", "failed")}
+    if counts["queued"] + counts["running"] == 0:
+        status = "done"
+    else:
+        status = "running" if counts["running"] or counts["done"] or counts["failed"] else "queued"
+    return {
+        "id": batch_id,
+        "tier": jobs[0]["tier"],
+        "requested_by": jobs[0]["requested_by"],
+        "status": status,
+        "total": len(jobs),
+        "done": counts["done"],
+        "failed": counts["failed"],
+        "items": [
+            {
+                "index": index,
+                "filename": job["filename"],
+                "job_id": job["id"],
+                "status": job["status"],
+                "result": job["result"],
+                "error": job["error"],
+            }
+            for index, job in enumerate(jobs)
+        ],
+    }
+
+
+class _TierPool:
+    """Worker threads that take one tier's jobs from the queue, oldest first."""
+
+    def __init__(self, tier: str, size: int):
+        self.tier = tier
+        self.size = max(1, size)
+        self._wakeup = threading.Condition()
+        self._stopping = False
+        self._threads = []
+
+    def start(self) -> None:
+        for n in range(self.size):
+            thread = threading.Thread(target=self._work, name=f"analysis-{self.tier}-{n + 1}", daemon=True)
+            thread.start()
+            self._threads.append(thread)
+
+    def wake(self) -> None:
+        with self._wakeup:
+            self._wakeup.notify()
+
+    def wake_all(self) -> None:
+        with self._wakeup:
+            self._wakeup.notify_all()
+
+    def stop(self) -> None:
+        """Let idle workers exit; a running analysis finishes, or is requeued on the next start."""
+        with self._wakeup:
+            self._stopping = True
+            self._wakeup.notify_all()
+
+    def _work(self) -> None:
+        while not self._stopping:
+            try:
+                job = claim_analysis_job(self.tier)
+            except Exception:
+                logger.exception(f"Could not take a {self.tier} analysis job from the queue")
+                job = None
+            if job is None:
+                with self._wakeup:
+                    if not self._stopping:
+                        self._wakeup.wait(IDLE_POLL_SECONDS)
+                continue
+            self._run(job)
+
+    def _run(self, job: Dict[str, Any]) -> None:
+        # Log lines and the Ollama call carry the id of the request that queued the job
+        token = request_id_var.set(job.get("request_id"))
+        try:
+            with tracing.span("analysis.job", tier=self.tier, job_id=job["id"], batch_id=job.get("batch_id")):
+                try:
+                    # Claiming the job put it in the preprocessing stage
+                    with _stage(self.tier, "preprocessing"):
+                        image_bytes = image_store.get(job["image_hash"])
+                    set_analysis_job_stage(job["id"], "inference")
+                    # Jobs are only queued when there was no cached result to use
+                    result = analyze(image_bytes, self.tier, refresh=True)
+                except Exception as e:
+                    logger.error(f"Analysis job {job['id']} ({self.tier}) failed: {e}")
+                    finish_analysis_job(job["id"], error=str(e) or "Analysis failed")
+                    event = "analysis.failed"
+                else:
+                    logger.info(f"Analysis job {job['id']} ({self.tier}) done in {result['analysis_time']}")
+                    with _stage(self.tier, "saving"):
+                        set_analysis_job_stage(job["id"], "saving")
+                        finish_analysis_job(job["id"], result=result)
+                    event = "analysis.done"
+        finally:
+            request_id_var.reset(token)
+        if job.get("requested_by"):
+            webhooks.notify(job["requested_by"], event, {"job": get_analysis_job(job["id"])})
+
+
+_pools: Dict[str, _TierPool] = {}
+
+
+def start() -> None:
+    """Requeue interrupted jobs, forget old finished jobs and results, and start a pool per tier."""
+    check_configuration()
+    requeued = requeue_interrupted_jobs(ANALYSIS_JOB_MAX_ATTEMPTS)
+    if requeued:
+        logger.info(f"Requeued {requeued} analysis job(s) interrupted by a restart")
+    delete_finished_analysis_jobs(datetime.utcnow() - timedelta(hours=ANALYSIS_JOB_RETENTION_HOURS))
+    delete_cached_analyses(datetime.utcnow() - timedelta(days=max(ANALYSIS_CACHE_DAYS, 0)))
+
+    for tier in TIERS:
+        # Inline tiers' pools only see batch jobs
+        if tier in _pools:
+            continue
+        pool = _TierPool(tier, ANALYSIS_WORKERS.get(tier, 1))
+        pool.start()
+        _pools[tier] = pool
+
+
+def stop() -> None:
+    for pool in _pools.values():
+        pool.stop()
+    _pools.clear()
@@ -0,0 +1,233 @@
+"""
+Analysis providers: what actually looks at an image for a tier.
+
+A provider takes image bytes and a tier and returns candidate
+identifications, best first. analysis_jobs asks provider_for(tier), which
+follows ANALYSIS_PROVIDERS, so switching AI services is a configuration
+change rather than a change to the routes. To add one, subclass
+AnalysisProvider and register it in PROVIDERS.
+
+A provider that can't run here (see available()) is replaced by
+ANALYSIS_FALLBACK_PROVIDER, so e.g. the instant tier uses the local ONNX
+model when it is installed and Ollama otherwise.
+"""
+
+import logging
+import os
+import threading
+from abc import ABC, abstractmethod
+from dataclasses import dataclass
+from io import BytesIO
+from typing import Dict, List, Optional, Tuple, Type
+
+import numpy as np
+from PIL import Image
+
+from config import (
+    ANALYSIS_FALLBACK_PROVIDER,
+    ANALYSIS_PROVIDERS,
+    ANALYSIS_TIMEOUTS,
+    ONNX_LABELS_PATH,
+    ONNX_MODEL_PATH,
+    ONNX_TOP_K,
+)
+from fast_analyzer import FastAnalyzer
+
+logger = logging.getLogger(__name__)
+
+TIERS = ("instant", "fast", "balanced", "thorough")
+
+
+@dataclass
+class Candidate:
+    """One possible identification of an artifact"""
+
+    name: str
+    description: str = ""
+    confidence: float = 0.0
+
+
+class AnalysisProvider(ABC):
+    name: str = ""
+    tiers: Tuple[str, ...] = TIERS
+
+    def supports(self, tier: str) -> bool:
+        return tier in self.tiers
+
+    def available(self) -> bool:
+        """Whether this provider can run here; if not, the fallback provider is used."""
+        return True
+
+    @abstractmethod
+    def describe(self, tier: str) -> str:
+        """How this provider analyses the tier, shown as the result's method, e.g. "Ollama (qwen3-vl:2b)"."""
+
+    @abstractmethod
+    def analyze(self, image_bytes: bytes, tier: str) -> List[Candidate]:
+        """Candidate identifications, best first. Raises RuntimeError when the service fails."""
+
+
+class OllamaProvider(AnalysisProvider):
+    """A vision LLM served by Ollama (OLLAMA_ENDPOINT), with a model per tier; see FastAnalyzer."""
+
+    name = "ollama"
+
+    # API tier -> FastAnalyzer tier
+    ANALYZER_TIERS = {
+        "instant": "INSTANT",
+        "fast": "FAST",
+        "balanced": "BALANCED",
+        "thorough": "QUALITY",
+    }
+
+    def describe(self, tier: str) -> str:
+        model = FastAnalyzer.MODELS.get(self.ANALYZER_TIERS[tier])
+        return f"Ollama ({model})" if model else "ViT Classification"
+
+    def analyze(self, image_bytes: bytes, tier: str) -> List[Candidate]:
+        image = Image.open(BytesIO(image_bytes)).convert("RGB")
+        analyzer = FastAnalyzer(tier=self.ANALYZER_TIERS[tier], timeout=ANALYSIS_TIMEOUTS.get(tier))
+        result = analyzer.analyze_artifact(image)
+        return [
+            Candidate(
+                name=result.get("name", "Unknown"),
+                description=result.get("description", ""),
+                confidence=float(result.get("confidence", 0.0)),
+            )
+        ]
+
+
+class StubProvider(AnalysisProvider):
+    """The same canned answers for every image, for development without an AI service."""
+
+    name = "stub"
+
+    CANDIDATES = [
+        Candidate("Pottery shard", "Fragment of a fired clay vessel with a worn slip.", 0.62),
+        Candidate("Stone tool", "Flaked stone with a worked edge, possibly a scraper.", 0.23),
+        Candidate("Bone fragment", "Weathered piece of animal bone.", 0.08),
+    ]
+
+    def describe(self, tier: str) -> str:
+        return "Stub (no AI)"
+
+    def analyze(self, image_bytes: bytes, tier: str) -> List[Candidate]:
+        return list(self.CANDIDATES)
+
+
+class OnnxProvider(AnalysisProvider):
+    """
+    A local image classifier (ONNX_MODEL_PATH) run with onnxruntime, so basic
+    identification works offline. Unavailable until onnxruntime and the model
+    file are both installed.
+    """
+
+    name = "onnx"
+    tiers = ("instant",)
+
+    # ImageNet normalisation, which most exported classifiers expect
+    MEAN = np.array([0.485, 0.456, 0.406], dtype=np.float32)
+    STD = np.array([0.229, 0.224, 0.225], dtype=np.float32)
+    DEFAULT_SIZE = 224
+
+    def __init__(self):
+        self.session = None
+        self.labels: List[str] = []
+        self.problem: Optional[str] = None
+        try:
+            import onnxruntime
+        except ImportError:
+            self.problem = "onnxruntime is not installed"
+            return
+        if not os.path.isfile(ONNX_MODEL_PATH):
+            self.problem = f"no model at {ONNX_MODEL_PATH}"
+            return
+        try:
+            self.session = onnxruntime.InferenceSession(ONNX_MODEL_PATH, providers=["CPUExecutionProvider"])
+        except Exception as e:
+            self.problem = f"could not load {ONNX_MODEL_PATH}: {e}"
+            return
+        if os.path.isfile(ONNX_LABELS_PATH):
+            with open(ONNX_LABELS_PATH, encoding="utf-8") as f:
+                self.labels = [line.strip() for line in f if line.strip()]
+        logger.info(f"Local classifier loaded from {ONNX_MODEL_PATH} ({len(self.labels)} labels)")
+
+    def available(self) -> bool:
+        return self.session is not None
+
+    def describe(self, tier: str) -> str:
+        return f"Local model ({os.path.basename(ONNX_MODEL_PATH)})"
+
+    def _label(self, index: int) -> str:
+        return self.labels[index] if index < len(self.labels) else f"class {index}"
+
+    def analyze(self, image_bytes: bytes, tier: str) -> List[Candidate]:
+        model_input = self.session.get_inputs()[0]
+        # NCHW; dynamic dimensions come through as names or None
+        height, width = (d if isinstance(d, int) and d > 0 else self.DEFAULT_SIZE for d in model_input.shape[2:4])
+        image = Image.open(BytesIO(image_bytes)).convert("RGB").resize((width, height))
+        pixels = (np.asarray(image, dtype=np.float32) / 255.0 - self.MEAN) / self.STD
+        batch = pixels.transpose(2, 0, 1)[np.newaxis]
+
+        scores = np.asarray(self.session.run(None, {model_input.name: batch})[0], dtype=np.float64).reshape(-1)
+        # Models end in either logits or probabilities
+        if scores.min() < 0 or not np.isclose(scores.sum(), 1.0, atol=1e-3):
+            scores = np.exp(scores - scores.max())
+            scores /= scores.sum()
+
+        return [
+            Candidate(
+                name=self._label(int(i)),
+                description=f"Classified as {self._label(int(i))} by the local model",
+                confidence=float(scores[i]),
+            )
+            for i in np.argsort(scores)[::-1][:max(1, ONNX_TOP_K)]
+        ]
+
+
+PROVIDERS: Dict[str, Type[AnalysisProvider]] = {
+    OllamaProvider.name: OllamaProvider,
+    OnnxProvider.name: OnnxProvider,
+    StubProvider.name: StubProvider,
+}
+
+_instances: Dict[str, AnalysisProvider] = {}
+_instances_lock = threading.Lock()
+_fallbacks_logged = set()
+
+
+def _provider(name: str, tier: str) -> AnalysisProvider:
+    provider_class = PROVIDERS.get(name)
+    if provider_class is None:
+        raise ValueError(f"Unknown analysis provider {name!r} for the {tier} tier; use one of {', '.join(PROVIDERS)}")
+    # Providers may load models, so each is created once
+    with _instances_lock:
+        if name not in _instances:
+            _instances[name] = provider_class()
+        return _instances[name]
+
+
+def provider_for(tier: str) -> AnalysisProvider:
+    """
+    The configured provider for a tier, or the fallback provider if that one
+    can't run here. ValueError if a provider is unknown or can't do the tier.
+    """
+    provider = _provider(ANALYSIS_PROVIDERS.get(tier, ""), tier)
+    if not provider.available():
+        if (tier, provider.name) not in _fallbacks_logged:
+            _fallbacks_logged.add((tier, provider.name))
+            reason = getattr(provider, "problem", None) or "not available"
+            logger.warning(
+                f"Analysis provider {provider.name!r} can't run ({reason}); "
+                f"using {ANALYSIS_FALLBACK_PROVIDER!r} for the {tier} tier"
+            )
+        provider = _provider(ANALYSIS_FALLBACK_PROVIDER, tier)
+    if not provider.supports(tier):
+        raise ValueError(f"Analysis provider {provider.name!r} does not support the {tier} tier")
+    return provider
+
+
+def check_configuration() -> None:
+    """Fail at startup rather than on the first analysis if ANALYSIS_PROVIDERS is wrong."""
+    for tier in TIERS:
+        provider_for(tier)
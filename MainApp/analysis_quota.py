@@ -0,0 +1,129 @@
+"""
+Rate limits and monthly quotas for analyses.
+
+Every analysis request takes a token from the caller's bucket: one bucket
+per API key for calls made with a key, else one per user, so a runaway
+script can't lock its owner out of the web app. Each analysis also costs
+its tier's weight in units (ANALYSIS_QUOTA_WEIGHTS) from the user's quota
+for the calendar month (UTC), API-key calls included. A request that
+would go past ANALYSIS_MONTHLY_QUOTA is refused whole; cached answers cost
+the same as fresh ones, so what a user spends doesn't depend on what
+others happened to analyse first.
+
+Responses say where the caller stands:
+    X-RateLimit-Limit, X-RateLimit-Remaining   the request bucket
+    X-Quota-Limit, X-Quota-Used, X-Quota-Remaining, X-Quota-Reset
+The quota headers are left out for exempt roles and when there is no quota.
+
+Buckets live in memory, like the login limits in rate_limit.py; usage is
+kept in the analysis_usage table so it survives restarts.
+"""
+
+import math
+import threading
+from datetime import datetime
+from typing import Any, Dict
+
+from auth import TokenUser
+from config import (
+    ANALYSIS_MONTHLY_QUOTA,
+    ANALYSIS_QUOTA_EXEMPT_ROLES,
+    ANALYSIS_QUOTA_WEIGHTS,
+    ANALYZE_RATE_LIMIT_BURST,
+    ANALYZE_RATE_LIMIT_PER_MINUTE,
+)
+from database import charge_analysis_usage, get_analysis_usage
+from errors import QuotaExceededError, RateLimitedError
+from rate_limit import TokenBucketLimiter
+
+_limiter = TokenBucketLimiter(capacity=ANALYZE_RATE_LIMIT_BURST, refill_per_second=ANALYZE_RATE_LIMIT_PER_MINUTE / 60)
+# Checking the month's total and adding to it must not interleave between requests
+_charge_lock = threading.Lock()
+
+
+def weight(tier: str) -> int:
+    """Quota units one analysis at ``tier`` costs."""
+    return ANALYSIS_QUOTA_WEIGHTS.get(tier, ANALYSIS_QUOTA_WEIGHTS["fast"])
+
+
+def _month(now: datetime) -> str:
+    return now.strftime("%Y-%m")
+
+
+def _month_end(now: datetime) -> datetime:
+    """Midnight UTC on the first of next month, when the quota resets."""
+    return datetime(now.year + now.month // 12, now.month % 12 + 1, 1)
+
+
+def _bucket(user: TokenUser) -> str:
+    return f"key:{user.api_key_id}" if user.api_key_id is not None else f"user:{user.username}"
+
+
+def _quota_headers(used: int, now: datetime) -> Dict[str, str]:
+    return {
+        "X-Quota-Limit": str(ANALYSIS_MONTHLY_QUOTA),
+        "X-Quota-Used": str(used),
+        "X-Quota-Remaining": str(max(ANALYSIS_MONTHLY_QUOTA - used, 0)),
+        "X-Quota-Reset": _month_end(now).isoformat() + "Z",
+    }
+
+
+def admit(user: TokenUser, tier: str, analyses: int = 1) -> Dict[str, str]:
+    """
+    Take a request token and charge ``analyses`` at ``tier`` to the user's
+    month. Returns the headers to send; raises RateLimitedError or
+    QuotaExceededError (both 429 with Retry-After) instead.
+    """
+    now = datetime.utcnow()
+    units = weight(tier) * analyses
+    if user.role in ANALYSIS_QUOTA_EXEMPT_ROLES:
+        charge_analysis_usage(user.username, _month(now), tier, analyses, units, quota=0)
+        return {}
+
+    bucket = _bucket(user)
+    headers = {"X-RateLimit-Limit": str(ANALYZE_RATE_LIMIT_BURST)}
+    retry_after = _limiter.take(bucket)
+    if retry_after:
+        headers.update({"X-RateLimit-Remaining": "0", "Retry-After": str(math.ceil(retry_after))})
+        raise RateLimitedError("Too many analysis requests, please slow down", headers=headers)
+    headers["X-RateLimit-Remaining"] = str(_limiter.remaining(bucket))
+
+    with _charge_lock:
+        used = charge_analysis_usage(user.username, _month(now), tier, analyses, units, ANALYSIS_MONTHLY_QUOTA)
+    if used is None:
+        status = usage(user.username, user.role)
+        headers.update(_quota_headers(status["used"], now))
+        headers["Retry-After"] = str(math.ceil((_month_end(now) - now).total_seconds()))
+        raise QuotaExceededError(
+            f"This would use {units} analysis units but only {status['remaining']} of this month's "
+            f"{ANALYSIS_MONTHLY_QUOTA} are left",
+            headers=headers,
+            units=units,
+            remaining=status["remaining"],
+        )
+    if ANALYSIS_MONTHLY_QUOTA:
+        headers.update(_quota_headers(used, now))
+    return headers
+
+
+def usage(username: str, role: str) -> Dict[str, Any]:
+    """A user's analyses and units this month, against their quota and rate limit (None when exempt)."""
+    now = datetime.utcnow()
+    by_tier = get_analysis_usage(username, _month(now))
+    used = sum(row["units"] for row in by_tier)
+    limited = role not in ANALYSIS_QUOTA_EXEMPT_ROLES
+    has_quota = limited and ANALYSIS_MONTHLY_QUOTA > 0
+    return {
+        "username": username,
+        "month": _month(now),
+        "quota": ANALYSIS_MONTHLY_QUOTA if has_quota else None,
+        "used": used,
+        "remaining": max(ANALYSIS_MONTHLY_QUOTA - used, 0) if has_quota else None,
+        "resets_at": _month_end(now).isoformat() + "Z",
+        "analyses": sum(row["analyses"] for row in by_tier),
+        "by_tier": by_tier,
+        "weights": dict(ANALYSIS_QUOTA_WEIGHTS),
+        "rate_limit": {"per_minute": ANALYZE_RATE_LIMIT_PER_MINUTE, "burst": ANALYZE_RATE_LIMIT_BURST}
+        if limited
+        else None,
+    }
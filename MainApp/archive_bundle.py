@@ -0,0 +1,296 @@
+"""
+Collection bundles: a ZIP of artifacts with their images, for moving part of
+an archive from one backend to another.
+
+    manifest.json        {"format": "fll-artifacts", "version": 1, "exported_at",
+                          "source", "collections": [...], "artifacts": [...]}
+    images/<sha256>      images and thumbnails, named by content hash
+    models/<id>.<format> 3D models
+
+Artifacts in the manifest carry the database's fields (see Artifact.to_dict);
+image_hash and thumbnail_hash name files in images/, model_3d_file one in
+models/, and collection_id refers to the manifest's collections. Images are
+already compressed, so they are stored rather than deflated.
+
+Importing refuses a bundle that is too large or has no readable manifest
+before anything is saved; after that, an artifact with a missing or damaged
+file fails on its own. An artifact whose image the importer can already see
+is a conflict, settled by ``on_conflict``: "skip" it, "replace" the existing
+artifact's descriptive fields with the bundle's (only where the importer
+owns it, or is staff), or import a "duplicate".
+
+Imported collections belong to the importer, and so do imported artifacts,
+except that staff imports keep the original owner, uploader and
+verification. Team ids mean nothing on another backend, so team-visible
+artifacts come in private.
+"""
+
+import hashlib
+import json
+import logging
+import tempfile
+import zipfile
+from datetime import datetime, timezone
+from io import BytesIO
+from typing import IO, Any, Dict, List, Optional
+
+from PIL import Image
+
+import image_embeddings
+import image_store
+from config import IMPORT_MAX_ARTIFACTS, IMPORT_MAX_MB
+from database import (
+    Viewer,
+    create_collection,
+    find_artifact_by_image,
+    get_collection,
+    list_collections,
+    overwrite_artifact,
+    save_artifact,
+)
+
+logger = logging.getLogger(__name__)
+
+FORMAT = "fll-artifacts"
+VERSION = 1
+MANIFEST = "manifest.json"
+CONFLICT_POLICIES = ("skip", "replace", "duplicate")
+IMPORT_STATUSES = ("imported", "replaced", "skipped", "failed")
+
+# What an artifact is, as opposed to who has it; copied on every import
+CONTENT_FIELDS = (
+    "name", "value", "age", "description", "cultural_context", "material", "function", "rarity",
+    "confidence", "tags", "notes", "form_data", "tier", "provenance", "historical_context", "references",
+    "captured_at", "latitude", "longitude", "original_format", "original_size",
+)
+# Only kept when archive staff import
+REVIEW_FIELDS = ("verification_status", "verified_by", "verified_at", "verification_comments")
+TIMESTAMP_FIELDS = ("uploaded_at", "analyzed_at", "verified_at", "captured_at")
+# Left out of the manifest: ids are remapped and files are stored alongside
+_NOT_EXPORTED = (
+    "model_3d_data", "version", "updated_at", "team_id", "deleted_at", "deleted_by",
+    "locked_at", "locked_by", "lock_reason",
+)
+
+
+class BundleError(ValueError):
+    """The upload isn't a bundle this backend can import."""
+
+
+def _image_name(image_hash: str) -> str:
+    return f"images/{image_hash}"
+
+
+def write_bundle(artifacts: List[Dict[str, Any]], viewer: Viewer, source: str) -> str:
+    """Write ``artifacts`` (database dicts) and their collections to a temporary ZIP; the caller deletes it."""
+    collections = {}
+    for collection_id in {a["collection_id"] for a in artifacts if a.get("collection_id")}:
+        collection = get_collection(collection_id, viewer)
+        if collection:
+            collections[collection_id] = {
+                key: collection[key] for key in ("id", "name", "description", "visibility", "created_at")
+            }
+
+    handle = tempfile.NamedTemporaryFile(prefix="artifacts-", suffix=".zip", delete=False)
+    with handle, zipfile.ZipFile(handle, "w") as bundle:
+        written = set()
+        entries = []
+        for artifact in artifacts:
+            entry = {key: value for key, value in artifact.items() if key not in _NOT_EXPORTED}
+            if entry.get("collection_id") not in collections:
+                entry["collection_id"] = None
+            if entry.get("visibility") == "team":
+                entry["visibility"] = "private"
+            for key in ("image_hash", "thumbnail_hash"):
+                image_hash = entry.get(key)
+                if not image_hash or image_hash in written:
+                    continue
+                try:
+                    bundle.writestr(_image_name(image_hash), image_store.get(image_hash), zipfile.ZIP_STORED)
+                    written.add(image_hash)
+                except Exception as e:
+                    logger.warning(f"Leaving artifact {artifact['id']}'s {key} out of the export: {e}")
+                    entry[key] = None
+            if artifact.get("model_3d_data"):
+                entry["model_3d_file"] = f"models/{artifact['id']}.{artifact.get('model_3d_format') or 'obj'}"
+                bundle.writestr(entry["model_3d_file"], artifact["model_3d_data"], zipfile.ZIP_DEFLATED)
+            entries.append(entry)
+
+        manifest = {
+            "format": FORMAT,
+            "version": VERSION,
+            "exported_at": datetime.utcnow().isoformat() + "Z",
+            "source": source,
+            "collections": list(collections.values()),
+            "artifacts": entries,
+        }
+        bundle.writestr(MANIFEST, json.dumps(manifest, indent=2), zipfile.ZIP_DEFLATED)
+    return handle.name
+
+
+def _read_manifest(bundle: zipfile.ZipFile) -> Dict[str, Any]:
+    # Uncompressed sizes, so a small bomb can't expand past the limit
+    if sum(info.file_size for info in bundle.infolist()) > IMPORT_MAX_MB * 1024 * 1024:
+        raise BundleError(f"Bundles may hold at most {IMPORT_MAX_MB} MB")
+    try:
+        manifest = json.loads(bundle.read(MANIFEST))
+    except KeyError:
+        raise BundleError(f"No {MANIFEST} in the bundle")
+    except ValueError:
+        raise BundleError(f"{MANIFEST} isn't valid JSON")
+    if not isinstance(manifest, dict) or manifest.get("format") != FORMAT:
+        raise BundleError("Not an artifact bundle")
+    if manifest.get("version") != VERSION:
+        raise BundleError(f"Unsupported bundle version {manifest.get('version')!r}")
+    artifacts = manifest.get("artifacts")
+    if not isinstance(artifacts, list) or not all(isinstance(a, dict) for a in artifacts):
+        raise BundleError("The manifest's artifacts must be a list of objects")
+    if len(artifacts) > IMPORT_MAX_ARTIFACTS:
+        raise BundleError(f"Bundles may hold at most {IMPORT_MAX_ARTIFACTS} artifacts")
+    if not isinstance(manifest.get("collections", []), list):
+        raise BundleError("The manifest's collections must be a list")
+    return manifest
+
+
+def _read_image(bundle: zipfile.ZipFile, image_hash: Optional[str]) -> Optional[bytes]:
+    if not image_hash:
+        return None
+    try:
+        data = bundle.read(_image_name(image_hash))
+    except KeyError:
+        raise BundleError(f"{_image_name(image_hash)} is missing")
+    if hashlib.sha256(data).hexdigest() != image_hash:
+        raise BundleError(f"{_image_name(image_hash)} is damaged")
+    return data
+
+
+def _thumbnail(image_bytes: bytes) -> bytes:
+    image = Image.open(BytesIO(image_bytes))
+    image.thumbnail((200, 200))
+    buffer = BytesIO()
+    image.save(buffer, format="PNG")
+    return buffer.getvalue()
+
+
+def _timestamp(value: Any) -> Optional[datetime]:
+    if not isinstance(value, str) or not value:
+        return None
+    try:
+        parsed = datetime.fromisoformat(value.replace("Z", "+00:00"))
+    except ValueError:
+        return None
+    return parsed.astimezone(timezone.utc).replace(tzinfo=None) if parsed.tzinfo else parsed
+
+
+def _fields(entry: Dict[str, Any], viewer: Viewer) -> Dict[str, Any]:
+    """The artifact's fields as they should be stored for this importer."""
+    fields = {key: entry[key] for key in CONTENT_FIELDS if key in entry}
+    if isinstance(fields.get("form_data"), dict):
+        fields["form_data"] = json.dumps(fields["form_data"])
+    if viewer.sees_all:
+        fields.update({key: entry[key] for key in REVIEW_FIELDS if key in entry})
+    for key in TIMESTAMP_FIELDS:
+        if key in fields or (key in entry and key not in REVIEW_FIELDS):
+            fields[key] = _timestamp(entry.get(key))
+    return fields
+
+
+class _Collections:
+    """Maps the bundle's collection ids to the importer's, creating collections on first use."""
+
+    def __init__(self, manifest: Dict[str, Any], viewer: Viewer):
+        self.viewer = viewer
+        self.source = {c.get("id"): c for c in manifest.get("collections", []) if isinstance(c, dict)}
+        self.mapped: Dict[Any, int] = {}
+        self.created = 0
+
+    def target(self, source_id: Any) -> Optional[int]:
+        if source_id is None or source_id not in self.source:
+            return None
+        if source_id not in self.mapped:
+            collection = self.source[source_id]
+            name = str(collection.get("name") or "Imported collection")[:200]
+            # Importing the same bundle twice fills the same collection
+            existing = next(
+                (c for c in list_collections(Viewer(username=self.viewer.username), "mine") if c["name"] == name),
+                None,
+            )
+            if existing is None:
+                visibility = collection.get("visibility") if collection.get("visibility") == "public" else "private"
+                existing = create_collection(name, self.viewer.username, collection.get("description"), visibility)
+                self.created += 1
+            self.mapped[source_id] = existing["id"]
+        return self.mapped[source_id]
+
+
+def _import_one(
+    bundle: zipfile.ZipFile, entry: Dict[str, Any], viewer: Viewer, on_conflict: str, collections: _Collections
+) -> Dict[str, Any]:
+    image = _read_image(bundle, entry.get("image_hash"))
+    fields = _fields(entry, viewer)
+
+    existing = find_artifact_by_image(image, viewer) if image else None
+    if existing and on_conflict == "skip":
+        return {"id": existing["id"], "status": "skipped", "error": "Image already in the archive"}
+    if existing and on_conflict == "replace":
+        if existing.get("owner_id") != viewer.username and not viewer.sees_all:
+            return {"id": existing["id"], "status": "skipped", "error": "Image already in someone else's artifact"}
+        if existing.get("locked_at"):
+            return {"id": existing["id"], "status": "skipped", "error": "Image already in an artifact a curator has locked"}
+        overwrite_artifact(existing["id"], fields, changed_by=viewer.username)
+        return {"id": existing["id"], "status": "replaced"}
+
+    thumbnail = _read_image(bundle, entry.get("thumbnail_hash"))
+    if image and not thumbnail:
+        thumbnail = _thumbnail(image)
+    model_3d = None
+    if entry.get("model_3d_file"):
+        try:
+            model_3d = bundle.read(str(entry["model_3d_file"]))
+        except KeyError:
+            raise BundleError(f"{entry['model_3d_file']} is missing")
+        fields["model_3d_format"] = entry.get("model_3d_format") or "obj"
+
+    if viewer.sees_all:
+        fields["owner_id"] = entry.get("owner_id") or viewer.username
+        fields["uploaded_by"] = entry.get("uploaded_by") or viewer.username
+    else:
+        fields["owner_id"] = fields["uploaded_by"] = viewer.username
+    fields["visibility"] = "public" if entry.get("visibility") == "public" else "private"
+    fields["collection_id"] = collections.target(entry.get("collection_id"))
+
+    artifact_id = save_artifact(
+        fields, image_bytes=image, thumbnail_bytes=thumbnail, model_3d_bytes=model_3d, changed_by=viewer.username
+    )
+    if image:
+        image_embeddings.index_artifact(artifact_id, image)
+    return {"id": artifact_id, "status": "imported"}
+
+
+def import_bundle(file: IO[bytes], viewer: Viewer, on_conflict: str = "skip") -> Dict[str, Any]:
+    """Import a bundle (a seekable file) as ``viewer``; returns counts and what happened to each artifact."""
+    if on_conflict not in CONFLICT_POLICIES:
+        raise BundleError(f"on_conflict must be one of {', '.join(CONFLICT_POLICIES)}")
+    try:
+        bundle = zipfile.ZipFile(file)
+    except zipfile.BadZipFile:
+        raise BundleError("Not a ZIP file")
+
+    with bundle:
+        manifest = _read_manifest(bundle)
+        collections = _Collections(manifest, viewer)
+        results = []
+        for entry in manifest["artifacts"]:
+            result = {"source_id": entry.get("id"), "id": None, "status": "failed", "error": None}
+            try:
+                result.update(_import_one(bundle, entry, viewer, on_conflict, collections))
+            except (BundleError, OSError, ValueError, zipfile.BadZipFile) as e:
+                result["error"] = str(e)
+            except Exception as e:
+                logger.exception(f"Importing artifact {entry.get('id')!r} failed")
+                result["error"] = "Could not import this artifact; see the server log"
+            results.append(result)
+
+    counts = {status: sum(r["status"] == status for r in results) for status in IMPORT_STATUSES}
+    logger.info(f"{viewer.username} imported a bundle: {counts}")
+    return {**counts, "collections_created": collections.created, "artifacts": results}
@@ -0,0 +1,234 @@
+"""
+JWT access tokens and the FastAPI dependencies that check them.
+
+/auth/login issues a signed token carrying the username, role and expiry;
+protected routes take ``Depends(get_current_user)`` instead of trusting a
+username sent in the request body. Scripts and service accounts can send an
+``X-Api-Key`` header instead of a bearer token, and the browser frontend can
+sign in with /auth/session for an HTTP-only session cookie. Cookies are sent
+automatically, so unsafe requests using one must also carry the session's
+CSRF token in ``X-CSRF-Token``.
+
+Tokens from another issuer, such as a separate login service, are accepted
+when JWT_JWKS_URL is set: a token whose header names a key id ("kid") is
+checked against that issuer's published keys instead of JWT_SECRET. The
+issuer only vouches for the username; it must be an active local account,
+and the role is that account's.
+"""
+
+import logging
+import secrets
+import threading
+import time
+from datetime import datetime, timedelta, timezone
+from typing import Annotated, Optional
+
+from fastapi import Depends, Request
+from fastapi.security import APIKeyCookie, APIKeyHeader, HTTPAuthorizationCredentials, HTTPBearer
+from jose import JWTError, jwt
+from pydantic import BaseModel
+
+from config import (
+    ACCESS_TOKEN_EXPIRE_MINUTES,
+    JWKS_CACHE_SECONDS,
+    JWT_ALGORITHM,
+    JWT_AUDIENCE,
+    JWT_ISSUER,
+    JWT_JWKS_ALGORITHMS,
+    JWT_JWKS_URL,
+    JWT_SECRET,
+    JWT_USERNAME_CLAIM,
+    SESSION_COOKIE_NAME,
+)
+from errors import AuthenticationError, PermissionDeniedError
+
+logger = logging.getLogger(__name__)
+
+if not JWT_SECRET:
+    logger.warning("JWT_SECRET is not set; using a random key, sessions won't survive a restart")
+_secret = JWT_SECRET or secrets.token_urlsafe(32)
+
+if JWT_JWKS_URL and not JWT_ISSUER:
+    raise ValueError("JWT_ISSUER must be set when JWT_JWKS_URL is")
+
+_bearer = HTTPBearer(auto_error=False)
+_api_key = APIKeyHeader(name="X-Api-Key", auto_error=False)
+_session_cookie = APIKeyCookie(name=SESSION_COOKIE_NAME, auto_error=False)
+_challenge = {"WWW-Authenticate": "Bearer"}
+
+CSRF_HEADER = "X-CSRF-Token"
+_SAFE_METHODS = {"GET", "HEAD", "OPTIONS"}
+
+
+class TokenUser(BaseModel):
+    """The identity carried by a validated access token."""
+
+    username: str
+    role: str
+    # Set when the caller authenticated with an API key, which is rate limited on its own
+    api_key_id: Optional[int] = None
+
+
+def create_access_token(username, role):
+    """Sign an access token for the user. Returns (token, expires_in_seconds)."""
+    now = datetime.now(timezone.utc)
+    expires = now + timedelta(minutes=ACCESS_TOKEN_EXPIRE_MINUTES)
+    payload = {"sub": username, "role": role, "type": "access", "iat": now, "exp": expires}
+    return jwt.encode(payload, _secret, algorithm=JWT_ALGORITHM), ACCESS_TOKEN_EXPIRE_MINUTES * 60
+
+
+_jwks = {"keys": {}, "fetched_at": 0.0}
+_jwks_lock = threading.Lock()
+
+
+def _jwks_key(kid):
+    """The issuer's public key with this id, refetching the JWKS when it is stale or lacks the id."""
+    with _jwks_lock:
+        stale = time.monotonic() - _jwks["fetched_at"] > JWKS_CACHE_SECONDS
+        # Refetch for unknown ids too (keys get rotated), but at most once a minute
+        if stale or (kid not in _jwks["keys"] and time.monotonic() - _jwks["fetched_at"] > 60):
+            import requests
+
+            try:
+                response = requests.get(JWT_JWKS_URL, timeout=5)
+                response.raise_for_status()
+                _jwks["keys"] = {key["kid"]: key for key in response.json().get("keys", []) if "kid" in key}
+            except Exception as e:
+                logger.warning(f"Could not fetch signing keys from {JWT_JWKS_URL}: {e}")
+            _jwks["fetched_at"] = time.monotonic()
+        return _jwks["keys"].get(kid)
+
+
+def _decode_issuer_token(token, kid) -> TokenUser:
+    """A token signed by the JWKS issuer's key ``kid``."""
+    key = _jwks_key(kid)
+    if key is None:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    try:
+        payload = jwt.decode(
+            token,
+            key,
+            algorithms=JWT_JWKS_ALGORITHMS,
+            audience=JWT_AUDIENCE or None,
+            issuer=JWT_ISSUER,
+            options={"verify_aud": bool(JWT_AUDIENCE)},
+        )
+    except JWTError:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    username = payload.get(JWT_USERNAME_CLAIM)
+    if not username:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    from login import get_user_row
+
+    # Deactivated and unknown accounts are refused whatever the issuer says
+    row = get_user_row(str(username))
+    if row is None or not row[5]:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    return TokenUser(username=row[0], role=row[2])
+
+
+def decode_access_token(token) -> TokenUser:
+    """Validate signature, expiry and token type; raise AuthenticationError otherwise."""
+    try:
+        kid = jwt.get_unverified_header(token).get("kid")
+    except JWTError:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    if JWT_JWKS_URL and kid:
+        return _decode_issuer_token(token, kid)
+    try:
+        payload = jwt.decode(token, _secret, algorithms=[JWT_ALGORITHM])
+    except JWTError:
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    if payload.get("type") != "access" or not payload.get("sub"):
+        raise AuthenticationError("Invalid or expired token", headers=_challenge)
+    return TokenUser(username=payload["sub"], role=payload.get("role", "user"))
+
+
+def create_state_token(provider):
+    """Short-lived signed `state` for an OAuth round trip."""
+    now = datetime.now(timezone.utc)
+    payload = {
+        "type": "oauth_state",
+        "provider": provider,
+        "nonce": secrets.token_urlsafe(16),
+        "iat": now,
+        "exp": now + timedelta(minutes=10),
+    }
+    return jwt.encode(payload, _secret, algorithm=JWT_ALGORITHM)
+
+
+def verify_state_token(token, provider):
+    """True if the state was issued by us, for this provider, and hasn't expired."""
+    try:
+        payload = jwt.decode(token, _secret, algorithms=[JWT_ALGORITHM])
+    except JWTError:
+        return False
+    return payload.get("type") == "oauth_state" and payload.get("provider") == provider
+
+
+def verify_csrf(request: Request, csrf_token):
+    """Reject unsafe requests that don't echo the session's CSRF token."""
+    if request.method in _SAFE_METHODS:
+        return
+    sent = request.headers.get(CSRF_HEADER, "")
+    if not sent or not secrets.compare_digest(sent, csrf_token):
+        raise PermissionDeniedError(f"Missing or invalid {CSRF_HEADER} header")
+
+
+def get_optional_user(
+    request: Request,
+    credentials: Optional[HTTPAuthorizationCredentials] = Depends(_bearer),
+    api_key: Optional[str] = Depends(_api_key),
+    session_id: Optional[str] = Depends(_session_cookie),
+) -> Optional[TokenUser]:
+    """The caller's identity from a bearer token, X-Api-Key or session cookie, else None."""
+    if credentials is not None:
+        return decode_access_token(credentials.credentials)
+    if api_key:
+        from login import authenticate_api_key
+
+        owner = authenticate_api_key(api_key)
+        if owner is None:
+            raise AuthenticationError("Invalid or revoked API key")
+        return TokenUser(username=owner[0], role=owner[1], api_key_id=owner[2])
+    if session_id:
+        from login import get_browser_session
+
+        session = get_browser_session(session_id)
+        if session is None:
+            raise AuthenticationError("Session has expired")
+        username, role, csrf_token = session
+        verify_csrf(request, csrf_token)
+        return TokenUser(username=username, role=role)
+    return None
+
+
+def get_current_user(user: Optional[TokenUser] = Depends(get_optional_user)) -> TokenUser:
+    """Require a valid bearer token, API key or session cookie."""
+    if user is None:
+        raise AuthenticationError("Not authenticated", headers=_challenge)
+    return user
+
+
+# The signed-in caller, for routes that must not run anonymously:
+#     def handler(current_user: CurrentUser): ...
+CurrentUser = Annotated[TokenUser, Depends(get_current_user)]
+
+
+def require_roles(*roles):
+    """
+    Dependency factory: allow only callers with one of the given roles.
+    Admins are always allowed, matching the frontend's permission table.
+
+        @app.get("/api/users", dependencies=[Depends(require_roles("admin"))])
+    """
+    allowed = {"admin", *roles}
+
+    def check(user: TokenUser = Depends(get_current_user)) -> TokenUser:
+        if user.role not in allowed:
+            raise PermissionDeniedError(
+                f"Requires one of the roles: {', '.join(sorted(allowed))}"
+            )
+        return user
+
+    return check
@@ -1,213 +1,1102 @@
+import asyncio
 import base64
 import json
 import logging
 import os
+import secrets
 import sys
+from datetime import datetime, timedelta, timezone
 from io import BytesIO
-from typing import List, Optional
+from typing import Any, Dict, List, Optional, Tuple
+from urllib.parse import urlencode, urlparse
 
 import uvicorn
-from fastapi import FastAPI, HTTPException
+from fastapi import (
+    APIRouter,
+    Body,
+    Depends,
+    FastAPI,
+    HTTPException,
+    Query,
+    Request,
+    Response,
+    WebSocket,
+    WebSocketDisconnect,
+)
+from fastapi.concurrency import run_in_threadpool
+from fastapi.encoders import jsonable_encoder
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.middleware.gzip import GZipMiddleware
+from fastapi.responses import FileResponse, JSONResponse, RedirectResponse, StreamingResponse
 from PIL import Image
-from pydantic import BaseModel
+from sqlalchemy import text
+from starlette.background import BackgroundTask
 
 # Add project root to path
 PROJECT_DIR = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
 if PROJECT_DIR not in sys.path:
     sys.path.insert(0, PROJECT_DIR)
 
-# Set DATABASE_URL to use artifacts.db from project root
-if "DATABASE_URL" not in os.environ:
-    db_path = os.path.join(PROJECT_DIR, "artifacts.db")
-    os.environ["DATABASE_URL"] = f"sqlite:///{db_path}"
-
 # Import existing functionality
-import numpy as np
-
-from ai_analyzer import AIAnalyzer
+from config import (
+    ALLOW_DUPLICATE_IMAGES,
+    ANALYSIS_BATCH_MAX_IMAGES,
+    ARCHIVE_STAFF_ROLES,
+    ARTIFACT_DEFAULT_VISIBILITY,
+    BACKEND_URL,
+    CORS_ALLOW_CREDENTIALS,
+    CORS_HEADERS,
+    CORS_MAX_AGE,
+    CORS_METHODS,
+    CORS_ORIGINS,
+    FRONTEND_URL,
+    HOST,
+    IMPORT_MAX_MB,
+    IP_ALLOWLIST,
+    IP_DENYLIST,
+    METRICS_ENABLED,
+    METRICS_TOKEN,
+    PORT,
+    REGISTRATION_INVITE_ONLY,
+    SESSION_COOKIE_NAME,
+    SESSION_COOKIE_SAMESITE,
+    SESSION_COOKIE_SECURE,
+    TLS_CERT_FILE,
+    TLS_KEY_FILE,
+    TLS_KEY_PASSWORD,
+    TRASH_RETENTION_DAYS,
+    WEBHOOK_MAX_PER_USER,
+)
 from database import Artifact as DBArtifact
 from database import (
+    anonymize_user_references,
+    count_artifacts,
+    count_search_results,
+    create_collection,
+    create_flag,
+    create_webhook,
     delete_artifact,
+    delete_collection,
+    delete_webhook,
+    engine,
+    find_artifact_by_image,
     get_all_artifacts,
     get_artifact_by_id,
+    get_artifact_changes,
+    get_artifact_revision,
+    get_collection,
+    get_deleted_artifact,
+    get_flag,
+    get_sharing,
+    get_webhook,
+    image_access,
+    list_artifact_revisions,
+    list_collections,
+    list_deleted_artifacts,
+    list_flags,
+    list_tags,
+    list_webhooks,
+    LockedArtifactError,
+    merge_tags,
+    moderation_queue,
+    override_identification,
+    resolve_flag,
+    set_artifact_lock,
+    set_sharing,
+    StaleArtifactError,
+    Viewer,
     init_db,
+    patch_artifact,
+    purge_artifact,
+    restore_artifact,
+    revert_artifact_revision,
+    REVERTIBLE_FIELDS,
     save_artifact,
     search_artifacts,
     update_artifact,
     update_artifact_tags,
 )
-from fast_analyzer import FastAnalyzer
+from errors import (
+    AccountLockedError,
+    AppError,
+    ArtifactLockedError,
+    AuthenticationError,
+    ConflictError,
+    PermissionDeniedError,
+    DatabaseError,
+    DuplicateArtifactError,
+    ExternalServiceError,
+    NotFoundError,
+    PreconditionFailedError,
+    ValidationError,
+    register_error_handlers,
+)
+import analysis_jobs
+import analysis_quota
+import archive_bundle
+import backups
+import exif_metadata
+import graphql_api
+import image_embeddings
+import image_formats
+import image_store
+import metadata_export
+import metrics
+import tracing
+import trash
+import webhooks
+from ip_filter import IpFilterMiddleware, build_rules, invalidate as invalidate_ip_rules
+from request_compression import RequestDecompressionMiddleware
+from request_logging import RequestLoggingMiddleware, configure_logging
 
 # Configure logging
-logging.basicConfig(level=logging.INFO)
+configure_logging()
 logger = logging.getLogger(__name__)
 
 # Initialize FastAPI app
 app = FastAPI(
-    title="FLL Project API", description="API for the FLL Project", version="1.0.0"
+    title="FLL Project API",
+    description=(
+        "API for the FLL Project. Authenticate with `POST /api/v1/auth/login` and send the "
+        "access token as `Authorization: Bearer <token>`, or use an `X-Api-Key`. Browsers can "
+        "sign in with `POST /api/v1/auth/session` for a session cookie instead. "
+        "Interactive docs are at /docs; the raw spec at /openapi.json."
+    ),
+    version="1.0.0",
+    openapi_tags=[
+        {"name": "auth", "description": "Sign-in, tokens, password reset and first-run setup"},
+        {"name": "accounts", "description": "Profiles, user administration, organizations, audit log and API keys"},
+    ],
 )
 
-# CORS middleware
+class _GZipExceptEventStreams(GZipMiddleware):
+    """GZip buffers what it compresses, which would hold back server-sent events until the stream ends."""
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] == "http" and b"text/event-stream" in dict(scope["headers"]).get(b"accept", b""):
+            await self.app(scope, receive, send)
+            return
+        await super().__call__(scope, receive, send)
+
+
+# Compress large responses and accept compressed uploads
+app.add_middleware(_GZipExceptEventStreams, minimum_size=1024)
+app.add_middleware(RequestDecompressionMiddleware)
+
+register_error_handlers(app)
+
+# Refuse addresses outside the IP allow/deny lists; added before CORS so it runs
+# inside it and browsers can read the 403
+app.add_middleware(IpFilterMiddleware)
+
+# CORS middleware, configured from CORS_* settings
 app.add_middleware(
     CORSMiddleware,
-    allow_origins=["*"],
-    allow_credentials=True,
-    allow_methods=["*"],
-    allow_headers=["*"],
+    allow_origins=CORS_ORIGINS,
+    # A wildcard origin with credentials would let any site act as the user
+    allow_credentials=CORS_ALLOW_CREDENTIALS and "*" not in CORS_ORIGINS,
+    allow_methods=CORS_METHODS,
+    allow_headers=CORS_HEADERS,
+    expose_headers=[
+        "X-Total-Count",
+        "X-Request-ID",
+        "Link",
+        "ETag",
+        "Retry-After",
+        "X-RateLimit-Limit",
+        "X-RateLimit-Remaining",
+        "X-Quota-Limit",
+        "X-Quota-Used",
+        "X-Quota-Remaining",
+        "X-Quota-Reset",
+    ],
+    max_age=CORS_MAX_AGE,
 )
 
+# Outermost, so the logged status and latency cover everything above
+app.add_middleware(RequestLoggingMiddleware)
+
+# A span per request, when an OTLP endpoint is configured
+tracing.configure(app)
+
 # Initialize database
 init_db()
 
-# Import login functions for authentication
-import sqlite3
-import bcrypt
-from login import get_user_info, get_users, log_action, reset_password, add_user, get_audit_logs
 
+@app.on_event("startup")
+def start_analysis_workers():
+    analysis_jobs.start()
+    webhooks.start()
+    image_embeddings.start()
+    trash.start()
+    backups.start()
 
-# Models
-class FormData(BaseModel):
-    """Physical measurements and metadata captured during upload"""
 
-    length: Optional[float] = None
-    width: Optional[float] = None
-    thickness: Optional[float] = None
-    weight: Optional[float] = None
-    color: Optional[str] = None
-    location: Optional[str] = None
-    description: Optional[str] = None
-    artifact_name: Optional[str] = None
-    tags: Optional[List[str]] = []
+@app.on_event("shutdown")
+def stop_analysis_workers():
+    analysis_jobs.stop()
+    webhooks.stop()
+    trash.stop()
+    backups.stop()
+    tracing.shutdown()
 
+# Import login functions for authentication
+from auth import (
+    CurrentUser,
+    TokenUser,
+    create_access_token,
+    create_state_token,
+    decode_access_token,
+    get_current_user,
+    get_optional_user,
+    require_roles,
+    verify_csrf,
+    verify_state_token,
+)
+from login import (
+    ERASED_USERNAME,
+    ORG_RANKS,
+    OrganizationError,
+    PasswordResetError,
+    RefreshTokenError,
+    RegistrationError,
+    add_ip_rule,
+    add_member,
+    add_user,
+    count_admins,
+    create_api_key,
+    create_browser_session,
+    create_first_admin,
+    create_invite,
+    create_organization,
+    create_password_reset_token,
+    deactivate_user,
+    delete_ip_rule,
+    delete_organization,
+    delete_user,
+    email_in_use,
+    erase_user,
+    export_user_data,
+    find_user_for_identity,
+    find_user_for_reset,
+    get_admin_stats,
+    get_audit_logs,
+    get_browser_session,
+    get_credentials,
+    get_invite,
+    get_ip_rule,
+    get_login_history,
+    get_member_rank,
+    get_user_settings,
+    get_locked_until,
+    get_organization,
+    get_user_row,
+    get_user_info,
+    get_user_role,
+    get_users,
+    issue_refresh_token,
+    list_api_keys,
+    list_invites,
+    list_ip_rules,
+    list_members,
+    list_organizations,
+    log_action,
+    pending_user_migrations,
+    query_users,
+    reactivate_user,
+    record_failed_login,
+    record_login,
+    register_user,
+    remove_member,
+    reset_failed_logins,
+    reset_password,
+    reset_password_with_token,
+    revoke_api_key,
+    revoke_browser_session,
+    revoke_invite,
+    revoke_refresh_token,
+    revoke_user_sessions,
+    rotate_refresh_token,
+    save_user_settings,
+    set_member_rank,
+    unlock_user,
+    update_password_hash,
+    update_user,
+)
+from oauth import authorization_url, enabled_providers, fetch_identity
+from password_reset import send_reset_link
+from password_policy import describe_policy, is_breached
+from passwords import verify_password
+from presence import UNAUTHENTICATED_CLOSE_CODE, hub as presence_hub
+from rate_limit import enforce_auth_rate_limit
+from schemas import (
+    AccountDeletion,
+    AdminStats,
+    AnalysisUsageInfo,
+    AnalyzeRequest,
+    ApiKeyCreate,
+    ApiKeyCreated,
+    ApiKeyInfo,
+    ArtifactBase,
+    ArtifactFlag,
+    ArtifactLocation,
+    ArtifactPatch,
+    ArtifactRevision,
+    ArtifactSort,
+    ArtifactSyncRequest,
+    ArtifactUpdate,
+    AuditLogEntry,
+    BatchAnalyzeRequest,
+    BootstrapRequest,
+    BootstrapStatus,
+    CollectionCreate,
+    CollectionInfo,
+    ErrorResponse,
+    FlagCreate,
+    FlagResolution,
+    ForgotPasswordRequest,
+    IdentificationOverride,
+    ExportFormat,
+    ImportConflict,
+    ImportResult,
+    InviteCreate,
+    InviteCreated,
+    InviteInfo,
+    IpRuleCreate,
+    IpRuleInfo,
+    LockRequest,
+    LoginRecord,
+    LoginRequest,
+    LoginResponse,
+    MessageResponse,
+    OAuthProvider,
+    OrganizationCreate,
+    OrganizationInfo,
+    OrgMember,
+    OrgMemberAdd,
+    OrgMemberUpdate,
+    PasswordChange,
+    PasswordPolicy,
+    ProfileUpdate,
+    RefreshRequest,
+    RegisterRequest,
+    RegistrationInfo,
+    ResetPasswordRequest,
+    SessionResponse,
+    ShareScope,
+    Sharing,
+    SimilarArtifact,
+    SimilaritySearchRequest,
+    SortOrder,
+    TagChange,
+    TagCount,
+    TagMerge,
+    TagRename,
+    TokenResponse,
+    UserCreate,
+    UserCreated,
+    UserResponse,
+    UserUpdate,
+    VerificationRequest,
+    WebhookCreate,
+    WebhookCreated,
+    WebhookInfo,
+)
 
-class ArtifactBase(BaseModel):
-    name: str
-    description: Optional[str] = None
-    tags: List[str] = []
-    tier: str
-    image_data: str  # base64 encoded image
-    form_data: Optional[FormData] = None  # Physical measurements from upload
-    model_3d_data: Optional[str] = None  # base64 encoded 3D model file
-    model_3d_format: Optional[str] = None  # Format: "obj", "stl", "ply"
-    uploaded_by: Optional[str] = None  # Username of uploader
 
+def _reject_breached(password):
+    """Refuse a new password that shows up in known data breaches."""
+    if is_breached(password):
+        raise ValidationError("This password has appeared in a data breach; choose a different one")
 
-class Artifact(ArtifactBase):
-    id: int
-    has_3d_model: Optional[bool] = False  # Indicates if 3D model is available
 
+def _account_locked(locked_until):
+    retry_after = max(1, int((locked_until - datetime.now(timezone.utc)).total_seconds()))
+    return AccountLockedError(
+        "Account is temporarily locked after too many failed logins",
+        headers={"Retry-After": str(retry_after)},
+    )
 
-class VerificationRequest(BaseModel):
-    """Request model for artifact verification with required reason."""
-    verification_status: str  # "verified" or "rejected"
-    reason: str  # Required reason for the decision
-    verified_by: str  # Username of verifier
 
+# Health checks
+@app.get("/health/live")
+async def health_live():
+    """The process is up and serving requests"""
+    return {"status": "alive"}
 
-class LoginRequest(BaseModel):
-    username: str
-    password: str
 
+@app.get("/health/ready")
+def health_ready():
+    """Ready for traffic: both databases answer and no migrations are pending"""
+    checks = {}
+    try:
+        with engine.connect() as conn:
+            conn.execute(text("SELECT 1"))
+        checks["artifacts_db"] = {"ok": True}
+    except Exception as e:
+        checks["artifacts_db"] = {"ok": False, "error": type(e).__name__}
+    try:
+        pending = pending_user_migrations()
+        checks["users_db"] = {"ok": True}
+        checks["migrations"] = {"ok": not pending, "pending": pending}
+    except Exception as e:
+        checks["users_db"] = {"ok": False, "error": type(e).__name__}
+        checks["migrations"] = {"ok": False, "error": "users database unavailable"}
+
+    ready = all(check["ok"] for check in checks.values())
+    if not ready:
+        logger.warning(f"Readiness check failed: {checks}")
+    return JSONResponse(
+        status_code=200 if ready else 503,
+        content={"status": "ready" if ready else "not_ready", "checks": checks},
+    )
+
+
+@app.get("/metrics", include_in_schema=False)
+def metrics_endpoint(request: Request):
+    """Prometheus metrics (see metrics.py); needs METRICS_TOKEN as a bearer token when that is set"""
+    if not METRICS_ENABLED:
+        raise NotFoundError("Metrics are disabled")
+    if METRICS_TOKEN and not secrets.compare_digest(
+        request.headers.get("authorization", ""), f"Bearer {METRICS_TOKEN}"
+    ):
+        raise AuthenticationError("A valid metrics token is required")
+    body, content_type = metrics.render()
+    return Response(content=body, media_type=content_type)
+
+
+# Versioned API. Auth and account routes are served under /api/v1; the
+# original paths (/auth/..., /api/users, ...) stay mounted for existing
+# clients and are flagged as deprecated. Breaking changes go in a new version.
+API_V1_PREFIX = "/api/v1"
+# Documented on every versioned route; all errors share the ErrorResponse envelope
+ERROR_RESPONSES = {
+    status: {"model": ErrorResponse}
+    for status in (400, 401, 403, 404, 409, 422, 423, 429, 500)
+}
+auth_router = APIRouter(tags=["auth"], responses=ERROR_RESPONSES)
+account_router = APIRouter(tags=["accounts"], responses=ERROR_RESPONSES)
+
+
+def _legacy_path(request: Request, response: Response):
+    """Point callers of an unversioned path at its /api/v1 successor"""
+    path = request.url.path
+    successor = API_V1_PREFIX + (path[len("/api"):] if path.startswith("/api/") else path)
+    response.headers["Deprecation"] = "true"
+    response.headers["Link"] = f'<{successor}>; rel="successor-version"'
 
-class UserCreate(BaseModel):
-    username: str
-    name: str
-    password: str
-    role: str
-    email: str
 
+# Authentication endpoints
+# These handlers make blocking sqlite3 (and OAuth HTTP) calls, so they are plain
+# `def`: FastAPI runs them in its threadpool instead of stalling the event loop.
+def _authenticate(request: LoginRequest, http_request: Request):
+    """Check a username and password. Returns (username, name, role, email)."""
+    client_ip = http_request.client.host if http_request.client else "unknown"
+    enforce_auth_rate_limit(client_ip, request.username)
+    try:
+        result = get_credentials(request.username)
+        if not result:
+            raise AuthenticationError("Invalid username or password")
 
-class PasswordChange(BaseModel):
-    username: str
-    new_password: str
+        username, name, hashed_password, role, email, active = result
 
+        locked_until = get_locked_until(username)
+        if locked_until:
+            raise _account_locked(locked_until)
 
-# Authentication endpoints
-@app.post("/auth/login")
-async def login(request: LoginRequest):
-    """Authenticate user and return user info"""
-    try:
-        DB_FILE = os.path.join(PROJECT_DIR, "MainApp", "users.db")
-        with sqlite3.connect(DB_FILE, timeout=10) as conn:
-            c = conn.cursor()
-            c.execute("SELECT username, name, hashed_password, role, email FROM users WHERE username=?", (request.username,))
-            result = c.fetchone()
-            
-            if not result:
-                raise HTTPException(status_code=401, detail="Invalid username or password")
-            
-            username, name, hashed_password, role, email = result
-            
-            # Verify password using bcrypt
+        # Verify password; legacy bcrypt/plaintext rows are upgraded to Argon2
+        try:
+            password_valid, new_hash = verify_password(request.password, hashed_password)
+            if password_valid and new_hash:
+                update_password_hash(username, new_hash)
+        except Exception as e:
+            logger.error(f"Password verification error: {str(e)}")
             password_valid = False
-            try:
-                # Check if stored password is a bcrypt hash
-                if hashed_password and hashed_password.startswith("$2b$"):
-                    # It's a bcrypt hash, verify it
-                    password_valid = bcrypt.checkpw(request.password.encode(), hashed_password.encode())
-                else:
-                    # Plain text password (for migration) - hash and update it
-                    if hashed_password == request.password:
-                        # Password matches, hash it and update the database
-                        new_hash = bcrypt.hashpw(request.password.encode(), bcrypt.gensalt()).decode()
-                        c.execute("UPDATE users SET hashed_password=? WHERE username=?", (new_hash, username))
-                        conn.commit()
-                        password_valid = True
-                    else:
-                        password_valid = False
-            except Exception as e:
-                logger.error(f"Password verification error: {str(e)}")
-                password_valid = False
-            
-            if not password_valid:
-                raise HTTPException(status_code=401, detail="Invalid username or password")
-            
-            # Log login action
-            try:
-                log_action(username, "Logged in")
-            except:
-                pass  # Ignore logging errors
-            
-            return {
-                "username": username,
-                "name": name,
-                "email": email,
-                "role": role
-            }
+
+        if not password_valid:
+            record_login(username, client_ip, http_request.headers.get("user-agent"), succeeded=False)
+            locked_until = record_failed_login(username)
+            if locked_until:
+                raise _account_locked(locked_until)
+            raise AuthenticationError("Invalid username or password")
+
+        reset_failed_logins(username)
+        # Checked only after the password, so this doesn't reveal which accounts exist
+        if not active:
+            raise PermissionDeniedError("This account has been deactivated")
+
+        # Log login action
+        try:
+            log_action(username, "Logged in")
+            record_login(username, client_ip, http_request.headers.get("user-agent"))
+        except:
+            pass  # Ignore logging errors
+
+        return username, name, role, email
     except HTTPException:
         raise
     except Exception as e:
         logger.error(f"Login error: {str(e)}")
-        raise HTTPException(status_code=500, detail="Login failed")
+        raise AppError("Login failed")
 
 
-@app.get("/api/users")
-async def get_all_users():
-    """Get all users (admin only)"""
-    users = get_users()
-    return [
+@auth_router.post("/auth/login", response_model=LoginResponse)
+def login(request: LoginRequest, http_request: Request):
+    """Authenticate user and return user info with a signed access token"""
+    username, name, role, email = _authenticate(request, http_request)
+    access_token, expires_in = create_access_token(username, role)
+    return {
+        "username": username,
+        "name": name,
+        "email": email,
+        "role": role,
+        "access_token": access_token,
+        "refresh_token": issue_refresh_token(username),
+        "token_type": "bearer",
+        "expires_in": expires_in,
+    }
+
+
+def _set_session_cookie(response: Response, value, max_age):
+    response.set_cookie(
+        SESSION_COOKIE_NAME,
+        value,
+        max_age=max_age,
+        httponly=True,
+        samesite=SESSION_COOKIE_SAMESITE,
+        secure=SESSION_COOKIE_SECURE,
+        path="/",
+    )
+
+
+@auth_router.post("/auth/session", response_model=SessionResponse)
+def create_session(request: LoginRequest, http_request: Request, response: Response):
+    """Sign in the browser with an HTTP-only session cookie instead of tokens"""
+    username, name, role, email = _authenticate(request, http_request)
+    session_id, csrf_token, expires_in = create_browser_session(username)
+    _set_session_cookie(response, session_id, expires_in)
+    return {
+        "username": username,
+        "name": name,
+        "email": email,
+        "role": role,
+        "csrf_token": csrf_token,
+        "expires_in": expires_in,
+    }
+
+
+@auth_router.delete("/auth/session", response_model=MessageResponse)
+def end_session(http_request: Request, response: Response):
+    """End the cookie session (needs its X-CSRF-Token) and clear the cookie"""
+    session_id = http_request.cookies.get(SESSION_COOKIE_NAME)
+    session = get_browser_session(session_id)
+    if session:
+        verify_csrf(http_request, session[2])
+        revoke_browser_session(session_id)
+        log_action(session[0], "Logged out")
+    _set_session_cookie(response, "", 0)
+    return {"message": "Logged out"}
+
+
+@auth_router.post("/auth/refresh", response_model=TokenResponse)
+def refresh(request: RefreshRequest):
+    """Exchange a refresh token for a new access token (the refresh token is rotated)"""
+    try:
+        username, refresh_token = rotate_refresh_token(request.refresh_token)
+    except RefreshTokenError as e:
+        raise AuthenticationError(str(e))
+
+    role = get_user_role(username)
+    if role is None:
+        raise AuthenticationError("User no longer exists")
+    access_token, expires_in = create_access_token(username, role)
+    return {
+        "access_token": access_token,
+        "refresh_token": refresh_token,
+        "token_type": "bearer",
+        "expires_in": expires_in,
+    }
+
+
+@auth_router.post("/auth/logout", response_model=MessageResponse)
+def logout(request: RefreshRequest):
+    """Revoke the session behind a refresh token"""
+    username = revoke_refresh_token(request.refresh_token)
+    if username:
+        log_action(username, "Logged out")
+    return {"message": "Logged out"}
+
+
+@auth_router.post("/auth/password/forgot", response_model=MessageResponse)
+def forgot_password(request: ForgotPasswordRequest, http_request: Request):
+    """Send a password reset link to the user, if the account exists"""
+    client_ip = http_request.client.host if http_request.client else "unknown"
+    enforce_auth_rate_limit(client_ip, request.identifier)
+
+    user = find_user_for_reset(request.identifier.strip())
+    if user:
+        username, email = user
+        send_reset_link(username, email, create_password_reset_token(username))
+    # Same answer either way, so this can't be used to discover accounts
+    return {"message": "If that account exists, a reset link has been sent"}
+
+
+@auth_router.post("/auth/password/reset", response_model=MessageResponse)
+def reset_password_endpoint(request: ResetPasswordRequest):
+    """Set a new password using a token from a reset link"""
+    _reject_breached(request.new_password)
+    try:
+        reset_password_with_token(request.token, request.new_password)
+    except PasswordResetError as e:
+        raise ValidationError(str(e))
+    return {"message": "Password has been reset"}
+
+
+@auth_router.get("/auth/password/policy", response_model=PasswordPolicy)
+async def password_policy():
+    """The rules new passwords must meet"""
+    return describe_policy()
+
+
+@auth_router.get("/auth/bootstrap", response_model=BootstrapStatus)
+def bootstrap_status():
+    """Whether this install still needs its first admin account"""
+    return {"required": count_admins() == 0}
+
+
+@auth_router.post("/auth/bootstrap", response_model=UserCreated)
+def bootstrap(request: BootstrapRequest, http_request: Request):
+    """Create the first admin account; refused once any admin exists"""
+    client_ip = http_request.client.host if http_request.client else "unknown"
+    enforce_auth_rate_limit(client_ip)
+
+    if count_admins() > 0:
+        raise ConflictError("Setup has already been completed")
+    _reject_breached(request.password)
+    if not create_first_admin(request.username, request.name, request.password, request.email or ""):
+        raise ConflictError("Setup has already been completed or the username is taken")
+    return {"message": "Admin account created", "username": request.username}
+
+
+@auth_router.get("/auth/register", response_model=RegistrationInfo)
+async def registration_info():
+    """Whether registering needs an invite code"""
+    return {"invite_only": REGISTRATION_INVITE_ONLY}
+
+
+@auth_router.post("/auth/register", response_model=UserCreated, status_code=201)
+def register(request: RegisterRequest, http_request: Request):
+    """Create your own account, with an invite code while registration is invite-only"""
+    client_ip = http_request.client.host if http_request.client else "unknown"
+    enforce_auth_rate_limit(client_ip, request.username)
+
+    invite_code = (request.invite_code or "").strip() or None
+    if REGISTRATION_INVITE_ONLY and invite_code is None:
+        raise ValidationError("An invite code is required to register")
+    if email_in_use(request.email):
+        raise ConflictError("Another account already uses that email")
+    _reject_breached(request.password)
+    try:
+        role = register_user(request.username, request.name, request.password, request.email, invite_code)
+    except RegistrationError as e:
+        raise ValidationError(str(e))
+    if role is None:
+        raise ConflictError("Username already exists")
+    return {"message": "Account created", "username": request.username}
+
+
+OAUTH_STATE_COOKIE = "oauth_state"
+
+
+def _frontend_redirect(path, **params):
+    query = urlencode(params)
+    return RedirectResponse(f"{FRONTEND_URL.rstrip('/')}{path}{'?' + query if query else ''}")
+
+
+@auth_router.get("/auth/oauth/providers", response_model=List[OAuthProvider])
+async def oauth_providers():
+    """List the external sign-in providers that are configured"""
+    return enabled_providers()
+
+
+def _oauth_cookie_path(request: Request):
+    """The .../auth/oauth prefix this request came in under (versioned or legacy)."""
+    return request.url.path.rsplit("/", 2)[0]
+
+
+@auth_router.get("/auth/oauth/{provider}/start", response_class=RedirectResponse, status_code=307)
+async def oauth_start(provider: str, request: Request):
+    """Send the browser to the provider's consent page"""
+    state = create_state_token(provider)
+    response = RedirectResponse(authorization_url(provider, state))
+    # Ties the callback to this browser, so a forged callback link can't sign someone in
+    response.set_cookie(
+        OAUTH_STATE_COOKIE,
+        state,
+        max_age=600,
+        httponly=True,
+        samesite="lax",
+        path=_oauth_cookie_path(request),
+        secure=BACKEND_URL.startswith("https://"),
+    )
+    return response
+
+
+@auth_router.get("/auth/oauth/{provider}/callback", response_class=RedirectResponse, status_code=307)
+def oauth_callback(
+    provider: str, request: Request, code: str = "", state: str = "", error: str = ""
+):
+    """Finish an OAuth sign-in and hand the session tokens to the frontend"""
+    if error or not code:
+        return _frontend_redirect("/login", oauth_error=error or "Sign-in was cancelled")
+    if state != request.cookies.get(OAUTH_STATE_COOKIE) or not verify_state_token(state, provider):
+        return _frontend_redirect("/login", oauth_error="Sign-in expired, please try again")
+
+    try:
+        identity = fetch_identity(provider, code)
+    except AppError as e:
+        return _frontend_redirect("/login", oauth_error=e.detail)
+
+    username = find_user_for_identity(provider, identity["subject"], identity["email"])
+    role = get_user_role(username) if username else None
+    if role is None:
+        return _frontend_redirect(
+            "/login", oauth_error="No ArtiQuest account is linked to that sign-in. Ask an administrator."
+        )
+    if not get_user_row(username)[5]:
+        return _frontend_redirect("/login", oauth_error="This account has been deactivated")
+    if get_locked_until(username):
+        return _frontend_redirect("/login", oauth_error="This account is temporarily locked")
+
+    log_action(username, f"Logged in with {provider}")
+    record_login(
+        username,
+        request.client.host if request.client else "unknown",
+        request.headers.get("user-agent"),
+        method=provider,
+    )
+    access_token, expires_in = create_access_token(username, role)
+    # Tokens travel in the fragment so they never reach server logs or Referer headers
+    fragment = urlencode(
         {
-            "username": u[0],
-            "name": u[1],
-            "role": u[2],
-            "email": u[3]
+            "access_token": access_token,
+            "refresh_token": issue_refresh_token(username),
+            "expires_in": expires_in,
         }
-        for u in users
+    )
+    response = RedirectResponse(f"{FRONTEND_URL.rstrip('/')}/oauth/callback#{fragment}")
+    response.delete_cookie(OAUTH_STATE_COOKIE, path=_oauth_cookie_path(request))
+    return response
+
+
+@auth_router.get("/auth/me", response_model=UserResponse)
+def get_me(current_user: TokenUser = Depends(get_current_user)):
+    """Return the profile of the user the access token belongs to"""
+    info = get_user_info(current_user.username)
+    if not info:
+        raise AuthenticationError("User no longer exists")
+    name, email, role = info
+    return {"username": current_user.username, "name": name, "email": email, "role": role}
+
+
+def _user_item(row) -> dict:
+    """Shape a users-table row for UserResponse."""
+    username, name, role, email, locked_until, active, deactivated_at = row
+    # Expired locks are left in the table until the next login clears them
+    if locked_until and datetime.fromisoformat(locked_until) <= datetime.now(timezone.utc):
+        locked_until = None
+    return {
+        "username": username,
+        "name": name,
+        "role": role,
+        "email": email,
+        "locked_until": locked_until,
+        "active": bool(active),
+        "deactivated_at": deactivated_at,
+    }
+
+
+@account_router.patch("/user/profile", response_model=UserResponse)
+def update_profile(
+    update: ProfileUpdate, current_user: TokenUser = Depends(get_current_user)
+):
+    """Update the caller's own display name and email"""
+    changes = update.model_dump(exclude_unset=True, exclude_none=True)
+    if "name" in changes:
+        changes["name"] = changes["name"].strip()
+        if not changes["name"]:
+            raise ValidationError("Name can't be blank")
+    if "email" in changes and email_in_use(changes["email"], exclude_username=current_user.username):
+        raise ConflictError("Another account already uses that email")
+
+    if not update_user(current_user.username, changes, current_user.username):
+        raise NotFoundError("User not found")
+    return _user_item(get_user_row(current_user.username))
+
+
+@account_router.get("/user/export")
+def export_my_data(current_user: TokenUser = Depends(get_current_user)):
+    """Download everything stored about your account as JSON"""
+    data = export_user_data(current_user.username)
+    if data is None:
+        raise AuthenticationError("User no longer exists")
+    data["artifacts"] = [
+        {k: v for k, v in artifact.items() if k != "model_3d_data"}
+        for artifact in get_all_artifacts(limit=None, uploaded_by=current_user.username)
+    ]
+    data["exported_at"] = datetime.now(timezone.utc).isoformat()
+    log_action(current_user.username, "Exported account data")
+    return JSONResponse(
+        jsonable_encoder(data),
+        headers={"Content-Disposition": f'attachment; filename="{current_user.username}-data.json"'},
+    )
+
+
+# Settings are a free-form object owned by the frontend; this only bounds the size
+USER_SETTINGS_MAX_BYTES = 16 * 1024
+
+
+@account_router.get("/user/settings", response_model=Dict[str, Any])
+def get_my_settings(current_user: TokenUser = Depends(get_current_user)):
+    """Your saved frontend preferences (theme, default tier, language, ...)"""
+    return get_user_settings(current_user.username)
+
+
+@account_router.put("/user/settings", response_model=Dict[str, Any])
+def put_my_settings(
+    settings: Dict[str, Any] = Body(...), current_user: TokenUser = Depends(get_current_user)
+):
+    """Replace your saved frontend preferences with this JSON object"""
+    if len(json.dumps(settings)) > USER_SETTINGS_MAX_BYTES:
+        raise ValidationError(f"Settings must be under {USER_SETTINGS_MAX_BYTES // 1024} KB")
+    save_user_settings(current_user.username, settings)
+    return settings
+
+
+@account_router.get("/user/logins", response_model=List[LoginRecord])
+def my_login_history(
+    limit: int = Query(20, ge=1, le=100), current_user: TokenUser = Depends(get_current_user)
+):
+    """Your most recent sign-ins, newest first, to spot access you don't recognise"""
+    return [
+        {"logged_in_at": logged_in_at, "ip_address": ip_address, "user_agent": user_agent, "method": method}
+        for logged_in_at, ip_address, user_agent, method in get_login_history(current_user.username, limit)
     ]
 
 
-@app.post("/api/users")
-async def create_user(user: UserCreate):
+@account_router.delete("/user", response_model=MessageResponse)
+def delete_my_account(
+    request: AccountDeletion,
+    http_request: Request,
+    response: Response,
+    current_user: TokenUser = Depends(get_current_user),
+):
+    """Delete your own account and personal data; needs your current password"""
+    client_ip = http_request.client.host if http_request.client else "unknown"
+    enforce_auth_rate_limit(client_ip, current_user.username)
+
+    credentials = get_credentials(current_user.username)
+    if not credentials or not verify_password(request.password, credentials[2])[0]:
+        raise AuthenticationError("Password is incorrect")
+    if current_user.role == "admin" and count_admins() <= 1:
+        raise ConflictError("You are the only admin; make someone else an admin first")
+
+    # Artifacts stay in the archive, but no longer name their uploader or verifier
+    anonymize_user_references(current_user.username, ERASED_USERNAME)
+    erase_user(current_user.username)
+    _set_session_cookie(response, "", 0)
+    return {"message": "Your account and personal data have been deleted"}
+
+
+@account_router.get(
+    "/users",
+    response_model=List[UserResponse],
+    dependencies=[Depends(require_roles("admin"))],
+)
+def get_all_users():
+    """Get all users (admin only)"""
+    return [_user_item(u) for u in get_users()]
+
+
+@account_router.post(
+    "/users",
+    response_model=UserCreated,
+    dependencies=[Depends(require_roles("admin"))],
+)
+def create_user(user: UserCreate):
     """Create a new user (admin only)"""
+    _reject_breached(user.password)
     success = add_user(user.username, user.name, user.password, user.role, user.email)
     if not success:
-        raise HTTPException(status_code=400, detail="Username already exists")
+        raise ConflictError("Username already exists")
     return {"message": "User created successfully", "username": user.username}
 
 
-@app.get("/api/audit-logs")
-async def get_audit_logs_endpoint():
+@account_router.get(
+    "/admin/users",
+    response_model=List[UserResponse],
+    dependencies=[Depends(require_roles("admin"))],
+)
+def admin_list_users(
+    response: Response,
+    role: Optional[str] = None,
+    email: Optional[str] = None,
+    q: Optional[str] = None,
+    active: Optional[bool] = None,
+    limit: int = Query(50, ge=1, le=500),
+    offset: int = Query(0, ge=0),
+):
+    """List users with optional role/email/name/status filters; the total is in X-Total-Count"""
+    rows, total = query_users(role=role, email=email, search=q, active=active, limit=limit, offset=offset)
+    response.headers["X-Total-Count"] = str(total)
+    return [_user_item(row) for row in rows]
+
+
+@account_router.get(
+    "/admin/users/{username}",
+    response_model=UserResponse,
+    dependencies=[Depends(require_roles("admin"))],
+)
+def admin_get_user(username: str):
+    """Get one user (admin only)"""
+    row = get_user_row(username)
+    if not row:
+        raise NotFoundError("User not found")
+    return _user_item(row)
+
+
+@account_router.post(
+    "/admin/users",
+    response_model=UserResponse,
+    status_code=201,
+)
+def admin_create_user(
+    user: UserCreate, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Create a user (admin only)"""
+    if email_in_use(user.email):
+        raise ConflictError("Another account already uses that email")
+    _reject_breached(user.password)
+    if not add_user(user.username, user.name, user.password, user.role, user.email):
+        raise ConflictError("Username already exists")
+    log_action(current_user.username, f"Created user {user.username}")
+    return _user_item(get_user_row(user.username))
+
+
+@account_router.patch("/admin/users/{username}", response_model=UserResponse)
+def admin_update_user(
+    username: str,
+    update: UserUpdate,
+    current_user: TokenUser = Depends(require_roles("admin")),
+):
+    """Change a user's name, email or role (admin only)"""
+    existing = get_user_row(username)
+    if not existing:
+        raise NotFoundError("User not found")
+
+    changes = update.model_dump(exclude_unset=True, exclude_none=True)
+    if "email" in changes and email_in_use(changes["email"], exclude_username=username):
+        raise ConflictError("Another account already uses that email")
+    demoting = changes.get("role", "admin") != "admin" and existing[2] == "admin" and existing[5]
+    if demoting and count_admins() <= 1:
+        raise ConflictError("Can't demote the last admin")
+
+    update_user(username, changes, current_user.username)
+    if changes.get("role", existing[2]) != existing[2]:
+        presence_hub.notify_logout(username, "Your role was changed; please sign in again")
+    return _user_item(get_user_row(username))
+
+
+@account_router.delete("/admin/users/{username}", response_model=MessageResponse)
+def admin_delete_user(
+    username: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Delete a user and revoke their sessions and API keys (admin only)"""
+    if username == current_user.username:
+        raise ConflictError("You can't delete your own account here")
+    if not delete_user(username, current_user.username):
+        raise NotFoundError("User not found")
+    presence_hub.notify_logout(username, "Your account was deleted")
+    return {"message": f"{username} deleted"}
+
+
+@account_router.delete("/admin/users/{username}/sessions", response_model=MessageResponse)
+def admin_revoke_sessions(
+    username: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Sign a user out everywhere: revoke their sessions and close their open tabs (admin only)"""
+    if not get_user_row(username):
+        raise NotFoundError("User not found")
+    revoke_user_sessions(username)
+    log_action(current_user.username, f"Signed out {username} everywhere")
+    presence_hub.notify_logout(username, "An administrator signed you out")
+    return {"message": f"{username} signed out everywhere"}
+
+
+@account_router.post("/admin/users/{username}/deactivate", response_model=UserResponse)
+def admin_deactivate_user(
+    username: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Stop a user signing in, keeping their account and history (admin only)"""
+    existing = get_user_row(username)
+    if not existing:
+        raise NotFoundError("User not found")
+    if username == current_user.username:
+        raise ConflictError("You can't deactivate your own account")
+    if existing[2] == "admin" and existing[5] and count_admins() <= 1:
+        raise ConflictError("Can't deactivate the last admin")
+    if not deactivate_user(username, current_user.username):
+        raise ConflictError(f"{username} is already deactivated")
+    presence_hub.notify_logout(username, "Your account was deactivated")
+    return _user_item(get_user_row(username))
+
+
+@account_router.post("/admin/users/{username}/reactivate", response_model=UserResponse)
+def admin_reactivate_user(
+    username: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Let a deactivated user sign in again (admin only)"""
+    if not get_user_row(username):
+        raise NotFoundError("User not found")
+    if not reactivate_user(username, current_user.username):
+        raise ConflictError(f"{username} is already active")
+    return _user_item(get_user_row(username))
+
+
+@account_router.get(
+    "/admin/stats",
+    response_model=AdminStats,
+    dependencies=[Depends(require_roles("admin"))],
+)
+def admin_stats():
+    """User counts, active sessions, recent failed logins and lockouts (admin only)"""
+    stats = get_admin_stats()
+    stats["recent_failed_logins"] = [
+        {"username": username, "attempted_at": attempted_at, "ip_address": ip_address}
+        for username, attempted_at, ip_address in stats["recent_failed_logins"]
+    ]
+    stats["locked_accounts"] = [
+        {"username": username, "locked_until": locked_until} for username, locked_until in stats["locked_accounts"]
+    ]
+    return stats
+
+
+@account_router.post("/users/{username}/unlock", response_model=MessageResponse)
+def unlock_user_endpoint(
+    username: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Clear a lockout caused by repeated failed logins (admin only)"""
+    if not unlock_user(username, current_user.username):
+        raise NotFoundError("User not found")
+    return {"message": f"{username} unlocked"}
+
+
+@account_router.get(
+    "/audit-logs",
+    response_model=List[AuditLogEntry],
+    dependencies=[Depends(require_roles("admin"))],
+)
+def get_audit_logs_endpoint():
     """Get audit logs (admin only)"""
     logs = get_audit_logs()
     return [
@@ -220,23 +1109,399 @@ async def get_audit_logs_endpoint():
     ]
 
 
-@app.post("/api/users/change-password")
-async def change_password_endpoint(request: PasswordChange):
-    """Change user password"""
+def _invite_info(row):
+    invite_id, prefix, role, email, created_by, created_at, expires_at, used_by, used_at, revoked_at = row
+    return {
+        "id": invite_id,
+        "prefix": prefix,
+        "role": role,
+        "email": email,
+        "created_by": created_by,
+        "created_at": created_at,
+        "expires_at": expires_at,
+        "used_by": used_by,
+        "used_at": used_at,
+        "revoked_at": revoked_at,
+    }
+
+
+@account_router.post("/admin/invites", response_model=InviteCreated, status_code=201)
+def create_invite_endpoint(
+    request: InviteCreate, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Create a single-use registration code (admin only); the code is shown once"""
+    invite_id, code = create_invite(request.role, current_user.username, request.email)
+    return {**_invite_info(get_invite(invite_id)), "code": code}
+
+
+@account_router.get(
+    "/admin/invites",
+    response_model=List[InviteInfo],
+    dependencies=[Depends(require_roles("admin"))],
+)
+def list_invites_endpoint():
+    """All invites, newest first (admin only)"""
+    return [_invite_info(row) for row in list_invites()]
+
+
+@account_router.delete("/admin/invites/{invite_id}", response_model=MessageResponse)
+def revoke_invite_endpoint(
+    invite_id: int, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Revoke an unused invite (admin only)"""
+    if not revoke_invite(invite_id, current_user.username):
+        raise NotFoundError("Invite not found or already used")
+    return {"message": "Invite revoked"}
+
+
+def _ip_rule_info(row):
+    rule_id, cidr, action, note, created_by, created_at = row
+    return {
+        "id": rule_id,
+        "cidr": cidr,
+        "action": action,
+        "note": note,
+        "created_by": created_by,
+        "created_at": created_at,
+        "source": "admin",
+    }
+
+
+def _check_not_locked_out(request: Request, rules):
+    """Refuse a rule change that would block the admin making it."""
+    client_ip = request.client.host if request.client else ""
+    if not build_rules(rules).allows(client_ip):
+        raise ConflictError(f"This change would block your own address ({client_ip})")
+
+
+@account_router.get(
+    "/admin/ip-rules",
+    response_model=List[IpRuleInfo],
+    dependencies=[Depends(require_roles("admin"))],
+)
+def list_ip_rules_endpoint():
+    """Network access rules: the configured lists, then those added by admins (admin only)"""
+    configured = [{"cidr": cidr, "action": "allow", "source": "config"} for cidr in IP_ALLOWLIST]
+    configured += [{"cidr": cidr, "action": "deny", "source": "config"} for cidr in IP_DENYLIST]
+    return configured + [_ip_rule_info(row) for row in list_ip_rules()]
+
+
+@account_router.post("/admin/ip-rules", response_model=IpRuleInfo, status_code=201)
+def create_ip_rule_endpoint(
+    request: IpRuleCreate,
+    http_request: Request,
+    current_user: TokenUser = Depends(require_roles("admin")),
+):
+    """Allow or deny an address or CIDR range (admin only)"""
+    rules = [(row[2], row[1]) for row in list_ip_rules()] + [(request.action, request.cidr)]
+    _check_not_locked_out(http_request, rules)
+    note = request.note.strip() if request.note else None
+    rule_id = add_ip_rule(request.cidr, request.action, current_user.username, note or None)
+    invalidate_ip_rules()
+    return _ip_rule_info(get_ip_rule(rule_id))
+
+
+@account_router.delete("/admin/ip-rules/{rule_id}", response_model=MessageResponse)
+def delete_ip_rule_endpoint(
+    rule_id: int,
+    http_request: Request,
+    current_user: TokenUser = Depends(require_roles("admin")),
+):
+    """Remove a rule added by an admin (admin only); configured rules can't be removed here"""
+    rules = [(row[2], row[1]) for row in list_ip_rules() if row[0] != rule_id]
+    _check_not_locked_out(http_request, rules)
+    if not delete_ip_rule(rule_id, current_user.username):
+        raise NotFoundError("IP rule not found")
+    invalidate_ip_rules()
+    return {"message": "IP rule removed"}
+
+
+def _organization_info(row, rank=None):
+    org_id, slug, name, created_at, member_count = row[:5]
+    return {
+        "id": org_id,
+        "slug": slug,
+        "name": name,
+        "created_at": created_at,
+        "member_count": member_count,
+        "rank": rank,
+    }
+
+
+def _org_member(row):
+    username, name, email, rank, joined_at = row
+    return {"username": username, "name": name, "email": email, "rank": rank, "joined_at": joined_at}
+
+
+def _outranks(rank, required):
+    return ORG_RANKS.index(rank) <= ORG_RANKS.index(required)
+
+
+def _org_for(slug, user: TokenUser, required="member"):
+    """
+    The organization and the caller's own rank in it (None for non-members).
+    Admins may manage any organization as if they were an owner; anyone else
+    needs the required rank, and non-members can't tell the organization exists.
+    """
+    org = get_organization(slug)
+    rank = get_member_rank(org[0], user.username) if org else None
+    if not org or (rank is None and user.role != "admin"):
+        raise NotFoundError("Organization not found")
+    if user.role != "admin" and not _outranks(rank, required):
+        raise PermissionDeniedError(f"Requires the {required} rank in this organization")
+    return org, rank
+
+
+@account_router.get("/orgs", response_model=List[OrganizationInfo])
+def list_organizations_endpoint(current_user: TokenUser = Depends(get_current_user)):
+    """Organizations the caller belongs to; admins see every organization"""
+    mine = list_organizations(current_user.username)
+    if current_user.role != "admin":
+        return [_organization_info(row, row[5]) for row in mine]
+    ranks = {row[0]: row[5] for row in mine}
+    return [_organization_info(row, ranks.get(row[0])) for row in list_organizations()]
+
+
+@account_router.post("/orgs", response_model=OrganizationInfo, status_code=201)
+def create_organization_endpoint(
+    request: OrganizationCreate, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Create an organization with a first owner (admin only)"""
+    owner = request.owner or current_user.username
+    owner_row = get_user_row(owner)
+    if not owner_row or not owner_row[5]:
+        raise ValidationError(f"No active user named {owner}")
+    name = request.name.strip()
+    if not name:
+        raise ValidationError("Name can't be blank")
+    if create_organization(request.slug, name, owner, current_user.username) is None:
+        raise ConflictError("Another organization already uses that slug")
+    rank = "owner" if owner == current_user.username else None
+    return _organization_info(get_organization(request.slug), rank)
+
+
+@account_router.get("/orgs/{slug}", response_model=OrganizationInfo)
+def get_organization_endpoint(slug: str, current_user: TokenUser = Depends(get_current_user)):
+    """One organization the caller belongs to"""
+    org, rank = _org_for(slug, current_user)
+    return _organization_info(org, rank)
+
+
+@account_router.delete("/orgs/{slug}", response_model=MessageResponse)
+def delete_organization_endpoint(
+    slug: str, current_user: TokenUser = Depends(require_roles("admin"))
+):
+    """Delete an organization and its memberships; the accounts remain (admin only)"""
+    org, _ = _org_for(slug, current_user)
+    delete_organization(org[0], current_user.username)
+    return {"message": f"Organization {slug} deleted"}
+
+
+@account_router.get("/orgs/{slug}/members", response_model=List[OrgMember])
+def list_members_endpoint(slug: str, current_user: TokenUser = Depends(get_current_user)):
+    """Members of an organization, owners first"""
+    org, _ = _org_for(slug, current_user)
+    return [_org_member(row) for row in list_members(org[0])]
+
+
+def _member(org_id, username):
+    for row in list_members(org_id):
+        if row[0] == username:
+            return _org_member(row)
+    raise NotFoundError(f"{username} isn't a member")
+
+
+@account_router.post("/orgs/{slug}/members", response_model=OrgMember, status_code=201)
+def add_member_endpoint(
+    slug: str, request: OrgMemberAdd, current_user: TokenUser = Depends(get_current_user)
+):
+    """Add a user to the organization; managers may add members, owners any rank"""
+    org, rank = _org_for(slug, current_user, "manager")
+    if request.rank != "member" and current_user.role != "admin" and rank != "owner":
+        raise PermissionDeniedError("Only owners can add owners or managers")
+    user_row = get_user_row(request.username)
+    if not user_row or not user_row[5]:
+        raise NotFoundError(f"No active user named {request.username}")
+    if not add_member(org[0], request.username, request.rank, current_user.username):
+        raise ConflictError(f"{request.username} is already a member")
+    return _member(org[0], request.username)
+
+
+@account_router.patch("/orgs/{slug}/members/{username}", response_model=OrgMember)
+def update_member_endpoint(
+    slug: str,
+    username: str,
+    request: OrgMemberUpdate,
+    current_user: TokenUser = Depends(get_current_user),
+):
+    """Change a member's rank (owners only)"""
+    org, _ = _org_for(slug, current_user, "owner")
+    try:
+        if not set_member_rank(org[0], username, request.rank, current_user.username):
+            raise NotFoundError(f"{username} isn't a member")
+    except OrganizationError as e:
+        raise ConflictError(str(e))
+    return _member(org[0], username)
+
+
+@account_router.delete("/orgs/{slug}/members/{username}", response_model=MessageResponse)
+def remove_member_endpoint(
+    slug: str, username: str, current_user: TokenUser = Depends(get_current_user)
+):
+    """Remove a member, or leave; managers may only remove plain members"""
+    leaving = username == current_user.username
+    org, rank = _org_for(slug, current_user, "member" if leaving else "manager")
+    if not leaving and current_user.role != "admin" and rank != "owner":
+        if get_member_rank(org[0], username) not in (None, "member"):
+            raise PermissionDeniedError("Only owners can remove owners or managers")
+    try:
+        if not remove_member(org[0], username, current_user.username):
+            raise NotFoundError(f"{username} isn't a member")
+    except OrganizationError as e:
+        raise ConflictError(str(e))
+    return {"message": f"You left {slug}" if leaving else f"{username} removed from {slug}"}
+
+
+def _api_key_info(row):
+    key_id, username, name, prefix, created_at, last_used_at, revoked_at = row
+    return {
+        "id": key_id,
+        "username": username,
+        "name": name,
+        "prefix": prefix,
+        "created_at": created_at,
+        "last_used_at": last_used_at,
+        "revoked_at": revoked_at,
+    }
+
+
+@account_router.post("/api-keys", response_model=ApiKeyCreated)
+def create_api_key_endpoint(
+    request: ApiKeyCreate, current_user: TokenUser = Depends(get_current_user)
+):
+    """Create an API key for the caller; the key itself is only returned here"""
+    name = request.name.strip()
+    if not name:
+        raise ValidationError("Give the key a name")
+    key_id, key = create_api_key(current_user.username, name)
+    info = next(row for row in list_api_keys(current_user.username) if row[0] == key_id)
+    return {**_api_key_info(info), "key": key}
+
+
+@account_router.get("/api-keys", response_model=List[ApiKeyInfo])
+def list_api_keys_endpoint(
+    all_users: bool = False, current_user: TokenUser = Depends(get_current_user)
+):
+    """List the caller's API keys (admins can pass all_users=true)"""
+    if all_users and current_user.role != "admin":
+        raise PermissionDeniedError("Only admins can list everyone's API keys")
+    rows = list_api_keys(None if all_users else current_user.username)
+    return [_api_key_info(row) for row in rows]
+
+
+@account_router.delete("/api-keys/{key_id}", response_model=MessageResponse)
+def revoke_api_key_endpoint(
+    key_id: int, current_user: TokenUser = Depends(get_current_user)
+):
+    """Revoke one of the caller's API keys (admins can revoke any)"""
+    owner = None if current_user.role == "admin" else current_user.username
+    if not revoke_api_key(key_id, owner):
+        raise NotFoundError("API key not found")
+    log_action(current_user.username, f"API key {key_id} revoked")
+    return {"message": "API key revoked"}
+
+
+@account_router.post("/users/change-password", response_model=MessageResponse)
+def change_password_endpoint(
+    request: PasswordChange, current_user: TokenUser = Depends(get_current_user)
+):
+    """Change user password (your own, or anyone's as admin)"""
+    if request.username != current_user.username and current_user.role != "admin":
+        raise PermissionDeniedError("You can only change your own password")
+    _reject_breached(request.new_password)
     reset_password(request.username, request.new_password)
     return {"message": "Password updated successfully"}
 
 
+def _viewer(user: Optional[TokenUser]) -> Viewer:
+    """Who is asking, for the database's access checks: staff see everything, others what is theirs or shared."""
+    if user is None:
+        return Viewer()
+    if user.role in ARCHIVE_STAFF_ROLES or user.role == "admin":
+        return Viewer(username=user.username, sees_all=True)
+    return Viewer(username=user.username, team_ids=tuple(row[0] for row in list_organizations(user.username)))
+
+
+def _default_scope(user: Optional[TokenUser], scope: Optional[str]) -> str:
+    """Lists show the caller's own artifacts unless they ask for more; anonymous callers see public ones."""
+    return scope or ("mine" if user else "all")
+
+
+def _require_owner(item: Dict[str, Any], user: TokenUser, what: str = "artifact") -> None:
+    """Only an artifact's or collection's owner, or archive staff, may change it or its sharing."""
+    if item.get("owner_id") != user.username and not _viewer(user).sees_all:
+        raise PermissionDeniedError(f"Only the {what}'s owner can change it")
+
+
+def _editable_artifact(artifact_id: int, user: TokenUser, check_lock: bool = True) -> Dict[str, Any]:
+    """
+    The artifact, if the caller may change it: 404 if they can't see it, 403
+    if they can't edit it, 423 if a curator has locked it (unless ``check_lock`` is off).
+    """
+    artifact = get_artifact_by_id(artifact_id, _viewer(user))
+    if not artifact:
+        raise NotFoundError("Artifact not found")
+    _require_owner(artifact, user)
+    if check_lock and artifact.get("locked_at"):
+        raise ArtifactLockedError(
+            f"Artifact is locked by {artifact['locked_by']}: {artifact['lock_reason']}",
+            locked_by=artifact["locked_by"],
+        )
+    return artifact
+
+
+def _require_staff(user: TokenUser, action: str = "verify or reject artifacts") -> None:
+    if not _viewer(user).sees_all:
+        raise PermissionDeniedError(f"Only archive staff can {action}")
+
+
+def _check_sharing(visibility: str, team_id: Optional[int], shared_with: List[str], user: TokenUser) -> None:
+    """Team visibility needs a team the caller belongs to; shares need existing users."""
+    if visibility == "team":
+        if team_id is None:
+            raise ValidationError("team_id is required when visibility is team")
+        if not _viewer(user).sees_all and get_member_rank(team_id, user.username) is None:
+            raise PermissionDeniedError("You can only share with organizations you belong to")
+    unknown = [username for username in shared_with if get_user_info(username) is None]
+    if unknown:
+        raise ValidationError(f"No such user: {', '.join(unknown)}")
+
+
 @app.post("/api/artifacts")
-async def create_artifact(artifact: ArtifactBase):
-    """Create a new artifact with optional form data and 3D model"""
+async def create_artifact(artifact: ArtifactBase, current_user: CurrentUser):
+    """
+    Create a new artifact with optional form data and 3D model, owned by
+    the caller with ARTIFACT_DEFAULT_VISIBILITY. An image already in the
+    archive (that the caller can see) is refused with 409 and the existing
+    artifact's id, unless ALLOW_DUPLICATE_IMAGES is set.
+    """
     try:
-        # Decode base64 image
-        image_data = base64.b64decode(
-            artifact.image_data.split(",")[1]
-            if "," in artifact.image_data
-            else artifact.image_data
-        )
+        uploaded_image = _decode_image(artifact.image_data)
+        model_3d_bytes = _decode_image(artifact.model_3d_data) if artifact.model_3d_data else None
+    except ValueError:
+        raise ValidationError("image_data and model_3d_data must be valid base64")
+    try:
+        # HEIC, TIFF, BMP and RAW are stored as JPEG or PNG
+        image_data, original = _normalized_image(uploaded_image)
+
+        if not ALLOW_DUPLICATE_IMAGES:
+            existing = find_artifact_by_image(image_data, _viewer(current_user))
+            if existing:
+                raise DuplicateArtifactError(
+                    f'This image is already in the archive as "{existing["name"]}" (artifact #{existing["id"]})',
+                    headers={"Location": f"/api/artifacts/{existing['id']}"},
+                    existing_id=existing["id"],
+                )
 
         # Create thumbnail
         image = Image.open(BytesIO(image_data))
@@ -251,21 +1516,21 @@ async def create_artifact(artifact: ArtifactBase):
             "description": artifact.description,
             "tags": ",".join(artifact.tags) if artifact.tags else "",
             "tier": artifact.tier,
-            "uploaded_by": artifact.uploaded_by,
+            # The signed-in user, whatever the client claims
+            "uploaded_by": current_user.username,
+            "owner_id": current_user.username,
+            "visibility": ARTIFACT_DEFAULT_VISIBILITY,
         }
 
+        artifact_data.update(original)
+        # When and where the photo was taken, if the camera recorded it
+        artifact_data.update(exif_metadata.extract(uploaded_image))
+
         # Store form data as JSON string if provided
         if artifact.form_data:
             artifact_data["form_data"] = json.dumps(artifact.form_data.dict())
 
-        # Decode and store 3D model data if provided
-        model_3d_bytes = None
-        if artifact.model_3d_data:
-            model_3d_bytes = base64.b64decode(
-                artifact.model_3d_data.split(",")[1]
-                if "," in artifact.model_3d_data
-                else artifact.model_3d_data
-            )
+        if model_3d_bytes is not None:
             artifact_data["model_3d_format"] = artifact.model_3d_format or "obj"
 
         artifact_id = save_artifact(
@@ -274,101 +1539,314 @@ async def create_artifact(artifact: ArtifactBase):
             thumbnail_bytes=thumbnail_data,
             model_3d_bytes=model_3d_bytes
         )
+        image_embeddings.index_artifact(artifact_id, image_data)
 
         return {"id": artifact_id, "message": "Artifact created successfully", "has_3d_model": model_3d_bytes is not None}
+    except HTTPException:
+        raise
     except Exception as e:
         logger.error(f"Error creating artifact: {str(e)}")
-        raise HTTPException(status_code=400, detail=str(e))
+        raise DatabaseError("Failed to create artifact")
+
+
+def _image_url(image_hash: Optional[str]) -> Optional[str]:
+    """Path of a stored image, relative to the API's base URL."""
+    return f"/api/images/{image_hash}" if image_hash else None
+
+
+def _artifact_list_item(a: dict, include_images: bool = True) -> dict:
+    """Shape a database artifact dict for list/search responses.
+
+    Without ``include_images`` only the thumbnail is linked; clients that
+    show a grid fetch the full image from GET /api/artifacts/{id} when opened.
+    """
+    item = {
+        "id": a["id"],
+        "name": a["name"],
+        "description": a.get("description"),
+        "tags": a["tags"].split(",") if a.get("tags") else [],
+        "tier": a.get("tier") or "standard",
+        "age": a.get("age"),
+        "thumbnail_url": _image_url(a.get("thumbnail_hash") or a.get("image_hash")),
+        "uploaded_at": a.get("uploaded_at"),
+        "analyzed_at": a.get("analyzed_at"),
+        "captured_at": a.get("captured_at"),
+        "latitude": a.get("latitude"),
+        "longitude": a.get("longitude"),
+        "confidence": a.get("confidence"),
+        "form_data": a.get("form_data"),
+        "uploaded_by": a.get("uploaded_by"),
+        "verification_status": a.get("verification_status") or "pending",
+        "owner_id": a.get("owner_id"),
+        "visibility": a.get("visibility") or "private",
+        "collection_id": a.get("collection_id"),
+    }
+    if include_images:
+        item["image_url"] = _image_url(a.get("image_hash"))
+    return item
+
+
+ARTIFACT_PAGE_MAX = 500
+# Most points one GET /api/artifacts/locations returns
+ARTIFACT_LOCATIONS_MAX = 2000
+
+
+def _bbox(value: Optional[str]) -> Optional[Tuple[float, float, float, float]]:
+    """Parse a "west,south,east,north" bounding box in degrees; west > east crosses the antimeridian."""
+    if not value:
+        return None
+    try:
+        west, south, east, north = (float(part) for part in value.split(","))
+    except ValueError:
+        raise ValidationError("bbox must be west,south,east,north in degrees")
+    if not (-180 <= west <= 180 and -180 <= east <= 180 and -90 <= south <= north <= 90):
+        raise ValidationError("bbox is out of range: longitudes -180..180, latitudes -90..90 with south <= north")
+    return west, south, east, north
+
+
+def _set_pagination_headers(request: Request, response: Response, limit: int, offset: int, total: int):
+    """X-Total-Count, plus a Link header with first/prev/next/last pages (RFC 8288)"""
+    response.headers["X-Total-Count"] = str(total)
+    pages = {"first": 0, "last": max(total - 1, 0) // limit * limit}
+    if offset > 0:
+        pages["prev"] = max(offset - limit, 0)
+    if offset + limit < total:
+        pages["next"] = offset + limit
+    response.headers["Link"] = ", ".join(
+        f'<{request.url.include_query_params(limit=limit, offset=page_offset)}>; rel="{rel}"'
+        for rel, page_offset in pages.items()
+    )
 
 
 @app.get("/api/artifacts")
-async def get_all_artifacts_endpoint():
-    """Get all artifacts"""
-    artifacts = get_all_artifacts(include_images=True)
-    result = []
-    for a in artifacts:
-        image_data_url = None
-
-        # Handle image_base64 field from database (when include_images=True)
-        if a.get("image_base64"):
-            image_data_url = f"data:image/png;base64,{a['image_base64']}"
-        # Fallback for image_data field if it's binary
-        elif a.get("image_data"):
-            if isinstance(a["image_data"], bytes):
-                image_data_url = f"data:image/png;base64,{base64.b64encode(a['image_data']).decode()}"
-            else:
-                image_data_url = f"data:image/png;base64,{a['image_data']}"
-
-        result.append(
-            {
-                "id": a["id"],
-                "name": a["name"],
-                "description": a.get("description"),
-                "tags": a["tags"].split(",") if a.get("tags") else [],
-                "tier": a.get("tier") or "standard",
-                "thumbnail": image_data_url,
-                "image_data": image_data_url,
-                "uploaded_at": a.get("uploaded_at"),
-                "analyzed_at": a.get("analyzed_at"),
-                "confidence": a.get("confidence"),
-                "form_data": a.get("form_data"),
-            }
-        )
-    return result
+async def get_all_artifacts_endpoint(
+    request: Request,
+    response: Response,
+    scope: Optional[ShareScope] = None,
+    collection_id: Optional[int] = None,
+    uploaded_by: Optional[str] = None,
+    era: Optional[str] = None,
+    tier: Optional[str] = None,
+    tag: Optional[str] = None,
+    min_confidence: Optional[float] = Query(None, ge=0, le=1),
+    verified: Optional[bool] = None,
+    bbox: Optional[str] = None,
+    sort: ArtifactSort = "uploaded_at",
+    order: SortOrder = "desc",
+    limit: int = Query(100, ge=1, le=ARTIFACT_PAGE_MAX),
+    offset: int = Query(0, ge=0),
+    include_images: bool = True,
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """List artifacts, filtered and sorted on the server, a page at a time
+
+    - scope: mine (the default when signed in), shared (other people's
+      artifacts you can see) or all; anonymous callers get public artifacts
+    - collection_id: only artifacts in this collection
+    - era: text in the estimated age, e.g. "bronze age"
+    - tier: analysis tier
+    - tag: comma-separated tags the artifact must all have
+    - min_confidence: lowest analysis confidence, 0-1
+    - verified: true for verified artifacts only, false for pending and rejected
+    - bbox: west,south,east,north in degrees; only photos taken inside it
+      (by their EXIF GPS position)
+    - include_images: false to link only the thumbnail, not the full image
+
+    The total is in X-Total-Count; the Link header points at the other pages.
+    """
+    filters = {
+        "viewer": _viewer(current_user),
+        "scope": _default_scope(current_user, scope),
+        "collection_id": collection_id,
+        "uploaded_by": uploaded_by,
+        "era": era,
+        "tier": tier,
+        "tags": tag,
+        "min_confidence": min_confidence,
+        "verified": verified,
+        "bbox": _bbox(bbox),
+    }
+    artifacts = get_all_artifacts(limit=limit, offset=offset, sort=sort, order=order, include_blobs=False, **filters)
+    _set_pagination_headers(request, response, limit, offset, count_artifacts(**filters))
+    return [_artifact_list_item(a, include_images) for a in artifacts]
 
 
 @app.get("/api/artifacts/search")
-async def search_artifacts_endpoint(q: str = ""):
-    """Search artifacts by query string"""
-    results = search_artifacts(q)
-    result = []
-    for a in results:
-        image_data_url = None
-
-        # Handle image_base64 field from database
-        if a.get("image_base64"):
-            image_data_url = f"data:image/png;base64,{a['image_base64']}"
-        # Fallback for image_data field if it's binary
-        elif a.get("image_data"):
-            if isinstance(a["image_data"], bytes):
-                image_data_url = f"data:image/png;base64,{base64.b64encode(a['image_data']).decode()}"
-            else:
-                image_data_url = f"data:image/png;base64,{a['image_data']}"
-
-        result.append(
-            {
-                "id": a["id"],
-                "name": a["name"],
-                "description": a.get("description"),
-                "tags": a["tags"].split(",") if a.get("tags") else [],
-                "tier": a.get("tier") or "standard",
-                "thumbnail": image_data_url,
-                "image_data": image_data_url,
-                "uploaded_at": a.get("uploaded_at"),
-                "analyzed_at": a.get("analyzed_at"),
-                "confidence": a.get("confidence"),
-                "form_data": a.get("form_data"),
-            }
-        )
-    return result
+async def search_artifacts_endpoint(
+    request: Request,
+    response: Response,
+    q: str = "",
+    scope: Optional[ShareScope] = None,
+    uploaded_by: Optional[str] = None,
+    limit: int = Query(50, ge=1, le=ARTIFACT_PAGE_MAX),
+    offset: int = Query(0, ge=0),
+    include_images: bool = True,
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """Search artifacts by query string, best match first; scoped, paged and shaped like GET /api/artifacts"""
+    access = {"viewer": _viewer(current_user), "scope": _default_scope(current_user, scope)}
+    results = search_artifacts(q, limit=limit, offset=offset, uploaded_by=uploaded_by, include_blobs=False, **access)
+    _set_pagination_headers(
+        request, response, limit, offset, count_search_results(q, uploaded_by=uploaded_by, **access)
+    )
+    return [_artifact_list_item(a, include_images) for a in results]
+
+
+@app.get("/api/artifacts/locations", response_model=List[ArtifactLocation])
+async def artifact_locations_endpoint(
+    response: Response,
+    bbox: Optional[str] = None,
+    scope: Optional[ShareScope] = None,
+    collection_id: Optional[int] = None,
+    limit: int = Query(500, ge=1, le=ARTIFACT_LOCATIONS_MAX),
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """
+    Where the artifacts the caller can see were photographed, for a map:
+    those with an EXIF GPS position inside ``bbox`` (west,south,east,north;
+    the whole world if left out), newest first. The total inside the box is
+    in X-Total-Count, so a map can tell when to zoom in.
+    """
+    filters = {
+        "viewer": _viewer(current_user),
+        "scope": _default_scope(current_user, scope),
+        "collection_id": collection_id,
+        "bbox": _bbox(bbox) or (-180.0, -90.0, 180.0, 90.0),
+    }
+    artifacts = get_all_artifacts(limit=limit, include_blobs=False, **filters)
+    response.headers["X-Total-Count"] = str(count_artifacts(**filters))
+    return [
+        {
+            "id": a["id"],
+            "name": a["name"],
+            "latitude": a["latitude"],
+            "longitude": a["longitude"],
+            "captured_at": a.get("captured_at"),
+            "thumbnail_url": _image_url(a.get("thumbnail_hash") or a.get("image_hash")),
+        }
+        for a in artifacts
+    ]
 
 
-@app.get("/api/artifacts/{artifact_id}")
-async def get_artifact(artifact_id: int):
-    """Get a single artifact by ID"""
-    artifact = get_artifact_by_id(artifact_id)
-    if not artifact:
-        raise HTTPException(status_code=404, detail="Artifact not found")
+TAGS_MAX = 1000
 
-    image_data_url = None
 
-    # Handle image_data field - it's binary bytes from get_artifact_by_id
-    if artifact.get("image_data"):
-        if isinstance(artifact["image_data"], bytes):
-            image_data_url = f"data:image/png;base64,{base64.b64encode(artifact['image_data']).decode()}"
-        else:
-            image_data_url = f"data:image/png;base64,{artifact['image_data']}"
+@app.get("/api/tags", response_model=List[TagCount])
+async def list_tags_endpoint(
+    q: Optional[str] = None,
+    limit: int = Query(100, ge=1, le=TAGS_MAX),
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """
+    The tag vocabulary: tags on the artifacts the caller can see, with how
+    many carry each, most used first. ``q`` keeps tags containing it (any
+    case), for autocomplete.
+    """
+    return await run_in_threadpool(list_tags, _viewer(current_user), q, limit)
+
+
+@app.post("/api/tags/merge", response_model=TagChange)
+async def merge_tags_endpoint(merge: TagMerge, current_user: TokenUser = Depends(require_roles("admin"))):
+    """Replace the ``sources`` tags with ``target`` on every artifact (admin only)"""
+    target = " ".join(merge.target.split())
+    if not target or "," in target:
+        raise ValidationError("target must be a tag name without commas")
+    changed = await run_in_threadpool(merge_tags, merge.sources, target, current_user.username)
+    log_action(current_user.username, f"Merged tags {', '.join(merge.sources)} into {target} ({changed} artifacts)")
+    return {"tag": target, "artifacts_changed": changed}
+
+
+@app.post("/api/tags/rename", response_model=TagChange)
+async def rename_tag_endpoint(rename: TagRename, current_user: TokenUser = Depends(require_roles("admin"))):
+    """Rename a tag on every artifact (admin only); renaming to a tag already in use merges the two"""
+    new_name = " ".join(rename.new_name.split())
+    if not new_name or "," in new_name:
+        raise ValidationError("new_name must be a tag name without commas")
+    changed = await run_in_threadpool(merge_tags, [rename.tag], new_name, current_user.username)
+    log_action(current_user.username, f"Renamed tag {rename.tag} to {new_name} ({changed} artifacts)")
+    return {"tag": new_name, "artifacts_changed": changed}
+
+
+def _artifact_sync(
+    updated_since: Optional[str],
+    uploaded_by: Optional[str],
+    include_images: bool,
+    user: Optional[TokenUser],
+    known_ids: Optional[List[int]] = None,
+) -> Dict[str, Any]:
+    since = None
+    if updated_since:
+        try:
+            since = datetime.fromisoformat(updated_since.replace("Z", "+00:00"))
+        except ValueError:
+            raise ValidationError("updated_since must be an ISO 8601 timestamp")
+        if since.tzinfo is not None:
+            since = since.astimezone(timezone.utc).replace(tzinfo=None)
+
+    changes = get_artifact_changes(
+        since, uploaded_by=uploaded_by, viewer=_viewer(user), include_blobs=False, known_ids=known_ids
+    )
+    return {
+        "artifacts": [_artifact_list_item(a, include_images) for a in changes["artifacts"]],
+        "deleted_ids": changes["deleted_ids"],
+        "synced_at": changes["synced_at"].isoformat() + "Z",
+    }
+
+
+@app.get("/api/artifacts/sync")
+async def sync_artifacts_endpoint(
+    updated_since: Optional[str] = None,
+    uploaded_by: Optional[str] = None,
+    include_images: bool = True,
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """Return artifacts changed, and the caller's own ids deleted, since ``updated_since``.
+
+    Clients pass back the ``synced_at`` value from their previous sync; omitting
+    it returns every artifact the caller can see. Artifacts are shaped like
+    GET /api/artifacts, include_images included. Use POST to also learn which
+    held artifacts were unshared or otherwise went out of reach.
+    """
+    return await run_in_threadpool(_artifact_sync, updated_since, uploaded_by, include_images, current_user)
+
+
+@app.post("/api/artifacts/sync")
+async def sync_known_artifacts_endpoint(
+    sync: ArtifactSyncRequest, current_user: Optional[TokenUser] = Depends(get_optional_user)
+):
+    """
+    Like GET /api/artifacts/sync, but ``deleted_ids`` are those of
+    ``known_ids`` (the artifacts the client holds) that the caller can no
+    longer see, for whatever reason.
+    """
+    return await run_in_threadpool(
+        _artifact_sync, sync.updated_since, sync.uploaded_by, sync.include_images, current_user, sync.known_ids
+    )
 
+
+@app.get("/api/images/{image_hash}")
+async def get_image(image_hash: str, current_user: Optional[TokenUser] = Depends(get_optional_user)):
+    """
+    Serve a stored artifact image by its SHA-256 hash, to callers who can see
+    an artifact using it. Only images of public artifacts may be cached by
+    shared caches; the rest are 404 to everyone else.
+    """
+    access = await run_in_threadpool(image_access, image_hash, _viewer(current_user))
+    if not access or not await run_in_threadpool(image_store.exists, image_hash):
+        raise NotFoundError("Image not found")
+    # The content of a hash never changes
+    headers = {"Cache-Control": f"{access}, max-age=31536000, immutable", "ETag": f'"{image_hash}"'}
+    if access == "private":
+        headers["Vary"] = "Authorization, Cookie"
+    path = image_store.local_path(image_hash)
+    if path:
+        return FileResponse(path, media_type=image_store.media_type(image_hash), headers=headers)
+    data = await run_in_threadpool(image_store.get, image_hash)
+    return Response(content=data, media_type=image_store.sniff_media_type(data), headers=headers)
+
+
+def _artifact_detail(artifact: Dict[str, Any]) -> Dict[str, Any]:
+    """The full API representation of an artifact, as returned by GET and PATCH."""
     # Handle 3D model data
     model_3d_data_url = None
     has_3d_model = False
@@ -386,10 +1864,17 @@ async def get_artifact(artifact_id: int):
         "description": artifact.get("description"),
         "tags": artifact["tags"].split(",") if artifact.get("tags") else [],
         "tier": artifact.get("tier") or "standard",
-        "image_data": image_data_url,
-        "thumbnail": image_data_url,
+        "age": artifact.get("age"),
+        "notes": artifact.get("notes"),
+        "image_url": _image_url(artifact.get("image_hash")),
+        "thumbnail_url": _image_url(artifact.get("thumbnail_hash") or artifact.get("image_hash")),
         "uploaded_at": artifact.get("uploaded_at"),
         "analyzed_at": artifact.get("analyzed_at"),
+        "captured_at": artifact.get("captured_at"),
+        "original_format": artifact.get("original_format"),
+        "original_size": artifact.get("original_size"),
+        "latitude": artifact.get("latitude"),
+        "longitude": artifact.get("longitude"),
         "confidence": artifact.get("confidence"),
         "form_data": artifact.get("form_data"),
         "verification_status": artifact.get("verification_status", "pending"),
@@ -398,278 +1883,625 @@ async def get_artifact(artifact_id: int):
         "model_3d_data": model_3d_data_url,
         "model_3d_format": artifact.get("model_3d_format"),
         "has_3d_model": has_3d_model,
+        "updated_at": artifact.get("updated_at"),
+        "version": artifact.get("version"),
+        "uploaded_by": artifact.get("uploaded_by"),
+        "owner_id": artifact.get("owner_id"),
+        "visibility": artifact.get("visibility") or "private",
+        "team_id": artifact.get("team_id"),
+        "collection_id": artifact.get("collection_id"),
+        "locked_at": artifact.get("locked_at"),
+        "locked_by": artifact.get("locked_by"),
+        "lock_reason": artifact.get("lock_reason"),
+        "identification_overridden_by": artifact.get("identification_overridden_by"),
+        "identification_overridden_at": artifact.get("identification_overridden_at"),
     }
 
 
-class AnalyzeRequest(BaseModel):
-    image_data: str
-    tier: Optional[str] = "fast"
+def _etag(version: str) -> str:
+    return f'"{version}"'
 
 
-class BatchAnalyzeRequest(BaseModel):
-    images: List[str]  # List of base64 encoded images
-    tier: Optional[str] = "fast"
+def _if_match_version(if_match: Optional[str]) -> Optional[str]:
+    """
+    The version an If-Match header asks for: None for no header or "*",
+    else the single quoted version. Weak or multiple tags can't match.
+    """
+    if if_match is None or if_match.strip() == "*":
+        return None
+    tag = if_match.strip()
+    if len(tag) < 2 or not (tag.startswith('"') and tag.endswith('"')) or "," in tag:
+        raise PreconditionFailedError("If-Match must be a single strong ETag from GET /api/artifacts/{id}")
+    return tag[1:-1]
 
 
-class SimilaritySearchRequest(BaseModel):
-    image_data: str  # base64 encoded image
-    limit: Optional[int] = 10
+@app.get("/api/artifacts/{artifact_id}")
+async def get_artifact(
+    artifact_id: int, response: Response, current_user: Optional[TokenUser] = Depends(get_optional_user)
+):
+    """
+    Get a single artifact by ID. The ETag header is its current version, for
+    If-Match on PATCH. Artifacts the caller may not see are 404.
+    """
+    artifact = get_artifact_by_id(artifact_id, _viewer(current_user))
+    if not artifact:
+        raise NotFoundError("Artifact not found")
+    response.headers["ETag"] = _etag(artifact["version"])
+    return _artifact_detail(artifact)
 
 
-@app.post("/api/analyze")
-async def analyze_endpoint(req: AnalyzeRequest):
-    """Analyze an uploaded image and return analysis results."""
+@app.patch("/api/artifacts/{artifact_id}")
+async def patch_artifact_endpoint(
+    artifact_id: int, changes: ArtifactPatch, request: Request, response: Response, current_user: CurrentUser
+):
+    """
+    Change some of an artifact's fields: name, description, era, tags, notes,
+    collection_id and verification_status. Send the ETag from GET as If-Match
+    to fail with 412 instead of overwriting someone else's change. Only the
+    owner and archive staff may patch, and only staff may change verification.
+    """
+    expected_version = _if_match_version(request.headers.get("if-match"))
+    _editable_artifact(artifact_id, current_user)
+    fields = changes.model_dump(exclude_unset=True)
+    if not fields:
+        raise ValidationError("Send at least one field to change", status_code=422)
+    if "verification_status" in fields:
+        _require_staff(current_user)
+    if fields.get("collection_id") is not None:
+        collection = get_collection(fields["collection_id"], _viewer(current_user))
+        if not collection:
+            raise NotFoundError("Collection not found")
+        _require_owner(collection, current_user, "collection")
     try:
-        # Decode base64 image (support data URL or raw base64)
-        raw = req.image_data
-        if "," in raw:
-            raw = raw.split(",", 1)[1]
-        image_bytes = base64.b64decode(raw)
-        image = Image.open(BytesIO(image_bytes)).convert("RGB")
-
-        # Map tier from frontend format to FastAnalyzer tier format
-        tier_input = (req.tier or "fast").lower()
-        tier_map = {
-            "instant": "INSTANT",
-            "fast": "FAST",
-            "balanced": "BALANCED",
-            "thorough": "QUALITY",  # Frontend uses "thorough", FastAnalyzer uses "QUALITY"
-        }
-        tier = tier_map.get(tier_input, "FAST")
+        artifact = patch_artifact(artifact_id, fields, expected_version, current_user.username)
+    except StaleArtifactError:
+        raise PreconditionFailedError("Artifact was changed by someone else; reload it and try again")
+    except LockedArtifactError as e:
+        raise ArtifactLockedError(str(e), locked_by=e.locked_by)
+    if not artifact:
+        raise NotFoundError("Artifact not found")
+    response.headers["ETag"] = _etag(artifact["version"])
+    return _artifact_detail(artifact)
 
-        # Create analyzer with the specified tier
-        analyzer = FastAnalyzer(tier=tier)
 
-        # Run analysis (may raise RuntimeError from Ollama client)
-        result = analyzer.analyze_artifact(image)
+@app.delete("/api/artifacts/{artifact_id}")
+async def delete_artifact_endpoint(artifact_id: int, current_user: CurrentUser):
+    """
+    Move an artifact to the trash (owner or archive staff). It can be
+    restored with POST /api/artifacts/{id}/restore until it is purged.
+    """
+    _editable_artifact(artifact_id, current_user)
+    try:
+        deleted = delete_artifact(artifact_id, deleted_by=current_user.username)
+    except Exception as e:
+        logger.error(f"Error deleting artifact {artifact_id}: {str(e)}")
+        raise DatabaseError("Failed to delete artifact")
 
-        # Map tier back to frontend format for response
-        tier_reverse_map = {
-            "INSTANT": "instant",
-            "FAST": "fast",
-            "BALANCED": "balanced",
-            "QUALITY": "thorough",  # FastAnalyzer uses "QUALITY", frontend expects "thorough"
-        }
-        result_tier = result.get("tier", tier)
-        response_tier = tier_reverse_map.get(result_tier, tier_input)
-
-        # Normalize response shape similar to frontend expectations
-        response = {
-            "name": result.get("name", "Unknown"),
-            "description": result.get("description", ""),
-            "confidence": float(result.get("confidence", 0.0)),
-            "method": result.get("method", "Unknown"),
-            "tier": response_tier,
-            "analysis_time": result.get("analysis_time", "N/A"),
-        }
+    if not deleted:
+        raise NotFoundError("Artifact not found")
+    return {"id": artifact_id, "message": "Artifact moved to the trash"}
+
+
+HISTORY_PAGE_MAX = 200
+
+
+@app.get("/api/artifacts/{artifact_id}/history", response_model=List[ArtifactRevision])
+async def get_artifact_history(
+    artifact_id: int,
+    request: Request,
+    response: Response,
+    current_user: CurrentUser,
+    limit: int = Query(50, ge=1, le=HISTORY_PAGE_MAX),
+    offset: int = Query(0, ge=0),
+):
+    """
+    Every change to an artifact, newest first: who made it, when, and each
+    field's old and new value. Anyone who can see the artifact may read it.
+    The total is in X-Total-Count.
+    """
+    if not get_artifact_by_id(artifact_id, _viewer(current_user)):
+        raise NotFoundError("Artifact not found")
+    revisions, total = list_artifact_revisions(artifact_id, limit=limit, offset=offset)
+    _set_pagination_headers(request, response, limit, offset, total)
+    return revisions
+
+
+@app.post("/api/artifacts/{artifact_id}/history/{revision_id}/revert")
+async def revert_artifact_change(artifact_id: int, revision_id: int, response: Response, current_user: CurrentUser):
+    """
+    Undo one edit: the descriptive fields it changed go back to their
+    earlier values, recorded as a new "reverted" revision. Only "updated"
+    revisions can be reverted, not creation, ownership, sharing, the trash
+    or locks. Same rules as editing: owner or archive staff, and not while
+    locked; only staff may revert a verification or move into a collection
+    they don't own.
+    """
+    _editable_artifact(artifact_id, current_user)
+    revision = get_artifact_revision(artifact_id, revision_id)
+    if not revision:
+        raise NotFoundError("Revision not found")
+    changes = {change["field"]: change for change in revision["changes"] if change["field"] in REVERTIBLE_FIELDS}
+    if revision["action"] != "updated" or not changes:
+        raise ValidationError(f"A {revision['action']} revision can't be reverted")
+    if any(field.startswith(("verification_", "verified_")) for field in changes):
+        _require_staff(current_user)
+    if "collection_id" in changes and changes["collection_id"]["old"] is not None:
+        collection = get_collection(changes["collection_id"]["old"], _viewer(current_user))
+        if not collection:
+            raise NotFoundError("Collection not found")
+        _require_owner(collection, current_user, "collection")
+    reverted = revert_artifact_revision(artifact_id, revision_id, current_user.username)
+    if not reverted:
+        raise NotFoundError("Artifact not found")
+    log_action(current_user.username, f"Reverted revision {revision_id} of artifact {artifact_id}")
+    response.headers["ETag"] = _etag(reverted["version"])
+    return _artifact_detail(reverted)
+
+
+def _trashed_artifact(artifact_id: int, user: TokenUser) -> Dict[str, Any]:
+    """An artifact in the trash that the caller owns, or any for staff; 404 otherwise."""
+    artifact = get_deleted_artifact(artifact_id)
+    if not artifact or (artifact.get("owner_id") != user.username and not _viewer(user).sees_all):
+        raise NotFoundError("Artifact not in the trash")
+    return artifact
+
+
+@app.post("/api/artifacts/{artifact_id}/restore")
+def restore_artifact_endpoint(artifact_id: int, response: Response, current_user: CurrentUser):
+    """Take an artifact out of the trash (owner or archive staff)"""
+    _trashed_artifact(artifact_id, current_user)
+    artifact = restore_artifact(artifact_id, current_user.username)
+    if not artifact:
+        raise NotFoundError("Artifact not in the trash")
+    response.headers["ETag"] = _etag(artifact["version"])
+    return _artifact_detail(artifact)
+
+
+def _trash_item(a: dict) -> dict:
+    """A list item for an artifact in the trash, with when it was deleted, by whom and when it will be purged."""
+    purge_at = None
+    if TRASH_RETENTION_DAYS > 0:
+        deleted_at = datetime.fromisoformat(a["deleted_at"].rstrip("Z"))
+        purge_at = (deleted_at + timedelta(days=TRASH_RETENTION_DAYS)).isoformat() + "Z"
+    return {
+        **_artifact_list_item(a),
+        "deleted_at": a["deleted_at"],
+        "deleted_by": a.get("deleted_by"),
+        "purge_at": purge_at,
+    }
+
+
+@app.get("/api/trash")
+def list_trash(
+    request: Request,
+    response: Response,
+    current_user: CurrentUser,
+    limit: int = Query(100, ge=1, le=ARTIFACT_PAGE_MAX),
+    offset: int = Query(0, ge=0),
+):
+    """
+    The caller's artifacts in the trash (everyone's for archive staff), most
+    recently deleted first, with when each will be purged (null if never).
+    The total is in X-Total-Count.
+    """
+    artifacts, total = list_deleted_artifacts(_viewer(current_user), limit=limit, offset=offset)
+    _set_pagination_headers(request, response, limit, offset, total)
+    return [_trash_item(a) for a in artifacts]
+
+
+@app.delete("/api/trash/{artifact_id}")
+def purge_trash_item(artifact_id: int, current_user: CurrentUser):
+    """Delete an artifact in the trash for good (owner or archive staff); this can't be undone"""
+    _trashed_artifact(artifact_id, current_user)
+    if not purge_artifact(artifact_id):
+        raise NotFoundError("Artifact not in the trash")
+    return {"id": artifact_id, "message": "Artifact deleted permanently"}
+
+
+def _decode_image(data: str) -> bytes:
+    """Raw bytes of a base64 image (data URL or plain base64)."""
+    if "," in data:
+        data = data.split(",", 1)[1]
+    return base64.b64decode(data)
 
-        # Forward embedding if present
-        if "embedding" in result:
-            response["embedding"] = result.get("embedding")
 
-        return response
+def _normalized_image(image_bytes: bytes) -> Tuple[bytes, Dict[str, Any]]:
+    """The image as JPEG or PNG, and its original format (see image_formats.py); 400 if it can't be read."""
+    try:
+        return image_formats.normalize(image_bytes)
+    except image_formats.UnsupportedImageError as e:
+        raise ValidationError(str(e))
+
 
+@app.post("/api/analyze")
+async def analyze_endpoint(req: AnalyzeRequest, response: Response, current_user: CurrentUser):
+    """
+    Analyze an uploaded image. Inline tiers (see ANALYSIS_INLINE_TIERS)
+    and cached results (unless refresh is set) return the result; other
+    tiers answer 202 with a queued job to poll at GET /api/analyze/jobs/{id}.
+    Rate limited, and charged to the caller's monthly quota (see
+    analysis_quota.py and GET /api/usage); the X-Quota-* headers say what's left.
+    """
+    tier = analysis_jobs.normalize_tier(req.tier)
+    try:
+        image_bytes = _decode_image(req.image_data)
+    except ValueError:
+        raise ValidationError("image_data is not valid base64")
+    image_bytes, _ = await run_in_threadpool(_normalized_image, image_bytes)
+    response.headers.update(await run_in_threadpool(analysis_quota.admit, current_user, tier))
+
+    if not analysis_jobs.runs_inline(tier):
+        cached = None if req.refresh else await run_in_threadpool(analysis_jobs.cached_result, image_bytes, tier)
+        if cached:
+            return cached
+        job = analysis_jobs.submit(image_bytes, tier, requested_by=current_user.username)
+        response.status_code = 202
+        response.headers["Location"] = f"/api/analyze/jobs/{job['id']}"
+        return job
+
+    try:
+        return await run_in_threadpool(analysis_jobs.analyze, image_bytes, tier, req.refresh)
     except RuntimeError as e:
         # Surface runtime errors (e.g., Ollama generation failures)
         logger.error(f"Analysis runtime error: {str(e)}")
-        raise HTTPException(status_code=502, detail=str(e))
+        raise ExternalServiceError(str(e))
     except Exception as e:
         logger.exception("Unexpected error during analysis")
-        raise HTTPException(status_code=500, detail=str(e))
+        raise AppError("Analysis failed")
+
+
+@app.get("/api/usage", response_model=AnalysisUsageInfo)
+async def usage_endpoint(current_user: CurrentUser, username: Optional[str] = None):
+    """
+    The caller's analyses this month: units used of the monthly quota, by
+    tier, and the rate limit. Admins can pass another username.
+    """
+    if username is None or username == current_user.username:
+        return await run_in_threadpool(analysis_quota.usage, current_user.username, current_user.role)
+    if current_user.role != "admin":
+        raise PermissionDeniedError("Only admins can see other users' usage")
+    row = get_user_row(username)
+    if row is None:
+        raise NotFoundError("User not found")
+    return await run_in_threadpool(analysis_quota.usage, username, row[2])
+
+
+def _sees_analysis(requested_by: Optional[str], user: TokenUser) -> bool:
+    """Analyses are private to whoever asked for them; archive staff see all."""
+    return requested_by == user.username or _viewer(user).sees_all
+
+
+@app.get("/api/analyze/jobs/{job_id}")
+async def analysis_job_endpoint(job_id: str, current_user: CurrentUser):
+    """A queued analysis: status is queued, running, done (with result) or failed (with error)."""
+    job = analysis_jobs.get_job(job_id)
+    if not job or not _sees_analysis(job["requested_by"], current_user):
+        raise NotFoundError("Analysis job not found")
+    return job
+
 
+# How often an events stream checks on its job, and sends a comment to keep proxies from closing it
+JOB_EVENTS_POLL_SECONDS = 0.5
+JOB_EVENTS_KEEPALIVE_SECONDS = 15
 
-@app.post("/api/analyze/batch")
-async def batch_analyze_endpoint(req: BatchAnalyzeRequest):
-    """Analyze multiple uploaded images and return analysis results for each."""
+
+def _sse(event: str, data: Dict[str, Any]) -> str:
+    return f"event: {event}\ndata: {json.dumps(data)}\n\n"
+
+
+@app.get("/api/analyze/jobs/{job_id}/events")
+async def analysis_job_events_endpoint(job_id: str, current_user: CurrentUser):
+    """
+    Stream a queued analysis's progress as server-sent events: a ``stage``
+    event (received, preprocessing, inference, saving) whenever it moves on,
+    then ``done`` or ``failed`` with the whole job, after which the stream ends.
+    """
+    job = await run_in_threadpool(analysis_jobs.get_job, job_id)
+    if not job or not _sees_analysis(job["requested_by"], current_user):
+        raise NotFoundError("Analysis job not found")
+
+    async def events():
+        current, stage, quiet = job, None, 0.0
+        yield "retry: 2000\n\n"
+        while True:
+            if current["stage"] != stage:
+                stage = current["stage"]
+                yield _sse("stage", {"id": job_id, "status": current["status"], "stage": stage})
+                quiet = 0.0
+            if current["status"] in ("done", "failed"):
+                yield _sse(current["status"], current)
+                return
+            await asyncio.sleep(JOB_EVENTS_POLL_SECONDS)
+            quiet += JOB_EVENTS_POLL_SECONDS
+            if quiet >= JOB_EVENTS_KEEPALIVE_SECONDS:
+                yield ": keep-alive\n\n"
+                quiet = 0.0
+            current = await run_in_threadpool(analysis_jobs.get_job, job_id)
+            if not current:
+                # Removed by the retention cleanup of another process
+                yield _sse("failed", {"id": job_id, "status": "failed", "error": "Analysis job not found"})
+                return
+
+    return StreamingResponse(
+        events(),
+        media_type="text/event-stream",
+        headers={"Cache-Control": "no-cache", "X-Accel-Buffering": "no"},
+    )
+
+
+def _readable_image(image_bytes: bytes) -> Tuple[bytes, Optional[str]]:
+    """The image as JPEG or PNG and None, or the bytes as they were and why they can't be analysed."""
+    if not image_bytes:
+        return image_bytes, "Empty file"
     try:
-        results = []
-
-        # Map tier from frontend format to FastAnalyzer tier format
-        tier_input = (req.tier or "fast").lower()
-        tier_map = {
-            "instant": "INSTANT",
-            "fast": "FAST",
-            "balanced": "BALANCED",
-            "thorough": "QUALITY",
-        }
-        tier = tier_map.get(tier_input, "FAST")
-        analyzer = FastAnalyzer(tier=tier)
+        return image_formats.normalize(image_bytes)[0], None
+    except image_formats.UnsupportedImageError as e:
+        return image_bytes, str(e)
 
-        for img_data in req.images:
-            try:
-                # Decode base64 image
-                raw = img_data
-                if "," in raw:
-                    raw = raw.split(",", 1)[1]
-                image_bytes = base64.b64decode(raw)
-                image = Image.open(BytesIO(image_bytes)).convert("RGB")
-
-                # Run analysis
-                result = analyzer.analyze_artifact(image)
-
-                # Map tier back to frontend format
-                tier_reverse_map = {
-                    "INSTANT": "instant",
-                    "FAST": "fast",
-                    "BALANCED": "balanced",
-                    "QUALITY": "thorough",
-                }
-                result_tier = result.get("tier", tier)
-                response_tier = tier_reverse_map.get(result_tier, tier_input)
-
-                results.append(
-                    {
-                        "name": result.get("name", "Unknown"),
-                        "description": result.get("description", ""),
-                        "confidence": float(result.get("confidence", 0.0)),
-                        "method": result.get("method", "Unknown"),
-                        "tier": response_tier,
-                        "analysis_time": result.get("analysis_time", "N/A"),
-                    }
-                )
-            except Exception as e:
-                logger.error(f"Error processing image in batch: {str(e)}")
-                results.append(
-                    {
-                        "error": str(e),
-                        "name": "Error",
-                        "description": f"Failed to analyze: {str(e)}",
-                        "confidence": 0.0,
-                    }
-                )
 
-        return {"results": results}
+def _form_flag(value: Any) -> bool:
+    return str(value or "").strip().lower() in ("1", "true", "yes", "on")
 
-    except Exception as e:
-        logger.exception("Unexpected error during batch analysis")
-        raise HTTPException(status_code=500, detail=str(e))
 
+@app.post("/api/analyze/batch", status_code=202)
+async def batch_analyze_endpoint(request: Request, response: Response, current_user: CurrentUser):
+    """
+    Queue an analysis per image and answer 202 with the batch, whose items
+    carry a status each; poll GET /api/analyze/batch/{id} until it is done.
 
-@app.post("/api/similarity-search")
-async def similarity_search_endpoint(req: SimilaritySearchRequest):
-    """Find similar artifacts using embedding similarity search."""
+    Send multipart/form-data with the images as ``files`` (plus optional
+    ``tier`` and ``refresh`` fields), or JSON with base64 ``images``. Images
+    that can't be read fail on their own without failing the batch; the
+    others are charged to the monthly quota together, or refused together.
+    """
+    images = []
+    if request.headers.get("content-type", "").startswith("multipart/form-data"):
+        form = await request.form(max_files=ANALYSIS_BATCH_MAX_IMAGES)
+        tier, refresh = form.get("tier"), _form_flag(form.get("refresh"))
+        for upload in form.getlist("files"):
+            if isinstance(upload, str):
+                raise ValidationError("files must be uploaded files")
+            images.append((upload.filename, await upload.read()))
+    else:
+        try:
+            req = BatchAnalyzeRequest.model_validate(await request.json())
+        except ValueError:
+            raise ValidationError("Send images as multipart files, or JSON with base64 images")
+        tier, refresh = req.tier, req.refresh
+        for image_data in req.images:
+            try:
+                images.append((None, _decode_image(image_data)))
+            except ValueError:
+                images.append((None, b""))
+
+    if not images:
+        raise ValidationError("No images to analyze")
+    if len(images) > ANALYSIS_BATCH_MAX_IMAGES:
+        raise ValidationError(f"At most {ANALYSIS_BATCH_MAX_IMAGES} images per batch")
+
+    items = [(filename, *_readable_image(data)) for filename, data in images]
+    tier = analysis_jobs.normalize_tier(tier)
+    readable = sum(problem is None for _, _, problem in items)
+    if readable:
+        # One request, but every readable image counts towards the quota
+        response.headers.update(await run_in_threadpool(analysis_quota.admit, current_user, tier, readable))
+    batch = await run_in_threadpool(
+        analysis_jobs.submit_batch, items, tier, refresh, current_user.username
+    )
+    response.headers["Location"] = f"/api/analyze/batch/{batch['id']}"
+    return batch
+
+
+@app.get("/api/analyze/batch/{batch_id}")
+async def analysis_batch_endpoint(batch_id: str, current_user: CurrentUser):
+    """A batch and each image's status (queued, running, done with result, or failed with error)."""
+    batch = analysis_jobs.get_batch(batch_id)
+    if not batch or not _sees_analysis(batch["requested_by"], current_user):
+        raise NotFoundError("Analysis batch not found")
+    return batch
+
+
+def _own_webhook(webhook_id: int, user: TokenUser) -> Dict[str, Any]:
+    """The webhook, if it is the caller's (admins may manage any)."""
+    webhook = get_webhook(webhook_id)
+    if not webhook or (webhook["owner_id"] != user.username and user.role != "admin"):
+        raise NotFoundError("Webhook not found")
+    return webhook
+
+
+@app.post("/api/webhooks", response_model=WebhookCreated, status_code=201)
+async def create_webhook_endpoint(req: WebhookCreate, current_user: CurrentUser):
+    """
+    Register a URL to be POSTed when one of the caller's queued analyses
+    finishes or fails. The secret that signs deliveries is only returned here.
+    """
+    url = req.url.strip()
+    parsed = urlparse(url)
+    if parsed.scheme not in ("http", "https") or not parsed.netloc:
+        raise ValidationError("Webhook URL must be an http or https URL")
     try:
-        from ai_analyzer import AIAnalyzer
+        # Deliveries check again each time; this catches a wrong URL straight away
+        await run_in_threadpool(webhooks.check_receiver, url)
+    except webhooks.ReceiverNotAllowed as e:
+        raise ValidationError(str(e))
+    if not req.events:
+        raise ValidationError("Choose at least one event")
+    if len(list_webhooks(current_user.username)) >= WEBHOOK_MAX_PER_USER:
+        raise ValidationError(f"At most {WEBHOOK_MAX_PER_USER} webhooks per user")
+    return create_webhook(current_user.username, url, sorted(set(req.events)))
 
-        limit_val = req.limit or 10
 
-        # Decode query image
-        raw = req.image_data
-        if "," in raw:
-            raw = raw.split(",", 1)[1]
-        image_bytes = base64.b64decode(raw)
-        image = Image.open(BytesIO(image_bytes)).convert("RGB")
+@app.get("/api/webhooks", response_model=List[WebhookInfo])
+async def list_webhooks_endpoint(current_user: CurrentUser, all_users: bool = False):
+    """The caller's webhooks and how their last delivery went (admins can pass all_users=true)."""
+    if all_users and current_user.role != "admin":
+        raise PermissionDeniedError("Only admins can list everyone's webhooks")
+    return list_webhooks(None if all_users else current_user.username)
 
-        # Get embedding for query image
-        analyzer = AIAnalyzer()
-        query_result = analyzer.analyze_image(image, model_choice="clip")
 
-        if "embedding" not in query_result:
-            raise HTTPException(
-                status_code=400, detail="Could not generate embedding for query image"
-            )
+@app.delete("/api/webhooks/{webhook_id}", response_model=MessageResponse)
+async def delete_webhook_endpoint(webhook_id: int, current_user: CurrentUser):
+    _own_webhook(webhook_id, current_user)
+    delete_webhook(webhook_id)
+    return {"message": "Webhook deleted"}
 
-        query_embedding = np.array(query_result["embedding"], dtype=np.float32)
-
-        # Get all artifacts with embeddings
-        all_artifacts = get_all_artifacts(limit=1000, include_images=False)
-
-        # Filter artifacts that have embeddings
-        artifacts_with_embeddings = []
-        for artifact in all_artifacts:
-            # Check if artifact has embedding stored
-            # Note: In the current implementation, embeddings are not stored in DB,
-            # so this is a placeholder. In production, you'd store embeddings in the DB.
-            # For now, we'll use the name/description similarity as a fallback
-            artifacts_with_embeddings.append(artifact)
-
-        # If no embeddings stored, use text-based similarity as fallback
-        if not artifacts_with_embeddings:
-            # Fallback to text search based on any available text fields
-            search_results = search_artifacts("")
-            return [
-                {
-                    "id": a["id"],
-                    "name": a["name"],
-                    "description": a.get("description"),
-                    "similarity_score": 0.5,  # Placeholder
-                    "thumbnail": f"data:image/png;base64,{a['thumbnail']}"
-                    if a.get("thumbnail")
-                    else None,
-                }
-                for a in search_results[:limit_val]
-            ]
-
-        # Perform similarity search using AIAnalyzer's similarity_search method
-        search_result = analyzer.similarity_search(
-            query_embedding, artifacts_with_embeddings
-        )
 
-        # Format results to match the old Streamlit version
-        results = []
-
-        # Add closest match if available
-        if "closest_match" in search_result and "similarity_score" in search_result:
-            # Find the artifact in our list
-            for artifact in artifacts_with_embeddings:
-                if artifact.get("name") == search_result["closest_match"]:
-                    results.append(
-                        {
-                            "id": artifact.get("id"),
-                            "name": artifact.get("name", "Unknown"),
-                            "description": artifact.get("description"),
-                            "similarity_score": search_result["similarity_score"],
-                            "thumbnail": f"data:image/png;base64,{artifact['thumbnail']}"
-                            if artifact.get("thumbnail")
-                            else None,
-                        }
-                    )
-                    break
-
-        # Add alternative matches
-        if "alternative_matches" in search_result:
-            for match in search_result["alternative_matches"][: limit_val - 1]:
-                artifact_data = match.get("artifact", {})
-                results.append(
-                    {
-                        "id": artifact_data.get("id"),
-                        "name": artifact_data.get("name", "Unknown"),
-                        "description": artifact_data.get("description"),
-                        "similarity_score": match.get("score", 0.0),
-                        "thumbnail": f"data:image/png;base64,{artifact_data['thumbnail']}"
-                        if artifact_data.get("thumbnail")
-                        else None,
-                    }
-                )
+@app.post("/api/webhooks/{webhook_id}/ping", response_model=MessageResponse, status_code=202)
+async def ping_webhook_endpoint(webhook_id: int, current_user: CurrentUser):
+    """Queue a signed test delivery (event "ping") to check the receiver."""
+    webhook = _own_webhook(webhook_id, current_user)
+    webhooks.ping(webhook["owner_id"], webhook_id)
+    return {"message": "Test delivery queued"}
 
-        return results[:limit_val]
 
-    except Exception as e:
-        logger.exception("Unexpected error during similarity search")
-        raise HTTPException(status_code=500, detail=str(e))
+def _similar_results(matches) -> List[Dict[str, Any]]:
+    return [
+        {
+            "id": artifact["id"],
+            "name": artifact.get("name") or "Unknown",
+            "description": artifact.get("description"),
+            "similarity_score": round(score, 4),
+            "thumbnail_url": _image_url(artifact.get("thumbnail_hash") or artifact.get("image_hash")),
+        }
+        for artifact, score in matches
+    ]
+
+
+@app.post("/api/similarity-search", response_model=List[SimilarArtifact])
+async def similarity_search_endpoint(
+    req: SimilaritySearchRequest, current_user: Optional[TokenUser] = Depends(get_optional_user)
+):
+    """Artifacts whose images look most like the uploaded one (see image_embeddings.py)."""
+    try:
+        image_bytes = _decode_image(req.image_data)
+    except ValueError:
+        raise ValidationError("image_data is not valid base64")
+    image_bytes, _ = await run_in_threadpool(_normalized_image, image_bytes)
+    try:
+        query = await run_in_threadpool(image_embeddings.embed, image_bytes)
+    except Exception:
+        raise ValidationError("image_data is not a valid base64 image")
+    limit = min(max(req.limit or 10, 1), 100)
+    matches = await run_in_threadpool(image_embeddings.similar_artifacts, query, _viewer(current_user), limit)
+    return _similar_results(matches)
+
+
+@app.get("/api/artifacts/{artifact_id}/similar", response_model=List[SimilarArtifact])
+async def similar_artifacts_endpoint(
+    artifact_id: int,
+    limit: int = Query(10, ge=1, le=100),
+    current_user: Optional[TokenUser] = Depends(get_optional_user),
+):
+    """The artifacts the caller can see whose images look most like this one's, most similar first."""
+    viewer = _viewer(current_user)
+    if not get_artifact_by_id(artifact_id, viewer):
+        raise NotFoundError("Artifact not found")
+    matches = await run_in_threadpool(image_embeddings.similar_to_artifact, artifact_id, viewer, limit)
+    return _similar_results(matches or [])
+
+
+@app.get("/api/export")
+async def export_artifacts_endpoint(
+    request: Request,
+    current_user: CurrentUser,
+    scope: Optional[ShareScope] = None,
+    collection_id: Optional[int] = None,
+    uploaded_by: Optional[str] = None,
+    era: Optional[str] = None,
+    tier: Optional[str] = None,
+    tag: Optional[str] = None,
+    min_confidence: Optional[float] = Query(None, ge=0, le=1),
+    verified: Optional[bool] = None,
+    bbox: Optional[str] = None,
+    format: ExportFormat = "bundle",
+):
+    """
+    Download the artifacts matching GET /api/artifacts's filters. By default
+    as a ZIP bundle (JSON manifest, images and 3D models) that POST
+    /api/import on this or another backend can ingest; see archive_bundle.py.
+    format=dublin-core or format=lido gives catalogue metadata as XML instead,
+    for ingest by museum collection systems; see metadata_export.py.
+    """
+    viewer = _viewer(current_user)
+    filters = {
+        "viewer": viewer,
+        "scope": _default_scope(current_user, scope),
+        "collection_id": collection_id,
+        "uploaded_by": uploaded_by,
+        "era": era,
+        "tier": tier,
+        "tags": tag,
+        "min_confidence": min_confidence,
+        "verified": verified,
+        "bbox": _bbox(bbox),
+    }
+    artifacts = await run_in_threadpool(get_all_artifacts, limit=None, sort="uploaded_at", order="asc", **filters)
+    if format != "bundle":
+        content = await run_in_threadpool(metadata_export.export, format, artifacts, viewer, str(request.base_url))
+        return Response(
+            content,
+            media_type="application/xml",
+            headers={"Content-Disposition": f'attachment; filename="{metadata_export.filename(format)}"'},
+        )
+    path = await run_in_threadpool(archive_bundle.write_bundle, artifacts, viewer, str(request.base_url))
+    return FileResponse(
+        path,
+        media_type="application/zip",
+        filename=f"artifacts-{datetime.utcnow():%Y%m%d-%H%M%S}.zip",
+        background=BackgroundTask(os.remove, path),
+    )
+
+
+@app.post("/api/import", response_model=ImportResult)
+async def import_artifacts_endpoint(
+    request: Request, current_user: CurrentUser, on_conflict: ImportConflict = "skip"
+):
+    """
+    Import a bundle from GET /api/export, uploaded as the multipart field
+    "file". Artifacts whose image the caller can already see are skipped,
+    replace the existing artifact's details (on_conflict=replace, your own
+    artifacts only unless you're staff) or are imported again
+    (on_conflict=duplicate). Everything imported belongs to the caller.
+    """
+    if not request.headers.get("content-type", "").startswith("multipart/form-data"):
+        raise ValidationError("Upload the bundle as the multipart field \"file\"")
+    form = await request.form(max_files=1)
+    upload = form.get("file")
+    if upload is None or isinstance(upload, str):
+        raise ValidationError("Upload the bundle as the multipart field \"file\"")
+    if (upload.size or 0) > IMPORT_MAX_MB * 1024 * 1024:
+        raise ValidationError(f"Bundles may be at most {IMPORT_MAX_MB} MB")
+    try:
+        return await run_in_threadpool(archive_bundle.import_bundle, upload.file, _viewer(current_user), on_conflict)
+    except archive_bundle.BundleError as e:
+        raise ValidationError(str(e))
 
 
 @app.patch("/api/artifacts/{artifact_id}/verification")
-async def update_artifact_verification(artifact_id: int, verification_status: str):
+async def update_artifact_verification(artifact_id: int, verification_status: str, current_user: CurrentUser):
     """Update verification status of an artifact (deprecated - use POST /verify instead)."""
+    _require_staff(current_user)
     try:
         from database import update_artifact_verification
 
-        result = update_artifact_verification(artifact_id, verification_status)
+        result = update_artifact_verification(artifact_id, verification_status, changed_by=current_user.username)
         if not result:
-            raise HTTPException(status_code=404, detail="Artifact not found")
+            raise NotFoundError("Artifact not found")
         return result
+    except HTTPException:
+        raise
     except Exception as e:
         logger.error(f"Error updating verification status: {str(e)}")
-        raise HTTPException(status_code=400, detail=str(e))
+        raise DatabaseError("Failed to update verification status")
 
 
 @app.post("/api/artifacts/{artifact_id}/verify")
-async def verify_artifact_with_reason(artifact_id: int, request: VerificationRequest):
+async def verify_artifact_with_reason(artifact_id: int, request: VerificationRequest, current_user: CurrentUser):
     """
-    Verify or reject an artifact with a required reason.
+    Verify or reject an artifact with a required reason (archive staff only).
     Sends email notification to the uploader.
     """
+    _require_staff(current_user)
     try:
         from database import update_artifact_verification, get_artifact_by_id
         from login import get_user_info
@@ -677,12 +2509,12 @@ async def verify_artifact_with_reason(artifact_id: int, request: VerificationReq
 
         # Validate reason is provided
         if not request.reason or not request.reason.strip():
-            raise HTTPException(status_code=400, detail="Reason is required for verification/rejection")
+            raise ValidationError("Reason is required for verification/rejection")
 
         # Get artifact details before update
         artifact = get_artifact_by_id(artifact_id)
         if not artifact:
-            raise HTTPException(status_code=404, detail="Artifact not found")
+            raise NotFoundError("Artifact not found")
 
         artifact_name = artifact.get("name", "Unknown Artifact")
         uploaded_by = artifact.get("uploaded_by")
@@ -691,20 +2523,20 @@ async def verify_artifact_with_reason(artifact_id: int, request: VerificationReq
         if request.verification_status.lower() == "rejected":
             # Delete the artifact for rejections
             from database import delete_artifact
-            deleted = delete_artifact(artifact_id)
+            deleted = delete_artifact(artifact_id, deleted_by=current_user.username)
             if not deleted:
-                raise HTTPException(status_code=404, detail="Artifact not found")
-            result = {"id": artifact_id, "verification_status": "rejected", "message": "Artifact rejected and deleted"}
+                raise NotFoundError("Artifact not found")
+            result = {"id": artifact_id, "verification_status": "rejected", "message": "Artifact rejected and moved to the trash"}
         else:
             # Approve the artifact
             result = update_artifact_verification(
                 artifact_id,
                 request.verification_status,
-                verified_by=request.verified_by,
+                verified_by=current_user.username,
                 comments=request.reason
             )
             if not result:
-                raise HTTPException(status_code=404, detail="Artifact not found")
+                raise NotFoundError("Artifact not found")
 
         # Send email notification to uploader
         email_sent = False
@@ -719,7 +2551,7 @@ async def verify_artifact_with_reason(artifact_id: int, request: VerificationReq
                             artifact_name=artifact_name,
                             status=request.verification_status,
                             reason=request.reason,
-                            verified_by=request.verified_by
+                            verified_by=current_user.username
                         )
             except Exception as email_error:
                 logger.warning(f"Could not send email notification: {email_error}")
@@ -731,36 +2563,174 @@ async def verify_artifact_with_reason(artifact_id: int, request: VerificationReq
         raise
     except Exception as e:
         logger.error(f"Error verifying artifact: {str(e)}")
-        raise HTTPException(status_code=400, detail=str(e))
+        raise DatabaseError("Failed to verify artifact")
+
+
+# Moderation: archive staff (curators) flag artifacts for review, work
+# through the queue of flagged ones, correct identifications and lock
+# artifacts against edits. Every action goes to the audit log.
+MODERATION_PAGE_MAX = 200
+
+
+def _moderated_artifact(artifact_id: int, user: TokenUser) -> Dict[str, Any]:
+    _require_staff(user, "moderate artifacts")
+    artifact = get_artifact_by_id(artifact_id)
+    if not artifact:
+        raise NotFoundError("Artifact not found")
+    return artifact
+
+
+@app.post("/api/artifacts/{artifact_id}/flags", response_model=ArtifactFlag, status_code=201)
+def flag_artifact(artifact_id: int, flag: FlagCreate, current_user: CurrentUser):
+    """Put an artifact in the moderation queue with a reason (archive staff only)"""
+    _moderated_artifact(artifact_id, current_user)
+    created = create_flag(artifact_id, flag.reason.strip(), current_user.username)
+    log_action(current_user.username, f"Flagged artifact {artifact_id}: {created['reason']}")
+    return created
+
+
+@app.get("/api/artifacts/{artifact_id}/flags", response_model=List[ArtifactFlag])
+def list_artifact_flags(artifact_id: int, current_user: CurrentUser):
+    """An artifact's flags, open and resolved, newest first (archive staff only)"""
+    _moderated_artifact(artifact_id, current_user)
+    return list_flags(artifact_id)
+
+
+@app.post("/api/moderation/flags/{flag_id}/resolve", response_model=ArtifactFlag)
+def resolve_artifact_flag(flag_id: int, body: FlagResolution, current_user: CurrentUser):
+    """Close a flag, saying what was done; the artifact leaves the queue with its last open flag"""
+    _require_staff(current_user, "moderate artifacts")
+    flag = get_flag(flag_id)
+    if not flag:
+        raise NotFoundError("Flag not found")
+    resolved = resolve_flag(flag_id, current_user.username, body.resolution.strip())
+    if not resolved:
+        raise ConflictError(f"Flag was already resolved by {flag['resolved_by']}")
+    log_action(
+        current_user.username, f"Resolved flag {flag_id} on artifact {flag['artifact_id']}: {resolved['resolution']}"
+    )
+    return resolved
+
+
+@app.get("/api/moderation/queue")
+def get_moderation_queue(
+    request: Request,
+    response: Response,
+    current_user: CurrentUser,
+    limit: int = Query(50, ge=1, le=MODERATION_PAGE_MAX),
+    offset: int = Query(0, ge=0),
+):
+    """
+    Artifacts with open flags, the one flagged longest ago first, each with
+    its open flags (archive staff only). The total is in X-Total-Count.
+    """
+    _require_staff(current_user, "moderate artifacts")
+    artifacts, total = moderation_queue(limit=limit, offset=offset)
+    _set_pagination_headers(request, response, limit, offset, total)
+    return [
+        {
+            **_artifact_list_item(a),
+            "owner_id": a.get("owner_id"),
+            "locked_by": a.get("locked_by"),
+            "identification_overridden_by": a.get("identification_overridden_by"),
+            "open_flags": a["open_flags"],
+        }
+        for a in artifacts
+    ]
+
+
+@app.post("/api/artifacts/{artifact_id}/identification")
+def override_artifact_identification(
+    artifact_id: int, override: IdentificationOverride, response: Response, current_user: CurrentUser
+):
+    """
+    Replace the analysis' identification with a curator's (archive staff
+    only): the name, and any of description, era, cultural_context, material
+    and function that are sent. Works on locked artifacts; the previous name
+    and the reason go to the audit log.
+    """
+    artifact = _moderated_artifact(artifact_id, current_user)
+    fields = override.model_dump(exclude_unset=True, exclude={"reason"})
+    if "era" in fields:
+        fields["age"] = fields.pop("era")
+    updated = override_identification(artifact_id, fields, current_user.username)
+    if not updated:
+        raise NotFoundError("Artifact not found")
+    log_action(
+        current_user.username,
+        f"Overrode identification of artifact {artifact_id} ({artifact['name']} -> {updated['name']}): "
+        f"{override.reason.strip()}",
+    )
+    response.headers["ETag"] = _etag(updated["version"])
+    return _artifact_detail(updated)
+
+
+@app.post("/api/artifacts/{artifact_id}/lock")
+def lock_artifact(artifact_id: int, lock: LockRequest, response: Response, current_user: CurrentUser):
+    """
+    Lock an artifact against edits, sharing changes and deletion by anyone,
+    its owner and staff included, until it is unlocked (archive staff only).
+    Changes are refused with 423 and code artifact_locked.
+    """
+    artifact = _moderated_artifact(artifact_id, current_user)
+    if artifact.get("locked_at"):
+        raise ConflictError(f"Artifact is already locked by {artifact['locked_by']}")
+    locked = set_artifact_lock(artifact_id, current_user.username, lock.reason.strip())
+    if not locked:
+        raise NotFoundError("Artifact not found")
+    log_action(current_user.username, f"Locked artifact {artifact_id}: {locked['lock_reason']}")
+    response.headers["ETag"] = _etag(locked["version"])
+    return _artifact_detail(locked)
+
+
+@app.delete("/api/artifacts/{artifact_id}/lock")
+def unlock_artifact(artifact_id: int, response: Response, current_user: CurrentUser):
+    """Let an artifact be edited again (archive staff only)"""
+    artifact = _moderated_artifact(artifact_id, current_user)
+    if not artifact.get("locked_at"):
+        raise ConflictError("Artifact is not locked")
+    unlocked = set_artifact_lock(artifact_id, None, changed_by=current_user.username)
+    if not unlocked:
+        raise NotFoundError("Artifact not found")
+    log_action(current_user.username, f"Unlocked artifact {artifact_id}")
+    response.headers["ETag"] = _etag(unlocked["version"])
+    return _artifact_detail(unlocked)
 
 
 @app.put("/api/artifacts/{artifact_id}")
-async def update_artifact_endpoint(artifact_id: int, update_data: dict):
+async def update_artifact_endpoint(artifact_id: int, update: ArtifactUpdate, current_user: CurrentUser):
     """Update or manage an artifact.
 
     Supported:
     - name, description, tags: update basic fields
     - form_data: update physical measurements and metadata
     - verification_status='verified': mark as verified
-    - verification_status='rejected': delete the artifact
+    - verification_status='rejected': move the artifact to the trash
+
+    Only the owner and archive staff may update, and only staff may verify or reject.
     """
+    _editable_artifact(artifact_id, current_user)
+    if update.verification_status:
+        _require_staff(current_user)
     try:
         import json
 
+        # Only the fields the client actually sent
+        update_data = update.model_dump(exclude_unset=True)
         logger.info(f"Received update data for artifact {artifact_id}: {update_data}")
 
         # Handle rejection/deletion first
-        verification_status = update_data.get("verification_status", "")
+        verification_status = update_data.get("verification_status") or ""
         if verification_status.lower() == "rejected":
-            deleted = delete_artifact(artifact_id)
+            deleted = delete_artifact(artifact_id, deleted_by=current_user.username)
             if not deleted:
-                raise HTTPException(status_code=404, detail="Artifact not found")
-            return {"id": artifact_id, "message": "Artifact deleted"}
+                raise NotFoundError("Artifact not found")
+            return {"id": artifact_id, "message": "Artifact moved to the trash"}
 
         # Check if artifact exists
         artifact = get_artifact_by_id(artifact_id)
         if not artifact:
-            raise HTTPException(status_code=404, detail="Artifact not found")
+            raise NotFoundError("Artifact not found")
 
         # Build update data from raw dict
         db_update_data = {}
@@ -787,7 +2757,7 @@ async def update_artifact_endpoint(artifact_id: int, update_data: dict):
                     existing_form_data = artifact["form_data"]
 
             # Update with new values
-            existing_form_data.update(update_data["form_data"])
+            existing_form_data.update(update_data["form_data"] or {})
             db_update_data["form_data"] = json.dumps(existing_form_data)
 
         if verification_status.lower() == "verified":
@@ -795,9 +2765,9 @@ async def update_artifact_endpoint(artifact_id: int, update_data: dict):
 
         # Update artifact in database
         if db_update_data:
-            result = update_artifact(artifact_id, db_update_data)
+            result = update_artifact(artifact_id, db_update_data, current_user.username)
             if not result:
-                raise HTTPException(status_code=500, detail="Failed to update artifact")
+                raise DatabaseError("Failed to update artifact")
             return {
                 "id": artifact_id,
                 "message": "Artifact updated successfully",
@@ -808,11 +2778,164 @@ async def update_artifact_endpoint(artifact_id: int, update_data: dict):
     except HTTPException:
         raise
     except Exception as e:
-        logger.error(f"Unexpected error updating artifact {artifact_id}: {str(e)}")
-        raise HTTPException(status_code=500, detail=str(e))
+        logger.exception(f"Unexpected error updating artifact {artifact_id}")
+        raise AppError("Failed to update artifact")
+
+
+@app.get("/api/artifacts/{artifact_id}/sharing", response_model=Sharing)
+def get_artifact_sharing(artifact_id: int, current_user: CurrentUser):
+    """Who may see an artifact besides its owner and archive staff (owner or staff only)"""
+    _editable_artifact(artifact_id, current_user, check_lock=False)
+    return get_sharing("artifact", artifact_id)
+
+
+@app.put("/api/artifacts/{artifact_id}/sharing", response_model=Sharing)
+def set_artifact_sharing(artifact_id: int, sharing: Sharing, current_user: CurrentUser):
+    """
+    Replace who may see an artifact: private (owner and shared_with only),
+    team (also members of team_id) or public (everyone)
+    """
+    _editable_artifact(artifact_id, current_user)
+    _check_sharing(sharing.visibility, sharing.team_id, sharing.shared_with, current_user)
+    return set_sharing(
+        "artifact", artifact_id, sharing.visibility, sharing.team_id, sharing.shared_with, current_user.username
+    )
+
+
+def _editable_collection(collection_id: int, user: TokenUser) -> Dict[str, Any]:
+    collection = get_collection(collection_id, _viewer(user))
+    if not collection:
+        raise NotFoundError("Collection not found")
+    _require_owner(collection, user, "collection")
+    return collection
+
+
+@app.get("/api/collections", response_model=List[CollectionInfo])
+def list_collections_endpoint(
+    scope: ShareScope = "all", current_user: Optional[TokenUser] = Depends(get_optional_user)
+):
+    """Collections the caller can see: their own, shared with them, their teams' and public ones"""
+    return list_collections(_viewer(current_user), scope)
+
+
+@app.post("/api/collections", response_model=CollectionInfo, status_code=201)
+def create_collection_endpoint(collection: CollectionCreate, current_user: CurrentUser):
+    """Create a collection owned by the caller; add artifacts by patching their collection_id"""
+    _check_sharing(collection.visibility, collection.team_id, [], current_user)
+    return create_collection(
+        collection.name.strip(),
+        current_user.username,
+        description=collection.description,
+        visibility=collection.visibility,
+        team_id=collection.team_id,
+    )
+
+
+@app.get("/api/collections/{collection_id}", response_model=CollectionInfo)
+def get_collection_endpoint(
+    collection_id: int, current_user: Optional[TokenUser] = Depends(get_optional_user)
+):
+    """A collection; list its artifacts with GET /api/artifacts?collection_id=...&scope=all"""
+    collection = get_collection(collection_id, _viewer(current_user))
+    if not collection:
+        raise NotFoundError("Collection not found")
+    return collection
+
+
+@app.delete("/api/collections/{collection_id}", response_model=MessageResponse)
+def delete_collection_endpoint(collection_id: int, current_user: CurrentUser):
+    """Delete a collection (owner or staff); its artifacts are kept"""
+    _editable_collection(collection_id, current_user)
+    delete_collection(collection_id, current_user.username)
+    return {"message": "Collection deleted"}
+
+
+@app.get("/api/collections/{collection_id}/sharing", response_model=Sharing)
+def get_collection_sharing(collection_id: int, current_user: CurrentUser):
+    _editable_collection(collection_id, current_user)
+    return get_sharing("collection", collection_id)
+
+
+@app.put("/api/collections/{collection_id}/sharing", response_model=Sharing)
+def set_collection_sharing(collection_id: int, sharing: Sharing, current_user: CurrentUser):
+    """Replace who may see a collection, and with it every artifact in it"""
+    _editable_collection(collection_id, current_user)
+    _check_sharing(sharing.visibility, sharing.team_id, sharing.shared_with, current_user)
+    return set_sharing(
+        "collection", collection_id, sharing.visibility, sharing.team_id, sharing.shared_with, current_user.username
+    )
+
+
+# Presence: who is online, and forced sign-outs (see presence.py). Declared on
+# the app because the legacy router copies' dependencies expect HTTP requests.
+WS_AUTH_TIMEOUT_SECONDS = 10
+
+
+async def _websocket_user(websocket: WebSocket) -> Optional[TokenUser]:
+    """
+    The socket's user, from the session cookie or else a first message of
+    {"type": "auth", "token": "<access token>"}; browsers can't set headers
+    on WebSockets. None if neither checks out.
+    """
+    session_id = websocket.cookies.get(SESSION_COOKIE_NAME)
+    if session_id:
+        # Cookies go along on cross-site sockets too and CORS doesn't apply,
+        # so only trust them from the frontend's own origins
+        if websocket.headers.get("origin") not in CORS_ORIGINS:
+            return None
+        session = await run_in_threadpool(get_browser_session, session_id)
+        return TokenUser(username=session[0], role=session[1]) if session else None
+    try:
+        message = await asyncio.wait_for(websocket.receive_json(), WS_AUTH_TIMEOUT_SECONDS)
+        user = decode_access_token(message.get("token") or "")
+    except (asyncio.TimeoutError, AuthenticationError, AttributeError, KeyError, ValueError, WebSocketDisconnect):
+        return None
+    row = await run_in_threadpool(get_user_row, user.username)
+    return user if row and row[5] else None
+
+
+@app.websocket(API_V1_PREFIX + "/ws/presence")
+async def presence_socket(websocket: WebSocket):
+    """Keep the online-users list current and deliver forced sign-outs"""
+    await websocket.accept()
+    user = await _websocket_user(websocket)
+    if user is None:
+        await websocket.close(code=UNAUTHENTICATED_CLOSE_CODE)
+        return
+    await presence_hub.connect(user.username, websocket)
+    try:
+        while True:
+            await websocket.receive_text()  # Clients only send keep-alives
+    except WebSocketDisconnect:
+        pass
+    finally:
+        await presence_hub.disconnect(user.username, websocket)
+
+
+async def _graphql_context(current_user: Optional[TokenUser] = Depends(get_optional_user)) -> Dict[str, Any]:
+    """Who is asking, for graphql_api's resolvers; authenticated like any REST request."""
+    return {"user": current_user, "viewer": _viewer(current_user)}
+
+
+app.include_router(graphql_api.router(_graphql_context), prefix="/api/graphql", include_in_schema=False)
+app.include_router(auth_router, prefix=API_V1_PREFIX)
+app.include_router(account_router, prefix=API_V1_PREFIX)
+# Unversioned compatibility paths
+app.include_router(auth_router, dependencies=[Depends(_legacy_path)], include_in_schema=False)
+app.include_router(account_router, prefix="/api", dependencies=[Depends(_legacy_path)], include_in_schema=False)
 
 
 if __name__ == "__main__":
     # Run without reload to avoid subprocess issues
     # For development with auto-reload, use: uvicorn backend.main:app --reload
-    uvicorn.run(app, host="0.0.0.0", port=8000, reload=False)
+    if bool(TLS_CERT_FILE) != bool(TLS_KEY_FILE):
+        sys.exit("TLS_CERT_FILE and TLS_KEY_FILE must be set together")
+    uvicorn.run(
+        app,
+        host=HOST,
+        port=PORT,
+        reload=False,
+        ssl_certfile=TLS_CERT_FILE,
+        ssl_keyfile=TLS_KEY_FILE,
+        ssl_keyfile_password=TLS_KEY_PASSWORD,
+    )
@@ -0,0 +1,255 @@
+"""
+Scheduled backups of the artifact and user databases.
+
+Every BACKUP_INTERVAL_HOURS a background thread writes a backup into
+BACKUP_DIR, a directory named for when it was taken (e.g.
+``20261016T020000Z``) holding:
+
+- ``artifacts.db`` and ``users.db``: consistent snapshots, taken with
+  SQLite's online backup API while the backend keeps serving
+- ``images.json``: the hash of every image the artifacts reference, so a
+  restore can check the image store still has them all
+- ``backup.json``: when it was taken and what is in it
+
+A backup is written under a temporary name and renamed when complete, so
+a crash never leaves half of one looking whole. After each new backup all
+but the newest BACKUP_KEEP are deleted. Databases on PostgreSQL are left
+out (use pg_dump). Images are stored by content hash and never rewritten,
+so the image store is not copied; back up IMAGE_STORE_DIR or version the
+bucket alongside.
+
+From the command line (restore with the backend stopped):
+    python MainApp/backups.py create
+    python MainApp/backups.py list
+    python MainApp/backups.py restore 20261016T020000Z
+"""
+
+import json
+import logging
+import os
+import re
+import shutil
+import sqlite3
+import threading
+import time
+from datetime import datetime, timedelta
+from typing import Any, Dict, List, Optional
+
+import image_store
+from config import BACKUP_DIR, BACKUP_INTERVAL_HOURS, BACKUP_KEEP, DATABASE_URL, USER_DATABASE_URL
+from user_store import DEFAULT_SQLITE_PATH
+
+logger = logging.getLogger(__name__)
+
+_NAME = re.compile(r"^\d{8}T\d{6}Z$")
+_NAME_FORMAT = "%Y%m%dT%H%M%SZ"
+_PARTIAL_SUFFIX = ".partial"
+
+_stopping = threading.Event()
+_thread: Optional[threading.Thread] = None
+# One backup at a time, whether scheduled or asked for
+_running = threading.Lock()
+
+
+def _sqlite_path(url: Optional[str], default: Optional[str] = None) -> Optional[str]:
+    """The file behind a SQLite URL; None for other databases."""
+    if not url:
+        return default
+    return url[len("sqlite:///"):] if url.startswith("sqlite:///") else None
+
+
+def databases() -> Dict[str, Optional[str]]:
+    """Backup file name -> the SQLite file it is taken from, or None when that database isn't SQLite."""
+    return {
+        "artifacts.db": _sqlite_path(DATABASE_URL),
+        "users.db": _sqlite_path(USER_DATABASE_URL, DEFAULT_SQLITE_PATH),
+    }
+
+
+def _snapshot(source: str, target: str) -> None:
+    src = sqlite3.connect(source)
+    dst = sqlite3.connect(target)
+    try:
+        # Copies page by page without blocking writers for long; the result is one point in time
+        src.backup(dst)
+    finally:
+        dst.close()
+        src.close()
+
+
+def _image_hashes(artifacts_db: str) -> List[str]:
+    conn = sqlite3.connect(artifacts_db)
+    try:
+        rows = conn.execute("SELECT image_hash, thumbnail_hash FROM artifacts").fetchall()
+    finally:
+        conn.close()
+    return sorted({value for row in rows for value in row if value})
+
+
+def create_backup() -> Dict[str, Any]:
+    """Take a backup now, then drop the ones past BACKUP_KEEP; returns what backup.json says."""
+    with _running:
+        os.makedirs(BACKUP_DIR, exist_ok=True)
+        taken_at = datetime.utcnow()
+        name = taken_at.strftime(_NAME_FORMAT)
+        partial = os.path.join(BACKUP_DIR, name + _PARTIAL_SUFFIX)
+        shutil.rmtree(partial, ignore_errors=True)
+        os.makedirs(partial)
+
+        info: Dict[str, Any] = {"name": name, "taken_at": taken_at.isoformat() + "Z", "databases": [], "skipped": []}
+        try:
+            for filename, source in databases().items():
+                if source is None or not os.path.exists(source):
+                    info["skipped"].append(filename)
+                    continue
+                _snapshot(source, os.path.join(partial, filename))
+                info["databases"].append(filename)
+
+            hashes = _image_hashes(os.path.join(partial, "artifacts.db")) if "artifacts.db" in info["databases"] else []
+            with open(os.path.join(partial, "images.json"), "w", encoding="utf-8") as f:
+                json.dump({"image_store": image_store.describe(), "hashes": hashes}, f)
+            info["images"] = len(hashes)
+            with open(os.path.join(partial, "backup.json"), "w", encoding="utf-8") as f:
+                json.dump(info, f, indent=2)
+            os.replace(partial, os.path.join(BACKUP_DIR, name))
+        except Exception:
+            shutil.rmtree(partial, ignore_errors=True)
+            raise
+
+    logger.info(f"Backed up {', '.join(info['databases']) or 'nothing'} to {os.path.join(BACKUP_DIR, name)}")
+    prune()
+    return info
+
+
+def list_backups() -> List[Dict[str, Any]]:
+    """Complete backups in BACKUP_DIR, newest first."""
+    if not os.path.isdir(BACKUP_DIR):
+        return []
+    backups = []
+    for name in sorted(os.listdir(BACKUP_DIR), reverse=True):
+        path = os.path.join(BACKUP_DIR, name, "backup.json")
+        if not _NAME.match(name) or not os.path.exists(path):
+            continue
+        with open(path, encoding="utf-8") as f:
+            backups.append(json.load(f))
+    return backups
+
+
+def prune(keep: int = BACKUP_KEEP) -> List[str]:
+    """Delete all but the newest ``keep`` backups, and any left half-written; returns the names deleted."""
+    deleted = [backup["name"] for backup in list_backups()[max(keep, 1):]]
+    for name in deleted:
+        shutil.rmtree(os.path.join(BACKUP_DIR, name), ignore_errors=True)
+    for name in os.listdir(BACKUP_DIR) if os.path.isdir(BACKUP_DIR) else []:
+        path = os.path.join(BACKUP_DIR, name)
+        # Another process (the CLI, say) may still be writing a recent one
+        if name.endswith(_PARTIAL_SUFFIX) and time.time() - os.path.getmtime(path) > 3600:
+            shutil.rmtree(path, ignore_errors=True)
+    if deleted:
+        logger.info(f"Deleted {len(deleted)} old backup(s), keeping the newest {keep}")
+    return deleted
+
+
+def restore_backup(name: str) -> Dict[str, Any]:
+    """
+    Put a backup's databases back in place of the current ones, which are
+    kept next to them with a ``.before-restore-<time>`` suffix. Only run
+    this with the backend stopped. Returns the databases restored and the
+    images the backup references that the image store no longer has.
+    """
+    directory = os.path.join(BACKUP_DIR, name)
+    if not _NAME.match(name) or not os.path.exists(os.path.join(directory, "backup.json")):
+        raise FileNotFoundError(f"No backup named {name} in {BACKUP_DIR}")
+
+    stamp = datetime.utcnow().strftime(_NAME_FORMAT)
+    restored = []
+    for filename, target in databases().items():
+        snapshot = os.path.join(directory, filename)
+        if not os.path.exists(snapshot):
+            continue
+        if target is None:
+            logger.warning(f"{filename} is in the backup but that database isn't SQLite now; not restored")
+            continue
+        # Its write-ahead log goes too: left behind, it would be replayed onto the restored database
+        for suffix in ("", "-wal", "-shm"):
+            if os.path.exists(target + suffix):
+                os.replace(target + suffix, f"{target}.before-restore-{stamp}{suffix}")
+        os.makedirs(os.path.dirname(os.path.abspath(target)), exist_ok=True)
+        shutil.copyfile(snapshot, target)
+        restored.append(filename)
+
+    with open(os.path.join(directory, "images.json"), encoding="utf-8") as f:
+        hashes = json.load(f)["hashes"]
+    missing = [image_hash for image_hash in hashes if not image_store.exists(image_hash)]
+    if missing:
+        logger.warning(f"{len(missing)} image(s) the backup refers to are missing from {image_store.describe()}")
+    return {"name": name, "restored": restored, "missing_images": missing}
+
+
+def _seconds_until_due() -> float:
+    backups = list_backups()
+    if not backups:
+        return 0
+    last = datetime.fromisoformat(backups[0]["taken_at"].rstrip("Z"))
+    due = last + timedelta(hours=BACKUP_INTERVAL_HOURS)
+    return max((due - datetime.utcnow()).total_seconds(), 0)
+
+
+def _work() -> None:
+    while not _stopping.is_set():
+        try:
+            # Counted from the newest backup, so restarts don't take extra ones
+            if _stopping.wait(_seconds_until_due()):
+                return
+            create_backup()
+        except Exception:
+            logger.exception("Backup failed")
+            # Not straight away again; the next try is an interval later
+            if _stopping.wait(BACKUP_INTERVAL_HOURS * 3600):
+                return
+
+
+def start() -> None:
+    global _thread
+    if _thread or BACKUP_INTERVAL_HOURS <= 0:
+        return
+    _stopping.clear()
+    _thread = threading.Thread(target=_work, name="backups", daemon=True)
+    _thread.start()
+
+
+def stop() -> None:
+    global _thread
+    _stopping.set()
+    _thread = None
+
+
+if __name__ == "__main__":
+    import argparse
+    import sys
+
+    logging.basicConfig(level=logging.INFO)
+    parser = argparse.ArgumentParser(description=f"Back up or restore the databases (backups in {BACKUP_DIR})")
+    commands = parser.add_subparsers(dest="command", required=True)
+    commands.add_parser("create", help="take a backup now")
+    commands.add_parser("list", help="list backups, newest first")
+    restore = commands.add_parser("restore", help="restore a backup; stop the backend first")
+    restore.add_argument("name", help="the backup's name, from list")
+    args = parser.parse_args()
+
+    if args.command == "create":
+        print(f"Created backup {create_backup()['name']}")
+    elif args.command == "list":
+        for backup in list_backups():
+            print(f"{backup['name']}  {', '.join(backup['databases'])}  {backup.get('images', 0)} images")
+    else:
+        try:
+            result = restore_backup(args.name)
+        except FileNotFoundError as e:
+            sys.exit(str(e))
+        print(f"Restored {', '.join(result['restored']) or 'nothing'} from {result['name']}")
+        if result["missing_images"]:
+            print(f"{len(result['missing_images'])} image(s) are missing from the image store:")
+            for image_hash in result["missing_images"]:
+                print(f"  {image_hash}")
+            sys.exit(1)
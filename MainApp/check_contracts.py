@@ -0,0 +1,170 @@
+"""
+Check the API contract between the frontend and the backend.
+
+1. Every payload in contracts/fixtures.json must validate against the
+   matching model in schemas.py and survive a round trip unchanged.
+2. Every frontend interface in frontend/src/types/index.ts that mirrors a
+   backend model must declare exactly the same fields.
+
+Run from anywhere: python MainApp/check_contracts.py
+Exits non-zero and lists the differences if the two sides have drifted.
+"""
+
+import json
+import os
+import re
+import sys
+
+from pydantic import ValidationError
+
+import schemas
+
+MAIN_APP_DIR = os.path.dirname(os.path.abspath(__file__))
+REPO_DIR = os.path.dirname(MAIN_APP_DIR)
+FIXTURES_FILE = os.path.join(REPO_DIR, "contracts", "fixtures.json")
+TYPES_FILE = os.path.join(REPO_DIR, "frontend", "src", "types", "index.ts")
+
+# Frontend interface name -> backend model name
+MIRRORED_TYPES = {
+    "User": "UserResponse",
+    "UserCreate": "UserCreate",
+    "LoginRequest": "LoginRequest",
+    "AuditLog": "AuditLogEntry",
+    "FormData": "FormData",
+    "ArtifactCreate": "ArtifactBase",
+    "ArtifactUpdate": "ArtifactUpdate",
+    "ArtifactPatch": "ArtifactPatch",
+    "Sharing": "Sharing",
+    "CollectionCreate": "CollectionCreate",
+    "CollectionInfo": "CollectionInfo",
+    "WebhookCreate": "WebhookCreate",
+    "WebhookInfo": "WebhookInfo",
+    "ProfileUpdate": "ProfileUpdate",
+    "VerificationRequest": "VerificationRequest",
+    "AnalyzeRequest": "AnalyzeRequest",
+    "TierUsage": "TierUsage",
+    "AnalysisRateLimit": "AnalysisRateLimit",
+    "AnalysisUsageInfo": "AnalysisUsageInfo",
+    "SimilarArtifact": "SimilarArtifact",
+    "ArtifactLocation": "ArtifactLocation",
+    "TagCount": "TagCount",
+    "TagMerge": "TagMerge",
+    "TagRename": "TagRename",
+    "TagChange": "TagChange",
+    "FlagCreate": "FlagCreate",
+    "FlagResolution": "FlagResolution",
+    "ArtifactFlag": "ArtifactFlag",
+    "LockRequest": "LockRequest",
+    "IdentificationOverride": "IdentificationOverride",
+    "FieldChange": "FieldChange",
+    "ArtifactRevision": "ArtifactRevision",
+    "ImportedArtifact": "ImportedArtifact",
+    "ImportResult": "ImportResult",
+    "BootstrapRequest": "BootstrapRequest",
+    "MessageResponse": "MessageResponse",
+    "UserCreated": "UserCreated",
+    "OAuthProvider": "OAuthProvider",
+    "SessionResponse": "SessionResponse",
+    "PasswordPolicy": "PasswordPolicy",
+    "AccountDeletion": "AccountDeletion",
+    "LoginRecord": "LoginRecord",
+    "RegisterRequest": "RegisterRequest",
+    "RegistrationInfo": "RegistrationInfo",
+    "InviteCreate": "InviteCreate",
+    "InviteInfo": "InviteInfo",
+    "InviteCreated": "InviteCreated",
+    "IpRuleCreate": "IpRuleCreate",
+    "IpRuleInfo": "IpRuleInfo",
+    "OrganizationCreate": "OrganizationCreate",
+    "OrganizationInfo": "OrganizationInfo",
+    "OrgMember": "OrgMember",
+    "OrgMemberAdd": "OrgMemberAdd",
+    "OrgMemberUpdate": "OrgMemberUpdate",
+    "AdminStats": "AdminStats",
+    "FailedLogin": "FailedLogin",
+    "LockedAccount": "LockedAccount",
+}
+
+
+def check_fixtures():
+    """Validate each fixture against its model and round-trip it."""
+    errors = []
+    with open(FIXTURES_FILE, encoding="utf-8") as f:
+        fixtures = json.load(f)
+
+    for model_name, payload in fixtures.items():
+        model = getattr(schemas, model_name, None)
+        if model is None:
+            errors.append(f"{model_name}: no such model in schemas.py")
+            continue
+        try:
+            parsed = model.model_validate(payload)
+        except ValidationError as e:
+            errors.append(f"{model_name}: fixture does not validate\n{e}")
+            continue
+
+        unknown = set(payload) - set(model.model_fields)
+        if unknown:
+            errors.append(f"{model_name}: fixture has fields the model ignores: {sorted(unknown)}")
+
+        dumped = parsed.model_dump(mode="json", exclude_unset=True)
+        if dumped != payload:
+            errors.append(f"{model_name}: round trip changed the payload\n  sent: {payload}\n  got:  {dumped}")
+
+    return errors
+
+
+def frontend_interfaces():
+    """
+    Parse `export interface X extends Y { field?: type }` blocks into
+    {name: {fields}}, including fields inherited from other interfaces here.
+    """
+    with open(TYPES_FILE, encoding="utf-8") as f:
+        source = f.read()
+
+    own, parents = {}, {}
+    for match in re.finditer(r"export interface (\w+)(?: extends (\w+))?[^{]*\{(.*?)\n\}", source, re.S):
+        name, parent, body = match.groups()
+        own[name] = set(re.findall(r"^\s*(\w+)\??:", body, re.M))
+        parents[name] = parent
+
+    def fields(name):
+        parent = parents.get(name)
+        return own[name] | (fields(parent) if parent in own else set())
+
+    return {name: fields(name) for name in own}
+
+
+def check_types():
+    """Compare field names of mirrored frontend interfaces and backend models."""
+    errors = []
+    interfaces = frontend_interfaces()
+
+    for ts_name, model_name in MIRRORED_TYPES.items():
+        if ts_name not in interfaces:
+            errors.append(f"{ts_name}: missing from {os.path.relpath(TYPES_FILE, REPO_DIR)}")
+            continue
+        ts_fields = interfaces[ts_name]
+        py_fields = set(getattr(schemas, model_name).model_fields)
+        if ts_fields != py_fields:
+            errors.append(
+                f"{ts_name} <-> schemas.{model_name}: "
+                f"frontend only {sorted(ts_fields - py_fields)}, backend only {sorted(py_fields - ts_fields)}"
+            )
+
+    return errors
+
+
+def main():
+    errors = check_fixtures() + check_types()
+    if errors:
+        print("❌ Contract check failed:")
+        for error in errors:
+            print(f"  - {error}")
+        return 1
+    print("✅ Frontend and backend contracts match")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
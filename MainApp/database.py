@@ -1,8 +1,13 @@
 import base64
-import os
+import hashlib
+import json
+import logging
+import re
+import uuid
 from contextlib import contextmanager
-from datetime import datetime
-from typing import Any, Dict, List, Optional, Union
+from dataclasses import dataclass
+from datetime import datetime, timedelta
+from typing import Any, Dict, List, Optional, Tuple, Union
 
 from sqlalchemy import (
     Column,
@@ -13,33 +18,46 @@ from sqlalchemy import (
     String,
     Text,
     create_engine,
+    event,
+    func,
     inspect,
+    literal,
+    or_,
+    select,
     text,
 )
 from sqlalchemy.ext.declarative import declarative_base
-from sqlalchemy.orm import sessionmaker
+from sqlalchemy.orm import defer, sessionmaker
+
+import exif_metadata
+import image_store
+from config import DATABASE_URL, SQLITE_BUSY_TIMEOUT_MS, SQLITE_STATEMENT_CACHE_SIZE, SYNC_OVERLAP_SECONDS
+from user_store import configure_sqlite_connection
+
+logger = logging.getLogger(__name__)
 
 # ----------------------------------------------------------------------
 # Configuration
 # ----------------------------------------------------------------------
-# Prefer the DATABASE_URL environment variable (set by docker-compose or .env).
-# If it is missing or empty, fall back to a local SQLite file.
-_DB_URL = os.getenv("DATABASE_URL")
-if not _DB_URL:
-    # Default SQLite database located in the project root directory
-    _DB_DIR = os.path.dirname(os.path.abspath(__file__))
-    _DB_PATH = os.path.join(_DB_DIR, "artifacts.db")
-    _DB_URL = f"sqlite:///{_DB_PATH}"
+# DATABASE_URL comes from the environment or .env (set by docker-compose);
+# config falls back to a local SQLite file in MainApp/.
+_DB_URL = DATABASE_URL
 
 # SQLite requires a special ``check_same_thread`` flag; other DBMS do not.
 # Add connection pooling for better performance
 if _DB_URL.startswith("sqlite"):
     engine = create_engine(
         _DB_URL,
-        connect_args={"check_same_thread": False},
+        connect_args={
+            "check_same_thread": False,
+            "timeout": SQLITE_BUSY_TIMEOUT_MS / 1000,
+            "cached_statements": SQLITE_STATEMENT_CACHE_SIZE,
+        },
         pool_pre_ping=True,  # Verify connections before using
         pool_recycle=3600,  # Recycle connections after 1 hour
     )
+    # WAL and busy timeout, same as the user database
+    event.listen(engine, "connect", lambda conn, _record: configure_sqlite_connection(conn))
 else:
     # PostgreSQL connection pooling
     engine = create_engine(
@@ -77,7 +95,14 @@ class Artifact(Base):
     rarity: Optional[str] = Column(String(200))
     confidence: Optional[float] = Column(Float)
 
-    # Image data
+    # Images live in image_store; these are their SHA-256 hashes
+    image_hash: Optional[str] = Column(String(64), index=True)
+    thumbnail_hash: Optional[str] = Column(String(64), index=True)
+    # What the image was uploaded as (e.g. "HEIC", "RAW") and its size in
+    # bytes; anything but JPEG and PNG is stored converted (see image_formats.py)
+    original_format: Optional[str] = Column(String(20))
+    original_size: Optional[int] = Column(Integer)
+    # Pre-image-store blobs, moved out by init_db and left empty
     image_data: Optional[bytes] = Column(LargeBinary)
     thumbnail: Optional[bytes] = Column(LargeBinary)
     
@@ -90,6 +115,11 @@ class Artifact(Base):
     analyzed_at: Optional[datetime] = Column(DateTime, default=datetime.utcnow)
     updated_at: Optional[datetime] = Column(DateTime)
 
+    # When and where the photo was taken, from its EXIF data (camera local time; WGS 84 degrees)
+    captured_at: Optional[datetime] = Column(DateTime)
+    latitude: Optional[float] = Column(Float, index=True)
+    longitude: Optional[float] = Column(Float)
+
     # Expert verification fields
     verification_status: str = Column(String(50), default="pending")
     verified_by: Optional[str] = Column(String(200))
@@ -102,6 +132,8 @@ class Artifact(Base):
     references: Optional[str] = Column(Text)
     # Tags (comma-separated)
     tags: Optional[str] = Column(Text)
+    # Free-form notes from the team
+    notes: Optional[str] = Column(Text)
 
     # Form data (JSON string with physical measurements)
     form_data: Optional[str] = Column(Text)
@@ -112,8 +144,39 @@ class Artifact(Base):
     # User who uploaded the artifact
     uploaded_by: Optional[str] = Column(String(200))
 
-    def to_dict(self) -> Dict[str, Any]:
-        """Convert artifact to a plain‑dictionary representation."""
+    # Who may see it: the owner (a username), anyone it is shared with, and
+    # depending on visibility the owner's team (an organization) or everyone
+    owner_id: Optional[str] = Column(String(200), index=True)
+    visibility: str = Column(String(20), default="private")
+    team_id: Optional[int] = Column(Integer)
+    collection_id: Optional[int] = Column(Integer, index=True)
+
+    # Set while the artifact is in the trash; it is left out of everything
+    # else until restored or purged for good (see trash.py)
+    deleted_at: Optional[datetime] = Column(DateTime, index=True)
+    deleted_by: Optional[str] = Column(String(200))
+
+    # Set while a curator has locked the artifact; nobody may edit, share or
+    # delete it until it is unlocked (see the moderation endpoints)
+    locked_at: Optional[datetime] = Column(DateTime)
+    locked_by: Optional[str] = Column(String(200))
+    lock_reason: Optional[str] = Column(Text)
+    # Set when a curator replaced the analysis' identification (name, era...)
+    identification_overridden_by: Optional[str] = Column(String(200))
+    identification_overridden_at: Optional[datetime] = Column(DateTime)
+
+    @property
+    def version(self) -> str:
+        """Opaque token that changes on every update; the ETag for optimistic concurrency."""
+        stamp = self.updated_at or self.uploaded_at
+        return stamp.isoformat() if stamp else ""
+
+    def to_dict(self, include_blobs: bool = True) -> Dict[str, Any]:
+        """Convert artifact to a plain‑dictionary representation.
+
+        Without ``include_blobs`` the 3D model is left out (as None), for rows
+        queried with ``_without_blobs``.
+        """
         return {
             "id": self.id,
             "name": self.name,
@@ -125,9 +188,11 @@ class Artifact(Base):
             "function": self.function,
             "rarity": self.rarity,
             "confidence": self.confidence,
-            "image_data": self.image_data,
-            "thumbnail": self.thumbnail,
-            "model_3d_data": self.model_3d_data,
+            "image_hash": self.image_hash,
+            "thumbnail_hash": self.thumbnail_hash,
+            "original_format": self.original_format,
+            "original_size": self.original_size,
+            "model_3d_data": self.model_3d_data if include_blobs else None,
             "model_3d_format": self.model_3d_format,
             "uploaded_at": self.uploaded_at.isoformat() + "Z"
             if self.uploaded_at
@@ -138,6 +203,9 @@ class Artifact(Base):
             "updated_at": self.updated_at.isoformat() + "Z"
             if self.updated_at
             else None,
+            "captured_at": self.captured_at.isoformat() if self.captured_at else None,
+            "latitude": self.latitude,
+            "longitude": self.longitude,
             "verification_status": self.verification_status,
             "verified_by": self.verified_by,
             "verified_at": self.verified_at.isoformat() if self.verified_at else None,
@@ -146,12 +214,261 @@ class Artifact(Base):
             "historical_context": self.historical_context,
             "references": self.references,
             "tags": self.tags,
+            "notes": self.notes,
+            "version": self.version,
             "form_data": self.form_data,
             "tier": self.tier,
             "uploaded_by": self.uploaded_by,
+            "owner_id": self.owner_id,
+            "visibility": self.visibility,
+            "team_id": self.team_id,
+            "collection_id": self.collection_id,
+            "deleted_at": self.deleted_at.isoformat() + "Z" if self.deleted_at else None,
+            "deleted_by": self.deleted_by,
+            "locked_at": self.locked_at.isoformat() + "Z" if self.locked_at else None,
+            "locked_by": self.locked_by,
+            "lock_reason": self.lock_reason,
+            "identification_overridden_by": self.identification_overridden_by,
+            "identification_overridden_at": self.identification_overridden_at.isoformat() + "Z"
+            if self.identification_overridden_at
+            else None,
+        }
+
+
+class Collection(Base):
+    """A named set of artifacts; sharing a collection shares everything in it."""
+
+    __tablename__ = "collections"
+
+    id: int = Column(Integer, primary_key=True, index=True)
+    name: str = Column(String(200), nullable=False)
+    description: Optional[str] = Column(Text)
+    owner_id: str = Column(String(200), nullable=False, index=True)
+    visibility: str = Column(String(20), nullable=False, default="private")
+    team_id: Optional[int] = Column(Integer)
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "name": self.name,
+            "description": self.description,
+            "owner_id": self.owner_id,
+            "visibility": self.visibility,
+            "team_id": self.team_id,
+            "created_at": self.created_at.isoformat() + "Z" if self.created_at else None,
+        }
+
+
+class ArtifactShare(Base):
+    """An artifact shared with one user."""
+
+    __tablename__ = "artifact_shares"
+
+    artifact_id: int = Column(Integer, primary_key=True)
+    username: str = Column(String(200), primary_key=True, index=True)
+    shared_by: Optional[str] = Column(String(200))
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+
+
+class CollectionShare(Base):
+    """A collection shared with one user."""
+
+    __tablename__ = "collection_shares"
+
+    collection_id: int = Column(Integer, primary_key=True)
+    username: str = Column(String(200), primary_key=True, index=True)
+    shared_by: Optional[str] = Column(String(200))
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+
+
+class ArtifactEmbedding(Base):
+    """An artifact image's embedding for visual similarity search (see image_embeddings.py)"""
+
+    __tablename__ = "artifact_embeddings"
+
+    artifact_id: int = Column(Integer, primary_key=True)
+    model: str = Column(String(100), nullable=False, index=True)  # Embedder that made it; others are recomputed
+    vector: bytes = Column(LargeBinary, nullable=False)  # float32 unit vector
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+
+
+class ArtifactTombstone(Base):
+    """Record of a deleted artifact so syncing clients can drop it locally"""
+
+    __tablename__ = "artifact_tombstones"
+
+    artifact_id: int = Column(Integer, primary_key=True)
+    deleted_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False, index=True)
+
+
+class ArtifactFlag(Base):
+    """A curator's report that an artifact needs review; open until resolved"""
+
+    __tablename__ = "artifact_flags"
+
+    id: int = Column(Integer, primary_key=True, index=True)
+    artifact_id: int = Column(Integer, nullable=False, index=True)
+    reason: str = Column(Text, nullable=False)
+    flagged_by: str = Column(String(200), nullable=False)
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+    resolved_at: Optional[datetime] = Column(DateTime, index=True)
+    resolved_by: Optional[str] = Column(String(200))
+    resolution: Optional[str] = Column(Text)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "artifact_id": self.artifact_id,
+            "reason": self.reason,
+            "flagged_by": self.flagged_by,
+            "created_at": self.created_at.isoformat() + "Z" if self.created_at else None,
+            "resolved_at": self.resolved_at.isoformat() + "Z" if self.resolved_at else None,
+            "resolved_by": self.resolved_by,
+            "resolution": self.resolution,
         }
 
 
+class ArtifactRevision(Base):
+    """One change to an artifact: who made it and each field's old and new value (see _record_revisions)"""
+
+    __tablename__ = "artifact_revisions"
+
+    id: int = Column(Integer, primary_key=True, index=True)
+    artifact_id: int = Column(Integer, nullable=False, index=True)
+    # created, updated, deleted, restored, locked, unlocked, identified or reverted
+    action: str = Column(String(20), nullable=False)
+    changed_by: Optional[str] = Column(String(200))
+    changed_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+    changes: str = Column(Text, nullable=False)  # JSON {field: [old, new]}
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "artifact_id": self.artifact_id,
+            "action": self.action,
+            "changed_by": self.changed_by,
+            "changed_at": self.changed_at.isoformat() + "Z" if self.changed_at else None,
+            "changes": [
+                {"field": field, "old": old, "new": new} for field, (old, new) in json.loads(self.changes).items()
+            ],
+        }
+
+
+class AnalysisJob(Base):
+    """A queued image analysis; kept in the database so a restart picks it up again"""
+
+    __tablename__ = "analysis_jobs"
+
+    id: str = Column(String(32), primary_key=True)
+    tier: str = Column(String(20), nullable=False)
+    # queued -> running -> done | failed
+    status: str = Column(String(20), nullable=False, default="queued", index=True)
+    # Finer progress for the events stream: received -> preprocessing -> inference -> saving -> done | failed
+    stage: str = Column(String(20), nullable=False, default="received")
+    image_hash: str = Column(String(64), nullable=False)
+    result: Optional[str] = Column(Text)  # JSON analysis result once done
+    error: Optional[str] = Column(Text)
+    attempts: int = Column(Integer, nullable=False, default=0)
+    request_id: Optional[str] = Column(String(64))
+    # Jobs submitted together by POST /api/analyze/batch, in upload order
+    batch_id: Optional[str] = Column(String(32), index=True)
+    batch_index: Optional[int] = Column(Integer)
+    filename: Optional[str] = Column(String(300))
+    # Who queued it; their webhooks hear when it finishes
+    requested_by: Optional[str] = Column(String(200))
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False, index=True)
+    started_at: Optional[datetime] = Column(DateTime)
+    finished_at: Optional[datetime] = Column(DateTime)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "tier": self.tier,
+            "status": self.status,
+            "stage": self.stage,
+            "batch_id": self.batch_id,
+            "filename": self.filename,
+            "requested_by": self.requested_by,
+            "result": json.loads(self.result) if self.result else None,
+            "error": self.error,
+            "attempts": self.attempts,
+            "created_at": self.created_at.isoformat() + "Z" if self.created_at else None,
+            "started_at": self.started_at.isoformat() + "Z" if self.started_at else None,
+            "finished_at": self.finished_at.isoformat() + "Z" if self.finished_at else None,
+        }
+
+
+class AnalysisCacheEntry(Base):
+    """An earlier analysis of the same image bytes at the same tier"""
+
+    __tablename__ = "analysis_cache"
+
+    image_hash: str = Column(String(64), primary_key=True)  # sha256 of the image bytes
+    tier: str = Column(String(20), primary_key=True)
+    provider: str = Column(String(50), nullable=False)
+    result: str = Column(Text, nullable=False)  # JSON analysis result
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False, index=True)
+
+
+class AnalysisUsage(Base):
+    """How many analyses a user ran in a month at one tier, and the quota units they cost."""
+
+    __tablename__ = "analysis_usage"
+
+    username: str = Column(String(200), primary_key=True)
+    month: str = Column(String(7), primary_key=True)  # "2026-10", UTC
+    tier: str = Column(String(20), primary_key=True)
+    analyses: int = Column(Integer, nullable=False, default=0)
+    units: int = Column(Integer, nullable=False, default=0)
+
+
+class Webhook(Base):
+    """A URL called when one of its owner's queued analyses finishes or fails"""
+
+    __tablename__ = "webhooks"
+
+    id: int = Column(Integer, primary_key=True, index=True)
+    owner_id: str = Column(String(200), nullable=False, index=True)
+    url: str = Column(String(2000), nullable=False)
+    secret: str = Column(String(64), nullable=False)  # HMAC key for the signature header
+    events: str = Column(String(200), nullable=False)  # Comma-separated, e.g. "analysis.done,analysis.failed"
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+    last_delivery_at: Optional[datetime] = Column(DateTime)
+    last_status: Optional[str] = Column(String(20))  # delivered | failed, for the latest finished delivery
+    last_error: Optional[str] = Column(Text)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "owner_id": self.owner_id,
+            "url": self.url,
+            "events": [e for e in self.events.split(",") if e],
+            "created_at": self.created_at.isoformat() + "Z" if self.created_at else None,
+            "last_delivery_at": self.last_delivery_at.isoformat() + "Z" if self.last_delivery_at else None,
+            "last_status": self.last_status,
+            "last_error": self.last_error,
+        }
+
+
+class WebhookDelivery(Base):
+    """One event to POST to a webhook; the table is the retry queue"""
+
+    __tablename__ = "webhook_deliveries"
+
+    id: str = Column(String(32), primary_key=True)
+    webhook_id: int = Column(Integer, nullable=False, index=True)
+    event: str = Column(String(50), nullable=False)
+    payload: str = Column(Text, nullable=False)  # JSON body, signed as sent
+    # pending -> sending -> delivered | failed (back to pending while retries remain)
+    status: str = Column(String(20), nullable=False, default="pending", index=True)
+    attempts: int = Column(Integer, nullable=False, default=0)
+    next_attempt_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False, index=True)
+    last_error: Optional[str] = Column(Text)
+    created_at: datetime = Column(DateTime, default=datetime.utcnow, nullable=False)
+    finished_at: Optional[datetime] = Column(DateTime)
+
+
 # ----------------------------------------------------------------------
 # Helper functions
 # ----------------------------------------------------------------------
@@ -159,6 +476,7 @@ def init_db() -> None:
     """Create all tables defined by the ORM models."""
     Base.metadata.create_all(bind=engine)
     # Ensure required columns exist for existing databases
+    read_existing_exif = False
     try:
         inspector = inspect(engine)
         columns = [c["name"] for c in inspector.get_columns("artifacts")]
@@ -179,10 +497,205 @@ def init_db() -> None:
                 conn.execute(text("ALTER TABLE artifacts ADD COLUMN model_3d_format VARCHAR(10)"))
             if "uploaded_by" not in columns:
                 conn.execute(text("ALTER TABLE artifacts ADD COLUMN uploaded_by VARCHAR(200)"))
+            if "image_hash" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN image_hash VARCHAR(64)"))
+            if "thumbnail_hash" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN thumbnail_hash VARCHAR(64)"))
+            if "notes" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN notes TEXT"))
+            if "owner_id" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN owner_id VARCHAR(200)"))
+                # Artifacts from before ownership belong to their uploader
+                conn.execute(text("UPDATE artifacts SET owner_id = uploaded_by"))
+            if "visibility" not in columns:
+                # ...and stay visible to everyone, as they were
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN visibility VARCHAR(20) DEFAULT 'public'"))
+            if "team_id" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN team_id INTEGER"))
+            if "collection_id" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN collection_id INTEGER"))
+            if "captured_at" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN captured_at DATETIME"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN latitude FLOAT"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN longitude FLOAT"))
+                conn.execute(text("CREATE INDEX IF NOT EXISTS ix_artifacts_latitude ON artifacts (latitude)"))
+                read_existing_exif = True
+            if "original_format" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN original_format VARCHAR(20)"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN original_size INTEGER"))
+            if "deleted_at" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN deleted_at DATETIME"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN deleted_by VARCHAR(200)"))
+                conn.execute(text("CREATE INDEX IF NOT EXISTS ix_artifacts_deleted_at ON artifacts (deleted_at)"))
+            if "locked_at" not in columns:
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN locked_at DATETIME"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN locked_by VARCHAR(200)"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN lock_reason TEXT"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN identification_overridden_by VARCHAR(200)"))
+                conn.execute(text("ALTER TABLE artifacts ADD COLUMN identification_overridden_at DATETIME"))
+            job_columns = [c["name"] for c in inspector.get_columns("analysis_jobs")]
+            if "batch_id" not in job_columns:
+                conn.execute(text("ALTER TABLE analysis_jobs ADD COLUMN batch_id VARCHAR(32)"))
+                conn.execute(text("CREATE INDEX IF NOT EXISTS ix_analysis_jobs_batch_id ON analysis_jobs (batch_id)"))
+            if "batch_index" not in job_columns:
+                conn.execute(text("ALTER TABLE analysis_jobs ADD COLUMN batch_index INTEGER"))
+            if "filename" not in job_columns:
+                conn.execute(text("ALTER TABLE analysis_jobs ADD COLUMN filename VARCHAR(300)"))
+            if "requested_by" not in job_columns:
+                conn.execute(text("ALTER TABLE analysis_jobs ADD COLUMN requested_by VARCHAR(200)"))
+            if "stage" not in job_columns:
+                conn.execute(text("ALTER TABLE analysis_jobs ADD COLUMN stage VARCHAR(20) DEFAULT 'received' NOT NULL"))
+                conn.execute(text("UPDATE analysis_jobs SET stage = status WHERE status IN ('done', 'failed')"))
             conn.commit()
     except Exception:
         # Best-effort; ignore if not supported or already exists
         pass
+    _move_images_to_store()
+    if read_existing_exif:
+        _read_existing_exif()
+    _create_search_index()
+
+
+def _read_existing_exif(batch_size: int = 50) -> None:
+    """Fill in capture time and place for artifacts uploaded before they were read from EXIF."""
+    located = 0
+    last_id = 0
+    while True:
+        with get_db() as db:
+            artifacts = (
+                db.query(Artifact)
+                .filter(Artifact.id > last_id, Artifact.image_hash.isnot(None))
+                .order_by(Artifact.id)
+                .limit(batch_size)
+                .all()
+            )
+            if not artifacts:
+                break
+            for artifact in artifacts:
+                last_id = artifact.id
+                try:
+                    metadata = exif_metadata.extract(image_store.get(artifact.image_hash))
+                except Exception:
+                    continue
+                artifact.captured_at = metadata["captured_at"]
+                artifact.latitude = metadata["latitude"]
+                artifact.longitude = metadata["longitude"]
+                located += artifact.latitude is not None
+    if located:
+        logger.info(f"Read photo locations for {located} existing artifacts")
+
+
+def _move_images_to_store(batch_size: int = 50) -> None:
+    """Copy image blobs left in the artifacts table into image_store, then clear them."""
+    moved = 0
+    while True:
+        with get_db() as db:
+            artifacts = (
+                db.query(Artifact)
+                .filter(Artifact.image_data.isnot(None) | Artifact.thumbnail.isnot(None))
+                .limit(batch_size)
+                .all()
+            )
+            if not artifacts:
+                break
+            for artifact in artifacts:
+                if artifact.image_data is not None:
+                    artifact.image_hash = image_store.put(artifact.image_data)
+                    artifact.image_data = None
+                if artifact.thumbnail is not None:
+                    artifact.thumbnail_hash = image_store.put(artifact.thumbnail)
+                    artifact.thumbnail = None
+            moved += len(artifacts)
+    if moved:
+        logger.info(f"Moved images for {moved} artifacts into {image_store.describe()}")
+
+
+# ----------------------------------------------------------------------
+# Full-text search (SQLite FTS5)
+# ----------------------------------------------------------------------
+# artifacts_fts mirrors the searchable text of each artifact under the same
+# rowid, kept current by triggers. "notes" gathers the team's notes and the
+# longer free-text fields.
+# Other databases, or SQLite builds without FTS5, keep the LIKE search below.
+_FTS_NOTES = (
+    "coalesce({row}.notes, '') || ' ' || coalesce({row}.cultural_context, '') || ' ' || coalesce({row}.material, '') || ' ' || "
+    "coalesce({row}.provenance, '') || ' ' || coalesce({row}.historical_context, '') || ' ' || "
+    "coalesce({row}.verification_comments, '')"
+)
+_FTS_INSERT = (
+    "INSERT INTO artifacts_fts (rowid, name, description, tags, notes) "
+    "VALUES ({row}.id, {row}.name, {row}.description, {row}.tags, " + _FTS_NOTES + ");"
+)
+# bm25 column weights for name, description, tags, notes: name hits rank first
+_FTS_WEIGHTS = "10.0, 3.0, 5.0, 1.0"
+
+_search_index_ready = False
+
+
+def _create_search_index() -> None:
+    """Create artifacts_fts and its triggers, filling it from existing artifacts the first time.
+
+    The triggers are recreated on every start so changes to them apply to existing databases.
+    """
+    global _search_index_ready
+    if engine.dialect.name != "sqlite":
+        return
+    try:
+        with engine.begin() as conn:
+            exists = conn.execute(
+                text("SELECT 1 FROM sqlite_master WHERE name = 'artifacts_fts'")
+            ).first()
+            conn.execute(text(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS artifacts_fts USING fts5("
+                "name, description, tags, notes, "
+                "tokenize = 'unicode61 remove_diacritics 2', prefix = '2 3')"
+            ))
+            for trigger in ("artifacts_fts_insert", "artifacts_fts_delete", "artifacts_fts_update"):
+                conn.execute(text(f"DROP TRIGGER IF EXISTS {trigger}"))
+            conn.execute(text(
+                "CREATE TRIGGER IF NOT EXISTS artifacts_fts_insert AFTER INSERT ON artifacts BEGIN "
+                + _FTS_INSERT.format(row="new") + " END"
+            ))
+            conn.execute(text(
+                "CREATE TRIGGER IF NOT EXISTS artifacts_fts_delete AFTER DELETE ON artifacts BEGIN "
+                "DELETE FROM artifacts_fts WHERE rowid = old.id; END"
+            ))
+            conn.execute(text(
+                "CREATE TRIGGER IF NOT EXISTS artifacts_fts_update AFTER UPDATE OF "
+                "name, description, tags, notes, cultural_context, material, provenance, "
+                "historical_context, verification_comments ON artifacts BEGIN "
+                "DELETE FROM artifacts_fts WHERE rowid = old.id; "
+                + _FTS_INSERT.format(row="new") + " END"
+            ))
+            if not exists:
+                conn.execute(text(
+                    "INSERT INTO artifacts_fts (rowid, name, description, tags, notes) "
+                    "SELECT id, name, description, tags, " + _FTS_NOTES.format(row="artifacts")
+                    + " FROM artifacts"
+                ))
+        _search_index_ready = True
+    except Exception as e:
+        logger.warning(f"Full-text search unavailable, using LIKE matching: {e}")
+
+
+_QUERY_TERM = re.compile(r'"([^"]*)"?|(\S+)')
+
+
+def _fts_query(query: str) -> Optional[str]:
+    """Turn a search box query into an FTS5 MATCH expression.
+
+    ``"roman coin"`` matches the phrase; any other word matches as a prefix
+    (``amph`` finds "amphora"). All terms must match. FTS5 operators typed by
+    the user are treated as plain words, so no input is a syntax error.
+    """
+    terms = []
+    for phrase, word in _QUERY_TERM.findall(query or ""):
+        value = phrase if phrase else word.replace('"', "").rstrip("*")
+        if not re.search(r"\w", value):
+            continue
+        quoted = '"' + value.replace('"', '""') + '"'
+        terms.append(quoted if phrase else quoted + "*")
+    return " ".join(terms) or None
 
 
 @contextmanager
@@ -199,6 +712,88 @@ def get_db():
         db.close()
 
 
+# ----------------------------------------------------------------------
+# Change history
+# ----------------------------------------------------------------------
+# Left out of artifact history: the key, the sync stamp and binary blobs
+_UNTRACKED_FIELDS = {"id", "updated_at", "image_data", "thumbnail", "model_3d_data"}
+_TRACKED_FIELDS = [attr.key for attr in inspect(Artifact).column_attrs if attr.key not in _UNTRACKED_FIELDS]
+
+
+def _history_value(value: Any) -> Any:
+    return value.isoformat() if isinstance(value, datetime) else value
+
+
+def _attribute_changes(db, changed_by: Optional[str], action: Optional[str] = None) -> None:
+    """
+    Credit the artifact changes ``db`` goes on to flush to ``changed_by``.
+    ``action`` names them instead of the one worked out from what changed.
+    """
+    db.info["changed_by"] = changed_by
+    db.info["revision_action"] = action
+
+
+def _revision_action(changes: Dict[str, List[Any]]) -> str:
+    if "deleted_at" in changes:
+        return "deleted" if changes["deleted_at"][1] else "restored"
+    if "locked_at" in changes:
+        return "locked" if changes["locked_at"][1] else "unlocked"
+    return "updated"
+
+
+def _add_revisions(db, revisions: List[Tuple[int, str, Dict[str, List[Any]]]]) -> None:
+    """Insert (artifact_id, action, {field: [old, new]}) revisions; ones that changed nothing are dropped."""
+    now = datetime.utcnow()
+    rows = [
+        {
+            "artifact_id": artifact_id,
+            "action": action,
+            "changed_by": db.info.get("changed_by"),
+            "changed_at": now,
+            "changes": json.dumps(changes),
+        }
+        for artifact_id, action, changes in revisions
+        if changes
+    ]
+    if rows:
+        # Inside a flush, so straight onto its connection rather than db.add
+        db.connection().execute(ArtifactRevision.__table__.insert(), rows)
+
+
+@event.listens_for(SessionLocal, "after_flush")
+def _record_revisions(db, _flush_context) -> None:
+    """
+    Keep a revision of every artifact a flush adds or changes, so each
+    mutation lands in the history whichever function made it. Bulk UPDATEs
+    skip the session; their callers record revisions with _add_revisions.
+    """
+    revisions = []
+    for artifact in db.new:
+        if isinstance(artifact, Artifact):
+            changes = {
+                field: [None, _history_value(getattr(artifact, field))]
+                for field in _TRACKED_FIELDS
+                if getattr(artifact, field) is not None
+            }
+            revisions.append((artifact.id, "created", changes))
+    for artifact in db.dirty:
+        if not isinstance(artifact, Artifact):
+            continue
+        state = inspect(artifact)
+        changes = {}
+        for field in _TRACKED_FIELDS:
+            history = state.attrs[field].history
+            if not history.has_changes():
+                continue
+            old = history.deleted[0] if history.deleted else None
+            new = history.added[0] if history.added else None
+            if old != new:
+                changes[field] = [_history_value(old), _history_value(new)]
+        if changes:
+            revisions.append((artifact.id, db.info.get("revision_action") or _revision_action(changes), changes))
+    _add_revisions(db, revisions)
+
+
 def _normalize_tags_input(tags: Optional[Union[List[str], str]]) -> List[str]:
     """Return a de-duplicated list of trimmed tags, collapsing extra spaces."""
     if tags is None:
@@ -221,14 +816,241 @@ def _normalize_tags_input(tags: Optional[Union[List[str], str]]) -> List[str]:
     return normalized
 
 
+def _store_image(data: Optional[Union[bytes, str]]) -> Optional[str]:
+    """Put raw or base64 image bytes in the image store and return the hash."""
+    if not data:
+        return None
+    if isinstance(data, str):
+        data = base64.b64decode(data.split(",", 1)[1] if "," in data else data)
+    return image_store.put(data)
+
+
+def _release_images(*image_hashes: Optional[str]) -> None:
+    """Delete image files no remaining artifact or unfinished analysis job refers to (images are shared by hash)."""
+    with get_db() as db:
+        for image_hash in {h for h in image_hashes if h}:
+            still_used = (
+                db.query(Artifact.id)
+                .filter((Artifact.image_hash == image_hash) | (Artifact.thumbnail_hash == image_hash))
+                .first()
+            ) or (
+                db.query(AnalysisJob.id)
+                .filter(AnalysisJob.image_hash == image_hash, AnalysisJob.status.in_(("queued", "running")))
+                .first()
+            )
+            if still_used is None:
+                image_store.delete(image_hash)
+
+
+# ----------------------------------------------------------------------
+# Ownership and sharing
+# ----------------------------------------------------------------------
+VISIBILITIES = ("private", "team", "public")
+SHARE_SCOPES = ("mine", "shared", "all")
+
+
+@dataclass(frozen=True)
+class Viewer:
+    """Who is asking, for the access checks on queries.
+
+    Anonymous callers (no username) see only public artifacts; ``sees_all``
+    is for archive staff. Functions taking ``viewer=None`` don't check access.
+    """
+
+    username: Optional[str] = None
+    team_ids: Tuple[int, ...] = ()
+    sees_all: bool = False
+
+
+def _access_conditions(db, model, share_model, share_key, viewer: Viewer) -> List[Any]:
+    """Conditions, any of which lets ``viewer`` see a row of ``model`` (artifacts or collections)."""
+    conditions = [model.visibility == "public"]
+    if viewer.username:
+        conditions.append(model.owner_id == viewer.username)
+        conditions.append(model.id.in_(db.query(share_key).filter(share_model.username == viewer.username)))
+        if viewer.team_ids:
+            conditions.append((model.visibility == "team") & model.team_id.in_(viewer.team_ids))
+    return conditions
+
+
+def _visible_collection_ids(db, viewer: Viewer):
+    return db.query(Collection.id).filter(
+        or_(*_access_conditions(db, Collection, CollectionShare, CollectionShare.collection_id, viewer))
+    )
+
+
+def _visible_artifacts(db, q, viewer: Optional[Viewer]):
+    """Restrict an artifact query to those the viewer may see, directly or through a collection; never trashed ones."""
+    q = q.filter(Artifact.deleted_at.is_(None))
+    if viewer is None or viewer.sees_all:
+        return q
+    conditions = _access_conditions(db, Artifact, ArtifactShare, ArtifactShare.artifact_id, viewer)
+    conditions.append(Artifact.collection_id.in_(_visible_collection_ids(db, viewer)))
+    return q.filter(or_(*conditions))
+
+
+def _scoped_artifacts(db, q, viewer: Optional[Viewer], scope: str = "all"):
+    """``_visible_artifacts``, narrowed to the viewer's own ("mine") or other people's ("shared") artifacts."""
+    q = _visible_artifacts(db, q, viewer)
+    if viewer is not None and viewer.username:
+        if scope == "mine":
+            q = q.filter(Artifact.owner_id == viewer.username)
+        elif scope == "shared":
+            q = q.filter(or_(Artifact.owner_id != viewer.username, Artifact.owner_id.is_(None)))
+    return q
+
+
+_SHARE_MODELS = {
+    "artifact": (Artifact, ArtifactShare, "artifact_id"),
+    "collection": (Collection, CollectionShare, "collection_id"),
+}
+
+
+def get_sharing(kind: str, target_id: int) -> Optional[Dict[str, Any]]:
+    """Visibility, team and the users an artifact or collection (``kind``) is shared with; None if it doesn't exist."""
+    model, share_model, key = _SHARE_MODELS[kind]
+    with get_db() as db:
+        target = db.query(model).filter(model.id == target_id).first()
+        if target is None:
+            return None
+        usernames = [
+            row.username
+            for row in db.query(share_model)
+            .filter(getattr(share_model, key) == target_id)
+            .order_by(share_model.username)
+        ]
+        return {
+            "visibility": target.visibility or "private",
+            "team_id": target.team_id,
+            "shared_with": usernames,
+        }
+
+
+def set_sharing(
+    kind: str,
+    target_id: int,
+    visibility: str,
+    team_id: Optional[int],
+    shared_with: List[str],
+    shared_by: str,
+) -> Optional[Dict[str, Any]]:
+    """Replace who an artifact or collection is shared with. Returns the new sharing, or None if it doesn't exist."""
+    model, share_model, key = _SHARE_MODELS[kind]
+    with get_db() as db:
+        _attribute_changes(db, shared_by)
+        target = db.query(model).filter(model.id == target_id).first()
+        if target is None:
+            return None
+        target.visibility = visibility
+        target.team_id = team_id
+        # Lets syncing clients pick up the change, or drop what they may no longer see
+        if kind == "artifact":
+            target.updated_at = datetime.utcnow()
+        else:
+            db.query(Artifact).filter(Artifact.collection_id == target_id).update(
+                {"updated_at": datetime.utcnow()}, synchronize_session=False
+            )
+        wanted = set(shared_with) - {target.owner_id}
+        existing = db.query(share_model).filter(getattr(share_model, key) == target_id).all()
+        for share in existing:
+            if share.username not in wanted:
+                db.delete(share)
+        for username in wanted - {share.username for share in existing}:
+            db.add(share_model(**{key: target_id, "username": username, "shared_by": shared_by}))
+        db.flush()
+    return get_sharing(kind, target_id)
+
+
+def create_collection(
+    name: str,
+    owner_id: str,
+    description: Optional[str] = None,
+    visibility: str = "private",
+    team_id: Optional[int] = None,
+) -> Dict[str, Any]:
+    with get_db() as db:
+        collection = Collection(
+            name=name, description=description, owner_id=owner_id, visibility=visibility, team_id=team_id
+        )
+        db.add(collection)
+        db.flush()
+        return collection.to_dict()
+
+
+def get_collection(collection_id: int, viewer: Optional[Viewer] = None) -> Optional[Dict[str, Any]]:
+    """A collection with its artifact count, or None if it doesn't exist or the viewer can't see it."""
+    with get_db() as db:
+        q = db.query(Collection).filter(Collection.id == collection_id)
+        if viewer is not None and not viewer.sees_all:
+            q = q.filter(Collection.id.in_(_visible_collection_ids(db, viewer)))
+        collection = q.first()
+        if collection is None:
+            return None
+        count = (
+            db.query(Artifact.id)
+            .filter(Artifact.collection_id == collection_id, Artifact.deleted_at.is_(None))
+            .count()
+        )
+        return {**collection.to_dict(), "artifact_count": count}
+
+
+def list_collections(viewer: Viewer, scope: str = "all") -> List[Dict[str, Any]]:
+    """Collections the viewer can see, with how many artifacts each holds; ``scope`` as for artifacts."""
+    with get_db() as db:
+        q = db.query(Collection)
+        if not viewer.sees_all:
+            q = q.filter(Collection.id.in_(_visible_collection_ids(db, viewer)))
+        if viewer.username and scope == "mine":
+            q = q.filter(Collection.owner_id == viewer.username)
+        elif viewer.username and scope == "shared":
+            q = q.filter(Collection.owner_id != viewer.username)
+        counts = dict(
+            db.query(Artifact.collection_id, func.count(Artifact.id))
+            .filter(Artifact.collection_id.isnot(None), Artifact.deleted_at.is_(None))
+            .group_by(Artifact.collection_id)
+            .all()
+        )
+        return [
+            {**collection.to_dict(), "artifact_count": counts.get(collection.id, 0)}
+            for collection in q.order_by(Collection.name, Collection.id)
+        ]
+
+
+def delete_collection(collection_id: int, deleted_by: Optional[str] = None) -> bool:
+    """Delete a collection; its artifacts stay, outside any collection. Returns True if deleted."""
+    with get_db() as db:
+        _attribute_changes(db, deleted_by)
+        collection = db.query(Collection).filter(Collection.id == collection_id).first()
+        if collection is None:
+            return False
+        _add_revisions(
+            db,
+            [
+                (row.id, "updated", {"collection_id": [collection_id, None]})
+                for row in db.query(Artifact.id).filter(Artifact.collection_id == collection_id)
+            ],
+        )
+        db.query(Artifact).filter(Artifact.collection_id == collection_id).update(
+            {"collection_id": None, "updated_at": datetime.utcnow()}, synchronize_session=False
+        )
+        db.query(CollectionShare).filter(CollectionShare.collection_id == collection_id).delete(
+            synchronize_session=False
+        )
+        db.delete(collection)
+        db.flush()
+        return True
+
+
 def save_artifact(
     artifact_data: Dict[str, Any],
     image_bytes: bytes = None,
     thumbnail_bytes: bytes = None,
     model_3d_bytes: bytes = None,
+    changed_by: Optional[str] = None,
 ) -> int:
-    """Persist a newly analysed artifact and return its primary key."""
+    """Persist a newly analysed artifact and return its primary key; its history credits ``changed_by``, else the uploader."""
     with get_db() as db:
+        _attribute_changes(db, changed_by or artifact_data.get("uploaded_by"))
         tags_list = _normalize_tags_input(artifact_data.get("tags"))
         # Handle both old format (image_data in artifact_data) and new format (separate params)
         image_to_save = image_bytes if image_bytes else artifact_data.get("image_data")
@@ -248,15 +1070,34 @@ def save_artifact(
             function=artifact_data.get("function"),
             rarity=artifact_data.get("rarity"),
             confidence=artifact_data.get("confidence", 0.0),
-            image_data=image_to_save,
-            thumbnail=thumbnail_to_save,
+            image_hash=_store_image(image_to_save),
+            thumbnail_hash=_store_image(thumbnail_to_save),
+            original_format=artifact_data.get("original_format"),
+            original_size=artifact_data.get("original_size"),
             model_3d_data=model_3d_to_save,
             model_3d_format=artifact_data.get("model_3d_format"),
-            analyzed_at=datetime.utcnow(),
+            uploaded_at=artifact_data.get("uploaded_at") or datetime.utcnow(),
+            analyzed_at=artifact_data.get("analyzed_at") or datetime.utcnow(),
+            updated_at=datetime.utcnow(),
             tags=",".join(tags_list) if tags_list else None,
             form_data=artifact_data.get("form_data"),
             tier=artifact_data.get("tier"),
             uploaded_by=artifact_data.get("uploaded_by"),
+            owner_id=artifact_data.get("owner_id"),
+            visibility=artifact_data.get("visibility") or "private",
+            team_id=artifact_data.get("team_id"),
+            collection_id=artifact_data.get("collection_id"),
+            notes=artifact_data.get("notes"),
+            provenance=artifact_data.get("provenance"),
+            historical_context=artifact_data.get("historical_context"),
+            references=artifact_data.get("references"),
+            verification_status=artifact_data.get("verification_status") or "pending",
+            verified_by=artifact_data.get("verified_by"),
+            verified_at=artifact_data.get("verified_at"),
+            verification_comments=artifact_data.get("verification_comments"),
+            captured_at=artifact_data.get("captured_at"),
+            latitude=artifact_data.get("latitude"),
+            longitude=artifact_data.get("longitude"),
         )
         db.add(artifact)
         db.flush()  # Obtain PK without committing twice
@@ -264,103 +1105,260 @@ def save_artifact(
         return artifact_id
 
 
+# Sort keys accepted by get_all_artifacts
+ARTIFACT_SORT_COLUMNS = {
+    "uploaded_at": Artifact.uploaded_at,
+    "name": Artifact.name,
+    "confidence": Artifact.confidence,
+    "tier": Artifact.tier,
+}
+
+
+def _escape_like(value: str) -> str:
+    return value.replace("\\", "\\\\").replace("%", "\\%").replace("_", "\\_")
+
+
+def _filtered_artifacts(
+    db,
+    viewer: Optional[Viewer] = None,
+    scope: str = "all",
+    collection_id: Optional[int] = None,
+    uploaded_by: Optional[str] = None,
+    era: Optional[str] = None,
+    tier: Optional[str] = None,
+    tags: Optional[Union[List[str], str]] = None,
+    min_confidence: Optional[float] = None,
+    verified: Optional[bool] = None,
+    bbox: Optional[Tuple[float, float, float, float]] = None,
+):
+    """Query for the artifacts matching the list filters.
+
+    ``era`` matches anywhere in the estimated age ("roman" finds "Roman
+    Empire, 2nd century"); every tag in ``tags`` must be on the artifact;
+    ``verified`` splits verified artifacts from pending and rejected ones;
+    ``bbox`` (west, south, east, north degrees) keeps photos taken inside it,
+    and crosses the antimeridian when west > east.
+    Only artifacts ``viewer`` may see are included (see ``_scoped_artifacts``).
+    """
+    q = _scoped_artifacts(db, db.query(Artifact), viewer, scope)
+    if collection_id is not None:
+        q = q.filter(Artifact.collection_id == collection_id)
+    if uploaded_by:
+        q = q.filter(Artifact.uploaded_by == uploaded_by)
+    if era:
+        q = q.filter(Artifact.age.ilike(f"%{_escape_like(era.strip())}%", escape="\\"))
+    if tier:
+        q = q.filter(func.lower(Artifact.tier) == tier.strip().lower())
+    for tag in _normalize_tags_input(tags):
+        # Tags are stored comma-separated; match whole tags only
+        padded = func.lower(literal(",").concat(Artifact.tags).concat(","))
+        q = q.filter(padded.like(f"%,{_escape_like(tag.lower())},%", escape="\\"))
+    if min_confidence is not None:
+        q = q.filter(Artifact.confidence >= min_confidence)
+    if verified is True:
+        q = q.filter(Artifact.verification_status == "verified")
+    elif verified is False:
+        q = q.filter(
+            or_(Artifact.verification_status != "verified", Artifact.verification_status.is_(None))
+        )
+    if bbox is not None:
+        west, south, east, north = bbox
+        q = q.filter(Artifact.latitude.between(south, north))
+        if west <= east:
+            q = q.filter(Artifact.longitude.between(west, east))
+        else:
+            q = q.filter(or_(Artifact.longitude >= west, Artifact.longitude <= east))
+    return q
+
+
+def _without_blobs(q, include_blobs: bool):
+    """Leave the 3D model and pre-image-store blobs unread, unless ``include_blobs``."""
+    if include_blobs:
+        return q
+    return q.options(defer(Artifact.model_3d_data), defer(Artifact.image_data), defer(Artifact.thumbnail))
+
+
 def get_all_artifacts(
-    limit: int = 100, offset: int = 0, include_images: bool = True
+    limit: Optional[int] = 100,
+    offset: int = 0,
+    sort: str = "uploaded_at",
+    order: str = "desc",
+    include_blobs: bool = True,
+    **filters: Any,
 ) -> List[Dict[str, Any]]:
-    """Return a paginated list of artifacts; optionally embed base64 image data."""
+    """Return a filtered, sorted page of artifacts (``limit=None`` for all); images are referenced by hash.
+
+    ``filters`` are those of ``_filtered_artifacts``. Lists that don't show
+    the 3D model pass ``include_blobs=False`` to skip reading it.
+    """
     with get_db() as db:
+        q = _without_blobs(_filtered_artifacts(db, **filters), include_blobs)
+        column = ARTIFACT_SORT_COLUMNS[sort]
+        direction = column.asc() if order == "asc" else column.desc()
         artifacts = (
-            db.query(Artifact)
-            .order_by(Artifact.uploaded_at.desc())
+            q.order_by(direction.nulls_last(), Artifact.id.desc())
             .limit(limit)
             .offset(offset)
             .all()
         )
-        results: List[Dict[str, Any]] = []
-        for artifact in artifacts:
-            data = artifact.to_dict()
-            # Convert binary fields to base64 strings for JSON serialization
-            if include_images:
-                if data.get("image_data") is not None:
-                    data["image_data"] = base64.b64encode(data["image_data"]).decode(
-                        "utf-8"
-                    )
-                if data.get("thumbnail") is not None:
-                    data["thumbnail"] = base64.b64encode(data["thumbnail"]).decode(
-                        "utf-8"
-                    )
-            else:
-                # Remove binary fields if not including images
-                data.pop("image_data", None)
-                data.pop("thumbnail", None)
-            results.append(data)
-        return results
+        return [artifact.to_dict(include_blobs) for artifact in artifacts]
 
 
-def get_artifact_by_id(artifact_id: int) -> Optional[Dict[str, Any]]:
-    """Fetch a single artifact by its primary key."""
+def count_artifacts(**filters: Any) -> int:
+    """How many artifacts match the ``get_all_artifacts`` filters."""
     with get_db() as db:
-        artifact = db.query(Artifact).filter(Artifact.id == artifact_id).first()
+        return _filtered_artifacts(db, **filters).count()
+
+
+def count_stored_artifacts() -> Dict[str, int]:
+    """How many artifacts are stored, as {"active": n, "trashed": n}."""
+    with get_db() as db:
+        trashed = Artifact.deleted_at.isnot(None)
+        rows = db.query(trashed, func.count(Artifact.id)).group_by(trashed).all()
+        counts = {"active": 0, "trashed": 0}
+        for is_trashed, count in rows:
+            counts["trashed" if is_trashed else "active"] = count
+        return counts
+
+
+def find_artifact_by_image(image_bytes: bytes, viewer: Optional[Viewer] = None) -> Optional[Dict[str, Any]]:
+    """The oldest artifact ``viewer`` can see whose image has exactly these bytes (same content hash), or None."""
+    image_hash = hashlib.sha256(image_bytes).hexdigest()
+    with get_db() as db:
+        artifact = (
+            _visible_artifacts(db, db.query(Artifact), viewer)
+            .filter(Artifact.image_hash == image_hash)
+            .order_by(Artifact.id)
+            .first()
+        )
+        return artifact.to_dict() if artifact else None
+
+
+def image_access(image_hash: str, viewer: Viewer) -> Optional[str]:
+    """
+    Whether ``viewer`` may fetch a stored image: "public" if a public artifact
+    uses it, "private" if only artifacts they can see (or their own in the
+    trash; any for staff) do, None if none they can see.
+    """
+    uses = (Artifact.image_hash == image_hash) | (Artifact.thumbnail_hash == image_hash)
+    with get_db() as db:
+        if _visible_artifacts(db, db.query(Artifact.id), None).filter(uses, Artifact.visibility == "public").first():
+            return "public"
+        if _visible_artifacts(db, db.query(Artifact.id), viewer).filter(uses).first():
+            return "private"
+        if viewer.username:
+            trashed = db.query(Artifact.id).filter(uses, Artifact.deleted_at.isnot(None))
+            if not viewer.sees_all:
+                trashed = trashed.filter(Artifact.owner_id == viewer.username)
+            if trashed.first():
+                return "private"
+        return None
+
+
+def get_artifacts_by_ids(artifact_ids: List[int], viewer: Optional[Viewer] = None) -> Dict[int, Dict[str, Any]]:
+    """The artifacts among ``artifact_ids`` that exist and ``viewer`` can see, by id."""
+    if not artifact_ids:
+        return {}
+    with get_db() as db:
+        artifacts = _visible_artifacts(db, db.query(Artifact), viewer).filter(Artifact.id.in_(artifact_ids)).all()
+        return {artifact.id: artifact.to_dict() for artifact in artifacts}
+
+
+def get_artifact_by_id(artifact_id: int, viewer: Optional[Viewer] = None) -> Optional[Dict[str, Any]]:
+    """Fetch a single artifact by its primary key; None if ``viewer`` can't see it."""
+    with get_db() as db:
+        artifact = _visible_artifacts(db, db.query(Artifact), viewer).filter(Artifact.id == artifact_id).first()
         if not artifact:
             return None
-        # Convert to dict which now includes image_data and thumbnail
-        data = artifact.to_dict()
-        # Convert binary fields to base64 strings for JSON serialization
-        if data.get("image_data") is not None:
-            data["image_data"] = base64.b64encode(data["image_data"]).decode("utf-8")
-        if data.get("thumbnail") is not None:
-            data["thumbnail"] = base64.b64encode(data["thumbnail"]).decode("utf-8")
-        return data
+        return artifact.to_dict()
 
 
-def search_artifacts(
+def _search_query(
+    db,
     query: str,
-    limit: int = 50,
     tags: Optional[List[str]] = None,
-) -> List[Dict[str, Any]]:
-    """Search artifacts by keywords in text fields and optional tags."""
+    uploaded_by: Optional[str] = None,
+    viewer: Optional[Viewer] = None,
+    scope: str = "all",
+):
+    """Query for artifacts matching a search, best match first.
 
+    With the FTS5 index, results are ranked by relevance and quoted phrases
+    and word prefixes are supported (see ``_fts_query``); otherwise each
+    keyword must appear somewhere in the text and results are newest first.
+    """
     keywords = [kw for kw in (query or "").split() if kw]
     tag_filters = _normalize_tags_input(tags)
+    match = _fts_query(query) if _search_index_ready else None
 
-    with get_db() as db:
-        q = db.query(Artifact)
+    q = _scoped_artifacts(db, db.query(Artifact), viewer, scope)
 
-        if keywords:
-            for kw in keywords:
-                pattern = f"%{kw}%"
-                q = q.filter(
-                    Artifact.id.in_(
-                        db.query(Artifact.id).filter(
-                            (Artifact.name.ilike(pattern))
-                            | (Artifact.description.ilike(pattern))
-                            | (Artifact.cultural_context.ilike(pattern))
-                            | (Artifact.material.ilike(pattern))
-                            | (Artifact.tags.ilike(pattern))
-                        )
+    if match:
+        ranked = (
+            text(
+                f"SELECT rowid AS artifact_id, bm25(artifacts_fts, {_FTS_WEIGHTS}) AS rank "
+                "FROM artifacts_fts WHERE artifacts_fts MATCH :match"
+            )
+            .bindparams(match=match)
+            .columns(artifact_id=Integer, rank=Float)
+            .subquery()
+        )
+        # bm25 scores are negative; lower is more relevant
+        q = q.join(ranked, ranked.c.artifact_id == Artifact.id).order_by(ranked.c.rank)
+    elif keywords:
+        for kw in keywords:
+            pattern = f"%{kw}%"
+            q = q.filter(
+                Artifact.id.in_(
+                    db.query(Artifact.id).filter(
+                        (Artifact.name.ilike(pattern))
+                        | (Artifact.description.ilike(pattern))
+                        | (Artifact.cultural_context.ilike(pattern))
+                        | (Artifact.material.ilike(pattern))
+                        | (Artifact.tags.ilike(pattern))
                     )
                 )
-        else:
-            # No keywords: still allow tag filtering without restricting base query
-            q = q.filter(True)  # no-op filter for consistent chaining
+            )
+    else:
+        # No keywords: still allow tag filtering without restricting base query
+        q = q.filter(True)  # no-op filter for consistent chaining
 
-        if tag_filters:
-            for t in tag_filters:
-                q = q.filter(Artifact.tags.ilike(f"%{t}%"))
+    if tag_filters:
+        for t in tag_filters:
+            q = q.filter(Artifact.tags.ilike(f"%{t}%"))
 
-        artifacts = q.order_by(Artifact.uploaded_at.desc()).limit(limit).all()
-        results: List[Dict[str, Any]] = []
-        for artifact in artifacts:
-            data = artifact.to_dict()
-            # Convert binary fields to base64 strings for JSON serialization
-            if data.get("image_data") is not None:
-                data["image_data"] = base64.b64encode(data["image_data"]).decode(
-                    "utf-8"
-                )
-            if data.get("thumbnail") is not None:
-                data["thumbnail"] = base64.b64encode(data["thumbnail"]).decode("utf-8")
-            results.append(data)
-        return results
+    if uploaded_by:
+        q = q.filter(Artifact.uploaded_by == uploaded_by)
+
+    return q.order_by(Artifact.uploaded_at.desc(), Artifact.id.desc())
+
+
+def search_artifacts(
+    query: str,
+    limit: int = 50,
+    offset: int = 0,
+    tags: Optional[List[str]] = None,
+    uploaded_by: Optional[str] = None,
+    viewer: Optional[Viewer] = None,
+    scope: str = "all",
+    include_blobs: bool = True,
+) -> List[Dict[str, Any]]:
+    """Search artifacts by keywords in text fields and optional tags (see ``_search_query``)."""
+    with get_db() as db:
+        q = _search_query(db, query, tags=tags, uploaded_by=uploaded_by, viewer=viewer, scope=scope)
+        q = _without_blobs(q, include_blobs)
+        return [artifact.to_dict(include_blobs) for artifact in q.limit(limit).offset(offset).all()]
+
+
+def count_search_results(
+    query: str,
+    tags: Optional[List[str]] = None,
+    uploaded_by: Optional[str] = None,
+    viewer: Optional[Viewer] = None,
+    scope: str = "all",
+) -> int:
+    with get_db() as db:
+        return _search_query(db, query, tags=tags, uploaded_by=uploaded_by, viewer=viewer, scope=scope).count()
 
 
 def update_artifact_verification(
@@ -368,9 +1366,11 @@ def update_artifact_verification(
     status: str,
     verified_by: Optional[str] = None,
     comments: Optional[str] = None,
+    changed_by: Optional[str] = None,
 ) -> Optional[Dict[str, Any]]:
     """Change verification fields for a given artifact."""
     with get_db() as db:
+        _attribute_changes(db, changed_by or verified_by)
         artifact = db.query(Artifact).filter(Artifact.id == artifact_id).first()
         if not artifact:
             return None
@@ -382,40 +1382,32 @@ def update_artifact_verification(
             artifact.verification_comments = comments
         if status.lower() == "verified":
             artifact.verified_at = datetime.utcnow()
+        artifact.updated_at = datetime.utcnow()
 
         db.flush()
-        data = artifact.to_dict()
-        # Convert binary fields to base64 strings for JSON serialization
-        if data.get("image_data") is not None:
-            data["image_data"] = base64.b64encode(data["image_data"]).decode("utf-8")
-        if data.get("thumbnail") is not None:
-            data["thumbnail"] = base64.b64encode(data["thumbnail"]).decode("utf-8")
-        return data
+        return artifact.to_dict()
 
 
 def update_artifact_tags(
-    artifact_id: int, tags: Optional[Union[List[str], str]]
+    artifact_id: int, tags: Optional[Union[List[str], str]], changed_by: Optional[str] = None
 ) -> Optional[Dict[str, Any]]:
     """Update the tags for a given artifact and return its dict."""
     with get_db() as db:
+        _attribute_changes(db, changed_by)
         artifact = db.query(Artifact).filter(Artifact.id == artifact_id).first()
         if not artifact:
             return None
         tags_list = _normalize_tags_input(tags)
         artifact.tags = ",".join(tags_list) if tags_list else None
+        artifact.updated_at = datetime.utcnow()
         db.flush()
-        data = artifact.to_dict()
-        # Convert binary fields to base64 strings for JSON serialization
-        if data.get("image_data") is not None:
-            data["image_data"] = base64.b64encode(data["image_data"]).decode("utf-8")
-        if data.get("thumbnail") is not None:
-            data["thumbnail"] = base64.b64encode(data["thumbnail"]).decode("utf-8")
-        return data
+        return artifact.to_dict()
 
 
-def update_artifact(artifact_id: int, update_data: Dict[str, Any]) -> bool:
+def update_artifact(artifact_id: int, update_data: Dict[str, Any], changed_by: Optional[str] = None) -> bool:
     """Update an artifact with the provided data. Returns True if updated."""
     with get_db() as db:
+        _attribute_changes(db, changed_by)
         artifact = db.query(Artifact).filter(Artifact.id == artifact_id).first()
         if not artifact:
             return False
@@ -440,12 +1432,911 @@ def update_artifact(artifact_id: int, update_data: Dict[str, Any]) -> bool:
         return True
 
 
-def delete_artifact(artifact_id: int) -> bool:
-    """Delete an artifact by id. Returns True if deleted."""
+# Columns overwrite_artifact may set, e.g. from an imported bundle
+def list_tags(viewer: Optional[Viewer] = None, contains: Optional[str] = None, limit: int = 100) -> List[Dict[str, Any]]:
+    """
+    Tags on the artifacts ``viewer`` can see, with how many carry each, most
+    used first. ``contains`` keeps those with that text in them, any case.
+    """
+    needle = " ".join((contains or "").split()).lower()
+    counts: Dict[str, int] = {}
+    with get_db() as db:
+        q = _visible_artifacts(db, db.query(Artifact.tags), viewer).filter(Artifact.tags.isnot(None))
+        if needle:
+            q = q.filter(Artifact.tags.ilike(f"%{needle}%"))
+        for (tags,) in q:
+            for tag in _normalize_tags_input(tags):
+                if needle in tag.lower():
+                    counts[tag] = counts.get(tag, 0) + 1
+    ranked = sorted(counts.items(), key=lambda item: (-item[1], item[0].lower(), item[0]))
+    return [{"tag": tag, "count": count} for tag, count in ranked[:limit]]
+
+
+def merge_tags(sources: List[str], target: str, changed_by: Optional[str] = None) -> int:
+    """
+    Replace every tag in ``sources`` with ``target`` on all artifacts, the
+    trash included; renaming is merging one tag. Tags match exactly, so
+    "Roman" and "roman" can be merged. Returns how many artifacts changed.
+    """
+    target = " ".join(target.split())
+    sources_set = {" ".join(source.split()) for source in sources} - {target}
+    if not target or not sources_set:
+        return 0
+    now = datetime.utcnow()
+    changed = 0
+    with get_db() as db:
+        _attribute_changes(db, changed_by)
+        candidates = db.query(Artifact).filter(or_(*(Artifact.tags.ilike(f"%{source}%") for source in sources_set)))
+        for artifact in candidates:
+            tags = _normalize_tags_input(artifact.tags)
+            if not sources_set.intersection(tags):
+                continue
+            merged = _normalize_tags_input([target if tag in sources_set else tag for tag in tags])
+            artifact.tags = ",".join(merged)
+            # Lets syncing clients pick up the change
+            artifact.updated_at = now
+            changed += 1
+        db.flush()
+    return changed
+
+
+OVERWRITABLE_FIELDS = (
+    "name", "value", "age", "description", "cultural_context", "material", "function", "rarity",
+    "confidence", "tags", "notes", "form_data", "tier", "provenance", "historical_context", "references",
+    "captured_at", "latitude", "longitude", "verification_status", "verified_by", "verified_at", "verification_comments",
+)
+# Columns revert_artifact_revision may set back; ownership, sharing, the
+# trash and locks have endpoints of their own
+REVERTIBLE_FIELDS = OVERWRITABLE_FIELDS + ("collection_id",)
+
+
+def overwrite_artifact(artifact_id: int, fields: Dict[str, Any], changed_by: Optional[str] = None) -> bool:
+    """Replace an artifact's descriptive fields (see OVERWRITABLE_FIELDS) with ``fields``. False if it doesn't exist."""
     with get_db() as db:
+        _attribute_changes(db, changed_by)
         artifact = db.query(Artifact).filter(Artifact.id == artifact_id).first()
         if not artifact:
             return False
-        db.delete(artifact)
+        for field in OVERWRITABLE_FIELDS:
+            if field not in fields:
+                continue
+            value = fields[field]
+            if field == "tags":
+                tags_list = _normalize_tags_input(value)
+                value = ",".join(tags_list) if tags_list else None
+            setattr(artifact, field, value)
+        artifact.updated_at = datetime.utcnow()
+        return True
+
+
+class StaleArtifactError(Exception):
+    """The artifact changed after the version an update was based on."""
+
+
+class LockedArtifactError(Exception):
+    """A curator locked the artifact before an update could be written."""
+
+    def __init__(self, locked_by: Optional[str], lock_reason: Optional[str]):
+        super().__init__(f"Artifact is locked by {locked_by}: {lock_reason}")
+        self.locked_by = locked_by
+        self.lock_reason = lock_reason
+
+
+def patch_artifact(
+    artifact_id: int,
+    changes: Dict[str, Any],
+    expected_version: Optional[str] = None,
+    changed_by: Optional[str] = None,
+) -> Optional[Dict[str, Any]]:
+    """Apply a partial update and return the updated artifact, or None if it doesn't exist.
+
+    ``changes`` may hold name, description, era, tags, notes, collection_id
+    and verification_status. With ``expected_version`` (an earlier ``version``)
+    the update only happens if nobody changed the artifact since; otherwise
+    StaleArtifactError is raised. A locked artifact raises LockedArtifactError.
+    The checks and the write are one UPDATE. ``changes`` must not be empty.
+    """
+    now = datetime.utcnow()
+    values: Dict[str, Any] = {"updated_at": now}
+    for field in ("name", "description", "notes", "collection_id"):
+        if field in changes:
+            values[field] = changes[field]
+    if "era" in changes:
+        values["age"] = changes["era"]
+    if "tags" in changes:
+        tags_list = _normalize_tags_input(changes["tags"])
+        values["tags"] = ",".join(tags_list) if tags_list else None
+    if "verification_status" in changes:
+        values["verification_status"] = changes["verification_status"]
+        if changes["verification_status"] == "verified":
+            values["verified_at"] = now
+
+    tracked = [field for field in values if field in _TRACKED_FIELDS]
+    with get_db() as db:
+        _attribute_changes(db, changed_by)
+        # The UPDATE skips the session, so the revision is worked out here
+        before = db.query(*(getattr(Artifact, field) for field in tracked)).filter(Artifact.id == artifact_id).first()
+        q = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.locked_at.is_(None))
+        if expected_version is not None:
+            try:
+                expected = datetime.fromisoformat(expected_version)
+            except ValueError:
+                expected = None
+            q = q.filter(func.coalesce(Artifact.updated_at, Artifact.uploaded_at) == expected)
+        if q.update(values, synchronize_session=False) == 0:
+            current = (
+                db.query(Artifact.locked_at, Artifact.locked_by, Artifact.lock_reason)
+                .filter(Artifact.id == artifact_id)
+                .first()
+            )
+            if current is None:
+                return None
+            if current.locked_at is not None:
+                raise LockedArtifactError(current.locked_by, current.lock_reason)
+            raise StaleArtifactError()
+        diff = {
+            field: [_history_value(old), _history_value(values[field])]
+            for field, old in zip(tracked, before)
+            if old != values[field]
+        }
+        _add_revisions(db, [(artifact_id, "updated", diff)])
+        db.flush()
+        return db.query(Artifact).filter(Artifact.id == artifact_id).one().to_dict()
+
+
+def anonymize_user_references(username: str, replacement: str) -> int:
+    """
+    Replace a username on the artifacts and collections it uploaded, owns or
+    verified, and drop what was shared with it, its webhooks and its analysis
+    usage. Returns the number of artifacts changed.
+    """
+    with get_db() as db:
+        _attribute_changes(db, replacement)
+        artifacts = (
+            db.query(Artifact)
+            .filter(
+                (Artifact.uploaded_by == username)
+                | (Artifact.verified_by == username)
+                | (Artifact.owner_id == username)
+            )
+            .all()
+        )
+        for artifact in artifacts:
+            if artifact.uploaded_by == username:
+                artifact.uploaded_by = replacement
+            if artifact.verified_by == username:
+                artifact.verified_by = replacement
+            if artifact.owner_id == username:
+                artifact.owner_id = replacement
+            # Lets syncing clients pick up the change
+            artifact.updated_at = datetime.utcnow()
+        db.query(Collection).filter(Collection.owner_id == username).update(
+            {"owner_id": replacement}, synchronize_session=False
+        )
+        for share_model in (ArtifactShare, CollectionShare):
+            db.query(share_model).filter(share_model.username == username).delete(synchronize_session=False)
+        db.query(AnalysisJob).filter(AnalysisJob.requested_by == username).update(
+            {"requested_by": None}, synchronize_session=False
+        )
+        webhook_ids = [row.id for row in db.query(Webhook.id).filter(Webhook.owner_id == username)]
+        if webhook_ids:
+            db.query(WebhookDelivery).filter(WebhookDelivery.webhook_id.in_(webhook_ids)).delete(
+                synchronize_session=False
+            )
+            db.query(Webhook).filter(Webhook.id.in_(webhook_ids)).delete(synchronize_session=False)
+        db.query(AnalysisUsage).filter(AnalysisUsage.username == username).delete(synchronize_session=False)
+        db.flush()
+        return len(artifacts)
+
+
+def delete_artifact(artifact_id: int, deleted_by: Optional[str] = None) -> bool:
+    """
+    Move an artifact to the trash. It keeps its images, shares, embedding
+    and history until purged, and syncing clients see it as deleted.
+    Returns True if it was there to delete.
+    """
+    now = datetime.utcnow()
+    with get_db() as db:
+        _attribute_changes(db, deleted_by)
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.is_(None)).first()
+        if not artifact:
+            return False
+        artifact.deleted_at = now
+        artifact.deleted_by = deleted_by
+        artifact.updated_at = now
+        db.merge(ArtifactTombstone(artifact_id=artifact_id, deleted_at=now))
         db.flush()
         return True
+
+
+def restore_artifact(artifact_id: int, restored_by: Optional[str] = None) -> Optional[Dict[str, Any]]:
+    """Take an artifact out of the trash; None if it isn't in it."""
+    with get_db() as db:
+        _attribute_changes(db, restored_by)
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.isnot(None)).first()
+        if not artifact:
+            return None
+        artifact.deleted_at = None
+        artifact.deleted_by = None
+        # Newer than the tombstone was, so syncing clients fetch it again
+        artifact.updated_at = datetime.utcnow()
+        db.query(ArtifactTombstone).filter(ArtifactTombstone.artifact_id == artifact_id).delete(
+            synchronize_session=False
+        )
+        db.flush()
+        return artifact.to_dict()
+
+
+def get_deleted_artifact(artifact_id: int) -> Optional[Dict[str, Any]]:
+    """An artifact in the trash, or None."""
+    with get_db() as db:
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.isnot(None)).first()
+        return artifact.to_dict() if artifact else None
+
+
+def list_deleted_artifacts(viewer: Viewer, limit: int = 50, offset: int = 0) -> Tuple[List[Dict[str, Any]], int]:
+    """The viewer's own trashed artifacts (every one for staff), most recently deleted first, and their total."""
+    with get_db() as db:
+        q = db.query(Artifact).filter(Artifact.deleted_at.isnot(None))
+        if not viewer.sees_all:
+            q = q.filter(Artifact.owner_id == viewer.username)
+        total = q.count()
+        artifacts = q.order_by(Artifact.deleted_at.desc(), Artifact.id.desc()).limit(limit).offset(offset).all()
+        return [artifact.to_dict() for artifact in artifacts], total
+
+
+def purge_artifact(artifact_id: int) -> bool:
+    """
+    Delete a trashed artifact for good, with its shares, embedding, flags,
+    history and unshared images. Returns True if purged.
+    """
+    with get_db() as db:
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.isnot(None)).first()
+        if not artifact:
+            return False
+        image_hashes = (artifact.image_hash, artifact.thumbnail_hash)
+        db.query(ArtifactShare).filter(ArtifactShare.artifact_id == artifact_id).delete(synchronize_session=False)
+        db.query(ArtifactEmbedding).filter(ArtifactEmbedding.artifact_id == artifact_id).delete(synchronize_session=False)
+        db.query(ArtifactFlag).filter(ArtifactFlag.artifact_id == artifact_id).delete(synchronize_session=False)
+        db.query(ArtifactRevision).filter(ArtifactRevision.artifact_id == artifact_id).delete(synchronize_session=False)
+        db.delete(artifact)
+        db.flush()
+    # Only once the delete is committed
+    _release_images(*image_hashes)
+    return True
+
+
+def purge_deleted_artifacts(deleted_before: datetime) -> int:
+    """Purge artifacts that went into the trash before ``deleted_before``; returns how many."""
+    with get_db() as db:
+        artifact_ids = [
+            row.id
+            for row in db.query(Artifact.id).filter(Artifact.deleted_at.isnot(None), Artifact.deleted_at < deleted_before)
+        ]
+    return sum(purge_artifact(artifact_id) for artifact_id in artifact_ids)
+
+
+# ----------------------------------------------------------------------
+# Moderation
+# ----------------------------------------------------------------------
+def set_artifact_lock(
+    artifact_id: int, locked_by: Optional[str], reason: Optional[str] = None, changed_by: Optional[str] = None
+) -> Optional[Dict[str, Any]]:
+    """
+    Lock an artifact against edits, or unlock it when ``locked_by`` is None;
+    None if there is no such artifact. ``changed_by`` is who unlocked it.
+    """
+    with get_db() as db:
+        _attribute_changes(db, locked_by or changed_by)
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.is_(None)).first()
+        if not artifact:
+            return None
+        artifact.locked_at = datetime.utcnow() if locked_by else None
+        artifact.locked_by = locked_by
+        artifact.lock_reason = reason if locked_by else None
+        artifact.updated_at = datetime.utcnow()
+        db.flush()
+        return artifact.to_dict()
+
+
+def override_identification(artifact_id: int, fields: Dict[str, Any], overridden_by: str) -> Optional[Dict[str, Any]]:
+    """
+    Replace an artifact's identification with a curator's: ``fields`` may
+    hold name, description, age, cultural_context, material and function.
+    None if there is no such artifact.
+    """
+    with get_db() as db:
+        _attribute_changes(db, overridden_by, "identified")
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.is_(None)).first()
+        if not artifact:
+            return None
+        for key, value in fields.items():
+            setattr(artifact, key, value)
+        artifact.identification_overridden_by = overridden_by
+        artifact.identification_overridden_at = artifact.updated_at = datetime.utcnow()
+        db.flush()
+        return artifact.to_dict()
+
+
+def create_flag(artifact_id: int, reason: str, flagged_by: str) -> Dict[str, Any]:
+    with get_db() as db:
+        flag = ArtifactFlag(artifact_id=artifact_id, reason=reason, flagged_by=flagged_by)
+        db.add(flag)
+        db.flush()
+        return flag.to_dict()
+
+
+def get_flag(flag_id: int) -> Optional[Dict[str, Any]]:
+    with get_db() as db:
+        flag = db.query(ArtifactFlag).filter(ArtifactFlag.id == flag_id).first()
+        return flag.to_dict() if flag else None
+
+
+def list_flags(artifact_id: int) -> List[Dict[str, Any]]:
+    """An artifact's flags, open and resolved, newest first."""
+    with get_db() as db:
+        flags = (
+            db.query(ArtifactFlag)
+            .filter(ArtifactFlag.artifact_id == artifact_id)
+            .order_by(ArtifactFlag.created_at.desc(), ArtifactFlag.id.desc())
+            .all()
+        )
+        return [flag.to_dict() for flag in flags]
+
+
+def resolve_flag(flag_id: int, resolved_by: str, resolution: str) -> Optional[Dict[str, Any]]:
+    """Close an open flag; None if there is no such flag or it was already resolved."""
+    with get_db() as db:
+        flag = db.query(ArtifactFlag).filter(ArtifactFlag.id == flag_id, ArtifactFlag.resolved_at.is_(None)).first()
+        if not flag:
+            return None
+        flag.resolved_at = datetime.utcnow()
+        flag.resolved_by = resolved_by
+        flag.resolution = resolution
+        db.flush()
+        return flag.to_dict()
+
+
+def moderation_queue(limit: int = 50, offset: int = 0) -> Tuple[List[Dict[str, Any]], int]:
+    """
+    Artifacts with open flags, the longest-waiting first, each with its
+    open flags under "open_flags"; and how many there are. Trashed
+    artifacts are left out while they are in the trash.
+    """
+    with get_db() as db:
+        waiting = (
+            db.query(ArtifactFlag.artifact_id, func.min(ArtifactFlag.created_at).label("since"))
+            .filter(ArtifactFlag.resolved_at.is_(None))
+            .group_by(ArtifactFlag.artifact_id)
+            .subquery()
+        )
+        q = db.query(Artifact).join(waiting, waiting.c.artifact_id == Artifact.id).filter(Artifact.deleted_at.is_(None))
+        total = q.count()
+        artifacts = q.order_by(waiting.c.since, Artifact.id).limit(limit).offset(offset).all()
+        ids = [artifact.id for artifact in artifacts]
+        open_flags: Dict[int, List[Dict[str, Any]]] = {artifact_id: [] for artifact_id in ids}
+        if ids:
+            flags = (
+                db.query(ArtifactFlag)
+                .filter(ArtifactFlag.artifact_id.in_(ids), ArtifactFlag.resolved_at.is_(None))
+                .order_by(ArtifactFlag.created_at, ArtifactFlag.id)
+            )
+            for flag in flags:
+                open_flags[flag.artifact_id].append(flag.to_dict())
+        return [{**artifact.to_dict(), "open_flags": open_flags[artifact.id]} for artifact in artifacts], total
+
+
+def list_artifact_revisions(artifact_id: int, limit: int = 50, offset: int = 0) -> Tuple[List[Dict[str, Any]], int]:
+    """An artifact's revisions, newest first, and how many there are."""
+    with get_db() as db:
+        q = db.query(ArtifactRevision).filter(ArtifactRevision.artifact_id == artifact_id)
+        total = q.count()
+        revisions = q.order_by(ArtifactRevision.id.desc()).limit(limit).offset(offset).all()
+        return [revision.to_dict() for revision in revisions], total
+
+
+def get_artifact_revision(artifact_id: int, revision_id: int) -> Optional[Dict[str, Any]]:
+    with get_db() as db:
+        revision = (
+            db.query(ArtifactRevision)
+            .filter(ArtifactRevision.id == revision_id, ArtifactRevision.artifact_id == artifact_id)
+            .first()
+        )
+        return revision.to_dict() if revision else None
+
+
+def revert_artifact_revision(artifact_id: int, revision_id: int, reverted_by: str) -> Optional[Dict[str, Any]]:
+    """
+    Set the REVERTIBLE_FIELDS a revision changed back to their values from
+    before it, recorded as a "reverted" revision; later changes to other
+    fields stay. None if the artifact (outside the trash) or the revision
+    doesn't exist.
+    """
+    with get_db() as db:
+        _attribute_changes(db, reverted_by, "reverted")
+        artifact = db.query(Artifact).filter(Artifact.id == artifact_id, Artifact.deleted_at.is_(None)).first()
+        revision = (
+            db.query(ArtifactRevision)
+            .filter(ArtifactRevision.id == revision_id, ArtifactRevision.artifact_id == artifact_id)
+            .first()
+        )
+        if not artifact or not revision:
+            return None
+        for field, (old, _new) in json.loads(revision.changes).items():
+            if field not in REVERTIBLE_FIELDS:
+                continue
+            if old is not None and isinstance(Artifact.__table__.c[field].type, DateTime):
+                old = datetime.fromisoformat(old)
+            setattr(artifact, field, old)
+        artifact.updated_at = datetime.utcnow()
+        db.flush()
+        return artifact.to_dict()
+
+
+# ----------------------------------------------------------------------
+# Analysis jobs
+# ----------------------------------------------------------------------
+def create_analysis_job(
+    tier: str,
+    image_bytes: bytes,
+    request_id: Optional[str] = None,
+    batch_id: Optional[str] = None,
+    batch_index: Optional[int] = None,
+    filename: Optional[str] = None,
+    requested_by: Optional[str] = None,
+    result: Optional[Dict[str, Any]] = None,
+    error: Optional[str] = None,
+) -> Dict[str, Any]:
+    """Queue an analysis of ``image_bytes``; the image waits in the image store until the job finishes.
+
+    A job given its ``result`` (e.g. from the cache) or ``error`` is created
+    already finished and its image isn't stored.
+    """
+    finished = result is not None or error is not None
+    now = datetime.utcnow()
+    with get_db() as db:
+        job = AnalysisJob(
+            id=uuid.uuid4().hex,
+            tier=tier,
+            status=("failed" if error is not None else "done") if finished else "queued",
+            stage=("failed" if error is not None else "done") if finished else "received",
+            image_hash=hashlib.sha256(image_bytes).hexdigest() if finished else image_store.put(image_bytes),
+            result=json.dumps(result) if result is not None else None,
+            error=error,
+            request_id=request_id,
+            batch_id=batch_id,
+            batch_index=batch_index,
+            filename=filename,
+            requested_by=requested_by,
+            finished_at=now if finished else None,
+        )
+        db.add(job)
+        db.flush()
+        return job.to_dict()
+
+
+def get_analysis_job(job_id: str) -> Optional[Dict[str, Any]]:
+    with get_db() as db:
+        job = db.query(AnalysisJob).filter(AnalysisJob.id == job_id).first()
+        return job.to_dict() if job else None
+
+
+def get_analysis_batch(batch_id: str) -> List[Dict[str, Any]]:
+    """The jobs of a batch in upload order; empty if there is no such batch."""
+    with get_db() as db:
+        jobs = (
+            db.query(AnalysisJob)
+            .filter(AnalysisJob.batch_id == batch_id)
+            .order_by(AnalysisJob.batch_index, AnalysisJob.created_at)
+            .all()
+        )
+        return [job.to_dict() for job in jobs]
+
+
+def claim_analysis_job(tier: str) -> Optional[Dict[str, Any]]:
+    """Mark the oldest queued job of ``tier`` running and return it with its image hash, or None.
+
+    The status check is part of the UPDATE, so two workers never get the same job.
+    """
+    with get_db() as db:
+        while True:
+            job = (
+                db.query(AnalysisJob)
+                .filter(AnalysisJob.tier == tier, AnalysisJob.status == "queued")
+                .order_by(AnalysisJob.created_at, AnalysisJob.id)
+                .first()
+            )
+            if job is None:
+                return None
+            claimed = (
+                db.query(AnalysisJob)
+                .filter(AnalysisJob.id == job.id, AnalysisJob.status == "queued")
+                .update(
+                    {
+                        "status": "running",
+                        "stage": "preprocessing",
+                        "started_at": datetime.utcnow(),
+                        "attempts": AnalysisJob.attempts + 1,
+                    },
+                    synchronize_session=False,
+                )
+            )
+            if claimed:
+                db.flush()
+                db.refresh(job)
+                return {
+                    **job.to_dict(),
+                    "image_hash": job.image_hash,
+                    "request_id": job.request_id,
+                    "requested_by": job.requested_by,
+                }
+
+
+def finish_analysis_job(job_id: str, result: Optional[Dict[str, Any]] = None, error: Optional[str] = None) -> None:
+    """Record a job's result, or its error, and drop its image if nothing else uses it."""
+    with get_db() as db:
+        job = db.query(AnalysisJob).filter(AnalysisJob.id == job_id).first()
+        if job is None:
+            return
+        job.status = job.stage = "failed" if error else "done"
+        job.result = json.dumps(result) if result is not None else None
+        job.error = error
+        job.finished_at = datetime.utcnow()
+        image_hash = job.image_hash
+    _release_images(image_hash)
+
+
+def count_unfinished_analysis_jobs() -> Dict[Tuple[str, str], int]:
+    """How many jobs are queued or running, by (tier, status)."""
+    with get_db() as db:
+        rows = (
+            db.query(AnalysisJob.tier, AnalysisJob.status, func.count(AnalysisJob.id))
+            .filter(AnalysisJob.status.in_(("queued", "running")))
+            .group_by(AnalysisJob.tier, AnalysisJob.status)
+            .all()
+        )
+        return {(tier, status): count for tier, status, count in rows}
+
+
+def set_analysis_job_stage(job_id: str, stage: str) -> None:
+    """Record how far a running job has got (see AnalysisJob.stage)."""
+    with get_db() as db:
+        db.query(AnalysisJob).filter(AnalysisJob.id == job_id, AnalysisJob.status == "running").update(
+            {"stage": stage}, synchronize_session=False
+        )
+
+
+def requeue_interrupted_jobs(max_attempts: int) -> int:
+    """Put jobs left running by a previous process back in the queue; fail those out of attempts.
+
+    Returns how many were requeued.
+    """
+    with get_db() as db:
+        interrupted = db.query(AnalysisJob).filter(AnalysisJob.status == "running").all()
+        exhausted = []
+        for job in interrupted:
+            if job.attempts >= max_attempts:
+                job.status = job.stage = "failed"
+                job.error = "Analysis was interrupted too many times"
+                job.finished_at = datetime.utcnow()
+                exhausted.append(job.image_hash)
+            else:
+                job.status = "queued"
+                job.stage = "received"
+                job.started_at = None
+        requeued = len(interrupted) - len(exhausted)
+    _release_images(*exhausted)
+    return requeued
+
+
+def delete_finished_analysis_jobs(older_than: datetime) -> int:
+    """Forget done and failed jobs that finished before ``older_than``."""
+    with get_db() as db:
+        return (
+            db.query(AnalysisJob)
+            .filter(AnalysisJob.status.in_(("done", "failed")), AnalysisJob.finished_at < older_than)
+            .delete(synchronize_session=False)
+        )
+
+
+def get_cached_analysis(
+    image_hash: str, tier: str, provider: str, newer_than: datetime
+) -> Optional[Dict[str, Any]]:
+    """The cached result for an image and tier, if the same provider made it after ``newer_than``."""
+    with get_db() as db:
+        entry = (
+            db.query(AnalysisCacheEntry)
+            .filter(
+                AnalysisCacheEntry.image_hash == image_hash,
+                AnalysisCacheEntry.tier == tier,
+                AnalysisCacheEntry.provider == provider,
+                AnalysisCacheEntry.created_at > newer_than,
+            )
+            .first()
+        )
+        return json.loads(entry.result) if entry else None
+
+
+def cache_analysis(image_hash: str, tier: str, provider: str, result: Dict[str, Any]) -> None:
+    """Remember a result, replacing any earlier one for the image and tier."""
+    with get_db() as db:
+        db.merge(
+            AnalysisCacheEntry(
+                image_hash=image_hash,
+                tier=tier,
+                provider=provider,
+                result=json.dumps(result),
+                created_at=datetime.utcnow(),
+            )
+        )
+
+
+def delete_cached_analyses(older_than: datetime) -> int:
+    with get_db() as db:
+        return (
+            db.query(AnalysisCacheEntry)
+            .filter(AnalysisCacheEntry.created_at < older_than)
+            .delete(synchronize_session=False)
+        )
+
+
+# ----------------------------------------------------------------------
+# Image embeddings
+# ----------------------------------------------------------------------
+def save_artifact_embedding(artifact_id: int, model: str, vector: bytes) -> None:
+    with get_db() as db:
+        db.merge(ArtifactEmbedding(artifact_id=artifact_id, model=model, vector=vector, created_at=datetime.utcnow()))
+
+
+def load_artifact_embeddings(model: str) -> List[Tuple[int, bytes]]:
+    """Every (artifact id, vector) made by ``model``."""
+    with get_db() as db:
+        rows = db.query(ArtifactEmbedding.artifact_id, ArtifactEmbedding.vector).filter(ArtifactEmbedding.model == model)
+        return [(row.artifact_id, row.vector) for row in rows]
+
+
+def artifacts_missing_embedding(model: str, limit: int = 50) -> List[Tuple[int, str]]:
+    """(id, image hash) of artifacts with an image but no embedding from ``model``, oldest first."""
+    with get_db() as db:
+        current = select(ArtifactEmbedding.artifact_id).where(ArtifactEmbedding.model == model)
+        rows = (
+            db.query(Artifact.id, Artifact.image_hash)
+            .filter(Artifact.image_hash.isnot(None), Artifact.id.notin_(current))
+            .order_by(Artifact.id)
+            .limit(limit)
+            .all()
+        )
+        return [(row.id, row.image_hash) for row in rows]
+
+
+# ----------------------------------------------------------------------
+# Analysis usage
+# ----------------------------------------------------------------------
+def charge_analysis_usage(username: str, month: str, tier: str, analyses: int, units: int, quota: int) -> Optional[int]:
+    """
+    Record ``analyses`` at ``tier`` costing ``units`` against a user's month,
+    unless that would take them past ``quota`` units (0 for no quota).
+    Returns the units used this month afterwards, or None when refused.
+    """
+    with get_db() as db:
+        used = (
+            db.query(func.coalesce(func.sum(AnalysisUsage.units), 0))
+            .filter(AnalysisUsage.username == username, AnalysisUsage.month == month)
+            .scalar()
+        )
+        if quota and used + units > quota:
+            return None
+        row = db.get(AnalysisUsage, (username, month, tier))
+        if row is None:
+            row = AnalysisUsage(username=username, month=month, tier=tier, analyses=0, units=0)
+            db.add(row)
+        row.analyses += analyses
+        row.units += units
+        db.flush()
+        return used + units
+
+
+def get_analysis_usage(username: str, month: str) -> List[Dict[str, Any]]:
+    """A user's analyses and units by tier for a month."""
+    with get_db() as db:
+        rows = (
+            db.query(AnalysisUsage)
+            .filter(AnalysisUsage.username == username, AnalysisUsage.month == month)
+            .order_by(AnalysisUsage.tier)
+        )
+        return [{"tier": row.tier, "analyses": row.analyses, "units": row.units} for row in rows]
+
+
+# ----------------------------------------------------------------------
+# Webhooks
+# ----------------------------------------------------------------------
+def create_webhook(owner_id: str, url: str, events: List[str]) -> Dict[str, Any]:
+    """Register a webhook with a new random secret; the secret is only in this result."""
+    with get_db() as db:
+        webhook = Webhook(owner_id=owner_id, url=url, secret=uuid.uuid4().hex + uuid.uuid4().hex, events=",".join(events))
+        db.add(webhook)
+        db.flush()
+        return {**webhook.to_dict(), "secret": webhook.secret}
+
+
+def list_webhooks(owner_id: Optional[str] = None) -> List[Dict[str, Any]]:
+    """A user's webhooks, or everyone's when ``owner_id`` is None, newest first."""
+    with get_db() as db:
+        q = db.query(Webhook)
+        if owner_id is not None:
+            q = q.filter(Webhook.owner_id == owner_id)
+        return [webhook.to_dict() for webhook in q.order_by(Webhook.id.desc()).all()]
+
+
+def get_webhook(webhook_id: int) -> Optional[Dict[str, Any]]:
+    with get_db() as db:
+        webhook = db.query(Webhook).filter(Webhook.id == webhook_id).first()
+        return webhook.to_dict() if webhook else None
+
+
+def delete_webhook(webhook_id: int) -> bool:
+    """Remove a webhook and any deliveries still waiting for it."""
+    with get_db() as db:
+        deleted = db.query(Webhook).filter(Webhook.id == webhook_id).delete(synchronize_session=False)
+        db.query(WebhookDelivery).filter(WebhookDelivery.webhook_id == webhook_id).delete(synchronize_session=False)
+        return bool(deleted)
+
+
+def queue_webhook_deliveries(
+    owner_id: str, event: str, payload: Dict[str, Any], webhook_id: Optional[int] = None
+) -> int:
+    """
+    Queue ``payload`` for each of the owner's webhooks subscribed to ``event``
+    (or just ``webhook_id``, whatever it subscribes to). Returns how many were queued.
+    """
+    with get_db() as db:
+        q = db.query(Webhook).filter(Webhook.owner_id == owner_id)
+        if webhook_id is not None:
+            q = q.filter(Webhook.id == webhook_id)
+        queued = 0
+        for webhook in q.all():
+            if webhook_id is None and event not in webhook.events.split(","):
+                continue
+            delivery_id = uuid.uuid4().hex
+            body = json.dumps({"id": delivery_id, "event": event, "webhook_id": webhook.id, **payload})
+            db.add(WebhookDelivery(id=delivery_id, webhook_id=webhook.id, event=event, payload=body))
+            queued += 1
+        return queued
+
+
+def claim_webhook_delivery() -> Optional[Dict[str, Any]]:
+    """Mark the oldest due delivery sending and return it with its webhook's URL and secret, or None."""
+    with get_db() as db:
+        while True:
+            delivery = (
+                db.query(WebhookDelivery)
+                .filter(WebhookDelivery.status == "pending", WebhookDelivery.next_attempt_at <= datetime.utcnow())
+                .order_by(WebhookDelivery.next_attempt_at, WebhookDelivery.id)
+                .first()
+            )
+            if delivery is None:
+                return None
+            claimed = (
+                db.query(WebhookDelivery)
+                .filter(WebhookDelivery.id == delivery.id, WebhookDelivery.status == "pending")
+                .update(
+                    {"status": "sending", "attempts": WebhookDelivery.attempts + 1},
+                    synchronize_session=False,
+                )
+            )
+            if not claimed:
+                continue
+            webhook = db.query(Webhook).filter(Webhook.id == delivery.webhook_id).first()
+            if webhook is None:
+                # Deleted since it was queued
+                db.query(WebhookDelivery).filter(WebhookDelivery.id == delivery.id).delete(synchronize_session=False)
+                continue
+            db.flush()
+            db.refresh(delivery)
+            return {
+                "id": delivery.id,
+                "event": delivery.event,
+                "payload": delivery.payload,
+                "attempts": delivery.attempts,
+                "url": webhook.url,
+                "secret": webhook.secret,
+            }
+
+
+def finish_webhook_delivery(
+    delivery_id: str, error: Optional[str] = None, retry_at: Optional[datetime] = None
+) -> None:
+    """Record a delivery attempt: delivered without ``error``, else retried at ``retry_at`` or failed."""
+    now = datetime.utcnow()
+    with get_db() as db:
+        delivery = db.query(WebhookDelivery).filter(WebhookDelivery.id == delivery_id).first()
+        if delivery is None:
+            return
+        delivery.last_error = error
+        if error and retry_at:
+            delivery.status = "pending"
+            delivery.next_attempt_at = retry_at
+            return
+        delivery.status = "failed" if error else "delivered"
+        delivery.finished_at = now
+        db.query(Webhook).filter(Webhook.id == delivery.webhook_id).update(
+            {"last_delivery_at": now, "last_status": delivery.status, "last_error": error},
+            synchronize_session=False,
+        )
+
+
+def requeue_interrupted_deliveries() -> int:
+    """Put deliveries a stopped process was sending back in the queue."""
+    with get_db() as db:
+        return (
+            db.query(WebhookDelivery)
+            .filter(WebhookDelivery.status == "sending")
+            .update({"status": "pending"}, synchronize_session=False)
+        )
+
+
+def delete_finished_webhook_deliveries(older_than: datetime) -> int:
+    with get_db() as db:
+        return (
+            db.query(WebhookDelivery)
+            .filter(WebhookDelivery.status.in_(("delivered", "failed")), WebhookDelivery.finished_at < older_than)
+            .delete(synchronize_session=False)
+        )
+
+
+def get_artifact_changes(
+    since: Optional[datetime] = None,
+    uploaded_by: Optional[str] = None,
+    viewer: Optional[Viewer] = None,
+    include_blobs: bool = True,
+    known_ids: Optional[List[int]] = None,
+) -> Dict[str, Any]:
+    """Return artifacts created/updated after ``since``, ids to drop, and the next ``since``.
+
+    With no ``since`` every artifact is returned, which doubles as the initial
+    full sync for a client with an empty archive. Only artifacts ``viewer``
+    may see are returned. Changes are re-read from SYNC_OVERLAP_SECONDS
+    before ``since``, as a write stamped earlier may commit after a read saw
+    a later one; clients merge artifacts by id, so repeats are harmless.
+
+    ``deleted_ids`` are those of ``known_ids`` (what the client holds) that
+    ``viewer`` can no longer see: deleted, trashed, unshared, or out of reach
+    for any other reason. Without ``known_ids`` only the viewer's own trashed
+    artifacts are reported (any deleted ones for staff), so no one learns
+    the ids of artifacts they could never see.
+    """
+    # Only if the read sees nothing, for a first sync of an empty archive
+    started_at = datetime.utcnow()
+    synced_at = since
+    reread_from = since - timedelta(seconds=SYNC_OVERLAP_SECONDS) if since is not None else None
+    changed_at = func.coalesce(Artifact.updated_at, Artifact.uploaded_at)
+    with get_db() as db:
+        q = _visible_artifacts(db, db.query(Artifact, changed_at.label("changed_at")), viewer)
+        q = _without_blobs(q, include_blobs)
+        if uploaded_by:
+            q = q.filter(Artifact.uploaded_by == uploaded_by)
+        if reread_from is not None:
+            q = q.filter(changed_at > reread_from)
+        results = []
+        for artifact, artifact_changed_at in q.order_by(Artifact.uploaded_at.desc()).all():
+            synced_at = max(synced_at or artifact_changed_at, artifact_changed_at)
+            results.append(artifact.to_dict(include_blobs))
+
+        deleted_ids: List[int] = []
+        if known_ids is not None:
+            still_visible = set()
+            for start in range(0, len(known_ids), 500):
+                q = _visible_artifacts(db, db.query(Artifact.id), viewer).filter(
+                    Artifact.id.in_(known_ids[start:start + 500])
+                )
+                still_visible.update(row.id for row in q)
+            deleted_ids = sorted(set(known_ids) - still_visible)
+        elif reread_from is not None and viewer is not None and viewer.username:
+            q = db.query(ArtifactTombstone.artifact_id).filter(ArtifactTombstone.deleted_at > reread_from)
+            if not viewer.sees_all:
+                # A purged artifact no longer says whose it was; its move to the trash was reported then
+                q = q.filter(
+                    ArtifactTombstone.artifact_id.in_(db.query(Artifact.id).filter(Artifact.owner_id == viewer.username))
+                )
+            deleted_ids = [row.artifact_id for row in q]
+        return {"artifacts": results, "deleted_ids": deleted_ids, "synced_at": synced_at or started_at}
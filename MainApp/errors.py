@@ -0,0 +1,168 @@
+"""
+Typed API errors for the FLL Project backend.
+
+Each error carries its HTTP status and a stable machine-readable code, so
+handlers raise ``NotFoundError("Artifact not found")`` instead of picking a
+status by hand. They subclass HTTPException, which keeps the existing
+``except HTTPException: raise`` guards working and lets FastAPI render them.
+
+Every error response has the same envelope:
+    {"detail": "Artifact not found", "code": "not_found", "request_id": "..."}
+where request_id matches the request's log lines (see request_logging).
+Unexpected exceptions become a generic 500 so internals never reach clients.
+"""
+
+import logging
+
+from typing import Optional
+
+from fastapi import HTTPException, Request
+from fastapi.exceptions import RequestValidationError
+from fastapi.responses import JSONResponse
+from sqlalchemy.exc import SQLAlchemyError
+from starlette.exceptions import HTTPException as StarletteHTTPException
+
+logger = logging.getLogger(__name__)
+
+
+class AppError(HTTPException):
+    status_code = 500
+    code = "internal_error"
+
+    def __init__(
+        self, detail: str, status_code: Optional[int] = None, headers: Optional[dict] = None, **extra
+    ):
+        super().__init__(status_code=status_code or self.status_code, detail=detail, headers=headers)
+        # Added to the response body next to detail and code
+        self.extra = extra
+
+
+class ValidationError(AppError):
+    status_code = 400
+    code = "validation_error"
+
+
+class AuthenticationError(AppError):
+    status_code = 401
+    code = "authentication_failed"
+
+
+class PermissionDeniedError(AppError):
+    status_code = 403
+    code = "permission_denied"
+
+
+class NotFoundError(AppError):
+    status_code = 404
+    code = "not_found"
+
+
+class ConflictError(AppError):
+    status_code = 409
+    code = "conflict"
+
+
+class DuplicateArtifactError(ConflictError):
+    code = "duplicate_artifact"
+
+
+class PreconditionFailedError(AppError):
+    status_code = 412
+    code = "precondition_failed"
+
+
+class AccountLockedError(AppError):
+    status_code = 423
+    code = "account_locked"
+
+
+class ArtifactLockedError(AppError):
+    """A curator has locked the artifact against edits."""
+
+    status_code = 423
+    code = "artifact_locked"
+
+
+class RateLimitedError(AppError):
+    status_code = 429
+    code = "rate_limited"
+
+
+class QuotaExceededError(RateLimitedError):
+    code = "quota_exceeded"
+
+
+class DatabaseError(AppError):
+    status_code = 500
+    code = "database_error"
+
+
+class ExternalServiceError(AppError):
+    status_code = 502
+    code = "external_service_error"
+
+
+# Codes for plain HTTPExceptions, which only carry a status
+_CODES_BY_STATUS = {
+    cls.status_code: cls.code
+    for cls in (
+        AppError,
+        ValidationError,
+        AuthenticationError,
+        PermissionDeniedError,
+        NotFoundError,
+        ConflictError,
+        PreconditionFailedError,
+        AccountLockedError,
+        RateLimitedError,
+        ExternalServiceError,
+    )
+}
+
+
+def error_response(request: Request, status_code, detail, code, headers=None, **extra) -> JSONResponse:
+    """The standard error envelope."""
+    request_id = getattr(request.state, "request_id", None)
+    return JSONResponse(
+        status_code=status_code,
+        content={"detail": detail, "code": code, "request_id": request_id, **extra},
+        headers=headers,
+    )
+
+
+async def app_error_handler(request: Request, exc: AppError) -> JSONResponse:
+    return error_response(request, exc.status_code, exc.detail, exc.code, exc.headers, **exc.extra)
+
+
+async def http_exception_handler(request: Request, exc: StarletteHTTPException) -> JSONResponse:
+    # Plain HTTPExceptions, e.g. FastAPI's own 401s or unmatched routes
+    code = _CODES_BY_STATUS.get(exc.status_code, "internal_error" if exc.status_code >= 500 else "http_error")
+    return error_response(request, exc.status_code, exc.detail, code, getattr(exc, "headers", None))
+
+
+async def request_validation_error_handler(request: Request, exc: RequestValidationError) -> JSONResponse:
+    """422 with one message per invalid field, e.g. {"errors": {"email": "Must be a valid email address"}}"""
+    errors = {}
+    for error in exc.errors():
+        field = ".".join(str(part) for part in error["loc"][1:]) or error["loc"][0]
+        errors.setdefault(field, error["msg"].removeprefix("Value error, "))
+    return error_response(request, 422, "Some fields are invalid", "validation_error", errors=errors)
+
+
+async def database_error_handler(request: Request, exc: SQLAlchemyError) -> JSONResponse:
+    logger.error("Unhandled database error", exc_info=exc)
+    return await app_error_handler(request, DatabaseError("Database error"))
+
+
+async def unhandled_error_handler(request: Request, exc: Exception) -> JSONResponse:
+    logger.error("Unhandled error", exc_info=exc)
+    return await app_error_handler(request, AppError("Internal server error"))
+
+
+def register_error_handlers(app) -> None:
+    """Render every error, expected or not, in the standard envelope."""
+    app.add_exception_handler(AppError, app_error_handler)
+    app.add_exception_handler(StarletteHTTPException, http_exception_handler)
+    app.add_exception_handler(RequestValidationError, request_validation_error_handler)
+    app.add_exception_handler(SQLAlchemyError, database_error_handler)
+    app.add_exception_handler(Exception, unhandled_error_handler)
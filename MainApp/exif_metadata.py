@@ -0,0 +1,83 @@
+"""
+Capture time and place from a photo's EXIF data.
+
+Cameras and phones record when a photo was taken (DateTimeOriginal, else
+the file's DateTime) and, with location on, where (the GPS IFD's latitude
+and longitude as degree/minute/second rationals plus N/S and E/W refs).
+EXIF times have no time zone; they are kept as the camera's local time.
+
+Anything missing, unreadable or out of range comes back as None: plenty of
+uploads are screenshots, scans or photos with their metadata stripped.
+"""
+
+import logging
+from datetime import datetime
+from io import BytesIO
+from typing import Any, Dict, Optional
+
+from PIL import Image
+
+logger = logging.getLogger(__name__)
+
+# Tag numbers from the EXIF 2.3 specification
+EXIF_IFD = 0x8769
+GPS_IFD = 0x8825
+DATETIME = 0x0132
+DATETIME_ORIGINAL = 0x9003
+GPS_LATITUDE_REF = 1
+GPS_LATITUDE = 2
+GPS_LONGITUDE_REF = 3
+GPS_LONGITUDE = 4
+
+EXIF_TIME_FORMAT = "%Y:%m:%d %H:%M:%S"
+
+
+def _capture_time(value: Any) -> Optional[datetime]:
+    if isinstance(value, bytes):
+        value = value.decode("ascii", "ignore")
+    if not isinstance(value, str):
+        return None
+    try:
+        # Unknown parts are sometimes filled with spaces or zeros
+        return datetime.strptime(value.strip("\x00 ")[:19], EXIF_TIME_FORMAT)
+    except ValueError:
+        return None
+
+
+def _degrees(value: Any, ref: Any, limit: float) -> Optional[float]:
+    """Decimal degrees from (degrees, minutes, seconds) rationals, negative for S and W."""
+    try:
+        degrees, minutes, seconds = (float(part) for part in value)
+    except (TypeError, ValueError, ZeroDivisionError):
+        return None
+    decimal = degrees + minutes / 60 + seconds / 3600
+    if isinstance(ref, bytes):
+        ref = ref.decode("ascii", "ignore")
+    if str(ref).strip("\x00 ").upper() in ("S", "W"):
+        decimal = -decimal
+    if decimal != decimal or abs(decimal) > limit:  # NaN from a 0/0 rational, or nonsense
+        return None
+    return round(decimal, 7)
+
+
+def extract(image_bytes: bytes) -> Dict[str, Any]:
+    """``captured_at``, ``latitude`` and ``longitude`` of a photo, each None when unknown."""
+    found: Dict[str, Any] = {"captured_at": None, "latitude": None, "longitude": None}
+    try:
+        exif = Image.open(BytesIO(image_bytes)).getexif()
+    except Exception as e:
+        logger.debug(f"No EXIF read: {e}")
+        return found
+    if not exif:
+        return found
+
+    found["captured_at"] = _capture_time(exif.get_ifd(EXIF_IFD).get(DATETIME_ORIGINAL)) or _capture_time(
+        exif.get(DATETIME)
+    )
+    gps = exif.get_ifd(GPS_IFD)
+    latitude = _degrees(gps.get(GPS_LATITUDE), gps.get(GPS_LATITUDE_REF), 90)
+    longitude = _degrees(gps.get(GPS_LONGITUDE), gps.get(GPS_LONGITUDE_REF), 180)
+    # (0, 0) is what some cameras write before they have a fix
+    if latitude is not None and longitude is not None and (latitude, longitude) != (0, 0):
+        found["latitude"], found["longitude"] = latitude, longitude
+    return found
@@ -0,0 +1,33 @@
+"""
+Write the API's OpenAPI specification to a file, for client generation.
+
+The running server also serves it at /openapi.json, with Swagger UI at /docs.
+
+    python MainApp/export_openapi.py [output path]
+
+Defaults to contracts/openapi.json. Importing the app initialises the
+databases, so run it with the same environment as the server.
+"""
+
+import json
+import os
+import sys
+
+MAIN_APP_DIR = os.path.dirname(os.path.abspath(__file__))
+DEFAULT_OUTPUT = os.path.join(os.path.dirname(MAIN_APP_DIR), "contracts", "openapi.json")
+
+sys.path.insert(0, os.path.join(MAIN_APP_DIR, "backend"))
+from main import app  # noqa: E402
+
+
+def main():
+    output = sys.argv[1] if len(sys.argv) > 1 else DEFAULT_OUTPUT
+    with open(output, "w", encoding="utf-8") as f:
+        json.dump(app.openapi(), f, indent=2)
+        f.write("\n")
+    print(f"✅ Wrote OpenAPI spec to {output}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -22,8 +22,16 @@ class FastAnalyzer:
     - QUALITY: Qwen3-VL latest (~1-2 minutes)
     """
     
-    def __init__(self, tier: str = "FAST"):
+    # Ollama model per LLM tier
+    MODELS = {
+        "FAST": "qwen3-vl:2b",  # ~20-40 seconds
+        "BALANCED": "qwen2-vl:7b",  # ~30-60 seconds
+        "QUALITY": "qwen3-vl:latest",  # ~1-2 minutes
+    }
+
+    def __init__(self, tier: str = "FAST", timeout: Optional[int] = None):
         self.tier = tier.upper()
+        self.timeout = timeout  # Seconds per Ollama request; 2 minutes if not given
         self._setup_analyzer()
     
     def _setup_analyzer(self):
@@ -33,23 +41,19 @@ class FastAnalyzer:
             from ai_analyzer import AIAnalyzer
             self.analyzer_type = "vit"
             self.analyzer = AIAnalyzer()
+            if self.timeout:
+                self.analyzer.ollama.timeout = self.timeout
             self.expected_time = "1-2 seconds"
             
         elif self.tier in ["FAST", "BALANCED", "QUALITY"]:
             # Use Ollama with appropriate model
             from ai_analyzer import OllamaClient
             
-            model_map = {
-                "FAST": "qwen3-vl:2b",           # ~20-40 seconds
-                "BALANCED": "qwen2-vl:7b",    # ~30-60 seconds  
-                "QUALITY": "qwen3-vl:latest"  # ~1-2 minutes
-            }
-            
-            self.model = model_map[self.tier]
+            self.model = self.MODELS[self.tier]
             self.analyzer_type = "ollama"
             self.ollama = OllamaClient(
                 model=self.model,
-                timeout=120  # 2 minutes max
+                timeout=self.timeout or 120  # 2 minutes max by default
             )
             
             time_map = {
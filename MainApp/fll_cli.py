@@ -0,0 +1,229 @@
+"""
+fll-cli: analyse a directory of artifact photos in bulk through a running
+backend, for digitizing an existing photo archive without the web app.
+
+Every image under the directory (recursively, by extension) is sent to
+POST /api/analyze at the chosen tier, queued analyses are polled until they
+finish, and each identified image is saved as an artifact owned by the API
+key's user (unless --analyze-only). Several images are worked on at once
+(--concurrency); connection errors, 429s and 5xx answers are retried with
+backoff (--retries), honouring Retry-After. Images already in the archive
+are reported as duplicates with the existing artifact's id, not saved again.
+
+A report with a row per image (path, status, name, confidence, artifact id,
+attempts, error) is written as CSV or JSON, chosen by the file extension.
+
+    export FLL_API_KEY=...   # from the API Keys page
+    python MainApp/fll_cli.py ~/photos --tier balanced --concurrency 4 --report report.csv
+
+Exits with 1 when any image failed.
+"""
+
+import argparse
+import base64
+import csv
+import json
+import os
+import sys
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Dict, List, Optional
+
+import requests
+
+DEFAULT_URL = os.getenv("FLL_API_URL", "http://localhost:8000")
+TIERS = ("instant", "fast", "balanced", "thorough")
+# What the backend accepts (see image_formats.py); matched case-insensitively
+IMAGE_EXTENSIONS = (
+    ".jpg", ".jpeg", ".png", ".webp", ".gif", ".heic", ".heif", ".tif", ".tiff", ".bmp",
+    ".dng", ".cr2", ".cr3", ".nef", ".arw", ".orf", ".rw2", ".raf",
+)
+REPORT_FIELDS = ("path", "status", "name", "confidence", "artifact_id", "attempts", "error")
+
+REQUEST_TIMEOUT_SECONDS = 120
+JOB_POLL_SECONDS = 2
+# A queued analysis that takes longer than this is reported as failed
+JOB_TIMEOUT_SECONDS = 30 * 60
+BACKOFF_MAX_SECONDS = 60
+
+
+class CliError(Exception):
+    """A request that failed for good: a 4xx, or out of retries."""
+
+    def __init__(self, message: str, status: Optional[int] = None, body: Optional[Dict[str, Any]] = None):
+        super().__init__(message)
+        self.status = status
+        self.body = body or {}
+
+
+class Client:
+    """The backend's API, with an API key and retries."""
+
+    def __init__(self, url: str, api_key: str, retries: int):
+        self.url = url.rstrip("/")
+        self.retries = retries
+        self._local = threading.local()
+        self._headers = {"X-Api-Key": api_key}
+
+    def _session(self) -> requests.Session:
+        # Sessions aren't thread-safe; one per worker
+        if not hasattr(self._local, "session"):
+            self._local.session = requests.Session()
+            self._local.session.headers.update(self._headers)
+        return self._local.session
+
+    def request(self, method: str, path: str, attempts: List[int], **kwargs: Any) -> requests.Response:
+        """The response to a request, retried on connection errors, 429 and 5xx; counts tries in ``attempts``."""
+        attempt = 0
+        while True:
+            attempts[0] += 1
+            try:
+                response = self._session().request(method, self.url + path, timeout=REQUEST_TIMEOUT_SECONDS, **kwargs)
+            except requests.RequestException as e:
+                error: CliError = CliError(f"Could not reach {self.url}: {e}")
+                wait = None
+            else:
+                if response.status_code < 400:
+                    return response
+                try:
+                    body = response.json()
+                except ValueError:
+                    body = {}
+                error = CliError(body.get("detail") or f"HTTP {response.status_code}", response.status_code, body)
+                if response.status_code != 429 and response.status_code < 500:
+                    raise error
+                wait = response.headers.get("Retry-After")
+            if attempt == self.retries:
+                raise error
+            time.sleep(float(wait) if wait and wait.isdigit() else min(2 ** attempt, BACKOFF_MAX_SECONDS))
+            attempt += 1
+
+    def analyze(self, image_data: str, tier: str, attempts: List[int]) -> Dict[str, Any]:
+        """The analysis result, waiting for it when the backend queues the analysis."""
+        response = self.request("post", "/api/analyze", attempts, json={"image_data": image_data, "tier": tier})
+        if response.status_code != 202:
+            return response.json()
+        job = response.json()
+        deadline = time.monotonic() + JOB_TIMEOUT_SECONDS
+        while job["status"] not in ("done", "failed"):
+            if time.monotonic() > deadline:
+                raise CliError(f"Analysis job {job['id']} did not finish in {JOB_TIMEOUT_SECONDS // 60} minutes")
+            time.sleep(JOB_POLL_SECONDS)
+            job = self.request("get", f"/api/analyze/jobs/{job['id']}", attempts).json()
+        if job["status"] == "failed":
+            raise CliError(job.get("error") or "Analysis failed")
+        return job["result"]
+
+    def create_artifact(self, artifact: Dict[str, Any], attempts: List[int]) -> Dict[str, Any]:
+        return self.request("post", "/api/artifacts", attempts, json=artifact).json()
+
+
+def find_images(directory: str) -> List[str]:
+    """Image files under ``directory``, in a stable order."""
+    found = []
+    for root, dirs, files in os.walk(directory):
+        dirs[:] = sorted(d for d in dirs if not d.startswith("."))
+        found.extend(
+            os.path.join(root, name)
+            for name in sorted(files)
+            if not name.startswith(".") and name.lower().endswith(IMAGE_EXTENSIONS)
+        )
+    return found
+
+
+def process(client: Client, path: str, tier: str, save: bool) -> Dict[str, Any]:
+    """Analyse (and save) one image; returns its report row."""
+    row: Dict[str, Any] = {"path": path, "status": "failed"}
+    attempts = [0]
+    try:
+        with open(path, "rb") as f:
+            image_data = base64.b64encode(f.read()).decode("ascii")
+        result = client.analyze(image_data, tier, attempts)
+        row.update(name=result.get("name"), confidence=result.get("confidence"))
+        if not save:
+            row["status"] = "analyzed"
+            return row
+        try:
+            artifact = client.create_artifact(
+                {
+                    "name": result["name"],
+                    "description": result.get("description"),
+                    # As the web app's batch upload tags them
+                    "tags": result["name"].split(),
+                    "tier": tier,
+                    "image_data": image_data,
+                },
+                attempts,
+            )
+        except CliError as e:
+            if e.status != 409 or "existing_id" not in e.body:
+                raise
+            row.update(status="duplicate", artifact_id=e.body["existing_id"])
+            return row
+        row.update(status="saved", artifact_id=artifact["id"])
+    except (CliError, OSError) as e:
+        row["error"] = str(e)
+    finally:
+        row["attempts"] = attempts[0]
+    return row
+
+
+def write_report(path: str, rows: List[Dict[str, Any]]) -> None:
+    if path.lower().endswith(".json"):
+        with open(path, "w", encoding="utf-8") as f:
+            json.dump([{field: row.get(field) for field in REPORT_FIELDS} for row in rows], f, indent=2)
+        return
+    with open(path, "w", encoding="utf-8", newline="") as f:
+        writer = csv.DictWriter(f, fieldnames=REPORT_FIELDS, extrasaction="ignore")
+        writer.writeheader()
+        writer.writerows(rows)
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    parser = argparse.ArgumentParser(prog="fll-cli", description="Analyse and import a directory of artifact photos")
+    parser.add_argument("directory", help="searched recursively for images")
+    parser.add_argument("--tier", choices=TIERS, default="fast", help="analysis tier (default: fast)")
+    parser.add_argument("--concurrency", type=int, default=2, help="images worked on at once (default: 2)")
+    parser.add_argument("--retries", type=int, default=3, help="retries per request on 429, 5xx and network errors (default: 3)")
+    parser.add_argument("--report", default="fll-report.csv", help="report file, .csv or .json (default: fll-report.csv)")
+    parser.add_argument("--analyze-only", action="store_true", help="report the analyses without saving artifacts")
+    parser.add_argument("--url", default=DEFAULT_URL, help=f"the backend (default: $FLL_API_URL or {DEFAULT_URL})")
+    parser.add_argument("--api-key", default=os.getenv("FLL_API_KEY"), help="an API key (default: $FLL_API_KEY)")
+    args = parser.parse_args(argv)
+
+    if not args.api_key:
+        parser.error("an API key is required: pass --api-key or set FLL_API_KEY")
+    if not os.path.isdir(args.directory):
+        parser.error(f"{args.directory} is not a directory")
+    images = find_images(args.directory)
+    if not images:
+        print(f"No images found in {args.directory}")
+        return 0
+
+    client = Client(args.url, args.api_key, max(args.retries, 0))
+    rows: List[Dict[str, Any]] = []
+    print(f"Analysing {len(images)} image(s) at the {args.tier} tier with {args.url}")
+    with ThreadPoolExecutor(max_workers=max(args.concurrency, 1)) as pool:
+        futures = [pool.submit(process, client, path, args.tier, not args.analyze_only) for path in images]
+        try:
+            for done, future in enumerate(futures, 1):
+                row = future.result()
+                rows.append(row)
+                detail = row.get("error") or f"{row.get('name')} ({row.get('confidence')})"
+                print(f"[{done}/{len(images)}] {row['status']:<9} {row['path']}: {detail}")
+        except KeyboardInterrupt:
+            # Finish what's in flight, skip the rest, and still write the report
+            print("Stopping...")
+            for future in futures:
+                future.cancel()
+            rows.extend(future.result() for future in futures[len(rows):] if not future.cancelled())
+
+    write_report(args.report, rows)
+    counts = {status: sum(row["status"] == status for row in rows) for status in ("saved", "analyzed", "duplicate", "failed")}
+    print(", ".join(f"{count} {status}" for status, count in counts.items() if count) + f"; report in {args.report}")
+    return 1 if counts["failed"] else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
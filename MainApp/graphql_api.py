@@ -0,0 +1,410 @@
+"""
+GraphQL API, served at /api/graphql next to the REST endpoints.
+
+For clients that want to pick their fields: an artifact list with just
+names and tags, or an artifact with its collection in one request. Images
+are only ever URLs (``imageUrl``, ``thumbnailUrl``) and 3D models a flag,
+so nothing large comes back unless asked for over REST.
+
+    query {
+      artifacts(filter: {tag: "pottery"}, limit: 20) {
+        total
+        items { id name era tags collection { name } }
+      }
+    }
+
+Queries: ``artifacts``, ``artifact``, ``collections``, ``collection``,
+``me``, and ``users``/``user`` (admins, or yourself). Mutations:
+``updateArtifact`` (the PATCH /api/artifacts/{id} fields, with an optional
+``version`` for the same optimistic check as If-Match) and
+``deleteArtifact`` (to the trash). Access rules are the REST API's: the
+caller sees what GET would show them, and only owners and archive staff
+edit, and not while a curator has locked the artifact. Failures are GraphQL errors whose ``extensions.code`` is the REST
+error code, e.g. "not_found".
+
+Authentication is the same bearer token, API key or session cookie
+(mutations need X-CSRF-Token with a cookie); main.py builds the context.
+"""
+
+from typing import Any, Dict, List, Optional
+
+import strawberry
+from graphql import GraphQLError
+from pydantic import ValidationError as PydanticValidationError
+from strawberry.extensions import QueryDepthLimiter
+from strawberry.fastapi import GraphQLRouter
+from strawberry.types import Info
+
+from config import GRAPHQL_IDE, GRAPHQL_MAX_DEPTH
+from database import (
+    LockedArtifactError,
+    StaleArtifactError,
+    Viewer,
+    count_artifacts,
+    count_search_results,
+    delete_artifact,
+    get_all_artifacts,
+    get_artifact_by_id,
+    get_collection,
+    list_collections,
+    patch_artifact,
+    search_artifacts,
+)
+from errors import (
+    AppError,
+    ArtifactLockedError,
+    AuthenticationError,
+    NotFoundError,
+    PermissionDeniedError,
+    PreconditionFailedError,
+    ValidationError,
+)
+from login import get_user_row, query_users
+from schemas import ArtifactPatch
+
+# Largest page any list returns, as for GET /api/artifacts
+PAGE_MAX = 500
+
+
+def _fail(error: AppError) -> GraphQLError:
+    return GraphQLError(error.detail, extensions={"code": error.code, **error.extra})
+
+
+def _viewer(info: Info) -> Viewer:
+    return info.context["viewer"]
+
+
+def _user(info: Info):
+    """The signed-in caller, or an unauthenticated error."""
+    user = info.context["user"]
+    if user is None:
+        raise _fail(AuthenticationError("Not authenticated"))
+    return user
+
+
+def _page(limit: int, offset: int) -> Dict[str, int]:
+    if not 1 <= limit <= PAGE_MAX or offset < 0:
+        raise _fail(ValidationError(f"limit must be 1-{PAGE_MAX} and offset at least 0"))
+    return {"limit": limit, "offset": offset}
+
+
+def _image_url(image_hash: Optional[str]) -> Optional[str]:
+    return f"/api/images/{image_hash}" if image_hash else None
+
+
+@strawberry.type
+class Collection:
+    id: int
+    name: str
+    description: Optional[str]
+    owner_id: str
+    visibility: str
+    created_at: Optional[str]
+    artifact_count: int
+
+    @strawberry.field(description="Artifacts in the collection that the caller can see, newest first")
+    def artifacts(self, info: Info, limit: int = 100, offset: int = 0) -> List["Artifact"]:
+        rows = get_all_artifacts(viewer=_viewer(info), collection_id=self.id, **_page(limit, offset))
+        return [Artifact.from_dict(row) for row in rows]
+
+    @staticmethod
+    def from_dict(collection: Dict[str, Any]) -> "Collection":
+        return Collection(
+            id=collection["id"],
+            name=collection["name"],
+            description=collection.get("description"),
+            owner_id=collection["owner_id"],
+            visibility=collection["visibility"],
+            created_at=collection.get("created_at"),
+            artifact_count=collection.get("artifact_count", 0),
+        )
+
+
+@strawberry.type
+class Artifact:
+    id: int
+    name: str
+    description: Optional[str]
+    era: Optional[str] = strawberry.field(description="The estimated age")
+    tags: List[str]
+    tier: Optional[str]
+    notes: Optional[str]
+    value: Optional[str]
+    material: Optional[str]
+    cultural_context: Optional[str]
+    function: Optional[str]
+    rarity: Optional[str]
+    confidence: Optional[float]
+    provenance: Optional[str]
+    historical_context: Optional[str]
+    references: Optional[str]
+    image_url: Optional[str] = strawberry.field(description="Path under the API, like the REST responses'")
+    thumbnail_url: Optional[str]
+    original_format: Optional[str]
+    has_3d_model: bool
+    uploaded_by: Optional[str]
+    uploaded_at: Optional[str]
+    analyzed_at: Optional[str]
+    updated_at: Optional[str]
+    captured_at: Optional[str]
+    latitude: Optional[float]
+    longitude: Optional[float]
+    verification_status: str
+    verified_by: Optional[str]
+    verified_at: Optional[str]
+    owner_id: Optional[str]
+    visibility: str
+    collection_id: Optional[int]
+    locked_by: Optional[str] = strawberry.field(description="The curator who locked it against edits, if locked")
+    version: str = strawberry.field(description="Pass back to updateArtifact to refuse overwriting newer edits")
+
+    @strawberry.field
+    def collection(self, info: Info) -> Optional[Collection]:
+        if self.collection_id is None:
+            return None
+        collection = get_collection(self.collection_id, _viewer(info))
+        return Collection.from_dict(collection) if collection else None
+
+    @staticmethod
+    def from_dict(a: Dict[str, Any]) -> "Artifact":
+        return Artifact(
+            id=a["id"],
+            name=a["name"],
+            description=a.get("description"),
+            era=a.get("age"),
+            tags=a["tags"].split(",") if a.get("tags") else [],
+            tier=a.get("tier"),
+            notes=a.get("notes"),
+            value=a.get("value"),
+            material=a.get("material"),
+            cultural_context=a.get("cultural_context"),
+            function=a.get("function"),
+            rarity=a.get("rarity"),
+            confidence=a.get("confidence"),
+            provenance=a.get("provenance"),
+            historical_context=a.get("historical_context"),
+            references=a.get("references"),
+            image_url=_image_url(a.get("image_hash")),
+            thumbnail_url=_image_url(a.get("thumbnail_hash") or a.get("image_hash")),
+            original_format=a.get("original_format"),
+            has_3d_model=bool(a.get("model_3d_data")),
+            uploaded_by=a.get("uploaded_by"),
+            uploaded_at=a.get("uploaded_at"),
+            analyzed_at=a.get("analyzed_at"),
+            updated_at=a.get("updated_at"),
+            captured_at=a.get("captured_at"),
+            latitude=a.get("latitude"),
+            longitude=a.get("longitude"),
+            verification_status=a.get("verification_status") or "pending",
+            verified_by=a.get("verified_by"),
+            verified_at=a.get("verified_at"),
+            owner_id=a.get("owner_id"),
+            visibility=a.get("visibility") or "private",
+            collection_id=a.get("collection_id"),
+            locked_by=a.get("locked_by"),
+            version=a.get("version") or "",
+        )
+
+
+@strawberry.input(description="The GET /api/artifacts filters; search ranks by relevance and ignores the others but tag")
+class ArtifactFilter:
+    search: Optional[str] = None
+    scope: str = "all"
+    collection_id: Optional[int] = None
+    uploaded_by: Optional[str] = None
+    era: Optional[str] = None
+    tier: Optional[str] = None
+    tag: Optional[str] = strawberry.field(default=None, description="Comma-separated; every tag must match")
+    min_confidence: Optional[float] = None
+    verified: Optional[bool] = None
+
+
+@strawberry.type
+class ArtifactPage:
+    items: List[Artifact]
+    filters: strawberry.Private[Dict[str, Any]]
+    search: strawberry.Private[Optional[str]]
+
+    @strawberry.field(description="How many artifacts match, across all pages")
+    def total(self) -> int:
+        if self.search:
+            return count_search_results(self.search, **self.filters)
+        return count_artifacts(**self.filters)
+
+
+@strawberry.type
+class User:
+    username: str
+    name: str
+    email: str
+    role: str
+    active: bool
+
+    @staticmethod
+    def from_row(row) -> "User":
+        username, name, role, email, _locked_until, active, _deactivated_at = row
+        return User(username=username, name=name, email=email, role=role, active=bool(active))
+
+
+@strawberry.type
+class UserPage:
+    items: List[User]
+    total: int
+
+
+@strawberry.input(description="Only the fields that are set change, as with PATCH /api/artifacts/{id}")
+class ArtifactChanges:
+    name: Optional[str] = strawberry.UNSET
+    description: Optional[str] = strawberry.UNSET
+    era: Optional[str] = strawberry.UNSET
+    tags: Optional[List[str]] = strawberry.UNSET
+    notes: Optional[str] = strawberry.UNSET
+    collection_id: Optional[int] = strawberry.UNSET
+    verification_status: Optional[str] = strawberry.UNSET
+
+
+def _visible_artifact(artifact_id: int, viewer: Viewer) -> Dict[str, Any]:
+    artifact = get_artifact_by_id(artifact_id, viewer)
+    if not artifact:
+        raise _fail(NotFoundError("Artifact not found"))
+    return artifact
+
+
+def _require_owner(item: Dict[str, Any], viewer: Viewer, what: str = "artifact") -> None:
+    if item.get("owner_id") != viewer.username and not viewer.sees_all:
+        raise _fail(PermissionDeniedError(f"Only the {what}'s owner can change it"))
+
+
+def _editable_artifact(artifact_id: int, viewer: Viewer) -> Dict[str, Any]:
+    artifact = _visible_artifact(artifact_id, viewer)
+    _require_owner(artifact, viewer)
+    if artifact.get("locked_at"):
+        raise _fail(ArtifactLockedError(f"Artifact is locked by {artifact['locked_by']}: {artifact['lock_reason']}"))
+    return artifact
+
+
+@strawberry.type
+class Query:
+    @strawberry.field(description="A page of the artifacts the caller can see, newest first unless searching")
+    def artifacts(
+        self, info: Info, filter: Optional[ArtifactFilter] = None, limit: int = 100, offset: int = 0
+    ) -> ArtifactPage:
+        filter = filter or ArtifactFilter()
+        page = _page(limit, offset)
+        if filter.scope not in ("mine", "shared", "all"):
+            raise _fail(ValidationError("scope must be mine, shared or all"))
+        if filter.search:
+            filters = {"tags": filter.tag, "uploaded_by": filter.uploaded_by, "viewer": _viewer(info), "scope": filter.scope}
+            rows = search_artifacts(filter.search, **page, **filters)
+        else:
+            filters = {
+                "viewer": _viewer(info),
+                "scope": filter.scope,
+                "collection_id": filter.collection_id,
+                "uploaded_by": filter.uploaded_by,
+                "era": filter.era,
+                "tier": filter.tier,
+                "tags": filter.tag,
+                "min_confidence": filter.min_confidence,
+                "verified": filter.verified,
+            }
+            rows = get_all_artifacts(**page, **filters)
+        return ArtifactPage(items=[Artifact.from_dict(row) for row in rows], filters=filters, search=filter.search)
+
+    @strawberry.field
+    def artifact(self, info: Info, id: int) -> Optional[Artifact]:
+        artifact = get_artifact_by_id(id, _viewer(info))
+        return Artifact.from_dict(artifact) if artifact else None
+
+    @strawberry.field(description="Collections the caller can see; scope is mine, shared or all")
+    def collections(self, info: Info, scope: str = "all") -> List[Collection]:
+        if scope not in ("mine", "shared", "all"):
+            raise _fail(ValidationError("scope must be mine, shared or all"))
+        return [Collection.from_dict(c) for c in list_collections(_viewer(info), scope)]
+
+    @strawberry.field
+    def collection(self, info: Info, id: int) -> Optional[Collection]:
+        collection = get_collection(id, _viewer(info))
+        return Collection.from_dict(collection) if collection else None
+
+    @strawberry.field(description="The signed-in caller")
+    def me(self, info: Info) -> User:
+        return User.from_row(get_user_row(_user(info).username))
+
+    @strawberry.field(description="A user: yourself, or anyone for admins")
+    def user(self, info: Info, username: str) -> Optional[User]:
+        caller = _user(info)
+        if username != caller.username and caller.role != "admin":
+            raise _fail(PermissionDeniedError("Only admins can look up other users"))
+        row = get_user_row(username)
+        return User.from_row(row) if row else None
+
+    @strawberry.field(description="Accounts, filtered like GET /api/v1/users (admins only)")
+    def users(
+        self,
+        info: Info,
+        role: Optional[str] = None,
+        search: Optional[str] = None,
+        active: Optional[bool] = None,
+        limit: int = 50,
+        offset: int = 0,
+    ) -> UserPage:
+        if _user(info).role != "admin":
+            raise _fail(PermissionDeniedError("Only admins can list users"))
+        rows, total = query_users(role=role, search=search, active=active, **_page(limit, offset))
+        return UserPage(items=[User.from_row(row) for row in rows], total=total)
+
+
+@strawberry.type
+class Mutation:
+    @strawberry.mutation(description="Change an artifact (owner or archive staff; verification is staff only)")
+    def update_artifact(
+        self, info: Info, id: int, changes: ArtifactChanges, version: Optional[str] = None
+    ) -> Artifact:
+        user = _user(info)
+        viewer = _viewer(info)
+        _editable_artifact(id, viewer)
+        sent = {key: value for key, value in vars(changes).items() if value is not strawberry.UNSET}
+        try:
+            fields = ArtifactPatch.model_validate(sent).model_dump(exclude_unset=True)
+        except PydanticValidationError as e:
+            raise _fail(ValidationError("; ".join(error["msg"] for error in e.errors())))
+        if not fields:
+            raise _fail(ValidationError("Send at least one field to change", status_code=422))
+        if "verification_status" in fields and not viewer.sees_all:
+            raise _fail(PermissionDeniedError("Only archive staff can verify or reject artifacts"))
+        if fields.get("collection_id") is not None:
+            collection = get_collection(fields["collection_id"], viewer)
+            if not collection:
+                raise _fail(NotFoundError("Collection not found"))
+            _require_owner(collection, viewer, "collection")
+        try:
+            artifact = patch_artifact(id, fields, version, user.username)
+        except StaleArtifactError:
+            raise _fail(PreconditionFailedError("Artifact was changed by someone else; reload it and try again"))
+        except LockedArtifactError as e:
+            raise _fail(ArtifactLockedError(str(e)))
+        if not artifact:
+            raise _fail(NotFoundError("Artifact not found"))
+        return Artifact.from_dict(artifact)
+
+    @strawberry.mutation(description="Move an artifact to the trash (owner or archive staff); returns its id")
+    def delete_artifact(self, info: Info, id: int) -> int:
+        user = _user(info)
+        viewer = _viewer(info)
+        _editable_artifact(id, viewer)
+        if not delete_artifact(id, deleted_by=user.username):
+            raise _fail(NotFoundError("Artifact not found"))
+        return id
+
+
+schema = strawberry.Schema(
+    query=Query,
+    mutation=Mutation,
+    extensions=[QueryDepthLimiter(max_depth=GRAPHQL_MAX_DEPTH)],
+)
+
+
+def router(context_getter) -> GraphQLRouter:
+    """The /api/graphql router; ``context_getter`` is a FastAPI dependency returning {"user", "viewer"}."""
+    return GraphQLRouter(schema, context_getter=context_getter, graphql_ide="graphiql" if GRAPHQL_IDE else None)
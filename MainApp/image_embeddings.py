@@ -0,0 +1,309 @@
+"""
+Image embeddings for visual similarity search.
+
+An embedder turns image bytes into a fixed-length unit vector, so that
+similar-looking images have a high cosine similarity. EMBEDDING_PROVIDER
+picks one: "histogram" (colour distribution and coarse layout, no model
+needed) or "onnx" (the output of a feature model at EMBEDDING_MODEL_PATH,
+e.g. a CLIP image encoder), which falls back to histogram when onnxruntime
+or the model file is missing.
+
+Analyses include the image's embedding, and each new artifact's is stored in
+artifact_embeddings under the embedder's model name. Artifacts without one
+for the current embedder (older uploads, or after switching embedders) are
+embedded in the background on start.
+
+Searches use an in-memory index of the stored vectors. Exact cosine
+similarity by one matrix product is fast enough for an archive's tens of
+thousands of images; the index is reloaded from the table once it is older
+than EMBEDDING_INDEX_REFRESH_SECONDS so uploads through other processes show
+up. Visibility is checked against the database after ranking.
+"""
+
+import logging
+import os
+import threading
+import time
+from abc import ABC, abstractmethod
+from io import BytesIO
+from typing import Any, Dict, List, Optional, Tuple, Type
+
+import numpy as np
+from PIL import Image
+
+import image_store
+from config import EMBEDDING_INDEX_REFRESH_SECONDS, EMBEDDING_MODEL_PATH, EMBEDDING_PROVIDER
+from database import (
+    Viewer,
+    artifacts_missing_embedding,
+    get_artifacts_by_ids,
+    load_artifact_embeddings,
+    save_artifact_embedding,
+)
+
+logger = logging.getLogger(__name__)
+
+
+def _unit(vector: np.ndarray) -> np.ndarray:
+    vector = np.asarray(vector, dtype=np.float32).reshape(-1)
+    norm = float(np.linalg.norm(vector))
+    return vector / norm if norm > 0 else vector
+
+
+class Embedder(ABC):
+    name: str = ""
+
+    def available(self) -> bool:
+        return True
+
+    @property
+    def model(self) -> str:
+        """Stored with each vector; vectors from a different model are recomputed."""
+        return self.name
+
+    @abstractmethod
+    def embed(self, image_bytes: bytes) -> np.ndarray:
+        """A float32 unit vector for the image. Raises on unreadable images."""
+
+
+class HistogramEmbedder(Embedder):
+    """
+    A joint hue/saturation/value histogram plus a tiny greyscale thumbnail.
+    Finds photos of the same object or similar-coloured finds; it knows
+    nothing about what the object is.
+    """
+
+    name = "histogram"
+    # Bump when the computation changes, so stored vectors are recomputed
+    VERSION = 1
+    BINS = (8, 4, 4)
+    LAYOUT_SIZE = 8
+    LAYOUT_WEIGHT = 0.5
+
+    @property
+    def model(self) -> str:
+        return f"{self.name}-v{self.VERSION}"
+
+    def embed(self, image_bytes: bytes) -> np.ndarray:
+        image = Image.open(BytesIO(image_bytes)).convert("RGB")
+        hsv = np.asarray(image.resize((64, 64)).convert("HSV"), dtype=np.float32).reshape(-1, 3) / 256.0
+        histogram, _ = np.histogramdd(hsv, bins=self.BINS, range=((0, 1), (0, 1), (0, 1)))
+        # Square roots make cosine similarity behave like the Hellinger distance between histograms
+        colour = np.sqrt(histogram.reshape(-1) / histogram.sum())
+
+        size = self.LAYOUT_SIZE
+        layout = np.asarray(image.convert("L").resize((size, size)), dtype=np.float32).reshape(-1)
+        layout = _unit(layout - layout.mean()) * self.LAYOUT_WEIGHT
+        return _unit(np.concatenate([colour, layout]))
+
+
+class OnnxEmbedder(Embedder):
+    """
+    A feature model (EMBEDDING_MODEL_PATH) run with onnxruntime: float NCHW
+    RGB input, its first output flattened is the embedding.
+    """
+
+    name = "onnx"
+
+    # ImageNet normalisation, as for the local classifier
+    MEAN = np.array([0.485, 0.456, 0.406], dtype=np.float32)
+    STD = np.array([0.229, 0.224, 0.225], dtype=np.float32)
+    DEFAULT_SIZE = 224
+
+    def __init__(self):
+        self.session = None
+        self.problem: Optional[str] = None
+        try:
+            import onnxruntime
+        except ImportError:
+            self.problem = "onnxruntime is not installed"
+            return
+        if not os.path.isfile(EMBEDDING_MODEL_PATH):
+            self.problem = f"no model at {EMBEDDING_MODEL_PATH}"
+            return
+        try:
+            self.session = onnxruntime.InferenceSession(EMBEDDING_MODEL_PATH, providers=["CPUExecutionProvider"])
+        except Exception as e:
+            self.problem = f"could not load {EMBEDDING_MODEL_PATH}: {e}"
+            return
+        logger.info(f"Embedding model loaded from {EMBEDDING_MODEL_PATH}")
+
+    def available(self) -> bool:
+        return self.session is not None
+
+    @property
+    def model(self) -> str:
+        return f"{self.name}:{os.path.basename(EMBEDDING_MODEL_PATH)}"
+
+    def embed(self, image_bytes: bytes) -> np.ndarray:
+        model_input = self.session.get_inputs()[0]
+        height, width = (d if isinstance(d, int) and d > 0 else self.DEFAULT_SIZE for d in model_input.shape[2:4])
+        image = Image.open(BytesIO(image_bytes)).convert("RGB").resize((width, height))
+        pixels = (np.asarray(image, dtype=np.float32) / 255.0 - self.MEAN) / self.STD
+        output = self.session.run(None, {model_input.name: pixels.transpose(2, 0, 1)[np.newaxis]})[0]
+        return _unit(output)
+
+
+EMBEDDERS: Dict[str, Type[Embedder]] = {
+    HistogramEmbedder.name: HistogramEmbedder,
+    OnnxEmbedder.name: OnnxEmbedder,
+}
+
+_embedder: Optional[Embedder] = None
+_embedder_lock = threading.Lock()
+
+
+def embedder() -> Embedder:
+    """The configured embedder, or the histogram one if it can't run here."""
+    global _embedder
+    with _embedder_lock:
+        if _embedder is None:
+            embedder_class = EMBEDDERS.get(EMBEDDING_PROVIDER)
+            if embedder_class is None:
+                raise ValueError(f"Unknown EMBEDDING_PROVIDER {EMBEDDING_PROVIDER!r}; use one of {', '.join(EMBEDDERS)}")
+            chosen = embedder_class()
+            if not chosen.available():
+                reason = getattr(chosen, "problem", None) or "not available"
+                logger.warning(f"Embedder {chosen.name!r} can't run ({reason}); using histogram")
+                chosen = HistogramEmbedder()
+            _embedder = chosen
+        return _embedder
+
+
+def embed(image_bytes: bytes) -> np.ndarray:
+    return embedder().embed(image_bytes)
+
+
+class _Index:
+    """Unit vectors of every embedded artifact, searched by cosine similarity."""
+
+    def __init__(self):
+        self._lock = threading.Lock()
+        self._ids: List[int] = []
+        self._positions: Dict[int, int] = {}
+        self._matrix = np.zeros((0, 0), dtype=np.float32)
+        self._loaded_at = 0.0
+
+    def _load(self) -> None:
+        rows = [
+            (artifact_id, np.frombuffer(vector, dtype=np.float32))
+            for artifact_id, vector in load_artifact_embeddings(embedder().model)
+        ]
+        # Images that couldn't be embedded are stored as zero vectors, which match nothing
+        rows = [(artifact_id, vector) for artifact_id, vector in rows if vector.any()]
+        vectors = [vector for _, vector in rows]
+        with self._lock:
+            self._ids = [artifact_id for artifact_id, _ in rows]
+            self._positions = {artifact_id: n for n, artifact_id in enumerate(self._ids)}
+            self._matrix = np.vstack(vectors) if vectors else np.zeros((0, 0), dtype=np.float32)
+            self._loaded_at = time.monotonic()
+
+    def _fresh(self) -> None:
+        if not self._loaded_at or time.monotonic() - self._loaded_at > EMBEDDING_INDEX_REFRESH_SECONDS:
+            self._load()
+
+    def add(self, artifact_id: int, vector: np.ndarray) -> None:
+        with self._lock:
+            if not self._loaded_at:
+                return  # Picked up by the first load
+            if artifact_id in self._positions:
+                self._matrix[self._positions[artifact_id]] = vector
+                return
+            self._positions[artifact_id] = len(self._ids)
+            self._ids.append(artifact_id)
+            self._matrix = np.vstack([self._matrix, vector]) if self._matrix.size else vector[np.newaxis].copy()
+
+    def vector(self, artifact_id: int) -> Optional[np.ndarray]:
+        self._fresh()
+        with self._lock:
+            position = self._positions.get(artifact_id)
+            return None if position is None else self._matrix[position].copy()
+
+    def ranked(self, query: np.ndarray) -> List[Tuple[int, float]]:
+        """Every (artifact id, similarity), most similar first."""
+        self._fresh()
+        with self._lock:
+            if not self._ids or self._matrix.shape[1] != query.shape[0]:
+                return []
+            scores = self._matrix @ query
+            ids = self._ids
+        order = np.argsort(-scores)
+        return [(ids[n], float(scores[n])) for n in order]
+
+
+_index = _Index()
+
+
+def index_artifact(artifact_id: int, image_bytes: bytes) -> None:
+    """Embed and store a new artifact's image. Never raises: similarity search is a nice-to-have."""
+    try:
+        vector = embed(image_bytes)
+        save_artifact_embedding(artifact_id, embedder().model, vector.tobytes())
+        _index.add(artifact_id, vector)
+    except Exception as e:
+        logger.warning(f"Could not embed artifact {artifact_id}'s image: {e}")
+
+
+def similar_artifacts(
+    query: np.ndarray, viewer: Optional[Viewer], limit: int = 10, exclude: Optional[int] = None
+) -> List[Tuple[Dict[str, Any], float]]:
+    """The ``limit`` artifacts ``viewer`` can see that look most like ``query``, with their similarity."""
+    ranked = [(artifact_id, score) for artifact_id, score in _index.ranked(query) if artifact_id != exclude]
+    found: List[Tuple[Dict[str, Any], float]] = []
+    # Check visibility a page at a time, so a private archive doesn't mean loading everything
+    page = max(limit * 4, 50)
+    for start in range(0, len(ranked), page):
+        chunk = ranked[start:start + page]
+        visible = get_artifacts_by_ids([artifact_id for artifact_id, _ in chunk], viewer)
+        found.extend((visible[artifact_id], score) for artifact_id, score in chunk if artifact_id in visible)
+        if len(found) >= limit:
+            break
+    return found[:limit]
+
+
+def similar_to_artifact(artifact_id: int, viewer: Optional[Viewer], limit: int = 10) -> Optional[List[Tuple[Dict[str, Any], float]]]:
+    """Artifacts that look like an artifact, or None when it has no embedding (e.g. no image) yet."""
+    vector = _index.vector(artifact_id)
+    if vector is None:
+        return None
+    return similar_artifacts(vector, viewer, limit, exclude=artifact_id)
+
+
+def _blank_embedding() -> np.ndarray:
+    """A zero vector as long as the embedder's."""
+    buffer = BytesIO()
+    Image.new("RGB", (32, 32), "white").save(buffer, format="PNG")
+    return np.zeros_like(embed(buffer.getvalue()))
+
+
+def _backfill(batch_size: int = 50) -> None:
+    model = embedder().model
+    done = 0
+    while True:
+        missing = artifacts_missing_embedding(model, batch_size)
+        if not missing:
+            break
+        for artifact_id, image_hash in missing:
+            try:
+                vector = embed(image_store.get(image_hash))
+            except Exception as e:
+                # Stored as a zero vector so it isn't retried on every start
+                logger.warning(f"Could not embed artifact {artifact_id}'s image: {e}")
+                vector = _blank_embedding()
+            save_artifact_embedding(artifact_id, model, vector.tobytes())
+            done += 1
+    if done:
+        logger.info(f"Embedded {done} artifact image(s) for similarity search ({model})")
+        _index._load()
+
+
+def start() -> None:
+    """Embed artifacts that have no embedding from the current embedder, in the background."""
+
+    def run():
+        try:
+            _backfill()
+        except Exception:
+            logger.exception("Embedding existing artifact images failed")
+
+    threading.Thread(target=run, name="embedding-backfill", daemon=True).start()
@@ -0,0 +1,122 @@
+"""
+Uploaded images in whatever format, stored and analysed as JPEG or PNG.
+
+Clients send what the camera or scanner produced: HEIC from phones, TIFF
+from scanners, BMP, or camera RAW (DNG, CR2, CR3, NEF, ARW, ORF, RW2,
+RAF...). ``normalize`` turns anything that isn't already JPEG or PNG into
+one: PNG when the image has transparency, else JPEG at IMAGE_JPEG_QUALITY.
+EXIF orientation is applied, 16-bit and float samples are scaled to 8 bits,
+only a TIFF's first page is kept, and the EXIF block goes along so capture
+time and GPS position can still be read from the stored image. JPEG and
+PNG uploads are kept byte for byte, so their content hashes don't change.
+
+HEIC/HEIF needs pillow-heif and RAW needs rawpy; without them those
+uploads are refused like any other unreadable file. Most RAW formats are
+TIFF underneath, where Pillow would only find the small preview, so a TIFF
+is offered to rawpy first.
+"""
+
+import logging
+from io import BytesIO
+from typing import Any, Dict, Optional, Tuple
+
+import numpy as np
+from PIL import Image, ImageOps
+
+from config import IMAGE_JPEG_QUALITY
+
+logger = logging.getLogger(__name__)
+
+try:
+    from pillow_heif import register_heif_opener
+
+    # Lets Image.open read HEIC/HEIF everywhere, exif_metadata included
+    register_heif_opener()
+    HEIF_SUPPORTED = True
+except ImportError:
+    HEIF_SUPPORTED = False
+
+try:
+    import rawpy
+except ImportError:
+    rawpy = None
+
+NORMALIZED_FORMATS = ("JPEG", "PNG")
+# Pillow's format names for the ones clients know by another
+_FORMAT_NAMES = {"HEIF": "HEIC", "DIB": "BMP"}
+
+
+class UnsupportedImageError(ValueError):
+    """The bytes aren't an image this server can decode."""
+
+
+def _decode_raw(data: bytes) -> Optional[Image.Image]:
+    if rawpy is None:
+        return None
+    try:
+        with rawpy.imread(BytesIO(data)) as raw:
+            return Image.fromarray(raw.postprocess(use_camera_wb=True, output_bps=8))
+    except Exception:
+        return None
+
+
+def _to_8_bit(image: Image.Image) -> Image.Image:
+    """Scale 16-bit and float greyscale (common in scanner TIFFs) to 8 bits rather than clip it."""
+    if image.mode not in ("I", "I;16", "I;16B", "I;16L", "F"):
+        return image
+    samples = np.asarray(image, dtype=np.float64)
+    top = samples.max() or 1
+    return Image.fromarray((samples * (255 / top)).clip(0, 255).astype(np.uint8), "L")
+
+
+def _has_alpha(image: Image.Image) -> bool:
+    return image.mode in ("RGBA", "LA", "PA") or (image.mode == "P" and "transparency" in image.info)
+
+
+def normalize(data: bytes) -> Tuple[bytes, Dict[str, Any]]:
+    """
+    The image as JPEG or PNG bytes, and what it was uploaded as:
+    ``original_format`` (e.g. "HEIC", "TIFF", "RAW") and ``original_size``
+    in bytes. Raises UnsupportedImageError for anything else.
+    """
+    if not data:
+        raise UnsupportedImageError("Empty image")
+    image = None
+    try:
+        image = Image.open(BytesIO(data))
+        original_format = _FORMAT_NAMES.get(image.format, image.format or "unknown")
+    except Exception:
+        original_format = None
+
+    if image is None or original_format == "TIFF":
+        raw = _decode_raw(data)
+        if raw is not None:
+            image, original_format = raw, "RAW"
+    if image is None:
+        if data[4:8] == b"ftyp" and not HEIF_SUPPORTED:
+            raise UnsupportedImageError("HEIC images need pillow-heif installed on the server")
+        raise UnsupportedImageError("Not an image format this server can read")
+
+    original = {"original_format": original_format, "original_size": len(data)}
+    if original_format in NORMALIZED_FORMATS:
+        try:
+            image.verify()
+        except Exception:
+            raise UnsupportedImageError(f"This {original_format} image is damaged")
+        return data, original
+
+    try:
+        image.seek(0)
+        # Turned upright, with the orientation tag dropped from its EXIF to match
+        image = ImageOps.exif_transpose(image)
+        params = {"exif": image.info["exif"]} if image.info.get("exif") else {}
+        image = _to_8_bit(image)
+        buffer = BytesIO()
+        if _has_alpha(image):
+            image.convert("RGBA").save(buffer, format="PNG", optimize=True, **params)
+        else:
+            image.convert("RGB").save(buffer, format="JPEG", quality=IMAGE_JPEG_QUALITY, **params)
+    except Exception as e:
+        logger.info(f"Could not convert a {original_format} upload: {e}")
+        raise UnsupportedImageError(f"Could not read this {original_format} image")
+    return buffer.getvalue(), original
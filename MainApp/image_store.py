@@ -0,0 +1,269 @@
+"""
+Content-addressed storage for artifact images.
+
+Images are named by the SHA-256 of their bytes and spread over
+subdirectories by the first two hex digits (``ab/abcdef...``). Identical
+uploads share one copy and a stored image never changes, so
+GET /api/images/{hash} can be cached forever. The artifacts table keeps only
+the hashes, which keeps list responses small.
+
+Where the bytes live is chosen by IMAGE_STORE_BACKEND: files under
+IMAGE_STORE_DIR ("local"), or an S3-compatible bucket such as MinIO ("s3")
+so several backend instances can share one store. Callers only use the
+module functions below.
+"""
+import hashlib
+import os
+import re
+import tempfile
+from abc import ABC, abstractmethod
+from typing import Optional, Tuple
+
+from config import (
+    IMAGE_STORE_BACKEND,
+    IMAGE_STORE_DIR,
+    S3_ACCESS_KEY_ID,
+    S3_BUCKET,
+    S3_ENDPOINT_URL,
+    S3_PREFIX,
+    S3_REGION,
+    S3_SECRET_ACCESS_KEY,
+)
+
+_HASH = re.compile(r"^[0-9a-f]{64}$")
+
+# Leading bytes of the formats uploads arrive in
+_SIGNATURES = (
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"BM", "image/bmp"),
+)
+
+
+def is_valid_hash(value):
+    return bool(value) and bool(_HASH.match(value))
+
+
+def _relative_name(image_hash):
+    if not is_valid_hash(image_hash):
+        raise ValueError(f"Not an image hash: {image_hash!r}")
+    return f"{image_hash[:2]}/{image_hash}"
+
+
+def sniff_media_type(data):
+    """The MIME type of image bytes, from their first few bytes."""
+    head = data[:12]
+    if head[:4] == b"RIFF" and head[8:12] == b"WEBP":
+        return "image/webp"
+    for signature, mime in _SIGNATURES:
+        if head.startswith(signature):
+            return mime
+    return "application/octet-stream"
+
+
+class ImageBackend(ABC):
+    """Where image bytes are kept. Names are ``ab/abcdef...``; stored bytes never change."""
+
+    @abstractmethod
+    def exists(self, name) -> bool: ...
+
+    @abstractmethod
+    def read(self, name) -> bytes: ...
+
+    @abstractmethod
+    def write(self, name, data) -> None:
+        """Store bytes under a name not stored yet; readers never see partial data."""
+
+    @abstractmethod
+    def delete(self, name) -> None: ...
+
+    def local_path(self, name) -> Optional[str]:
+        """A file path for the image if it is on this machine's disk, else None."""
+        return None
+
+    @abstractmethod
+    def usage(self) -> Tuple[int, int]:
+        """How many images are stored and their total size in bytes; lists the whole store."""
+
+    @abstractmethod
+    def describe(self) -> str: ...
+
+
+class LocalImageBackend(ImageBackend):
+    def __init__(self, root):
+        self.root = root
+
+    def local_path(self, name):
+        return os.path.join(self.root, *name.split("/"))
+
+    def exists(self, name):
+        return os.path.isfile(self.local_path(name))
+
+    def read(self, name):
+        with open(self.local_path(name), "rb") as f:
+            return f.read()
+
+    def write(self, name, data):
+        path = self.local_path(name)
+        directory = os.path.dirname(path)
+        os.makedirs(directory, exist_ok=True)
+        # Write then rename, so a reader never sees a half-written file
+        fd, temp_path = tempfile.mkstemp(dir=directory, suffix=".tmp")
+        try:
+            with os.fdopen(fd, "wb") as f:
+                f.write(data)
+            os.replace(temp_path, path)
+        except BaseException:
+            if os.path.exists(temp_path):
+                os.unlink(temp_path)
+            raise
+
+    def delete(self, name):
+        path = self.local_path(name)
+        if os.path.isfile(path):
+            os.unlink(path)
+
+    def usage(self):
+        count = size = 0
+        for directory, _, files in os.walk(self.root):
+            for filename in files:
+                if is_valid_hash(filename):
+                    count += 1
+                    size += os.path.getsize(os.path.join(directory, filename))
+        return count, size
+
+    def describe(self):
+        return self.root
+
+
+class S3ImageBackend(ImageBackend):
+    """A bucket on S3 or an S3-compatible service (MinIO, ...) set by S3_ENDPOINT_URL. Needs boto3."""
+
+    def __init__(self, bucket, prefix="", endpoint_url=None, region=None, access_key_id=None, secret_access_key=None):
+        import boto3
+
+        if not bucket:
+            raise ValueError("S3_BUCKET must be set when IMAGE_STORE_BACKEND is s3")
+        self.bucket = bucket
+        self.prefix = prefix
+        self.client = boto3.client(
+            "s3",
+            endpoint_url=endpoint_url or None,
+            region_name=region or None,
+            aws_access_key_id=access_key_id or None,
+            aws_secret_access_key=secret_access_key or None,
+        )
+
+    def _key(self, name):
+        return f"{self.prefix}{name}"
+
+    def exists(self, name):
+        from botocore.exceptions import ClientError
+
+        try:
+            self.client.head_object(Bucket=self.bucket, Key=self._key(name))
+            return True
+        except ClientError as e:
+            if e.response.get("Error", {}).get("Code") in ("404", "NoSuchKey", "NotFound"):
+                return False
+            raise
+
+    def read(self, name):
+        return self.client.get_object(Bucket=self.bucket, Key=self._key(name))["Body"].read()
+
+    def write(self, name, data):
+        # A PUT is atomic: the object appears whole or not at all
+        self.client.put_object(
+            Bucket=self.bucket,
+            Key=self._key(name),
+            Body=data,
+            ContentType=sniff_media_type(data),
+            CacheControl="public, max-age=31536000, immutable",
+        )
+
+    def delete(self, name):
+        self.client.delete_object(Bucket=self.bucket, Key=self._key(name))
+
+    def usage(self):
+        count = size = 0
+        for page in self.client.get_paginator("list_objects_v2").paginate(Bucket=self.bucket, Prefix=self.prefix):
+            for item in page.get("Contents", ()):
+                count += 1
+                size += item["Size"]
+        return count, size
+
+    def describe(self):
+        return f"s3://{self.bucket}/{self.prefix}"
+
+
+_backend = None
+
+
+def backend():
+    """The configured backend, created on first use."""
+    global _backend
+    if _backend is None:
+        if IMAGE_STORE_BACKEND == "s3":
+            _backend = S3ImageBackend(
+                S3_BUCKET,
+                prefix=S3_PREFIX,
+                endpoint_url=S3_ENDPOINT_URL,
+                region=S3_REGION,
+                access_key_id=S3_ACCESS_KEY_ID,
+                secret_access_key=S3_SECRET_ACCESS_KEY,
+            )
+        elif IMAGE_STORE_BACKEND == "local":
+            _backend = LocalImageBackend(IMAGE_STORE_DIR)
+        else:
+            raise ValueError(f"Unknown IMAGE_STORE_BACKEND {IMAGE_STORE_BACKEND!r}; use local or s3")
+    return _backend
+
+
+def exists(image_hash):
+    return is_valid_hash(image_hash) and backend().exists(_relative_name(image_hash))
+
+
+def get(image_hash):
+    """An image's bytes. Raises ValueError for anything but a SHA-256 hex digest."""
+    return backend().read(_relative_name(image_hash))
+
+
+def local_path(image_hash):
+    """The image's file path when the store is on local disk, else None."""
+    return backend().local_path(_relative_name(image_hash))
+
+
+def put(data):
+    """Store image bytes and return their hash; storing the same bytes again is a no-op."""
+    image_hash = hashlib.sha256(data).hexdigest()
+    name = _relative_name(image_hash)
+    if not backend().exists(name):
+        backend().write(name, data)
+    return image_hash
+
+
+def delete(image_hash):
+    """Remove an image. Callers must check no artifact still refers to it."""
+    if is_valid_hash(image_hash):
+        backend().delete(_relative_name(image_hash))
+
+
+def media_type(image_hash):
+    """The image's MIME type, sniffed from its first bytes."""
+    path = local_path(image_hash)
+    if path:
+        with open(path, "rb") as f:
+            return sniff_media_type(f.read(12))
+    return sniff_media_type(get(image_hash))
+
+
+def usage():
+    """(images stored, total bytes), by listing the store; slow for large ones."""
+    return backend().usage()
+
+
+def describe():
+    """Where images are stored, for log messages."""
+    return backend().describe()
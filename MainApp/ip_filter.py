@@ -0,0 +1,116 @@
+"""
+IP allow and deny lists, enforced before a request reaches any route.
+
+Rules come from IP_ALLOWLIST / IP_DENYLIST plus the ip_rules table, which
+admins manage at runtime through /admin/ip-rules. A denied address is always
+refused; when any allow rule exists, only addresses inside one get through.
+Health checks are exempt so load balancers and probes keep working.
+
+Database rules are cached and re-read every RULES_TTL_SECONDS, so changes
+made through another worker take effect shortly after; ``invalidate`` makes
+the current worker pick them up immediately.
+"""
+import ipaddress
+import logging
+import time
+
+from starlette.concurrency import run_in_threadpool
+from starlette.requests import Request
+
+from config import IP_ALLOWLIST, IP_DENYLIST
+from errors import error_response
+
+logger = logging.getLogger(__name__)
+
+RULES_TTL_SECONDS = 30
+EXEMPT_PATH_PREFIXES = ("/health/",)
+
+
+def parse_network(value):
+    """An address or CIDR range as a network; host bits are ignored. Raises ValueError."""
+    return ipaddress.ip_network(value.strip(), strict=False)
+
+
+class IpRules:
+    """A set of allowed and denied networks."""
+
+    def __init__(self, allow=(), deny=()):
+        self.allow = list(allow)
+        self.deny = list(deny)
+
+    def allows(self, ip):
+        try:
+            address = ipaddress.ip_address(ip)
+        except ValueError:
+            # No usable client address (e.g. a test client); only an allowlist can refuse it
+            return not self.allow
+        if any(address in network for network in self.deny):
+            return False
+        return not self.allow or any(address in network for network in self.allow)
+
+
+def build_rules(rules):
+    """IpRules from the configured lists plus [(action, cidr)] entries."""
+    entries = [("allow", cidr) for cidr in IP_ALLOWLIST] + [("deny", cidr) for cidr in IP_DENYLIST]
+    allow, deny = [], []
+    for action, cidr in entries + list(rules):
+        (allow if action == "allow" else deny).append(parse_network(cidr))
+    return IpRules(allow, deny)
+
+
+def load_rules():
+    from login import list_ip_rules
+
+    return build_rules((row[2], row[1]) for row in list_ip_rules())
+
+
+# Fail fast on a malformed IP_ALLOWLIST / IP_DENYLIST entry
+build_rules([])
+
+_cache = {"rules": None, "loaded_at": 0.0}
+
+
+def invalidate():
+    """Re-read the rules on the next request."""
+    _cache["loaded_at"] = 0.0
+
+
+async def current_rules():
+    if _cache["rules"] is None or time.monotonic() - _cache["loaded_at"] > RULES_TTL_SECONDS:
+        try:
+            _cache["rules"] = await run_in_threadpool(load_rules)
+        except Exception:
+            logger.exception("Could not load IP rules; keeping the previous set")
+            if _cache["rules"] is None:
+                _cache["rules"] = build_rules([])
+        _cache["loaded_at"] = time.monotonic()
+    return _cache["rules"]
+
+
+class IpFilterMiddleware:
+    """Refuse requests from addresses the IP rules don't allow with a 403; sockets are closed."""
+
+    def __init__(self, app):
+        self.app = app
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] not in ("http", "websocket") or scope["path"].startswith(EXEMPT_PATH_PREFIXES):
+            await self.app(scope, receive, send)
+            return
+
+        client = scope.get("client")
+        ip = client[0] if client else ""
+        rules = await current_rules()
+        if rules.allows(ip):
+            await self.app(scope, receive, send)
+            return
+
+        logger.warning(f"Refused request from {ip}", extra={"client_ip": ip})
+        if scope["type"] == "websocket":
+            # Closing before the handshake is accepted rejects it with a 403
+            await send({"type": "websocket.close", "code": 1008})
+            return
+        response = error_response(
+            Request(scope), 403, "Access from your network is not allowed", "ip_blocked"
+        )
+        await response(scope, receive, send)
@@ -0,0 +1,212 @@
+"""
+Load test a running backend: concurrent POST /api/analyze and GET
+/api/artifacts traffic, with latency percentiles and error rates per tier,
+to check the analysis job queue and the database pools under load.
+
+Each worker picks a request at a time: a page of the artifact list
+(--list-share of them, at a random offset) or an analysis of one of the
+--image files at one of --tiers. Queued analyses (202) are polled until
+they finish, so an analysis's latency is how long the caller waits for the
+result; the time to the 202 is reported separately as "accepted". Nothing
+is retried, so every 429, 5xx, failed job and network error counts.
+
+Analyses skip the result cache (refresh) unless --cached is given, and are
+charged to the API key's user's quota: use a role in
+ANALYSIS_QUOTA_EXEMPT_ROLES, and expect the rate limit to show as 429s.
+
+    export FLL_API_KEY=...
+    python MainApp/load_test.py --image photo.jpg --tiers instant,fast --concurrency 16 --duration 60
+    python MainApp/load_test.py --image a.jpg --image b.png --requests 500 --json results.json
+
+Exits with 1 when more than --max-error-rate of the requests failed.
+"""
+
+import argparse
+import base64
+import json
+import math
+import os
+import random
+import sys
+import threading
+import time
+from collections import defaultdict
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any, Dict, List, Optional, Tuple
+
+from fll_cli import DEFAULT_URL, TIERS, CliError, Client
+
+PERCENTILES = (50, 90, 95, 99)
+LIST_PAGE_SIZE = 20
+# Finer than fll-cli's, so queued analyses are timed closely
+JOB_POLL_SECONDS = 0.25
+
+
+class Results:
+    """Latencies and errors per (operation, tier), shared by the workers."""
+
+    def __init__(self) -> None:
+        self._lock = threading.Lock()
+        self.latencies: Dict[Tuple[str, str], List[float]] = defaultdict(list)
+        self.errors: Dict[Tuple[str, str], Dict[str, int]] = defaultdict(lambda: defaultdict(int))
+
+    def record(self, operation: str, tier: str, seconds: float, error: Optional[str] = None) -> None:
+        with self._lock:
+            if error is None:
+                self.latencies[(operation, tier)].append(seconds)
+            else:
+                self.errors[(operation, tier)][error] += 1
+
+    def summary(self, elapsed: float) -> List[Dict[str, Any]]:
+        rows = []
+        for key in sorted(set(self.latencies) | set(self.errors)):
+            latencies = sorted(self.latencies.get(key, []))
+            failed = sum(self.errors.get(key, {}).values())
+            total = len(latencies) + failed
+            row: Dict[str, Any] = {
+                "operation": key[0],
+                "tier": key[1],
+                "requests": total,
+                "errors": failed,
+                "error_rate": failed / total if total else 0.0,
+                "per_second": total / elapsed if elapsed else 0.0,
+                "errors_by_kind": dict(self.errors.get(key, {})),
+            }
+            for p in PERCENTILES:
+                row[f"p{p}_ms"] = _percentile(latencies, p) * 1000 if latencies else None
+            row["max_ms"] = latencies[-1] * 1000 if latencies else None
+            rows.append(row)
+        return rows
+
+
+def _percentile(ordered: List[float], p: float) -> float:
+    """Nearest-rank percentile of an ascending list."""
+    return ordered[max(math.ceil(p / 100 * len(ordered)) - 1, 0)]
+
+
+def _error_kind(error: CliError) -> str:
+    return f"HTTP {error.status}" if error.status else "network"
+
+
+def analyze_once(client: Client, results: Results, image_data: str, tier: str, refresh: bool) -> None:
+    attempts = [0]
+    started = time.perf_counter()
+    try:
+        response = client.request("post", "/api/analyze", attempts, json={"image_data": image_data, "tier": tier, "refresh": refresh})
+        if response.status_code == 202:
+            results.record("accepted", tier, time.perf_counter() - started)
+            job = response.json()
+            while job["status"] not in ("done", "failed"):
+                time.sleep(JOB_POLL_SECONDS)
+                job = client.request("get", f"/api/analyze/jobs/{job['id']}", attempts).json()
+            if job["status"] == "failed":
+                results.record("analyze", tier, 0, "job failed")
+                return
+        results.record("analyze", tier, time.perf_counter() - started)
+    except CliError as e:
+        results.record("analyze", tier, 0, _error_kind(e))
+
+
+def list_once(client: Client, results: Results, max_offset: int) -> None:
+    attempts = [0]
+    started = time.perf_counter()
+    params = {"limit": LIST_PAGE_SIZE, "offset": random.randint(0, max_offset), "scope": "all"}
+    try:
+        client.request("get", "/api/artifacts", attempts, params=params)
+        results.record("list", "-", time.perf_counter() - started)
+    except CliError as e:
+        results.record("list", "-", 0, _error_kind(e))
+
+
+def run(args: argparse.Namespace, images: List[str]) -> Tuple[Results, float]:
+    client = Client(args.url, args.api_key, retries=0)
+    results = Results()
+    issued = [0]
+    lock = threading.Lock()
+    deadline = time.monotonic() + args.duration if args.requests is None else None
+
+    def take() -> bool:
+        """Claim the next request; False once the run is over."""
+        with lock:
+            if args.requests is not None and issued[0] >= args.requests:
+                return False
+            issued[0] += 1
+        return deadline is None or time.monotonic() < deadline
+
+    def worker() -> None:
+        while take():
+            if random.random() < args.list_share:
+                list_once(client, results, args.max_offset)
+            else:
+                analyze_once(client, results, random.choice(images), random.choice(args.tiers), not args.cached)
+
+    started = time.monotonic()
+    with ThreadPoolExecutor(max_workers=args.concurrency) as pool:
+        for future in [pool.submit(worker) for _ in range(args.concurrency)]:
+            future.result()
+    return results, time.monotonic() - started
+
+
+def _ms(value: Optional[float]) -> str:
+    return "-" if value is None else f"{value:.0f}"
+
+
+def print_summary(rows: List[Dict[str, Any]], elapsed: float) -> None:
+    print(f"\n{'operation':<10} {'tier':<9} {'reqs':>6} {'err%':>6} {'req/s':>7} " + " ".join(f"{'p' + str(p):>7}" for p in PERCENTILES) + f" {'max':>7}  (ms)")
+    for row in rows:
+        print(
+            f"{row['operation']:<10} {row['tier']:<9} {row['requests']:>6} {row['error_rate'] * 100:>5.1f}% {row['per_second']:>7.2f} "
+            + " ".join(f"{_ms(row[f'p{p}_ms']):>7}" for p in PERCENTILES)
+            + f" {_ms(row['max_ms']):>7}"
+        )
+        if row["errors_by_kind"]:
+            print(f"{'':<20} errors: " + ", ".join(f"{kind} x{count}" for kind, count in sorted(row["errors_by_kind"].items())))
+    print(f"\n{sum(row['requests'] for row in rows if row['operation'] != 'accepted')} requests in {elapsed:.1f}s")
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    parser = argparse.ArgumentParser(description="Load test the analyze pipeline and the artifact list")
+    parser.add_argument("--image", action="append", required=True, help="an image to analyse; repeat for several")
+    parser.add_argument("--tiers", default="fast", help=f"comma-separated, picked at random per analysis (of {', '.join(TIERS)}; default: fast)")
+    parser.add_argument("--concurrency", type=int, default=8, help="requests in flight (default: 8)")
+    parser.add_argument("--duration", type=float, default=30, help="seconds to run (default: 30)")
+    parser.add_argument("--requests", type=int, help="stop after this many requests instead of --duration")
+    parser.add_argument("--list-share", type=float, default=0.5, help="fraction of requests that list artifacts (default: 0.5)")
+    parser.add_argument("--max-offset", type=int, default=200, help="list pages start at a random offset up to this (default: 200)")
+    parser.add_argument("--cached", action="store_true", help="let analyses come from the result cache")
+    parser.add_argument("--max-error-rate", type=float, default=0.01, help="fail the run above this (default: 0.01)")
+    parser.add_argument("--json", help="also write the summary to this file")
+    parser.add_argument("--url", default=DEFAULT_URL, help=f"the backend (default: $FLL_API_URL or {DEFAULT_URL})")
+    parser.add_argument("--api-key", default=os.getenv("FLL_API_KEY"), help="an API key (default: $FLL_API_KEY)")
+    args = parser.parse_args(argv)
+
+    if not args.api_key:
+        parser.error("an API key is required: pass --api-key or set FLL_API_KEY")
+    args.tiers = [tier.strip() for tier in args.tiers.split(",") if tier.strip()]
+    unknown = [tier for tier in args.tiers if tier not in TIERS]
+    if unknown or not args.tiers:
+        parser.error(f"unknown tier(s): {', '.join(unknown) or '(none given)'}")
+    if args.concurrency < 1 or not 0 <= args.list_share <= 1:
+        parser.error("--concurrency must be at least 1 and --list-share between 0 and 1")
+    images = []
+    for path in args.image:
+        with open(path, "rb") as f:
+            images.append(base64.b64encode(f.read()).decode("ascii"))
+
+    limit = f"{args.requests} requests" if args.requests is not None else f"{args.duration:g}s"
+    print(f"Load testing {args.url} with {args.concurrency} concurrent requests for {limit}...")
+    results, elapsed = run(args, images)
+    rows = results.summary(elapsed)
+    print_summary(rows, elapsed)
+    if args.json:
+        with open(args.json, "w", encoding="utf-8") as f:
+            json.dump({"elapsed_seconds": elapsed, "concurrency": args.concurrency, "results": rows}, f, indent=2)
+
+    counted = [row for row in rows if row["operation"] != "accepted"]
+    total = sum(row["requests"] for row in counted)
+    failed = sum(row["errors"] for row in counted)
+    return 1 if total and failed / total > args.max_error_rate else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
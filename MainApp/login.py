@@ -3,74 +3,91 @@ User authentication and management utilities.
 This module provides database functions for user management and authentication.
 The UI is now handled by the React frontend.
 """
-import sqlite3
-from datetime import datetime
+import hashlib
+import json
+import logging
+import secrets
+import uuid
+from datetime import datetime, timedelta, timezone
 
-import bcrypt
-import yaml
-from yaml.loader import SafeLoader
+from config import (
+    ADMIN_EMAIL,
+    ADMIN_NAME,
+    ADMIN_PASSWORD,
+    ADMIN_USERNAME,
+    INVITE_EXPIRE_DAYS,
+    LOCKOUT_MINUTES,
+    LOCKOUT_THRESHOLD,
+    PASSWORD_RESET_EXPIRE_MINUTES,
+    REFRESH_TOKEN_EXPIRE_DAYS,
+    SESSION_EXPIRE_HOURS,
+    USER_DATABASE_URL,
+)
+from migrate import migrate, pending_migrations
+from passwords import hash_password, migrate_plaintext_passwords
+from user_store import create_user_store
 
-DB_FILE = "users.db"
+logger = logging.getLogger(__name__)
+
+# SQLite in MainApp/users.db unless USER_DATABASE_URL points somewhere else
+_store = create_user_store(USER_DATABASE_URL)
 
 
 # ----------------------------------------------------------------------
 # Database Functions
 # ----------------------------------------------------------------------
 def init_db():
-    """Initialize the users database and create tables if they don't exist."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
-        c = conn.cursor()
-        c.execute("""CREATE TABLE IF NOT EXISTS users (
-                        username TEXT PRIMARY KEY,
-                        name TEXT,
-                        hashed_password TEXT,
-                        role TEXT,
-                        email TEXT)""")
-        c.execute("""CREATE TABLE IF NOT EXISTS audit_logs (
-                        id INTEGER PRIMARY KEY AUTOINCREMENT,
-                        timestamp TEXT,
-                        username TEXT,
-                        action TEXT)""")
-
-        # Load config and insert users from user.yaml if not in DB
+    """Bring the users database schema up to date and seed it."""
+    migrate(_store)
+    with _store.connect() as conn:
+        # Rows created before hashing was introduced
+        migrated = migrate_plaintext_passwords(conn)
+        if migrated:
+            logger.info(f"Hashed {migrated} plaintext password(s)")
+        conn.commit()
+
+    if ADMIN_PASSWORD:
+        if create_first_admin(ADMIN_USERNAME, ADMIN_NAME, ADMIN_PASSWORD, ADMIN_EMAIL):
+            logger.info(f"Created admin account '{ADMIN_USERNAME}' from ADMIN_PASSWORD")
+    elif count_admins() == 0:
+        logger.warning(
+            "No admin account exists. Set ADMIN_PASSWORD and restart, create one "
+            "with POST /auth/bootstrap, or run admin_cli.py create-user --role admin"
+        )
+
+
+def create_first_admin(username, name, password, email):
+    """
+    Create an admin account, but only while there is no admin at all.
+    Returns False (and changes nothing) once any admin exists.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        # A single statement, so two first-run requests can't both succeed
         try:
-            with open("user.yaml") as file:
-                config = yaml.load(file, Loader=SafeLoader)
-            
-            # Insert users from config.yaml if not in DB
-            for username, details in config.get("credentials", {}).get("usernames", {}).items():
-                c.execute("SELECT * FROM users WHERE username=?", (username,))
-                if not c.fetchone():
-                    # Hash password if it's not already hashed
-                    password = details.get("password", "")
-                    if not password.startswith("$2b$"):  # bcrypt hash prefix
-                        hashed_pw = bcrypt.hashpw(password.encode(), bcrypt.gensalt()).decode()
-                    else:
-                        hashed_pw = password
-                    
-                    c.execute(
-                        "INSERT INTO users VALUES (?, ?, ?, ?, ?)",
-                        (
-                            username,
-                            details.get("name", ""),
-                            hashed_pw,
-                            details.get("role", "user"),
-                            details.get("email", ""),
-                        ),
-                    )
-        except FileNotFoundError:
-            # user.yaml not found, skip initialization from config
-            pass
-        except Exception as e:
-            # Log error but don't fail initialization
-            print(f"Warning: Could not load users from user.yaml: {e}")
-        
+            c.execute(
+                """INSERT INTO users (username, name, hashed_password, role, email)
+                   SELECT ?, ?, ?, 'admin', ?
+                   WHERE NOT EXISTS (SELECT 1 FROM users WHERE role='admin')""",
+                (username, name, hash_password(password), email),
+            )
+        except _store.integrity_errors:
+            return False  # Username taken by a non-admin account
         conn.commit()
+        if c.rowcount == 0:
+            return False
+    log_action(username, "Initial admin account created")
+    return True
+
+
+def pending_user_migrations():
+    """Migrations the user database still needs; raises if it can't be reached."""
+    return pending_migrations(_store)
 
 
 def log_action(username, action):
     """Log an action to the audit log."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
         c.execute(
             "INSERT INTO audit_logs (timestamp, username, action) VALUES (?, ?, ?)",
@@ -81,7 +98,7 @@ def log_action(username, action):
 
 def get_user_role(username):
     """Get the role of a user by username."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
         c.execute("SELECT role FROM users WHERE username=?", (username,))
         result = c.fetchone()
@@ -90,58 +107,1149 @@ def get_user_role(username):
 
 def get_user_info(username):
     """Get user information (name, email, role) by username."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
         c.execute("SELECT name, email, role FROM users WHERE username=?", (username,))
         return c.fetchone()
 
 
+def get_credentials(username):
+    """(username, name, hashed_password, role, email, active) for login, or None."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT username, name, hashed_password, role, email, active FROM users WHERE username=?",
+            (username,),
+        )
+        return c.fetchone()
+
+
+def update_password_hash(username, hashed_password):
+    """Replace a stored hash without logging it as a password change (rehash on login)."""
+    with _store.connect() as conn:
+        conn.cursor().execute(
+            "UPDATE users SET hashed_password=? WHERE username=?", (hashed_password, username)
+        )
+        conn.commit()
+
+
+USER_COLUMNS = "username, name, role, email, locked_until, active, deactivated_at"
+
+
 def get_users():
     """Get all users from the database."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
-        c.execute("SELECT username, name, role, email FROM users")
+        c.execute(f"SELECT {USER_COLUMNS} FROM users")
         return c.fetchall()
 
 
 def add_user(username, name, password, role, email):
     """Add a new user to the database."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
-        hashed_pw = bcrypt.hashpw(password.encode(), bcrypt.gensalt()).decode()
+        hashed_pw = hash_password(password)
         try:
             c.execute(
-                "INSERT INTO users VALUES (?, ?, ?, ?, ?)",
+                "INSERT INTO users (username, name, hashed_password, role, email) VALUES (?, ?, ?, ?, ?)",
                 (username, name, hashed_pw, role, email),
             )
             conn.commit()
             log_action(username, f"User {username} created")
             return True
-        except sqlite3.IntegrityError:
+        except _store.integrity_errors:
             return False
 
 
 def reset_password(username, new_password):
     """Reset a user's password."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+    with _store.connect() as conn:
         c = conn.cursor()
-        hashed_pw = bcrypt.hashpw(new_password.encode(), bcrypt.gensalt()).decode()
+        hashed_pw = hash_password(new_password)
         c.execute(
             "UPDATE users SET hashed_password=? WHERE username=?", (hashed_pw, username)
         )
         conn.commit()
         log_action(username, "Password reset")
+    # Sessions opened with the old password shouldn't outlive it
+    revoke_user_sessions(username)
+
+
+def query_users(role=None, email=None, search=None, active=None, limit=50, offset=0):
+    """
+    Filtered, paginated user listing.
+    Returns (rows, total) where rows match get_users() and total ignores paging.
+    """
+    clauses, params = [], []
+    if role:
+        clauses.append("role=?")
+        params.append(role)
+    if active is not None:
+        clauses.append("active=?")
+        params.append(1 if active else 0)
+    if email:
+        clauses.append("lower(email) LIKE ?")
+        params.append(f"%{email.lower()}%")
+    if search:
+        clauses.append("(lower(username) LIKE ? OR lower(name) LIKE ?)")
+        params += [f"%{search.lower()}%"] * 2
+    where = f" WHERE {' AND '.join(clauses)}" if clauses else ""
+
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT COUNT(*) FROM users{where}", params)
+        total = c.fetchone()[0]
+        c.execute(
+            f"SELECT {USER_COLUMNS} FROM users{where} ORDER BY username LIMIT ? OFFSET ?",
+            params + [limit, offset],
+        )
+        return c.fetchall(), total
+
+
+def get_user_row(username):
+    """A single user in the same shape as get_users() rows, or None."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {USER_COLUMNS} FROM users WHERE username=?", (username,))
+        return c.fetchone()
+
+
+def email_in_use(email, exclude_username=None):
+    """True if another account already has this email address."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT 1 FROM users WHERE lower(email)=lower(?) AND (? IS NULL OR username<>?)",
+            (email, exclude_username, exclude_username),
+        )
+        return c.fetchone() is not None
+
+
+def count_admins():
+    """Admins who can still sign in."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT COUNT(*) FROM users WHERE role='admin' AND active=1")
+        return c.fetchone()[0]
+
+
+def update_user(username, changes, updated_by):
+    """Apply name/email/role changes. Returns False if the user doesn't exist."""
+    allowed = {k: v for k, v in changes.items() if k in ("name", "email", "role")}
+    if not allowed:
+        return get_user_row(username) is not None
+    assignments = ", ".join(f"{column}=?" for column in allowed)
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            f"UPDATE users SET {assignments} WHERE username=?",
+            list(allowed.values()) + [username],
+        )
+        conn.commit()
+        if c.rowcount == 0:
+            return False
+    log_action(updated_by, f"Updated {username}: {', '.join(sorted(allowed))}")
+    if "role" in allowed:
+        # Existing sessions carry the old role; make the user sign in again
+        revoke_user_sessions(username)
+    return True
+
+
+def delete_user(username, deleted_by):
+    """Remove a user and everything that lets them sign in. Returns False if not found."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("DELETE FROM users WHERE username=?", (username,))
+        if c.rowcount == 0:
+            return False
+        c.execute("DELETE FROM user_identities WHERE username=?", (username,))
+        c.execute("DELETE FROM organization_members WHERE username=?", (username,))
+        c.execute(
+            "UPDATE api_keys SET revoked_at=? WHERE username=? AND revoked_at IS NULL",
+            (_now().isoformat(), username),
+        )
+        c.execute(
+            "UPDATE refresh_tokens SET revoked_at=? WHERE username=? AND revoked_at IS NULL",
+            (_now().isoformat(), username),
+        )
+        c.execute(
+            "UPDATE browser_sessions SET revoked_at=? WHERE username=? AND revoked_at IS NULL",
+            (_now().isoformat(), username),
+        )
+        conn.commit()
+    log_action(deleted_by, f"Deleted user {username}")
+    return True
+
+
+def deactivate_user(username, deactivated_by):
+    """
+    Stop a user signing in while keeping their account, history and artifacts.
+    Ends their sessions; their API keys stop working until reactivation.
+    Returns False if there is no such active user.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE users SET active=0, deactivated_at=? WHERE username=? AND active=1",
+            (_now().isoformat(), username),
+        )
+        conn.commit()
+        if c.rowcount == 0:
+            return False
+    revoke_user_sessions(username)
+    log_action(deactivated_by, f"Deactivated {username}")
+    return True
+
+
+def reactivate_user(username, reactivated_by):
+    """Let a deactivated user sign in again. Returns False if there is no such inactive user."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            """UPDATE users SET active=1, deactivated_at=NULL, failed_attempts=0, locked_until=NULL
+               WHERE username=? AND active=0""",
+            (username,),
+        )
+        conn.commit()
+        if c.rowcount == 0:
+            return False
+    log_action(reactivated_by, f"Reactivated {username}")
+    return True
+
+
+# ----------------------------------------------------------------------
+# Account Lockout
+# ----------------------------------------------------------------------
+def get_locked_until(username):
+    """Return when the account's lock expires, or None if it isn't locked."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT locked_until FROM users WHERE username=?", (username,))
+        row = c.fetchone()
+    if not row or not row[0]:
+        return None
+    locked_until = datetime.fromisoformat(row[0])
+    return locked_until if locked_until > datetime.now(timezone.utc) else None
+
+
+def record_failed_login(username):
+    """Count a failed login; lock the account once the threshold is reached.
+
+    Returns the lock expiry if this attempt locked the account, else None.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE users SET failed_attempts = failed_attempts + 1 WHERE username=?",
+            (username,),
+        )
+        c.execute("SELECT failed_attempts FROM users WHERE username=?", (username,))
+        row = c.fetchone()
+        locked_until = None
+        if row and row[0] >= LOCKOUT_THRESHOLD:
+            locked_until = datetime.now(timezone.utc) + timedelta(minutes=LOCKOUT_MINUTES)
+            c.execute(
+                "UPDATE users SET failed_attempts=0, locked_until=? WHERE username=?",
+                (locked_until.isoformat(), username),
+            )
+        conn.commit()
+    if locked_until:
+        log_action(username, f"Account locked after {LOCKOUT_THRESHOLD} failed logins")
+    return locked_until
+
+
+def reset_failed_logins(username):
+    """Clear the failure count after a successful login."""
+    with _store.connect() as conn:
+        conn.cursor().execute(
+            "UPDATE users SET failed_attempts=0, locked_until=NULL WHERE username=?",
+            (username,),
+        )
+        conn.commit()
+
+
+def unlock_user(username, unlocked_by):
+    """Lift a lockout early. Returns False if the user doesn't exist."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE users SET failed_attempts=0, locked_until=NULL WHERE username=?",
+            (username,),
+        )
+        conn.commit()
+        if c.rowcount == 0:
+            return False
+    log_action(unlocked_by, f"Unlocked account {username}")
+    return True
+
+
+def get_audit_logs(limit=50, username=None):
+    """Get the latest audit log entries, optionally only one user's."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT timestamp, username, action FROM audit_logs WHERE ? IS NULL OR username=? ORDER BY id DESC LIMIT ?",
+            (username, username, limit),
+        )
+        return c.fetchall()
+
+
+# ----------------------------------------------------------------------
+# Refresh Tokens
+# ----------------------------------------------------------------------
+class RefreshTokenError(Exception):
+    """The refresh token is unknown, expired or revoked."""
+
+
+def _hash_token(token):
+    return hashlib.sha256(token.encode()).hexdigest()
+
+
+def _now():
+    return datetime.now(timezone.utc)
+
+
+def _store_refresh_token(c, username, family_id):
+    token = secrets.token_urlsafe(48)
+    now = _now()
+    c.execute(
+        "INSERT INTO refresh_tokens (token_hash, username, family_id, created_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+        (
+            _hash_token(token),
+            username,
+            family_id,
+            now.isoformat(),
+            (now + timedelta(days=REFRESH_TOKEN_EXPIRE_DAYS)).isoformat(),
+        ),
+    )
+    return token
+
+
+def issue_refresh_token(username):
+    """Start a new session for the user and return its refresh token."""
+    with _store.connect() as conn:
+        token = _store_refresh_token(conn.cursor(), username, uuid.uuid4().hex)
+        conn.commit()
+        return token
+
+
+def _reused_refresh_token(conn, c, username, family_id, now):
+    """Revoke the session of a token presented twice; returns the error to raise."""
+    c.execute(
+        "UPDATE refresh_tokens SET revoked_at=? WHERE family_id=? AND revoked_at IS NULL",
+        (now.isoformat(), family_id),
+    )
+    conn.commit()
+    log_action(username, "Refresh token reused; session revoked")
+    return RefreshTokenError("Refresh token has already been used")
+
+
+def rotate_refresh_token(token):
+    """
+    Exchange a refresh token for a new one. Returns (username, new_token).
+
+    Each token works once. Presenting an already-used token means it was
+    copied, so the whole session is revoked and RefreshTokenError raised.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT username, family_id, expires_at, revoked_at FROM refresh_tokens WHERE token_hash=?",
+            (_hash_token(token),),
+        )
+        row = c.fetchone()
+        if not row:
+            raise RefreshTokenError("Invalid refresh token")
+
+        username, family_id, expires_at, revoked_at = row
+        now = _now()
+        if revoked_at:
+            raise _reused_refresh_token(conn, c, username, family_id, now)
+        if datetime.fromisoformat(expires_at) <= now:
+            raise RefreshTokenError("Refresh token has expired")
+
+        # Claim the token in one statement: of two concurrent refreshes with
+        # the same token only one can, and the other counts as reuse
+        c.execute(
+            "UPDATE refresh_tokens SET revoked_at=? WHERE token_hash=? AND revoked_at IS NULL",
+            (now.isoformat(), _hash_token(token)),
+        )
+        if c.rowcount != 1:
+            raise _reused_refresh_token(conn, c, username, family_id, now)
+        new_token = _store_refresh_token(c, username, family_id)
+        conn.commit()
+        return username, new_token
+
+
+def revoke_refresh_token(token):
+    """End the session the refresh token belongs to. Returns the username, if any."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT username, family_id FROM refresh_tokens WHERE token_hash=?", (_hash_token(token),))
+        row = c.fetchone()
+        if not row:
+            return None
+        username, family_id = row
+        c.execute(
+            "UPDATE refresh_tokens SET revoked_at=? WHERE family_id=? AND revoked_at IS NULL",
+            (_now().isoformat(), family_id),
+        )
+        conn.commit()
+        return username
+
+
+def revoke_user_sessions(username):
+    """Revoke every refresh token and browser session the user holds (e.g. after a password change)."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE refresh_tokens SET revoked_at=? WHERE username=? AND revoked_at IS NULL",
+            (_now().isoformat(), username),
+        )
+        c.execute(
+            "UPDATE browser_sessions SET revoked_at=? WHERE username=? AND revoked_at IS NULL",
+            (_now().isoformat(), username),
+        )
+        conn.commit()
+
+
+# ----------------------------------------------------------------------
+# Invites and Registration
+# ----------------------------------------------------------------------
+INVITE_PREFIX = "inv_"
+
+
+class RegistrationError(Exception):
+    """The invite code is unknown, used, revoked, expired or for someone else."""
+
+
+def create_invite(role, created_by, email=None):
+    """Create a single-use invite. Returns (invite_id, code); the code is shown only once."""
+    code = INVITE_PREFIX + secrets.token_urlsafe(16)
+    now = _now()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        invite_id = _store.insert_returning_id(
+            c,
+            """INSERT INTO invites (code_hash, prefix, role, email, created_by, created_at, expires_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)""",
+            (
+                _hash_token(code),
+                code[:8],
+                role,
+                email,
+                created_by,
+                now.isoformat(),
+                (now + timedelta(days=INVITE_EXPIRE_DAYS)).isoformat(),
+            ),
+        )
+        conn.commit()
+    log_action(created_by, f"Created a {role} invite" + (f" for {email}" if email else ""))
+    return invite_id, code
+
+
+INVITE_COLUMNS = "id, prefix, role, email, created_by, created_at, expires_at, used_by, used_at, revoked_at"
+
+
+def get_invite(invite_id):
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {INVITE_COLUMNS} FROM invites WHERE id=?", (invite_id,))
+        return c.fetchone()
+
+
+def list_invites():
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {INVITE_COLUMNS} FROM invites ORDER BY id DESC")
+        return c.fetchall()
+
+
+def revoke_invite(invite_id, revoked_by):
+    """Revoke an unused invite. Returns False if there is no such unused invite."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE invites SET revoked_at=? WHERE id=? AND used_at IS NULL AND revoked_at IS NULL",
+            (_now().isoformat(), invite_id),
+        )
+        conn.commit()
+        if c.rowcount == 0:
+            return False
+    log_action(revoked_by, f"Revoked invite {invite_id}")
+    return True
+
+
+def register_user(username, name, password, email, invite_code=None, default_role="user"):
+    """
+    Create an account through self-service registration. With an invite code
+    the invite is claimed in the same transaction and its role used.
+
+    Returns the new user's role, or None if the username is taken; raises
+    RegistrationError for a bad invite.
+    """
+    now = _now()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        role = default_role
+        if invite_code is not None:
+            c.execute(
+                "SELECT id, role, email, expires_at, used_at, revoked_at FROM invites WHERE code_hash=?",
+                (_hash_token(invite_code),),
+            )
+            row = c.fetchone()
+            if not row or row[4] or row[5]:
+                raise RegistrationError("Invalid or already used invite code")
+            invite_id, role, invite_email, expires_at = row[:4]
+            if datetime.fromisoformat(expires_at) <= now:
+                raise RegistrationError("This invite has expired")
+            if invite_email and invite_email.lower() != email.lower():
+                raise RegistrationError("This invite is for a different email address")
+            # Guarded on used_at so two sign-ups racing for one code can't both win
+            c.execute(
+                "UPDATE invites SET used_by=?, used_at=? WHERE id=? AND used_at IS NULL",
+                (username, now.isoformat(), invite_id),
+            )
+            if c.rowcount == 0:
+                raise RegistrationError("Invalid or already used invite code")
+        try:
+            c.execute(
+                "INSERT INTO users (username, name, hashed_password, role, email) VALUES (?, ?, ?, ?, ?)",
+                (username, name, hash_password(password), role, email),
+            )
+        except _store.integrity_errors:
+            conn.rollback()
+            return None
+        conn.commit()
+    log_action(username, "Registered" + (" with an invite" if invite_code is not None else ""))
+    return role
+
+
+# ----------------------------------------------------------------------
+# User Settings
+# ----------------------------------------------------------------------
+def get_user_settings(username):
+    """The user's saved settings object; empty if nothing has been saved."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT settings FROM user_settings WHERE username=?", (username,))
+        row = c.fetchone()
+    return json.loads(row[0]) if row else {}
+
+
+def save_user_settings(username, settings):
+    """Replace the user's settings with a JSON-serialisable dict."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        values = (json.dumps(settings), _now().isoformat(), username)
+        c.execute("UPDATE user_settings SET settings=?, updated_at=? WHERE username=?", values)
+        if c.rowcount == 0:
+            c.execute("INSERT INTO user_settings (settings, updated_at, username) VALUES (?, ?, ?)", values)
+        conn.commit()
+
+
+# ----------------------------------------------------------------------
+# Login History
+# ----------------------------------------------------------------------
+# Older entries are pruned so the table doesn't grow without bound; successes
+# and failures are kept separately so a burst of bad guesses can't push out
+# the user's real sign-ins
+LOGIN_HISTORY_KEEP = 100
+
+
+def record_login(username, ip_address, user_agent, method="password", succeeded=True):
+    """Remember a sign-in, or a failed password attempt on an existing account."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            """INSERT INTO login_history (username, logged_in_at, ip_address, user_agent, method, succeeded)
+               VALUES (?, ?, ?, ?, ?, ?)""",
+            (username, _now().isoformat(), ip_address, (user_agent or "")[:300], method, int(succeeded)),
+        )
+        c.execute(
+            """DELETE FROM login_history WHERE username=? AND succeeded=? AND id NOT IN (
+                   SELECT id FROM login_history WHERE username=? AND succeeded=? ORDER BY id DESC LIMIT ?
+               )""",
+            (username, int(succeeded), username, int(succeeded), LOGIN_HISTORY_KEEP),
+        )
+        conn.commit()
+
+
+def get_login_history(username, limit=20):
+    """The user's most recent sign-ins, newest first: (logged_in_at, ip_address, user_agent, method)."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            """SELECT logged_in_at, ip_address, user_agent, method FROM login_history
+               WHERE username=? AND succeeded=1 ORDER BY id DESC LIMIT ?""",
+            (username, limit),
+        )
+        return c.fetchall()
+
+
+# ----------------------------------------------------------------------
+# Admin Statistics
+# ----------------------------------------------------------------------
+def get_admin_stats(recent_limit=10):
+    """Account, session and login-failure figures for the admin dashboard."""
+    now = _now()
+    since = (now - timedelta(hours=24)).isoformat()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT role, active, COUNT(*) FROM users GROUP BY role, active")
+        users_by_role, active_users, deactivated_users = {}, 0, 0
+        for role, active, count in c.fetchall():
+            if active:
+                users_by_role[role] = users_by_role.get(role, 0) + count
+                active_users += count
+            else:
+                deactivated_users += count
+
+        # A session is a live refresh-token family or browser session
+        c.execute(
+            """SELECT username, family_id FROM refresh_tokens WHERE revoked_at IS NULL AND expires_at > ?
+               UNION SELECT username, session_hash FROM browser_sessions WHERE revoked_at IS NULL AND expires_at > ?""",
+            (now.isoformat(), now.isoformat()),
+        )
+        sessions = c.fetchall()
+
+        c.execute("SELECT COUNT(*) FROM login_history WHERE succeeded=0 AND logged_in_at > ?", (since,))
+        failed_logins_24h = c.fetchone()[0]
+        c.execute(
+            """SELECT username, logged_in_at, ip_address FROM login_history
+               WHERE succeeded=0 ORDER BY id DESC LIMIT ?""",
+            (recent_limit,),
+        )
+        recent_failed_logins = c.fetchall()
+
+        c.execute(
+            "SELECT username, locked_until FROM users WHERE locked_until > ? ORDER BY locked_until",
+            (now.isoformat(),),
+        )
+        locked_accounts = c.fetchall()
+
+    return {
+        "total_users": active_users + deactivated_users,
+        "active_users": active_users,
+        "deactivated_users": deactivated_users,
+        "users_by_role": users_by_role,
+        "active_sessions": len(sessions),
+        "signed_in_users": len({username for username, _ in sessions}),
+        "failed_logins_24h": failed_logins_24h,
+        "recent_failed_logins": recent_failed_logins,
+        "locked_accounts": locked_accounts,
+        "generated_at": now.isoformat(),
+    }
+
+
+# ----------------------------------------------------------------------
+# Personal Data (export and erasure)
+# ----------------------------------------------------------------------
+# Tables whose rows belong to a single user, with the columns safe to export
+# (never hashes or secrets). They are erased along with the account.
+_PERSONAL_TABLES = {
+    "user_identities": "provider, subject, email, linked_at",
+    "api_keys": "id, name, prefix, created_at, last_used_at, revoked_at",
+    "refresh_tokens": "created_at, expires_at, revoked_at",
+    "browser_sessions": "created_at, expires_at, revoked_at",
+    "password_reset_tokens": "created_at, expires_at, used_at",
+    "login_history": "logged_in_at, ip_address, user_agent, method, succeeded",
+    "user_settings": "settings, updated_at",
+    "organization_members": "org_id, rank, joined_at",
+}
+
+# Stands in for an erased user in the audit log and on their artifacts
+ERASED_USERNAME = "deleted-user"
+
+
+def _rows_as_dicts(c, columns):
+    names = [column.strip() for column in columns.split(",")]
+    return [dict(zip(names, row)) for row in c.fetchall()]
+
+
+def export_user_data(username):
+    """Everything the user database holds about an account, or None if it doesn't exist."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        account_columns = "username, name, role, email, failed_attempts, locked_until, active, deactivated_at"
+        c.execute(f"SELECT {account_columns} FROM users WHERE username=?", (username,))
+        accounts = _rows_as_dicts(c, account_columns)
+        if not accounts:
+            return None
+        data = {"account": accounts[0]}
+        for table, columns in _PERSONAL_TABLES.items():
+            c.execute(f"SELECT {columns} FROM {table} WHERE username=?", (username,))
+            data[table] = _rows_as_dicts(c, columns)
+        c.execute("SELECT timestamp, action FROM audit_logs WHERE username=? ORDER BY id", (username,))
+        data["audit_logs"] = _rows_as_dicts(c, "timestamp, action")
+    return data
+
+
+def erase_user(username):
+    """
+    Delete an account and its personal data at the user's request. Audit
+    entries are kept for accountability but no longer name the user.
+    Returns False if the user doesn't exist.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("DELETE FROM users WHERE username=?", (username,))
+        if c.rowcount == 0:
+            return False
+        for table in _PERSONAL_TABLES:
+            c.execute(f"DELETE FROM {table} WHERE username=?", (username,))
+        c.execute("UPDATE audit_logs SET username=? WHERE username=?", (ERASED_USERNAME, username))
+        c.execute("UPDATE invites SET created_by=? WHERE created_by=?", (ERASED_USERNAME, username))
+        c.execute("UPDATE invites SET used_by=?, email=NULL WHERE used_by=?", (ERASED_USERNAME, username))
+        conn.commit()
+    log_action(ERASED_USERNAME, "Account deleted at the user's request")
+    return True
+
+
+# ----------------------------------------------------------------------
+# Browser Sessions
+# ----------------------------------------------------------------------
+def create_browser_session(username):
+    """Start a cookie session. Returns (session_id, csrf_token, expires_in_seconds)."""
+    session_id = secrets.token_urlsafe(32)
+    csrf_token = secrets.token_urlsafe(32)
+    now = _now()
+    with _store.connect() as conn:
+        conn.cursor().execute(
+            "INSERT INTO browser_sessions (session_hash, username, csrf_token, created_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+            (
+                _hash_token(session_id),
+                username,
+                csrf_token,
+                now.isoformat(),
+                (now + timedelta(hours=SESSION_EXPIRE_HOURS)).isoformat(),
+            ),
+        )
+        conn.commit()
+    return session_id, csrf_token, SESSION_EXPIRE_HOURS * 3600
+
+
+def get_browser_session(session_id):
+    """Return (username, role, csrf_token) for a live session, else None."""
+    if not session_id:
+        return None
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            """SELECT u.username, u.role, s.csrf_token, s.expires_at FROM browser_sessions s
+               JOIN users u ON u.username = s.username
+               WHERE s.session_hash=? AND s.revoked_at IS NULL AND u.active=1""",
+            (_hash_token(session_id),),
+        )
+        row = c.fetchone()
+    if not row or datetime.fromisoformat(row[3]) <= _now():
+        return None
+    return row[0], row[1], row[2]
+
+
+def revoke_browser_session(session_id):
+    """End a cookie session. Returns the username, if it was live."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT username FROM browser_sessions WHERE session_hash=? AND revoked_at IS NULL",
+            (_hash_token(session_id),),
+        )
+        row = c.fetchone()
+        if not row:
+            return None
+        c.execute(
+            "UPDATE browser_sessions SET revoked_at=? WHERE session_hash=?",
+            (_now().isoformat(), _hash_token(session_id)),
+        )
+        conn.commit()
+        return row[0]
+
+
+# ----------------------------------------------------------------------
+# Password Reset
+# ----------------------------------------------------------------------
+class PasswordResetError(Exception):
+    """The reset token is unknown, expired or already used."""
+
+
+def find_user_for_reset(identifier):
+    """Look an active user up by username or email. Returns (username, email) or None."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT username, email FROM users WHERE (username=? OR lower(email)=lower(?)) AND active=1",
+            (identifier, identifier),
+        )
+        return c.fetchone()
+
+
+def create_password_reset_token(username):
+    """Issue a single-use reset token, replacing any outstanding ones for the user."""
+    token = secrets.token_urlsafe(32)
+    now = _now()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "UPDATE password_reset_tokens SET used_at=? WHERE username=? AND used_at IS NULL",
+            (now.isoformat(), username),
+        )
+        c.execute(
+            "INSERT INTO password_reset_tokens (token_hash, username, created_at, expires_at) VALUES (?, ?, ?, ?)",
+            (
+                _hash_token(token),
+                username,
+                now.isoformat(),
+                (now + timedelta(minutes=PASSWORD_RESET_EXPIRE_MINUTES)).isoformat(),
+            ),
+        )
+        conn.commit()
+    log_action(username, "Password reset requested")
+    return token
+
+
+def reset_password_with_token(token, new_password):
+    """Set a new password using a reset token. Returns the username."""
+    now = _now()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        # Claim the token in one statement, so two requests racing with the same link can't both succeed
+        c.execute(
+            "UPDATE password_reset_tokens SET used_at=? WHERE token_hash=? AND used_at IS NULL AND expires_at > ?",
+            (now.isoformat(), _hash_token(token), now.isoformat()),
+        )
+        if c.rowcount != 1:
+            raise PasswordResetError("This reset link is invalid or has expired")
+        c.execute("SELECT username FROM password_reset_tokens WHERE token_hash=?", (_hash_token(token),))
+        row = c.fetchone()
+        conn.commit()
+
+    username = row[0]
+    reset_password(username, new_password)
+    # A reset proves control of the account, so lift any lockout too
+    reset_failed_logins(username)
+    return username
+
+
+# ----------------------------------------------------------------------
+# External Identities
+# ----------------------------------------------------------------------
+def find_user_for_identity(provider, subject, email=None):
+    """
+    Resolve an OAuth identity to a local username.
+
+    A known (provider, subject) pair wins. Otherwise a verified email that
+    matches exactly one local account links the identity to it. Returns None
+    if no account matches; new accounts are never created this way.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT username FROM user_identities WHERE provider=? AND subject=?",
+            (provider, subject),
+        )
+        row = c.fetchone()
+        if row:
+            return row[0]
+        if not email:
+            return None
+
+        c.execute("SELECT username FROM users WHERE lower(email)=lower(?)", (email,))
+        matches = c.fetchall()
+        if len(matches) != 1:
+            return None
+        username = matches[0][0]
+        c.execute(
+            "INSERT INTO user_identities (provider, subject, username, email, linked_at) VALUES (?, ?, ?, ?, ?)",
+            (provider, subject, username, email, _now().isoformat()),
+        )
+        conn.commit()
+    log_action(username, f"Linked {provider} sign-in")
+    return username
+
+
+# ----------------------------------------------------------------------
+# API Keys
+# ----------------------------------------------------------------------
+API_KEY_PREFIX = "fll_"
+
+
+def create_api_key(username, name):
+    """Create a key for the user. Returns (key_id, key); the key is shown only once."""
+    key = API_KEY_PREFIX + secrets.token_urlsafe(32)
+    with _store.connect() as conn:
+        c = conn.cursor()
+        key_id = _store.insert_returning_id(
+            c,
+            "INSERT INTO api_keys (username, name, prefix, key_hash, created_at) VALUES (?, ?, ?, ?, ?)",
+            (username, name, key[:12], _hash_token(key), _now().isoformat()),
+        )
+        conn.commit()
+    log_action(username, f"API key '{name}' created")
+    return key_id, key
+
+
+def list_api_keys(username=None):
+    """Keys (without secrets) for one user, or for everyone if username is None."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        query = "SELECT id, username, name, prefix, created_at, last_used_at, revoked_at FROM api_keys"
+        if username is None:
+            c.execute(query + " ORDER BY id DESC")
+        else:
+            c.execute(query + " WHERE username=? ORDER BY id DESC", (username,))
+        return c.fetchall()
+
+
+def revoke_api_key(key_id, username=None):
+    """Revoke a key; when username is given it must own the key. Returns False if not found."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        if username is None:
+            c.execute(
+                "UPDATE api_keys SET revoked_at=? WHERE id=? AND revoked_at IS NULL",
+                (_now().isoformat(), key_id),
+            )
+        else:
+            c.execute(
+                "UPDATE api_keys SET revoked_at=? WHERE id=? AND username=? AND revoked_at IS NULL",
+                (_now().isoformat(), key_id, username),
+            )
+        conn.commit()
+        return c.rowcount > 0
+
+
+def authenticate_api_key(key):
+    """Return (username, role, key id) for a valid, unrevoked key, else None."""
+    if not key or not key.startswith(API_KEY_PREFIX):
+        return None
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            """SELECT k.id, u.username, u.role FROM api_keys k
+               JOIN users u ON u.username = k.username
+               WHERE k.key_hash=? AND k.revoked_at IS NULL AND u.active=1""",
+            (_hash_token(key),),
+        )
+        row = c.fetchone()
+        if not row:
+            return None
+        c.execute("UPDATE api_keys SET last_used_at=? WHERE id=?", (_now().isoformat(), row[0]))
+        conn.commit()
+        return row[1], row[2], row[0]
+
+
+# ----------------------------------------------------------------------
+# Organizations
+# ----------------------------------------------------------------------
+# Ranks within an organization, highest first. Owners manage ranks and may
+# remove anyone; managers add and remove plain members.
+ORG_RANKS = ("owner", "manager", "member")
+
+
+class OrganizationError(Exception):
+    """The change would leave an organization without an owner."""
+
+
+def create_organization(slug, name, owner, created_by):
+    """Create an organization with `owner` as its first owner. Returns the id, or None if the slug is taken."""
+    now = _now().isoformat()
+    with _store.connect() as conn:
+        c = conn.cursor()
+        try:
+            org_id = _store.insert_returning_id(
+                c,
+                "INSERT INTO organizations (slug, name, created_by, created_at) VALUES (?, ?, ?, ?)",
+                (slug, name, created_by, now),
+            )
+        except _store.integrity_errors:
+            conn.rollback()
+            return None
+        c.execute(
+            "INSERT INTO organization_members (org_id, username, rank, joined_at) VALUES (?, ?, 'owner', ?)",
+            (org_id, owner, now),
+        )
+        conn.commit()
+    log_action(created_by, f"Created organization {slug} owned by {owner}")
+    return org_id
+
+
+ORG_COLUMNS = """o.id, o.slug, o.name, o.created_at,
+    (SELECT COUNT(*) FROM organization_members m WHERE m.org_id = o.id)"""
+
+
+def get_organization(slug):
+    """(id, slug, name, created_at, member_count), or None."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {ORG_COLUMNS} FROM organizations o WHERE o.slug=?", (slug,))
+        return c.fetchone()
+
+
+def list_organizations(username=None):
+    """
+    Organizations as (id, slug, name, created_at, member_count, rank), where
+    rank is the user's. Without a username every organization is listed and
+    rank is None.
+    """
+    with _store.connect() as conn:
+        c = conn.cursor()
+        if username is None:
+            c.execute(f"SELECT {ORG_COLUMNS}, NULL FROM organizations o ORDER BY o.name")
+        else:
+            c.execute(
+                f"""SELECT {ORG_COLUMNS}, r.rank FROM organizations o
+                    JOIN organization_members r ON r.org_id = o.id AND r.username = ?
+                    ORDER BY o.name""",
+                (username,),
+            )
+        return c.fetchall()
+
+
+def delete_organization(org_id, deleted_by):
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("SELECT slug FROM organizations WHERE id=?", (org_id,))
+        row = c.fetchone()
+        if not row:
+            return False
+        c.execute("DELETE FROM organization_members WHERE org_id=?", (org_id,))
+        c.execute("DELETE FROM organizations WHERE id=?", (org_id,))
+        conn.commit()
+    log_action(deleted_by, f"Deleted organization {row[0]}")
+    return True
+
+
+def get_member_rank(org_id, username):
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT rank FROM organization_members WHERE org_id=? AND username=?", (org_id, username)
+        )
+        row = c.fetchone()
+        return row[0] if row else None
 
 
-def get_audit_logs():
-    """Get the last 50 audit log entries."""
-    with sqlite3.connect(DB_FILE, timeout=10) as conn:
+def list_members(org_id):
+    """[(username, name, email, rank, joined_at)], owners first."""
+    with _store.connect() as conn:
         c = conn.cursor()
         c.execute(
-            "SELECT timestamp, username, action FROM audit_logs ORDER BY id DESC LIMIT 50"
+            """SELECT m.username, u.name, u.email, m.rank, m.joined_at FROM organization_members m
+               JOIN users u ON u.username = m.username
+               WHERE m.org_id=?
+               ORDER BY CASE m.rank WHEN 'owner' THEN 0 WHEN 'manager' THEN 1 ELSE 2 END, m.username""",
+            (org_id,),
         )
         return c.fetchall()
 
 
+def add_member(org_id, username, rank, added_by):
+    """Returns False if the user is already a member."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        try:
+            c.execute(
+                "INSERT INTO organization_members (org_id, username, rank, joined_at) VALUES (?, ?, ?, ?)",
+                (org_id, username, rank, _now().isoformat()),
+            )
+        except _store.integrity_errors:
+            conn.rollback()
+            return False
+        conn.commit()
+    log_action(added_by, f"Added {username} to organization {org_id} as {rank}")
+    return True
+
+
+def _count_owners(c, org_id):
+    c.execute("SELECT COUNT(*) FROM organization_members WHERE org_id=? AND rank='owner'", (org_id,))
+    return c.fetchone()[0]
+
+
+def set_member_rank(org_id, username, rank, changed_by):
+    """Returns False if the user isn't a member; raises OrganizationError for the last owner."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT rank FROM organization_members WHERE org_id=? AND username=?", (org_id, username)
+        )
+        row = c.fetchone()
+        if not row:
+            return False
+        if row[0] == "owner" and rank != "owner" and _count_owners(c, org_id) <= 1:
+            raise OrganizationError("An organization needs at least one owner")
+        c.execute(
+            "UPDATE organization_members SET rank=? WHERE org_id=? AND username=?", (rank, org_id, username)
+        )
+        conn.commit()
+    log_action(changed_by, f"Made {username} {rank} of organization {org_id}")
+    return True
+
+
+def remove_member(org_id, username, removed_by):
+    """Returns False if the user isn't a member; raises OrganizationError for the last owner."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(
+            "SELECT rank FROM organization_members WHERE org_id=? AND username=?", (org_id, username)
+        )
+        row = c.fetchone()
+        if not row:
+            return False
+        if row[0] == "owner" and _count_owners(c, org_id) <= 1:
+            raise OrganizationError("An organization needs at least one owner")
+        c.execute("DELETE FROM organization_members WHERE org_id=? AND username=?", (org_id, username))
+        conn.commit()
+    log_action(removed_by, f"Removed {username} from organization {org_id}")
+    return True
+
+
+# ----------------------------------------------------------------------
+# IP Rules
+# ----------------------------------------------------------------------
+IP_RULE_COLUMNS = "id, cidr, action, note, created_by, created_at"
+
+
+def list_ip_rules():
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {IP_RULE_COLUMNS} FROM ip_rules ORDER BY id")
+        return c.fetchall()
+
+
+def get_ip_rule(rule_id):
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute(f"SELECT {IP_RULE_COLUMNS} FROM ip_rules WHERE id=?", (rule_id,))
+        return c.fetchone()
+
+
+def add_ip_rule(cidr, action, created_by, note=None):
+    """Add an allow or deny rule for a normalised network. Returns the rule id."""
+    with _store.connect() as conn:
+        c = conn.cursor()
+        rule_id = _store.insert_returning_id(
+            c,
+            "INSERT INTO ip_rules (cidr, action, note, created_by, created_at) VALUES (?, ?, ?, ?, ?)",
+            (cidr, action, note, created_by, _now().isoformat()),
+        )
+        conn.commit()
+    log_action(created_by, f"Added IP {action} rule for {cidr}")
+    return rule_id
+
+
+def delete_ip_rule(rule_id, deleted_by):
+    """Returns False if there is no such rule."""
+    rule = get_ip_rule(rule_id)
+    if not rule:
+        return False
+    with _store.connect() as conn:
+        c = conn.cursor()
+        c.execute("DELETE FROM ip_rules WHERE id=?", (rule_id,))
+        conn.commit()
+    log_action(deleted_by, f"Removed IP {rule[2]} rule for {rule[1]}")
+    return True
+
+
 # Initialize database on module import
 init_db()
@@ -0,0 +1,244 @@
+"""
+Artifacts as museum catalogue metadata, for GET /api/export?format=...
+
+- ``dublin-core``: one ``rdf:Description`` per artifact in RDF/XML, with the
+  fifteen Dublin Core elements (http://purl.org/dc/elements/1.1/) that apply:
+  title, description, subject (tags), date (the era), coverage (the
+  culture), format (material and measurements), type ("PhysicalObject"),
+  source (provenance), relation (the collection) and identifier
+- ``lido``: a LIDO 1.0 ``lidoWrap`` (http://www.lido-schema.org), one
+  ``lido:lido`` record per artifact: its title, description, measurements,
+  a production event with the era, culture and material, tags as subjects,
+  and links to the artifact and its image
+
+Identifiers and links are URLs on this backend (``source`` is its base
+URL), so a catalogue can point back at the original. Photo locations are
+left out: they say where the photo was taken, not where the artifact was
+found. Usernames and review fields stay out too.
+"""
+
+import json
+from datetime import datetime
+from typing import Any, Callable, Dict, List, Optional
+from xml.etree import ElementTree as ET
+
+from database import Viewer, get_collection
+
+DC = "http://purl.org/dc/elements/1.1/"
+RDF = "http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+LIDO = "http://www.lido-schema.org"
+XSI = "http://www.w3.org/2001/XMLSchema-instance"
+XML_LANG = "{http://www.w3.org/XML/1998/namespace}lang"
+
+ET.register_namespace("dc", DC)
+ET.register_namespace("rdf", RDF)
+ET.register_namespace("lido", LIDO)
+ET.register_namespace("xsi", XSI)
+
+# The physical measurements in form_data, with their units
+_MEASUREMENTS = (("length", "cm"), ("width", "cm"), ("thickness", "cm"), ("weight", "g"))
+
+
+def _form_data(artifact: Dict[str, Any]) -> Dict[str, Any]:
+    value = artifact.get("form_data")
+    if isinstance(value, str):
+        try:
+            value = json.loads(value)
+        except ValueError:
+            return {}
+    return value if isinstance(value, dict) else {}
+
+
+def _measurements(artifact: Dict[str, Any]) -> List[tuple]:
+    """(type, unit, value) for each measurement recorded in form_data."""
+    form = _form_data(artifact)
+    found = []
+    for kind, unit in _MEASUREMENTS:
+        try:
+            value = float(form.get(kind))
+        except (TypeError, ValueError):
+            continue
+        if value > 0:
+            found.append((kind, unit, f"{value:g}"))
+    return found
+
+
+def _tags(artifact: Dict[str, Any]) -> List[str]:
+    tags = artifact.get("tags") or []
+    return [tag.strip() for tag in (tags.split(",") if isinstance(tags, str) else tags) if tag.strip()]
+
+
+def _artifact_url(source: str, artifact: Dict[str, Any]) -> str:
+    return f"{source.rstrip('/')}/api/artifacts/{artifact['id']}"
+
+
+def _image_url(source: str, artifact: Dict[str, Any]) -> Optional[str]:
+    image_hash = artifact.get("image_hash")
+    return f"{source.rstrip('/')}/api/images/{image_hash}" if image_hash else None
+
+
+def _collection_names(artifacts: List[Dict[str, Any]], viewer: Viewer) -> Dict[int, str]:
+    names = {}
+    for collection_id in {a["collection_id"] for a in artifacts if a.get("collection_id")}:
+        collection = get_collection(collection_id, viewer)
+        if collection:
+            names[collection_id] = collection["name"]
+    return names
+
+
+def _text(parent: ET.Element, tag: str, value: Any, **attrib: str) -> Optional[ET.Element]:
+    """A child element holding ``value``; nothing when the value is empty."""
+    if value is None or str(value).strip() == "":
+        return None
+    element = ET.SubElement(parent, tag, attrib)
+    element.text = str(value).strip()
+    return element
+
+
+def _term(parent: ET.Element, tag: str, value: Any) -> None:
+    """LIDO's <tag><lido:term>value</lido:term></tag>, skipped when empty."""
+    if value is None or str(value).strip() == "":
+        return
+    _text(ET.SubElement(parent, tag), f"{{{LIDO}}}term", value)
+
+
+def _serialize(root: ET.Element) -> bytes:
+    ET.indent(root)
+    return ET.tostring(root, encoding="utf-8", xml_declaration=True)
+
+
+def dublin_core(artifacts: List[Dict[str, Any]], viewer: Viewer, source: str) -> bytes:
+    """``artifacts`` (database dicts) as Dublin Core in RDF/XML."""
+    collections = _collection_names(artifacts, viewer)
+    root = ET.Element(f"{{{RDF}}}RDF")
+    for artifact in artifacts:
+        url = _artifact_url(source, artifact)
+        record = ET.SubElement(root, f"{{{RDF}}}Description", {f"{{{RDF}}}about": url})
+        elements = [
+            ("identifier", url),
+            ("title", artifact.get("name")),
+            ("description", artifact.get("description")),
+            ("description", artifact.get("historical_context")),
+            *(("subject", tag) for tag in _tags(artifact)),
+            ("date", artifact.get("age")),
+            ("coverage", artifact.get("cultural_context")),
+            ("type", "PhysicalObject"),
+            ("format", artifact.get("material")),
+            *(("format", f"{kind}: {value} {unit}") for kind, unit, value in _measurements(artifact)),
+            ("source", artifact.get("provenance")),
+            ("relation", collections.get(artifact.get("collection_id"))),
+            ("relation", _image_url(source, artifact)),
+        ]
+        for element, value in elements:
+            _text(record, f"{{{DC}}}{element}", value)
+    return _serialize(root)
+
+
+def _ns(tag: str) -> str:
+    """A tag or attribute name in the LIDO namespace."""
+    return f"{{{LIDO}}}{tag}"
+
+
+def _lido_record(artifact: Dict[str, Any], source: str, collections: Dict[int, str]) -> ET.Element:
+    record = ET.Element(_ns("lido"))
+    _text(record, _ns("lidoRecID"), f"fll-{artifact['id']}", **{_ns("source"): source, _ns("type"): "local"})
+
+    descriptive = ET.SubElement(record, _ns("descriptiveMetadata"), {XML_LANG: "en"})
+    classification = ET.SubElement(descriptive, _ns("objectClassificationWrap"))
+    work_type = ET.SubElement(classification, _ns("objectWorkTypeWrap"))
+    _term(work_type, _ns("objectWorkType"), "archaeological object")
+    tags = _tags(artifact)
+    if tags:
+        classifications = ET.SubElement(classification, _ns("classificationWrap"))
+        for tag in tags:
+            _term(classifications, _ns("classification"), tag)
+
+    identification = ET.SubElement(descriptive, _ns("objectIdentificationWrap"))
+    titles = ET.SubElement(ET.SubElement(identification, _ns("titleWrap")), _ns("titleSet"))
+    _text(titles, _ns("appellationValue"), artifact.get("name") or "Untitled")
+    if collections.get(artifact.get("collection_id")):
+        repository = ET.SubElement(ET.SubElement(identification, _ns("repositoryWrap")), _ns("repositorySet"))
+        _text(
+            ET.SubElement(ET.SubElement(repository, _ns("repositoryName")), _ns("legalBodyName")),
+            _ns("appellationValue"),
+            collections[artifact["collection_id"]],
+        )
+    descriptions = [artifact.get("description"), artifact.get("historical_context"), _form_data(artifact).get("description")]
+    if any(descriptions):
+        wrap = ET.SubElement(identification, _ns("objectDescriptionWrap"))
+        for description in descriptions:
+            if description:
+                _text(ET.SubElement(wrap, _ns("objectDescriptionSet")), _ns("descriptiveNoteValue"), description)
+    measurements = _measurements(artifact)
+    if measurements:
+        measurement_set = ET.SubElement(ET.SubElement(identification, _ns("objectMeasurementsWrap")), _ns("objectMeasurementsSet"))
+        _text(
+            measurement_set,
+            _ns("displayObjectMeasurements"),
+            ", ".join(f"{kind} {value} {unit}" for kind, unit, value in measurements),
+        )
+        object_measurements = ET.SubElement(measurement_set, _ns("objectMeasurements"))
+        for kind, unit, value in measurements:
+            entry = ET.SubElement(object_measurements, _ns("measurementsSet"))
+            _text(entry, _ns("measurementType"), kind)
+            _text(entry, _ns("measurementUnit"), unit)
+            _text(entry, _ns("measurementValue"), value)
+
+    if artifact.get("age") or artifact.get("cultural_context") or artifact.get("material"):
+        event = ET.SubElement(ET.SubElement(ET.SubElement(descriptive, _ns("eventWrap")), _ns("eventSet")), _ns("event"))
+        _term(event, _ns("eventType"), "Production")
+        _term(event, _ns("culture"), artifact.get("cultural_context"))
+        _term(event, _ns("periodName"), artifact.get("age"))
+        if artifact.get("material"):
+            materials = ET.SubElement(event, _ns("eventMaterialsTech"))
+            _text(materials, _ns("displayMaterialsTech"), artifact["material"])
+
+    if tags:
+        subjects = ET.SubElement(ET.SubElement(descriptive, _ns("objectRelationWrap")), _ns("subjectWrap"))
+        for tag in tags:
+            subject = ET.SubElement(ET.SubElement(subjects, _ns("subjectSet")), _ns("subject"))
+            _term(subject, _ns("subjectConcept"), tag)
+
+    administrative = ET.SubElement(record, _ns("administrativeMetadata"), {XML_LANG: "en"})
+    record_wrap = ET.SubElement(administrative, _ns("recordWrap"))
+    _text(record_wrap, _ns("recordID"), str(artifact["id"]), **{_ns("type"): "local"})
+    _term(record_wrap, _ns("recordType"), "item")
+    record_source = ET.SubElement(ET.SubElement(record_wrap, _ns("recordSource")), _ns("legalBodyName"))
+    _text(record_source, _ns("appellationValue"), source)
+    info = ET.SubElement(record_wrap, _ns("recordInfoSet"))
+    _text(info, _ns("recordInfoLink"), _artifact_url(source, artifact))
+    _text(info, _ns("recordMetadataDate"), artifact.get("updated_at") or artifact.get("uploaded_at"))
+    image_url = _image_url(source, artifact)
+    if image_url:
+        resource = ET.SubElement(ET.SubElement(administrative, _ns("resourceWrap")), _ns("resourceSet"))
+        representation = ET.SubElement(resource, _ns("resourceRepresentation"), {_ns("type"): "image_master"})
+        _text(representation, _ns("linkResource"), image_url)
+    return record
+
+
+def lido(artifacts: List[Dict[str, Any]], viewer: Viewer, source: str) -> bytes:
+    """``artifacts`` (database dicts) as a LIDO 1.0 lidoWrap."""
+    collections = _collection_names(artifacts, viewer)
+    root = ET.Element(
+        f"{{{LIDO}}}lidoWrap",
+        {f"{{{XSI}}}schemaLocation": f"{LIDO} http://www.lido-schema.org/schema/v1.0/lido-v1.0.xsd"},
+    )
+    for artifact in artifacts:
+        root.append(_lido_record(artifact, source, collections))
+    return _serialize(root)
+
+
+# Export format (the ``format`` query parameter) -> writer
+FORMATS: Dict[str, Callable[[List[Dict[str, Any]], Viewer, str], bytes]] = {
+    "dublin-core": dublin_core,
+    "lido": lido,
+}
+
+
+def export(format: str, artifacts: List[Dict[str, Any]], viewer: Viewer, source: str) -> bytes:
+    """The artifacts in ``format`` (a key of FORMATS), as UTF-8 XML."""
+    return FORMATS[format](artifacts, viewer, source)
+
+
+def filename(format: str, when: Optional[datetime] = None) -> str:
+    return f"artifacts-{format}-{(when or datetime.utcnow()):%Y%m%d-%H%M%S}.xml"
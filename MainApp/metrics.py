@@ -0,0 +1,113 @@
+"""
+Prometheus metrics, served at GET /metrics.
+
+Analysis (recorded by analysis_jobs.py as it works):
+
+- ``fll_analysis_duration_seconds{tier,cached}``: a whole analysis, cache
+  lookups included, for per-tier latency
+- ``fll_analysis_stage_duration_seconds{tier,stage}``: preprocessing
+  (queued jobs), inference, embedding, caching and saving (queued jobs)
+- ``fll_analysis_provider_requests_total{provider,tier,outcome}``: provider
+  calls that returned candidates ("ok"), none ("empty") or raised ("error");
+  the error rate is the "error" share of the rate
+
+Read from the database and image store on each scrape:
+
+- ``fll_analysis_queue_jobs{tier,status}``: queued and running jobs
+- ``fll_artifacts{state}``: active and trashed artifacts
+- ``fll_image_store_images`` and ``fll_image_store_bytes``: storage usage,
+  measured at most every STORAGE_METRICS_SECONDS since it lists the store
+
+The standard process_* and python_* metrics come along too.
+"""
+
+import logging
+import threading
+import time
+from typing import Iterator, List, Optional, Tuple
+
+from prometheus_client import CONTENT_TYPE_LATEST, REGISTRY, Counter, Histogram, generate_latest
+from prometheus_client.core import GaugeMetricFamily
+from prometheus_client.registry import Collector
+
+import image_store
+from analysis_providers import TIERS
+from config import STORAGE_METRICS_SECONDS
+from database import count_stored_artifacts, count_unfinished_analysis_jobs
+
+logger = logging.getLogger(__name__)
+
+# From instant (a second or two) to thorough (several minutes)
+_ANALYSIS_BUCKETS = (0.1, 0.5, 1, 2, 5, 10, 20, 30, 60, 120, 300, 600)
+
+ANALYSIS_SECONDS = Histogram(
+    "fll_analysis_duration_seconds",
+    "Time to answer an analysis, by tier and whether it came from the cache",
+    ["tier", "cached"],
+    buckets=_ANALYSIS_BUCKETS,
+)
+ANALYSIS_STAGE_SECONDS = Histogram(
+    "fll_analysis_stage_duration_seconds",
+    "Time spent in each stage of an analysis",
+    ["tier", "stage"],
+    buckets=_ANALYSIS_BUCKETS,
+)
+PROVIDER_REQUESTS = Counter(
+    "fll_analysis_provider_requests_total",
+    "Analysis provider calls by outcome: ok, empty (no candidates) or error",
+    ["provider", "tier", "outcome"],
+)
+
+
+class _StateCollector(Collector):
+    """Queue depth, artifact counts and storage usage, read when scraped."""
+
+    def __init__(self):
+        self._lock = threading.Lock()
+        self._storage: Optional[Tuple[int, int]] = None
+        self._storage_measured = 0.0
+
+    def _storage_usage(self) -> Optional[Tuple[int, int]]:
+        with self._lock:
+            if self._storage is None or time.monotonic() - self._storage_measured >= STORAGE_METRICS_SECONDS:
+                try:
+                    self._storage = image_store.usage()
+                except Exception as e:
+                    logger.warning(f"Could not measure image storage: {e}")
+                # A failed listing isn't retried on every scrape either
+                self._storage_measured = time.monotonic()
+            return self._storage
+
+    def describe(self) -> List[GaugeMetricFamily]:
+        # Without this, registering would call collect() and read the database at import
+        return []
+
+    def collect(self) -> Iterator[GaugeMetricFamily]:
+        try:
+            queue = GaugeMetricFamily("fll_analysis_queue_jobs", "Analysis jobs waiting or running", labels=["tier", "status"])
+            counts = count_unfinished_analysis_jobs()
+            for tier in TIERS:
+                for status in ("queued", "running"):
+                    queue.add_metric([tier, status], counts.get((tier, status), 0))
+            yield queue
+
+            artifacts = GaugeMetricFamily("fll_artifacts", "Stored artifacts, active or in the trash", labels=["state"])
+            for state, count in count_stored_artifacts().items():
+                artifacts.add_metric([state], count)
+            yield artifacts
+        except Exception as e:
+            logger.warning(f"Could not read queue and artifact metrics: {e}")
+
+        storage = self._storage_usage()
+        if storage is not None:
+            images, size = storage
+            yield GaugeMetricFamily("fll_image_store_images", "Images in the image store", value=images)
+            yield GaugeMetricFamily("fll_image_store_bytes", "Total size of the image store", value=size)
+
+
+REGISTRY.register(_StateCollector())
+
+
+def render() -> Tuple[bytes, str]:
+    """The metrics in Prometheus' text format, and its content type."""
+    return generate_latest(REGISTRY), CONTENT_TYPE_LATEST
@@ -0,0 +1,108 @@
+"""
+Versioned schema migrations for the user database.
+
+Each file in migrations/ is named ``NNNN_description.sql`` and applied once,
+in order; applied versions are recorded in ``schema_migrations``. Statements
+are separated by ``;`` at the end of a line, and ``{id_column}`` is replaced
+with the store's auto-increment primary key definition so the same files
+work on SQLite and PostgreSQL.
+
+Never edit a migration that has shipped; add a new one instead.
+
+Run by login.init_db at startup, or by hand:
+    python MainApp/migrate.py
+"""
+
+import logging
+import os
+import re
+from datetime import datetime, timezone
+
+logger = logging.getLogger(__name__)
+
+MIGRATIONS_DIR = os.path.join(os.path.dirname(os.path.abspath(__file__)), "migrations")
+
+_FILENAME = re.compile(r"^(\d{4})_\w+\.sql$")
+_CREATES_TABLE = re.compile(r"CREATE TABLE (?:IF NOT EXISTS )?(\w+)", re.I)
+_ADDS_COLUMN = re.compile(r"ALTER TABLE (\w+) ADD COLUMN (\w+)", re.I)
+
+
+def load_migrations(directory=MIGRATIONS_DIR):
+    """[(version, filename, sql)] sorted by version."""
+    migrations = []
+    for filename in sorted(os.listdir(directory)):
+        match = _FILENAME.match(filename)
+        if not match:
+            continue
+        with open(os.path.join(directory, filename), encoding="utf-8") as f:
+            migrations.append((match.group(1), filename, f.read()))
+    return migrations
+
+
+def _statements(sql, store):
+    sql = "\n".join(line for line in sql.splitlines() if not line.strip().startswith("--"))
+    sql = sql.replace("{id_column}", store.id_column)
+    return [statement.strip() for statement in re.split(r";\s*$", sql, flags=re.M) if statement.strip()]
+
+
+def _already_in_schema(store, c, sql):
+    """True if everything the migration creates is already there (databases made before migrations)."""
+    for table in _CREATES_TABLE.findall(sql):
+        if not store.table_columns(c, table):
+            return False
+    for table, column in _ADDS_COLUMN.findall(sql):
+        if column not in store.table_columns(c, table):
+            return False
+    return True
+
+
+def pending_migrations(store, directory=MIGRATIONS_DIR):
+    """Filenames of migrations not yet applied to the store's database."""
+    with store.connect() as conn:
+        c = conn.cursor()
+        done = set()
+        if store.table_columns(c, "schema_migrations"):
+            c.execute("SELECT version FROM schema_migrations")
+            done = {row[0] for row in c.fetchall()}
+    return [filename for version, filename, _ in load_migrations(directory) if version not in done]
+
+
+def migrate(store, directory=MIGRATIONS_DIR):
+    """Apply pending migrations. Returns the versions applied."""
+    with store.connect() as conn:
+        c = conn.cursor()
+        # Databases created by the old init_db already have some of the schema
+        legacy = bool(store.table_columns(c, "users")) and not store.table_columns(c, "schema_migrations")
+        c.execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (version TEXT PRIMARY KEY, applied_at TEXT NOT NULL)"
+        )
+        c.execute("SELECT version FROM schema_migrations")
+        done = {row[0] for row in c.fetchall()}
+        conn.commit()
+
+        applied = []
+        for version, filename, sql in load_migrations(directory):
+            if version in done:
+                continue
+            if legacy and _already_in_schema(store, c, sql):
+                logger.info(f"Recording {filename} as applied (schema already present)")
+            else:
+                logger.info(f"Applying migration {filename}")
+                for statement in _statements(sql, store):
+                    c.execute(statement)
+                applied.append(version)
+            c.execute(
+                "INSERT INTO schema_migrations (version, applied_at) VALUES (?, ?)",
+                (version, datetime.now(timezone.utc).isoformat()),
+            )
+            conn.commit()
+        return applied
+
+
+if __name__ == "__main__":
+    from config import USER_DATABASE_URL
+    from user_store import create_user_store
+
+    logging.basicConfig(level=logging.INFO)
+    applied = migrate(create_user_store(USER_DATABASE_URL))
+    print(f"Applied {len(applied)} migration(s)" if applied else "Database is up to date")
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS users (
+    username TEXT PRIMARY KEY,
+    name TEXT,
+    hashed_password TEXT,
+    role TEXT,
+    email TEXT
+);
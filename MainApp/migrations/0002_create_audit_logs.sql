@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS audit_logs (
+    {id_column},
+    timestamp TEXT,
+    username TEXT,
+    action TEXT
+);
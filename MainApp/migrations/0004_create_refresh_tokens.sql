@@ -0,0 +1,10 @@
+-- Only a hash of each refresh token is stored. Tokens rotated from the
+-- same login share a family_id so a reused token can revoke the chain.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    token_hash TEXT PRIMARY KEY,
+    username TEXT NOT NULL,
+    family_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT
+);
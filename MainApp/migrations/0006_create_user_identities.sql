@@ -0,0 +1,9 @@
+-- External sign-in identities (OAuth) linked to local accounts
+CREATE TABLE IF NOT EXISTS user_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    username TEXT NOT NULL,
+    email TEXT,
+    linked_at TEXT NOT NULL,
+    PRIMARY KEY (provider, subject)
+);
@@ -0,0 +1,11 @@
+-- Long-lived keys for scripts and service accounts; only a hash is kept
+CREATE TABLE IF NOT EXISTS api_keys (
+    {id_column},
+    username TEXT NOT NULL,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT,
+    revoked_at TEXT
+);
@@ -0,0 +1,10 @@
+-- Server-side sessions behind the browser's session cookie. Only a hash of
+-- the cookie value is stored; csrf_token must accompany unsafe requests.
+CREATE TABLE IF NOT EXISTS browser_sessions (
+    session_hash TEXT PRIMARY KEY,
+    username TEXT NOT NULL,
+    csrf_token TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT
+);
@@ -0,0 +1,10 @@
+-- One row per successful sign-in, so users can review recent access.
+-- method is "password" or the OAuth provider id.
+CREATE TABLE IF NOT EXISTS login_history (
+    {id_column},
+    username TEXT NOT NULL,
+    logged_in_at TEXT NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    method TEXT NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Single-use registration codes. Only a hash of each code is stored; prefix
+-- lets admins tell them apart. email, when set, restricts who may use it.
+CREATE TABLE IF NOT EXISTS invites (
+    {id_column},
+    code_hash TEXT NOT NULL UNIQUE,
+    prefix TEXT NOT NULL,
+    role TEXT NOT NULL,
+    email TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    used_by TEXT,
+    used_at TEXT,
+    revoked_at TEXT
+);
@@ -0,0 +1,7 @@
+-- Frontend preferences (theme, default tier, ...) as one JSON object per
+-- user, so they follow the user between machines.
+CREATE TABLE IF NOT EXISTS user_settings (
+    username TEXT PRIMARY KEY,
+    settings TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Network access rules managed at runtime by admins, on top of the
+-- IP_ALLOWLIST / IP_DENYLIST settings. cidr is a normalised network, e.g.
+-- 10.20.0.0/16 or 203.0.113.7/32; action is 'allow' or 'deny'.
+CREATE TABLE IF NOT EXISTS ip_rules (
+    {id_column},
+    cidr TEXT NOT NULL,
+    action TEXT NOT NULL,
+    note TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
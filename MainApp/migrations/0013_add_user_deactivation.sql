@@ -0,0 +1,3 @@
+-- Deactivated accounts keep their row, history and artifacts but can't sign in.
+ALTER TABLE users ADD COLUMN active INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE users ADD COLUMN deactivated_at TEXT;
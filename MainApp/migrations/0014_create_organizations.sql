@@ -0,0 +1,17 @@
+-- Organizations let several excavation teams share one deployment. Each
+-- member holds an org-scoped rank: owner, manager or member.
+CREATE TABLE IF NOT EXISTS organizations (
+    {id_column},
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS organization_members (
+    org_id INTEGER NOT NULL,
+    username TEXT NOT NULL,
+    rank TEXT NOT NULL,
+    joined_at TEXT NOT NULL,
+    PRIMARY KEY (org_id, username)
+);
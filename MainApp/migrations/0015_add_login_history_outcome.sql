@@ -0,0 +1,3 @@
+-- Failed password attempts on existing accounts are recorded too, for the
+-- admin dashboard; users' own sign-in history only shows successes.
+ALTER TABLE login_history ADD COLUMN succeeded INTEGER NOT NULL DEFAULT 1;
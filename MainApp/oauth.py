@@ -0,0 +1,129 @@
+"""
+OAuth2 authorization-code sign-in with Google and GitHub.
+
+The backend drives the whole exchange: /auth/oauth/{provider}/start sends the
+browser to the provider, and the provider returns to .../callback with a code
+that is swapped here for the user's verified identity.
+"""
+
+import logging
+from urllib.parse import urlencode
+
+import requests
+
+from config import (
+    BACKEND_URL,
+    GITHUB_CLIENT_ID,
+    GITHUB_CLIENT_SECRET,
+    GOOGLE_CLIENT_ID,
+    GOOGLE_CLIENT_SECRET,
+)
+from errors import ExternalServiceError, NotFoundError
+
+logger = logging.getLogger(__name__)
+
+PROVIDERS = {
+    "google": {
+        "name": "Google",
+        "client_id": GOOGLE_CLIENT_ID,
+        "client_secret": GOOGLE_CLIENT_SECRET,
+        "authorize_url": "https://accounts.google.com/o/oauth2/v2/auth",
+        "token_url": "https://oauth2.googleapis.com/token",
+        "userinfo_url": "https://openidconnect.googleapis.com/v1/userinfo",
+        "scope": "openid email profile",
+    },
+    "github": {
+        "name": "GitHub",
+        "client_id": GITHUB_CLIENT_ID,
+        "client_secret": GITHUB_CLIENT_SECRET,
+        "authorize_url": "https://github.com/login/oauth/authorize",
+        "token_url": "https://github.com/login/oauth/access_token",
+        "userinfo_url": "https://api.github.com/user",
+        "emails_url": "https://api.github.com/user/emails",
+        "scope": "read:user user:email",
+    },
+}
+
+TIMEOUT = 10
+
+
+def enabled_providers():
+    """Providers with credentials configured, as [{id, name}]."""
+    return [
+        {"id": key, "name": p["name"]}
+        for key, p in PROVIDERS.items()
+        if p["client_id"] and p["client_secret"]
+    ]
+
+
+def _provider(key):
+    p = PROVIDERS.get(key)
+    if not p or not (p["client_id"] and p["client_secret"]):
+        raise NotFoundError(f"Sign-in with '{key}' is not available")
+    return p
+
+
+def callback_url(key):
+    return f"{BACKEND_URL.rstrip('/')}/auth/oauth/{key}/callback"
+
+
+def authorization_url(key, state):
+    """Where to send the browser to start signing in."""
+    p = _provider(key)
+    params = {
+        "client_id": p["client_id"],
+        "redirect_uri": callback_url(key),
+        "response_type": "code",
+        "scope": p["scope"],
+        "state": state,
+    }
+    return f"{p['authorize_url']}?{urlencode(params)}"
+
+
+def fetch_identity(key, code):
+    """
+    Exchange an authorization code for the user's identity.
+    Returns {"subject", "email", "name"}; email is None unless verified.
+    """
+    p = _provider(key)
+    try:
+        token_response = requests.post(
+            p["token_url"],
+            data={
+                "client_id": p["client_id"],
+                "client_secret": p["client_secret"],
+                "code": code,
+                "redirect_uri": callback_url(key),
+                "grant_type": "authorization_code",
+            },
+            headers={"Accept": "application/json"},
+            timeout=TIMEOUT,
+        )
+        token_response.raise_for_status()
+        access_token = token_response.json().get("access_token")
+        if not access_token:
+            raise ExternalServiceError(f"{p['name']} did not return an access token")
+
+        headers = {"Authorization": f"Bearer {access_token}", "Accept": "application/json"}
+        profile = requests.get(p["userinfo_url"], headers=headers, timeout=TIMEOUT)
+        profile.raise_for_status()
+        info = profile.json()
+
+        if key == "google":
+            return {
+                "subject": str(info["sub"]),
+                "email": info.get("email") if info.get("email_verified") else None,
+                "name": info.get("name"),
+            }
+
+        # GitHub only exposes verified addresses through the emails endpoint
+        emails = requests.get(p["emails_url"], headers=headers, timeout=TIMEOUT)
+        emails.raise_for_status()
+        primary = next(
+            (e["email"] for e in emails.json() if e.get("primary") and e.get("verified")),
+            None,
+        )
+        return {"subject": str(info["id"]), "email": primary, "name": info.get("name") or info.get("login")}
+    except requests.RequestException as e:
+        logger.error(f"OAuth exchange with {key} failed: {e}")
+        raise ExternalServiceError(f"Could not complete sign-in with {p['name']}")
@@ -0,0 +1,84 @@
+"""
+Rules for new passwords, configured with the PASSWORD_* settings.
+
+Length and character classes are checked while the request is parsed
+(schemas.Password), so a weak password comes back as a 422 field error.
+The breach check asks the Pwned Passwords range API, which only ever sees the
+first five hex digits of the password's SHA-1 (k-anonymity); it makes a
+network call, so handlers run it themselves via is_breached. GET
+/auth/password/policy describes the rules for the frontend's live feedback.
+"""
+
+import hashlib
+import logging
+
+import requests
+
+from config import (
+    PASSWORD_CHECK_BREACHED,
+    PASSWORD_MIN_LENGTH,
+    PASSWORD_REQUIRE_DIGIT,
+    PASSWORD_REQUIRE_LOWERCASE,
+    PASSWORD_REQUIRE_SYMBOL,
+    PASSWORD_REQUIRE_UPPERCASE,
+)
+
+logger = logging.getLogger(__name__)
+
+PASSWORD_MAX_LENGTH = 128
+PWNED_RANGE_URL = "https://api.pwnedpasswords.com/range/"
+
+
+def describe_policy():
+    """The active rules, shaped for schemas.PasswordPolicy."""
+    return {
+        "min_length": PASSWORD_MIN_LENGTH,
+        "max_length": PASSWORD_MAX_LENGTH,
+        "require_uppercase": PASSWORD_REQUIRE_UPPERCASE,
+        "require_lowercase": PASSWORD_REQUIRE_LOWERCASE,
+        "require_digit": PASSWORD_REQUIRE_DIGIT,
+        "require_symbol": PASSWORD_REQUIRE_SYMBOL,
+        "check_breached": PASSWORD_CHECK_BREACHED,
+    }
+
+
+def password_problems(password):
+    """What the password still needs, e.g. ["contain a digit"]; empty if it passes."""
+    problems = []
+    if len(password) < PASSWORD_MIN_LENGTH:
+        problems.append(f"be at least {PASSWORD_MIN_LENGTH} characters long")
+    if len(password) > PASSWORD_MAX_LENGTH:
+        problems.append(f"be at most {PASSWORD_MAX_LENGTH} characters long")
+    if PASSWORD_REQUIRE_UPPERCASE and not any(ch.isupper() for ch in password):
+        problems.append("contain an uppercase letter")
+    if PASSWORD_REQUIRE_LOWERCASE and not any(ch.islower() for ch in password):
+        problems.append("contain a lowercase letter")
+    if PASSWORD_REQUIRE_DIGIT and not any(ch.isdigit() for ch in password):
+        problems.append("contain a digit")
+    if PASSWORD_REQUIRE_SYMBOL and all(ch.isalnum() or ch.isspace() for ch in password):
+        problems.append("contain a symbol")
+    return problems
+
+
+def is_breached(password):
+    """
+    True if the password appears in a known breach. Returns False when the
+    check is disabled or the API can't be reached, so an outage never blocks
+    password changes.
+    """
+    if not PASSWORD_CHECK_BREACHED:
+        return False
+    digest = hashlib.sha1(password.encode()).hexdigest().upper()
+    prefix, suffix = digest[:5], digest[5:]
+    try:
+        response = requests.get(PWNED_RANGE_URL + prefix, headers={"Add-Padding": "true"}, timeout=3)
+        response.raise_for_status()
+    except requests.RequestException as e:
+        logger.warning(f"Breached-password check skipped: {e}")
+        return False
+    for line in response.text.splitlines():
+        candidate, _, count = line.partition(":")
+        # Padding entries have a count of 0
+        if candidate == suffix and count.strip() != "0":
+            return True
+    return False
@@ -0,0 +1,57 @@
+"""
+Delivery of password reset links.
+
+The backend is chosen with PASSWORD_RESET_DELIVERY:
+- "console": log the link (development; nothing leaves the machine)
+- "email":   send it through email_utils.send_email
+"""
+
+import logging
+from urllib.parse import urlencode
+
+from config import FRONTEND_URL, PASSWORD_RESET_DELIVERY, PASSWORD_RESET_EXPIRE_MINUTES
+
+logger = logging.getLogger(__name__)
+
+
+def reset_link(token):
+    return f"{FRONTEND_URL.rstrip('/')}/reset-password?{urlencode({'token': token})}"
+
+
+def _deliver_console(username, email, link):
+    logger.warning(f"Password reset link for {username}: {link}")
+    return True
+
+
+def _deliver_email(username, email, link):
+    from email_utils import send_email
+
+    if not email:
+        logger.warning(f"No email address on file for {username}; reset link not sent")
+        return False
+    body = (
+        f"Hello {username},\n\n"
+        f"Use this link to choose a new ArtiQuest password:\n{link}\n\n"
+        f"The link expires in {PASSWORD_RESET_EXPIRE_MINUTES} minutes and can only be used once. "
+        "If you didn't ask for a reset you can ignore this message."
+    )
+    return send_email(email, "ArtiQuest password reset", body)
+
+
+DELIVERY_BACKENDS = {
+    "console": _deliver_console,
+    "email": _deliver_email,
+}
+
+
+def send_reset_link(username, email, token):
+    """Deliver a reset link with the configured backend. Returns True on success."""
+    deliver = DELIVERY_BACKENDS.get(PASSWORD_RESET_DELIVERY)
+    if deliver is None:
+        logger.error(f"Unknown PASSWORD_RESET_DELIVERY '{PASSWORD_RESET_DELIVERY}', falling back to console")
+        deliver = _deliver_console
+    try:
+        return deliver(username, email, reset_link(token))
+    except Exception as e:
+        logger.error(f"Failed to deliver password reset link to {username}: {e}")
+        return False
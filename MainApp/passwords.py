@@ -0,0 +1,76 @@
+"""
+Password hashing for user accounts.
+
+New hashes use Argon2id. Older rows may still hold bcrypt hashes or, from
+before hashing was added, plaintext; both are still accepted at login and
+are transparently re-hashed with Argon2 once the password has been checked.
+"""
+
+import bcrypt
+from argon2 import PasswordHasher
+from argon2.exceptions import InvalidHashError, VerificationError, VerifyMismatchError
+
+_hasher = PasswordHasher()
+
+ARGON2_PREFIX = "$argon2"
+BCRYPT_PREFIXES = ("$2a$", "$2b$", "$2y$")
+
+
+def hash_password(password):
+    """Hash a password with Argon2id."""
+    return _hasher.hash(password)
+
+
+def is_hashed(stored):
+    """True if the stored value is an Argon2 or bcrypt hash rather than plaintext."""
+    return bool(stored) and stored.startswith((ARGON2_PREFIX,) + BCRYPT_PREFIXES)
+
+
+def verify_password(password, stored):
+    """
+    Check a password against the stored value.
+
+    Returns (valid, new_hash). new_hash is set when the password was right
+    but the stored value should be replaced: a legacy bcrypt hash, plaintext,
+    or an Argon2 hash made with outdated parameters.
+    """
+    if not stored:
+        return False, None
+
+    if stored.startswith(ARGON2_PREFIX):
+        try:
+            _hasher.verify(stored, password)
+        except (VerifyMismatchError, VerificationError, InvalidHashError):
+            return False, None
+        return True, hash_password(password) if _hasher.check_needs_rehash(stored) else None
+
+    if stored.startswith(BCRYPT_PREFIXES):
+        try:
+            valid = bcrypt.checkpw(password.encode(), stored.encode())
+        except ValueError:
+            return False, None
+        return valid, hash_password(password) if valid else None
+
+    # Plaintext left over from before passwords were hashed
+    if stored == password:
+        return True, hash_password(password)
+    return False, None
+
+
+def migrate_plaintext_passwords(conn):
+    """
+    Hash any plaintext passwords still stored in the users table.
+
+    bcrypt rows can't be converted without the password, so they are
+    upgraded at the user's next successful login instead.
+    Returns the number of rows updated.
+    """
+    c = conn.cursor()
+    c.execute("SELECT username, hashed_password FROM users")
+    plaintext = [(username, pw) for username, pw in c.fetchall() if pw and not is_hashed(pw)]
+    for username, pw in plaintext:
+        c.execute(
+            "UPDATE users SET hashed_password=? WHERE username=?",
+            (hash_password(pw), username),
+        )
+    return len(plaintext)
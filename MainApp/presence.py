@@ -0,0 +1,85 @@
+"""
+Who is online, over a WebSocket.
+
+Signed-in clients keep a socket open to /api/v1/ws/presence. Whenever
+someone connects or disconnects, every client is sent the updated list:
+
+    {"type": "presence", "online": ["alice", "bob"]}
+
+When an admin signs a user out (revoking their sessions, deactivating or
+deleting the account, or changing its role), that user's sockets are sent
+
+    {"type": "logout", "reason": "..."}
+
+and closed, so open tabs sign out straight away rather than at their next
+API call. State is per process: with several workers, each only knows about
+the sockets connected to it.
+"""
+import asyncio
+import logging
+from collections import defaultdict
+
+logger = logging.getLogger(__name__)
+
+# Close codes in the 4000-4999 range are free for applications
+FORCED_LOGOUT_CLOSE_CODE = 4001
+UNAUTHENTICATED_CLOSE_CODE = 4401
+
+
+class PresenceHub:
+    """The open presence sockets, grouped by username."""
+
+    def __init__(self):
+        self._sockets = defaultdict(set)
+        self._loop = None
+
+    def online(self):
+        return sorted(self._sockets)
+
+    async def connect(self, username, websocket):
+        self._loop = asyncio.get_running_loop()
+        self._sockets[username].add(websocket)
+        await self.broadcast()
+
+    async def disconnect(self, username, websocket):
+        sockets = self._sockets.get(username)
+        if sockets is None:
+            return
+        sockets.discard(websocket)
+        if not sockets:
+            del self._sockets[username]
+        await self.broadcast()
+
+    @staticmethod
+    async def _send(websocket, message):
+        try:
+            await websocket.send_json(message)
+        except Exception:
+            pass  # Already closing; its own handler will disconnect it
+
+    async def broadcast(self):
+        message = {"type": "presence", "online": self.online()}
+        for sockets in list(self._sockets.values()):
+            for websocket in list(sockets):
+                await self._send(websocket, message)
+
+    async def force_logout(self, username, reason):
+        for websocket in list(self._sockets.get(username, ())):
+            await self._send(websocket, {"type": "logout", "reason": reason})
+            try:
+                await websocket.close(code=FORCED_LOGOUT_CLOSE_CODE)
+            except Exception:
+                pass
+
+    def notify_logout(self, username, reason):
+        """
+        force_logout for synchronous code: the account endpoints are plain
+        `def` handlers running in a threadpool, away from the event loop.
+        """
+        if self._loop is None or username not in self._sockets:
+            return
+        asyncio.run_coroutine_threadsafe(self.force_logout(username, reason), self._loop)
+        logger.info(f"Signed out {username}'s open sessions: {reason}")
+
+
+hub = PresenceHub()
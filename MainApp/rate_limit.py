@@ -0,0 +1,74 @@
+"""
+In-memory token-bucket rate limiting.
+
+Used on the authentication endpoints to slow down credential stuffing: each
+client IP and each username gets its own bucket, and a request is refused
+with 429 + Retry-After once either bucket is empty. State is per process,
+which is enough for the single-worker deployment this app runs as.
+"""
+
+import math
+import threading
+import time
+
+from config import AUTH_RATE_LIMIT_IP_BURST, AUTH_RATE_LIMIT_USER_BURST
+from errors import RateLimitedError
+
+
+class TokenBucketLimiter:
+    """Buckets of `capacity` tokens per key, refilled at `refill_per_second`."""
+
+    def __init__(self, capacity, refill_per_second, max_keys=10000):
+        self.capacity = capacity
+        self.refill_per_second = refill_per_second
+        self.max_keys = max_keys
+        self._buckets = {}  # key -> (tokens, last_refill)
+        self._lock = threading.Lock()
+
+    def take(self, key):
+        """Consume one token. Returns 0 if allowed, else seconds until one is available."""
+        now = time.monotonic()
+        with self._lock:
+            tokens, last = self._buckets.get(key, (self.capacity, now))
+            tokens = min(self.capacity, tokens + (now - last) * self.refill_per_second)
+            if tokens < 1:
+                self._buckets[key] = (tokens, now)
+                return (1 - tokens) / self.refill_per_second
+            self._buckets[key] = (tokens - 1, now)
+            self._prune(now)
+            return 0
+
+    def remaining(self, key):
+        """Whole tokens left in a key's bucket, without taking one."""
+        now = time.monotonic()
+        with self._lock:
+            tokens, last = self._buckets.get(key, (self.capacity, now))
+            return int(min(self.capacity, tokens + (now - last) * self.refill_per_second))
+
+    def _prune(self, now):
+        # Full buckets carry no state worth keeping; drop them when the table grows
+        if len(self._buckets) <= self.max_keys:
+            return
+        full_after = self.capacity / self.refill_per_second
+        self._buckets = {
+            key: (tokens, last)
+            for key, (tokens, last) in self._buckets.items()
+            if now - last < full_after
+        }
+
+
+# 10 attempts per IP and 5 per username, refilling one a minute / one every two minutes
+_ip_limiter = TokenBucketLimiter(capacity=AUTH_RATE_LIMIT_IP_BURST, refill_per_second=1 / 60)
+_username_limiter = TokenBucketLimiter(capacity=AUTH_RATE_LIMIT_USER_BURST, refill_per_second=1 / 120)
+
+
+def enforce_auth_rate_limit(client_ip, username=None):
+    """Raise RateLimitedError if this IP or username has made too many attempts."""
+    retry_after = _ip_limiter.take(f"ip:{client_ip}")
+    if username:
+        retry_after = max(retry_after, _username_limiter.take(f"user:{username.lower()}"))
+    if retry_after:
+        raise RateLimitedError(
+            "Too many attempts, please try again later",
+            headers={"Retry-After": str(math.ceil(retry_after))},
+        )
@@ -0,0 +1,123 @@
+"""
+ASGI middleware that transparently decompresses request bodies.
+Clients uploading large base64 images can send them with
+``Content-Encoding: gzip`` (or ``deflate``/``zstd``); handlers downstream
+always see the plain body.
+"""
+import io
+import json
+import zlib
+
+try:
+    import zstandard
+except ImportError:  # zstd support is optional
+    zstandard = None
+
+# Refuse bodies that inflate beyond this, to guard against compression bombs
+MAX_DECOMPRESSED_SIZE = 256 * 1024 * 1024
+
+
+class DecompressionError(Exception):
+    pass
+
+
+def _inflate(body: bytes, wbits: int) -> bytes:
+    decompressor = zlib.decompressobj(wbits)
+    data = decompressor.decompress(body, MAX_DECOMPRESSED_SIZE)
+    if decompressor.unconsumed_tail:
+        raise DecompressionError("Decompressed body too large")
+    return data + decompressor.flush()
+
+
+def _unzstd(body: bytes) -> bytes:
+    # Read at most one byte past the limit, so a bomb is never inflated in full
+    reader = zstandard.ZstdDecompressor().stream_reader(io.BytesIO(body), read_across_frames=True)
+    chunks, size = [], 0
+    while size <= MAX_DECOMPRESSED_SIZE:
+        chunk = reader.read(min(1024 * 1024, MAX_DECOMPRESSED_SIZE + 1 - size))
+        if not chunk:
+            return b"".join(chunks)
+        chunks.append(chunk)
+        size += len(chunk)
+    raise DecompressionError("Decompressed body too large")
+
+
+def supported_encodings():
+    """Return the content encodings this server can decode."""
+    encodings = {
+        "gzip": lambda body: _inflate(body, 16 + zlib.MAX_WBITS),
+        "deflate": lambda body: _inflate(body, zlib.MAX_WBITS),
+    }
+    if zstandard is not None:
+        encodings["zstd"] = _unzstd
+    return encodings
+
+
+class RequestDecompressionMiddleware:
+    """Decode compressed request bodies before they reach the routes."""
+
+    def __init__(self, app):
+        self.app = app
+        self.decoders = supported_encodings()
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] != "http":
+            await self.app(scope, receive, send)
+            return
+
+        headers = dict(scope["headers"])
+        encoding = headers.get(b"content-encoding", b"").decode("latin-1").strip().lower()
+        if not encoding or encoding == "identity":
+            await self.app(scope, receive, send)
+            return
+
+        decoder = self.decoders.get(encoding)
+        if decoder is None:
+            await self._reject(send, 415, f"Unsupported Content-Encoding: {encoding}")
+            return
+
+        # Buffer the whole compressed body
+        chunks = []
+        more_body = True
+        while more_body:
+            message = await receive()
+            if message["type"] == "http.disconnect":
+                return
+            chunks.append(message.get("body", b""))
+            more_body = message.get("more_body", False)
+
+        try:
+            body = decoder(b"".join(chunks))
+        except Exception as e:
+            await self._reject(send, 400, f"Could not decode request body: {e}")
+            return
+
+        scope = dict(scope)
+        scope["headers"] = [
+            (k, v) for k, v in scope["headers"]
+            if k not in (b"content-encoding", b"content-length")
+        ] + [(b"content-length", str(len(body)).encode())]
+
+        sent = False
+
+        async def receive_decoded():
+            nonlocal sent
+            if not sent:
+                sent = True
+                return {"type": "http.request", "body": body, "more_body": False}
+            return await receive()
+
+        await self.app(scope, receive_decoded, send)
+
+    @staticmethod
+    async def _reject(send, status: int, detail: str):
+        payload = json.dumps({"detail": detail}).encode()
+        await send({
+            "type": "http.response.start",
+            "status": status,
+            "headers": [
+                (b"content-type", b"application/json"),
+                (b"content-length", str(len(payload)).encode()),
+            ],
+        })
+        await send({"type": "http.response.body", "body": payload})
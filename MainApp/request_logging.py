@@ -0,0 +1,138 @@
+"""
+Structured request logging.
+
+``configure_logging`` sets up the root logger from LOG_LEVEL / LOG_FORMAT:
+"json" writes one JSON object per line (for log shippers), "text" keeps the
+usual human-readable format for local development.
+
+``RequestLoggingMiddleware`` logs every HTTP request once it finishes, with
+method, path, status, latency and a per-request id. The id is also attached
+to any other log line written while the request is being handled, so a
+failed login can be matched to its request.
+
+Clients may choose the id by sending an ``X-Request-ID`` header (the frontend
+does); otherwise one is generated. Either way it is returned in the response's
+``X-Request-ID`` header and in error bodies, and forwarded to the analysis
+model, so one id follows a request from the browser to Ollama and back.
+"""
+
+import contextvars
+import json
+import logging
+import re
+import time
+import uuid
+from datetime import datetime, timezone
+
+from config import LOG_FORMAT, LOG_LEVEL
+
+request_id_var = contextvars.ContextVar("request_id", default=None)
+
+REQUEST_ID_HEADER = "X-Request-ID"
+# Anything else is replaced, so client-supplied ids can't forge log lines
+_VALID_REQUEST_ID = re.compile(r"^[A-Za-z0-9._-]{1,64}$")
+
+access_logger = logging.getLogger("fll.access")
+
+# LogRecord attributes that aren't worth repeating in the JSON output
+_STANDARD_ATTRS = set(vars(logging.LogRecord("", 0, "", 0, "", None, None))) | {"message", "asctime"}
+
+
+class RequestIdFilter(logging.Filter):
+    """Copy the current request id onto every record."""
+
+    def filter(self, record):
+        record.request_id = request_id_var.get()
+        return True
+
+
+class JsonFormatter(logging.Formatter):
+    """Render a record as a single-line JSON object, including any `extra` fields."""
+
+    def format(self, record):
+        entry = {
+            "timestamp": datetime.fromtimestamp(record.created, timezone.utc).isoformat(),
+            "level": record.levelname,
+            "logger": record.name,
+            "message": record.getMessage(),
+        }
+        for key, value in vars(record).items():
+            if key not in _STANDARD_ATTRS and value is not None:
+                entry[key] = value
+        if record.exc_info:
+            entry["exception"] = self.formatException(record.exc_info)
+        return json.dumps(entry, default=str)
+
+
+def configure_logging():
+    """Point the root logger at stderr using the configured level and format."""
+    handler = logging.StreamHandler()
+    handler.addFilter(RequestIdFilter())
+    if LOG_FORMAT == "json":
+        handler.setFormatter(JsonFormatter())
+    else:
+        handler.setFormatter(
+            logging.Formatter("%(asctime)s %(levelname)s [%(request_id)s] %(name)s: %(message)s")
+        )
+
+    root = logging.getLogger()
+    root.handlers = [handler]
+    root.setLevel(LOG_LEVEL)
+    # The access log below replaces uvicorn's own
+    logging.getLogger("uvicorn.access").disabled = True
+
+
+def _incoming_request_id(scope):
+    """The client's X-Request-ID if usable, else a fresh id."""
+    name = REQUEST_ID_HEADER.lower().encode()
+    for key, value in scope.get("headers", ()):
+        if key == name:
+            candidate = value.decode("latin-1")
+            if _VALID_REQUEST_ID.match(candidate):
+                return candidate
+            break
+    return uuid.uuid4().hex
+
+
+class RequestLoggingMiddleware:
+    """Log method, path, status and latency for each request under its request id."""
+
+    def __init__(self, app):
+        self.app = app
+
+    async def __call__(self, scope, receive, send):
+        if scope["type"] != "http":
+            await self.app(scope, receive, send)
+            return
+
+        request_id = _incoming_request_id(scope)
+        token = request_id_var.set(request_id)
+        scope.setdefault("state", {})["request_id"] = request_id
+        status = 500
+        started = time.perf_counter()
+
+        async def send_wrapper(message):
+            nonlocal status
+            if message["type"] == "http.response.start":
+                status = message["status"]
+                headers = list(message.get("headers", []))
+                headers.append((REQUEST_ID_HEADER.lower().encode(), request_id.encode()))
+                message = {**message, "headers": headers}
+            await send(message)
+
+        try:
+            await self.app(scope, receive, send_wrapper)
+        finally:
+            latency_ms = round((time.perf_counter() - started) * 1000, 1)
+            client = scope.get("client")
+            access_logger.info(
+                f'{scope["method"]} {scope["path"]} {status} {latency_ms}ms',
+                extra={
+                    "method": scope["method"],
+                    "path": scope["path"],
+                    "status": status,
+                    "latency_ms": latency_ms,
+                    "client_ip": client[0] if client else None,
+                },
+            )
+            request_id_var.reset(token)
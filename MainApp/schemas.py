@@ -0,0 +1,626 @@
+"""
+Canonical request/response models for the FLL Project API.
+
+These are the single source of truth for the shapes exchanged with the
+frontend; frontend/src/types/index.ts mirrors them field for field, so
+change both together.
+"""
+
+import ipaddress
+import re
+from typing import Annotated, Dict, List, Literal, Optional, Union
+
+from pydantic import AfterValidator, BaseModel, Field
+
+from password_policy import PASSWORD_MAX_LENGTH, password_problems
+
+UserRole = Literal["admin", "user", "field", "onsite"]
+VerificationStatus = Literal["pending", "verified", "rejected"]
+OrgRank = Literal["owner", "manager", "member"]
+ArtifactSort = Literal["uploaded_at", "name", "confidence", "tier"]
+SortOrder = Literal["asc", "desc"]
+Visibility = Literal["private", "team", "public"]
+ShareScope = Literal["mine", "shared", "all"]
+WebhookEvent = Literal["analysis.done", "analysis.failed"]
+ImportConflict = Literal["skip", "replace", "duplicate"]
+ExportFormat = Literal["bundle", "dublin-core", "lido"]
+
+USERNAME_PATTERN = re.compile(r"[A-Za-z0-9][A-Za-z0-9_.-]{2,31}")
+EMAIL_PATTERN = re.compile(r"[^@\s]+@[^@\s]+\.[^@\s]+")
+SLUG_PATTERN = re.compile(r"[a-z0-9](?:[a-z0-9-]{0,38}[a-z0-9])?")
+
+
+def _check_username(value: str) -> str:
+    if not USERNAME_PATTERN.fullmatch(value):
+        raise ValueError("Must be 3-32 characters: letters, digits, '.', '_' or '-', starting with a letter or digit")
+    return value
+
+
+def _check_email(value: str) -> str:
+    value = value.strip()
+    if len(value) > 254 or not EMAIL_PATTERN.fullmatch(value):
+        raise ValueError("Must be a valid email address")
+    return value
+
+
+def _check_slug(value: str) -> str:
+    value = value.strip().lower()
+    if not SLUG_PATTERN.fullmatch(value):
+        raise ValueError("Must be 1-40 lowercase letters, digits or '-', not starting or ending with '-'")
+    return value
+
+
+def _check_password(value: str) -> str:
+    problems = password_problems(value)
+    if problems:
+        if len(problems) > 1:
+            problems = [", ".join(problems[:-1]) + " and " + problems[-1]]
+        raise ValueError(f"Must {problems[0]}")
+    return value
+
+
+def _check_cidr(value: str) -> str:
+    try:
+        return str(ipaddress.ip_network(value.strip(), strict=False))
+    except ValueError:
+        raise ValueError("Must be an IP address or CIDR range, e.g. 10.20.0.0/16")
+
+
+# Rules for new accounts and new passwords; existing values are accepted at login.
+# Password follows the configured policy (see password_policy).
+Username = Annotated[str, AfterValidator(_check_username)]
+Email = Annotated[str, AfterValidator(_check_email)]
+Password = Annotated[str, AfterValidator(_check_password)]
+Slug = Annotated[str, AfterValidator(_check_slug)]  # Short id used in URLs, e.g. "north-dig"
+Cidr = Annotated[str, AfterValidator(_check_cidr)]  # Normalised, e.g. "10.20.0.0/16"
+
+
+# Artifacts
+class FormData(BaseModel):
+    """Physical measurements and metadata captured during upload"""
+
+    length: Optional[float] = None
+    width: Optional[float] = None
+    thickness: Optional[float] = None
+    weight: Optional[float] = None
+    color: Optional[str] = None
+    location: Optional[str] = None
+    description: Optional[str] = None
+    artifact_name: Optional[str] = None
+    tags: Optional[List[str]] = []
+
+
+class ArtifactBase(BaseModel):
+    name: str
+    description: Optional[str] = None
+    tags: List[str] = []
+    tier: str
+    image_data: str  # base64 encoded image
+    form_data: Optional[FormData] = None  # Physical measurements from upload
+    model_3d_data: Optional[str] = None  # base64 encoded 3D model file
+    model_3d_format: Optional[str] = None  # Format: "obj", "stl", "ply"
+    uploaded_by: Optional[str] = None  # Username of uploader
+
+
+class Artifact(ArtifactBase):
+    id: int
+    has_3d_model: Optional[bool] = False  # Indicates if 3D model is available
+
+
+class ArtifactUpdate(BaseModel):
+    """Partial update; only the fields that are sent are applied."""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+    tags: Optional[Union[List[str], str]] = None  # List, or comma-separated string
+    form_data: Optional[dict] = None  # Merged into the stored form data
+    verification_status: Optional[str] = None
+
+
+def _check_artifact_name(value: str) -> str:
+    value = value.strip()
+    if not value:
+        raise ValueError("Must not be empty")
+    return value
+
+
+class ArtifactPatch(BaseModel):
+    """PATCH body; only the fields that are sent are changed. Era is stored as the artifact's age."""
+
+    name: Optional[Annotated[str, Field(max_length=500), AfterValidator(_check_artifact_name)]] = None
+    description: Optional[str] = None
+    era: Optional[str] = Field(None, max_length=300)
+    tags: Optional[List[Annotated[str, Field(max_length=100)]]] = None
+    notes: Optional[str] = None
+    collection_id: Optional[int] = None  # One of your collections, or null to take it out
+    verification_status: Optional[VerificationStatus] = None
+
+
+class ArtifactSyncRequest(BaseModel):
+    """POST /api/artifacts/sync: the GET parameters, plus the ids the client holds so it learns which to drop."""
+
+    updated_since: Optional[str] = None
+    uploaded_by: Optional[str] = None
+    include_images: bool = True
+    known_ids: List[int] = Field([], max_length=100_000)
+
+
+class Sharing(BaseModel):
+    """Who besides the owner and archive staff may see an artifact or collection."""
+
+    visibility: Visibility = "private"
+    team_id: Optional[int] = None  # Organization that sees it when visibility is team
+    shared_with: List[str] = []  # Usernames
+
+
+class CollectionCreate(BaseModel):
+    name: str = Field(..., min_length=1, max_length=200)
+    description: Optional[str] = None
+    visibility: Visibility = "private"
+    team_id: Optional[int] = None
+
+
+class CollectionInfo(BaseModel):
+    id: int
+    name: str
+    description: Optional[str] = None
+    owner_id: str
+    visibility: Visibility
+    team_id: Optional[int] = None
+    created_at: Optional[str] = None
+    artifact_count: int = 0
+
+
+class VerificationRequest(BaseModel):
+    """Request model for artifact verification with required reason."""
+
+    verification_status: str  # "verified" or "rejected"
+    reason: str  # Required reason for the decision
+    verified_by: str  # Username of verifier
+
+
+# Analysis
+class AnalyzeRequest(BaseModel):
+    image_data: str
+    tier: Optional[str] = "fast"
+    refresh: bool = False  # Analyse again even if this image and tier are cached
+
+
+class BatchAnalyzeRequest(BaseModel):
+    images: List[str]  # List of base64 encoded images
+    tier: Optional[str] = "fast"
+    refresh: bool = False
+
+
+class TierUsage(BaseModel):
+    tier: str
+    analyses: int
+    units: int
+
+
+class AnalysisRateLimit(BaseModel):
+    per_minute: int
+    burst: int
+
+
+class AnalysisUsageInfo(BaseModel):
+    """This month's analyses against the monthly quota (see analysis_quota.py)."""
+
+    username: str
+    month: str  # "2026-10", UTC
+    quota: Optional[int] = None  # Units per month; None when unlimited
+    used: int
+    remaining: Optional[int] = None
+    resets_at: str
+    analyses: int
+    by_tier: List[TierUsage]
+    weights: Dict[str, int]  # Units one analysis costs, by tier
+    rate_limit: Optional[AnalysisRateLimit] = None  # None when exempt
+
+
+class WebhookCreate(BaseModel):
+    url: str = Field(..., min_length=1, max_length=2000)  # http(s) URL to POST events to
+    events: List[WebhookEvent] = ["analysis.done", "analysis.failed"]
+
+
+class WebhookInfo(BaseModel):
+    id: int
+    owner_id: str
+    url: str
+    events: List[WebhookEvent]
+    created_at: Optional[str] = None
+    last_delivery_at: Optional[str] = None
+    last_status: Optional[Literal["delivered", "failed"]] = None
+    last_error: Optional[str] = None
+
+
+class WebhookCreated(WebhookInfo):
+    secret: str  # Signs every delivery; only returned once, at creation
+
+
+class SimilaritySearchRequest(BaseModel):
+    image_data: str  # base64 encoded image
+    limit: Optional[int] = 10
+
+
+class SimilarArtifact(BaseModel):
+    id: int
+    name: str
+    description: Optional[str] = None
+    similarity_score: float  # Cosine similarity of the image embeddings, 1 for identical
+    thumbnail_url: Optional[str] = None
+
+
+class ArtifactLocation(BaseModel):
+    """Where an artifact was photographed, from its EXIF GPS data."""
+
+    id: int
+    name: str
+    latitude: float
+    longitude: float
+    captured_at: Optional[str] = None  # Camera local time, no time zone
+    thumbnail_url: Optional[str] = None
+
+
+# Tag vocabulary
+class TagCount(BaseModel):
+    tag: str
+    count: int  # Artifacts with the tag, of those the caller can see
+
+
+class TagMerge(BaseModel):
+    sources: List[str] = Field(..., min_length=1)
+    target: str = Field(..., min_length=1, max_length=100)
+
+
+class TagRename(BaseModel):
+    tag: str = Field(..., min_length=1)
+    new_name: str = Field(..., min_length=1, max_length=100)
+
+
+class TagChange(BaseModel):
+    tag: str  # The tag the artifacts now carry
+    artifacts_changed: int
+
+
+# Moderation (archive staff)
+class FlagCreate(BaseModel):
+    reason: str = Field(..., min_length=1, max_length=2000)
+
+
+class FlagResolution(BaseModel):
+    resolution: str = Field(..., min_length=1, max_length=2000)  # What was done, e.g. "Identification corrected"
+
+
+class ArtifactFlag(BaseModel):
+    id: int
+    artifact_id: int
+    reason: str
+    flagged_by: str
+    created_at: str
+    resolved_at: Optional[str] = None  # None while the flag is open
+    resolved_by: Optional[str] = None
+    resolution: Optional[str] = None
+
+
+class LockRequest(BaseModel):
+    reason: str = Field(..., min_length=1, max_length=2000)
+
+
+class IdentificationOverride(BaseModel):
+    """A curator's identification; fields left out keep their current value."""
+
+    name: Annotated[str, Field(max_length=500), AfterValidator(_check_artifact_name)]
+    description: Optional[str] = None
+    era: Optional[str] = Field(None, max_length=300)
+    cultural_context: Optional[str] = None
+    material: Optional[str] = None
+    function: Optional[str] = None
+    reason: str = Field(..., min_length=1, max_length=2000)  # For the audit log
+
+
+# Artifact history (see ArtifactRevision in database.py)
+RevisionAction = Literal["created", "updated", "deleted", "restored", "locked", "unlocked", "identified", "reverted"]
+
+
+class FieldChange(BaseModel):
+    field: str  # Database column, e.g. "age" for the era
+    old: Union[str, int, float, None] = None
+    new: Union[str, int, float, None] = None
+
+
+class ArtifactRevision(BaseModel):
+    id: int
+    artifact_id: int
+    action: RevisionAction
+    changed_by: Optional[str] = None
+    changed_at: str
+    changes: List[FieldChange]
+
+
+# Collection bundles (see archive_bundle.py)
+class ImportedArtifact(BaseModel):
+    source_id: Optional[int] = None  # The artifact's id in the bundle
+    id: Optional[int] = None  # Here: the new artifact, or the existing one it was skipped for or replaced
+    status: Literal["imported", "replaced", "skipped", "failed"]
+    error: Optional[str] = None
+
+
+class ImportResult(BaseModel):
+    imported: int
+    replaced: int
+    skipped: int
+    failed: int
+    collections_created: int
+    artifacts: List[ImportedArtifact]
+
+
+# Common responses
+class MessageResponse(BaseModel):
+    message: str
+
+
+class ErrorResponse(BaseModel):
+    """The envelope every error is returned in (see errors.py)."""
+
+    detail: str
+    code: str  # Stable machine-readable code, e.g. "not_found"
+    request_id: Optional[str] = None  # Matches the server's log lines
+    errors: Optional[Dict[str, str]] = None  # Per-field messages on 422
+    existing_id: Optional[int] = None  # The artifact a duplicate_artifact upload matches
+
+
+# Users
+class LoginRequest(BaseModel):
+    username: str = Field(min_length=1, max_length=64)
+    password: str = Field(min_length=1, max_length=PASSWORD_MAX_LENGTH)
+
+
+class UserResponse(BaseModel):
+    username: str
+    name: str
+    email: str
+    role: UserRole
+    locked_until: Optional[str] = None  # Set while the account is locked out
+    active: bool = True  # False once deactivated; the account can't sign in
+    deactivated_at: Optional[str] = None
+
+
+class TokenResponse(BaseModel):
+    access_token: str
+    refresh_token: str
+    token_type: str = "bearer"
+    expires_in: int  # Seconds until the access token expires
+
+
+class LoginResponse(UserResponse, TokenResponse):
+    pass
+
+
+class SessionResponse(UserResponse):
+    """Cookie sign-in: the session id is in an HTTP-only cookie, not the body."""
+
+    csrf_token: str  # Send back as X-CSRF-Token on POST/PUT/PATCH/DELETE
+    expires_in: int  # Seconds until the session expires
+
+
+class RefreshRequest(BaseModel):
+    refresh_token: str
+
+
+class UserCreate(BaseModel):
+    username: Username
+    name: str = Field(min_length=1, max_length=100)
+    password: Password
+    role: UserRole
+    email: Email
+
+
+class BootstrapRequest(BaseModel):
+    """The first admin account, created once on a fresh install."""
+
+    username: Username
+    name: str = Field(min_length=1, max_length=100)
+    password: Password
+    email: Optional[Email] = None
+
+
+class UserCreated(MessageResponse):
+    username: str
+
+
+class OAuthProvider(BaseModel):
+    id: str
+    name: str
+
+
+class BootstrapStatus(BaseModel):
+    required: bool  # True until an admin account exists
+
+
+class RegisterRequest(BaseModel):
+    username: Username
+    name: str = Field(min_length=1, max_length=100)
+    email: Email
+    password: Password
+    invite_code: Optional[str] = None  # Required while registration is invite-only
+
+
+class RegistrationInfo(BaseModel):
+    invite_only: bool
+
+
+class InviteCreate(BaseModel):
+    role: UserRole = "user"  # Given to whoever registers with the invite
+    email: Optional[Email] = None  # Only this address may use it, when set
+
+
+class InviteInfo(BaseModel):
+    id: int
+    prefix: str
+    role: UserRole
+    email: Optional[str] = None
+    created_by: str
+    created_at: str
+    expires_at: str
+    used_by: Optional[str] = None
+    used_at: Optional[str] = None
+    revoked_at: Optional[str] = None
+
+
+class InviteCreated(InviteInfo):
+    code: str  # Only returned once, at creation
+
+
+class OrganizationCreate(BaseModel):
+    slug: Slug
+    name: str = Field(..., min_length=1, max_length=100)
+    owner: Optional[str] = None  # Username of the first owner; defaults to the creator
+
+
+class OrganizationInfo(BaseModel):
+    id: int
+    slug: str
+    name: str
+    created_at: str
+    member_count: int
+    rank: Optional[OrgRank] = None  # The caller's rank; None if they aren't a member
+
+
+class OrgMember(BaseModel):
+    username: str
+    name: str
+    email: str
+    rank: OrgRank
+    joined_at: str
+
+
+class OrgMemberAdd(BaseModel):
+    username: str
+    rank: OrgRank = "member"
+
+
+class OrgMemberUpdate(BaseModel):
+    rank: OrgRank
+
+
+class IpRuleCreate(BaseModel):
+    cidr: Cidr
+    action: Literal["allow", "deny"]
+    note: Optional[str] = Field(None, max_length=200)
+
+
+class IpRuleInfo(BaseModel):
+    id: Optional[int] = None  # None for rules from IP_ALLOWLIST / IP_DENYLIST
+    cidr: str
+    action: Literal["allow", "deny"]
+    note: Optional[str] = None
+    created_by: Optional[str] = None
+    created_at: Optional[str] = None
+    source: Literal["config", "admin"]
+
+
+class UserUpdate(BaseModel):
+    """Admin edit of an account; only the fields sent are changed."""
+
+    name: Optional[str] = Field(None, min_length=1, max_length=100)
+    email: Optional[Email] = None
+    role: Optional[UserRole] = None
+
+
+class ProfileUpdate(BaseModel):
+    """Fields a user may change on their own account."""
+
+    name: Optional[str] = Field(None, min_length=1, max_length=100)
+    email: Optional[Email] = None
+
+
+class AccountDeletion(BaseModel):
+    """Deleting your own account needs your current password."""
+
+    password: str = Field(min_length=1, max_length=PASSWORD_MAX_LENGTH)
+
+
+class PasswordChange(BaseModel):
+    username: str
+    new_password: Password
+
+
+class ForgotPasswordRequest(BaseModel):
+    identifier: str = Field(min_length=1, max_length=254)  # Username or email address
+
+
+class ResetPasswordRequest(BaseModel):
+    token: str
+    new_password: Password
+
+
+class PasswordPolicy(BaseModel):
+    """The rules new passwords must meet, for live feedback in the frontend."""
+
+    min_length: int
+    max_length: int
+    require_uppercase: bool
+    require_lowercase: bool
+    require_digit: bool
+    require_symbol: bool
+    check_breached: bool  # Passwords found in known breaches are refused on submit
+
+
+class ApiKeyCreate(BaseModel):
+    name: str  # What the key is for, e.g. "nightly import"
+
+
+class ApiKeyInfo(BaseModel):
+    id: int
+    username: str
+    name: str
+    prefix: str  # First characters of the key, to tell keys apart
+    created_at: str
+    last_used_at: Optional[str] = None
+    revoked_at: Optional[str] = None
+
+
+class ApiKeyCreated(ApiKeyInfo):
+    key: str  # Only returned once, at creation
+
+
+class FailedLogin(BaseModel):
+    username: str
+    attempted_at: str
+    ip_address: Optional[str] = None
+
+
+class LockedAccount(BaseModel):
+    username: str
+    locked_until: str
+
+
+class AdminStats(BaseModel):
+    """Figures for the admin dashboard, computed when requested."""
+
+    total_users: int
+    active_users: int
+    deactivated_users: int
+    users_by_role: Dict[str, int]  # Active users only
+    active_sessions: int  # Live refresh-token sessions and browser sessions
+    signed_in_users: int  # Distinct users holding at least one of those
+    failed_logins_24h: int  # Wrong passwords for existing accounts
+    recent_failed_logins: List[FailedLogin]
+    locked_accounts: List[LockedAccount]
+    generated_at: str
+
+
+class LoginRecord(BaseModel):
+    """One successful sign-in, for the user's own review."""
+
+    logged_in_at: str
+    ip_address: Optional[str] = None
+    user_agent: Optional[str] = None
+    method: str  # "password" or an OAuth provider id
+
+
+class AuditLogEntry(BaseModel):
+    timestamp: str
+    username: str
+    action: str
@@ -0,0 +1,175 @@
+"""
+Fill the artifact database with synthetic artifacts for development, so
+the gallery, pagination, filters and the map can be exercised without
+real finds. Never run it against a production database.
+
+Each artifact gets a generated photo (a randomly shaped, coloured object
+on a plain background, so every image is different), a plausible name,
+era, culture, material and tags, measurements, a confidence and tier, a
+verification status, an upload time within the past year and, for most,
+a find location. They belong to --owner and are public by default, and
+their provenance says they are synthetic.
+
+    python MainApp/seed_data.py 500
+    python MainApp/seed_data.py 50 --owner alice --visibility private --random-seed 1
+
+Point DATABASE_URL (and IMAGE_STORE_DIR) at a scratch location to keep
+seeded data apart from your own. Similarity embeddings are filled in by
+the backend's background indexer as usual.
+"""
+
+import argparse
+import json
+import math
+import random
+import sys
+from datetime import datetime, timedelta
+from io import BytesIO
+from typing import Any, Dict, Optional, get_args
+
+from PIL import Image, ImageDraw, ImageFilter
+
+from analysis_providers import TIERS
+from config import ADMIN_USERNAME
+from database import save_artifact
+from schemas import Visibility
+
+SEED_PROVENANCE = "Synthetic seed data (seed_data.py)"
+IMAGE_SIZE = (640, 480)
+
+MATERIALS = ("Bronze", "Flint", "Obsidian", "Bone", "Ceramic", "Iron", "Silver", "Amber", "Jade", "Terracotta", "Glass", "Shell")
+OBJECTS = (
+    "Arrowhead", "Axe Head", "Fibula", "Bead", "Figurine", "Amphora Shard", "Coin", "Scraper", "Spindle Whorl",
+    "Oil Lamp", "Pendant", "Needle", "Bowl", "Ring", "Blade", "Loom Weight", "Comb", "Seal",
+)
+ERAS = (
+    ("Paleolithic", "Stone tools from a hunter-gatherer camp"),
+    ("Neolithic", "Made by an early farming community"),
+    ("Bronze Age", "From a settlement layer of the second millennium BCE"),
+    ("Iron Age", "Found with other domestic remains of the first millennium BCE"),
+    ("Roman", "Typical of provincial workshops of the imperial period"),
+    ("Early Medieval", "From a burial of the migration period"),
+    ("Medieval", "Lost in a market town in the high middle ages"),
+)
+CULTURES = ("Celtic", "Roman", "Norse", "Etruscan", "Minoan", "Mycenaean", "Phoenician", "Saxon", "Egyptian", "Anatolian")
+CONDITIONS = ("intact", "fragmentary", "corroded", "restored", "worn")
+COLORS = ("brown", "grey", "green", "red", "black", "white", "ochre")
+STATUSES = ("pending", "pending", "verified", "verified", "verified", "rejected")
+# Finds are scattered around a few excavation sites, (latitude, longitude)
+SITES = ((51.18, -1.83), (41.89, 12.49), (37.97, 23.72), (35.30, 25.16), (59.33, 18.06), (30.05, 31.24))
+
+
+def _image(rng: random.Random) -> bytes:
+    """A JPEG of an irregular object on a backdrop, different every time."""
+    background = tuple(rng.randint(170, 235) for _ in range(3))
+    image = Image.new("RGB", IMAGE_SIZE, background)
+    draw = ImageDraw.Draw(image)
+    cx, cy = IMAGE_SIZE[0] / 2 + rng.uniform(-60, 60), IMAGE_SIZE[1] / 2 + rng.uniform(-40, 40)
+    radius = rng.uniform(80, 170)
+    points = []
+    for step in range(rng.randint(5, 12)):
+        angle = step / 12 * 2 * math.pi + rng.uniform(-0.2, 0.2)
+        reach = radius * rng.uniform(0.55, 1.0)
+        points.append((cx + reach * rng.uniform(0.9, 1.4) * math.cos(angle), cy + reach * math.sin(angle)))
+    base = tuple(rng.randint(40, 160) for _ in range(3))
+    draw.polygon(points, fill=base, outline=tuple(max(c - 30, 0) for c in base))
+    for _ in range(rng.randint(5, 25)):
+        x, y = rng.uniform(cx - radius / 2, cx + radius / 2), rng.uniform(cy - radius / 2, cy + radius / 2)
+        size = rng.uniform(2, 14)
+        shade = tuple(min(max(c + rng.randint(-40, 40), 0), 255) for c in base)
+        draw.ellipse((x - size, y - size, x + size, y + size), fill=shade)
+    image = image.filter(ImageFilter.GaussianBlur(rng.uniform(0.5, 1.5)))
+    buffer = BytesIO()
+    image.save(buffer, format="JPEG", quality=85)
+    return buffer.getvalue()
+
+
+def _thumbnail(image_bytes: bytes) -> bytes:
+    # As POST /api/artifacts makes them
+    image = Image.open(BytesIO(image_bytes))
+    image.thumbnail((200, 200))
+    buffer = BytesIO()
+    image.save(buffer, format="PNG")
+    return buffer.getvalue()
+
+
+def artifact_data(rng: random.Random, owner: str, visibility: str) -> Dict[str, Any]:
+    """Plausible details for one synthetic artifact, shaped for database.save_artifact."""
+    material, kind = rng.choice(MATERIALS), rng.choice(OBJECTS)
+    era, era_note = rng.choice(ERAS)
+    culture, condition = rng.choice(CULTURES), rng.choice(CONDITIONS)
+    name = f"{material} {kind}"
+    uploaded_at = datetime.utcnow() - timedelta(days=rng.uniform(0, 365))
+    status = rng.choice(STATUSES)
+    data: Dict[str, Any] = {
+        "name": name,
+        "description": f"A {condition} {name.lower()} of {culture} style. {era_note}.",
+        "age": era,
+        "cultural_context": culture,
+        "material": material,
+        "function": rng.choice(("Tool", "Ornament", "Vessel", "Weapon", "Ritual", "Trade")),
+        "rarity": rng.choice(("Common", "Uncommon", "Rare")),
+        "confidence": round(rng.uniform(0.35, 0.99), 2),
+        "tier": rng.choice(TIERS),
+        "tags": [material.lower(), *kind.lower().split(), era.lower(), culture.lower(), condition],
+        "form_data": json.dumps(
+            {
+                "length": round(rng.uniform(1, 40), 1),
+                "width": round(rng.uniform(0.5, 20), 1),
+                "thickness": round(rng.uniform(0.1, 5), 1),
+                "weight": round(rng.uniform(1, 900)),
+                "color": rng.choice(COLORS),
+            }
+        ),
+        "provenance": SEED_PROVENANCE,
+        "uploaded_by": owner,
+        "owner_id": owner,
+        "visibility": visibility,
+        "uploaded_at": uploaded_at,
+        "analyzed_at": uploaded_at,
+        "verification_status": status,
+    }
+    if status != "pending":
+        data.update(verified_by=owner, verified_at=uploaded_at + timedelta(days=rng.uniform(0, 14)))
+    if rng.random() < 0.8:
+        latitude, longitude = rng.choice(SITES)
+        data.update(latitude=latitude + rng.gauss(0, 0.05), longitude=longitude + rng.gauss(0, 0.05))
+        data["captured_at"] = uploaded_at - timedelta(hours=rng.uniform(1, 72))
+    return data
+
+
+def seed(count: int, owner: str, visibility: str, random_seed: Optional[int] = None) -> int:
+    """Create ``count`` synthetic artifacts; returns the last one's id."""
+    rng = random.Random(random_seed)
+    artifact_id = 0
+    for done in range(1, count + 1):
+        image = _image(rng)
+        artifact_id = save_artifact(
+            artifact_data(rng, owner, visibility),
+            image_bytes=image,
+            thumbnail_bytes=_thumbnail(image),
+            changed_by=owner,
+        )
+        if done % 50 == 0 or done == count:
+            print(f"  {done}/{count}")
+    return artifact_id
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Create synthetic artifacts for development")
+    parser.add_argument("count", type=int, nargs="?", default=100, help="how many (default: 100)")
+    parser.add_argument("--owner", default=ADMIN_USERNAME, help=f"who uploaded them (default: {ADMIN_USERNAME})")
+    parser.add_argument("--visibility", choices=get_args(Visibility), default="public")
+    parser.add_argument("--random-seed", type=int, help="repeat the same artifacts and images")
+    args = parser.parse_args()
+    if args.count < 1:
+        parser.error("count must be at least 1")
+
+    print(f"Seeding {args.count} artifact(s) for {args.owner}...")
+    last_id = seed(args.count, args.owner, args.visibility, args.random_seed)
+    print(f"✅ Created artifacts up to #{last_id}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,85 @@
+"""
+OpenTelemetry tracing.
+
+With OTEL_EXPORTER_OTLP_ENDPOINT set, ``configure`` sends spans to that
+collector (Jaeger, Tempo, Honeycomb...): one per HTTP request, and inside it
+the analysis stages from analysis_jobs.py (``analysis.preprocessing``,
+``analysis.inference``, ``analysis.embedding``, ``analysis.caching``,
+``analysis.saving``). Each span carries the request id from
+request_logging.py, so a trace can be found from a log line and the other
+way round. A queued job runs in a worker
+thread, so its spans start a new trace under ``analysis.job``; the request
+id still ties it to the request that queued it.
+
+Without an endpoint, or without the OpenTelemetry packages, ``span`` does
+nothing and costs nothing.
+"""
+
+import logging
+from contextlib import contextmanager
+from typing import Any, Iterator, Optional
+
+from config import OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_SERVICE_NAME
+from request_logging import request_id_var
+
+logger = logging.getLogger(__name__)
+
+_tracer = None
+
+
+def configure(app=None) -> None:
+    """Start exporting spans if an OTLP endpoint is set; ``app`` also gets a span per request."""
+    global _tracer
+    if not OTEL_EXPORTER_OTLP_ENDPOINT:
+        return
+    try:
+        from opentelemetry import trace
+        from opentelemetry.exporter.otlp.proto.http.trace_exporter import OTLPSpanExporter
+        from opentelemetry.sdk.resources import Resource
+        from opentelemetry.sdk.trace import TracerProvider
+        from opentelemetry.sdk.trace.export import BatchSpanProcessor
+    except ImportError:
+        logger.warning("OTEL_EXPORTER_OTLP_ENDPOINT is set but opentelemetry-sdk is not installed; tracing is off")
+        return
+
+    provider = TracerProvider(resource=Resource.create({"service.name": OTEL_SERVICE_NAME}))
+    # The exporter reads the endpoint and any OTEL_EXPORTER_OTLP_HEADERS itself
+    provider.add_span_processor(BatchSpanProcessor(OTLPSpanExporter()))
+    trace.set_tracer_provider(provider)
+    _tracer = trace.get_tracer("fll")
+
+    if app is not None:
+        try:
+            from opentelemetry.instrumentation.fastapi import FastAPIInstrumentor
+
+            FastAPIInstrumentor.instrument_app(app, excluded_urls="health/.*,metrics")
+        except ImportError:
+            logger.info("opentelemetry-instrumentation-fastapi is not installed; only analysis spans are traced")
+    logger.info(f"Tracing to {OTEL_EXPORTER_OTLP_ENDPOINT} as {OTEL_SERVICE_NAME}")
+
+
+@contextmanager
+def span(name: str, **attributes: Any) -> Iterator[Optional[Any]]:
+    """
+    A span around the block, a child of the current one; attributes that
+    are None are left out. Yields the span (None when tracing is off), e.g.
+    to add attributes learned inside the block. Exceptions are recorded on
+    the span and re-raised.
+    """
+    if _tracer is None:
+        yield None
+        return
+    attributes["request_id"] = request_id_var.get()
+    with _tracer.start_as_current_span(
+        name, attributes={key: value for key, value in attributes.items() if value is not None}
+    ) as current:
+        yield current
+
+
+def shutdown() -> None:
+    """Send any spans still buffered."""
+    if _tracer is None:
+        return
+    from opentelemetry import trace
+
+    trace.get_tracer_provider().shutdown()
@@ -0,0 +1,63 @@
+"""
+The artifact trash.
+
+Deleting an artifact (DELETE /api/artifacts/{id}, or rejecting it in
+review) only sets its deleted_at, whichever client asked. From then on it
+is left out of lists, searches, collections and similarity results, and
+syncing clients get its id in deleted_ids, but its images, shares and
+embedding stay, so POST /api/artifacts/{id}/restore brings it back whole.
+
+Artifacts that have been in the trash for TRASH_RETENTION_DAYS are purged
+for good by a background thread, once at start and then every
+PURGE_INTERVAL_SECONDS; with a retention of 0 they are kept until someone
+empties them from the trash by hand (DELETE /api/trash/{id}).
+"""
+
+import logging
+import threading
+from datetime import datetime, timedelta
+from typing import Optional
+
+from config import TRASH_RETENTION_DAYS
+from database import purge_deleted_artifacts
+
+logger = logging.getLogger(__name__)
+
+PURGE_INTERVAL_SECONDS = 3600
+
+_stopping = threading.Event()
+_thread: Optional[threading.Thread] = None
+
+
+def purge_expired() -> int:
+    """Purge artifacts trashed longer ago than the retention period; returns how many."""
+    if TRASH_RETENTION_DAYS <= 0:
+        return 0
+    purged = purge_deleted_artifacts(datetime.utcnow() - timedelta(days=TRASH_RETENTION_DAYS))
+    if purged:
+        logger.info(f"Purged {purged} artifact(s) in the trash for over {TRASH_RETENTION_DAYS} days")
+    return purged
+
+
+def _work() -> None:
+    while not _stopping.is_set():
+        try:
+            purge_expired()
+        except Exception:
+            logger.exception("Purging the trash failed")
+        _stopping.wait(PURGE_INTERVAL_SECONDS)
+
+
+def start() -> None:
+    global _thread
+    if _thread or TRASH_RETENTION_DAYS <= 0:
+        return
+    _stopping.clear()
+    _thread = threading.Thread(target=_work, name="trash-purge", daemon=True)
+    _thread.start()
+
+
+def stop() -> None:
+    global _thread
+    _stopping.set()
+    _thread = None
@@ -0,0 +1,266 @@
+"""
+Webhooks: POST a signed JSON event to URLs a user registered when one of
+their queued analyses finishes (analysis.done) or fails (analysis.failed).
+
+Deliveries are queued in the webhook_deliveries table and sent by one
+background thread, so a slow or unreachable receiver never holds up an
+analysis worker. A delivery that doesn't get a 2xx answer is retried after
+WEBHOOK_RETRY_SECONDS, doubling each time, until WEBHOOK_MAX_ATTEMPTS.
+
+Each request carries:
+
+- ``X-Webhook-Event``: the event name
+- ``X-Webhook-Delivery``: the delivery id, the same on every retry
+- ``X-Webhook-Timestamp``: Unix seconds when this attempt was sent
+- ``X-Webhook-Signature``: ``sha256=`` and the hex HMAC-SHA256 of
+  ``"{timestamp}.{body}"`` keyed with the webhook's secret
+
+Receivers should recompute the signature, compare it in constant time and
+refuse old timestamps.
+
+Receivers must be on public addresses (or in WEBHOOK_ALLOWED_NETWORKS), so
+webhooks can't be pointed at the server's own network. The host is resolved
+and checked on every connection, and the request goes to the address that
+was checked, so a DNS change after registration doesn't get around it.
+"""
+
+import hashlib
+import hmac
+import ipaddress
+import logging
+import socket
+import threading
+import time
+from datetime import datetime, timedelta
+from typing import Any, Dict, Optional
+from urllib.parse import urlparse
+
+import requests
+from requests.adapters import HTTPAdapter
+from urllib3.connection import HTTPConnection, HTTPSConnection
+from urllib3.connectionpool import HTTPConnectionPool, HTTPSConnectionPool
+
+from config import (
+    WEBHOOK_ALLOWED_NETWORKS,
+    WEBHOOK_MAX_ATTEMPTS,
+    WEBHOOK_RETENTION_DAYS,
+    WEBHOOK_RETRY_SECONDS,
+    WEBHOOK_TIMEOUT_SECONDS,
+)
+from database import (
+    claim_webhook_delivery,
+    delete_finished_webhook_deliveries,
+    finish_webhook_delivery,
+    queue_webhook_deliveries,
+    requeue_interrupted_deliveries,
+)
+
+logger = logging.getLogger(__name__)
+
+EVENTS = ("analysis.done", "analysis.failed")
+
+# The sender checks for due retries at least this often
+IDLE_POLL_SECONDS = 5
+
+_ALLOWED_NETWORKS = [ipaddress.ip_network(network, strict=False) for network in WEBHOOK_ALLOWED_NETWORKS]
+
+
+class ReceiverNotAllowed(OSError):
+    """The webhook's host can't be resolved or is on an address webhooks may not reach."""
+
+
+def _allowed(address: str) -> bool:
+    ip = ipaddress.ip_address(address.split("%")[0])  # Without an IPv6 zone
+    if isinstance(ip, ipaddress.IPv6Address) and ip.ipv4_mapped:
+        ip = ip.ipv4_mapped
+    if any(ip in network for network in _ALLOWED_NETWORKS):
+        return True
+    # Not global: loopback, private, link-local, shared, reserved and unspecified
+    return ip.is_global and not ip.is_multicast
+
+
+def _receiver_address(host: str, port: int) -> str:
+    """The address to connect to for ``host``; refused if any it resolves to isn't allowed."""
+    try:
+        addresses = [info[4][0] for info in socket.getaddrinfo(host, port, type=socket.SOCK_STREAM)]
+    except socket.gaierror:
+        raise ReceiverNotAllowed(f"Could not resolve {host}")
+    if not addresses or not all(_allowed(address) for address in addresses):
+        raise ReceiverNotAllowed(f"{host} is not an address webhooks may be sent to")
+    return addresses[0]
+
+
+def check_receiver(url: str) -> None:
+    """Raise ReceiverNotAllowed unless ``url``'s host is somewhere webhooks may be sent."""
+    parsed = urlparse(url)
+    _receiver_address(parsed.hostname or "", parsed.port or (443 if parsed.scheme == "https" else 80))
+
+
+class _CheckedHTTPConnection(HTTPConnection):
+    def _new_conn(self):
+        # Connect to the address that was checked, not whatever a second lookup returns
+        self._dns_host = _receiver_address(self._dns_host, self.port)
+        return super()._new_conn()
+
+
+class _CheckedHTTPSConnection(HTTPSConnection):
+    def _new_conn(self):
+        # TLS still verifies the certificate against the URL's host name
+        self._dns_host = _receiver_address(self._dns_host, self.port)
+        return super()._new_conn()
+
+
+class _CheckedHTTPConnectionPool(HTTPConnectionPool):
+    ConnectionCls = _CheckedHTTPConnection
+
+
+class _CheckedHTTPSConnectionPool(HTTPSConnectionPool):
+    ConnectionCls = _CheckedHTTPSConnection
+
+
+class _CheckedAdapter(HTTPAdapter):
+    def init_poolmanager(self, *args, **kwargs):
+        super().init_poolmanager(*args, **kwargs)
+        self.poolmanager.pool_classes_by_scheme = {
+            "http": _CheckedHTTPConnectionPool,
+            "https": _CheckedHTTPSConnectionPool,
+        }
+
+
+_session = requests.Session()
+# A proxy would make the connection, unchecked
+_session.trust_env = False
+_session.mount("http://", _CheckedAdapter())
+_session.mount("https://", _CheckedAdapter())
+
+
+def sign(secret: str, timestamp: str, body: str) -> str:
+    """The X-Webhook-Signature value for a body sent at ``timestamp``."""
+    digest = hmac.new(secret.encode(), f"{timestamp}.{body}".encode(), hashlib.sha256).hexdigest()
+    return f"sha256={digest}"
+
+
+def notify(owner: Optional[str], event: str, data: Dict[str, Any]) -> None:
+    """Queue ``event`` for the owner's webhooks. Never raises: a webhook problem mustn't fail the caller."""
+    if not owner:
+        return
+    try:
+        if queue_webhook_deliveries(owner, event, {"created_at": datetime.utcnow().isoformat() + "Z", "data": data}):
+            _sender.wake()
+    except Exception:
+        logger.exception(f"Could not queue {event} webhooks for {owner}")
+
+
+def ping(owner: str, webhook_id: int) -> None:
+    """Queue a test event for one webhook, whatever events it subscribes to."""
+    queue_webhook_deliveries(
+        owner,
+        "ping",
+        {"created_at": datetime.utcnow().isoformat() + "Z", "data": {}},
+        webhook_id=webhook_id,
+    )
+    _sender.wake()
+
+
+def _deliver(delivery: Dict[str, Any]) -> Optional[str]:
+    """POST one delivery; None when the receiver accepted it, else what went wrong."""
+    timestamp = str(int(time.time()))
+    headers = {
+        "Content-Type": "application/json",
+        "User-Agent": "FLL-Artifacts-Webhook/1.0",
+        "X-Webhook-Event": delivery["event"],
+        "X-Webhook-Delivery": delivery["id"],
+        "X-Webhook-Timestamp": timestamp,
+        "X-Webhook-Signature": sign(delivery["secret"], timestamp, delivery["payload"]),
+    }
+    try:
+        # Checked again on connecting; this gives a plain error for the common case
+        check_receiver(delivery["url"])
+    except ReceiverNotAllowed as e:
+        return str(e)
+    try:
+        response = _session.post(
+            delivery["url"],
+            data=delivery["payload"].encode(),
+            headers=headers,
+            timeout=WEBHOOK_TIMEOUT_SECONDS,
+            allow_redirects=False,
+        )
+    except requests.exceptions.RequestException as e:
+        return str(e) or e.__class__.__name__
+    if 200 <= response.status_code < 300:
+        return None
+    return f"HTTP {response.status_code}"
+
+
+class _Sender:
+    """The thread that sends due deliveries, oldest first."""
+
+    def __init__(self):
+        self._wakeup = threading.Condition()
+        self._stopping = False
+        self._thread: Optional[threading.Thread] = None
+
+    def start(self) -> None:
+        if self._thread:
+            return
+        self._stopping = False
+        self._thread = threading.Thread(target=self._work, name="webhooks", daemon=True)
+        self._thread.start()
+
+    def wake(self) -> None:
+        with self._wakeup:
+            self._wakeup.notify()
+
+    def stop(self) -> None:
+        """Let the thread exit after the delivery it is sending, if any."""
+        with self._wakeup:
+            self._stopping = True
+            self._wakeup.notify_all()
+        self._thread = None
+
+    def _work(self) -> None:
+        while not self._stopping:
+            try:
+                delivery = claim_webhook_delivery()
+            except Exception:
+                logger.exception("Could not take a webhook delivery from the queue")
+                delivery = None
+            if delivery is None:
+                with self._wakeup:
+                    if not self._stopping:
+                        self._wakeup.wait(IDLE_POLL_SECONDS)
+                continue
+            self._send(delivery)
+
+    def _send(self, delivery: Dict[str, Any]) -> None:
+        error = _deliver(delivery)
+        retry_at = None
+        if error is None:
+            logger.info(f"Webhook {delivery['event']} delivery {delivery['id']} sent")
+        elif delivery["attempts"] < WEBHOOK_MAX_ATTEMPTS:
+            delay = WEBHOOK_RETRY_SECONDS * 2 ** (delivery["attempts"] - 1)
+            retry_at = datetime.utcnow() + timedelta(seconds=delay)
+            logger.warning(f"Webhook delivery {delivery['id']} failed ({error}), retrying in {delay}s")
+        else:
+            logger.error(f"Webhook delivery {delivery['id']} failed ({error}), giving up")
+        try:
+            finish_webhook_delivery(delivery["id"], error=error, retry_at=retry_at)
+        except Exception:
+            logger.exception(f"Could not record webhook delivery {delivery['id']}")
+
+
+_sender = _Sender()
+
+
+def start() -> None:
+    """Requeue deliveries cut short by a restart, forget old ones and start sending."""
+    requeued = requeue_interrupted_deliveries()
+    if requeued:
+        logger.info(f"Requeued {requeued} webhook delivery(ies) interrupted by a restart")
+    delete_finished_webhook_deliveries(datetime.utcnow() - timedelta(days=WEBHOOK_RETENTION_DAYS))
+    _sender.start()
+
+
+def stop() -> None:
+    _sender.stop()
@@ -4,14 +4,31 @@ import { canAccess } from './utils/permissions'
 import Login from './pages/Login'
 import Dashboard from './pages/Dashboard'
 import ArtifactGallery from './pages/ArtifactGallery'
+import ArtifactWindow from './pages/ArtifactWindow'
 import UploadArtifact from './pages/UploadArtifact'
 import UserManagement from './pages/UserManagement'
 import AuditLogs from './pages/AuditLogs'
 import ChangePassword from './pages/ChangePassword'
+import Profile from './pages/Profile'
+import ApiKeys from './pages/ApiKeys'
+import Webhooks from './pages/Webhooks'
+import Trash from './pages/Trash'
+import Tags from './pages/Tags'
+import Moderation from './pages/Moderation'
+import ForgotPassword from './pages/ForgotPassword'
+import ResetPassword from './pages/ResetPassword'
+import OAuthCallback from './pages/OAuthCallback'
+import Setup from './pages/Setup'
+import Register from './pages/Register'
+import Invites from './pages/Invites'
+import IpRules from './pages/IpRules'
+import Organizations from './pages/Organizations'
 import Layout from './components/Layout'
 
 function ProtectedRoute({ children }: { children: React.ReactNode }) {
-  const { isAuthenticated } = useAuth()
+  const { isAuthenticated, loading } = useAuth()
+  // Wait for the stored session to be restored before deciding to redirect
+  if (loading) return null
   return isAuthenticated ? <>{children}</> : <Navigate to="/login" replace />
 }
 
@@ -21,6 +38,20 @@ function AppRoutes() {
   return (
     <Routes>
       <Route path="/login" element={<Login />} />
+      <Route path="/forgot-password" element={<ForgotPassword />} />
+      <Route path="/reset-password" element={<ResetPassword />} />
+      <Route path="/oauth/callback" element={<OAuthCallback />} />
+      <Route path="/setup" element={<Setup />} />
+      <Route path="/register" element={<Register />} />
+      {/* An artifact in a window of its own, without the sidebar */}
+      <Route
+        path="/artifacts/:id"
+        element={
+          <ProtectedRoute>
+            <ArtifactWindow />
+          </ProtectedRoute>
+        }
+      />
       <Route
         path="/"
         element={
@@ -35,10 +66,29 @@ function AppRoutes() {
         {canAccess(user?.role, 'user-management') && (
           <Route path="users" element={<UserManagement />} />
         )}
+        {canAccess(user?.role, 'user-management') && (
+          <Route path="invites" element={<Invites />} />
+        )}
+        {canAccess(user?.role, 'user-management') && (
+          <Route path="network-access" element={<IpRules />} />
+        )}
+        {canAccess(user?.role, 'delete') && (
+          <Route path="trash" element={<Trash />} />
+        )}
+        {canAccess(user?.role, 'moderation') && (
+          <Route path="moderation" element={<Moderation />} />
+        )}
+        {canAccess(user?.role, 'tag-management') && (
+          <Route path="tags" element={<Tags />} />
+        )}
         {canAccess(user?.role, 'audit-logs') && (
           <Route path="audit-logs" element={<AuditLogs />} />
         )}
+        <Route path="organizations" element={<Organizations />} />
+        <Route path="profile" element={<Profile />} />
         <Route path="change-password" element={<ChangePassword />} />
+        <Route path="api-keys" element={<ApiKeys />} />
+        <Route path="webhooks" element={<Webhooks />} />
       </Route>
     </Routes>
   )
@@ -0,0 +1,111 @@
+import { useRef, useState } from 'react'
+import { useAuth } from '../contexts/AuthContext'
+import { archiveApi } from '../services/api'
+import { ArtifactFilters, ExportFormat, ImportConflict, ImportResult } from '../types'
+import { canAccess } from '../utils/permissions'
+import { errorMessage } from '../utils/errors'
+import './ArchiveTransfer.css'
+
+interface ArchiveTransferProps {
+  uploadedBy?: string
+  filters: ArtifactFilters
+  /** Called after an import added or changed artifacts */
+  onImported: () => void
+}
+
+const CONFLICT_LABELS: Record<ImportConflict, string> = {
+  skip: 'Skip images already here',
+  replace: 'Replace their details',
+  duplicate: 'Import them again',
+}
+
+const FORMAT_LABELS: Record<ExportFormat, string> = {
+  bundle: 'ZIP bundle',
+  'dublin-core': 'Dublin Core XML',
+  lido: 'LIDO XML',
+}
+
+// Export what the gallery shows as a ZIP bundle or catalogue XML, or import one from another backend
+export default function ArchiveTransfer({ uploadedBy, filters, onImported }: ArchiveTransferProps) {
+  const { user } = useAuth()
+  const fileInput = useRef<HTMLInputElement>(null)
+  const [busy, setBusy] = useState<'export' | 'import' | null>(null)
+  const [format, setFormat] = useState<ExportFormat>('bundle')
+  const [onConflict, setOnConflict] = useState<ImportConflict>('skip')
+  const [result, setResult] = useState<ImportResult | null>(null)
+
+  const handleExport = async () => {
+    setBusy('export')
+    try {
+      const url = URL.createObjectURL(await archiveApi.export(uploadedBy, filters, format))
+      const link = document.createElement('a')
+      link.href = url
+      const date = new Date().toISOString().slice(0, 10)
+      link.download = format === 'bundle' ? `artifacts-${date}.zip` : `artifacts-${format}-${date}.xml`
+      link.click()
+      URL.revokeObjectURL(url)
+    } catch (error) {
+      alert(`Could not export artifacts: ${errorMessage(error)}`)
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  const handleImport = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const bundle = e.target.files?.[0]
+    e.target.value = ''
+    if (!bundle) return
+    setBusy('import')
+    setResult(null)
+    try {
+      const imported = await archiveApi.import(bundle, onConflict)
+      setResult(imported)
+      if (imported.imported || imported.replaced) onImported()
+    } catch (error) {
+      alert(`Could not import ${bundle.name}: ${errorMessage(error)}`)
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  const failures = result?.artifacts.filter((item) => item.status === 'failed') ?? []
+
+  return (
+    <div className="archive-transfer">
+      <select value={format} onChange={(e) => setFormat(e.target.value as ExportFormat)} aria-label="Export format">
+        {(Object.keys(FORMAT_LABELS) as ExportFormat[]).map((option) => (
+          <option key={option} value={option}>
+            {FORMAT_LABELS[option]}
+          </option>
+        ))}
+      </select>
+      <button onClick={handleExport} disabled={busy !== null}>
+        {busy === 'export' ? 'Exporting...' : '⬇️ Export'}
+      </button>
+      {canAccess(user?.role, 'upload') && (
+        <>
+          <select value={onConflict} onChange={(e) => setOnConflict(e.target.value as ImportConflict)}>
+            {(Object.keys(CONFLICT_LABELS) as ImportConflict[]).map((policy) => (
+              <option key={policy} value={policy}>
+                {CONFLICT_LABELS[policy]}
+              </option>
+            ))}
+          </select>
+          <button onClick={() => fileInput.current?.click()} disabled={busy !== null}>
+            {busy === 'import' ? 'Importing...' : '⬆️ Import'}
+          </button>
+          <input ref={fileInput} type="file" accept=".zip,application/zip" hidden onChange={handleImport} />
+        </>
+      )}
+      {result && (
+        <div className="archive-transfer-result" title={failures.map((item) => `#${item.source_id}: ${item.error}`).join('\n')}>
+          Imported {result.imported}, replaced {result.replaced}, skipped {result.skipped}
+          {result.failed > 0 && <span className="archive-transfer-failed">, {result.failed} failed</span>}
+          <button onClick={() => setResult(null)} aria-label="Dismiss">
+            ✕
+          </button>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -1,3 +1,4 @@
+import { useCachedImage } from '../services/imageCache'
 import { Artifact } from '../types'
 import './ArtifactCard.css'
 
@@ -7,12 +8,14 @@ interface ArtifactCardProps {
 }
 
 export default function ArtifactCard({ artifact, onClick }: ArtifactCardProps) {
+  const src = useCachedImage(artifact.id, artifact.thumbnail_url || artifact.image_url)
+
   return (
     <div className="artifact-card" onClick={onClick}>
       <div className="artifact-image">
-        {artifact.thumbnail || artifact.image_data ? (
+        {artifact.thumbnail_url || artifact.image_url ? (
           <img
-            src={artifact.thumbnail || artifact.image_data}
+            src={src}
             alt={artifact.name}
             onError={(e) => {
               e.currentTarget.src = 'data:image/svg+xml,%3Csvg xmlns="http://www.w3.org/2000/svg" width="200" height="200"%3E%3Crect fill="%23ddd" width="200" height="200"/%3E%3Ctext fill="%23999" font-family="sans-serif" font-size="14" x="50%25" y="50%25" text-anchor="middle" dy=".3em"%3ENo Image%3C/text%3E%3C/svg%3E'
@@ -1,19 +1,32 @@
 import { useState, useEffect } from 'react'
 import { useAuth } from '../contexts/AuthContext'
-import { artifactApi } from '../services/api'
+import { artifactApi, moderationApi } from '../services/api'
+import { useCachedImage } from '../services/imageCache'
 import { Artifact, FormData } from '../types'
 import { canAccess } from '../utils/permissions'
+import { errorMessage, toAppError } from '../utils/errors'
+import SharingPanel from './SharingPanel'
+import SimilarArtifacts from './SimilarArtifacts'
+import VersionHistory from './VersionHistory'
 import './ArtifactModal.css'
 
 interface ArtifactModalProps {
   artifact: Artifact
   onClose: () => void
   onUpdate: () => void
+  onDelete: (artifact: Artifact) => void
+  /** Filling a window of its own (pages/ArtifactWindow.tsx) rather than over a page */
+  standalone?: boolean
 }
 
-export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactModalProps) {
+// Named per artifact, so opening one that's already open brings its window forward
+const openInWindow = (id: number) =>
+  window.open(`/artifacts/${id}`, `artifact-${id}`, 'popup,width=1100,height=850')?.focus()
+
+export default function ArtifactModal({ artifact, onClose, onUpdate, onDelete, standalone = false }: ArtifactModalProps) {
   const { user } = useAuth()
   const [fullArtifact, setFullArtifact] = useState<Artifact>(artifact)
+  const imageSrc = useCachedImage(fullArtifact.id, fullArtifact.image_url)
   const [editMode, setEditMode] = useState(false)
   const [loading, setLoading] = useState(false)
   const [verificationReason, setVerificationReason] = useState('')
@@ -22,17 +35,25 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
   const [editName, setEditName] = useState('')
   const [editDescription, setEditDescription] = useState('')
   const [editTags, setEditTags] = useState<string[]>([])
+  const [editEra, setEditEra] = useState('')
+  const [editNotes, setEditNotes] = useState('')
 
   useEffect(() => {
     loadFullArtifact()
   }, [artifact.id])
 
+  const resetEditFields = (source: Artifact) => {
+    setEditName(source.name || '')
+    setEditDescription(source.description || '')
+    setEditTags(source.tags || [])
+    setEditEra(source.age || '')
+    setEditNotes(source.notes || '')
+  }
+
   // Initialize edit fields when artifact loads
   useEffect(() => {
     if (fullArtifact) {
-      setEditName(fullArtifact.name || '')
-      setEditDescription(fullArtifact.description || '')
-      setEditTags(fullArtifact.tags || [])
+      resetEditFields(fullArtifact)
     }
   }, [fullArtifact])
 
@@ -48,17 +69,30 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
   const handleSave = async () => {
     setLoading(true)
     try {
-      await artifactApi.update(artifact.id, {
-        name: editName.trim(),
-        description: editDescription.trim() || null,
-        tags: editTags.join(','),
-      })
-      await loadFullArtifact()
+      // The version makes the save fail instead of overwriting someone else's edit
+      const updated = await artifactApi.patch(
+        artifact.id,
+        {
+          name: editName.trim(),
+          description: editDescription.trim() || null,
+          era: editEra.trim() || null,
+          tags: editTags,
+          notes: editNotes.trim() || null,
+        },
+        fullArtifact.version
+      )
+      setFullArtifact(updated)
       setEditMode(false)
       onUpdate()
       alert('Artifact updated successfully!')
-    } catch (error: any) {
-      alert(`Failed to update artifact: ${error.message}`)
+    } catch (error) {
+      if (toAppError(error).code === 'precondition_failed') {
+        alert('Someone else changed this artifact while you were editing. It has been reloaded; please make your changes again.')
+        await loadFullArtifact()
+        setEditMode(false)
+      } else {
+        alert(`Failed to update artifact: ${errorMessage(error)}`)
+      }
     } finally {
       setLoading(false)
     }
@@ -81,13 +115,51 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
       onUpdate()
       setVerificationReason('')
       alert(`Artifact ${status === 'verified' ? 'approved' : 'rejected'} successfully!`)
-    } catch (error: any) {
-      alert(`Failed to ${status} artifact: ${error.message}`)
+    } catch (error) {
+      alert(`Failed to ${status} artifact: ${errorMessage(error)}`)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleFlag = async () => {
+    const reason = window.prompt(`Why does "${fullArtifact.name}" need review?`)?.trim()
+    if (!reason) return
+    setLoading(true)
+    try {
+      await moderationApi.flag(artifact.id, reason)
+      alert('Flagged; it is now in the moderation queue')
+    } catch (error) {
+      alert(`Failed to flag artifact: ${errorMessage(error)}`)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleLock = async () => {
+    let reason: string | undefined
+    if (!fullArtifact.locked_at) {
+      reason = window.prompt(`Lock "${fullArtifact.name}" against edits because:`)?.trim()
+      if (!reason) return
+    }
+    setLoading(true)
+    try {
+      setFullArtifact(reason ? await moderationApi.lock(artifact.id, reason) : await moderationApi.unlock(artifact.id))
+      onUpdate()
+    } catch (error) {
+      alert(`Failed to ${reason ? 'lock' : 'unlock'} artifact: ${errorMessage(error)}`)
     } finally {
       setLoading(false)
     }
   }
 
+  const handleDelete = () => {
+    if (!window.confirm(`Move "${fullArtifact.name}" to the trash? You can restore it from there.`)) {
+      return
+    }
+    onDelete(fullArtifact)
+  }
+
   const formData: FormData | null = fullArtifact.form_data
     ? (typeof fullArtifact.form_data === 'string'
         ? JSON.parse(fullArtifact.form_data)
@@ -97,14 +169,20 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
   // Admin has all permissions, including edit and verify
   const canEdit = canAccess(user?.role, 'edit')
   const canVerify = canAccess(user?.role, 'verify')
+  const canDelete = canAccess(user?.role, 'delete')
+  const canModerate = canAccess(user?.role, 'moderation')
+  // A curator's lock stops every change until it is lifted
+  const locked = !!fullArtifact.locked_at
+  // The server lets owners and archive staff change who can see an artifact
+  const canShare = !!user && (fullArtifact.owner_id === user.username || canAccess(user.role, 'all-artifacts'))
 
   return (
-    <div className="modal-overlay" onClick={onClose}>
+    <div className={`modal-overlay${standalone ? ' standalone' : ''}`} onClick={standalone ? undefined : onClose}>
       <div className="modal-content" onClick={(e) => e.stopPropagation()}>
         <div className="modal-header">
           <h2>{fullArtifact.name}</h2>
           <div className="modal-actions">
-            {canEdit && (
+            {canEdit && !locked && (
               <button
                 className="edit-button"
                 onClick={() => {
@@ -125,15 +203,23 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
                 onClick={() => {
                   setEditMode(false)
                   // Reset to original values
-                  setEditName(fullArtifact.name || '')
-                  setEditDescription(fullArtifact.description || '')
-                  setEditTags(fullArtifact.tags || [])
+                  resetEditFields(fullArtifact)
                 }}
                 disabled={loading}
               >
                 ✕ Cancel
               </button>
             )}
+            {canDelete && !editMode && !locked && (
+              <button className="delete-button" onClick={handleDelete} disabled={loading}>
+                🗑️ Delete
+              </button>
+            )}
+            {!standalone && (
+              <button className="window-button" onClick={() => openInWindow(fullArtifact.id)} title="Open in a new window">
+                ⧉ New Window
+              </button>
+            )}
             <button className="close-button" onClick={onClose}>
               ✕
             </button>
@@ -142,9 +228,9 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
 
         <div className="modal-body">
           <div className="modal-left">
-            {fullArtifact.image_data && (
+            {fullArtifact.image_url && (
               <img
-                src={fullArtifact.image_data}
+                src={imageSrc}
                 alt={fullArtifact.name}
                 className="artifact-image-full"
               />
@@ -172,6 +258,35 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
               <p><strong>ID:</strong> {fullArtifact.id}</p>
               <p><strong>Tier:</strong> {fullArtifact.tier || 'N/A'}</p>
               <p><strong>Uploaded:</strong> {fullArtifact.uploaded_at || 'N/A'}</p>
+              {locked && (
+                <p className="locked-notice">
+                  🔒 Locked by {fullArtifact.locked_by}: {fullArtifact.lock_reason}
+                </p>
+              )}
+              {fullArtifact.identification_overridden_by && (
+                <p><strong>Identified by curator:</strong> {fullArtifact.identification_overridden_by}</p>
+              )}
+              {fullArtifact.original_format && !['JPEG', 'PNG'].includes(fullArtifact.original_format) && (
+                <p>
+                  <strong>Uploaded as:</strong> {fullArtifact.original_format}
+                  {fullArtifact.original_size ? `, ${(fullArtifact.original_size / 1024 / 1024).toFixed(1)} MB` : ''}
+                </p>
+              )}
+              {fullArtifact.captured_at && (
+                <p><strong>Photographed:</strong> {fullArtifact.captured_at.replace('T', ' ')}</p>
+              )}
+              {fullArtifact.latitude != null && fullArtifact.longitude != null && (
+                <p>
+                  <strong>Location:</strong>{' '}
+                  <a
+                    href={`https://www.openstreetmap.org/?mlat=${fullArtifact.latitude}&mlon=${fullArtifact.longitude}#map=16/${fullArtifact.latitude}/${fullArtifact.longitude}`}
+                    target="_blank"
+                    rel="noopener noreferrer"
+                  >
+                    📍 {fullArtifact.latitude.toFixed(5)}, {fullArtifact.longitude.toFixed(5)}
+                  </a>
+                </p>
+              )}
             </div>
 
             <div className="info-section">
@@ -204,6 +319,21 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
               )}
             </div>
 
+            <div className="info-section">
+              <h3>Era</h3>
+              {editMode ? (
+                <input
+                  type="text"
+                  value={editEra}
+                  onChange={(e) => setEditEra(e.target.value)}
+                  className="edit-input"
+                  placeholder="e.g., Late Bronze Age"
+                />
+              ) : (
+                <p>{fullArtifact.age || 'Unknown'}</p>
+              )}
+            </div>
+
             <div className="info-section">
               <h3>Tags</h3>
               {editMode ? (
@@ -237,6 +367,21 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
               )}
             </div>
 
+            <div className="info-section">
+              <h3>Notes</h3>
+              {editMode ? (
+                <textarea
+                  value={editNotes}
+                  onChange={(e) => setEditNotes(e.target.value)}
+                  className="edit-textarea"
+                  placeholder="Notes for the team"
+                  rows={3}
+                />
+              ) : (
+                <p>{fullArtifact.notes || 'No notes'}</p>
+              )}
+            </div>
+
             <div className="info-section">
               <h3>Verification Status</h3>
               <span className={`status-badge status-${fullArtifact.verification_status || 'pending'}`}>
@@ -247,6 +392,22 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
               )}
             </div>
 
+            {canShare && <SharingPanel kind="artifact" id={fullArtifact.id} />}
+
+            <SimilarArtifacts id={fullArtifact.id} />
+
+            {user && (
+              <VersionHistory
+                id={fullArtifact.id}
+                version={fullArtifact.version}
+                canRevert={canEdit && canShare && !locked}
+                onReverted={(updated) => {
+                  setFullArtifact(updated)
+                  onUpdate()
+                }}
+              />
+            )}
+
             {formData && (
               <div className="info-section">
                 <h3>📐 Physical Measurements & Details</h3>
@@ -319,6 +480,16 @@ export default function ArtifactModal({ artifact, onClose, onUpdate }: ArtifactM
                 ❌ Reject
               </button>
             </div>
+            {canModerate && (
+              <div className="moderation-buttons">
+                <button onClick={handleFlag} disabled={loading}>
+                  🚩 Flag for review
+                </button>
+                <button onClick={handleLock} disabled={loading}>
+                  {locked ? '🔓 Unlock' : '🔒 Lock against edits'}
+                </button>
+              </div>
+            )}
           </div>
         )}
       </div>
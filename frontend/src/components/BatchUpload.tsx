@@ -0,0 +1,327 @@
+import { useEffect, useRef, useState } from 'react'
+import { useAuth } from '../contexts/AuthContext'
+import { artifactApi } from '../services/api'
+import { droppedFiles } from '../services/droppedFiles'
+import { isCancellation, useTaskRegistry } from '../services/tasks'
+import { AnalysisResult } from '../types'
+import { mapWithConcurrency } from '../utils/concurrency'
+import { downscaleImage, encodeImage, pickImages, PickOptions } from '../utils/files'
+import { analysisSizeLabel, ANALYSIS_SIZES, getSettings, updateSettings, MAX_BATCH_CONCURRENCY } from '../utils/settings'
+import { errorMessage } from '../utils/errors'
+import { notifyIfHidden, requestNotificationPermission } from '../utils/notifications'
+import './BatchUpload.css'
+
+type BatchStatus = 'queued' | 'analyzing' | 'saving' | 'done' | 'error'
+
+interface BatchItem {
+  id: number
+  file: File
+  status: BatchStatus
+  result?: AnalysisResult
+  error?: string
+  /** Share of the image encoded so far, while saving */
+  progress?: number
+}
+
+// The single analysis request covering every pending image
+const BATCH_TASK = 'batch-analysis'
+
+const statusLabels: Record<BatchStatus, string> = {
+  queued: '⏳ Queued',
+  analyzing: '🔍 Analyzing',
+  saving: '💾 Saving',
+  done: '✅ Saved',
+  error: '❌ Failed',
+}
+
+export default function BatchUpload() {
+  const { user } = useAuth()
+  const [items, setItems] = useState<BatchItem[]>([])
+  const [tier, setTier] = useState(getSettings().defaultTier)
+  const [concurrency, setConcurrency] = useState(getSettings().batchConcurrency)
+  const [maxDimension, setMaxDimension] = useState(getSettings().analysisMaxDimension)
+  const [running, setRunning] = useState(false)
+  const tasks = useTaskRegistry()
+  const nextId = useRef(0)
+  const stopped = useRef(false)
+  const removed = useRef(new Set<number>())
+  // Hash -> name of each image saved in this batch, so one picked twice is saved once
+  const savedImages = useRef(new Map<string, string>())
+
+  // Don't start queued items once the page is gone (in-flight ones are aborted by the registry)
+  useEffect(() => {
+    return () => {
+      stopped.current = true
+    }
+  }, [])
+
+  const taskKey = (id: number) => `batch-item-${id}`
+
+  const updateItem = (id: number, changes: Partial<BatchItem>) => {
+    setItems((current) => current.map((item) => (item.id === id ? { ...item, ...changes } : item)))
+  }
+
+  const handleFilesSelect = async (options: PickOptions) => {
+    let files: File[]
+    try {
+      files = await pickImages(options)
+    } catch (error) {
+      alert(`Could not open the file picker: ${errorMessage(error)}`)
+      return
+    }
+    if (files.length === 0) return
+    removed.current.clear()
+    savedImages.current.clear()
+    setItems(files.map((file) => ({ id: nextId.current++, file, status: 'queued' })))
+  }
+
+  // Images dropped onto the window join the queue; Start analyzes them with the rest
+  useEffect(
+    () =>
+      droppedFiles.listen((files) => {
+        setItems((current) => {
+          const queued = new Set(current.map((item) => `${item.file.name}:${item.file.size}:${item.file.lastModified}`))
+          const added = files.filter((file) => !queued.has(`${file.name}:${file.size}:${file.lastModified}`))
+          return [...current, ...added.map((file) => ({ id: nextId.current++, file, status: 'queued' as const }))]
+        })
+      }),
+    []
+  )
+
+  const handleRemove = (id: number) => {
+    removed.current.add(id)
+    tasks.cancel(taskKey(id))
+    setItems((current) => current.filter((item) => item.id !== id))
+  }
+
+  const handleStop = () => {
+    stopped.current = true
+    tasks.cancelAll()
+  }
+
+  const handleTierChange = (value: string) => {
+    setTier(value)
+    updateSettings({ defaultTier: value })
+  }
+
+  const handleMaxDimensionChange = (value: number) => {
+    setMaxDimension(value)
+    updateSettings({ analysisMaxDimension: value })
+  }
+
+  const handleConcurrencyChange = (value: number) => {
+    const limit = Math.min(MAX_BATCH_CONCURRENCY, Math.max(1, value || 1))
+    setConcurrency(limit)
+    updateSettings({ batchConcurrency: limit })
+  }
+
+  const saveItem = async (item: BatchItem, result: AnalysisResult) => {
+    if (stopped.current || removed.current.has(item.id)) return
+
+    const key = taskKey(item.id)
+    const signal = tasks.start(key)
+    try {
+      updateItem(item.id, { status: 'saving', result, progress: 0 })
+      const { dataUrl: imageData, sha256 } = await encodeImage(item.file, {
+        signal,
+        onProgress: ({ loaded, total }) => updateItem(item.id, { progress: total ? loaded / total : 1 }),
+      })
+      const savedAs = sha256 && savedImages.current.get(sha256)
+      if (savedAs) {
+        updateItem(item.id, { status: 'error', error: `Same image as ${savedAs}, already saved` })
+        return
+      }
+      await artifactApi.create({
+        name: result.name,
+        description: result.description,
+        tags: result.name.split(' ').filter(Boolean),
+        tier: tier,
+        image_data: imageData,
+        uploaded_by: user?.username,
+      }, signal)
+      if (sha256) savedImages.current.set(sha256, item.file.name)
+      updateItem(item.id, { status: 'done' })
+    } catch (error: any) {
+      if (isCancellation(error)) {
+        // Stopped or removed: put it back in the queue (no-op if removed)
+        updateItem(item.id, { status: 'queued', result: undefined, progress: undefined })
+        return
+      }
+      updateItem(item.id, {
+        status: 'error',
+        error: errorMessage(error),
+      })
+    } finally {
+      tasks.finish(key, signal)
+    }
+  }
+
+  const handleStart = async () => {
+    stopped.current = false
+    setRunning(true)
+    if (tier === 'thorough') requestNotificationPermission()
+    // Only (re)process items that haven't been saved yet
+    const pending = items.filter((item) => item.status !== 'done')
+    pending.forEach((item) => updateItem(item.id, { status: 'analyzing', result: undefined, error: undefined }))
+
+    // Analyze the whole batch in one request; batch items come back in upload order
+    const results = new Map<number, AnalysisResult>()
+    const signal = tasks.start(BATCH_TASK)
+    try {
+      // Smaller copies are analysed; the originals are what's saved
+      const files = await Promise.all(pending.map((item) => downscaleImage(item.file, maxDimension, signal)))
+      await artifactApi.batchAnalyze(files, tier, signal, (batch) => {
+        batch.items.forEach((batchItem) => {
+          const item = pending[batchItem.index]
+          if (!item || removed.current.has(item.id) || results.has(item.id)) return
+          if (batchItem.status === 'done' && batchItem.result) {
+            results.set(item.id, batchItem.result)
+            updateItem(item.id, { result: batchItem.result })
+          } else if (batchItem.status === 'failed') {
+            updateItem(item.id, { status: 'error', error: batchItem.error || 'Analysis failed' })
+          }
+        })
+      })
+    } catch (error: any) {
+      const cancelled = isCancellation(error)
+      pending
+        .filter((item) => !results.has(item.id))
+        .forEach((item) =>
+          updateItem(item.id, cancelled ? { status: 'queued' } : { status: 'error', error: errorMessage(error) })
+        )
+    } finally {
+      tasks.finish(BATCH_TASK, signal)
+    }
+
+    const analyzed = pending.filter((item) => results.has(item.id))
+    await mapWithConcurrency(analyzed, concurrency, (item) => saveItem(item, results.get(item.id)!))
+    setRunning(false)
+    if (tier === 'thorough' && !stopped.current) {
+      const failed = pending.length - analyzed.length
+      notifyIfHidden('Batch analysis finished', `${analyzed.length} identified${failed ? `, ${failed} failed` : ''}`)
+    }
+  }
+
+  const doneCount = items.filter((i) => i.status === 'done').length
+  const failedCount = items.filter((i) => i.status === 'error').length
+
+  return (
+    <div className="batch-upload">
+      <div className="batch-controls">
+        <div>
+          <label>Images</label>
+          <div className="file-pick-buttons">
+            <button
+              type="button"
+              className="file-input file-pick"
+              onClick={() => handleFilesSelect({ multiple: true })}
+              disabled={running}
+            >
+              📄 Choose Images…
+            </button>
+            <button
+              type="button"
+              className="file-input file-pick"
+              onClick={() => handleFilesSelect({ directory: true })}
+              disabled={running}
+            >
+              📁 Choose Folder…
+            </button>
+          </div>
+        </div>
+        <div>
+          <label>Analysis Quality</label>
+          <select value={tier} onChange={(e) => handleTierChange(e.target.value)} disabled={running}>
+            <option value="fast">Fast (~20-40s)</option>
+            <option value="balanced">Balanced (~30-60s)</option>
+            <option value="thorough">Thorough (~1-2 min)</option>
+          </select>
+        </div>
+        <div>
+          <label>Image Size for Analysis</label>
+          <select
+            value={maxDimension}
+            onChange={(e) => handleMaxDimensionChange(parseInt(e.target.value, 10))}
+            disabled={running}
+          >
+            {ANALYSIS_SIZES.map((size) => (
+              <option key={size} value={size}>
+                {analysisSizeLabel(size)}
+              </option>
+            ))}
+          </select>
+        </div>
+        <div>
+          <label>Parallel saves</label>
+          <input
+            type="number"
+            min={1}
+            max={MAX_BATCH_CONCURRENCY}
+            value={concurrency}
+            onChange={(e) => handleConcurrencyChange(parseInt(e.target.value, 10))}
+            disabled={running}
+          />
+        </div>
+      </div>
+
+      {running ? (
+        <button onClick={handleStop} className="stop-batch-button">
+          ⏹ Stop ({doneCount}/{items.length} saved)
+        </button>
+      ) : (
+        <button
+          onClick={handleStart}
+          disabled={items.length === 0 || doneCount === items.length}
+          className="analyze-button"
+        >
+          🔍 Analyze & Save {items.length} Image(s)
+        </button>
+      )}
+
+      {items.length > 0 && (
+        <>
+          <p className="batch-summary">
+            {doneCount} saved, {failedCount} failed, {items.length - doneCount - failedCount} remaining
+          </p>
+          <table className="batch-table">
+            <thead>
+              <tr>
+                <th>File</th>
+                <th>Status</th>
+                <th>Result</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {items.map((item) => (
+                <tr key={item.id} className={`batch-row status-${item.status}`}>
+                  <td>{item.file.name}</td>
+                  <td>
+                    {statusLabels[item.status]}
+                    {item.status === 'saving' && item.progress !== undefined && item.progress < 1 && (
+                      ` ${Math.round(item.progress * 100)}%`
+                    )}
+                  </td>
+                  <td>
+                    {item.error
+                      ? item.error
+                      : item.result
+                        ? `${item.result.name} (${(item.result.confidence * 100).toFixed(1)}%)`
+                        : ''}
+                  </td>
+                  <td>
+                    {item.status !== 'done' && (
+                      <button className="remove-item-button" onClick={() => handleRemove(item.id)} title="Remove">
+                        ✕
+                      </button>
+                    )}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,14 @@
+import { useCachedImage } from '../services/imageCache'
+
+interface CachedImageProps {
+  artifactId: number
+  path?: string | null
+  alt: string
+  className?: string
+}
+
+// An artifact image in a list, loaded through the image cache like the cards'
+export default function CachedImage({ artifactId, path, alt, className }: CachedImageProps) {
+  const src = useCachedImage(artifactId, path)
+  return <img className={className} src={src} alt={alt} />
+}
@@ -1,5 +1,9 @@
+import { useEffect, useState } from 'react'
 import { Outlet, useNavigate, useLocation } from 'react-router-dom'
 import { useAuth } from '../contexts/AuthContext'
+import { droppedFiles } from '../services/droppedFiles'
+import { usePresence } from '../services/presence'
+import { droppedImages } from '../utils/files'
 import { canAccess } from '../utils/permissions'
 import './Layout.css'
 
@@ -7,6 +11,47 @@ export default function Layout() {
   const { user, logout } = useAuth()
   const navigate = useNavigate()
   const location = useLocation()
+  const online = usePresence()
+  const [dragging, setDragging] = useState(false)
+  const canBatchUpload = canAccess(user?.role, 'batch-upload')
+
+  // Files dragged from the OS onto any page go to the batch upload's queue
+  useEffect(() => {
+    if (!canBatchUpload) return
+    const carriesFiles = (e: DragEvent) => !!e.dataTransfer?.types.includes('Files')
+    const handleDragOver = (e: DragEvent) => {
+      if (!carriesFiles(e)) return
+      e.preventDefault()
+      setDragging(true)
+    }
+    const handleDragLeave = (e: DragEvent) => {
+      // Only leaving the window itself, not moving between elements
+      if (e.relatedTarget === null) setDragging(false)
+    }
+    const handleDrop = (e: DragEvent) => {
+      if (!carriesFiles(e) || !e.dataTransfer) return
+      e.preventDefault()
+      setDragging(false)
+      droppedImages(e.dataTransfer)
+        .then((files) => {
+          if (files.length === 0) {
+            alert('None of the dropped files are images.')
+            return
+          }
+          droppedFiles.add(files)
+          navigate('/upload', { state: { mode: 'batch' } })
+        })
+        .catch((error) => console.error('Could not read the dropped files:', error))
+    }
+    window.addEventListener('dragover', handleDragOver)
+    window.addEventListener('dragleave', handleDragLeave)
+    window.addEventListener('drop', handleDrop)
+    return () => {
+      window.removeEventListener('dragover', handleDragOver)
+      window.removeEventListener('dragleave', handleDragLeave)
+      window.removeEventListener('drop', handleDrop)
+    }
+  }, [canBatchUpload, navigate])
 
   const getMenuItems = () => {
     if (!user) return []
@@ -15,8 +60,16 @@ export default function Layout() {
       { path: '/', label: 'Dashboard', icon: '📊', permission: null },
       { path: '/upload', label: 'Upload Artifacts', icon: '📤', permission: 'upload' },
       { path: '/gallery', label: 'Gallery Artifacts', icon: '🏺', permission: 'gallery' },
+      { path: '/trash', label: 'Trash', icon: '🗑️', permission: 'delete' },
+      { path: '/moderation', label: 'Moderation', icon: '🚩', permission: 'moderation' },
       { path: '/users', label: 'User Management', icon: '👥', permission: 'user-management' },
+      { path: '/invites', label: 'Invites', icon: '✉️', permission: 'user-management' },
+      { path: '/network-access', label: 'Network Access', icon: '🛡️', permission: 'user-management' },
+      { path: '/organizations', label: 'Organizations', icon: '🏢', permission: null },
+      { path: '/tags', label: 'Tags', icon: '🏷️', permission: 'tag-management' },
       { path: '/audit-logs', label: 'Audit Logs', icon: '📜', permission: 'audit-logs' },
+      { path: '/api-keys', label: 'API Keys', icon: '🗝️', permission: null },
+      { path: '/webhooks', label: 'Webhooks', icon: '🔔', permission: null },
     ]
 
     // Filter menu items based on user permissions
@@ -37,6 +90,11 @@ export default function Layout() {
             <div className="user-info">
               <p>Welcome, {user.name}!</p>
               <p className="user-role">{user.role}</p>
+              {online.length > 0 && (
+                <p className="online-count" title={online.join(', ')}>
+                  🟢 {online.length} online
+                </p>
+              )}
             </div>
           )}
         </div>
@@ -51,6 +109,13 @@ export default function Layout() {
               <span className="nav-label">{item.label}</span>
             </button>
           ))}
+          <button
+            className={`nav-item ${location.pathname === '/profile' ? 'active' : ''}`}
+            onClick={() => navigate('/profile')}
+          >
+            <span className="nav-icon">👤</span>
+            <span className="nav-label">My Profile</span>
+          </button>
           <button
             className="nav-item"
             onClick={() => navigate('/change-password')}
@@ -67,6 +132,7 @@ export default function Layout() {
       <main className="main-content">
         <Outlet />
       </main>
+      {dragging && <div className="drop-overlay">📥 Drop images to analyze them in a batch</div>}
     </div>
   )
 }
@@ -0,0 +1,48 @@
+import { useEffect, useState } from 'react'
+import { authApi } from '../services/api'
+import { PasswordPolicy } from '../types'
+import { DEFAULT_PASSWORD_POLICY, passwordRules, passwordStrength } from '../utils/passwordPolicy'
+import './PasswordStrength.css'
+
+const STRENGTH_LABELS = ['', 'Weak', 'Fair', 'Good', 'Strong']
+
+// Fetched once per page load and shared by every password form
+let policyRequest: Promise<PasswordPolicy> | null = null
+
+export function usePasswordPolicy(): PasswordPolicy {
+  const [policy, setPolicy] = useState(DEFAULT_PASSWORD_POLICY)
+
+  useEffect(() => {
+    policyRequest = policyRequest ?? authApi.passwordPolicy().catch(() => DEFAULT_PASSWORD_POLICY)
+    policyRequest.then(setPolicy)
+  }, [])
+
+  return policy
+}
+
+interface PasswordStrengthProps {
+  password: string
+  policy: PasswordPolicy
+}
+
+// Live checklist of the password rules plus a strength meter
+export default function PasswordStrength({ password, policy }: PasswordStrengthProps) {
+  const strength = passwordStrength(password)
+
+  return (
+    <div className="password-strength">
+      <div className={`strength-meter strength-${strength}`}>
+        <div className="strength-bar" style={{ width: `${strength * 25}%` }} />
+      </div>
+      {password && <span className="strength-label">{STRENGTH_LABELS[strength]}</span>}
+      <ul className="password-rules">
+        {passwordRules(policy).map((rule) => (
+          <li key={rule.label} className={rule.met(password) ? 'met' : ''}>
+            {rule.met(password) ? '✅' : '⬜'} {rule.label}
+          </li>
+        ))}
+        {policy.check_breached && <li className="note">🔒 Not found in known data breaches (checked on save)</li>}
+      </ul>
+    </div>
+  )
+}
@@ -0,0 +1,103 @@
+import { useEffect, useState } from 'react'
+import { artifactApi, collectionApi, orgApi } from '../services/api'
+import { OrganizationInfo, Visibility } from '../types'
+import { errorMessage } from '../utils/errors'
+import './SharingPanel.css'
+
+const VISIBILITY_LABELS: Record<Visibility, string> = {
+  private: '🔒 Only me and the people below',
+  team: '👥 My organization',
+  public: '🌍 Everyone',
+}
+
+interface SharingPanelProps {
+  kind: 'artifact' | 'collection'
+  id: number
+}
+
+// Who besides the owner and archive staff can see an artifact or collection
+export default function SharingPanel({ kind, id }: SharingPanelProps) {
+  const sharingApi = kind === 'artifact' ? artifactApi : collectionApi
+  const [visibility, setVisibility] = useState<Visibility>('private')
+  const [teamId, setTeamId] = useState<number | null>(null)
+  const [sharedWith, setSharedWith] = useState('')
+  const [teams, setTeams] = useState<OrganizationInfo[]>([])
+  const [saving, setSaving] = useState(false)
+  const [message, setMessage] = useState('')
+
+  useEffect(() => {
+    sharingApi
+      .getSharing(id)
+      .then((sharing) => {
+        setVisibility(sharing.visibility ?? 'private')
+        setTeamId(sharing.team_id ?? null)
+        setSharedWith((sharing.shared_with ?? []).join(', '))
+      })
+      .catch((error) => setMessage(errorMessage(error)))
+    orgApi.list().then(setTeams).catch(() => setTeams([]))
+  }, [kind, id])
+
+  const handleSave = async () => {
+    setSaving(true)
+    setMessage('')
+    try {
+      const saved = await sharingApi.setSharing(id, {
+        visibility,
+        team_id: visibility === 'team' ? teamId : null,
+        shared_with: sharedWith
+          .split(',')
+          .map((u) => u.trim())
+          .filter(Boolean),
+      })
+      setSharedWith((saved.shared_with ?? []).join(', '))
+      setMessage('Sharing saved')
+    } catch (error) {
+      setMessage(errorMessage(error))
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  return (
+    <div className="info-section sharing-panel">
+      <h3>🤝 Share this {kind}</h3>
+      <select
+        className="edit-input"
+        value={visibility}
+        onChange={(e) => setVisibility(e.target.value as Visibility)}
+      >
+        {(Object.keys(VISIBILITY_LABELS) as Visibility[]).map((v) => (
+          <option key={v} value={v}>{VISIBILITY_LABELS[v]}</option>
+        ))}
+      </select>
+      {visibility === 'team' && (
+        <select
+          className="edit-input"
+          value={teamId ?? ''}
+          onChange={(e) => setTeamId(e.target.value ? Number(e.target.value) : null)}
+        >
+          <option value="">Choose an organization</option>
+          {teams.map((team) => (
+            <option key={team.id} value={team.id}>{team.name}</option>
+          ))}
+        </select>
+      )}
+      <input
+        className="edit-input"
+        value={sharedWith}
+        onChange={(e) => setSharedWith(e.target.value)}
+        placeholder="Also share with usernames, comma-separated"
+      />
+      <div className="sharing-actions">
+        <button
+          className="edit-button"
+          onClick={handleSave}
+          disabled={saving || (visibility === 'team' && teamId === null)}
+        >
+          {saving ? 'Saving…' : 'Save sharing'}
+        </button>
+        {message && <span className="sharing-message">{message}</span>}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,46 @@
+import { useEffect, useState } from 'react'
+import { artifactApi } from '../services/api'
+import { SimilarArtifact } from '../types'
+import CachedImage from './CachedImage'
+import './SimilarArtifacts.css'
+
+interface SimilarArtifactsProps {
+  id: number
+}
+
+// Artifacts whose photos look like this one's; hidden when there are none
+export default function SimilarArtifacts({ id }: SimilarArtifactsProps) {
+  const [similar, setSimilar] = useState<SimilarArtifact[]>([])
+
+  useEffect(() => {
+    let current = true
+    artifactApi
+      .similar(id)
+      .then((results) => current && setSimilar(results))
+      .catch(() => current && setSimilar([]))
+    return () => {
+      current = false
+    }
+  }, [id])
+
+  if (similar.length === 0) return null
+
+  return (
+    <div className="info-section similar-artifacts">
+      <h3>🔎 Looks Similar</h3>
+      <div className="similar-grid">
+        {similar.map((item) => (
+          <div key={item.id} className="similar-item" title={item.description || item.name}>
+            {item.thumbnail_url ? (
+              <CachedImage artifactId={item.id} path={item.thumbnail_url} alt={item.name} />
+            ) : (
+              <div className="similar-placeholder">🏺</div>
+            )}
+            <span className="similar-name">{item.name}</span>
+            <span className="similar-score">{(item.similarity_score * 100).toFixed(0)}% match</span>
+          </div>
+        ))}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,126 @@
+import { useEffect, useState } from 'react'
+import { artifactApi } from '../services/api'
+import { Artifact, ArtifactRevision, RevisionAction } from '../types'
+import { errorMessage } from '../utils/errors'
+import './VersionHistory.css'
+
+interface VersionHistoryProps {
+  id: number
+  /** Reloads the history when the artifact changes */
+  version?: string
+  canRevert: boolean
+  onReverted: (artifact: Artifact) => void
+}
+
+const PAGE_SIZE = 20
+
+// Only edits can be undone; the server refuses the rest
+const REVERTIBLE: RevisionAction[] = ['updated']
+
+const ACTION_LABELS: Record<RevisionAction, string> = {
+  created: 'Created',
+  updated: 'Edited',
+  deleted: 'Moved to the trash',
+  restored: 'Restored from the trash',
+  locked: 'Locked',
+  unlocked: 'Unlocked',
+  identified: 'Identified by a curator',
+  reverted: 'Reverted a change',
+}
+
+// Column names the server reports, as the modal labels them
+const FIELD_LABELS: Record<string, string> = { age: 'era', collection_id: 'collection' }
+
+const formatValue = (value?: string | number | null) => (value === null || value === undefined || value === '' ? '—' : String(value))
+
+// Every change to an artifact, newest first, each one undoable by its editors
+export default function VersionHistory({ id, version, canRevert, onReverted }: VersionHistoryProps) {
+  const [open, setOpen] = useState(false)
+  const [revisions, setRevisions] = useState<ArtifactRevision[]>([])
+  const [total, setTotal] = useState(0)
+  const [busy, setBusy] = useState(false)
+
+  useEffect(() => {
+    if (!open) return
+    let current = true
+    artifactApi
+      .history(id, { limit: PAGE_SIZE })
+      .then((page) => {
+        if (!current) return
+        setRevisions(page.revisions)
+        setTotal(page.total)
+      })
+      .catch((error) => console.error('Failed to load the artifact history:', error))
+    return () => {
+      current = false
+    }
+  }, [id, version, open])
+
+  const loadMore = async () => {
+    try {
+      const page = await artifactApi.history(id, { limit: PAGE_SIZE, offset: revisions.length })
+      setRevisions([...revisions, ...page.revisions])
+      setTotal(page.total)
+    } catch (error) {
+      console.error('Failed to load the artifact history:', error)
+    }
+  }
+
+  const handleRevert = async (revision: ArtifactRevision) => {
+    if (!window.confirm('Undo this change? The fields it changed get their earlier values back.')) return
+    setBusy(true)
+    try {
+      onReverted(await artifactApi.revert(id, revision.id))
+    } catch (error) {
+      alert(`Failed to revert: ${errorMessage(error)}`)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  return (
+    <div className="info-section version-history">
+      <h3>
+        🕘 Version History{' '}
+        <button className="version-history-toggle" onClick={() => setOpen(!open)}>
+          {open ? 'Hide' : 'Show'}
+        </button>
+      </h3>
+      {open &&
+        (revisions.length === 0 ? (
+          <p>No changes recorded</p>
+        ) : (
+          <ul className="version-history-list">
+            {revisions.map((revision) => (
+              <li key={revision.id}>
+                <div className="version-history-meta">
+                  <strong>{ACTION_LABELS[revision.action]}</strong> by {revision.changed_by || 'the system'},{' '}
+                  {new Date(revision.changed_at).toLocaleString()}
+                  {canRevert && REVERTIBLE.includes(revision.action) && (
+                    <button onClick={() => handleRevert(revision)} disabled={busy}>
+                      Undo
+                    </button>
+                  )}
+                </div>
+                {revision.action !== 'created' && (
+                  <ul className="version-history-changes">
+                    {revision.changes.map((change) => (
+                      <li key={change.field}>
+                        {FIELD_LABELS[change.field] ?? change.field.replace(/_/g, ' ')}:{' '}
+                        <del>{formatValue(change.old)}</del> → <ins>{formatValue(change.new)}</ins>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </li>
+            ))}
+          </ul>
+        ))}
+      {open && total > revisions.length && (
+        <button className="version-history-toggle" onClick={loadMore}>
+          Show older changes
+        </button>
+      )}
+    </div>
+  )
+}
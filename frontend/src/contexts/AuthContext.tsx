@@ -1,12 +1,23 @@
 import React, { createContext, useContext, useState, useEffect, ReactNode } from 'react'
-import { api } from '../services/api'
+import { authApi } from '../services/api'
+import { archiveSync } from '../services/archiveSync'
+import { imageCache } from '../services/imageCache'
+import { presence } from '../services/presence'
+import { AuthTokens, cookieAuth, session } from '../services/session'
 import { User } from '../types'
+import { errorMessage } from '../utils/errors'
+import { loadAccountSettings } from '../utils/settings'
+
+const syncSettings = () =>
+  loadAccountSettings().catch((error) => console.warn('Could not load your saved settings:', error))
 
 interface AuthContextType {
   user: User | null
   isAuthenticated: boolean
   login: (username: string, password: string) => Promise<void>
+  loginWithTokens: (tokens: AuthTokens) => Promise<void>
   logout: () => void
+  updateUser: (profile: User) => void
   loading: boolean
 }
 
@@ -18,33 +29,89 @@ export function AuthProvider({ children }: { children: ReactNode }) {
 
   useEffect(() => {
     // Check if user is already logged in (from localStorage)
-    const storedUser = localStorage.getItem('user')
-    if (storedUser) {
-      try {
-        setUser(JSON.parse(storedUser))
-      } catch (e) {
-        localStorage.removeItem('user')
-      }
-    }
+    setUser(session.getUser())
     setLoading(false)
+
+    // Confirm the stored token is still good and pick up any profile/role changes;
+    // an expired session is cleared by the API client's 401 handling
+    if (session.getTokens() || (cookieAuth && session.getUser())) {
+      authApi
+        .me()
+        .then((profile) => {
+          session.save(profile, session.getTokens() ?? undefined)
+          setUser(profile)
+          syncSettings()
+        })
+        .catch((error) => console.warn('Could not refresh the signed-in profile:', error))
+    }
   }, [])
 
+  // Stay connected to the presence socket while signed in; an admin can sign us out through it
+  useEffect(() => {
+    if (!user) return
+    presence.start(user.username, (reason) => {
+      logout()
+      alert(reason)
+    })
+    return () => presence.stop()
+  }, [user?.username])
+
   const login = async (username: string, password: string) => {
     try {
-      // For now, we'll use a simple authentication check
-      // In production, this should call a proper auth endpoint
-      const response = await api.post('/auth/login', { username, password })
-      const userData = response.data
+      if (cookieAuth) {
+        const { csrf_token, expires_in: _expiresIn, ...userData } = await authApi.createSession(username, password)
+        session.save(userData)
+        session.saveCsrfToken(csrf_token)
+        setUser(userData)
+        syncSettings()
+        return
+      }
+
+      const {
+        access_token,
+        refresh_token,
+        token_type: _tokenType,
+        expires_in: _expiresIn,
+        ...userData
+      } = await authApi.login(username, password)
+      session.save(userData, access_token ? { access_token, refresh_token } : undefined)
       setUser(userData)
-      localStorage.setItem('user', JSON.stringify(userData))
+      syncSettings()
     } catch (error: any) {
-      throw new Error(error.response?.data?.detail || 'Login failed')
+      // Keep the status so the login page can tell bad credentials from outages
+      const loginError: Error & { status?: number } = new Error(error.response ? errorMessage(error) : 'Login failed')
+      loginError.status = error.response?.status
+      throw loginError
     }
   }
 
+  // Finish a sign-in that happened elsewhere (OAuth), where only tokens come back
+  const loginWithTokens = async (tokens: AuthTokens) => {
+    session.saveTokens(tokens)
+    const profile = await authApi.me()
+    session.save(profile, session.getTokens() ?? undefined)
+    setUser(profile)
+    syncSettings()
+  }
+
+  // Keep the stored session in step after the user edits their own profile
+  const updateUser = (profile: User) => {
+    session.save(profile, session.getTokens() ?? undefined)
+    setUser(profile)
+  }
+
   const logout = () => {
+    // Revoke the server-side session; signing out locally doesn't wait for it
+    const refreshToken = session.getTokens()?.refresh_token
+    if (cookieAuth) {
+      authApi.endSession(session.getCsrfToken()).catch((error) => console.warn('Could not revoke session:', error))
+    } else if (refreshToken) {
+      authApi.logout(refreshToken).catch((error) => console.warn('Could not revoke session:', error))
+    }
     setUser(null)
-    localStorage.removeItem('user')
+    session.clear()
+    archiveSync.reset()
+    imageCache.clear().catch((error) => console.warn('Could not clear the image cache:', error))
   }
 
   return (
@@ -53,7 +120,9 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         user,
         isAuthenticated: !!user,
         login,
+        loginWithTokens,
         logout,
+        updateUser,
         loading,
       }}
     >
@@ -0,0 +1,120 @@
+import { useEffect, useState } from 'react'
+import { apiKeyApi } from '../services/api'
+import { ApiKeyInfo } from '../types'
+import { errorMessage } from '../utils/errors'
+import './ApiKeys.css'
+
+export default function ApiKeys() {
+  const [keys, setKeys] = useState<ApiKeyInfo[]>([])
+  const [loading, setLoading] = useState(true)
+  const [name, setName] = useState('')
+  const [newKey, setNewKey] = useState<string | null>(null)
+
+  useEffect(() => {
+    loadKeys()
+  }, [])
+
+  const loadKeys = async () => {
+    try {
+      setKeys(await apiKeyApi.list())
+    } catch (error) {
+      console.error('Failed to load API keys:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleCreate = async (e: React.FormEvent) => {
+    e.preventDefault()
+    try {
+      const created = await apiKeyApi.create(name.trim())
+      setNewKey(created.key)
+      setName('')
+      await loadKeys()
+    } catch (error) {
+      alert(`Failed to create API key: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRevoke = async (key: ApiKeyInfo) => {
+    if (!window.confirm(`Revoke "${key.name}"? Anything using it will stop working.`)) return
+    try {
+      await apiKeyApi.revoke(key.id)
+      await loadKeys()
+    } catch (error) {
+      alert(`Failed to revoke API key: ${errorMessage(error)}`)
+    }
+  }
+
+  const formatDate = (value?: string | null) => (value ? new Date(value).toLocaleString() : '—')
+
+  if (loading) {
+    return <div className="loading">Loading API keys...</div>
+  }
+
+  return (
+    <div className="api-keys">
+      <h1>🗝️ API Keys</h1>
+      <p className="api-keys-intro">
+        Keys let scripts and tools call the API as you without signing in. Send the key in an{' '}
+        <code>X-Api-Key</code> header.
+      </p>
+
+      <form onSubmit={handleCreate} className="api-key-form">
+        <input
+          type="text"
+          value={name}
+          onChange={(e) => setName(e.target.value)}
+          placeholder="What is this key for? e.g. nightly import"
+          required
+        />
+        <button type="submit">➕ Create Key</button>
+      </form>
+
+      {newKey && (
+        <div className="new-key">
+          <p>Copy this key now, it won't be shown again:</p>
+          <code>{newKey}</code>
+          <button onClick={() => setNewKey(null)}>Done</button>
+        </div>
+      )}
+
+      {keys.length === 0 ? (
+        <p>No API keys yet</p>
+      ) : (
+        <table className="api-keys-table">
+          <thead>
+            <tr>
+              <th>Name</th>
+              <th>Key</th>
+              <th>Created</th>
+              <th>Last used</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {keys.map((key) => (
+              <tr key={key.id} className={key.revoked_at ? 'revoked' : ''}>
+                <td>{key.name}</td>
+                <td>
+                  <code>{key.prefix}…</code>
+                </td>
+                <td>{formatDate(key.created_at)}</td>
+                <td>{formatDate(key.last_used_at)}</td>
+                <td>
+                  {key.revoked_at ? (
+                    'Revoked'
+                  ) : (
+                    <button className="revoke-button" onClick={() => handleRevoke(key)}>
+                      Revoke
+                    </button>
+                  )}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  )
+}
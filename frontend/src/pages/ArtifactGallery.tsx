@@ -1,88 +1,161 @@
-import { useEffect, useState } from 'react'
+import { useEffect, useMemo, useRef, useState } from 'react'
 import { useSearchParams } from 'react-router-dom'
-import { artifactApi } from '../services/api'
-import { Artifact } from '../types'
+import { useAuth } from '../contexts/AuthContext'
+import { artifactApi, tagApi } from '../services/api'
+import { archiveSync } from '../services/archiveSync'
+import { imageCache } from '../services/imageCache'
+import { useTaskRegistry } from '../services/tasks'
+import { Artifact, ArtifactFilters, ArtifactPage, ArtifactSort, TagCount } from '../types'
+import ArchiveTransfer from '../components/ArchiveTransfer'
 import ArtifactCard from '../components/ArtifactCard'
 import ArtifactModal from '../components/ArtifactModal'
+import { canAccess } from '../utils/permissions'
+import { errorMessage, toAppError } from '../utils/errors'
 import './ArtifactGallery.css'
 
+const SERVER_SORTS: ArtifactSort[] = ['uploaded_at', 'name', 'confidence', 'tier']
+// Search and filter results arrive this many at a time
+const PAGE_SIZE = 60
+
+// The loaded artifacts in the chosen order; searches keep the server's ranking for "relevance"
+function sortArtifacts(items: Artifact[], sortBy: string, sortOrder: 'asc' | 'desc'): Artifact[] {
+  return [...items].sort((a, b) => {
+    let comparison = 0;
+    
+    switch (sortBy) {
+      case 'relevance':
+        // Server order (best match first); the sort is stable
+        return 0;
+      case 'name':
+        comparison = (a.name || '').localeCompare(b.name || '');
+        break;
+      case 'uploaded_at': {
+        const parseDate = (dateStr: string | undefined): number => {
+          if (!dateStr) return 0;
+          // Handle both ISO strings and other formats
+          const date = new Date(dateStr);
+          // If date is invalid, return 0 to sort it at the beginning/end
+          return isNaN(date.getTime()) ? 0 : date.getTime();
+        };
+        const dateA = parseDate(a.uploaded_at);
+        const dateB = parseDate(b.uploaded_at);
+        comparison = dateA - dateB;
+        break;
+      }
+      case 'confidence':
+        comparison = (a.confidence || 0) - (b.confidence || 0);
+        break;
+      case 'tier':
+        comparison = (a.tier || '').localeCompare(b.tier || '');
+        break;
+      default:
+        comparison = 0;
+    }
+    
+    return sortOrder === 'asc' ? comparison : -comparison;
+  });
+}
+
 export default function ArtifactGallery() {
-  const [artifacts, setArtifacts] = useState<Artifact[]>([])
+  const { user } = useAuth()
+  const canSeeAll = canAccess(user?.role, 'all-artifacts')
+  // As loaded; `artifacts` below is what's shown, sorted
+  const [loadedArtifacts, setArtifacts] = useState<Artifact[]>([])
   const [loading, setLoading] = useState(true)
   const [searchQuery, setSearchQuery] = useState('')
   const [selectedArtifact, setSelectedArtifact] = useState<Artifact | null>(null)
   const [searchParams, setSearchParams] = useSearchParams()
 
   // ---- NEW: Sorting state ----
-  const [sortBy, setSortBy] = useState<string>('name')
+  // Searches come back ranked by relevance; keep that order unless asked otherwise
+  const [sortBy, setSortBy] = useState<string>(() => (searchParams.get('q') ? 'relevance' : 'name'))
   const [sortOrder, setSortOrder] = useState<'asc' | 'desc'>('asc')
 
+  // Derived once per change of the list or the order, not on every render (each keystroke in the search box)
+  const artifacts = useMemo(() => sortArtifacts(loadedArtifacts, sortBy, sortOrder), [loadedArtifacts, sortBy, sortOrder])
+
+  // Filters run on the server (GET /api/artifacts) rather than over the whole archive
+  const [filters, setFilters] = useState<ArtifactFilters>({})
+  const filtering = Object.values(filters).some((value) => value !== undefined && value !== '')
+  const setFilter = <K extends keyof ArtifactFilters>(key: K, value: ArtifactFilters[K]) =>
+    setFilters((current) => ({ ...current, [key]: value === '' ? undefined : value }))
+
+  // Suggestions for the tag filter, from the server's vocabulary
+  const [tagVocabulary, setTagVocabulary] = useState<TagCount[]>([])
+  useEffect(() => {
+    tagApi.list().then(setTagVocabulary).catch(() => setTagVocabulary([]))
+  }, [])
+  const tagOptions = useMemo(
+    () =>
+      tagVocabulary.map((t) => (
+        <option key={t.tag} value={t.tag}>
+          {t.count}
+        </option>
+      )),
+    [tagVocabulary]
+  )
+
+  // Matches on the server for searches and filters; null while showing the synced archive
+  const [total, setTotal] = useState<number | null>(null)
+  const [loadingMore, setLoadingMore] = useState(false)
+  // The page after the loaded ones, requested in the background so "Load more" is instant
+  const prefetched = useRef<{ offset: number; page: Promise<ArtifactPage> } | null>(null)
+  const loadMoreButton = useRef<HTMLButtonElement>(null)
+  const tasks = useTaskRegistry()
+
+  // Everyone starts on their own uploads; widening shows what others shared
+  // with them (curators see the whole archive)
+  const [showAll, setShowAll] = useState(false)
+  const uploadedBy = showAll ? undefined : user?.username
+
   useEffect(() => {
     const query = searchParams.get('q') || ''
     setSearchQuery(query)
     loadArtifacts(query)
-  }, [searchParams, sortBy, sortOrder]) // reload when sorting changes
-
-  const sortArtifacts = (items: Artifact[]) => {
-    return [...items].sort((a, b) => {
-      let comparison = 0;
-      
-      switch (sortBy) {
-        case 'name':
-          comparison = (a.name || '').localeCompare(b.name || '');
-          break;
-        case 'uploaded_at': {
-          const parseDate = (dateStr: string | undefined): number => {
-            if (!dateStr) return 0;
-            // Handle both ISO strings and other formats
-            const date = new Date(dateStr);
-            // If date is invalid, return 0 to sort it at the beginning/end
-            return isNaN(date.getTime()) ? 0 : date.getTime();
-          };
-          const dateA = parseDate(a.uploaded_at);
-          const dateB = parseDate(b.uploaded_at);
-          comparison = dateA - dateB;
-          break;
-        }
-        case 'confidence':
-          comparison = (a.confidence || 0) - (b.confidence || 0);
-          break;
-        case 'tier':
-          comparison = (a.tier || '').localeCompare(b.tier || '');
-          break;
-        default:
-          comparison = 0;
-      }
-      
-      return sortOrder === 'asc' ? comparison : -comparison;
-    });
+  }, [searchParams, sortBy, sortOrder, uploadedBy, filters]) // reload when sorting, scope or filters change
+
+  const fetchPage = (query: string, offset: number, signal?: AbortSignal): Promise<ArtifactPage> => {
+    if (query.trim()) {
+      return artifactApi.search(query, uploadedBy, { limit: PAGE_SIZE, offset }, signal)
+    }
+    const sort: ArtifactSort | undefined = SERVER_SORTS.includes(sortBy as ArtifactSort) ? (sortBy as ArtifactSort) : undefined
+    return artifactApi.getAll(uploadedBy, { ...filters, sort, order: sortOrder, limit: PAGE_SIZE, offset }, signal)
+  }
+
+  const cancelPrefetch = () => {
+    tasks.cancel('prefetch')
+    prefetched.current = null
   }
 
   const loadArtifacts = async (query: string = '') => {
+    // A prefetched page belongs to the previous query, filters or order
+    cancelPrefetch()
     setLoading(true)
     try {
       let data
 
-      if (query.trim()) {
-        // Try search API first
+      if (query.trim() || filtering) {
         try {
-          data = await artifactApi.search(query)
+          const page = await fetchPage(query, 0)
+          data = page.artifacts
+          setTotal(page.total)
         } catch (searchError: any) {
+          if (!query.trim()) throw searchError
           console.warn('Search failed. Falling back to client-side filtering:', searchError)
-          const allArtifacts = await artifactApi.getAll()
+          const allArtifacts = await archiveSync.sync(uploadedBy)
           const q = query.toLowerCase()
           data = allArtifacts.filter((a: any) =>
             a.name?.toLowerCase().includes(q) ||
             a.description?.toLowerCase().includes(q) ||
             a.tags?.some((tag: string) => tag.toLowerCase().includes(q))
           )
+          setTotal(null)
         }
       } else {
-        data = await artifactApi.getAll();
+        // Only pulls what changed since the last visit
+        data = await archiveSync.sync(uploadedBy);
+        setTotal(null)
       }
-      
-      // Apply sorting to the data
-      data = sortArtifacts(data);
 
       setArtifacts(data)
     } catch (error) {
@@ -93,15 +166,92 @@ export default function ArtifactGallery() {
     }
   }
 
+  // Once a page is showing, fetch the next while the user looks through it
+  useEffect(() => {
+    const offset = loadedArtifacts.length
+    if (loading || total === null || offset >= total || prefetched.current?.offset === offset) return
+    cancelPrefetch()
+    const signal = tasks.start('prefetch')
+    const page = fetchPage(searchParams.get('q') || '', offset, signal)
+    // Failures surface if "Load more" ends up using it
+    page.catch(() => undefined).finally(() => tasks.finish('prefetch', signal))
+    prefetched.current = { offset, page }
+  }, [loading, loadedArtifacts, total])
+
+  const loadMore = async () => {
+    if (loadingMore) return
+    setLoadingMore(true)
+    const query = searchParams.get('q') || ''
+    const offset = loadedArtifacts.length
+    const ahead = prefetched.current?.offset === offset ? prefetched.current.page : null
+    prefetched.current = null
+    try {
+      // Ask again if the prefetch failed or was cancelled
+      const page = await (ahead ? ahead.catch(() => fetchPage(query, offset)) : fetchPage(query, offset))
+      setArtifacts((current) => {
+        const seen = new Set(current.map((a) => a.id))
+        return [...current, ...page.artifacts.filter((a) => !seen.has(a.id))]
+      })
+      setTotal(page.total)
+    } catch (error) {
+      alert(`Failed to load more artifacts: ${errorMessage(error)}`)
+    } finally {
+      setLoadingMore(false)
+    }
+  }
+
+  // Infinite scroll: load the next page as the "Load more" button comes near
+  useEffect(() => {
+    const button = loadMoreButton.current
+    if (!button || loadingMore || typeof IntersectionObserver === 'undefined') return
+    const observer = new IntersectionObserver((entries) => entries[0]?.isIntersecting && loadMore(), { rootMargin: '400px' })
+    observer.observe(button)
+    return () => observer.disconnect()
+  }, [loadingMore, loadedArtifacts, total])
+
+  const handleDelete = async (artifact: Artifact) => {
+    // Remove locally first so the archive reflects the delete immediately
+    const previous = loadedArtifacts
+    setArtifacts((current) => current.filter((a) => a.id !== artifact.id))
+    setTotal((current) => (current === null ? current : current - 1))
+    setSelectedArtifact(null)
+
+    try {
+      await artifactApi.delete(artifact.id)
+      archiveSync.remove(artifact.id)
+      imageCache.remove(artifact.id)
+    } catch (error) {
+      if (toAppError(error).code === 'not_found') {
+        // Already gone on the server, local state is now in sync
+        archiveSync.remove(artifact.id)
+        return
+      }
+      console.error('Failed to delete artifact:', error)
+      setArtifacts(previous)
+      setTotal((current) => (current === null ? current : current + 1))
+      alert(`Failed to delete artifact: ${errorMessage(error)}`)
+    }
+  }
+
   const handleSearch = (e: React.FormEvent) => {
     e.preventDefault()
     if (searchQuery) {
       setSearchParams({ q: searchQuery })
+      setSortBy('relevance')
     } else {
       setSearchParams({})
+      if (sortBy === 'relevance') setSortBy('name')
     }
   }
 
+  const cards = useMemo(
+    () =>
+      artifacts.map((artifact) => (
+        <ArtifactCard key={artifact.id} artifact={artifact} onClick={() => setSelectedArtifact(artifact)} />
+      )),
+    [artifacts]
+  )
+
   if (loading) {
     return <div className="gallery-loading">Loading artifacts...</div>
   }
@@ -117,7 +267,7 @@ export default function ArtifactGallery() {
             type="text"
             value={searchQuery}
             onChange={(e) => setSearchQuery(e.target.value)}
-            placeholder="Search by name, description, material, or tags"
+            placeholder='Search by name, description, material, or tags ("quotes" for a phrase)'
             className="search-input"
           />
           <button type="submit" className="search-button">
@@ -132,6 +282,7 @@ export default function ArtifactGallery() {
             onChange={(e) => setSortBy(e.target.value)}
             className="sort-select"
           >
+            {searchParams.get('q') && <option value="relevance">Relevance</option>}
             <option value="name">Name</option>
             <option value="uploaded_at">Upload Date</option>
             <option value="confidence">Confidence</option>
@@ -142,23 +293,100 @@ export default function ArtifactGallery() {
             value={sortOrder}
             onChange={(e) => setSortOrder(e.target.value as 'asc' | 'desc')}
             className="sort-select"
+            disabled={sortBy === 'relevance'}
           >
             <option value="asc">⬆️ Ascending</option>
             <option value="desc">⬇️ Descending</option>
           </select>
         </div>
+
+        {/* ---------- Filters (not applied to searches) ---------- */}
+        {!searchParams.get('q') && (
+          <div className="filter-controls">
+            {/* Keyed on the value so clearing the filters empties the box */}
+            <input
+              key={`era-${filters.era ?? ''}`}
+              type="text"
+              defaultValue={filters.era}
+              placeholder="Era, e.g. Roman"
+              onBlur={(e) => setFilter('era', e.target.value.trim())}
+              onKeyDown={(e) => e.key === 'Enter' && setFilter('era', e.currentTarget.value.trim())}
+              className="filter-input"
+            />
+            <input
+              key={`tag-${filters.tag ?? ''}`}
+              type="text"
+              defaultValue={filters.tag}
+              placeholder="Tags, comma-separated"
+              list="gallery-tags"
+              onBlur={(e) => setFilter('tag', e.target.value.trim())}
+              onKeyDown={(e) => e.key === 'Enter' && setFilter('tag', e.currentTarget.value.trim())}
+              className="filter-input"
+            />
+            <datalist id="gallery-tags">{tagOptions}</datalist>
+            <select value={filters.tier ?? ''} onChange={(e) => setFilter('tier', e.target.value)} className="sort-select">
+              <option value="">Any tier</option>
+              <option value="instant">Instant</option>
+              <option value="fast">Fast</option>
+              <option value="balanced">Balanced</option>
+              <option value="thorough">Thorough</option>
+            </select>
+            <select
+              value={filters.verified === undefined ? '' : String(filters.verified)}
+              onChange={(e) => setFilter('verified', e.target.value === '' ? undefined : e.target.value === 'true')}
+              className="sort-select"
+            >
+              <option value="">Any status</option>
+              <option value="true">✅ Verified</option>
+              <option value="false">⏳ Not verified</option>
+            </select>
+            <select
+              value={filters.min_confidence ?? ''}
+              onChange={(e) => setFilter('min_confidence', e.target.value === '' ? undefined : Number(e.target.value))}
+              className="sort-select"
+            >
+              <option value="">Any confidence</option>
+              <option value="0.5">50%+</option>
+              <option value="0.7">70%+</option>
+              <option value="0.9">90%+</option>
+            </select>
+            {filtering && (
+              <button onClick={() => setFilters({})} className="clear-filters">
+                ✖ Clear filters
+              </button>
+            )}
+          </div>
+        )}
+
+        {user && (
+          <div className="scope-toggle">
+            <button
+              className={!showAll ? 'active' : ''}
+              onClick={() => setShowAll(false)}
+            >
+              👤 My Uploads
+            </button>
+            <button
+              className={showAll ? 'active' : ''}
+              onClick={() => setShowAll(true)}
+            >
+              {canSeeAll ? '🗂️ All Artifacts' : '🤝 Mine and Shared'}
+            </button>
+          </div>
+        )}
       </div>
 
       {/* ---------- Stats ---------- */}
       <div className="gallery-stats">
         <div className="stat">
-          <strong>Total Artifacts:</strong> {artifacts.length}
+          <strong>{uploadedBy ? 'My Artifacts:' : 'Total Artifacts:'}</strong> {total ?? artifacts.length}
         </div>
         {searchQuery && (
           <div className="stat">
-            <strong>Search Results:</strong> {artifacts.length}
+            <strong>Search Results:</strong> {total ?? artifacts.length}
           </div>
         )}
+        <ArchiveTransfer uploadedBy={uploadedBy} filters={filters} onImported={() => loadArtifacts(searchQuery)} />
       </div>
 
       {/* ---------- Grid or Empty ---------- */}
@@ -167,15 +395,13 @@ export default function ArtifactGallery() {
           <p>📭 No artifacts found. Start by uploading one!</p>
         </div>
       ) : (
-        <div className="gallery-grid">
-          {artifacts.map((artifact) => (
-            <ArtifactCard
-              key={artifact.id}
-              artifact={artifact}
-              onClick={() => setSelectedArtifact(artifact)}
-            />
-          ))}
-        </div>
+        <div className="gallery-grid">{cards}</div>
+      )}
+
+      {total !== null && artifacts.length < total && (
+        <button ref={loadMoreButton} className="load-more" onClick={loadMore} disabled={loadingMore}>
+          {loadingMore ? 'Loading...' : `⬇️ Load more (${artifacts.length} of ${total})`}
+        </button>
       )}
 
       {/* ---------- Modal ---------- */}
@@ -184,6 +410,7 @@ export default function ArtifactGallery() {
           artifact={selectedArtifact}
           onClose={() => setSelectedArtifact(null)}
           onUpdate={loadArtifacts}
+          onDelete={handleDelete}
         />
       )}
     </div>
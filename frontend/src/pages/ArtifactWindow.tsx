@@ -0,0 +1,46 @@
+import { useEffect, useState } from 'react'
+import { useParams } from 'react-router-dom'
+import ArtifactModal from '../components/ArtifactModal'
+import { artifactApi } from '../services/api'
+import { archiveSync } from '../services/archiveSync'
+import { imageCache } from '../services/imageCache'
+import { Artifact } from '../types'
+import { errorMessage } from '../utils/errors'
+
+/**
+ * One artifact's details on their own, for a separate browser window
+ * (opened from ArtifactModal) so several can be kept side by side.
+ */
+export default function ArtifactWindow() {
+  const { id } = useParams()
+  const [artifact, setArtifact] = useState<Artifact | null>(null)
+  const [error, setError] = useState('')
+
+  const load = () => {
+    artifactApi
+      .getById(Number(id))
+      .then((data) => {
+        setArtifact(data)
+        document.title = `${data.name} - ArtiQuest`
+      })
+      .catch((e) => setError(errorMessage(e)))
+  }
+
+  useEffect(load, [id])
+
+  const handleDelete = async (target: Artifact) => {
+    try {
+      await artifactApi.delete(target.id)
+      archiveSync.remove(target.id)
+      imageCache.remove(target.id)
+      window.close()
+    } catch (e) {
+      alert(`Failed to delete artifact: ${errorMessage(e)}`)
+    }
+  }
+
+  if (error) return <div className="gallery-loading">Could not load this artifact: {error}</div>
+  if (!artifact) return <div className="gallery-loading">Loading artifact...</div>
+
+  return <ArtifactModal artifact={artifact} onClose={() => window.close()} onUpdate={load} onDelete={handleDelete} standalone />
+}
@@ -1,6 +1,8 @@
 import { useState } from 'react'
 import { useAuth } from '../contexts/AuthContext'
+import PasswordStrength, { usePasswordPolicy } from '../components/PasswordStrength'
 import { userApi } from '../services/api'
+import { passwordProblems } from '../utils/passwordPolicy'
 import './ChangePassword.css'
 
 export default function ChangePassword() {
@@ -10,6 +12,7 @@ export default function ChangePassword() {
   const [loading, setLoading] = useState(false)
   const [success, setSuccess] = useState(false)
   const [error, setError] = useState('')
+  const policy = usePasswordPolicy()
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
@@ -20,8 +23,9 @@ export default function ChangePassword() {
       return
     }
 
-    if (newPassword.length < 6) {
-      setError('Password must be at least 6 characters long')
+    const problems = passwordProblems(newPassword, policy)
+    if (problems.length > 0) {
+      setError(`Password needs ${problems.join(', ')}`)
       return
     }
 
@@ -57,8 +61,9 @@ export default function ChangePassword() {
             value={newPassword}
             onChange={(e) => setNewPassword(e.target.value)}
             required
-            minLength={6}
+            minLength={policy.min_length}
           />
+          <PasswordStrength password={newPassword} policy={policy} />
         </div>
 
         <div className="form-group">
@@ -69,7 +74,7 @@ export default function ChangePassword() {
             value={confirmPassword}
             onChange={(e) => setConfirmPassword(e.target.value)}
             required
-            minLength={6}
+            minLength={policy.min_length}
           />
         </div>
 
@@ -1,14 +1,16 @@
 import { useEffect, useState } from 'react'
 import { useAuth } from '../contexts/AuthContext'
-import { artifactApi, userApi } from '../services/api'
-import { Artifact, AuditLog } from '../types'
-import { canAccess } from '../utils/permissions'
+import { adminUserApi, userApi } from '../services/api'
+import { archiveSync } from '../services/archiveSync'
+import { AdminStats, Artifact, AuditLog, UserRole } from '../types'
+import { ROLE_LABELS, canAccess } from '../utils/permissions'
 import './Dashboard.css'
 
 export default function Dashboard() {
   const { user } = useAuth()
   const [artifacts, setArtifacts] = useState<Artifact[]>([])
   const [auditLogs, setAuditLogs] = useState<AuditLog[]>([])
+  const [stats, setStats] = useState<AdminStats | null>(null)
   const [loading, setLoading] = useState(true)
 
   useEffect(() => {
@@ -17,14 +19,16 @@ export default function Dashboard() {
 
   const loadData = async () => {
     try {
-      const [artifactsData, logsData] = await Promise.all([
-        artifactApi.getAll(),
+      const [artifactsData, logsData, statsData] = await Promise.all([
+        archiveSync.sync(),
         canAccess(user?.role, 'audit-logs') ? userApi.getAuditLogs() : Promise.resolve([]),
+        canAccess(user?.role, 'user-management') ? adminUserApi.stats() : Promise.resolve(null),
       ])
       setArtifacts(artifactsData)
       if (canAccess(user?.role, 'audit-logs')) {
         setAuditLogs(logsData)
       }
+      setStats(statsData)
     } catch (error) {
       console.error('Failed to load dashboard data:', error)
     } finally {
@@ -54,15 +58,84 @@ export default function Dashboard() {
               <div className="stat-value">{auditLogs.length}</div>
               <div className="stat-label">Recent Actions</div>
             </div>
+            {stats && (
+              <>
+                <div className="stat-card">
+                  <div className="stat-value">{stats.active_users}</div>
+                  <div className="stat-label">Active Users</div>
+                </div>
+                <div className="stat-card">
+                  <div className="stat-value">{stats.signed_in_users}</div>
+                  <div className="stat-label">Signed In ({stats.active_sessions} sessions)</div>
+                </div>
+                <div className="stat-card">
+                  <div className={`stat-value ${stats.failed_logins_24h > 0 ? 'warning' : ''}`}>
+                    {stats.failed_logins_24h}
+                  </div>
+                  <div className="stat-label">Failed Logins (24h)</div>
+                </div>
+              </>
+            )}
           </div>
 
-          <div className="dashboard-section">
-            <h2>User Role Distribution</h2>
-            <div className="role-distribution">
-              {/* This would be better with a chart library, but showing basic stats */}
-              <p>User management available in User Management section</p>
-            </div>
-          </div>
+          {stats && (
+            <>
+              <div className="dashboard-section">
+                <h2>User Role Distribution</h2>
+                <div className="role-distribution">
+                  {Object.entries(stats.users_by_role).map(([role, count]) => (
+                    <div key={role} className="role-count">
+                      <span>{ROLE_LABELS[role as UserRole] ?? role}</span>
+                      <strong>{count}</strong>
+                    </div>
+                  ))}
+                  {stats.deactivated_users > 0 && (
+                    <div className="role-count muted">
+                      <span>Deactivated</span>
+                      <strong>{stats.deactivated_users}</strong>
+                    </div>
+                  )}
+                </div>
+              </div>
+
+              <div className="dashboard-section">
+                <h2>Locked Accounts</h2>
+                {stats.locked_accounts.length > 0 ? (
+                  <div className="activity-list">
+                    {stats.locked_accounts.map((account) => (
+                      <div key={account.username} className="activity-item warning">
+                        <span className="activity-action">🔒 {account.username}</span>
+                        <span className="activity-time">
+                          until {new Date(account.locked_until).toLocaleString()}
+                        </span>
+                      </div>
+                    ))}
+                  </div>
+                ) : (
+                  <p>No accounts are locked</p>
+                )}
+              </div>
+
+              <div className="dashboard-section">
+                <h2>Recent Failed Logins</h2>
+                {stats.recent_failed_logins.length > 0 ? (
+                  <div className="activity-list">
+                    {stats.recent_failed_logins.map((attempt, idx) => (
+                      <div key={idx} className="activity-item warning">
+                        <span className="activity-action">
+                          {attempt.username}
+                          {attempt.ip_address && ` from ${attempt.ip_address}`}
+                        </span>
+                        <span className="activity-time">{new Date(attempt.attempted_at).toLocaleString()}</span>
+                      </div>
+                    ))}
+                  </div>
+                ) : (
+                  <p>No failed logins</p>
+                )}
+              </div>
+            </>
+          )}
         </>
       ) : (
         <>
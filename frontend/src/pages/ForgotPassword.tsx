@@ -0,0 +1,61 @@
+import { useState } from 'react'
+import { Link } from 'react-router-dom'
+import { authApi } from '../services/api'
+import { errorMessage } from '../utils/errors'
+import './Login.css'
+
+export default function ForgotPassword() {
+  const [identifier, setIdentifier] = useState('')
+  const [sent, setSent] = useState(false)
+  const [error, setError] = useState('')
+  const [loading, setLoading] = useState(false)
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setError('')
+    setLoading(true)
+    try {
+      await authApi.forgotPassword(identifier.trim())
+      setSent(true)
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  return (
+    <div className="login-container">
+      <div className="login-box">
+        <h1>🏺 ArtiQuest</h1>
+        <h2>Forgot Password</h2>
+        {sent ? (
+          <div className="success-message">
+            If that account exists, a reset link is on its way. Check your messages and follow the link to choose a new password.
+          </div>
+        ) : (
+          <form onSubmit={handleSubmit}>
+            <div className="form-group">
+              <label htmlFor="identifier">Username or email</label>
+              <input
+                id="identifier"
+                type="text"
+                value={identifier}
+                onChange={(e) => setIdentifier(e.target.value)}
+                required
+                autoFocus
+              />
+            </div>
+            {error && <div className="error-message">{error}</div>}
+            <button type="submit" disabled={loading} className="login-button">
+              {loading ? 'Sending...' : 'Send Reset Link'}
+            </button>
+          </form>
+        )}
+        <Link to="/login" className="login-link">
+          ← Back to login
+        </Link>
+      </div>
+    </div>
+  )
+}
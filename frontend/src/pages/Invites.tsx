@@ -0,0 +1,142 @@
+import { useEffect, useState } from 'react'
+import { inviteApi } from '../services/api'
+import { InviteInfo, UserRole } from '../types'
+import { errorMessage } from '../utils/errors'
+import { ROLE_LABELS } from '../utils/permissions'
+import './Invites.css'
+
+const inviteStatus = (invite: InviteInfo) => {
+  if (invite.used_at) return `Used by ${invite.used_by}`
+  if (invite.revoked_at) return 'Revoked'
+  if (new Date(invite.expires_at) <= new Date()) return 'Expired'
+  return 'Open'
+}
+
+export default function Invites() {
+  const [invites, setInvites] = useState<InviteInfo[]>([])
+  const [loading, setLoading] = useState(true)
+  const [role, setRole] = useState<UserRole>('user')
+  const [email, setEmail] = useState('')
+  const [newLink, setNewLink] = useState<string | null>(null)
+
+  useEffect(() => {
+    loadInvites()
+  }, [])
+
+  const loadInvites = async () => {
+    try {
+      setInvites(await inviteApi.list())
+    } catch (error) {
+      console.error('Failed to load invites:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleCreate = async (e: React.FormEvent) => {
+    e.preventDefault()
+    try {
+      const created = await inviteApi.create({ role, email: email.trim() || undefined })
+      setNewLink(`${window.location.origin}/register?code=${encodeURIComponent(created.code)}`)
+      setEmail('')
+      await loadInvites()
+    } catch (error) {
+      alert(`Failed to create invite: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRevoke = async (invite: InviteInfo) => {
+    if (!window.confirm(`Revoke invite ${invite.prefix}…?`)) return
+    try {
+      await inviteApi.revoke(invite.id)
+      await loadInvites()
+    } catch (error) {
+      alert(`Failed to revoke invite: ${errorMessage(error)}`)
+    }
+  }
+
+  const formatDate = (value?: string | null) => (value ? new Date(value).toLocaleString() : '—')
+
+  if (loading) {
+    return <div className="loading">Loading invites...</div>
+  }
+
+  return (
+    <div className="invites">
+      <h1>✉️ Invites</h1>
+      <p className="invites-intro">
+        Each invite lets one person create an account with the chosen role. Limit it to an email address to stop it
+        being passed on.
+      </p>
+
+      <form onSubmit={handleCreate} className="invite-form">
+        <select value={role} onChange={(e) => setRole(e.target.value as UserRole)}>
+          {Object.entries(ROLE_LABELS).map(([value, label]) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <input
+          type="email"
+          value={email}
+          onChange={(e) => setEmail(e.target.value)}
+          placeholder="Only for this email (optional)"
+        />
+        <button type="submit">➕ Create Invite</button>
+      </form>
+
+      {newLink && (
+        <div className="new-invite">
+          <p>Send this link to the new user; it works once and won't be shown again:</p>
+          <code>{newLink}</code>
+          <button onClick={() => setNewLink(null)}>Done</button>
+        </div>
+      )}
+
+      {invites.length === 0 ? (
+        <p>No invites yet</p>
+      ) : (
+        <table className="invites-table">
+          <thead>
+            <tr>
+              <th>Code</th>
+              <th>Role</th>
+              <th>Email</th>
+              <th>Created</th>
+              <th>Expires</th>
+              <th>Status</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {invites.map((invite) => {
+              const status = inviteStatus(invite)
+              return (
+                <tr key={invite.id} className={status === 'Open' ? '' : 'closed'}>
+                  <td>
+                    <code>{invite.prefix}…</code>
+                  </td>
+                  <td>{ROLE_LABELS[invite.role]}</td>
+                  <td>{invite.email || 'Anyone'}</td>
+                  <td>
+                    {formatDate(invite.created_at)} by {invite.created_by}
+                  </td>
+                  <td>{formatDate(invite.expires_at)}</td>
+                  <td>{status}</td>
+                  <td>
+                    {status === 'Open' && (
+                      <button className="revoke-button" onClick={() => handleRevoke(invite)}>
+                        Revoke
+                      </button>
+                    )}
+                  </td>
+                </tr>
+              )
+            })}
+          </tbody>
+        </table>
+      )}
+    </div>
+  )
+}
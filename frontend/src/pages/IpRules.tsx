@@ -0,0 +1,123 @@
+import { useEffect, useState } from 'react'
+import { ipRuleApi } from '../services/api'
+import { IpRuleAction, IpRuleInfo } from '../types'
+import { errorMessage } from '../utils/errors'
+import './IpRules.css'
+
+export default function IpRules() {
+  const [rules, setRules] = useState<IpRuleInfo[]>([])
+  const [loading, setLoading] = useState(true)
+  const [cidr, setCidr] = useState('')
+  const [action, setAction] = useState<IpRuleAction>('allow')
+  const [note, setNote] = useState('')
+
+  useEffect(() => {
+    loadRules()
+  }, [])
+
+  const loadRules = async () => {
+    try {
+      setRules(await ipRuleApi.list())
+    } catch (error) {
+      console.error('Failed to load IP rules:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleAdd = async (e: React.FormEvent) => {
+    e.preventDefault()
+    try {
+      await ipRuleApi.create({ cidr: cidr.trim(), action, note: note.trim() || undefined })
+      setCidr('')
+      setNote('')
+      await loadRules()
+    } catch (error) {
+      alert(`Failed to add rule: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRemove = async (rule: IpRuleInfo) => {
+    if (rule.id == null || !window.confirm(`Remove the ${rule.action} rule for ${rule.cidr}?`)) return
+    try {
+      await ipRuleApi.remove(rule.id)
+      await loadRules()
+    } catch (error) {
+      alert(`Failed to remove rule: ${errorMessage(error)}`)
+    }
+  }
+
+  if (loading) {
+    return <div className="loading">Loading network rules...</div>
+  }
+
+  const allowlistActive = rules.some((rule) => rule.action === 'allow')
+
+  return (
+    <div className="ip-rules">
+      <h1>🛡️ Network Access</h1>
+      <p className="ip-rules-intro">
+        Denied addresses are always refused. Once any allow rule exists, only addresses inside an allowed range can
+        sign in or use the API.
+      </p>
+      <p className={`ip-rules-mode ${allowlistActive ? 'restricted' : ''}`}>
+        {allowlistActive ? '🔒 Restricted to the allowed ranges below' : '🌐 Open to all addresses not denied'}
+      </p>
+
+      <form onSubmit={handleAdd} className="ip-rule-form">
+        <select value={action} onChange={(e) => setAction(e.target.value as IpRuleAction)}>
+          <option value="allow">Allow</option>
+          <option value="deny">Deny</option>
+        </select>
+        <input
+          type="text"
+          value={cidr}
+          onChange={(e) => setCidr(e.target.value)}
+          placeholder="Address or range, e.g. 10.20.0.0/16"
+          required
+        />
+        <input type="text" value={note} onChange={(e) => setNote(e.target.value)} placeholder="Note (optional)" />
+        <button type="submit">➕ Add Rule</button>
+      </form>
+
+      {rules.length === 0 ? (
+        <p>No rules; every address is allowed</p>
+      ) : (
+        <table className="ip-rules-table">
+          <thead>
+            <tr>
+              <th>Range</th>
+              <th>Action</th>
+              <th>Note</th>
+              <th>Added</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {rules.map((rule) => (
+              <tr key={`${rule.source}-${rule.id ?? rule.cidr}-${rule.action}`}>
+                <td>
+                  <code>{rule.cidr}</code>
+                </td>
+                <td className={`action-${rule.action}`}>{rule.action === 'allow' ? 'Allow' : 'Deny'}</td>
+                <td>{rule.note || '—'}</td>
+                <td>
+                  {rule.source === 'config'
+                    ? 'Server configuration'
+                    : `${rule.created_at ? new Date(rule.created_at).toLocaleString() : ''} by ${rule.created_by}`}
+                </td>
+                <td>
+                  {rule.source === 'admin' && (
+                    <button className="remove-button" onClick={() => handleRemove(rule)}>
+                      Remove
+                    </button>
+                  )}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  )
+}
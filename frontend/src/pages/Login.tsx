@@ -1,8 +1,9 @@
-import Cookies  from 'js-cookie';
-import { useState } from 'react'
-import { useNavigate } from 'react-router-dom'
+import { useEffect, useState } from 'react'
+import { Link, Navigate, useNavigate, useSearchParams } from 'react-router-dom'
 import { useAuth } from '../contexts/AuthContext'
-import { api } from '../services/api'
+import { authApi } from '../services/api'
+import { isDemoMode } from '../services/mockApi'
+import { OAuthProvider } from '../types'
 import './Login.css'
 
 
@@ -11,40 +12,42 @@ export default function Login() {
   const [password, setPassword] = useState('')
   const [error, setError] = useState('')
   const [loading, setLoading] = useState(false)
-  const { login } = useAuth()
+  const { login, isAuthenticated } = useAuth()
   const navigate = useNavigate()
-  
-  const handleLogin = () => {
-    // Saves Cookies (Hopefully)
-    Cookies.set(username, username, { expires: 7  });
-    console.log(username); // Shows user
-  };
+  const [searchParams] = useSearchParams()
+  const sessionExpired = searchParams.get('expired') === '1'
+  const oauthError = searchParams.get('oauth_error')
+  const [providers, setProviders] = useState<OAuthProvider[]>([])
+  const [setupRequired, setSetupRequired] = useState(false)
+
+  useEffect(() => {
+    authApi
+      .oauthProviders()
+      .then(setProviders)
+      .catch(() => setProviders([]))
+    authApi
+      .bootstrapStatus()
+      .then((status) => setSetupRequired(status.required))
+      .catch(() => setSetupRequired(false))
+  }, [])
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
     setError('')
     setLoading(true)
 
-
-
-    
     try {
-      // First, try to authenticate with the backend
-      // Since we don't have a proper auth endpoint yet, we'll check credentials directly
-      // This is a temporary solution - in production, use proper JWT/auth tokens
-      const response = await api.post('/auth/login', { username, password })
-      
-      if (response.data) {
-        await login(username, password)
-        navigate('/')
-      } else {
-        setError('Invalid username or password')
-      }
+      await login(username, password)
+      navigate('/')
     } catch (err: any) {
-      // If auth endpoint fails, show error - never bypass password verification
-      if (err.response?.status === 401) {
+      // Never bypass password verification, just report why it failed
+      if (err.status === 401) {
         setError('Invalid username or password')
-      } else if (err.response?.status === 404) {
+      } else if (err.status === 423) {
+        setError('This account is temporarily locked after too many failed attempts. Try again later or ask an administrator to unlock it.')
+      } else if (err.status === 429) {
+        setError('Too many sign-in attempts. Please wait a few minutes and try again.')
+      } else if (err.status === 404) {
         setError('Authentication service unavailable. Please contact administrator.')
       } else {
         setError('Login failed. Please check your credentials and try again.')
@@ -54,11 +57,26 @@ export default function Login() {
     }
   }
 
+  // Already signed in (e.g. restored session), skip the form
+  if (isAuthenticated) {
+    return <Navigate to="/" replace />
+  }
+
   return (
     <div className="login-container">
       <div className="login-box">
         <h1>🏺 ArtiQuest</h1>
         <h2>Login</h2>
+        {isDemoMode && (
+          <div className="demo-notice">
+            Demo mode: sign in as <strong>admin/admin</strong>, <strong>field/field</strong> or <strong>onsite/onsite</strong>
+          </div>
+        )}
+        {setupRequired && (
+          <div className="demo-notice">
+            No administrator has been set up yet. <Link to="/setup">Create the admin account</Link> to get started.
+          </div>
+        )}
         <form onSubmit={handleSubmit}>
           <div className="form-group">
             <label htmlFor="username">Username</label>
@@ -81,11 +99,36 @@ export default function Login() {
               required
             />
           </div>
+          {oauthError && !error && <div className="error-message">{oauthError}</div>}
+          {sessionExpired && !error && (
+            <div className="error-message">Your session has expired. Please sign in again.</div>
+          )}
           {error && <div className="error-message">{error}</div>}
-          <button type="submit" disabled={loading} className="login-button" onClick={handleLogin}>
+          <button type="submit" disabled={loading} className="login-button">
             {loading ? 'Logging in...' : 'Login'}
           </button>
         </form>
+        {providers.length > 0 && (
+          <div className="oauth-providers">
+            <div className="oauth-divider">or</div>
+            {providers.map((provider) => (
+              <button
+                key={provider.id}
+                type="button"
+                className="oauth-button"
+                onClick={() => authApi.startOAuth(provider.id)}
+              >
+                Sign in with {provider.name}
+              </button>
+            ))}
+          </div>
+        )}
+        <Link to="/forgot-password" className="login-link">
+          Forgot your password?
+        </Link>
+        <Link to="/register" className="login-link">
+          Have an invite? Create an account
+        </Link>
       </div>
     </div>
   )
@@ -0,0 +1,152 @@
+import { useEffect, useState } from 'react'
+import { moderationApi } from '../services/api'
+import { ArtifactFlag, ModerationQueueItem } from '../types'
+import CachedImage from '../components/CachedImage'
+import { errorMessage } from '../utils/errors'
+import './ApiKeys.css'
+import './Trash.css'
+import './Moderation.css'
+
+const PAGE_SIZE = 50
+
+// Curators work through flagged artifacts here; everything done is written to the audit log
+export default function Moderation() {
+  const [items, setItems] = useState<ModerationQueueItem[]>([])
+  const [total, setTotal] = useState(0)
+  const [loading, setLoading] = useState(true)
+  const [busyId, setBusyId] = useState<number | null>(null)
+
+  useEffect(() => {
+    loadQueue()
+  }, [])
+
+  const loadQueue = async () => {
+    try {
+      const page = await moderationApi.queue({ limit: PAGE_SIZE })
+      setItems(page.items)
+      setTotal(page.total)
+    } catch (error) {
+      console.error('Failed to load the moderation queue:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const act = async (item: ModerationQueueItem, action: () => Promise<unknown>, failure: string) => {
+    setBusyId(item.id)
+    try {
+      await action()
+      await loadQueue()
+    } catch (error) {
+      alert(`${failure}: ${errorMessage(error)}`)
+    } finally {
+      setBusyId(null)
+    }
+  }
+
+  const handleResolve = (item: ModerationQueueItem, flag: ArtifactFlag) => {
+    const resolution = window.prompt(`What was done about "${flag.reason}"?`)?.trim()
+    if (!resolution) return
+    act(item, () => moderationApi.resolve(flag.id, resolution), 'Failed to resolve the flag')
+  }
+
+  const handleOverride = (item: ModerationQueueItem) => {
+    const name = window.prompt(`Identify "${item.name}" as:`, item.name)?.trim()
+    if (!name) return
+    const reason = window.prompt('Why? (for the audit log)')?.trim()
+    if (!reason) return
+    act(item, () => moderationApi.overrideIdentification(item.id, { name, reason }), 'Failed to change the identification')
+  }
+
+  const handleLock = (item: ModerationQueueItem) => {
+    if (item.locked_by) {
+      if (!window.confirm(`Unlock "${item.name}" so it can be edited again?`)) return
+      act(item, () => moderationApi.unlock(item.id), 'Failed to unlock')
+      return
+    }
+    const reason = window.prompt(`Lock "${item.name}" against edits because:`)?.trim()
+    if (!reason) return
+    act(item, () => moderationApi.lock(item.id, reason), 'Failed to lock')
+  }
+
+  const formatDate = (value?: string | null) => (value ? new Date(value).toLocaleString() : '—')
+
+  if (loading) {
+    return <div className="loading">Loading the moderation queue...</div>
+  }
+
+  return (
+    <div className="api-keys">
+      <h1>🚩 Moderation</h1>
+      <p className="api-keys-intro">
+        Artifacts flagged for review, the one waiting longest first. Correct an identification, lock an
+        artifact against edits, and resolve each flag once it is dealt with. Flag artifacts from their
+        details in the gallery.
+      </p>
+
+      {items.length === 0 ? (
+        <p>Nothing is waiting for review</p>
+      ) : (
+        <>
+          {total > items.length && (
+            <p className="trash-count">
+              Showing the {items.length} longest waiting of {total}
+            </p>
+          )}
+          <table className="api-keys-table">
+            <thead>
+              <tr>
+                <th></th>
+                <th>Artifact</th>
+                <th>Flags</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {items.map((item) => (
+                <tr key={item.id}>
+                  <td>
+                    {item.thumbnail_url && (
+                      <CachedImage className="trash-thumbnail" artifactId={item.id} path={item.thumbnail_url} alt="" />
+                    )}
+                  </td>
+                  <td>
+                    {item.name}
+                    {item.owner_id && <div className="trash-owner">{item.owner_id}</div>}
+                    {item.locked_by && <div className="moderation-locked">🔒 Locked by {item.locked_by}</div>}
+                    {item.identification_overridden_by && (
+                      <div className="trash-owner">Identified by {item.identification_overridden_by}</div>
+                    )}
+                  </td>
+                  <td>
+                    <ul className="moderation-flags">
+                      {item.open_flags.map((flag) => (
+                        <li key={flag.id}>
+                          <span className="moderation-reason">{flag.reason}</span>
+                          <div className="trash-owner">
+                            {flag.flagged_by}, {formatDate(flag.created_at)}
+                          </div>
+                          <button onClick={() => handleResolve(item, flag)} disabled={busyId !== null}>
+                            Resolve
+                          </button>
+                        </li>
+                      ))}
+                    </ul>
+                  </td>
+                  <td className="trash-actions">
+                    <button onClick={() => handleOverride(item)} disabled={busyId !== null}>
+                      Re-identify
+                    </button>
+                    <button onClick={() => handleLock(item)} disabled={busyId !== null}>
+                      {item.locked_by ? 'Unlock' : 'Lock'}
+                    </button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </>
+      )}
+    </div>
+  )
+}
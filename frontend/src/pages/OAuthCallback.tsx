@@ -0,0 +1,46 @@
+import { useEffect, useRef } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../contexts/AuthContext'
+import './Login.css'
+
+/**
+ * Landing page after an OAuth sign-in. The backend puts the session tokens
+ * in the URL fragment; store them, load the profile, and move on.
+ */
+export default function OAuthCallback() {
+  const { loginWithTokens } = useAuth()
+  const navigate = useNavigate()
+  const handled = useRef(false)
+
+  useEffect(() => {
+    // StrictMode runs effects twice in development; the refresh token is single-use
+    if (handled.current) return
+    handled.current = true
+
+    const params = new URLSearchParams(window.location.hash.slice(1))
+    const accessToken = params.get('access_token')
+    const refreshToken = params.get('refresh_token')
+    // Don't leave tokens in the address bar or history
+    window.history.replaceState(null, '', window.location.pathname)
+
+    if (!accessToken) {
+      navigate('/login?oauth_error=' + encodeURIComponent('Sign-in did not complete'), { replace: true })
+      return
+    }
+
+    loginWithTokens({ access_token: accessToken, refresh_token: refreshToken ?? undefined })
+      .then(() => navigate('/', { replace: true }))
+      .catch(() =>
+        navigate('/login?oauth_error=' + encodeURIComponent('Could not load your account'), { replace: true })
+      )
+  }, [])
+
+  return (
+    <div className="login-container">
+      <div className="login-box">
+        <h1>🏺 ArtiQuest</h1>
+        <h2>Signing you in...</h2>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,275 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from '../contexts/AuthContext'
+import { orgApi } from '../services/api'
+import { OrganizationInfo, OrgMember, OrgRank } from '../types'
+import { errorMessage } from '../utils/errors'
+import './Organizations.css'
+
+const RANK_LABELS: Record<OrgRank, string> = {
+  owner: 'Owner',
+  manager: 'Manager',
+  member: 'Member',
+}
+
+export default function Organizations() {
+  const { user } = useAuth()
+  const isAdmin = user?.role === 'admin'
+  const [orgs, setOrgs] = useState<OrganizationInfo[]>([])
+  const [selected, setSelected] = useState<OrganizationInfo | null>(null)
+  const [members, setMembers] = useState<OrgMember[]>([])
+  const [loading, setLoading] = useState(true)
+  const [newOrg, setNewOrg] = useState({ slug: '', name: '', owner: '' })
+  const [newMember, setNewMember] = useState<{ username: string; rank: OrgRank }>({ username: '', rank: 'member' })
+
+  useEffect(() => {
+    loadOrgs()
+  }, [])
+
+  useEffect(() => {
+    if (selected) loadMembers(selected.slug)
+  }, [selected?.slug])
+
+  const loadOrgs = async () => {
+    try {
+      const data = await orgApi.list()
+      setOrgs(data)
+      setSelected((current) => data.find((org) => org.slug === current?.slug) ?? data[0] ?? null)
+    } catch (error) {
+      console.error('Failed to load organizations:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const loadMembers = async (slug: string) => {
+    try {
+      setMembers(await orgApi.members(slug))
+    } catch (error) {
+      console.error('Failed to load members:', error)
+      setMembers([])
+    }
+  }
+
+  // Admins manage every organization as if they owned it
+  const rank: OrgRank | null = isAdmin ? 'owner' : selected?.rank ?? null
+  const canManage = rank === 'owner' || rank === 'manager'
+
+  const handleCreate = async (e: React.FormEvent) => {
+    e.preventDefault()
+    try {
+      const created = await orgApi.create({
+        slug: newOrg.slug.trim(),
+        name: newOrg.name.trim(),
+        owner: newOrg.owner.trim() || undefined,
+      })
+      setNewOrg({ slug: '', name: '', owner: '' })
+      setSelected(created)
+      await loadOrgs()
+    } catch (error) {
+      alert(`Failed to create organization: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleDelete = async () => {
+    if (!selected || !window.confirm(`Delete ${selected.name}? Members keep their accounts.`)) return
+    try {
+      await orgApi.remove(selected.slug)
+      setSelected(null)
+      await loadOrgs()
+    } catch (error) {
+      alert(`Failed to delete organization: ${errorMessage(error)}`)
+    }
+  }
+
+  const refresh = async () => {
+    if (selected) await loadMembers(selected.slug)
+    await loadOrgs()
+  }
+
+  const handleAddMember = async (e: React.FormEvent) => {
+    e.preventDefault()
+    if (!selected) return
+    try {
+      await orgApi.addMember(selected.slug, { username: newMember.username.trim(), rank: newMember.rank })
+      setNewMember({ username: '', rank: 'member' })
+      await refresh()
+    } catch (error) {
+      alert(`Failed to add member: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRankChange = async (member: OrgMember, newRank: OrgRank) => {
+    if (!selected) return
+    try {
+      await orgApi.setRank(selected.slug, member.username, newRank)
+      await refresh()
+    } catch (error) {
+      alert(`Failed to change rank: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRemove = async (member: OrgMember) => {
+    if (!selected) return
+    const leaving = member.username === user?.username
+    const question = leaving ? `Leave ${selected.name}?` : `Remove ${member.username} from ${selected.name}?`
+    if (!window.confirm(question)) return
+    try {
+      await orgApi.removeMember(selected.slug, member.username)
+      await refresh()
+    } catch (error) {
+      alert(`Failed to remove member: ${errorMessage(error)}`)
+    }
+  }
+
+  if (loading) {
+    return <div className="loading">Loading organizations...</div>
+  }
+
+  return (
+    <div className="organizations">
+      <h1>🏢 Organizations</h1>
+      <p className="organizations-intro">
+        Each team manages its own members. Owners set ranks; managers can add and remove members.
+      </p>
+
+      {isAdmin && (
+        <form onSubmit={handleCreate} className="org-form">
+          <input
+            type="text"
+            value={newOrg.name}
+            onChange={(e) => setNewOrg({ ...newOrg, name: e.target.value })}
+            placeholder="Name, e.g. North Dig Team"
+            required
+          />
+          <input
+            type="text"
+            value={newOrg.slug}
+            onChange={(e) => setNewOrg({ ...newOrg, slug: e.target.value.toLowerCase() })}
+            placeholder="Short id, e.g. north-dig"
+            required
+          />
+          <input
+            type="text"
+            value={newOrg.owner}
+            onChange={(e) => setNewOrg({ ...newOrg, owner: e.target.value })}
+            placeholder="Owner username (default: you)"
+          />
+          <button type="submit">➕ Create</button>
+        </form>
+      )}
+
+      {orgs.length === 0 ? (
+        <p>You aren't a member of any organization yet</p>
+      ) : (
+        <div className="org-layout">
+          <ul className="org-list">
+            {orgs.map((org) => (
+              <li key={org.slug}>
+                <button
+                  className={org.slug === selected?.slug ? 'selected' : ''}
+                  onClick={() => setSelected(org)}
+                >
+                  <strong>{org.name}</strong>
+                  <span>
+                    {org.member_count} member{org.member_count === 1 ? '' : 's'}
+                    {org.rank && ` · ${RANK_LABELS[org.rank]}`}
+                  </span>
+                </button>
+              </li>
+            ))}
+          </ul>
+
+          {selected && (
+            <div className="org-detail">
+              <div className="org-detail-header">
+                <h2>
+                  {selected.name} <code>{selected.slug}</code>
+                </h2>
+                {isAdmin && (
+                  <button className="remove-button" onClick={handleDelete}>
+                    🗑️ Delete
+                  </button>
+                )}
+              </div>
+
+              {canManage && (
+                <form onSubmit={handleAddMember} className="org-form">
+                  <input
+                    type="text"
+                    value={newMember.username}
+                    onChange={(e) => setNewMember({ ...newMember, username: e.target.value })}
+                    placeholder="Username"
+                    required
+                  />
+                  {rank === 'owner' && (
+                    <select
+                      value={newMember.rank}
+                      onChange={(e) => setNewMember({ ...newMember, rank: e.target.value as OrgRank })}
+                    >
+                      {Object.entries(RANK_LABELS).map(([value, label]) => (
+                        <option key={value} value={value}>
+                          {label}
+                        </option>
+                      ))}
+                    </select>
+                  )}
+                  <button type="submit">➕ Add Member</button>
+                </form>
+              )}
+
+              <table className="org-members-table">
+                <thead>
+                  <tr>
+                    <th>Username</th>
+                    <th>Name</th>
+                    <th>Email</th>
+                    <th>Rank</th>
+                    <th></th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {members.map((member) => {
+                    const removable =
+                      member.username === user?.username ||
+                      rank === 'owner' ||
+                      (rank === 'manager' && member.rank === 'member')
+                    return (
+                      <tr key={member.username}>
+                        <td>{member.username}</td>
+                        <td>{member.name}</td>
+                        <td>{member.email}</td>
+                        <td>
+                          {rank === 'owner' ? (
+                            <select
+                              value={member.rank}
+                              onChange={(e) => handleRankChange(member, e.target.value as OrgRank)}
+                            >
+                              {Object.entries(RANK_LABELS).map(([value, label]) => (
+                                <option key={value} value={value}>
+                                  {label}
+                                </option>
+                              ))}
+                            </select>
+                          ) : (
+                            RANK_LABELS[member.rank]
+                          )}
+                        </td>
+                        <td>
+                          {removable && (
+                            <button className="remove-button" onClick={() => handleRemove(member)}>
+                              {member.username === user?.username ? 'Leave' : 'Remove'}
+                            </button>
+                          )}
+                        </td>
+                      </tr>
+                    )
+                  })}
+                </tbody>
+              </table>
+            </div>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,242 @@
+import { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import { useAuth } from '../contexts/AuthContext'
+import { userApi } from '../services/api'
+import { AnalysisUsageInfo, LoginRecord, ProfileUpdate } from '../types'
+import { errorMessage } from '../utils/errors'
+import './Profile.css'
+
+export default function Profile() {
+  const { user, updateUser, logout } = useAuth()
+  const navigate = useNavigate()
+  const [name, setName] = useState(user?.name ?? '')
+  const [email, setEmail] = useState(user?.email ?? '')
+  const [loading, setLoading] = useState(false)
+  const [success, setSuccess] = useState(false)
+  const [error, setError] = useState('')
+  const [exporting, setExporting] = useState(false)
+  const [deletePassword, setDeletePassword] = useState('')
+  const [deleting, setDeleting] = useState(false)
+  const [deleteError, setDeleteError] = useState('')
+  const [logins, setLogins] = useState<LoginRecord[]>([])
+  const [usage, setUsage] = useState<AnalysisUsageInfo | null>(null)
+
+  useEffect(() => {
+    userApi
+      .getLoginHistory()
+      .then(setLogins)
+      .catch((err) => console.warn('Could not load sign-in history:', err))
+    userApi
+      .getUsage()
+      .then(setUsage)
+      .catch((err) => console.warn('Could not load analysis usage:', err))
+  }, [])
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setError('')
+
+    if (!name.trim()) {
+      setError('Name is required')
+      return
+    }
+
+    // Only send what actually changed
+    const changes: ProfileUpdate = {}
+    if (name.trim() !== user?.name) changes.name = name.trim()
+    if (email.trim() !== user?.email) changes.email = email.trim()
+    if (Object.keys(changes).length === 0) return
+
+    setLoading(true)
+    try {
+      const profile = await userApi.updateProfile(changes)
+      updateUser(profile)
+      setName(profile.name)
+      setEmail(profile.email)
+      setSuccess(true)
+      setTimeout(() => setSuccess(false), 3000)
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleExport = async () => {
+    setExporting(true)
+    try {
+      const data = await userApi.exportData()
+      const url = URL.createObjectURL(new Blob([JSON.stringify(data, null, 2)], { type: 'application/json' }))
+      const link = document.createElement('a')
+      link.href = url
+      link.download = `${user?.username ?? 'account'}-data.json`
+      link.click()
+      URL.revokeObjectURL(url)
+    } catch (err) {
+      alert(`Could not export your data: ${errorMessage(err)}`)
+    } finally {
+      setExporting(false)
+    }
+  }
+
+  const handleDelete = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setDeleteError('')
+    if (!window.confirm('Permanently delete your account? This cannot be undone.')) return
+
+    setDeleting(true)
+    try {
+      await userApi.deleteAccount(deletePassword)
+      logout()
+      navigate('/login')
+    } catch (err) {
+      setDeleteError(errorMessage(err))
+      setDeleting(false)
+    }
+  }
+
+  return (
+    <div className="profile">
+      <h1>👤 My Profile</h1>
+
+      <form onSubmit={handleSubmit} className="profile-form">
+        <div className="form-group">
+          <label htmlFor="profile-username">Username</label>
+          <input id="profile-username" type="text" value={user?.username ?? ''} disabled />
+        </div>
+
+        <div className="form-group">
+          <label htmlFor="profile-name">Name</label>
+          <input
+            id="profile-name"
+            type="text"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            required
+            maxLength={100}
+          />
+        </div>
+
+        <div className="form-group">
+          <label htmlFor="profile-email">Email</label>
+          <input
+            id="profile-email"
+            type="email"
+            value={email}
+            onChange={(e) => setEmail(e.target.value)}
+            required
+          />
+        </div>
+
+        {error && <div className="error-message">{error}</div>}
+        {success && <div className="success-message">✅ Profile updated!</div>}
+
+        <button type="submit" disabled={loading} className="submit-button">
+          {loading ? 'Saving...' : 'Save Changes'}
+        </button>
+      </form>
+
+      <section className="profile-section">
+        <h2>🕒 Recent Sign-ins</h2>
+        <p>If you don't recognise a sign-in, change your password.</p>
+        {logins.length === 0 ? (
+          <p>No sign-ins recorded yet</p>
+        ) : (
+          <table className="login-history-table">
+            <thead>
+              <tr>
+                <th>When</th>
+                <th>How</th>
+                <th>IP address</th>
+                <th>Device</th>
+              </tr>
+            </thead>
+            <tbody>
+              {logins.map((login) => (
+                <tr key={`${login.logged_in_at}-${login.ip_address}`}>
+                  <td>{new Date(login.logged_in_at).toLocaleString()}</td>
+                  <td>{login.method === 'password' ? 'Password' : login.method}</td>
+                  <td>{login.ip_address || '—'}</td>
+                  <td className="user-agent" title={login.user_agent ?? ''}>
+                    {login.user_agent || '—'}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        )}
+      </section>
+
+      {usage && (
+        <section className="profile-section">
+          <h2>📊 Analyses This Month</h2>
+          {usage.quota != null ? (
+            <>
+              <p>
+                {usage.used} of {usage.quota} units used ({usage.remaining} left); resets{' '}
+                {new Date(usage.resets_at).toLocaleDateString()}. Slower tiers cost more:{' '}
+                {Object.entries(usage.weights)
+                  .map(([tier, units]) => `${tier} ${units}`)
+                  .join(', ')}
+                .
+              </p>
+              <progress className="usage-bar" value={usage.used} max={usage.quota} />
+            </>
+          ) : (
+            <p>{usage.analyses} analyses so far; your account has no monthly limit.</p>
+          )}
+          {usage.by_tier.length > 0 && (
+            <table className="login-history-table">
+              <thead>
+                <tr>
+                  <th>Tier</th>
+                  <th>Analyses</th>
+                  <th>Units</th>
+                </tr>
+              </thead>
+              <tbody>
+                {usage.by_tier.map((row) => (
+                  <tr key={row.tier}>
+                    <td>{row.tier}</td>
+                    <td>{row.analyses}</td>
+                    <td>{row.units}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          )}
+        </section>
+      )}
+
+      <section className="profile-section">
+        <h2>🗂️ Your Data</h2>
+        <p>Download a copy of everything ArtiQuest stores about your account.</p>
+        <button type="button" onClick={handleExport} disabled={exporting} className="secondary-button">
+          {exporting ? 'Preparing...' : '⬇️ Download My Data'}
+        </button>
+      </section>
+
+      <form onSubmit={handleDelete} className="profile-section danger-zone">
+        <h2>⚠️ Delete Account</h2>
+        <p>
+          Removes your account, sign-ins and API keys. Artifacts you uploaded stay in the archive without your
+          name.
+        </p>
+        <div className="form-group">
+          <label htmlFor="delete-password">Current password</label>
+          <input
+            id="delete-password"
+            type="password"
+            value={deletePassword}
+            onChange={(e) => setDeletePassword(e.target.value)}
+            required
+          />
+        </div>
+        {deleteError && <div className="error-message">{deleteError}</div>}
+        <button type="submit" disabled={deleting} className="danger-button">
+          {deleting ? 'Deleting...' : 'Delete My Account'}
+        </button>
+      </form>
+    </div>
+  )
+}
@@ -0,0 +1,135 @@
+import { useEffect, useState } from 'react'
+import { Link, useNavigate, useSearchParams } from 'react-router-dom'
+import PasswordStrength, { usePasswordPolicy } from '../components/PasswordStrength'
+import { authApi } from '../services/api'
+import { errorMessage } from '../utils/errors'
+import { passwordProblems } from '../utils/passwordPolicy'
+import './Login.css'
+
+// Self-service sign-up; invite links open this page with ?code=...
+export default function Register() {
+  const [searchParams] = useSearchParams()
+  const [inviteCode, setInviteCode] = useState(searchParams.get('code') || '')
+  const [inviteOnly, setInviteOnly] = useState(true)
+  const [username, setUsername] = useState('')
+  const [name, setName] = useState('')
+  const [email, setEmail] = useState('')
+  const [password, setPassword] = useState('')
+  const [confirmPassword, setConfirmPassword] = useState('')
+  const [error, setError] = useState('')
+  const [loading, setLoading] = useState(false)
+  const policy = usePasswordPolicy()
+  const navigate = useNavigate()
+
+  useEffect(() => {
+    authApi
+      .registrationInfo()
+      .then((info) => setInviteOnly(info.invite_only))
+      .catch(() => setInviteOnly(true))
+  }, [])
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setError('')
+
+    if (password !== confirmPassword) {
+      setError('Passwords do not match')
+      return
+    }
+
+    const problems = passwordProblems(password, policy)
+    if (problems.length > 0) {
+      setError(`Password needs ${problems.join(', ')}`)
+      return
+    }
+
+    setLoading(true)
+    try {
+      await authApi.register({
+        username: username.trim(),
+        name: name.trim(),
+        email: email.trim(),
+        password,
+        invite_code: inviteCode.trim() || undefined,
+      })
+      alert('Your account has been created. Please sign in.')
+      navigate('/login')
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  return (
+    <div className="login-container">
+      <div className="login-box">
+        <h1>🏺 ArtiQuest</h1>
+        <h2>Create an Account</h2>
+        <form onSubmit={handleSubmit}>
+          {(inviteOnly || inviteCode) && (
+            <div className="form-group">
+              <label htmlFor="register-invite">Invite code</label>
+              <input
+                id="register-invite"
+                type="text"
+                value={inviteCode}
+                onChange={(e) => setInviteCode(e.target.value)}
+                required={inviteOnly}
+              />
+            </div>
+          )}
+          <div className="form-group">
+            <label htmlFor="register-username">Username</label>
+            <input
+              id="register-username"
+              type="text"
+              value={username}
+              onChange={(e) => setUsername(e.target.value)}
+              required
+              autoFocus
+            />
+          </div>
+          <div className="form-group">
+            <label htmlFor="register-name">Name</label>
+            <input id="register-name" type="text" value={name} onChange={(e) => setName(e.target.value)} required />
+          </div>
+          <div className="form-group">
+            <label htmlFor="register-email">Email</label>
+            <input id="register-email" type="email" value={email} onChange={(e) => setEmail(e.target.value)} required />
+          </div>
+          <div className="form-group">
+            <label htmlFor="register-password">Password</label>
+            <input
+              id="register-password"
+              type="password"
+              value={password}
+              onChange={(e) => setPassword(e.target.value)}
+              required
+              minLength={policy.min_length}
+            />
+            <PasswordStrength password={password} policy={policy} />
+          </div>
+          <div className="form-group">
+            <label htmlFor="register-confirm-password">Confirm password</label>
+            <input
+              id="register-confirm-password"
+              type="password"
+              value={confirmPassword}
+              onChange={(e) => setConfirmPassword(e.target.value)}
+              required
+              minLength={policy.min_length}
+            />
+          </div>
+          {error && <div className="error-message">{error}</div>}
+          <button type="submit" disabled={loading} className="login-button">
+            {loading ? 'Creating...' : 'Create Account'}
+          </button>
+        </form>
+        <Link to="/login" className="login-link">
+          ← Back to login
+        </Link>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,91 @@
+import { useState } from 'react'
+import { Link, useNavigate, useSearchParams } from 'react-router-dom'
+import PasswordStrength, { usePasswordPolicy } from '../components/PasswordStrength'
+import { authApi } from '../services/api'
+import { errorMessage } from '../utils/errors'
+import { passwordProblems } from '../utils/passwordPolicy'
+import './Login.css'
+
+export default function ResetPassword() {
+  const [searchParams] = useSearchParams()
+  const token = searchParams.get('token') || ''
+  const [newPassword, setNewPassword] = useState('')
+  const [confirmPassword, setConfirmPassword] = useState('')
+  const [error, setError] = useState('')
+  const [loading, setLoading] = useState(false)
+  const policy = usePasswordPolicy()
+  const navigate = useNavigate()
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setError('')
+
+    if (newPassword !== confirmPassword) {
+      setError('Passwords do not match')
+      return
+    }
+
+    const problems = passwordProblems(newPassword, policy)
+    if (problems.length > 0) {
+      setError(`Password needs ${problems.join(', ')}`)
+      return
+    }
+
+    setLoading(true)
+    try {
+      await authApi.resetPassword(token, newPassword)
+      alert('Your password has been reset. Please sign in with the new password.')
+      navigate('/login')
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  return (
+    <div className="login-container">
+      <div className="login-box">
+        <h1>🏺 ArtiQuest</h1>
+        <h2>Choose a New Password</h2>
+        {!token ? (
+          <div className="error-message">This reset link is incomplete. Please request a new one.</div>
+        ) : (
+          <form onSubmit={handleSubmit}>
+            <div className="form-group">
+              <label htmlFor="new-password">New Password</label>
+              <input
+                id="new-password"
+                type="password"
+                value={newPassword}
+                onChange={(e) => setNewPassword(e.target.value)}
+                required
+                minLength={policy.min_length}
+                autoFocus
+              />
+              <PasswordStrength password={newPassword} policy={policy} />
+            </div>
+            <div className="form-group">
+              <label htmlFor="confirm-password">Confirm Password</label>
+              <input
+                id="confirm-password"
+                type="password"
+                value={confirmPassword}
+                onChange={(e) => setConfirmPassword(e.target.value)}
+                required
+                minLength={policy.min_length}
+              />
+            </div>
+            {error && <div className="error-message">{error}</div>}
+            <button type="submit" disabled={loading} className="login-button">
+              {loading ? 'Saving...' : 'Reset Password'}
+            </button>
+          </form>
+        )}
+        <Link to={token ? '/login' : '/forgot-password'} className="login-link">
+          {token ? '← Back to login' : 'Request a new link'}
+        </Link>
+      </div>
+    </div>
+  )
+}
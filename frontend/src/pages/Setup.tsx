@@ -0,0 +1,107 @@
+import { useState } from 'react'
+import { Link, useNavigate } from 'react-router-dom'
+import PasswordStrength, { usePasswordPolicy } from '../components/PasswordStrength'
+import { authApi } from '../services/api'
+import { errorMessage } from '../utils/errors'
+import { passwordProblems } from '../utils/passwordPolicy'
+import './Login.css'
+
+// First-run setup: only works until the first admin account exists
+export default function Setup() {
+  const [username, setUsername] = useState('admin')
+  const [name, setName] = useState('')
+  const [email, setEmail] = useState('')
+  const [password, setPassword] = useState('')
+  const [confirmPassword, setConfirmPassword] = useState('')
+  const [error, setError] = useState('')
+  const [loading, setLoading] = useState(false)
+  const policy = usePasswordPolicy()
+  const navigate = useNavigate()
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    setError('')
+
+    if (password !== confirmPassword) {
+      setError('Passwords do not match')
+      return
+    }
+
+    const problems = passwordProblems(password, policy)
+    if (problems.length > 0) {
+      setError(`Password needs ${problems.join(', ')}`)
+      return
+    }
+
+    setLoading(true)
+    try {
+      await authApi.bootstrap({ username: username.trim(), name: name.trim(), email: email.trim() || undefined, password })
+      alert('Admin account created. Please sign in.')
+      navigate('/login')
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  return (
+    <div className="login-container">
+      <div className="login-box">
+        <h1>🏺 ArtiQuest</h1>
+        <h2>First-Run Setup</h2>
+        <form onSubmit={handleSubmit}>
+          <div className="form-group">
+            <label htmlFor="setup-username">Admin username</label>
+            <input
+              id="setup-username"
+              type="text"
+              value={username}
+              onChange={(e) => setUsername(e.target.value)}
+              required
+              autoFocus
+            />
+          </div>
+          <div className="form-group">
+            <label htmlFor="setup-name">Name</label>
+            <input id="setup-name" type="text" value={name} onChange={(e) => setName(e.target.value)} required />
+          </div>
+          <div className="form-group">
+            <label htmlFor="setup-email">Email</label>
+            <input id="setup-email" type="email" value={email} onChange={(e) => setEmail(e.target.value)} />
+          </div>
+          <div className="form-group">
+            <label htmlFor="setup-password">Password</label>
+            <input
+              id="setup-password"
+              type="password"
+              value={password}
+              onChange={(e) => setPassword(e.target.value)}
+              required
+              minLength={policy.min_length}
+            />
+            <PasswordStrength password={password} policy={policy} />
+          </div>
+          <div className="form-group">
+            <label htmlFor="setup-confirm-password">Confirm password</label>
+            <input
+              id="setup-confirm-password"
+              type="password"
+              value={confirmPassword}
+              onChange={(e) => setConfirmPassword(e.target.value)}
+              required
+              minLength={policy.min_length}
+            />
+          </div>
+          {error && <div className="error-message">{error}</div>}
+          <button type="submit" disabled={loading} className="login-button">
+            {loading ? 'Creating...' : 'Create Admin Account'}
+          </button>
+        </form>
+        <Link to="/login" className="login-link">
+          ← Back to login
+        </Link>
+      </div>
+    </div>
+  )
+}
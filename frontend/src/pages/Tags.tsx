@@ -0,0 +1,136 @@
+import { useEffect, useState } from 'react'
+import { tagApi } from '../services/api'
+import { TagCount } from '../types'
+import { errorMessage } from '../utils/errors'
+import './ApiKeys.css'
+import './Tags.css'
+
+// Admins tidy the vocabulary here: fix a misspelt tag, or fold variants like "Roman" and "roman" into one
+export default function Tags() {
+  const [tags, setTags] = useState<TagCount[]>([])
+  const [loading, setLoading] = useState(true)
+  const [filter, setFilter] = useState('')
+  const [selected, setSelected] = useState<string[]>([])
+  const [target, setTarget] = useState('')
+  const [busy, setBusy] = useState(false)
+
+  useEffect(() => {
+    loadTags()
+  }, [])
+
+  const loadTags = async () => {
+    try {
+      setTags(await tagApi.list(undefined, 1000))
+    } catch (error) {
+      console.error('Failed to load tags:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const toggle = (tag: string) => {
+    setSelected((current) => (current.includes(tag) ? current.filter((t) => t !== tag) : [...current, tag]))
+  }
+
+  const handleRename = async (tag: string) => {
+    const newName = window.prompt(`Rename "${tag}" on every artifact to:`, tag)?.trim()
+    if (!newName || newName === tag) return
+    setBusy(true)
+    try {
+      const result = await tagApi.rename(tag, newName)
+      alert(`Renamed on ${result.artifacts_changed} artifact(s)`)
+      setSelected((current) => current.filter((t) => t !== tag))
+      await loadTags()
+    } catch (error) {
+      alert(`Failed to rename "${tag}": ${errorMessage(error)}`)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  const handleMerge = async (e: React.FormEvent) => {
+    e.preventDefault()
+    const into = target.trim()
+    if (!window.confirm(`Replace ${selected.map((t) => `"${t}"`).join(', ')} with "${into}" on every artifact?`)) return
+    setBusy(true)
+    try {
+      const result = await tagApi.merge(selected, into)
+      alert(`Merged on ${result.artifacts_changed} artifact(s)`)
+      setSelected([])
+      setTarget('')
+      await loadTags()
+    } catch (error) {
+      alert(`Failed to merge tags: ${errorMessage(error)}`)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  if (loading) {
+    return <div className="loading">Loading tags...</div>
+  }
+
+  const needle = filter.trim().toLowerCase()
+  const shown = needle ? tags.filter((t) => t.tag.toLowerCase().includes(needle)) : tags
+
+  return (
+    <div className="api-keys">
+      <h1>🏷️ Tags</h1>
+      <p className="api-keys-intro">
+        Every tag in the archive and how many artifacts carry it. Renaming or merging changes the tag on all
+        of them, including artifacts in the trash.
+      </p>
+
+      <form onSubmit={handleMerge} className="api-key-form">
+        <input type="search" value={filter} onChange={(e) => setFilter(e.target.value)} placeholder="Find a tag" />
+        <input
+          type="text"
+          value={target}
+          onChange={(e) => setTarget(e.target.value)}
+          placeholder={selected.length ? `Merge ${selected.length} selected into...` : 'Select tags to merge'}
+          list="tag-vocabulary"
+          disabled={selected.length === 0}
+        />
+        <datalist id="tag-vocabulary">
+          {tags.map((t) => (
+            <option key={t.tag} value={t.tag} />
+          ))}
+        </datalist>
+        <button type="submit" disabled={busy || selected.length === 0 || !target.trim()}>
+          🔀 Merge
+        </button>
+      </form>
+
+      {shown.length === 0 ? (
+        <p>{tags.length === 0 ? 'No tags yet' : 'No tags match'}</p>
+      ) : (
+        <table className="api-keys-table">
+          <thead>
+            <tr>
+              <th></th>
+              <th>Tag</th>
+              <th>Artifacts</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {shown.map((t) => (
+              <tr key={t.tag}>
+                <td>
+                  <input type="checkbox" checked={selected.includes(t.tag)} onChange={() => toggle(t.tag)} />
+                </td>
+                <td className="tag-name">{t.tag}</td>
+                <td>{t.count}</td>
+                <td>
+                  <button className="tag-rename" onClick={() => handleRename(t.tag)} disabled={busy}>
+                    Rename
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,134 @@
+import { useEffect, useState } from 'react'
+import { useAuth } from '../contexts/AuthContext'
+import { artifactApi } from '../services/api'
+import { TrashedArtifact } from '../types'
+import CachedImage from '../components/CachedImage'
+import { canAccess } from '../utils/permissions'
+import { errorMessage } from '../utils/errors'
+import './ApiKeys.css'
+import './Trash.css'
+
+const PAGE_SIZE = 50
+
+export default function Trash() {
+  const { user } = useAuth()
+  const [artifacts, setArtifacts] = useState<TrashedArtifact[]>([])
+  const [total, setTotal] = useState(0)
+  const [loading, setLoading] = useState(true)
+  const [busyId, setBusyId] = useState<number | null>(null)
+
+  useEffect(() => {
+    loadTrash()
+  }, [])
+
+  const loadTrash = async () => {
+    try {
+      const page = await artifactApi.trash({ limit: PAGE_SIZE })
+      setArtifacts(page.artifacts)
+      setTotal(page.total)
+    } catch (error) {
+      console.error('Failed to load the trash:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const handleRestore = async (artifact: TrashedArtifact) => {
+    setBusyId(artifact.id)
+    try {
+      // Restoring counts as a change, so the next archive sync brings it back
+      await artifactApi.restore(artifact.id)
+      await loadTrash()
+    } catch (error) {
+      alert(`Failed to restore "${artifact.name}": ${errorMessage(error)}`)
+    } finally {
+      setBusyId(null)
+    }
+  }
+
+  const handlePurge = async (artifact: TrashedArtifact) => {
+    if (!window.confirm(`Permanently delete "${artifact.name}"? This cannot be undone.`)) return
+    setBusyId(artifact.id)
+    try {
+      await artifactApi.purge(artifact.id)
+      await loadTrash()
+    } catch (error) {
+      alert(`Failed to delete "${artifact.name}": ${errorMessage(error)}`)
+    } finally {
+      setBusyId(null)
+    }
+  }
+
+  const formatDate = (value?: string | null) => (value ? new Date(value).toLocaleString() : '—')
+  const seesAll = canAccess(user?.role, 'all-artifacts')
+
+  if (loading) {
+    return <div className="loading">Loading the trash...</div>
+  }
+
+  return (
+    <div className="api-keys">
+      <h1>🗑️ Trash</h1>
+      <p className="api-keys-intro">
+        Deleted artifacts wait here{seesAll ? ', everyone’s,' : ''} until they are purged, whichever device
+        deleted them. Restore one to put it back with its images, sharing and collection.
+      </p>
+
+      {artifacts.length === 0 ? (
+        <p>The trash is empty</p>
+      ) : (
+        <>
+          {total > artifacts.length && (
+            <p className="trash-count">
+              Showing the {artifacts.length} most recently deleted of {total}
+            </p>
+          )}
+          <table className="api-keys-table">
+            <thead>
+              <tr>
+                <th></th>
+                <th>Artifact</th>
+                <th>Deleted</th>
+                <th>Purged</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {artifacts.map((artifact) => (
+                <tr key={artifact.id}>
+                  <td>
+                    {artifact.thumbnail_url && (
+                      <CachedImage
+                        className="trash-thumbnail"
+                        artifactId={artifact.id}
+                        path={artifact.thumbnail_url}
+                        alt=""
+                      />
+                    )}
+                  </td>
+                  <td>
+                    {artifact.name}
+                    {seesAll && artifact.owner_id && <div className="trash-owner">{artifact.owner_id}</div>}
+                  </td>
+                  <td>
+                    {formatDate(artifact.deleted_at)}
+                    {artifact.deleted_by && <div className="trash-owner">by {artifact.deleted_by}</div>}
+                  </td>
+                  <td>{artifact.purge_at ? formatDate(artifact.purge_at) : 'Kept until emptied'}</td>
+                  <td className="trash-actions">
+                    <button onClick={() => handleRestore(artifact)} disabled={busyId !== null}>
+                      Restore
+                    </button>
+                    <button className="revoke-button" onClick={() => handlePurge(artifact)} disabled={busyId !== null}>
+                      Delete forever
+                    </button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </>
+      )}
+    </div>
+  )
+}
@@ -1,32 +1,103 @@
 import { useState, useEffect, useRef } from 'react'
+import { useLocation } from 'react-router-dom'
 import { useAuth } from '../contexts/AuthContext'
 import { artifactApi } from '../services/api'
-import { FormData, AnalysisResult } from '../types'
+import { isCancellation, useTaskRegistry } from '../services/tasks'
+import { FormData, AnalysisResult, AnalysisStage } from '../types'
+import { downscaleImage, encodeImage, EncodedImage, pickImages } from '../utils/files'
+import BatchUpload from '../components/BatchUpload'
+import { canAccess } from '../utils/permissions'
+import { errorMessage } from '../utils/errors'
+import { notifyIfHidden, requestNotificationPermission } from '../utils/notifications'
+import { analysisSizeLabel, ANALYSIS_SIZES, getSettings, updateSettings } from '../utils/settings'
 import './UploadArtifact.css'
 
+const stageLabels: Partial<Record<AnalysisStage, string>> = {
+  received: 'Waiting in queue...',
+  preprocessing: 'Preparing image...',
+  inference: 'Identifying artifact...',
+  saving: 'Saving result...',
+}
+
+// Longest side of the image preview, in pixels
+const PREVIEW_SIZE = 800
+
 export default function UploadArtifact() {
   const { user } = useAuth()
-  const [uploadMode, setUploadMode] = useState<'single' | 'batch'>('single')
+  const location = useLocation()
+  // Files dropped onto the window (components/Layout.tsx) arrive in batch mode
+  const droppedForBatch = (location.state as { mode?: string } | null)?.mode === 'batch'
+  const [uploadMode, setUploadMode] = useState<'single' | 'batch'>(droppedForBatch ? 'batch' : 'single')
+  useEffect(() => {
+    if (droppedForBatch) setUploadMode('batch')
+  }, [location.key])
   const [imageSource, setImageSource] = useState<'upload' | 'camera'>('upload')
   const [imageFile, setImageFile] = useState<File | null>(null)
   const [imagePreview, setImagePreview] = useState<string | null>(null)
   const [formData, setFormData] = useState<Partial<FormData>>({})
   const [analysisResult, setAnalysisResult] = useState<AnalysisResult | null>(null)
-  const [tier, setTier] = useState('fast')
+  const [tier, setTier] = useState(getSettings().defaultTier)
+  const [maxDimension, setMaxDimension] = useState(getSettings().analysisMaxDimension)
   const [loading, setLoading] = useState(false)
+  const [stage, setStage] = useState<AnalysisStage | null>(null)
+  // Share of the image read and encoded so far, while that's under way
+  const [encodeProgress, setEncodeProgress] = useState<number | null>(null)
+  const encoding = useRef<{ file: File; image: Promise<EncodedImage> } | null>(null)
   const [saved, setSaved] = useState(false)
   const [stream, setStream] = useState<MediaStream | null>(null)
   const videoRef = useRef<HTMLVideoElement | null>(null)
+  // Scrolled to when a result notification is clicked
+  const resultsRef = useRef<HTMLDivElement>(null)
+  // Aborted automatically when the user navigates away from this page
+  const tasks = useTaskRegistry()
+
+  // A new image makes any in-flight analysis of the old one irrelevant
+  const discardPendingAnalysis = () => {
+    if (tasks.isRunning('analysis')) {
+      tasks.cancel('analysis')
+      setLoading(false)
+    }
+    setAnalysisResult(null)
+  }
 
-  const handleImageSelect = (e: React.ChangeEvent<HTMLInputElement>) => {
-    const file = e.target.files?.[0]
+  // Encoded once per image, in a worker, for the preview, the analysis and the save
+  const encodedImage = (file: File) => {
+    if (encoding.current?.file !== file) {
+      const setProgress = (progress: number | null) => {
+        if (encoding.current?.file === file) setEncodeProgress(progress)
+      }
+      setEncodeProgress(0)
+      const image = encodeImage(file, {
+        previewSize: PREVIEW_SIZE,
+        onProgress: ({ loaded, total }) => setProgress(total ? loaded / total : 1),
+      })
+      image.then(
+        () => setProgress(null),
+        () => setProgress(null)
+      )
+      encoding.current = { file, image }
+    }
+    return encoding.current.image
+  }
+
+  const handleImageSelect = async () => {
+    let file: File | undefined
+    try {
+      file = (await pickImages())[0]
+    } catch (error) {
+      alert(`Could not open the file picker: ${errorMessage(error)}`)
+      return
+    }
     if (file) {
+      discardPendingAnalysis()
       setImageFile(file)
-      const reader = new FileReader()
-      reader.onloadend = () => {
-        setImagePreview(reader.result as string)
-      }
-      reader.readAsDataURL(file)
+      setImagePreview(null)
+      encodedImage(file)
+        .then((image) => {
+          // An empty src shows the alt text instead
+          if (encoding.current?.file === file) setImagePreview(image.preview ?? '')
+        })
+        .catch((error) => console.error('Failed to read the image:', error))
     }
   }
 
@@ -67,6 +138,7 @@ export default function UploadArtifact() {
       canvas.toBlob((blob) => {
         if (blob) {
           const file = new File([blob], 'camera-capture.jpg', { type: 'image/jpeg' })
+          discardPendingAnalysis()
           setImageFile(file)
           setImagePreview(canvas.toDataURL('image/jpeg'))
           stopCamera()
@@ -93,36 +165,49 @@ export default function UploadArtifact() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [imageSource])
 
-  const convertToBase64 = (file: File): Promise<string> => {
-    return new Promise((resolve, reject) => {
-      const reader = new FileReader()
-      reader.readAsDataURL(file)
-      reader.onload = () => resolve(reader.result as string)
-      reader.onerror = (error) => reject(error)
-    })
-  }
-
-  const handleAnalyze = async () => {
+  const handleAnalyze = async (refresh = false) => {
     if (!imageFile) return
 
+    const signal = tasks.start('analysis')
     setLoading(true)
+    setStage(null)
+    // Thorough analyses take minutes; say when one is done if the user looks elsewhere meanwhile
+    if (tier === 'thorough') requestNotificationPermission()
     try {
-      const imageData = await convertToBase64(imageFile)
-      const result = await artifactApi.analyze(imageData, tier)
-      setAnalysisResult(result)
+      // The analysis gets a smaller copy; the original is what's saved
+      const analysisFile = await downscaleImage(imageFile, maxDimension, signal)
+      const { dataUrl: imageData } =
+        analysisFile === imageFile ? await encodedImage(imageFile) : await encodeImage(analysisFile, { signal })
+      const result = await artifactApi.analyze(imageData, tier, signal, refresh, (next) => {
+        if (!signal.aborted) setStage(next)
+      })
+      if (!signal.aborted) {
+        setAnalysisResult(result)
+        if (tier === 'thorough') {
+          notifyIfHidden(`Identified: ${result.name}`, `${(result.confidence * 100).toFixed(1)}% confidence`, () =>
+            resultsRef.current?.scrollIntoView({ behavior: 'smooth' })
+          )
+        }
+      }
     } catch (error: any) {
-      alert(`Analysis failed: ${error.message}`)
+      if (!isCancellation(error)) {
+        alert(`Analysis failed: ${errorMessage(error)}`)
+      }
     } finally {
-      setLoading(false)
+      tasks.finish('analysis', signal)
+      if (!signal.aborted) {
+        setLoading(false)
+      }
     }
   }
 
   const handleSave = async () => {
     if (!imageFile || !analysisResult) return
 
+    const signal = tasks.start('save')
     setLoading(true)
     try {
-      const imageData = await convertToBase64(imageFile)
+      const { dataUrl: imageData } = await encodedImage(imageFile)
       const tags = formData.tags || analysisResult.name.split(' ').filter(Boolean)
       
       await artifactApi.create({
@@ -133,24 +218,32 @@ export default function UploadArtifact() {
         image_data: imageData,
         form_data: Object.keys(formData).length > 0 ? formData : undefined,
         uploaded_by: user?.username,
-      })
+      }, signal)
 
       setSaved(true)
       setTimeout(() => {
         // Reset form
         setImageFile(null)
         setImagePreview(null)
+        encoding.current = null
         setFormData({})
         setAnalysisResult(null)
         setSaved(false)
       }, 2000)
     } catch (error: any) {
-      alert(`Failed to save artifact: ${error.message}`)
+      if (!isCancellation(error)) {
+        alert(`Failed to save artifact: ${errorMessage(error)}`)
+      }
     } finally {
-      setLoading(false)
+      tasks.finish('save', signal)
+      if (!signal.aborted) {
+        setLoading(false)
+      }
     }
   }
 
+  const canBatchUpload = canAccess(user?.role, 'batch-upload')
+
   return (
     <div className="upload-page">
       <h1>📤 Upload & Identify Artifact</h1>
@@ -162,15 +255,17 @@ export default function UploadArtifact() {
         >
           Single (with details)
         </button>
-        <button
-          className={uploadMode === 'batch' ? 'active' : ''}
-          onClick={() => setUploadMode('batch')}
-        >
-          Batch (multiple images)
-        </button>
+        {canBatchUpload && (
+          <button
+            className={uploadMode === 'batch' ? 'active' : ''}
+            onClick={() => setUploadMode('batch')}
+          >
+            Batch (multiple images)
+          </button>
+        )}
       </div>
 
-      {uploadMode === 'single' ? (
+      {uploadMode === 'single' || !canBatchUpload ? (
         <div className="upload-single">
           <div className="upload-grid">
             <div className="upload-left">
@@ -199,14 +294,12 @@ export default function UploadArtifact() {
 
               {imageSource === 'upload' ? (
                 <>
-                  <input
-                    type="file"
-                    accept="image/*"
-                    onChange={handleImageSelect}
-                    className="file-input"
-                  />
-                  {imagePreview && (
-                    <img src={imagePreview} alt="Preview" className="image-preview" />
+                  <button type="button" onClick={handleImageSelect} className="file-input file-pick">
+                    📄 {imageFile ? imageFile.name : 'Choose Image…'}
+                  </button>
+                  {imagePreview !== null && (
+                    // Browsers can't show HEIC or RAW; the server converts them on upload
+                    <img src={imagePreview || undefined} alt="No preview for this format" className="image-preview" />
                   )}
                 </>
               ) : (
@@ -316,23 +409,50 @@ export default function UploadArtifact() {
               <h2>🤖 AI Analysis</h2>
               <div>
                 <label>Analysis Quality</label>
-                <select value={tier} onChange={(e) => setTier(e.target.value)}>
+                <select
+                  value={tier}
+                  onChange={(e) => {
+                    setTier(e.target.value)
+                    updateSettings({ defaultTier: e.target.value })
+                  }}
+                >
                   <option value="fast">Fast (~20-40s)</option>
                   <option value="balanced">Balanced (~30-60s)</option>
                   <option value="thorough">Thorough (~1-2 min)</option>
                 </select>
               </div>
+              <div>
+                <label>Image Size for Analysis</label>
+                <select
+                  value={maxDimension}
+                  onChange={(e) => {
+                    const size = parseInt(e.target.value, 10)
+                    setMaxDimension(size)
+                    updateSettings({ analysisMaxDimension: size })
+                  }}
+                >
+                  {ANALYSIS_SIZES.map((size) => (
+                    <option key={size} value={size}>
+                      {analysisSizeLabel(size)}
+                    </option>
+                  ))}
+                </select>
+              </div>
 
               <button
-                onClick={handleAnalyze}
+                onClick={() => handleAnalyze()}
                 disabled={!imageFile || loading}
                 className="analyze-button"
               >
-                {loading ? 'Analyzing...' : '🔍 Analyze Artifact'}
+                {loading
+                  ? encodeProgress !== null
+                    ? `Reading image... ${Math.round(encodeProgress * 100)}%`
+                    : (stage && stageLabels[stage]) || 'Analyzing...'
+                  : '🔍 Analyze Artifact'}
               </button>
 
               {analysisResult && (
-                <div className="analysis-results">
+                <div className="analysis-results" ref={resultsRef}>
                   <h3>Analysis Results</h3>
                   <div className="results-grid">
                     <div>
@@ -347,6 +467,7 @@ export default function UploadArtifact() {
                     </div>
                     <div>
                       <strong>Time:</strong> {analysisResult.analysis_time}
+                      {analysisResult.cached && ' (earlier result)'}
                     </div>
                   </div>
                   <div>
@@ -355,6 +476,24 @@ export default function UploadArtifact() {
                   <div>
                     <strong>Description:</strong> {analysisResult.description}
                   </div>
+                  {analysisResult.candidates && analysisResult.candidates.length > 1 && (
+                    <div className="other-candidates">
+                      <strong>Other possibilities:</strong>
+                      <ul>
+                        {analysisResult.candidates.slice(1).map((candidate) => (
+                          <li key={candidate.name}>
+                            {candidate.name} ({(candidate.confidence * 100).toFixed(1)}%)
+                          </li>
+                        ))}
+                      </ul>
+                    </div>
+                  )}
+
+                  {analysisResult.cached && (
+                    <button onClick={() => handleAnalyze(true)} disabled={loading} className="reanalyze-button">
+                      🔄 Analyze again
+                    </button>
+                  )}
 
                   <button
                     onClick={handleSave}
@@ -369,9 +508,7 @@ export default function UploadArtifact() {
           </div>
         </div>
       ) : (
-        <div className="upload-batch">
-          <p>Batch upload functionality coming soon...</p>
-        </div>
+        <BatchUpload />
       )}
     </div>
   )
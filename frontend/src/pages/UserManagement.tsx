@@ -1,20 +1,30 @@
 import { useEffect, useState } from 'react'
-import { userApi } from '../services/api'
+import PasswordStrength, { usePasswordPolicy } from '../components/PasswordStrength'
+import { useAuth } from '../contexts/AuthContext'
+import { usePresence } from '../services/presence'
+import { adminUserApi, userApi } from '../services/api'
+import { User, UserCreate, UserRole } from '../types'
+import { errorMessage } from '../utils/errors'
+import { passwordProblems } from '../utils/passwordPolicy'
+import { ROLE_LABELS } from '../utils/permissions'
 import './UserManagement.css'
 
-interface User {
-  username: string
-  name: string
-  email: string
-  role: string
-}
+const PAGE_SIZE = 25
+
 
 export default function UserManagement() {
+  const { user: currentUser } = useAuth()
   const [users, setUsers] = useState<User[]>([])
+  const [total, setTotal] = useState(0)
+  const [page, setPage] = useState(0)
   const [searchQuery, setSearchQuery] = useState('')
+  const [roleFilter, setRoleFilter] = useState<UserRole | ''>('')
+  const [statusFilter, setStatusFilter] = useState<'' | 'active' | 'deactivated'>('')
   const [loading, setLoading] = useState(true)
   const [showAddForm, setShowAddForm] = useState(false)
-  const [newUser, setNewUser] = useState({
+  const policy = usePasswordPolicy()
+  const online = usePresence()
+  const [newUser, setNewUser] = useState<UserCreate>({
     username: '',
     name: '',
     password: '',
@@ -23,13 +33,22 @@ export default function UserManagement() {
   })
 
   useEffect(() => {
-    loadUsers()
-  }, [])
+    // Debounce typing in the search box
+    const timer = setTimeout(loadUsers, 300)
+    return () => clearTimeout(timer)
+  }, [searchQuery, roleFilter, statusFilter, page])
 
   const loadUsers = async () => {
     try {
-      const data = await userApi.getAll()
-      setUsers(data)
+      const data = await adminUserApi.list({
+        q: searchQuery.trim() || undefined,
+        role: roleFilter || undefined,
+        active: statusFilter ? statusFilter === 'active' : undefined,
+        limit: PAGE_SIZE,
+        offset: page * PAGE_SIZE,
+      })
+      setUsers(data.users)
+      setTotal(data.total)
     } catch (error) {
       console.error('Failed to load users:', error)
     } finally {
@@ -39,22 +58,80 @@ export default function UserManagement() {
 
   const handleAddUser = async (e: React.FormEvent) => {
     e.preventDefault()
+    const problems = passwordProblems(newUser.password, policy)
+    if (problems.length > 0) {
+      alert(`Password needs ${problems.join(', ')}`)
+      return
+    }
     try {
-      await userApi.create(newUser)
+      await adminUserApi.create(newUser)
       setShowAddForm(false)
       setNewUser({ username: '', name: '', password: '', role: 'user', email: '' })
       await loadUsers()
       alert('User added successfully!')
-    } catch (error: any) {
-      alert(`Failed to add user: ${error.message}`)
+    } catch (error) {
+      alert(`Failed to add user: ${errorMessage(error)}`)
     }
   }
 
-  const filteredUsers = users.filter(
-    (u) =>
-      u.username.toLowerCase().includes(searchQuery.toLowerCase()) ||
-      u.email.toLowerCase().includes(searchQuery.toLowerCase())
-  )
+  const handleUnlock = async (username: string) => {
+    try {
+      await userApi.unlock(username)
+      await loadUsers()
+    } catch (error) {
+      alert(`Failed to unlock ${username}: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleRoleChange = async (username: string, role: UserRole) => {
+    try {
+      await adminUserApi.update(username, { role })
+      await loadUsers()
+    } catch (error) {
+      alert(`Failed to change role: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleDeactivate = async (username: string) => {
+    if (!window.confirm(`Deactivate ${username}? They will be signed out and unable to sign in until reactivated.`)) return
+    try {
+      await adminUserApi.deactivate(username)
+      await loadUsers()
+    } catch (error) {
+      alert(`Failed to deactivate ${username}: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleReactivate = async (username: string) => {
+    try {
+      await adminUserApi.reactivate(username)
+      await loadUsers()
+    } catch (error) {
+      alert(`Failed to reactivate ${username}: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleSignOut = async (username: string) => {
+    if (!window.confirm(`Sign ${username} out everywhere? Their open tabs will close their session right away.`)) return
+    try {
+      await adminUserApi.revokeSessions(username)
+      alert(`${username} has been signed out`)
+    } catch (error) {
+      alert(`Failed to sign out ${username}: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleDelete = async (username: string) => {
+    if (!window.confirm(`Delete ${username}? They will be signed out and their API keys revoked.`)) return
+    try {
+      await adminUserApi.remove(username)
+      await loadUsers()
+    } catch (error) {
+      alert(`Failed to delete ${username}: ${errorMessage(error)}`)
+    }
+  }
+
+  const pageCount = Math.max(1, Math.ceil(total / PAGE_SIZE))
 
   if (loading) {
     return <div className="loading">Loading users...</div>
@@ -68,15 +145,45 @@ export default function UserManagement() {
         <input
           type="text"
           value={searchQuery}
-          onChange={(e) => setSearchQuery(e.target.value)}
-          placeholder="Search by username or email"
+          onChange={(e) => {
+            setSearchQuery(e.target.value)
+            setPage(0)
+          }}
+          placeholder="Search by username or name"
           className="search-input"
         />
+        <select
+          value={roleFilter}
+          onChange={(e) => {
+            setRoleFilter(e.target.value as UserRole | '')
+            setPage(0)
+          }}
+          className="role-filter"
+        >
+          <option value="">All roles</option>
+          {Object.entries(ROLE_LABELS).map(([role, label]) => (
+            <option key={role} value={role}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <select
+          value={statusFilter}
+          onChange={(e) => {
+            setStatusFilter(e.target.value as '' | 'active' | 'deactivated')
+            setPage(0)
+          }}
+          className="role-filter"
+        >
+          <option value="">All statuses</option>
+          <option value="active">Active</option>
+          <option value="deactivated">Deactivated</option>
+        </select>
       </div>
 
       <div className="users-section">
         <div className="section-header">
-          <h2>Current Users</h2>
+          <h2>Current Users ({total})</h2>
           <button onClick={() => setShowAddForm(!showAddForm)} className="add-button">
             {showAddForm ? '✕ Cancel' : '➕ Add New User'}
           </button>
@@ -112,18 +219,21 @@ export default function UserManagement() {
                   value={newUser.password}
                   onChange={(e) => setNewUser({ ...newUser, password: e.target.value })}
                   required
+                  minLength={policy.min_length}
                 />
+                <PasswordStrength password={newUser.password} policy={policy} />
               </div>
               <div>
                 <label>Role</label>
                 <select
                   value={newUser.role}
-                  onChange={(e) => setNewUser({ ...newUser, role: e.target.value })}
+                  onChange={(e) => setNewUser({ ...newUser, role: e.target.value as UserRole })}
                 >
-                  <option value="user">User</option>
-                  <option value="admin">Admin</option>
-                  <option value="field">Field Engineer</option>
-                  <option value="onsite">Lab Engineer</option>
+                  {Object.entries(ROLE_LABELS).map(([role, label]) => (
+                    <option key={role} value={role}>
+                      {label}
+                    </option>
+                  ))}
                 </select>
               </div>
             </div>
@@ -143,7 +253,7 @@ export default function UserManagement() {
         )}
 
         <div className="users-list">
-          {filteredUsers.length === 0 ? (
+          {users.length === 0 ? (
             <p>No users found</p>
           ) : (
             <table className="users-table">
@@ -153,22 +263,95 @@ export default function UserManagement() {
                   <th>Name</th>
                   <th>Email</th>
                   <th>Role</th>
+                  <th>Status</th>
+                  <th></th>
                 </tr>
               </thead>
               <tbody>
-                {filteredUsers.map((user) => (
-                  <tr key={user.username}>
-                    <td>{user.username}</td>
+                {users.map((user) => (
+                  <tr key={user.username} className={user.active === false ? 'deactivated' : ''}>
+                    <td>
+                      {online.includes(user.username) && <span title="Online now">🟢 </span>}
+                      {user.username}
+                    </td>
                     <td>{user.name}</td>
                     <td>{user.email}</td>
                     <td>
-                      <span className="role-badge">{user.role}</span>
+                      <select
+                        value={user.role}
+                        onChange={(e) => handleRoleChange(user.username, e.target.value as UserRole)}
+                        disabled={user.username === currentUser?.username}
+                        className="role-select"
+                      >
+                        {Object.entries(ROLE_LABELS).map(([role, label]) => (
+                          <option key={role} value={role}>
+                            {label}
+                          </option>
+                        ))}
+                      </select>
+                    </td>
+                    <td>
+                      {user.active === false ? (
+                        <span
+                          className="deactivated-badge"
+                          title={user.deactivated_at ? `Since ${new Date(user.deactivated_at).toLocaleString()}` : undefined}
+                        >
+                          ⏸️ Deactivated
+                        </span>
+                      ) : user.locked_until ? (
+                        <>
+                          <span className="locked-badge" title={`Locked until ${new Date(user.locked_until).toLocaleString()}`}>
+                            🔒 Locked
+                          </span>
+                          <button className="unlock-button" onClick={() => handleUnlock(user.username)}>
+                            Unlock
+                          </button>
+                        </>
+                      ) : (
+                        'Active'
+                      )}
+                    </td>
+                    <td>
+                      {user.username !== currentUser?.username && (
+                        <>
+                          {user.active === false ? (
+                            <button className="reactivate-button" onClick={() => handleReactivate(user.username)}>
+                              ▶️ Reactivate
+                            </button>
+                          ) : (
+                            <>
+                              <button className="deactivate-button" onClick={() => handleSignOut(user.username)}>
+                                🚪 Sign Out
+                              </button>
+                              <button className="deactivate-button" onClick={() => handleDeactivate(user.username)}>
+                                ⏸️ Deactivate
+                              </button>
+                            </>
+                          )}
+                          <button className="delete-user-button" onClick={() => handleDelete(user.username)}>
+                            🗑️ Delete
+                          </button>
+                        </>
+                      )}
                     </td>
                   </tr>
                 ))}
               </tbody>
             </table>
           )}
+          {pageCount > 1 && (
+            <div className="pagination">
+              <button onClick={() => setPage(page - 1)} disabled={page === 0}>
+                ← Previous
+              </button>
+              <span>
+                Page {page + 1} of {pageCount}
+              </span>
+              <button onClick={() => setPage(page + 1)} disabled={page + 1 >= pageCount}>
+                Next →
+              </button>
+            </div>
+          )}
         </div>
       </div>
     </div>
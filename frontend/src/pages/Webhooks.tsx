@@ -0,0 +1,155 @@
+import { useEffect, useState } from 'react'
+import { webhookApi } from '../services/api'
+import { WebhookEvent, WebhookInfo } from '../types'
+import { errorMessage } from '../utils/errors'
+import './ApiKeys.css'
+import './Webhooks.css'
+
+const EVENT_LABELS: Record<WebhookEvent, string> = {
+  'analysis.done': 'Analysis finished',
+  'analysis.failed': 'Analysis failed',
+}
+
+export default function Webhooks() {
+  const [webhooks, setWebhooks] = useState<WebhookInfo[]>([])
+  const [loading, setLoading] = useState(true)
+  const [url, setUrl] = useState('')
+  const [events, setEvents] = useState<WebhookEvent[]>(['analysis.done', 'analysis.failed'])
+  const [newSecret, setNewSecret] = useState<string | null>(null)
+
+  useEffect(() => {
+    loadWebhooks()
+  }, [])
+
+  const loadWebhooks = async () => {
+    try {
+      setWebhooks(await webhookApi.list())
+    } catch (error) {
+      console.error('Failed to load webhooks:', error)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  const toggleEvent = (event: WebhookEvent) => {
+    setEvents((current) => (current.includes(event) ? current.filter((e) => e !== event) : [...current, event]))
+  }
+
+  const handleCreate = async (e: React.FormEvent) => {
+    e.preventDefault()
+    try {
+      const created = await webhookApi.create({ url: url.trim(), events })
+      setNewSecret(created.secret)
+      setUrl('')
+      await loadWebhooks()
+    } catch (error) {
+      alert(`Failed to add webhook: ${errorMessage(error)}`)
+    }
+  }
+
+  const handlePing = async (webhook: WebhookInfo) => {
+    try {
+      await webhookApi.ping(webhook.id)
+      // The test is sent in the background; show how it went once it has had a moment
+      setTimeout(loadWebhooks, 3000)
+    } catch (error) {
+      alert(`Failed to send a test: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleDelete = async (webhook: WebhookInfo) => {
+    if (!window.confirm(`Stop calling ${webhook.url}?`)) return
+    try {
+      await webhookApi.remove(webhook.id)
+      await loadWebhooks()
+    } catch (error) {
+      alert(`Failed to delete webhook: ${errorMessage(error)}`)
+    }
+  }
+
+  const formatDate = (value?: string | null) => (value ? new Date(value).toLocaleString() : '—')
+
+  if (loading) {
+    return <div className="loading">Loading webhooks...</div>
+  }
+
+  return (
+    <div className="api-keys">
+      <h1>🔔 Webhooks</h1>
+      <p className="api-keys-intro">
+        We POST a JSON event to these URLs when one of your queued analyses finishes or fails, e.g. to post
+        to Slack or update a lab system. Each request is signed: check the <code>X-Webhook-Signature</code>{' '}
+        header, an HMAC-SHA256 of <code>{'{timestamp}.{body}'}</code> with the webhook's secret. Failed
+        deliveries are retried.
+      </p>
+
+      <form onSubmit={handleCreate} className="api-key-form">
+        <input
+          type="url"
+          value={url}
+          onChange={(e) => setUrl(e.target.value)}
+          placeholder="https://example.org/hooks/artifacts"
+          required
+        />
+        <div className="webhook-events">
+          {(Object.keys(EVENT_LABELS) as WebhookEvent[]).map((event) => (
+            <label key={event}>
+              <input type="checkbox" checked={events.includes(event)} onChange={() => toggleEvent(event)} />
+              {EVENT_LABELS[event]}
+            </label>
+          ))}
+        </div>
+        <button type="submit" disabled={events.length === 0}>
+          ➕ Add Webhook
+        </button>
+      </form>
+
+      {newSecret && (
+        <div className="new-key">
+          <p>Copy this signing secret now, it won't be shown again:</p>
+          <code>{newSecret}</code>
+          <button onClick={() => setNewSecret(null)}>Done</button>
+        </div>
+      )}
+
+      {webhooks.length === 0 ? (
+        <p>No webhooks yet</p>
+      ) : (
+        <table className="api-keys-table">
+          <thead>
+            <tr>
+              <th>URL</th>
+              <th>Events</th>
+              <th>Last delivery</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {webhooks.map((webhook) => (
+              <tr key={webhook.id}>
+                <td className="webhook-url">{webhook.url}</td>
+                <td>{webhook.events.map((event) => EVENT_LABELS[event]).join(', ')}</td>
+                <td>
+                  {formatDate(webhook.last_delivery_at)}
+                  {webhook.last_status && (
+                    <div className={`webhook-status ${webhook.last_status}`}>
+                      {webhook.last_status === 'delivered' ? '✅ Delivered' : `❌ ${webhook.last_error || 'Failed'}`}
+                    </div>
+                  )}
+                </td>
+                <td className="webhook-actions">
+                  <button className="revoke-button" onClick={() => handlePing(webhook)}>
+                    Test
+                  </button>
+                  <button className="revoke-button" onClick={() => handleDelete(webhook)}>
+                    Delete
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  )
+}
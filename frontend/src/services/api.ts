@@ -1,92 +1,816 @@
-import axios from 'axios'
+import axios, { AxiosResponse } from 'axios'
+import { isDemoMode, mockAdapter } from './mockApi'
+import { cookieAuth, session } from './session'
+import {
+  AccountDeletion,
+  AdminStats,
+  AnalysisUsageInfo,
+  AnalysisBatch,
+  AnalysisJob,
+  AnalysisStage,
+  AnalysisResult,
+  ApiKeyCreated,
+  ApiKeyInfo,
+  Artifact,
+  ArtifactChanges,
+  ArtifactCreate,
+  ArtifactCreated,
+  ArtifactFilters,
+  ArtifactFlag,
+  ArtifactLocation,
+  ArtifactPage,
+  ArtifactMutation,
+  ArtifactPatch,
+  ArtifactRevision,
+  ArtifactUpdate,
+  AuditLog,
+  BootstrapRequest,
+  BootstrapStatus,
+  CollectionCreate,
+  CollectionInfo,
+  ExportFormat,
+  IdentificationOverride,
+  ImportConflict,
+  ImportResult,
+  InviteCreate,
+  InviteCreated,
+  InviteInfo,
+  IpRuleCreate,
+  IpRuleInfo,
+  LoginRecord,
+  LoginResponse,
+  MessageResponse,
+  ModerationQueueItem,
+  OAuthProvider,
+  OrgMember,
+  OrgMemberAdd,
+  OrgMemberUpdate,
+  OrgRank,
+  OrganizationCreate,
+  OrganizationInfo,
+  PasswordPolicy,
+  ProfileUpdate,
+  RegisterRequest,
+  RegistrationInfo,
+  SessionResponse,
+  SimilarArtifact,
+  ShareScope,
+  Sharing,
+  TagChange,
+  TagCount,
+  TrashedArtifact,
+  User,
+  UserCreate,
+  UserCreated,
+  UserFilters,
+  UserUpdate,
+  VerificationRequest,
+  WebhookCreate,
+  WebhookCreated,
+  WebhookInfo,
+} from '../types'
+import { AppError } from '../utils/errors'
+import { requirePermission } from '../utils/permissions'
 
-const API_BASE_URL =  'http://localhost:8000'
+// How often to check on a queued analysis
+const ANALYSIS_POLL_MS = 2000
+
+const pause = (ms: number, signal?: AbortSignal) =>
+  new Promise<void>((resolve, reject) => {
+    if (signal?.aborted) return reject(new axios.CanceledError())
+    const timer = setTimeout(resolve, ms)
+    signal?.addEventListener('abort', () => {
+      clearTimeout(timer)
+      reject(new axios.CanceledError())
+    }, { once: true })
+  })
+
+export const API_BASE_URL =  'http://localhost:8000'
+
+const pollJob = async (job: AnalysisJob, signal?: AbortSignal, onStage?: (stage: AnalysisStage) => void) => {
+  while (job.status === 'queued' || job.status === 'running') {
+    await pause(ANALYSIS_POLL_MS, signal)
+    job = (await api.get<AnalysisJob>(`/api/analyze/jobs/${job.id}`, { signal })).data
+    onStage?.(job.stage)
+  }
+  return job
+}
+
+// Wait for a queued analysis on its server-sent events stream, falling back
+// to polling where there's no EventSource (demo mode) or the stream breaks.
+// EventSource can't send a bearer token, so only cookie sessions use the stream.
+const followJob = (job: AnalysisJob, signal?: AbortSignal, onStage?: (stage: AnalysisStage) => void) => {
+  onStage?.(job.stage)
+  if (isDemoMode || !cookieAuth || typeof EventSource === 'undefined') return pollJob(job, signal, onStage)
+
+  return new Promise<AnalysisJob>((resolve, reject) => {
+    const source = new EventSource(`${API_BASE_URL}/api/analyze/jobs/${job.id}/events`, {
+      withCredentials: cookieAuth,
+    })
+    const settle = (finish: () => void) => {
+      source.close()
+      signal?.removeEventListener('abort', abort)
+      finish()
+    }
+    const abort = () => settle(() => reject(new axios.CanceledError()))
+    if (signal?.aborted) return abort()
+    signal?.addEventListener('abort', abort, { once: true })
+
+    source.addEventListener('stage', (e) => onStage?.(JSON.parse((e as MessageEvent).data).stage))
+    const finished = (e: Event) => settle(() => resolve(JSON.parse((e as MessageEvent).data)))
+    source.addEventListener('done', finished)
+    source.addEventListener('failed', finished)
+    source.onerror = () => settle(() => resolve(pollJob(job, signal, onStage)))
+  })
+}
+
+/** Turn an image path from the API into something an <img> can load; data URLs pass through */
+export const imageUrl = (path?: string | null) =>
+  path && path.startsWith('/') ? `${API_BASE_URL}${path}` : path ?? undefined
 
 export const api = axios.create({
   baseURL: API_BASE_URL,
   headers: {
     'Content-Type': 'application/json',
   },
+  // Send the session cookie cross-origin when signed in with one
+  withCredentials: cookieAuth,
+  // Demo mode answers every request in-process instead of hitting the backend
+  ...(isDemoMode ? { adapter: mockAdapter } : {}),
+})
+
+// Bodies larger than this are gzip-compressed before upload
+const COMPRESSION_THRESHOLD = 64 * 1024
+
+const isPlainJson = (data: unknown) =>
+  Array.isArray(data) || Object.prototype.toString.call(data) === '[object Object]'
+
+// Compress large JSON payloads (base64 images) to cut upload time on slow links.
+// The backend decodes them via Content-Encoding; responses are gzipped by the
+// server and decompressed by the browser automatically.
+api.interceptors.request.use(async (config) => {
+  if (isDemoMode || typeof CompressionStream === 'undefined' || !isPlainJson(config.data)) {
+    return config
+  }
+
+  const json = JSON.stringify(config.data)
+  if (json.length < COMPRESSION_THRESHOLD) {
+    return config
+  }
+
+  const compressed = new Blob([json]).stream().pipeThrough(new CompressionStream('gzip'))
+  config.data = await new Response(compressed).arrayBuffer()
+  config.headers.set('Content-Encoding', 'gzip')
+  return config
+})
+
+// Tag each request with an id the backend logs and echoes back
+// (MainApp/request_logging.py), so a failure here can be found in its logs
+const newRequestId = () =>
+  typeof crypto !== 'undefined' && 'randomUUID' in crypto
+    ? crypto.randomUUID().replace(/-/g, '')
+    : Array.from({ length: 32 }, () => Math.floor(Math.random() * 16).toString(16)).join('')
+
+api.interceptors.request.use((config) => {
+  if (!config.headers.has('X-Request-ID')) {
+    config.headers.set('X-Request-ID', newRequestId())
+  }
+  return config
+})
+
+const SAFE_METHODS = ['get', 'head', 'options']
+
+// Attach the signed-in user's access token to every request; cookie sessions
+// instead need their CSRF token on anything that changes state
+api.interceptors.request.use((config) => {
+  const tokens = session.getTokens()
+  if (tokens?.access_token && !config.headers.has('Authorization')) {
+    config.headers.set('Authorization', `Bearer ${tokens.access_token}`)
+  }
+  const csrfToken = session.getCsrfToken()
+  const method = (config.method ?? 'get').toLowerCase()
+  if (cookieAuth && csrfToken && !SAFE_METHODS.includes(method) && !config.headers.has('X-CSRF-Token')) {
+    config.headers.set('X-CSRF-Token', csrfToken)
+  }
+  return config
 })
 
-// Add auth interceptor if needed
+// Requests whose 401 means bad credentials rather than an expired session
+const isAuthRequest = (url?: string) =>
+  url === '/api/v1/auth/login' || url === '/api/v1/auth/refresh' || url === '/api/v1/auth/session'
+
+// Shared so that concurrent 401s trigger a single refresh
+let refreshing: Promise<string | null> | null = null
+
+const refreshAccessToken = async (): Promise<string | null> => {
+  const refreshToken = session.getTokens()?.refresh_token
+  if (!refreshToken) return null
+  try {
+    const response = await api.post('/api/v1/auth/refresh', { refresh_token: refreshToken })
+    session.saveTokens(response.data)
+    return response.data.access_token
+  } catch (e) {
+    return null
+  }
+}
+
 api.interceptors.response.use(
   (response) => response,
-  (error) => {
-    if (error.response?.status === 401) {
-      // Handle unauthorized - clear auth and redirect
-      localStorage.removeItem('user')
-      window.location.href = '/login'
+  async (error) => {
+    const original = error.config
+    if (error.response?.status !== 401 || !original || isAuthRequest(original.url)) {
+      return Promise.reject(error)
     }
+
+    // Expired access token: refresh once and replay the request
+    if (!original._retried) {
+      original._retried = true
+      refreshing = refreshing ?? refreshAccessToken().finally(() => {
+        refreshing = null
+      })
+      const accessToken = await refreshing
+      if (accessToken) {
+        original.headers.set('Authorization', `Bearer ${accessToken}`)
+        return api(original)
+      }
+    }
+
+    // Session can't be recovered - prompt the user to sign in again
+    session.clear()
+    window.location.href = '/login?expired=1'
     return Promise.reject(error)
   }
 )
 
-// Auth endpoints (to be implemented in backend)
+// Paged lists send their full size in X-Total-Count
+const totalCount = (response: AxiosResponse<unknown[]>) =>
+  Number(response.headers['x-total-count'] ?? response.data.length)
+
+// Role of the signed-in user, as stored by AuthContext
+const currentRole = () => session.getUser()?.role
+
+const ensureCan = (feature: string) => requirePermission(currentRole(), feature)
+
+// Auth endpoints
 export const authApi = {
-  login: async (username: string, password: string) => {
-    // This will need to be implemented in the backend
-    // For now, we'll use a mock or direct database check
-    const response = await api.post('/auth/login', { username, password })
+  login: async (username: string, password: string): Promise<LoginResponse> => {
+    const response = await api.post<LoginResponse>('/api/v1/auth/login', { username, password })
+    return response.data
+  },
+  /** Cookie sign-in: the session id is set as an HTTP-only cookie */
+  createSession: async (username: string, password: string): Promise<SessionResponse> => {
+    const response = await api.post<SessionResponse>('/api/v1/auth/session', { username, password })
+    return response.data
+  },
+  endSession: async (csrfToken: string | null): Promise<void> => {
+    await api.delete('/api/v1/auth/session', { headers: csrfToken ? { 'X-CSRF-Token': csrfToken } : {} })
+  },
+  bootstrapStatus: async (): Promise<BootstrapStatus> => {
+    const response = await api.get<BootstrapStatus>('/api/v1/auth/bootstrap')
+    return response.data
+  },
+  /** First-run setup: creates the initial admin, refused once one exists */
+  bootstrap: async (account: BootstrapRequest): Promise<UserCreated> => {
+    const response = await api.post<UserCreated>('/api/v1/auth/bootstrap', account)
+    return response.data
+  },
+  registrationInfo: async (): Promise<RegistrationInfo> => {
+    const response = await api.get<RegistrationInfo>('/api/v1/auth/register')
+    return response.data
+  },
+  register: async (account: RegisterRequest): Promise<UserCreated> => {
+    const response = await api.post<UserCreated>('/api/v1/auth/register', account)
+    return response.data
+  },
+  oauthProviders: async (): Promise<OAuthProvider[]> => {
+    const response = await api.get<OAuthProvider[]>('/api/v1/auth/oauth/providers')
     return response.data
   },
-  logout: async () => {
-    await api.post('/auth/logout')
+  /** Full-page redirect into the provider's sign-in; comes back via /oauth/callback */
+  startOAuth: (provider: string) => {
+    window.location.href = `${API_BASE_URL}/api/v1/auth/oauth/${encodeURIComponent(provider)}/start`
+  },
+  forgotPassword: async (identifier: string): Promise<MessageResponse> => {
+    const response = await api.post<MessageResponse>('/api/v1/auth/password/forgot', { identifier })
+    return response.data
+  },
+  passwordPolicy: async (): Promise<PasswordPolicy> => {
+    const response = await api.get<PasswordPolicy>('/api/v1/auth/password/policy')
+    return response.data
+  },
+  resetPassword: async (token: string, newPassword: string): Promise<MessageResponse> => {
+    const response = await api.post<MessageResponse>('/api/v1/auth/password/reset', { token, new_password: newPassword })
+    return response.data
+  },
+  me: async (): Promise<User> => {
+    const response = await api.get<User>('/api/v1/auth/me')
+    return response.data
+  },
+  logout: async (refreshToken: string): Promise<void> => {
+    await api.post('/api/v1/auth/logout', { refresh_token: refreshToken })
   },
 }
 
 // Artifact endpoints
 export const artifactApi = {
-  getAll: async () => {
-    const response = await api.get('/api/artifacts')
+  /**
+   * One page, filtered and sorted by the server; use archiveSync for the whole archive.
+   * Covers everything the user may see unless `filters.scope` narrows it.
+   */
+  getAll: async (uploadedBy?: string, filters: ArtifactFilters = {}, signal?: AbortSignal): Promise<ArtifactPage> => {
+    const response = await api.get<Artifact[]>('/api/artifacts', {
+      params: { scope: 'all', ...filters, uploaded_by: uploadedBy, include_images: false },
+      signal,
+    })
+    return { artifacts: response.data, total: totalCount(response) }
+  },
+  sync: async (updatedSince?: string, uploadedBy?: string, knownIds: number[] = []): Promise<ArtifactChanges> => {
+    const response = await api.post<ArtifactChanges>('/api/artifacts/sync', {
+      updated_since: updatedSince,
+      uploaded_by: uploadedBy,
+      include_images: false,
+      known_ids: knownIds,
+    })
+    return response.data
+  },
+  getById: async (id: number): Promise<Artifact> => {
+    const response = await api.get<Artifact>(`/api/artifacts/${id}`)
+    return response.data
+  },
+  /** One page of results, best match first */
+  search: async (
+    query: string,
+    uploadedBy?: string,
+    page: { limit?: number; offset?: number } = {},
+    signal?: AbortSignal
+  ): Promise<ArtifactPage> => {
+    const response = await api.get<Artifact[]>('/api/artifacts/search', {
+      params: { q: query, scope: 'all', uploaded_by: uploadedBy, ...page, include_images: false },
+      signal,
+    })
+    return { artifacts: response.data, total: totalCount(response) }
+  },
+  create: async (artifact: ArtifactCreate, signal?: AbortSignal): Promise<ArtifactCreated> => {
+    ensureCan('upload')
+    const response = await api.post<ArtifactCreated>('/api/artifacts', artifact, { signal })
+    return response.data
+  },
+  update: async (id: number, data: ArtifactUpdate): Promise<ArtifactMutation> => {
+    ensureCan('edit')
+    const response = await api.put<ArtifactMutation>(`/api/artifacts/${id}`, data)
+    return response.data
+  },
+  /** Fails with precondition_failed if the artifact changed since `version` was read */
+  patch: async (id: number, changes: ArtifactPatch, version?: string): Promise<Artifact> => {
+    ensureCan('edit')
+    const headers = version ? { 'If-Match': `"${version}"` } : undefined
+    const response = await api.patch<Artifact>(`/api/artifacts/${id}`, changes, { headers })
+    return response.data
+  },
+  /** Every change to the artifact, newest first */
+  history: async (id: number, page: { limit?: number; offset?: number } = {}): Promise<{ revisions: ArtifactRevision[]; total: number }> => {
+    const response = await api.get<ArtifactRevision[]>(`/api/artifacts/${id}/history`, { params: page })
+    return { revisions: response.data, total: totalCount(response) }
+  },
+  /** Undoes one change; the fields it changed get their earlier values back */
+  revert: async (id: number, revisionId: number): Promise<Artifact> => {
+    ensureCan('edit')
+    const response = await api.post<Artifact>(`/api/artifacts/${id}/history/${revisionId}/revert`)
+    return response.data
+  },
+  /** Moves it to the trash, from where it can be restored until purged */
+  delete: async (id: number): Promise<ArtifactMutation> => {
+    ensureCan('delete')
+    const response = await api.delete<ArtifactMutation>(`/api/artifacts/${id}`)
+    return response.data
+  },
+  /** The user's trash (everyone's for staff), most recently deleted first */
+  trash: async (page: { limit?: number; offset?: number } = {}): Promise<{ artifacts: TrashedArtifact[]; total: number }> => {
+    const response = await api.get<TrashedArtifact[]>('/api/trash', { params: page })
+    return { artifacts: response.data, total: totalCount(response) }
+  },
+  restore: async (id: number): Promise<Artifact> => {
+    ensureCan('delete')
+    const response = await api.post<Artifact>(`/api/artifacts/${id}/restore`)
+    return response.data
+  },
+  /** Deletes a trashed artifact for good */
+  purge: async (id: number): Promise<ArtifactMutation> => {
+    ensureCan('delete')
+    const response = await api.delete<ArtifactMutation>(`/api/trash/${id}`)
     return response.data
   },
-  getById: async (id: number) => {
-    const response = await api.get(`/api/artifacts/${id}`)
+  /**
+   * Where artifacts were photographed (EXIF GPS), inside `bbox` ("west,south,east,north")
+   * or anywhere; `total` is how many are in the box, which may be more than returned
+   */
+  locations: async (
+    bbox?: string,
+    options: { scope?: ShareScope; collection_id?: number; limit?: number } = {}
+  ): Promise<{ locations: ArtifactLocation[]; total: number }> => {
+    const response = await api.get<ArtifactLocation[]>('/api/artifacts/locations', {
+      params: { scope: 'all', ...options, bbox },
+    })
+    return { locations: response.data, total: totalCount(response) }
+  },
+  /** Artifacts whose images look most like this one's, most similar first */
+  similar: async (id: number, limit = 6): Promise<SimilarArtifact[]> => {
+    const response = await api.get<SimilarArtifact[]>(`/api/artifacts/${id}/similar`, { params: { limit } })
+    return response.data
+  },
+  /** Owner or staff only */
+  getSharing: async (id: number): Promise<Sharing> => {
+    const response = await api.get<Sharing>(`/api/artifacts/${id}/sharing`)
+    return response.data
+  },
+  setSharing: async (id: number, sharing: Sharing): Promise<Sharing> => {
+    const response = await api.put<Sharing>(`/api/artifacts/${id}/sharing`, sharing)
+    return response.data
+  },
+  verify: async (id: number, verification: VerificationRequest): Promise<ArtifactMutation> => {
+    ensureCan('verify')
+    const response = await api.post<ArtifactMutation>(`/api/artifacts/${id}/verify`, verification)
+    return response.data
+  },
+  /**
+   * Slow tiers are queued on the server; this waits for the job to finish,
+   * telling `onStage` as it moves through its stages.
+   * Results are cached per image and tier unless `refresh` is set.
+   */
+  analyze: async (
+    imageData: string,
+    tier: string = 'fast',
+    signal?: AbortSignal,
+    refresh = false,
+    onStage?: (stage: AnalysisStage) => void
+  ): Promise<AnalysisResult> => {
+    const response = await api.post<AnalysisResult | AnalysisJob>(
+      '/api/analyze',
+      { image_data: imageData, tier, refresh },
+      { signal }
+    )
+    if (response.status !== 202) return response.data as AnalysisResult
+
+    const job = await followJob(response.data as AnalysisJob, signal, onStage)
+    if (job.status === 'failed' || !job.result) {
+      throw new AppError(job.error || 'Analysis failed', 'external_service_error', 502)
+    }
+    return job.result
+  },
+  // One multipart upload for the whole batch, then poll until every image is
+  // finished; onProgress sees each snapshot so items can update as they land
+  batchAnalyze: async (
+    files: File[],
+    tier: string = 'fast',
+    signal?: AbortSignal,
+    onProgress?: (batch: AnalysisBatch) => void
+  ): Promise<AnalysisBatch> => {
+    ensureCan('batch-upload')
+    const form = new FormData()
+    files.forEach((file) => form.append('files', file, file.name))
+    form.append('tier', tier)
+    // The instance default is JSON, which would make axios serialize the form
+    let batch = (await api.post<AnalysisBatch>('/api/analyze/batch', form, {
+      signal,
+      headers: { 'Content-Type': 'multipart/form-data' },
+    })).data
+    onProgress?.(batch)
+    while (batch.status !== 'done') {
+      await pause(ANALYSIS_POLL_MS, signal)
+      batch = (await api.get<AnalysisBatch>(`/api/analyze/batch/${batch.id}`, { signal })).data
+      onProgress?.(batch)
+    }
+    return batch
+  },
+}
+
+// Collections: sharing one shares every artifact in it
+export const collectionApi = {
+  list: async (scope: ShareScope = 'all'): Promise<CollectionInfo[]> => {
+    const response = await api.get<CollectionInfo[]>('/api/collections', { params: { scope } })
     return response.data
   },
-  search: async (query: string) => {
-    const response = await api.get('/api/artifacts/search', { params: { q: query } })
+  create: async (collection: CollectionCreate): Promise<CollectionInfo> => {
+    const response = await api.post<CollectionInfo>('/api/collections', collection)
     return response.data
   },
-  create: async (artifact: any) => {
-    const response = await api.post('/api/artifacts', artifact)
+  remove: async (id: number): Promise<MessageResponse> => {
+    const response = await api.delete<MessageResponse>(`/api/collections/${id}`)
     return response.data
   },
-  update: async (id: number, data: any) => {
-    const response = await api.put(`/api/artifacts/${id}`, data)
+  getSharing: async (id: number): Promise<Sharing> => {
+    const response = await api.get<Sharing>(`/api/collections/${id}/sharing`)
     return response.data
   },
-  verify: async (id: number, verification: { verification_status: string; reason: string; verified_by: string }) => {
-    const response = await api.post(`/api/artifacts/${id}/verify`, verification)
+  setSharing: async (id: number, sharing: Sharing): Promise<Sharing> => {
+    const response = await api.put<Sharing>(`/api/collections/${id}/sharing`, sharing)
     return response.data
   },
-  analyze: async (imageData: string, tier: string = 'fast') => {
-    const response = await api.post('/api/analyze', { image_data: imageData, tier })
+}
+
+// Bundles of artifacts and their images (a ZIP), for moving them between backends
+export const archiveApi = {
+  /** Every artifact matching the gallery filters (paging and sorting don't apply), as one ZIP or as catalogue XML */
+  export: async (uploadedBy?: string, filters: ArtifactFilters = {}, format: ExportFormat = 'bundle'): Promise<Blob> => {
+    const response = await api.get<Blob>('/api/export', {
+      params: { scope: 'all', ...filters, uploaded_by: uploadedBy, format },
+      responseType: 'blob',
+    })
     return response.data
   },
-  batchAnalyze: async (images: string[], tier: string = 'fast') => {
-    const response = await api.post('/api/analyze/batch', { images, tier })
+  import: async (bundle: File, onConflict: ImportConflict = 'skip'): Promise<ImportResult> => {
+    ensureCan('upload')
+    const form = new FormData()
+    form.append('file', bundle, bundle.name)
+    // As for batchAnalyze: keep axios from serializing the form as JSON
+    const response = await api.post<ImportResult>('/api/import', form, {
+      params: { on_conflict: onConflict },
+      headers: { 'Content-Type': 'multipart/form-data' },
+    })
     return response.data
   },
 }
 
 // User management endpoints (to be implemented in backend)
 export const userApi = {
-  getAll: async () => {
-    const response = await api.get('/api/users')
+  getAll: async (): Promise<User[]> => {
+    ensureCan('user-management')
+    const response = await api.get<User[]>('/api/v1/users')
+    return response.data
+  },
+  create: async (user: UserCreate): Promise<UserCreated> => {
+    ensureCan('user-management')
+    const response = await api.post<UserCreated>('/api/v1/users', user)
+    return response.data
+  },
+  unlock: async (username: string): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.post<MessageResponse>(`/api/v1/users/${encodeURIComponent(username)}/unlock`)
+    return response.data
+  },
+  getAuditLogs: async (): Promise<AuditLog[]> => {
+    ensureCan('audit-logs')
+    const response = await api.get<AuditLog[]>('/api/v1/audit-logs')
+    return response.data
+  },
+  updateProfile: async (changes: ProfileUpdate): Promise<User> => {
+    const response = await api.patch<User>('/api/v1/user/profile', changes)
+    return response.data
+  },
+  changePassword: async (username: string, newPassword: string): Promise<MessageResponse> => {
+    const response = await api.post<MessageResponse>('/api/v1/users/change-password', { username, new_password: newPassword })
+    return response.data
+  },
+  getLoginHistory: async (limit = 20): Promise<LoginRecord[]> => {
+    const response = await api.get<LoginRecord[]>('/api/v1/user/logins', { params: { limit } })
+    return response.data
+  },
+  /** This month's analyses against the monthly quota (admins may ask about another user) */
+  getUsage: async (username?: string): Promise<AnalysisUsageInfo> => {
+    const response = await api.get<AnalysisUsageInfo>('/api/usage', { params: { username } })
+    return response.data
+  },
+  /** Everything the server stores about the signed-in account */
+  exportData: async (): Promise<Record<string, unknown>> => {
+    const response = await api.get<Record<string, unknown>>('/api/v1/user/export')
+    return response.data
+  },
+  /** Permanently delete the signed-in account; needs its current password */
+  deleteAccount: async (password: string): Promise<MessageResponse> => {
+    const data: AccountDeletion = { password }
+    const response = await api.delete<MessageResponse>('/api/v1/user', { data })
+    return response.data
+  },
+}
+
+// Admin account management (paginated; total comes from X-Total-Count)
+export const adminUserApi = {
+  stats: async (): Promise<AdminStats> => {
+    ensureCan('user-management')
+    const response = await api.get<AdminStats>('/api/v1/admin/stats')
+    return response.data
+  },
+  list: async (filters: UserFilters = {}): Promise<{ users: User[]; total: number }> => {
+    ensureCan('user-management')
+    const response = await api.get<User[]>('/api/v1/admin/users', { params: filters })
+    return { users: response.data, total: totalCount(response) }
+  },
+  create: async (user: UserCreate): Promise<User> => {
+    ensureCan('user-management')
+    const response = await api.post<User>('/api/v1/admin/users', user)
+    return response.data
+  },
+  update: async (username: string, changes: UserUpdate): Promise<User> => {
+    ensureCan('user-management')
+    const response = await api.patch<User>(`/api/v1/admin/users/${encodeURIComponent(username)}`, changes)
+    return response.data
+  },
+  remove: async (username: string): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.delete<MessageResponse>(`/api/v1/admin/users/${encodeURIComponent(username)}`)
+    return response.data
+  },
+  /** Block sign-in but keep the account and its history */
+  deactivate: async (username: string): Promise<User> => {
+    ensureCan('user-management')
+    const response = await api.post<User>(`/api/v1/admin/users/${encodeURIComponent(username)}/deactivate`)
+    return response.data
+  },
+  /** Revoke every session the user holds and close their open tabs */
+  revokeSessions: async (username: string): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.delete<MessageResponse>(`/api/v1/admin/users/${encodeURIComponent(username)}/sessions`)
+    return response.data
+  },
+  reactivate: async (username: string): Promise<User> => {
+    ensureCan('user-management')
+    const response = await api.post<User>(`/api/v1/admin/users/${encodeURIComponent(username)}/reactivate`)
+    return response.data
+  },
+}
+
+// API keys for scripts and service accounts
+// Frontend preferences saved to the signed-in account (see utils/settings)
+export const settingsApi = {
+  get: async (): Promise<Record<string, unknown>> => {
+    const response = await api.get<Record<string, unknown>>('/api/v1/user/settings')
+    return response.data
+  },
+  save: async (settings: Record<string, unknown>): Promise<Record<string, unknown>> => {
+    const response = await api.put<Record<string, unknown>>('/api/v1/user/settings', settings)
+    return response.data
+  },
+}
+
+// Registration invites (admin only)
+export const inviteApi = {
+  list: async (): Promise<InviteInfo[]> => {
+    ensureCan('user-management')
+    const response = await api.get<InviteInfo[]>('/api/v1/admin/invites')
+    return response.data
+  },
+  create: async (invite: InviteCreate): Promise<InviteCreated> => {
+    ensureCan('user-management')
+    const response = await api.post<InviteCreated>('/api/v1/admin/invites', invite)
+    return response.data
+  },
+  revoke: async (id: number): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.delete<MessageResponse>(`/api/v1/admin/invites/${id}`)
+    return response.data
+  },
+}
+
+// Organizations: every user sees their own; admins create and delete them
+export const orgApi = {
+  list: async (): Promise<OrganizationInfo[]> => {
+    const response = await api.get<OrganizationInfo[]>('/api/v1/orgs')
+    return response.data
+  },
+  create: async (org: OrganizationCreate): Promise<OrganizationInfo> => {
+    ensureCan('user-management')
+    const response = await api.post<OrganizationInfo>('/api/v1/orgs', org)
+    return response.data
+  },
+  remove: async (slug: string): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.delete<MessageResponse>(`/api/v1/orgs/${encodeURIComponent(slug)}`)
     return response.data
   },
-  create: async (user: any) => {
-    const response = await api.post('/api/users', user)
+  members: async (slug: string): Promise<OrgMember[]> => {
+    const response = await api.get<OrgMember[]>(`/api/v1/orgs/${encodeURIComponent(slug)}/members`)
     return response.data
   },
-  getAuditLogs: async () => {
-    const response = await api.get('/api/audit-logs')
+  addMember: async (slug: string, member: OrgMemberAdd): Promise<OrgMember> => {
+    const response = await api.post<OrgMember>(`/api/v1/orgs/${encodeURIComponent(slug)}/members`, member)
     return response.data
   },
-  changePassword: async (username: string, newPassword: string) => {
-    const response = await api.post('/api/users/change-password', { username, new_password: newPassword })
+  setRank: async (slug: string, username: string, rank: OrgRank): Promise<OrgMember> => {
+    const update: OrgMemberUpdate = { rank }
+    const response = await api.patch<OrgMember>(
+      `/api/v1/orgs/${encodeURIComponent(slug)}/members/${encodeURIComponent(username)}`,
+      update
+    )
     return response.data
   },
-}
\ No newline at end of file
+  removeMember: async (slug: string, username: string): Promise<MessageResponse> => {
+    const response = await api.delete<MessageResponse>(
+      `/api/v1/orgs/${encodeURIComponent(slug)}/members/${encodeURIComponent(username)}`
+    )
+    return response.data
+  },
+}
+
+export const ipRuleApi = {
+  list: async (): Promise<IpRuleInfo[]> => {
+    ensureCan('user-management')
+    const response = await api.get<IpRuleInfo[]>('/api/v1/admin/ip-rules')
+    return response.data
+  },
+  create: async (rule: IpRuleCreate): Promise<IpRuleInfo> => {
+    ensureCan('user-management')
+    const response = await api.post<IpRuleInfo>('/api/v1/admin/ip-rules', rule)
+    return response.data
+  },
+  remove: async (id: number): Promise<MessageResponse> => {
+    ensureCan('user-management')
+    const response = await api.delete<MessageResponse>(`/api/v1/admin/ip-rules/${id}`)
+    return response.data
+  },
+}
+
+export const apiKeyApi = {
+  list: async (): Promise<ApiKeyInfo[]> => {
+    const response = await api.get<ApiKeyInfo[]>('/api/v1/api-keys')
+    return response.data
+  },
+  create: async (name: string): Promise<ApiKeyCreated> => {
+    const response = await api.post<ApiKeyCreated>('/api/v1/api-keys', { name })
+    return response.data
+  },
+  revoke: async (id: number): Promise<MessageResponse> => {
+    const response = await api.delete<MessageResponse>(`/api/v1/api-keys/${id}`)
+    return response.data
+  },
+}
+
+// The tag vocabulary, for autocomplete; merging and renaming are admin only
+export const tagApi = {
+  /** Most used first; `q` keeps tags containing it */
+  list: async (q?: string, limit = 100): Promise<TagCount[]> => {
+    const response = await api.get<TagCount[]>('/api/tags', { params: { q: q || undefined, limit } })
+    return response.data
+  },
+  merge: async (sources: string[], target: string): Promise<TagChange> => {
+    ensureCan('tag-management')
+    const response = await api.post<TagChange>('/api/tags/merge', { sources, target })
+    return response.data
+  },
+  rename: async (tag: string, newName: string): Promise<TagChange> => {
+    ensureCan('tag-management')
+    const response = await api.post<TagChange>('/api/tags/rename', { tag, new_name: newName })
+    return response.data
+  },
+}
+
+// Moderation (curators, i.e. archive staff); every action is audit-logged
+export const moderationApi = {
+  /** Artifacts with open flags, the one waiting longest first */
+  queue: async (page: { limit?: number; offset?: number } = {}): Promise<{ items: ModerationQueueItem[]; total: number }> => {
+    ensureCan('moderation')
+    const response = await api.get<ModerationQueueItem[]>('/api/moderation/queue', { params: page })
+    return { items: response.data, total: totalCount(response) }
+  },
+  flag: async (artifactId: number, reason: string): Promise<ArtifactFlag> => {
+    ensureCan('moderation')
+    const response = await api.post<ArtifactFlag>(`/api/artifacts/${artifactId}/flags`, { reason })
+    return response.data
+  },
+  /** Open and resolved, newest first */
+  flags: async (artifactId: number): Promise<ArtifactFlag[]> => {
+    ensureCan('moderation')
+    const response = await api.get<ArtifactFlag[]>(`/api/artifacts/${artifactId}/flags`)
+    return response.data
+  },
+  resolve: async (flagId: number, resolution: string): Promise<ArtifactFlag> => {
+    ensureCan('moderation')
+    const response = await api.post<ArtifactFlag>(`/api/moderation/flags/${flagId}/resolve`, { resolution })
+    return response.data
+  },
+  overrideIdentification: async (artifactId: number, override: IdentificationOverride): Promise<Artifact> => {
+    ensureCan('moderation')
+    const response = await api.post<Artifact>(`/api/artifacts/${artifactId}/identification`, override)
+    return response.data
+  },
+  /** Nobody can edit, share or delete it until unlocked */
+  lock: async (artifactId: number, reason: string): Promise<Artifact> => {
+    ensureCan('moderation')
+    const response = await api.post<Artifact>(`/api/artifacts/${artifactId}/lock`, { reason })
+    return response.data
+  },
+  unlock: async (artifactId: number): Promise<Artifact> => {
+    ensureCan('moderation')
+    const response = await api.delete<Artifact>(`/api/artifacts/${artifactId}/lock`)
+    return response.data
+  },
+}
+
+// Webhooks: called when one of your queued analyses finishes or fails
+export const webhookApi = {
+  list: async (): Promise<WebhookInfo[]> => {
+    const response = await api.get<WebhookInfo[]>('/api/webhooks')
+    return response.data
+  },
+  create: async (webhook: WebhookCreate): Promise<WebhookCreated> => {
+    const response = await api.post<WebhookCreated>('/api/webhooks', webhook)
+    return response.data
+  },
+  remove: async (id: number): Promise<MessageResponse> => {
+    const response = await api.delete<MessageResponse>(`/api/webhooks/${id}`)
+    return response.data
+  },
+  ping: async (id: number): Promise<MessageResponse> => {
+    const response = await api.post<MessageResponse>(`/api/webhooks/${id}/ping`)
+    return response.data
+  },
+}
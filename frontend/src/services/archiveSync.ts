@@ -0,0 +1,69 @@
+import { artifactApi } from './api'
+import { Artifact } from '../types'
+
+/**
+ * Client-side copy of the archive kept up to date with
+ * POST /api/artifacts/sync. The first call pulls everything; later calls only
+ * fetch artifacts created or changed since the previous sync and merge them
+ * into the local copy, dropping the held ones the server says are gone (or
+ * no longer visible). Each uploader scope (one user's uploads, or everything)
+ * is tracked separately.
+ */
+
+interface ArchiveCopy {
+  artifactsById: Map<number, Artifact>
+  syncedAt: string | null
+  inFlight: Promise<Artifact[]> | null
+}
+
+const ALL = '*'
+let copies = new Map<string, ArchiveCopy>()
+
+const copyFor = (scope: string): ArchiveCopy => {
+  let copy = copies.get(scope)
+  if (!copy) {
+    copy = { artifactsById: new Map(), syncedAt: null, inFlight: null }
+    copies.set(scope, copy)
+  }
+  return copy
+}
+
+const runSync = async (copy: ArchiveCopy, uploadedBy?: string): Promise<Artifact[]> => {
+  const knownIds = Array.from(copy.artifactsById.keys())
+  const changes = await artifactApi.sync(copy.syncedAt ?? undefined, uploadedBy, knownIds)
+
+  // Apply deletes before upserts so a reused id from a new upload survives
+  for (const id of changes.deleted_ids) {
+    copy.artifactsById.delete(id)
+  }
+  for (const artifact of changes.artifacts) {
+    copy.artifactsById.set(artifact.id, artifact)
+  }
+
+  copy.syncedAt = changes.synced_at
+  return Array.from(copy.artifactsById.values())
+}
+
+export const archiveSync = {
+  /** Pull pending changes and return the merged archive, optionally limited to one uploader */
+  sync: (uploadedBy?: string): Promise<Artifact[]> => {
+    const copy = copyFor(uploadedBy ?? ALL)
+    // Coalesce concurrent callers onto a single request
+    if (!copy.inFlight) {
+      copy.inFlight = runSync(copy, uploadedBy).finally(() => {
+        copy.inFlight = null
+      })
+    }
+    return copy.inFlight
+  },
+
+  /** Drop a locally deleted artifact without waiting for the next sync */
+  remove: (id: number) => {
+    copies.forEach((copy) => copy.artifactsById.delete(id))
+  },
+
+  /** Forget everything, forcing the next sync to be a full pull */
+  reset: () => {
+    copies = new Map()
+  },
+}
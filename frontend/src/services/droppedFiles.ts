@@ -0,0 +1,30 @@
+/**
+ * Images dropped onto the app window (components/Layout.tsx) on their way
+ * to the batch upload's queue. Files dropped while no batch upload is open
+ * wait here until one starts listening.
+ */
+
+type Listener = (files: File[]) => void
+
+let waiting: File[] = []
+let listener: Listener | null = null
+
+export const droppedFiles = {
+  add: (files: File[]) => {
+    if (listener) listener(files)
+    else waiting.push(...files)
+  },
+
+  /** Take dropped files as they come, starting with any already waiting; returns the unsubscribe */
+  listen: (handler: Listener) => {
+    listener = handler
+    if (waiting.length > 0) {
+      const files = waiting
+      waiting = []
+      handler(files)
+    }
+    return () => {
+      if (listener === handler) listener = null
+    }
+  },
+}
@@ -0,0 +1,176 @@
+import { useEffect, useState } from 'react'
+import { api, imageUrl } from './api'
+
+/**
+ * Artifact images kept on disk (Cache Storage) across visits, keyed by
+ * artifact id and image hash, so re-opening the archive doesn't fetch them
+ * again. Past MAX_CACHE_BYTES the least recently shown are evicted. Within
+ * a page, each image is read from disk once and shown from an object URL,
+ * freed when the last image showing it unmounts.
+ * Images are fetched through the API client, since only images of artifacts
+ * the caller can see are served and <img> can't send a bearer token. Without
+ * Cache Storage (plain http://) they are fetched the same way, just not kept.
+ */
+
+interface IndexEntry {
+  size: number
+  usedAt: number
+}
+
+const CACHE_NAME = 'artifact-images'
+// Sizes and last use of the cached images, for eviction
+const INDEX_KEY = 'imageCacheIndex'
+const MAX_CACHE_BYTES = 200 * 1024 * 1024
+const IMAGE_PATH = /^\/api\/images\/([^/?#]+)$/
+
+interface ObjectUrl {
+  url: Promise<string>
+  // Mounted images showing it; revoked when the last one goes
+  users: number
+}
+
+const objectUrls = new Map<string, ObjectUrl>()
+
+const supported = () => typeof caches !== 'undefined'
+
+const keyFor = (artifactId: number, hash: string) => `${artifactId}:${hash}`
+// Cache Storage keys are requests; entries live under a path of their own
+const requestFor = (key: string) => new Request(`/artifact-image-cache/${encodeURIComponent(key)}`)
+
+const readIndex = (): Record<string, IndexEntry> => {
+  try {
+    return JSON.parse(localStorage.getItem(INDEX_KEY) || '{}')
+  } catch (e) {
+    return {}
+  }
+}
+
+const writeIndex = (index: Record<string, IndexEntry>) => localStorage.setItem(INDEX_KEY, JSON.stringify(index))
+
+// Drop the least recently used entries from the index until it fits; returns their keys
+const evictFrom = (index: Record<string, IndexEntry>): string[] => {
+  let total = Object.values(index).reduce((sum, entry) => sum + entry.size, 0)
+  const evicted: string[] = []
+  for (const [key, entry] of Object.entries(index).sort(([, a], [, b]) => a.usedAt - b.usedAt)) {
+    if (total <= MAX_CACHE_BYTES) break
+    delete index[key]
+    total -= entry.size
+    evicted.push(key)
+  }
+  return evicted
+}
+
+const fetchBlob = async (path: string): Promise<Blob> => (await api.get<Blob>(path, { responseType: 'blob' })).data
+
+const loadBlob = async (key: string, path: string): Promise<Blob> => {
+  if (!supported()) return fetchBlob(path)
+  const cache = await caches.open(CACHE_NAME)
+  const hit = await cache.match(requestFor(key))
+  if (hit) {
+    const blob = await hit.blob()
+    const index = readIndex()
+    index[key] = { size: blob.size, usedAt: Date.now() }
+    writeIndex(index)
+    return blob
+  }
+
+  const blob = await fetchBlob(path)
+  await cache.put(requestFor(key), new Response(blob, { headers: { 'Content-Type': blob.type } }))
+  // Re-read: other images may have been added while this one downloaded
+  const index = readIndex()
+  index[key] = { size: blob.size, usedAt: Date.now() }
+  const evicted = evictFrom(index)
+  writeIndex(index)
+  await Promise.all(evicted.map((old) => cache.delete(requestFor(old))))
+  return blob
+}
+
+const revoke = (entry: ObjectUrl) =>
+  entry.url.then((url) => url.startsWith('blob:') && URL.revokeObjectURL(url), () => undefined)
+
+const forget = (matches: (key: string) => boolean) => {
+  const index = readIndex()
+  const keys = Object.keys(index).filter(matches)
+  keys.forEach((key) => delete index[key])
+  writeIndex(index)
+  objectUrls.forEach((entry, key) => {
+    if (!matches(key)) return
+    objectUrls.delete(key)
+    revoke(entry)
+  })
+  return keys
+}
+
+export const imageCache = {
+  /**
+   * A URL to show an artifact's image from: an object URL of the cached copy,
+   * or the server's URL for images that aren't cacheable (demo data URLs).
+   * Call `release` once it's no longer shown, so the object URL can be freed.
+   */
+  load: (artifactId: number, path: string): { url: Promise<string>; release: () => void } => {
+    const hash = path.match(IMAGE_PATH)?.[1]
+    const url = imageUrl(path) as string
+    if (!hash) return { url: Promise.resolve(url), release: () => undefined }
+    const key = keyFor(artifactId, hash)
+    let entry = objectUrls.get(key)
+    if (!entry) {
+      entry = {
+        url: loadBlob(key, path).then(URL.createObjectURL, (error) => {
+          console.warn('Could not load the image, falling back to its URL:', error)
+          return url
+        }),
+        users: 0,
+      }
+      objectUrls.set(key, entry)
+    }
+    entry.users += 1
+    const loaded = entry
+    let released = false
+    const release = () => {
+      if (released) return
+      released = true
+      loaded.users -= 1
+      // Already gone if the artifact was removed or the cache cleared meanwhile
+      if (loaded.users > 0 || objectUrls.get(key) !== loaded) return
+      objectUrls.delete(key)
+      revoke(loaded)
+    }
+    return { url: loaded.url, release }
+  },
+
+  /** Drop a deleted artifact's images */
+  remove: async (artifactId: number) => {
+    const keys = forget((key) => key.startsWith(`${artifactId}:`))
+    if (!supported() || keys.length === 0) return
+    const cache = await caches.open(CACHE_NAME)
+    await Promise.all(keys.map((key) => cache.delete(requestFor(key))))
+  },
+
+  /** Empty the cache, e.g. on sign-out so the next user of the browser can't see them */
+  clear: async () => {
+    forget(() => true)
+    localStorage.removeItem(INDEX_KEY)
+    if (supported()) await caches.delete(CACHE_NAME)
+  },
+}
+
+/** The src for an artifact's image, undefined while it loads */
+export function useCachedImage(artifactId: number, path?: string | null): string | undefined {
+  const [src, setSrc] = useState<string>()
+
+  useEffect(() => {
+    setSrc(undefined)
+    if (!path) return
+    let current = true
+    const { url, release } = imageCache.load(artifactId, path)
+    url.then((loaded) => {
+      if (current) setSrc(loaded)
+    })
+    return () => {
+      current = false
+      release()
+    }
+  }, [artifactId, path])
+
+  return src
+}
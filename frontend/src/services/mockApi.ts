@@ -0,0 +1,1371 @@
+import { AxiosAdapter, AxiosError, AxiosResponse, CanceledError, InternalAxiosRequestConfig } from 'axios'
+import { AnalysisBatch, AnalysisBatchItem, AnalysisJob, AnalysisResult, ApiKeyInfo, Artifact, ArtifactFlag, ArtifactRevision, ArtifactSort, AuditLog, CollectionInfo, FailedLogin, InviteInfo, IpRuleInfo, LoginRecord, OrgRank, RevisionAction, TrashedArtifact, User, Visibility, WebhookInfo } from '../types'
+
+/**
+ * In-process stand-in for the FastAPI backend, used when the app is built
+ * with VITE_DEMO_MODE=true. Every request is answered from a seeded archive
+ * held in memory, so the UI can be demonstrated without any server running.
+ * State resets on page reload.
+ */
+
+export const isDemoMode = import.meta.env.VITE_DEMO_MODE === 'true'
+
+// Simple colored placeholder so seeded artifacts have something to show
+const placeholderImage = (label: string, color: string) =>
+  'data:image/svg+xml,' +
+  encodeURIComponent(
+    `<svg xmlns="http://www.w3.org/2000/svg" width="400" height="300">` +
+      `<rect fill="${color}" width="400" height="300"/>` +
+      `<text fill="#fff" font-family="sans-serif" font-size="28" x="50%" y="50%" text-anchor="middle" dy=".3em">${label}</text>` +
+      `</svg>`
+  )
+
+const demoUsers: (User & { password: string })[] = [
+  { username: 'admin', name: 'Demo Admin', email: 'admin@example.com', role: 'admin', password: 'admin' },
+  { username: 'field', name: 'Demo Field Engineer', email: 'field@example.com', role: 'field', password: 'field' },
+  { username: 'onsite', name: 'Demo Lab Engineer', email: 'onsite@example.com', role: 'onsite', password: 'onsite' },
+]
+
+const seedArtifacts: Artifact[] = [
+  {
+    id: 1,
+    name: 'Roman Amphora Fragment',
+    description: 'Neck and handle of a terracotta amphora, likely used for wine transport around the 1st century AD.',
+    tags: ['pottery', 'roman', 'terracotta'],
+    age: '1st century AD, Roman Empire',
+    tier: 'balanced',
+    confidence: 0.87,
+    uploaded_at: '2024-03-02T10:15:00Z',
+    uploaded_by: 'field',
+    verification_status: 'verified',
+    verified_by: 'onsite',
+    captured_at: '2024-03-01T15:42:10',
+    latitude: 41.89021,
+    longitude: 12.49223,
+    image_url: placeholderImage('Amphora', '#b5651d'),
+  },
+  {
+    id: 2,
+    name: 'Flint Arrowhead',
+    description: 'Bifacially worked flint projectile point with a tanged base, Neolithic.',
+    tags: ['lithic', 'flint', 'neolithic'],
+    age: 'Neolithic',
+    tier: 'fast',
+    confidence: 0.79,
+    uploaded_at: '2024-03-05T14:40:00Z',
+    uploaded_by: 'field',
+    verification_status: 'pending',
+    captured_at: '2024-03-04T11:03:55',
+    latitude: 51.17886,
+    longitude: -1.82622,
+    image_url: placeholderImage('Arrowhead', '#6b6b6b'),
+  },
+  {
+    id: 3,
+    name: 'Bronze Fibula',
+    description: 'Bow-shaped bronze brooch used to fasten garments, Iron Age.',
+    tags: ['metal', 'bronze', 'jewelry'],
+    age: 'Iron Age',
+    tier: 'thorough',
+    confidence: 0.91,
+    uploaded_at: '2024-03-09T09:05:00Z',
+    uploaded_by: 'admin',
+    verification_status: 'verified',
+    verified_by: 'admin',
+    image_url: placeholderImage('Fibula', '#8c6d1f'),
+  },
+  {
+    id: 4,
+    name: 'Glass Bead',
+    description: 'Small blue glass bead with an eye motif.',
+    tags: ['glass', 'jewelry'],
+    age: 'Early medieval',
+    tier: 'fast',
+    confidence: 0.68,
+    uploaded_at: '2024-03-12T16:20:00Z',
+    uploaded_by: 'field',
+    verification_status: 'pending',
+    image_url: placeholderImage('Bead', '#1f5f8c'),
+  },
+]
+
+const cannedAnalyses: Omit<AnalysisResult, 'tier'>[] = [
+  {
+    name: 'Ceramic Sherd',
+    description: 'Fragment of a wheel-thrown ceramic vessel with a red slip, likely Late Roman.',
+    confidence: 0.82,
+    method: 'Demo Analyzer',
+    analysis_time: '0.8s',
+  },
+  {
+    name: 'Iron Nail',
+    description: 'Corroded hand-forged iron nail with a square shank, medieval.',
+    confidence: 0.74,
+    method: 'Demo Analyzer',
+    analysis_time: '0.6s',
+  },
+  {
+    name: 'Bone Comb',
+    description: 'Double-sided comb carved from antler or bone, Early Medieval.',
+    confidence: 0.88,
+    method: 'Demo Analyzer',
+    analysis_time: '1.1s',
+  },
+]
+
+const state = {
+  // Like artifacts from before ownership on the backend: their uploader's, visible to all
+  artifacts: seedArtifacts.map((a) => ({ ...a, owner_id: a.uploaded_by, visibility: 'public' as Visibility })),
+  users: demoUsers.map((u) => ({ ...u })),
+  auditLogs: [] as AuditLog[],
+  apiKeys: [] as ApiKeyInfo[],
+  webhooks: [] as WebhookInfo[],
+  invites: [] as (InviteInfo & { code: string })[],
+  ipRules: [] as IpRuleInfo[],
+  orgs: [] as { id: number; slug: string; name: string; created_at: string; members: { username: string; rank: OrgRank; joined_at: string }[] }[],
+  logins: [] as (LoginRecord & { username: string })[],
+  failedLogins: [] as FailedLogin[],
+  settings: {} as Record<string, Record<string, unknown>>,
+  nextId: seedArtifacts.length + 1,
+  analysisCount: 0,
+  analysisJobs: [] as (AnalysisJob & { cacheKey: string })[],
+  analysisBatches: [] as AnalysisBatch[],
+  // Analyses this session by username and tier, for GET /api/usage
+  analysisUsage: {} as Record<string, Record<string, number>>,
+  // Results by tier and image, like the backend's analysis cache
+  analysisCache: {} as Record<string, AnalysisResult>,
+  collections: [] as CollectionInfo[],
+  // Deleted artifacts, most recent first; the demo never purges them by itself
+  trash: [] as TrashedArtifact[],
+  // Curators' flags, open and resolved, oldest first
+  flags: [] as ArtifactFlag[],
+  // Changes to artifacts, newest first; the demo tracks only the fields it edits
+  revisions: [] as ArtifactRevision[],
+  // Usernames each artifact or collection is shared with, by "artifact:1" / "collection:1"
+  shares: {} as Record<string, string[]>,
+}
+
+const logAction = (username: string, action: string) => {
+  state.auditLogs.unshift({
+    timestamp: new Date().toISOString().replace('T', ' ').slice(0, 19),
+    username,
+    action,
+  })
+  state.auditLogs = state.auditLogs.slice(0, 50)
+}
+
+const toListItem = (a: Artifact): Artifact => ({ ...a, thumbnail_url: a.image_url })
+
+// For GET /api/artifacts, /search and /sync: include_images=false links only the thumbnail
+const listItemsFor = (query: Record<string, any>) => (a: Artifact): Artifact => {
+  const item = toListItem(a)
+  if (String(query.include_images) === 'false') delete item.image_url
+  return item
+}
+
+const analyze = (tier: string): AnalysisResult => {
+  const canned = cannedAnalyses[state.analysisCount % cannedAnalyses.length]
+  const runnerUp = cannedAnalyses[(state.analysisCount + 1) % cannedAnalyses.length]
+  state.analysisCount += 1
+  return {
+    ...canned,
+    tier,
+    provider: 'demo',
+    candidates: [canned, { ...runnerUp, confidence: runnerUp.confidence / 4 }].map(({ name, description, confidence }) => ({
+      name,
+      description,
+      confidence,
+    })),
+  }
+}
+
+class MockHttpError extends Error {
+  constructor(public status: number, public detail: string, public extra: Record<string, unknown> = {}) {
+    super(detail)
+  }
+}
+
+type Handler = (
+  params: Record<string, string>,
+  body: any,
+  query: Record<string, any>,
+  headers: Record<string, any>
+) => any
+
+const routes: { method: string; pattern: RegExp; handler: Handler }[] = []
+
+const route = (method: string, path: string, handler: Handler) => {
+  const pattern = new RegExp('^' + path.replace(/:(\w+)/g, '(?<$1>[^/]+)') + '$')
+  routes.push({ method, pattern, handler })
+}
+
+const findArtifact = (id: string) => {
+  const artifact = state.artifacts.find((a) => a.id === Number(id))
+  if (!artifact) throw new MockHttpError(404, 'Artifact not found')
+  return artifact
+}
+
+// A curator's lock refuses every change, like the backend's 423
+const unlockedArtifact = (id: string) => {
+  const artifact = findArtifact(id)
+  if (artifact.locked_at) {
+    throw new MockHttpError(423, `Artifact is locked by ${artifact.locked_by}: ${artifact.lock_reason}`, { code: 'artifact_locked' })
+  }
+  return artifact
+}
+
+route('post', '/api/v1/auth/login', (_p, body) => {
+  const user = state.users.find((u) => u.username === body.username && u.password === body.password)
+  if (!user) {
+    if (state.users.some((u) => u.username === body.username)) {
+      state.failedLogins.unshift({ username: body.username, attempted_at: new Date().toISOString(), ip_address: '127.0.0.1' })
+    }
+    throw new MockHttpError(401, 'Invalid username or password')
+  }
+  if (user.active === false) throw new MockHttpError(403, 'This account has been deactivated')
+  logAction(user.username, 'Logged in')
+  state.logins.unshift({
+    username: user.username,
+    logged_in_at: new Date().toISOString(),
+    ip_address: '127.0.0.1',
+    user_agent: navigator.userAgent,
+    method: 'password',
+  })
+  const { password: _password, ...info } = user
+  return {
+    ...info,
+    access_token: `demo-token.${user.username}`,
+    refresh_token: `demo-refresh.${user.username}`,
+    token_type: 'bearer',
+    expires_in: 3600,
+  }
+})
+
+route('post', '/api/v1/auth/refresh', (_p, body) => {
+  const username = String(body.refresh_token || '').replace(/^demo-refresh\./, '')
+  if (!state.users.some((u) => u.username === username)) throw new MockHttpError(401, 'Invalid refresh token')
+  return {
+    access_token: `demo-token.${username}`,
+    refresh_token: `demo-refresh.${username}`,
+    token_type: 'bearer',
+    expires_in: 3600,
+  }
+})
+
+// No external sign-in in demo mode
+route('get', '/api/v1/auth/oauth/providers', () => [])
+
+// The demo archive ships with an admin, so setup is never needed
+route('get', '/api/v1/auth/bootstrap', () => ({ required: false }))
+route('post', '/api/v1/auth/bootstrap', () => {
+  throw new MockHttpError(409, 'Setup has already been completed')
+})
+
+route('get', '/api/v1/auth/register', () => ({ invite_only: true }))
+
+route('post', '/api/v1/auth/register', (_p, body) => {
+  const invite = state.invites.find((i) => i.code === String(body.invite_code || '').trim())
+  if (!invite || invite.used_at || invite.revoked_at) throw new MockHttpError(400, 'Invalid or already used invite code')
+  if (invite.email && invite.email.toLowerCase() !== String(body.email).toLowerCase()) {
+    throw new MockHttpError(400, 'This invite is for a different email address')
+  }
+  if (state.users.some((u) => u.username === body.username)) throw new MockHttpError(409, 'Username already exists')
+  state.users.push({
+    username: body.username,
+    name: body.name,
+    email: body.email,
+    password: body.password,
+    role: invite.role,
+  })
+  invite.used_by = body.username
+  invite.used_at = new Date().toISOString()
+  logAction(body.username, 'Registered with an invite')
+  return { message: 'Account created', username: body.username }
+})
+
+route('get', '/api/v1/admin/invites', () => state.invites.map(({ code: _code, ...invite }) => invite))
+
+route('post', '/api/v1/admin/invites', (_p, body, _q, headers) => {
+  const now = new Date()
+  const code = `inv_demo${Math.random().toString(36).slice(2)}`
+  const invite = {
+    id: state.invites.length + 1,
+    prefix: code.slice(0, 8),
+    role: body.role || 'user',
+    email: body.email || null,
+    created_by: currentUser(headers).username,
+    created_at: now.toISOString(),
+    expires_at: new Date(now.getTime() + 7 * 24 * 3600 * 1000).toISOString(),
+    used_by: null,
+    used_at: null,
+    revoked_at: null,
+    code,
+  }
+  state.invites.unshift(invite)
+  return invite
+})
+
+route('delete', '/api/v1/admin/invites/:id', ({ id }) => {
+  const invite = state.invites.find((i) => i.id === Number(id))
+  if (!invite || invite.used_at || invite.revoked_at) throw new MockHttpError(404, 'Invite not found or already used')
+  invite.revoked_at = new Date().toISOString()
+  return { message: 'Invite revoked' }
+})
+
+// Organizations: admins see and manage all; members only their own
+type MockOrg = (typeof state.orgs)[number]
+
+const orgInfo = (org: MockOrg, username: string) => ({
+  id: org.id,
+  slug: org.slug,
+  name: org.name,
+  created_at: org.created_at,
+  member_count: org.members.length,
+  rank: org.members.find((m) => m.username === username)?.rank ?? null,
+})
+
+const orgMember = (member: MockOrg['members'][number]) => {
+  const user = state.users.find((u) => u.username === member.username)
+  return { ...member, name: user?.name ?? member.username, email: user?.email ?? '' }
+}
+
+const findOrg = (slug: string, headers: Record<string, any>) => {
+  const user = currentUser(headers)
+  const org = state.orgs.find((o) => o.slug === slug)
+  if (!org || (user.role !== 'admin' && !org.members.some((m) => m.username === user.username))) {
+    throw new MockHttpError(404, 'Organization not found')
+  }
+  return org
+}
+
+route('get', '/api/v1/orgs', (_p, _b, _q, headers) => {
+  const user = currentUser(headers)
+  return state.orgs
+    .filter((o) => user.role === 'admin' || o.members.some((m) => m.username === user.username))
+    .map((o) => orgInfo(o, user.username))
+})
+
+route('post', '/api/v1/orgs', (_p, body, _q, headers) => {
+  const user = currentUser(headers)
+  if (state.orgs.some((o) => o.slug === body.slug)) throw new MockHttpError(409, 'Another organization already uses that slug')
+  const owner = body.owner || user.username
+  if (!state.users.some((u) => u.username === owner)) throw new MockHttpError(400, `No active user named ${owner}`)
+  const now = new Date().toISOString()
+  const org = { id: state.orgs.length + 1, slug: body.slug, name: body.name, created_at: now, members: [{ username: owner, rank: 'owner' as OrgRank, joined_at: now }] }
+  state.orgs.push(org)
+  logAction(user.username, `Created organization ${org.slug} owned by ${owner}`)
+  return orgInfo(org, user.username)
+})
+
+route('get', '/api/v1/orgs/:slug', ({ slug }, _b, _q, headers) => orgInfo(findOrg(slug, headers), currentUser(headers).username))
+
+route('delete', '/api/v1/orgs/:slug', ({ slug }, _b, _q, headers) => {
+  const org = findOrg(slug, headers)
+  state.orgs.splice(state.orgs.indexOf(org), 1)
+  return { message: `Organization ${slug} deleted` }
+})
+
+route('get', '/api/v1/orgs/:slug/members', ({ slug }, _b, _q, headers) => findOrg(slug, headers).members.map(orgMember))
+
+route('post', '/api/v1/orgs/:slug/members', ({ slug }, body, _q, headers) => {
+  const org = findOrg(slug, headers)
+  if (!state.users.some((u) => u.username === body.username)) throw new MockHttpError(404, `No active user named ${body.username}`)
+  if (org.members.some((m) => m.username === body.username)) throw new MockHttpError(409, `${body.username} is already a member`)
+  const member = { username: body.username, rank: (body.rank || 'member') as OrgRank, joined_at: new Date().toISOString() }
+  org.members.push(member)
+  return orgMember(member)
+})
+
+route('patch', '/api/v1/orgs/:slug/members/:username', ({ slug, username }, body, _q, headers) => {
+  const org = findOrg(slug, headers)
+  const member = org.members.find((m) => m.username === username)
+  if (!member) throw new MockHttpError(404, `${username} isn't a member`)
+  if (member.rank === 'owner' && body.rank !== 'owner' && org.members.filter((m) => m.rank === 'owner').length <= 1) {
+    throw new MockHttpError(409, 'An organization needs at least one owner')
+  }
+  member.rank = body.rank
+  return orgMember(member)
+})
+
+route('delete', '/api/v1/orgs/:slug/members/:username', ({ slug, username }, _b, _q, headers) => {
+  const org = findOrg(slug, headers)
+  const member = org.members.find((m) => m.username === username)
+  if (!member) throw new MockHttpError(404, `${username} isn't a member`)
+  if (member.rank === 'owner' && org.members.filter((m) => m.rank === 'owner').length <= 1) {
+    throw new MockHttpError(409, 'An organization needs at least one owner')
+  }
+  org.members.splice(org.members.indexOf(member), 1)
+  return { message: `${username} removed from ${slug}` }
+})
+
+route('get', '/api/v1/admin/ip-rules', () => state.ipRules)
+
+route('post', '/api/v1/admin/ip-rules', (_p, body, _q, headers) => {
+  const cidr = String(body.cidr || '').trim()
+  if (!/^[0-9a-fA-F.:]+(\/\d{1,3})?$/.test(cidr)) {
+    throw new MockHttpError(422, 'Must be an IP address or CIDR range, e.g. 10.20.0.0/16')
+  }
+  const rule: IpRuleInfo = {
+    id: Math.max(0, ...state.ipRules.map((r) => r.id ?? 0)) + 1,
+    cidr: cidr.includes('/') ? cidr : `${cidr}/${cidr.includes(':') ? 128 : 32}`,
+    action: body.action === 'deny' ? 'deny' : 'allow',
+    note: body.note || null,
+    created_by: currentUser(headers).username,
+    created_at: new Date().toISOString(),
+    source: 'admin',
+  }
+  state.ipRules.push(rule)
+  logAction(rule.created_by!, `Added IP ${rule.action} rule for ${rule.cidr}`)
+  return rule
+})
+
+route('delete', '/api/v1/admin/ip-rules/:id', ({ id }) => {
+  const index = state.ipRules.findIndex((r) => r.id === Number(id))
+  if (index < 0) throw new MockHttpError(404, 'IP rule not found')
+  state.ipRules.splice(index, 1)
+  return { message: 'IP rule removed' }
+})
+
+route('post', '/api/v1/auth/password/forgot', () => ({
+  message: 'If that account exists, a reset link has been sent',
+}))
+
+route('get', '/api/v1/auth/password/policy', () => ({
+  min_length: 8,
+  max_length: 128,
+  require_uppercase: false,
+  require_lowercase: false,
+  require_digit: false,
+  require_symbol: false,
+  check_breached: false,
+}))
+
+route('post', '/api/v1/auth/password/reset', () => {
+  throw new MockHttpError(400, 'Password reset links are not sent in demo mode')
+})
+
+const currentUser = (headers: Record<string, any>) => {
+  const token = String(headers.Authorization || '').replace(/^Bearer /, '')
+  const user = state.users.find((u) => token === `demo-token.${u.username}`)
+  if (!user) throw new MockHttpError(401, 'Not authenticated')
+  return user
+}
+
+route('get', '/api/v1/auth/me', (_p, _b, _q, headers) => {
+  const { password: _password, ...info } = currentUser(headers)
+  return info
+})
+
+route('patch', '/api/v1/user/profile', (_p, body, _q, headers) => {
+  const user = currentUser(headers)
+  if (body.email && state.users.some((u) => u.username !== user.username && u.email === body.email)) {
+    throw new MockHttpError(409, 'Another account already uses that email')
+  }
+  if (body.name !== undefined) user.name = body.name.trim()
+  if (body.email !== undefined) user.email = body.email
+  const { password: _password, ...info } = user
+  return info
+})
+
+route('get', '/api/v1/user/settings', (_p, _b, _q, headers) => state.settings[currentUser(headers).username] ?? {})
+
+route('put', '/api/v1/user/settings', (_p, body, _q, headers) => {
+  state.settings[currentUser(headers).username] = body
+  return body
+})
+
+route('get', '/api/v1/user/logins', (_p, _b, query, headers) => {
+  const { username } = currentUser(headers)
+  return state.logins
+    .filter((login) => login.username === username)
+    .slice(0, Number(query.limit) || 20)
+    .map(({ username: _username, ...login }) => login)
+})
+
+route('get', '/api/v1/user/export', (_p, _b, _q, headers) => {
+  const { password: _password, ...account } = currentUser(headers)
+  return {
+    account,
+    api_keys: state.apiKeys.filter((k) => k.username === account.username),
+    audit_logs: state.auditLogs.filter((log) => log.username === account.username),
+    artifacts: state.artifacts
+      .filter((a) => a.uploaded_by === account.username)
+      .map(({ model_3d_data: _model, ...a }) => a),
+    exported_at: new Date().toISOString(),
+  }
+})
+
+route('delete', '/api/v1/user', (_p, body, _q, headers) => {
+  const user = currentUser(headers)
+  if (body.password !== user.password) throw new MockHttpError(401, 'Password is incorrect')
+  if (user.role === 'admin' && state.users.filter((u) => u.role === 'admin').length <= 1) {
+    throw new MockHttpError(409, 'You are the only admin; make someone else an admin first')
+  }
+  state.users = state.users.filter((u) => u !== user)
+  state.artifacts.forEach((a) => {
+    if (a.uploaded_by === user.username) a.uploaded_by = 'deleted-user'
+  })
+  return { message: 'Your account and personal data have been deleted' }
+})
+
+route('get', '/api/v1/api-keys', (_p, _b, _q, headers) => {
+  const { username } = currentUser(headers)
+  return state.apiKeys.filter((k) => k.username === username)
+})
+
+route('post', '/api/v1/api-keys', (_p, body, _q, headers) => {
+  const key = `fll_demo${Math.random().toString(36).slice(2)}`
+  const info: ApiKeyInfo = {
+    id: state.apiKeys.length + 1,
+    username: currentUser(headers).username,
+    name: body.name,
+    prefix: key.slice(0, 12),
+    created_at: new Date().toISOString(),
+    last_used_at: null,
+    revoked_at: null,
+  }
+  state.apiKeys.unshift(info)
+  return { ...info, key }
+})
+
+route('delete', '/api/v1/api-keys/:id', ({ id }) => {
+  const key = state.apiKeys.find((k) => k.id === Number(id))
+  if (!key) throw new MockHttpError(404, 'API key not found')
+  key.revoked_at = new Date().toISOString()
+  return { message: 'API key revoked' }
+})
+
+// Deliveries aren't sent in demo mode; a ping just marks the webhook delivered
+route('get', '/api/webhooks', (_p, _b, _q, headers) => {
+  const { username } = currentUser(headers)
+  return state.webhooks.filter((w) => w.owner_id === username)
+})
+
+route('post', '/api/webhooks', (_p, body, _q, headers) => {
+  if (!/^https?:\/\/[^/]+/.test(body.url || '')) {
+    throw new MockHttpError(400, 'Webhook URL must be an http or https URL')
+  }
+  const webhook: WebhookInfo = {
+    id: state.webhooks.length + 1,
+    owner_id: currentUser(headers).username,
+    url: body.url,
+    events: body.events || ['analysis.done', 'analysis.failed'],
+    created_at: new Date().toISOString(),
+    last_delivery_at: null,
+    last_status: null,
+    last_error: null,
+  }
+  state.webhooks.unshift(webhook)
+  return new MockResponse({ ...webhook, secret: Math.random().toString(36).slice(2).repeat(3) }, {}, 201)
+})
+
+route('delete', '/api/webhooks/:id', ({ id }) => {
+  if (!state.webhooks.some((w) => w.id === Number(id))) throw new MockHttpError(404, 'Webhook not found')
+  state.webhooks = state.webhooks.filter((w) => w.id !== Number(id))
+  return { message: 'Webhook deleted' }
+})
+
+route('post', '/api/webhooks/:id/ping', ({ id }) => {
+  const webhook = state.webhooks.find((w) => w.id === Number(id))
+  if (!webhook) throw new MockHttpError(404, 'Webhook not found')
+  Object.assign(webhook, { last_delivery_at: new Date().toISOString(), last_status: 'delivered', last_error: null })
+  return new MockResponse({ message: 'Test delivery queued' }, {}, 202)
+})
+
+route('post', '/api/v1/auth/logout', () => ({ message: 'Logged out' }))
+
+/** A handler result that also sets response headers */
+class MockResponse {
+  constructor(public data: unknown, public headers: Record<string, string>, public status = 200) {}
+}
+
+// One page of a list, with its full size in X-Total-Count like the backend
+const paged = <T>(items: T[], query: Record<string, any>, defaultLimit: number) => {
+  const offset = Number(query.offset || 0)
+  const limit = Number(query.limit || defaultLimit)
+  return new MockResponse(items.slice(offset, offset + limit), { 'x-total-count': String(items.length) })
+}
+
+const signedInUser = (headers: Record<string, any>) => {
+  try {
+    return currentUser(headers)
+  } catch {
+    return null
+  }
+}
+
+const isStaff = (user: User) => user.role === 'admin' || user.role === 'onsite'
+
+type Shareable = { id: number; owner_id?: string | null; visibility?: Visibility; team_id?: number | null }
+
+// The backend's access rule: public, or the user is staff, the owner, in the team, or shared with
+const canSee = (item: Shareable, kind: string, user: User | null) =>
+  item.visibility === 'public' ||
+  (!!user &&
+    (isStaff(user) ||
+      item.owner_id === user.username ||
+      (item.visibility === 'team' &&
+        state.orgs.some((o) => o.id === item.team_id && o.members.some((m) => m.username === user.username))) ||
+      (state.shares[`${kind}:${item.id}`] ?? []).includes(user.username)))
+
+const canSeeArtifact = (a: Artifact, user: User | null) =>
+  canSee(a, 'artifact', user) ||
+  state.collections.some((c) => c.id === a.collection_id && canSee(c, 'collection', user))
+
+// What the user may see, narrowed by scope (mine by default when signed in) and uploader
+const visibleArtifacts = (query: Record<string, any>, headers: Record<string, any>) => {
+  const user = signedInUser(headers)
+  const scope = query.scope || (user ? 'mine' : 'all')
+  return state.artifacts
+    .filter((a) => canSeeArtifact(a, user))
+    .filter((a) => !user || scope === 'all' || (a.owner_id === user.username) === (scope === 'mine'))
+    .filter((a) => !query.uploaded_by || a.uploaded_by === query.uploaded_by)
+}
+
+const SORT_KEYS: Record<ArtifactSort, (a: Artifact) => string | number | undefined> = {
+  uploaded_at: (a) => a.uploaded_at,
+  name: (a) => a.name.toLowerCase(),
+  confidence: (a) => a.confidence,
+  tier: (a) => a.tier,
+}
+
+// "west,south,east,north"; west > east wraps around the antimeridian, as on the backend
+const insideBbox = (a: Artifact, bbox?: string) => {
+  if (!bbox) return true
+  if (a.latitude == null || a.longitude == null) return false
+  const [west, south, east, north] = bbox.split(',').map(Number)
+  const inLongitude = west <= east ? a.longitude >= west && a.longitude <= east : a.longitude >= west || a.longitude <= east
+  return a.latitude >= south && a.latitude <= north && inLongitude
+}
+
+// Same filters and sorting as the backend's GET /api/artifacts
+route('get', '/api/artifacts', (_p, _b, query, headers) => {
+  const tags = String(query.tag || '')
+    .split(',')
+    .map((t) => t.trim().toLowerCase())
+    .filter(Boolean)
+  const era = String(query.era || '').toLowerCase()
+  const key = SORT_KEYS[(query.sort as ArtifactSort) || 'uploaded_at'] ?? SORT_KEYS.uploaded_at
+  const direction = query.order === 'asc' ? 1 : -1
+  const matches = visibleArtifacts(query, headers)
+    .filter((a) => query.collection_id === undefined || a.collection_id === Number(query.collection_id))
+    .filter((a) => !era || (a.age ?? '').toLowerCase().includes(era))
+    .filter((a) => !query.tier || a.tier === query.tier)
+    .filter((a) => tags.every((t) => a.tags.some((tag) => tag.toLowerCase() === t)))
+    .filter((a) => query.min_confidence === undefined || (a.confidence ?? 0) >= Number(query.min_confidence))
+    .filter((a) => query.verified === undefined || (a.verification_status === 'verified') === query.verified)
+    .filter((a) => insideBbox(a, query.bbox))
+    .sort((a, b) => {
+      const [x, y] = [key(a), key(b)]
+      if (x === y) return 0
+      if (x === undefined) return 1 // Missing values last, like the backend
+      if (y === undefined) return -1
+      return (x < y ? -1 : 1) * direction
+    })
+    .map(listItemsFor(query))
+  return paged(matches, query, 100)
+})
+
+route('get', '/api/artifacts/search', (_p, _b, query, headers) => {
+  const q = String(query.q || '').toLowerCase()
+  const matches = visibleArtifacts(query, headers)
+    .filter(
+      (a) =>
+        !q ||
+        a.name.toLowerCase().includes(q) ||
+        a.description?.toLowerCase().includes(q) ||
+        a.tags.some((t) => t.toLowerCase().includes(q))
+    )
+    .map(listItemsFor(query))
+  return paged(matches, query, 50)
+})
+
+route('get', '/api/artifacts/locations', (_p, _b, query, headers) => {
+  const located = visibleArtifacts(query, headers)
+    .filter((a) => query.collection_id === undefined || a.collection_id === Number(query.collection_id))
+    .filter((a) => a.latitude != null && a.longitude != null && insideBbox(a, query.bbox))
+    .map((a) => ({
+      id: a.id,
+      name: a.name,
+      latitude: a.latitude as number,
+      longitude: a.longitude as number,
+      captured_at: a.captured_at,
+      thumbnail_url: a.image_url,
+    }))
+  return paged(located, query, 500)
+})
+
+route('get', '/api/tags', (_p, _b, query, headers) => {
+  const needle = String(query.q || '').trim().toLowerCase()
+  const counts = new Map<string, number>()
+  for (const artifact of visibleArtifacts({ scope: 'all' }, headers)) {
+    for (const tag of new Set(artifact.tags ?? [])) {
+      if (tag.toLowerCase().includes(needle)) counts.set(tag, (counts.get(tag) ?? 0) + 1)
+    }
+  }
+  return Array.from(counts, ([tag, count]) => ({ tag, count }))
+    .sort((a, b) => b.count - a.count || a.tag.localeCompare(b.tag))
+    .slice(0, Number(query.limit || 100))
+})
+
+// Like the backend, trashed artifacts are retagged too
+const mergeTags = (sources: string[], target: string) => {
+  const into = target.trim().split(/\s+/).join(' ')
+  if (!into || into.includes(',')) throw new MockHttpError(400, 'Tag names cannot contain commas')
+  let changed = 0
+  for (const artifact of [...state.artifacts, ...state.trash]) {
+    if (!artifact.tags?.some((tag) => sources.includes(tag) && tag !== into)) continue
+    artifact.tags = Array.from(new Set(artifact.tags.map((tag) => (sources.includes(tag) ? into : tag))))
+    artifact.updated_at = new Date().toISOString()
+    changed += 1
+  }
+  return { tag: into, artifacts_changed: changed }
+}
+
+route('post', '/api/tags/merge', (_p, body) => mergeTags(body.sources ?? [], String(body.target ?? '')))
+
+route('post', '/api/tags/rename', (_p, body) => mergeTags([String(body.tag ?? '')], String(body.new_name ?? '')))
+
+// The demo archive is tiny, so every sync is simply a full one
+route('post', '/api/artifacts/sync', (_p, body, _q, headers) => {
+  const query = { uploaded_by: body.uploaded_by, include_images: body.include_images }
+  const artifacts = visibleArtifacts({ ...query, scope: 'all' }, headers)
+  const visibleIds = new Set(artifacts.map((artifact) => artifact.id))
+  return {
+    artifacts: artifacts.map(listItemsFor(query)),
+    deleted_ids: ((body.known_ids ?? []) as number[]).filter((id) => !visibleIds.has(id)),
+    synced_at: new Date().toISOString(),
+  }
+})
+
+// The backend's version is the last update time; If-Match on PATCH must carry it
+const artifactVersion = (artifact: Artifact) => artifact.updated_at ?? artifact.uploaded_at ?? ''
+
+const withVersion = (artifact: Artifact) => {
+  const version = artifactVersion(artifact)
+  return new MockResponse({ ...artifact, version }, { etag: `"${version}"` })
+}
+
+// The fields the demo edits, all of which a revert may set back
+const HISTORY_FIELDS = ['name', 'description', 'age', 'tags', 'notes', 'collection_id', 'verification_status'] as const
+type HistoryField = (typeof HISTORY_FIELDS)[number]
+
+// The backend keeps tags comma-separated, so that's how its history shows them
+const historyValue = (value: unknown) => (Array.isArray(value) ? value.join(',') || null : ((value as string | number | null) ?? null))
+
+const snapshot = (artifact: Artifact) => Object.fromEntries(HISTORY_FIELDS.map((f) => [f, artifact[f]])) as Pick<Artifact, HistoryField>
+
+const recordRevision = (artifact: Artifact, before: Pick<Artifact, HistoryField>, action: RevisionAction, headers: Record<string, any>) => {
+  const changes = HISTORY_FIELDS.filter((f) => historyValue(before[f]) !== historyValue(artifact[f])).map((field) => ({
+    field,
+    old: historyValue(before[field]),
+    new: historyValue(artifact[field]),
+  }))
+  if (!changes.length) return
+  state.revisions.unshift({
+    id: state.revisions.length + 1,
+    artifact_id: artifact.id,
+    action,
+    changed_by: currentUser(headers).username,
+    changed_at: new Date().toISOString(),
+    changes,
+  })
+}
+
+route('get', '/api/artifacts/:id', ({ id }, _b, _q, headers) => {
+  const artifact = findArtifact(id)
+  if (!canSeeArtifact(artifact, signedInUser(headers))) throw new MockHttpError(404, 'Artifact not found')
+  return withVersion(artifact)
+})
+
+// Demo artifacts have no real images to compare, so shared tags stand in for looking alike
+route('get', '/api/artifacts/:id/similar', ({ id }, _b, query, headers) => {
+  const user = signedInUser(headers)
+  const artifact = findArtifact(id)
+  if (!canSeeArtifact(artifact, user)) throw new MockHttpError(404, 'Artifact not found')
+  const tags = new Set(artifact.tags)
+  return state.artifacts
+    .filter((a) => a !== artifact && canSeeArtifact(a, user))
+    .map((a) => {
+      const shared = a.tags.filter((t) => tags.has(t)).length
+      const union = new Set([...a.tags, ...artifact.tags]).size
+      return { artifact: a, score: union ? shared / union : 0 }
+    })
+    .filter(({ score }) => score > 0)
+    .sort((a, b) => b.score - a.score)
+    .slice(0, Number(query.limit) || 10)
+    .map(({ artifact: a, score }) => ({
+      id: a.id,
+      name: a.name,
+      description: a.description,
+      similarity_score: score,
+      thumbnail_url: a.thumbnail_url ?? a.image_url,
+    }))
+})
+
+// Exports need real images and a ZIP or XML writer, so they're left to the backend
+route('get', '/api/export', () => {
+  throw new MockHttpError(400, 'Artifacts are not exported in demo mode')
+})
+
+route('post', '/api/import', () => {
+  throw new MockHttpError(400, 'Bundles are not imported in demo mode')
+})
+
+const uploadedFormat = (dataUrl: string) => {
+  const subtype = /^data:image\/([\w.+-]+)/.exec(dataUrl)?.[1]?.toUpperCase()
+  return subtype === 'JPG' ? 'JPEG' : subtype ?? null
+}
+
+route('post', '/api/artifacts', (_p, body, _q, headers) => {
+  const user = currentUser(headers)
+  // The backend compares content hashes; the same data URL is the same image here
+  const existing = state.artifacts.find((a) => a.image_url === body.image_data)
+  if (existing) {
+    throw new MockHttpError(409, `This image is already in the archive as "${existing.name}" (artifact #${existing.id})`, {
+      code: 'duplicate_artifact',
+      existing_id: existing.id,
+    })
+  }
+  const artifact: Artifact = {
+    id: state.nextId++,
+    name: body.name,
+    description: body.description,
+    tags: body.tags || [],
+    tier: body.tier,
+    // No image store here; the upload's data URL stands in for /api/images/{hash}
+    image_url: body.image_data,
+    // Nor any conversion: just what the data URL says it is
+    original_format: uploadedFormat(body.image_data),
+    form_data: body.form_data,
+    uploaded_by: user.username,
+    owner_id: user.username,
+    visibility: 'private',
+    uploaded_at: new Date().toISOString(),
+    verification_status: 'pending',
+  }
+  state.artifacts.unshift(artifact)
+  return { id: artifact.id, message: 'Artifact created successfully', has_3d_model: false }
+})
+
+route('put', '/api/artifacts/:id', ({ id }, body, _q, headers) => {
+  const artifact = unlockedArtifact(id)
+  if (body.verification_status === 'rejected') {
+    moveToTrash(artifact, headers)
+    return { id: artifact.id, message: 'Artifact moved to the trash' }
+  }
+  const before = snapshot(artifact)
+  if (body.name) artifact.name = body.name
+  if ('description' in body) artifact.description = body.description
+  if ('tags' in body) {
+    artifact.tags = String(body.tags)
+      .split(',')
+      .map((t) => t.trim())
+      .filter(Boolean)
+  }
+  recordRevision(artifact, before, 'updated', headers)
+  return { id: artifact.id, message: 'Artifact updated successfully', data: body }
+})
+
+route('patch', '/api/artifacts/:id', ({ id }, body, _q, headers) => {
+  const artifact = unlockedArtifact(id)
+  const ifMatch = headers['If-Match']
+  if (ifMatch && ifMatch !== '*' && ifMatch !== `"${artifactVersion(artifact)}"`) {
+    throw new MockHttpError(412, 'Artifact was changed by someone else; reload it and try again')
+  }
+  const before = snapshot(artifact)
+  if ('name' in body) artifact.name = body.name.trim()
+  if ('description' in body) artifact.description = body.description ?? undefined
+  if ('era' in body) artifact.age = body.era ?? undefined
+  if ('tags' in body) artifact.tags = body.tags
+  if ('notes' in body) artifact.notes = body.notes
+  if ('collection_id' in body) artifact.collection_id = body.collection_id
+  if ('verification_status' in body) artifact.verification_status = body.verification_status
+  artifact.updated_at = new Date().toISOString()
+  recordRevision(artifact, before, 'updated', headers)
+  return withVersion(artifact)
+})
+
+route('get', '/api/artifacts/:id/history', ({ id }, _b, query, headers) => {
+  const artifact = findArtifact(id)
+  if (!canSeeArtifact(artifact, currentUser(headers))) throw new MockHttpError(404, 'Artifact not found')
+  return paged(state.revisions.filter((r) => r.artifact_id === artifact.id), query, 50)
+})
+
+route('post', '/api/artifacts/:id/history/:revisionId/revert', ({ id, revisionId }, _b, _q, headers) => {
+  const artifact = findShareable(unlockedArtifact(id), 'artifact', headers)
+  const revision = state.revisions.find((r) => r.id === Number(revisionId) && r.artifact_id === artifact.id)
+  if (!revision) throw new MockHttpError(404, 'Revision not found')
+  const before = snapshot(artifact)
+  for (const { field, old } of revision.changes) {
+    if (field === 'tags') artifact.tags = old ? String(old).split(',') : []
+    else Object.assign(artifact, { [field]: old })
+  }
+  artifact.updated_at = new Date().toISOString()
+  recordRevision(artifact, before, 'reverted', headers)
+  logAction(currentUser(headers).username, `Reverted revision ${revision.id} of artifact ${artifact.id}`)
+  return withVersion(artifact)
+})
+
+const moveToTrash = (artifact: Artifact, headers: Record<string, any>) => {
+  const deletedAt = new Date().toISOString()
+  state.artifacts = state.artifacts.filter((a) => a !== artifact)
+  state.trash.unshift({ ...artifact, updated_at: deletedAt, deleted_at: deletedAt, deleted_by: currentUser(headers).username, purge_at: null })
+}
+
+route('delete', '/api/artifacts/:id', ({ id }, _b, _q, headers) => {
+  const artifact = unlockedArtifact(id)
+  moveToTrash(artifact, headers)
+  return { id: artifact.id, message: 'Artifact moved to the trash' }
+})
+
+// Owners see their own trash, staff everyone's
+const trashedArtifact = (id: string, headers: Record<string, any>) => {
+  const user = currentUser(headers)
+  const artifact = state.trash.find((a) => a.id === Number(id))
+  if (!artifact || (artifact.owner_id !== user.username && !isStaff(user))) {
+    throw new MockHttpError(404, 'Artifact not in the trash')
+  }
+  return artifact
+}
+
+route('get', '/api/trash', (_p, _b, query, headers) => {
+  const user = currentUser(headers)
+  return paged(state.trash.filter((a) => isStaff(user) || a.owner_id === user.username).map(toListItem), query, 100)
+})
+
+route('post', '/api/artifacts/:id/restore', ({ id }, _b, _q, headers) => {
+  const { deleted_at: _deletedAt, deleted_by: _deletedBy, purge_at: _purgeAt, ...artifact } = trashedArtifact(id, headers)
+  state.trash = state.trash.filter((a) => a.id !== artifact.id)
+  artifact.updated_at = new Date().toISOString()
+  state.artifacts.push(artifact)
+  return withVersion(artifact)
+})
+
+route('delete', '/api/trash/:id', ({ id }, _b, _q, headers) => {
+  const artifact = trashedArtifact(id, headers)
+  state.trash = state.trash.filter((a) => a !== artifact)
+  return { id: artifact.id, message: 'Artifact deleted permanently' }
+})
+
+// Owner or staff only, like the backend
+const findShareable = <T extends Shareable>(item: T | undefined, kind: string, headers: Record<string, any>): T => {
+  const user = currentUser(headers)
+  if (!item || !canSee(item, kind, user)) throw new MockHttpError(404, `${kind === 'artifact' ? 'Artifact' : 'Collection'} not found`)
+  if (item.owner_id !== user.username && !isStaff(user)) throw new MockHttpError(403, `Only the ${kind}'s owner can change it`)
+  return item
+}
+
+const sharingOf = (item: Shareable, kind: string) => ({
+  visibility: item.visibility ?? 'private',
+  team_id: item.team_id ?? null,
+  shared_with: state.shares[`${kind}:${item.id}`] ?? [],
+})
+
+const setSharing = (item: Shareable, kind: string, body: any) => {
+  if (body.visibility === 'team' && body.team_id == null) throw new MockHttpError(400, 'team_id is required when visibility is team')
+  const unknown = (body.shared_with ?? []).filter((u: string) => !state.users.some((user) => user.username === u))
+  if (unknown.length) throw new MockHttpError(400, `No such user: ${unknown.join(', ')}`)
+  item.visibility = body.visibility ?? 'private'
+  item.team_id = body.team_id ?? null
+  state.shares[`${kind}:${item.id}`] = (body.shared_with ?? []).filter((u: string) => u !== item.owner_id)
+  return sharingOf(item, kind)
+}
+
+route('get', '/api/artifacts/:id/sharing', ({ id }, _b, _q, headers) =>
+  sharingOf(findShareable(state.artifacts.find((a) => a.id === Number(id)), 'artifact', headers), 'artifact')
+)
+
+route('put', '/api/artifacts/:id/sharing', ({ id }, body, _q, headers) =>
+  setSharing(findShareable(unlockedArtifact(id), 'artifact', headers), 'artifact', body)
+)
+
+const collectionInfo = (c: CollectionInfo) => ({
+  ...c,
+  artifact_count: state.artifacts.filter((a) => a.collection_id === c.id).length,
+})
+
+route('get', '/api/collections', (_p, _b, query, headers) => {
+  const user = signedInUser(headers)
+  const scope = query.scope || 'all'
+  return state.collections
+    .filter((c) => canSee(c, 'collection', user))
+    .filter((c) => !user || scope === 'all' || (c.owner_id === user.username) === (scope === 'mine'))
+    .map(collectionInfo)
+})
+
+route('post', '/api/collections', (_p, body, _q, headers) => {
+  const user = currentUser(headers)
+  const collection: CollectionInfo = {
+    id: state.collections.length + 1,
+    name: String(body.name).trim(),
+    description: body.description ?? null,
+    owner_id: user.username,
+    visibility: body.visibility ?? 'private',
+    team_id: body.team_id ?? null,
+    created_at: new Date().toISOString(),
+  }
+  state.collections.push(collection)
+  return new MockResponse(collectionInfo(collection), {}, 201)
+})
+
+route('get', '/api/collections/:id', ({ id }, _b, _q, headers) => {
+  const collection = state.collections.find((c) => c.id === Number(id))
+  if (!collection || !canSee(collection, 'collection', signedInUser(headers))) throw new MockHttpError(404, 'Collection not found')
+  return collectionInfo(collection)
+})
+
+route('delete', '/api/collections/:id', ({ id }, _b, _q, headers) => {
+  const collection = findShareable(state.collections.find((c) => c.id === Number(id)), 'collection', headers)
+  state.collections = state.collections.filter((c) => c !== collection)
+  state.artifacts.filter((a) => a.collection_id === collection.id).forEach((a) => (a.collection_id = null))
+  return { message: 'Collection deleted' }
+})
+
+route('get', '/api/collections/:id/sharing', ({ id }, _b, _q, headers) =>
+  sharingOf(findShareable(state.collections.find((c) => c.id === Number(id)), 'collection', headers), 'collection')
+)
+
+route('put', '/api/collections/:id/sharing', ({ id }, body, _q, headers) =>
+  setSharing(findShareable(state.collections.find((c) => c.id === Number(id)), 'collection', headers), 'collection', body)
+)
+
+route('post', '/api/artifacts/:id/verify', ({ id }, body, _q, headers) => {
+  const artifact = findArtifact(id)
+  if (body.verification_status === 'rejected') {
+    moveToTrash(artifact, headers)
+    return { id: artifact.id, verification_status: 'rejected', message: 'Artifact rejected and moved to the trash', email_sent: false }
+  }
+  artifact.verification_status = body.verification_status
+  artifact.verified_by = body.verified_by
+  artifact.verified_at = new Date().toISOString()
+  return { ...artifact, email_sent: false }
+})
+
+// Moderation, for archive staff only; every action goes to the audit log
+const curator = (headers: Record<string, any>) => {
+  const user = currentUser(headers)
+  if (!isStaff(user)) throw new MockHttpError(403, 'Only archive staff can moderate artifacts')
+  return user
+}
+
+route('post', '/api/artifacts/:id/flags', ({ id }, body, _q, headers) => {
+  const user = curator(headers)
+  const artifact = findArtifact(id)
+  const flag: ArtifactFlag = {
+    id: state.flags.length + 1,
+    artifact_id: artifact.id,
+    reason: String(body.reason).trim(),
+    flagged_by: user.username,
+    created_at: new Date().toISOString(),
+    resolved_at: null,
+    resolved_by: null,
+    resolution: null,
+  }
+  state.flags.push(flag)
+  logAction(user.username, `Flagged artifact ${artifact.id}: ${flag.reason}`)
+  return new MockResponse(flag, {}, 201)
+})
+
+route('get', '/api/artifacts/:id/flags', ({ id }, _b, _q, headers) => {
+  curator(headers)
+  const artifact = findArtifact(id)
+  return state.flags.filter((f) => f.artifact_id === artifact.id).reverse()
+})
+
+route('post', '/api/moderation/flags/:id/resolve', ({ id }, body, _q, headers) => {
+  const user = curator(headers)
+  const flag = state.flags.find((f) => f.id === Number(id))
+  if (!flag) throw new MockHttpError(404, 'Flag not found')
+  if (flag.resolved_at) throw new MockHttpError(409, `Flag was already resolved by ${flag.resolved_by}`)
+  Object.assign(flag, { resolved_at: new Date().toISOString(), resolved_by: user.username, resolution: String(body.resolution).trim() })
+  logAction(user.username, `Resolved flag ${flag.id} on artifact ${flag.artifact_id}: ${flag.resolution}`)
+  return flag
+})
+
+route('get', '/api/moderation/queue', (_p, _b, query, headers) => {
+  curator(headers)
+  const open = state.flags.filter((f) => !f.resolved_at)
+  // Flags are kept oldest first, so artifacts come out longest-waiting first
+  const queued = Array.from(new Set(open.map((f) => f.artifact_id)))
+    .map((artifactId) => state.artifacts.find((a) => a.id === artifactId))
+    .filter((a): a is Artifact => !!a)
+  return paged(queued.map((a) => ({ ...toListItem(a), open_flags: open.filter((f) => f.artifact_id === a.id) })), query, 50)
+})
+
+route('post', '/api/artifacts/:id/identification', ({ id }, body, _q, headers) => {
+  const user = curator(headers)
+  const artifact = findArtifact(id)
+  const previous = artifact.name
+  const before = snapshot(artifact)
+  artifact.name = String(body.name).trim()
+  if ('description' in body) artifact.description = body.description ?? undefined
+  if ('era' in body) artifact.age = body.era ?? undefined
+  artifact.identification_overridden_by = user.username
+  artifact.identification_overridden_at = artifact.updated_at = new Date().toISOString()
+  recordRevision(artifact, before, 'identified', headers)
+  logAction(user.username, `Overrode identification of artifact ${artifact.id} (${previous} -> ${artifact.name}): ${body.reason}`)
+  return withVersion(artifact)
+})
+
+route('post', '/api/artifacts/:id/lock', ({ id }, body, _q, headers) => {
+  const user = curator(headers)
+  const artifact = findArtifact(id)
+  if (artifact.locked_at) throw new MockHttpError(409, `Artifact is already locked by ${artifact.locked_by}`)
+  Object.assign(artifact, { locked_at: new Date().toISOString(), locked_by: user.username, lock_reason: String(body.reason).trim() })
+  artifact.updated_at = artifact.locked_at!
+  logAction(user.username, `Locked artifact ${artifact.id}: ${artifact.lock_reason}`)
+  return withVersion(artifact)
+})
+
+route('delete', '/api/artifacts/:id/lock', ({ id }, _b, _q, headers) => {
+  const user = curator(headers)
+  const artifact = findArtifact(id)
+  if (!artifact.locked_at) throw new MockHttpError(409, 'Artifact is not locked')
+  Object.assign(artifact, { locked_at: null, locked_by: null, lock_reason: null, updated_at: new Date().toISOString() })
+  logAction(user.username, `Unlocked artifact ${artifact.id}`)
+  return withVersion(artifact)
+})
+
+// Like the backend's default ANALYSIS_INLINE_TIERS: only instant answers at once,
+// other tiers are queued and finish by the first poll
+const cacheKey = (tier: string, imageData: string) => `${tier}:${imageData}`
+
+// The backend's default tier weights; the demo counts analyses but sets no quota
+const QUOTA_WEIGHTS: Record<string, number> = { instant: 1, fast: 2, balanced: 5, thorough: 10 }
+
+const recordUsage = (headers: Record<string, any>, tier: string, analyses = 1) => {
+  const { username } = currentUser(headers)
+  const byTier = state.analysisUsage[username] ?? {}
+  state.analysisUsage[username] = { ...byTier, [tier]: (byTier[tier] ?? 0) + analyses }
+}
+
+route('get', '/api/usage', (_p, _b, query, headers) => {
+  const username = query.username || currentUser(headers).username
+  const byTier = Object.entries(state.analysisUsage[username] ?? {}).map(([tier, analyses]) => ({
+    tier,
+    analyses,
+    units: analyses * (QUOTA_WEIGHTS[tier] ?? QUOTA_WEIGHTS.fast),
+  }))
+  const now = new Date()
+  return {
+    username,
+    month: now.toISOString().slice(0, 7),
+    quota: null,
+    used: byTier.reduce((sum, row) => sum + row.units, 0),
+    remaining: null,
+    resets_at: new Date(Date.UTC(now.getUTCFullYear(), now.getUTCMonth() + 1, 1)).toISOString(),
+    analyses: byTier.reduce((sum, row) => sum + row.analyses, 0),
+    by_tier: byTier,
+    weights: QUOTA_WEIGHTS,
+    rate_limit: null,
+  }
+})
+
+route('post', '/api/analyze', (_p, body, _q, headers) => {
+  const tier = body.tier || 'fast'
+  recordUsage(headers, tier)
+  const key = cacheKey(tier, body.image_data)
+  if (!body.refresh && state.analysisCache[key]) return { ...state.analysisCache[key], cached: true }
+  if (tier === 'instant') return (state.analysisCache[key] = analyze(tier))
+  const job = {
+    id: String(state.analysisJobs.length + 1),
+    tier,
+    status: 'queued' as const,
+    stage: 'received' as const,
+    attempts: 0,
+    created_at: new Date().toISOString(),
+    cacheKey: key,
+  }
+  state.analysisJobs.push(job)
+  const { cacheKey: _key, ...info } = job
+  return new MockResponse(info, { location: `/api/analyze/jobs/${job.id}` }, 202)
+})
+
+route('get', '/api/analyze/jobs/:id', ({ id }) => {
+  const job = state.analysisJobs.find((j) => j.id === id)
+  if (!job) throw new MockHttpError(404, 'Analysis job not found')
+  if (job.status === 'queued') {
+    const result = analyze(job.tier)
+    state.analysisCache[job.cacheKey] = result
+    Object.assign(job, { status: 'done', stage: 'done', attempts: 1, result, finished_at: new Date().toISOString() })
+  }
+  const { cacheKey: _key, ...info } = job
+  return info
+})
+
+// Each image becomes one mock job; like single jobs, they all finish by the first poll
+route('post', '/api/analyze/batch', (_p, body, _q, headers) => {
+  const files: File[] = body instanceof FormData ? (body.getAll('files') as File[]) : body.images || []
+  const tier = (body instanceof FormData ? (body.get('tier') as string) : body.tier) || 'fast'
+  recordUsage(headers, tier, files.length)
+  const id = String(state.analysisBatches.length + 1)
+  const items = files.map((file, index) => ({
+    index,
+    filename: file instanceof File ? file.name : null,
+    job_id: `${id}-${index}`,
+    status: 'queued' as const,
+  }))
+  const batch = { id, tier, status: 'queued' as const, total: items.length, done: 0, failed: 0, items }
+  state.analysisBatches.push(batch)
+  return new MockResponse(batch, { location: `/api/analyze/batch/${id}` }, 202)
+})
+
+route('get', '/api/analyze/batch/:id', ({ id }) => {
+  const batch = state.analysisBatches.find((b) => b.id === id)
+  if (!batch) throw new MockHttpError(404, 'Analysis batch not found')
+  batch.items = batch.items.map((item): AnalysisBatchItem =>
+    item.status === 'queued' ? { ...item, status: 'done', result: analyze(batch.tier) } : item
+  )
+  Object.assign(batch, { status: 'done', done: batch.items.length })
+  return batch
+})
+
+route('get', '/api/v1/users', () => state.users.map(({ password: _password, ...u }) => u))
+
+route('post', '/api/v1/users', (_p, body) => {
+  if (state.users.some((u) => u.username === body.username)) {
+    throw new MockHttpError(400, 'Username already exists')
+  }
+  state.users.push({ ...body })
+  logAction(body.username, `User ${body.username} created`)
+  return { message: 'User created successfully', username: body.username }
+})
+
+route('get', '/api/v1/audit-logs', () => state.auditLogs)
+
+route('get', '/api/v1/admin/stats', () => {
+  const active = state.users.filter((u) => u.active !== false)
+  const usersByRole: Record<string, number> = {}
+  active.forEach((u) => (usersByRole[u.role] = (usersByRole[u.role] || 0) + 1))
+  const dayAgo = Date.now() - 24 * 3600 * 1000
+  return {
+    total_users: state.users.length,
+    active_users: active.length,
+    deactivated_users: state.users.length - active.length,
+    users_by_role: usersByRole,
+    // Everyone who has signed in this page load counts as signed in
+    active_sessions: state.logins.length,
+    signed_in_users: new Set(state.logins.map((l) => l.username)).size,
+    failed_logins_24h: state.failedLogins.filter((f) => new Date(f.attempted_at).getTime() > dayAgo).length,
+    recent_failed_logins: state.failedLogins.slice(0, 10),
+    locked_accounts: [],
+    generated_at: new Date().toISOString(),
+  }
+})
+
+const publicUser = ({ password: _password, ...u }: (typeof state.users)[number]) => u
+
+route('get', '/api/v1/admin/users', (_p, _b, query) => {
+  const q = String(query.q || '').toLowerCase()
+  const matches = state.users.filter(
+    (u) =>
+      (!query.role || u.role === query.role) &&
+      (query.active === undefined || String(u.active !== false) === String(query.active)) &&
+      (!q || u.username.toLowerCase().includes(q) || u.name.toLowerCase().includes(q))
+  )
+  const offset = Number(query.offset || 0)
+  return matches.slice(offset, offset + Number(query.limit || 50)).map(publicUser)
+})
+
+route('post', '/api/v1/admin/users', (_p, body) => {
+  if (state.users.some((u) => u.username === body.username)) {
+    throw new MockHttpError(409, 'Username already exists')
+  }
+  state.users.push({ ...body })
+  return publicUser(body)
+})
+
+route('patch', '/api/v1/admin/users/:username', ({ username }, body) => {
+  const user = state.users.find((u) => u.username === username)
+  if (!user) throw new MockHttpError(404, 'User not found')
+  Object.assign(user, body)
+  return publicUser(user)
+})
+
+route('delete', '/api/v1/admin/users/:username', ({ username }) => {
+  const index = state.users.findIndex((u) => u.username === username)
+  if (index === -1) throw new MockHttpError(404, 'User not found')
+  state.users.splice(index, 1)
+  return { message: `${username} deleted` }
+})
+
+route('post', '/api/v1/admin/users/:username/deactivate', ({ username }, _b, _q, headers) => {
+  const user = state.users.find((u) => u.username === username)
+  if (!user) throw new MockHttpError(404, 'User not found')
+  if (user.username === currentUser(headers).username) throw new MockHttpError(409, "You can't deactivate your own account")
+  if (user.active === false) throw new MockHttpError(409, `${username} is already deactivated`)
+  user.active = false
+  user.deactivated_at = new Date().toISOString()
+  logAction(currentUser(headers).username, `Deactivated ${username}`)
+  return publicUser(user)
+})
+
+route('delete', '/api/v1/admin/users/:username/sessions', ({ username }, _b, _q, headers) => {
+  if (!state.users.some((u) => u.username === username)) throw new MockHttpError(404, 'User not found')
+  logAction(currentUser(headers).username, `Signed out ${username} everywhere`)
+  return { message: `${username} signed out everywhere` }
+})
+
+route('post', '/api/v1/admin/users/:username/reactivate', ({ username }, _b, _q, headers) => {
+  const user = state.users.find((u) => u.username === username)
+  if (!user) throw new MockHttpError(404, 'User not found')
+  if (user.active !== false) throw new MockHttpError(409, `${username} is already active`)
+  user.active = true
+  user.deactivated_at = null
+  logAction(currentUser(headers).username, `Reactivated ${username}`)
+  return publicUser(user)
+})
+
+route('post', '/api/v1/users/:username/unlock', ({ username }) => ({ message: `${username} unlocked` }))
+
+route('post', '/api/v1/users/change-password', (_p, body) => {
+  const user = state.users.find((u) => u.username === body.username)
+  if (user) user.password = body.new_password
+  logAction(body.username, 'Password reset')
+  return { message: 'Password updated successfully' }
+})
+
+// Small delay so loading states are visible during a demo
+const latency = () => new Promise((resolve) => setTimeout(resolve, 150 + Math.random() * 250))
+
+export const mockAdapter: AxiosAdapter = async (config: InternalAxiosRequestConfig) => {
+  await latency()
+  if (config.signal?.aborted) {
+    throw new CanceledError(undefined, undefined, config)
+  }
+
+  const method = (config.method || 'get').toLowerCase()
+  const path = (config.url || '').split('?')[0]
+  const body = typeof config.data === 'string' && config.data ? JSON.parse(config.data) : config.data || {}
+
+  // Echo the request id like the backend does
+  const requestId = config.headers?.get?.('X-Request-ID')
+  const respond = (status: number, data: any, headers: Record<string, string> = {}): AxiosResponse => ({
+    data,
+    status,
+    statusText: String(status),
+    headers: { ...headers, ...(requestId ? { 'x-request-id': String(requestId) } : {}) },
+    config,
+  })
+
+  for (const r of routes) {
+    if (r.method !== method) continue
+    const match = r.pattern.exec(path)
+    if (!match) continue
+
+    try {
+      const result = r.handler(match.groups || {}, body, config.params || {}, config.headers || {})
+      return result instanceof MockResponse ? respond(result.status, result.data, result.headers) : respond(200, result)
+    } catch (error) {
+      if (error instanceof MockHttpError) {
+        const response = respond(error.status, { detail: error.detail, request_id: requestId, ...error.extra })
+        throw new AxiosError(error.detail, AxiosError.ERR_BAD_REQUEST, config, null, response)
+      }
+      throw error
+    }
+  }
+
+  const response = respond(404, { detail: 'Not Found' })
+  throw new AxiosError('Not Found', AxiosError.ERR_BAD_REQUEST, config, null, response)
+}
@@ -0,0 +1,99 @@
+import { useEffect, useState } from 'react'
+import { API_BASE_URL } from './api'
+import { isDemoMode } from './mockApi'
+import { cookieAuth, session } from './session'
+
+/**
+ * Live connection to the backend's presence socket (MainApp/presence.py).
+ * Keeps the list of online users current and hears when an admin signs this
+ * user out, so open tabs leave straight away. Reconnects with backoff after
+ * network drops. Demo mode has no server, so only the local user is online.
+ */
+
+type Listener = (online: string[]) => void
+
+const PRESENCE_URL = API_BASE_URL.replace(/^http/, 'ws') + '/api/v1/ws/presence'
+const RETRY_DELAYS_MS = [1000, 2000, 5000, 10000, 30000]
+// Matches presence.FORCED_LOGOUT_CLOSE_CODE; reconnecting would be pointless
+const FORCED_LOGOUT_CLOSE_CODE = 4001
+
+let socket: WebSocket | null = null
+let online: string[] = []
+let attempt = 0
+let retryTimer: ReturnType<typeof setTimeout> | undefined
+let onForcedLogout: ((reason: string) => void) | null = null
+const listeners = new Set<Listener>()
+
+const setOnline = (usernames: string[]) => {
+  online = usernames
+  listeners.forEach((listener) => listener(online))
+}
+
+const connect = () => {
+  const ws = new WebSocket(PRESENCE_URL)
+  socket = ws
+
+  ws.onopen = () => {
+    attempt = 0
+    // Cookie sessions authenticate the handshake itself
+    const token = session.getTokens()?.access_token
+    if (!cookieAuth && token) ws.send(JSON.stringify({ type: 'auth', token }))
+  }
+
+  ws.onmessage = (event) => {
+    try {
+      const message = JSON.parse(event.data)
+      if (message.type === 'presence') setOnline(message.online)
+      if (message.type === 'logout') onForcedLogout?.(message.reason)
+    } catch (e) {
+      console.warn('Ignoring malformed presence message:', e)
+    }
+  }
+
+  ws.onclose = (event) => {
+    if (socket !== ws) return // Replaced or stopped
+    socket = null
+    setOnline([])
+    if (event.code === FORCED_LOGOUT_CLOSE_CODE || !onForcedLogout) return
+    const delay = RETRY_DELAYS_MS[Math.min(attempt, RETRY_DELAYS_MS.length - 1)]
+    attempt += 1
+    retryTimer = setTimeout(connect, delay)
+  }
+}
+
+export const presence = {
+  /** Connect for the signed-in user; `handleLogout` runs if an admin signs them out */
+  start: (username: string, handleLogout: (reason: string) => void) => {
+    presence.stop()
+    onForcedLogout = handleLogout
+    if (isDemoMode) {
+      setOnline([username])
+      return
+    }
+    connect()
+  },
+
+  stop: () => {
+    onForcedLogout = null
+    clearTimeout(retryTimer)
+    const ws = socket
+    socket = null
+    ws?.close()
+    setOnline([])
+  },
+
+  subscribe: (listener: Listener) => {
+    listeners.add(listener)
+    listener(online)
+    return () => {
+      listeners.delete(listener)
+    }
+  },
+}
+
+/** Usernames currently online, kept up to date */
+export function usePresence() {
+  const [users, setUsers] = useState<string[]>(online)
+  useEffect(() => presence.subscribe(setUsers), [])
+  return users
+}
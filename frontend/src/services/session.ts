@@ -0,0 +1,64 @@
+import { User } from '../types'
+
+/**
+ * Persisted login state: the signed-in user's profile and the tokens the
+ * backend issued for them. Shared by AuthContext and the API client.
+ *
+ * In cookie mode the session itself lives in an HTTP-only cookie the browser
+ * manages; only its CSRF token is kept here. Demo mode has no real cookies,
+ * so it always uses tokens.
+ */
+export const cookieAuth = import.meta.env.VITE_AUTH_MODE === 'cookie' && import.meta.env.VITE_DEMO_MODE !== 'true'
+
+export interface AuthTokens {
+  access_token: string
+  refresh_token?: string
+}
+
+const USER_KEY = 'user'
+const TOKENS_KEY = 'auth_tokens'
+const CSRF_KEY = 'csrf_token'
+
+const read = <T>(key: string): T | null => {
+  try {
+    const stored = localStorage.getItem(key)
+    return stored ? (JSON.parse(stored) as T) : null
+  } catch (e) {
+    localStorage.removeItem(key)
+    return null
+  }
+}
+
+export const session = {
+  getUser: () => read<User>(USER_KEY),
+
+  getTokens: () => read<AuthTokens>(TOKENS_KEY),
+
+  save: (user: User, tokens?: AuthTokens) => {
+    localStorage.setItem(USER_KEY, JSON.stringify(user))
+    if (tokens?.access_token) {
+      localStorage.setItem(TOKENS_KEY, JSON.stringify(tokens))
+    } else {
+      localStorage.removeItem(TOKENS_KEY)
+    }
+  },
+
+  saveTokens: (tokens: AuthTokens) => {
+    // Keep the old refresh token if the server didn't rotate it
+    const previous = read<AuthTokens>(TOKENS_KEY)
+    localStorage.setItem(
+      TOKENS_KEY,
+      JSON.stringify({ ...tokens, refresh_token: tokens.refresh_token ?? previous?.refresh_token })
+    )
+  },
+
+  getCsrfToken: () => localStorage.getItem(CSRF_KEY),
+
+  saveCsrfToken: (csrfToken: string) => localStorage.setItem(CSRF_KEY, csrfToken),
+
+  clear: () => {
+    localStorage.removeItem(USER_KEY)
+    localStorage.removeItem(TOKENS_KEY)
+    localStorage.removeItem(CSRF_KEY)
+  },
+}
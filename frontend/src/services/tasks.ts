@@ -0,0 +1,75 @@
+import { useEffect, useRef } from 'react'
+import axios from 'axios'
+
+/**
+ * Registry of abortable in-flight work (analysis requests, uploads), keyed by
+ * a caller-chosen name. Starting a task under a key that's already running
+ * aborts the previous one, and everything can be cancelled at once when the
+ * owning page unmounts, so late responses never write into stale state.
+ */
+export class TaskRegistry {
+  private controllers = new Map<string, AbortController>()
+
+  /** Begin a task and return the signal to pass to the request */
+  start(key: string): AbortSignal {
+    this.cancel(key)
+    const controller = new AbortController()
+    this.controllers.set(key, controller)
+    return controller.signal
+  }
+
+  /** Forget a finished task, unless it has already been replaced */
+  finish(key: string, signal: AbortSignal) {
+    if (this.controllers.get(key)?.signal === signal) {
+      this.controllers.delete(key)
+    }
+  }
+
+  cancel(key: string) {
+    this.controllers.get(key)?.abort()
+    this.controllers.delete(key)
+  }
+
+  cancelAll() {
+    this.controllers.forEach((controller) => controller.abort())
+    this.controllers.clear()
+  }
+
+  isRunning(key: string) {
+    return this.controllers.has(key)
+  }
+}
+
+// Every live registry, so closing the window can cancel all of them
+const liveRegistries = new Set<TaskRegistry>()
+
+window.addEventListener('pagehide', () => {
+  liveRegistries.forEach((registry) => registry.cancelAll())
+})
+
+/**
+ * A TaskRegistry scoped to the calling component: all of its tasks are
+ * aborted when the component unmounts (e.g. the user navigates away).
+ */
+export function useTaskRegistry(): TaskRegistry {
+  const registryRef = useRef<TaskRegistry | null>(null)
+  if (!registryRef.current) {
+    registryRef.current = new TaskRegistry()
+  }
+
+  useEffect(() => {
+    const registry = registryRef.current!
+    liveRegistries.add(registry)
+    return () => {
+      registry.cancelAll()
+      liveRegistries.delete(registry)
+    }
+  }, [])
+
+  return registryRef.current
+}
+
+/** True if the error came from an aborted request rather than a real failure */
+export function isCancellation(error: unknown): boolean {
+  return axios.isCancel(error) || (error instanceof DOMException && error.name === 'AbortError')
+}
@@ -1,10 +1,30 @@
+// Mirrors the canonical models in MainApp/schemas.py; keep the two in step.
+
 export type UserRole = 'admin' | 'user' | 'field' | 'onsite'
 
+export type VerificationStatus = 'pending' | 'verified' | 'rejected'
+
+export type OrgRank = 'owner' | 'manager' | 'member'
+
+export type ArtifactSort = 'uploaded_at' | 'name' | 'confidence' | 'tier'
+
+export type SortOrder = 'asc' | 'desc'
+
+export type Visibility = 'private' | 'team' | 'public'
+
+/** mine: your own artifacts; shared: other people's you can see; all: both */
+export type ShareScope = 'mine' | 'shared' | 'all'
+
+export type WebhookEvent = 'analysis.done' | 'analysis.failed'
+
 export interface User {
   username: string
   name: string
   email: string
   role: UserRole
+  locked_until?: string | null
+  active?: boolean
+  deactivated_at?: string | null
 }
 
 export interface Artifact {
@@ -13,19 +33,137 @@ export interface Artifact {
   description?: string
   tags: string[]
   tier: string
-  thumbnail?: string
-  image_data?: string
+  age?: string
+  notes?: string | null
+  /** Image paths under the API (/api/images/{hash}); resolve with imageUrl() */
+  thumbnail_url?: string | null
+  image_url?: string | null
+  /** What the image was uploaded as (e.g. HEIC, RAW) and its size in bytes; stored as JPEG or PNG */
+  original_format?: string | null
+  original_size?: number | null
   uploaded_at?: string
   uploaded_by?: string
   analyzed_at?: string
+  /** From the photo's EXIF data: camera local time, and WGS 84 degrees */
+  captured_at?: string | null
+  latitude?: number | null
+  longitude?: number | null
   confidence?: number
   form_data?: FormData
-  verification_status?: 'pending' | 'verified' | 'rejected'
+  verification_status?: VerificationStatus
   verified_by?: string
   verified_at?: string
   has_3d_model?: boolean
   model_3d_data?: string
   model_3d_format?: string
+  updated_at?: string | null
+  /** Changes on every update; send it back as If-Match when patching */
+  version?: string
+  owner_id?: string | null
+  visibility?: Visibility
+  team_id?: number | null
+  collection_id?: number | null
+  /** Set while a curator has locked the artifact; edits fail with artifact_locked */
+  locked_at?: string | null
+  locked_by?: string | null
+  lock_reason?: string | null
+  /** Set when a curator replaced the analysis' identification */
+  identification_overridden_by?: string | null
+  identification_overridden_at?: string | null
+}
+
+/** Query parameters for GET /api/artifacts */
+export interface ArtifactFilters {
+  /** Defaults to mine when signed in */
+  scope?: ShareScope
+  collection_id?: number
+  era?: string
+  tier?: string
+  /** Comma-separated; every tag must match */
+  tag?: string
+  min_confidence?: number
+  /** "west,south,east,north" in degrees: photos taken inside it */
+  bbox?: string
+  verified?: boolean
+  sort?: ArtifactSort
+  order?: SortOrder
+  limit?: number
+  offset?: number
+}
+
+export interface ArtifactPage {
+  artifacts: Artifact[]
+  /** Matches across all pages */
+  total: number
+}
+
+export interface ArtifactCreate {
+  name: string
+  description?: string
+  tags: string[]
+  tier: string
+  image_data: string
+  form_data?: FormData
+  model_3d_data?: string
+  model_3d_format?: string
+  uploaded_by?: string
+}
+
+export interface ArtifactUpdate {
+  name?: string
+  description?: string | null
+  tags?: string[]
+  form_data?: FormData
+  verification_status?: string
+}
+
+/** PATCH /api/artifacts/{id}; only the fields that are sent are changed */
+export interface ArtifactPatch {
+  name?: string
+  description?: string | null
+  era?: string | null
+  tags?: string[]
+  notes?: string | null
+  collection_id?: number | null
+  verification_status?: VerificationStatus
+}
+
+/** Who besides the owner and archive staff may see an artifact or collection */
+export interface Sharing {
+  visibility?: Visibility
+  /** Organization that sees it when visibility is team */
+  team_id?: number | null
+  shared_with?: string[]
+}
+
+export interface CollectionCreate {
+  name: string
+  description?: string | null
+  visibility?: Visibility
+  team_id?: number | null
+}
+
+export interface CollectionInfo {
+  id: number
+  name: string
+  description?: string | null
+  owner_id: string
+  visibility: Visibility
+  team_id?: number | null
+  created_at?: string | null
+  artifact_count?: number
+}
+
+export interface VerificationRequest {
+  verification_status: 'verified' | 'rejected'
+  reason: string
+  verified_by: string
+}
+
+export interface ArtifactChanges {
+  artifacts: Artifact[]
+  deleted_ids: number[]
+  synced_at: string
 }
 
 export interface FormData {
@@ -40,16 +178,491 @@ export interface FormData {
   tags?: string[]
 }
 
+export interface AnalyzeRequest {
+  image_data: string
+  tier?: string
+  /** Analyse again even if this image and tier are cached */
+  refresh?: boolean
+}
+
+export interface TierUsage {
+  tier: string
+  analyses: number
+  units: number
+}
+
+export interface AnalysisRateLimit {
+  per_minute: number
+  burst: number
+}
+
+/** GET /api/usage: this month's analyses against the monthly quota */
+export interface AnalysisUsageInfo {
+  username: string
+  /** "2026-10", UTC */
+  month: string
+  /** Units per month; null when unlimited */
+  quota?: number | null
+  used: number
+  remaining?: number | null
+  resets_at: string
+  analyses: number
+  by_tier: TierUsage[]
+  /** Units one analysis costs, by tier */
+  weights: Record<string, number>
+  /** Null when exempt */
+  rate_limit?: AnalysisRateLimit | null
+}
+
+export interface AnalysisCandidate {
+  name: string
+  description: string
+  confidence: number
+}
+
 export interface AnalysisResult {
   name: string
   description: string
   confidence: number
   method: string
+  /** Backend analysis provider that answered, e.g. "ollama" */
+  provider?: string
   tier: string
   analysis_time: string
+  /** Every identification the provider offered, best (the one above) first */
+  candidates?: AnalysisCandidate[]
+  /** Reused from an earlier analysis of the same image at the same tier */
+  cached?: boolean
   embedding?: number[]
 }
 
+/** How far a queued analysis has got, as streamed by /api/analyze/jobs/{id}/events */
+export type AnalysisStage = 'received' | 'preprocessing' | 'inference' | 'saving' | 'done' | 'failed'
+
+/** A queued analysis (POST /api/analyze answers 202 with one for slow tiers) */
+export interface AnalysisJob {
+  id: string
+  tier: string
+  status: 'queued' | 'running' | 'done' | 'failed'
+  stage: AnalysisStage
+  /** Who asked for it; only they and archive staff can see the job */
+  requested_by?: string | null
+  result?: AnalysisResult | null
+  error?: string | null
+  attempts: number
+  created_at: string
+  started_at?: string | null
+  finished_at?: string | null
+}
+
+export interface LoginRequest {
+  username: string
+  password: string
+}
+
+export interface UserUpdate {
+  name?: string
+  email?: string
+  role?: UserRole
+}
+
+export interface ProfileUpdate {
+  name?: string
+  email?: string
+}
+
+export interface AccountDeletion {
+  password: string
+}
+
+export interface UserFilters {
+  q?: string
+  role?: UserRole
+  email?: string
+  active?: boolean
+  limit?: number
+  offset?: number
+}
+
+export interface OAuthProvider {
+  id: string
+  name: string
+}
+
+export interface PasswordPolicy {
+  min_length: number
+  max_length: number
+  require_uppercase: boolean
+  require_lowercase: boolean
+  require_digit: boolean
+  require_symbol: boolean
+  check_breached: boolean
+}
+
+export interface BootstrapRequest {
+  username: string
+  name: string
+  password: string
+  email?: string
+}
+
+export interface BootstrapStatus {
+  required: boolean
+}
+
+export interface RegisterRequest {
+  username: string
+  name: string
+  email: string
+  password: string
+  invite_code?: string
+}
+
+export interface RegistrationInfo {
+  invite_only: boolean
+}
+
+export interface InviteCreate {
+  role?: UserRole
+  email?: string
+}
+
+export interface InviteInfo {
+  id: number
+  prefix: string
+  role: UserRole
+  email?: string | null
+  created_by: string
+  created_at: string
+  expires_at: string
+  used_by?: string | null
+  used_at?: string | null
+  revoked_at?: string | null
+}
+
+export interface InviteCreated extends InviteInfo {
+  code: string
+}
+
+export interface OrganizationCreate {
+  slug: string
+  name: string
+  owner?: string
+}
+
+export interface OrganizationInfo {
+  id: number
+  slug: string
+  name: string
+  created_at: string
+  member_count: number
+  rank?: OrgRank | null
+}
+
+export interface OrgMember {
+  username: string
+  name: string
+  email: string
+  rank: OrgRank
+  joined_at: string
+}
+
+export interface OrgMemberAdd {
+  username: string
+  rank?: OrgRank
+}
+
+export interface OrgMemberUpdate {
+  rank: OrgRank
+}
+
+export type IpRuleAction = 'allow' | 'deny'
+
+export interface IpRuleCreate {
+  cidr: string
+  action: IpRuleAction
+  note?: string
+}
+
+export interface IpRuleInfo {
+  id?: number | null
+  cidr: string
+  action: IpRuleAction
+  note?: string | null
+  created_by?: string | null
+  created_at?: string | null
+  source: 'config' | 'admin'
+}
+
+export interface UserCreate {
+  username: string
+  name: string
+  password: string
+  role: UserRole
+  email: string
+}
+
+export interface LoginResponse extends User {
+  access_token?: string
+  refresh_token?: string
+  token_type?: string
+  expires_in?: number
+}
+
+export interface SessionResponse extends User {
+  csrf_token: string
+  expires_in: number
+}
+
+export interface ArtifactCreated {
+  id: number
+  message: string
+  has_3d_model: boolean
+}
+
+/** An artifact whose image looks like another, from /api/artifacts/{id}/similar */
+export interface SimilarArtifact {
+  id: number
+  name: string
+  description?: string | null
+  similarity_score: number
+  thumbnail_url?: string | null
+}
+
+/** A pin for the map: where an artifact was photographed */
+export interface ArtifactLocation {
+  id: number
+  name: string
+  latitude: number
+  longitude: number
+  captured_at?: string | null
+  thumbnail_url?: string | null
+}
+
+export type ImportConflict = 'skip' | 'replace' | 'duplicate'
+export type ExportFormat = 'bundle' | 'dublin-core' | 'lido'
+
+export interface ImportedArtifact {
+  source_id?: number | null
+  id?: number | null
+  status: 'imported' | 'replaced' | 'skipped' | 'failed'
+  error?: string | null
+}
+
+export interface ImportResult {
+  imported: number
+  replaced: number
+  skipped: number
+  failed: number
+  collections_created: number
+  artifacts: ImportedArtifact[]
+}
+
+/** A tag and how many of the artifacts the user can see carry it (GET /api/tags) */
+export interface TagCount {
+  tag: string
+  count: number
+}
+
+/** Admin only: replace every `sources` tag with `target` */
+export interface TagMerge {
+  sources: string[]
+  target: string
+}
+
+/** Admin only: renaming to a tag already in use merges them */
+export interface TagRename {
+  tag: string
+  new_name: string
+}
+
+export interface TagChange {
+  /** The tag the artifacts now carry */
+  tag: string
+  artifacts_changed: number
+}
+
+export interface FlagCreate {
+  reason: string
+}
+
+export interface FlagResolution {
+  /** What was done, e.g. "Identification corrected" */
+  resolution: string
+}
+
+/** A curator's report that an artifact needs review */
+export interface ArtifactFlag {
+  id: number
+  artifact_id: number
+  reason: string
+  flagged_by: string
+  created_at: string
+  /** Null while the flag is open */
+  resolved_at?: string | null
+  resolved_by?: string | null
+  resolution?: string | null
+}
+
+export interface LockRequest {
+  reason: string
+}
+
+/** A curator's identification; fields left out keep their current value */
+export interface IdentificationOverride {
+  name: string
+  description?: string | null
+  era?: string | null
+  cultural_context?: string | null
+  material?: string | null
+  function?: string | null
+  /** For the audit log */
+  reason: string
+}
+
+export type RevisionAction =
+  | 'created'
+  | 'updated'
+  | 'deleted'
+  | 'restored'
+  | 'locked'
+  | 'unlocked'
+  | 'identified'
+  | 'reverted'
+
+export interface FieldChange {
+  /** Database column, e.g. "age" for the era */
+  field: string
+  old?: string | number | null
+  new?: string | number | null
+}
+
+/** One change to an artifact (GET /api/artifacts/{id}/history) */
+export interface ArtifactRevision {
+  id: number
+  artifact_id: number
+  action: RevisionAction
+  changed_by?: string | null
+  changed_at: string
+  changes: FieldChange[]
+}
+
+/** An artifact with open flags (GET /api/moderation/queue) */
+export interface ModerationQueueItem extends Artifact {
+  open_flags: ArtifactFlag[]
+}
+
+/** An artifact in the trash (GET /api/trash) */
+export interface TrashedArtifact extends Artifact {
+  deleted_at: string
+  deleted_by?: string | null
+  /** When it will be deleted for good; null if the server keeps the trash */
+  purge_at: string | null
+}
+
+export interface ArtifactMutation {
+  id: number
+  message: string
+  verification_status?: VerificationStatus
+  email_sent?: boolean
+}
+
+/** One image of a batch analysis, in the order it was uploaded */
+export interface AnalysisBatchItem {
+  index: number
+  filename?: string | null
+  job_id: string
+  status: AnalysisJob['status']
+  result?: AnalysisResult | null
+  error?: string | null
+}
+
+/** POST /api/analyze/batch: every image is queued as its own job under one id */
+export interface AnalysisBatch {
+  id: string
+  tier: string
+  requested_by?: string | null
+  status: 'queued' | 'running' | 'done'
+  total: number
+  done: number
+  failed: number
+  items: AnalysisBatchItem[]
+}
+
+export interface MessageResponse {
+  message: string
+}
+
+export interface UserCreated extends MessageResponse {
+  username: string
+}
+
+export interface ApiKeyInfo {
+  id: number
+  username: string
+  name: string
+  prefix: string
+  created_at: string
+  last_used_at?: string | null
+  revoked_at?: string | null
+}
+
+export interface ApiKeyCreated extends ApiKeyInfo {
+  key: string
+}
+
+export interface WebhookCreate {
+  url: string
+  events?: WebhookEvent[]
+}
+
+export interface WebhookInfo {
+  id: number
+  owner_id: string
+  url: string
+  events: WebhookEvent[]
+  created_at?: string | null
+  last_delivery_at?: string | null
+  last_status?: 'delivered' | 'failed' | null
+  last_error?: string | null
+}
+
+/** The secret that signs deliveries is only returned once, at creation */
+export interface WebhookCreated extends WebhookInfo {
+  secret: string
+}
+
+export interface FailedLogin {
+  username: string
+  attempted_at: string
+  ip_address?: string | null
+}
+
+export interface LockedAccount {
+  username: string
+  locked_until: string
+}
+
+export interface AdminStats {
+  total_users: number
+  active_users: number
+  deactivated_users: number
+  users_by_role: Record<string, number>
+  active_sessions: number
+  signed_in_users: number
+  failed_logins_24h: number
+  recent_failed_logins: FailedLogin[]
+  locked_accounts: LockedAccount[]
+  generated_at: string
+}
+
+export interface LoginRecord {
+  logged_in_at: string
+  ip_address?: string | null
+  user_agent?: string | null
+  method: string
+}
+
 export interface AuditLog {
   timestamp: string
   username: string
@@ -0,0 +1,27 @@
+/**
+ * Run `worker` over `items` with at most `limit` calls in flight at once.
+ * Results come back in input order; a failing item doesn't stop the others.
+ */
+export async function mapWithConcurrency<T, R>(
+  items: T[],
+  limit: number,
+  worker: (item: T, index: number) => Promise<R>
+): Promise<PromiseSettledResult<R>[]> {
+  const results: PromiseSettledResult<R>[] = new Array(items.length)
+  let next = 0
+
+  const runLane = async () => {
+    while (next < items.length) {
+      const index = next++
+      try {
+        results[index] = { status: 'fulfilled', value: await worker(items[index], index) }
+      } catch (reason) {
+        results[index] = { status: 'rejected', reason }
+      }
+    }
+  }
+
+  const laneCount = Math.max(1, Math.min(limit, items.length))
+  await Promise.all(Array.from({ length: laneCount }, runLane))
+  return results
+}
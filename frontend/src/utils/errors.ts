@@ -0,0 +1,100 @@
+import axios from 'axios'
+
+/**
+ * Client-side view of the backend's typed errors (MainApp/errors.py).
+ * The server sends `{ detail, code, request_id }`; older routes send only
+ * `detail`, in which case the code is derived from the HTTP status. Invalid
+ * payloads (422) also carry `errors`, a message per field.
+ *
+ * Every request carries an `X-Request-ID` that the server logs and echoes, so
+ * errors keep it as `requestId` even when the body doesn't include one.
+ */
+export type ErrorCode =
+  | 'validation_error'
+  | 'authentication_failed'
+  | 'permission_denied'
+  | 'not_found'
+  | 'conflict'
+  | 'duplicate_artifact'
+  | 'precondition_failed'
+  | 'account_locked'
+  | 'rate_limited'
+  | 'quota_exceeded'
+  | 'ip_blocked'
+  | 'database_error'
+  | 'external_service_error'
+  | 'internal_error'
+  | 'http_error'
+  | 'network_error'
+
+const codeForStatus = (status: number): ErrorCode => {
+  switch (status) {
+    case 400:
+    case 422:
+      return 'validation_error'
+    case 401:
+      return 'authentication_failed'
+    case 403:
+      return 'permission_denied'
+    case 404:
+      return 'not_found'
+    case 409:
+      return 'conflict'
+    case 412:
+      return 'precondition_failed'
+    case 423:
+      return 'account_locked'
+    case 429:
+      return 'rate_limited'
+    case 502:
+      return 'external_service_error'
+    default:
+      return 'internal_error'
+  }
+}
+
+export class AppError extends Error {
+  constructor(
+    message: string,
+    public code: ErrorCode,
+    public status?: number,
+    public fieldErrors: Record<string, string> = {},
+    public requestId?: string
+  ) {
+    super(message)
+    this.name = 'AppError'
+  }
+}
+
+/** Normalise anything thrown by an API call into an AppError */
+export function toAppError(error: unknown): AppError {
+  if (error instanceof AppError) return error
+
+  if (axios.isAxiosError(error)) {
+    const sentId = error.config?.headers?.get?.('X-Request-ID')
+    if (!error.response) {
+      return new AppError('Could not reach the server', 'network_error', undefined, {}, sentId ? String(sentId) : undefined)
+    }
+    const { status, data, headers } = error.response
+    const detail = typeof data?.detail === 'string' ? data.detail : error.message
+    const requestId = data?.request_id ?? headers?.['x-request-id'] ?? sentId
+    return new AppError(detail, data?.code ?? codeForStatus(status), status, data?.errors ?? {}, requestId ? String(requestId) : undefined)
+  }
+
+  return new AppError(error instanceof Error ? error.message : String(error), 'internal_error')
+}
+
+/** The message to show the user for a failed API call */
+export function errorMessage(error: unknown): string {
+  const appError = toAppError(error)
+  const fields = Object.entries(appError.fieldErrors)
+  if (fields.length > 0) {
+    return `${appError.message}: ${fields.map(([field, message]) => `${field}: ${message}`).join('; ')}`
+  }
+  // Server faults and lost connections: give the user something to quote when reporting it
+  const serverFault = (appError.status && appError.status >= 500) || appError.code === 'network_error'
+  if (serverFault && appError.requestId) {
+    return `${appError.message} (reference ${appError.requestId})`
+  }
+  return appError.message
+}
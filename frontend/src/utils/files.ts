@@ -0,0 +1,226 @@
+/**
+ * What image pickers offer. The server converts HEIC, TIFF, BMP and camera
+ * RAW itself; RAW extensions are listed because browsers have no image/* type for them.
+ */
+export const IMAGE_ACCEPT = 'image/*,.heic,.heif,.tif,.tiff,.bmp,.dng,.cr2,.cr3,.nef,.arw,.orf,.rw2,.raf'
+
+const IMAGE_EXTENSIONS = IMAGE_ACCEPT.split(',').filter((type) => type.startsWith('.'))
+
+/** Whether a file looks like an image the upload pages take, by type or extension */
+export const isImageFile = (file: File) =>
+  file.type.startsWith('image/') || IMAGE_EXTENSIONS.some((extension) => file.name.toLowerCase().endsWith(extension))
+
+export interface PickOptions {
+  multiple?: boolean
+  /** Pick a folder and take every image in it, subfolders included */
+  directory?: boolean
+}
+
+// The File System Access API's pickers (Chromium); not in TypeScript's DOM types yet
+interface PickerWindow {
+  showOpenFilePicker?: (options: {
+    multiple?: boolean
+    types?: { description: string; accept: Record<string, string[]> }[]
+  }) => Promise<FileSystemFileHandle[]>
+  showDirectoryPicker?: (options?: { mode?: 'read' }) => Promise<FileSystemDirectoryHandle>
+}
+
+type DirectoryHandle = FileSystemDirectoryHandle & { values(): AsyncIterable<FileSystemHandle> }
+
+async function filesIn(directory: DirectoryHandle): Promise<File[]> {
+  const files: File[] = []
+  for await (const entry of directory.values()) {
+    if (entry.kind === 'directory') files.push(...(await filesIn(entry as DirectoryHandle)))
+    else files.push(await (entry as FileSystemFileHandle).getFile())
+  }
+  return files
+}
+
+// Browsers without the pickers: a file input, which still opens the OS dialog
+function pickWithInput({ multiple, directory }: PickOptions): Promise<File[]> {
+  return new Promise((resolve) => {
+    const input = document.createElement('input')
+    input.type = 'file'
+    input.accept = IMAGE_ACCEPT
+    input.multiple = !!multiple
+    input.webkitdirectory = !!directory
+    input.onchange = () => resolve(Array.from(input.files ?? []))
+    input.addEventListener('cancel', () => resolve([]))
+    input.click()
+  })
+}
+
+/**
+ * Let the user choose images (or a folder of them) with the operating
+ * system's file dialog. Resolves to no files if the dialog is cancelled;
+ * files in a folder that aren't images are left out.
+ */
+export async function pickImages(options: PickOptions = {}): Promise<File[]> {
+  const pickers = window as Window & PickerWindow
+  try {
+    if (options.directory && pickers.showDirectoryPicker) {
+      const files = await filesIn((await pickers.showDirectoryPicker({ mode: 'read' })) as DirectoryHandle)
+      return files.filter(isImageFile)
+    }
+    if (!options.directory && pickers.showOpenFilePicker) {
+      const handles = await pickers.showOpenFilePicker({
+        multiple: options.multiple,
+        types: [{ description: 'Images', accept: { 'image/*': IMAGE_EXTENSIONS } }],
+      })
+      return Promise.all(handles.map((handle) => handle.getFile()))
+    }
+  } catch (error) {
+    if (error instanceof DOMException && error.name === 'AbortError') return []
+    throw error
+  }
+  const files = await pickWithInput(options)
+  return options.directory ? files.filter(isImageFile) : files
+}
+
+async function entryFiles(entry: FileSystemEntry): Promise<File[]> {
+  if (entry.isFile) {
+    return [await new Promise<File>((resolve, reject) => (entry as FileSystemFileEntry).file(resolve, reject))]
+  }
+  const reader = (entry as FileSystemDirectoryEntry).createReader()
+  const files: File[] = []
+  // readEntries hands out a folder's entries a batch at a time, then an empty batch
+  for (;;) {
+    const batch = await new Promise<FileSystemEntry[]>((resolve, reject) => reader.readEntries(resolve, reject))
+    if (batch.length === 0) return files
+    for (const child of batch) files.push(...(await entryFiles(child)))
+  }
+}
+
+/** The images in a drag and drop, including those inside dropped folders */
+export async function droppedImages(data: DataTransfer): Promise<File[]> {
+  // Entries have to be taken before the drop event handler returns
+  const entries = Array.from(data.items)
+    .map((item) => item.webkitGetAsEntry())
+    .filter((entry): entry is FileSystemEntry => entry !== null)
+  if (entries.length === 0) return Array.from(data.files).filter(isImageFile)
+  const files = await Promise.all(entries.map(entryFiles))
+  return files.flat().filter(isImageFile)
+}
+
+export interface EncodedImage {
+  /** The whole file as a base64 data URL, as the API takes images */
+  dataUrl: string
+  /** Hex SHA-256 of the file's bytes; empty where the browser can't hash (plain http://) */
+  sha256: string
+  /** A small JPEG data URL to show, or null when the browser can't decode the format */
+  preview: string | null
+}
+
+export interface EncodeProgress {
+  loaded: number
+  total: number
+}
+
+export interface EncodeOptions {
+  onProgress?: (progress: EncodeProgress) => void
+  /** Longest side of the preview in pixels; no preview without it */
+  previewSize?: number
+  signal?: AbortSignal
+}
+
+// Messages between this module and workers/imageEncoder.ts
+export type EncoderRequest =
+  | { id: number; task: 'encode'; file: File; previewSize?: number }
+  | { id: number; task: 'downscale'; file: File; maxDimension: number }
+
+export type EncoderMessage =
+  | { id: number; type: 'progress'; loaded: number; total: number }
+  | { id: number; type: 'done'; image: EncodedImage }
+  | { id: number; type: 'downscaled'; image: Blob | null }
+  | { id: number; type: 'error'; message: string }
+
+type Result = Extract<EncoderMessage, { type: 'done' | 'downscaled' }>
+// Omit from each member; plain Omit would merge the union
+type WithoutId<T> = T extends unknown ? Omit<T, 'id'> : never
+
+interface PendingRequest {
+  resolve: (message: Result) => void
+  reject: (error: Error) => void
+  onProgress?: (progress: EncodeProgress) => void
+}
+
+let encoder: Worker | null = null
+let nextRequestId = 0
+const pending = new Map<number, PendingRequest>()
+
+// One worker for the app; it takes files in turn
+const getEncoder = () => {
+  if (!encoder) {
+    encoder = new Worker(new URL('../workers/imageEncoder.ts', import.meta.url), { type: 'module' })
+    encoder.onmessage = (event: MessageEvent<EncoderMessage>) => {
+      const message = event.data
+      const request = pending.get(message.id)
+      if (!request) return
+      if (message.type === 'progress') {
+        request.onProgress?.({ loaded: message.loaded, total: message.total })
+        return
+      }
+      pending.delete(message.id)
+      if (message.type === 'error') request.reject(new Error(`Could not read the image: ${message.message}`))
+      else request.resolve(message)
+    }
+  }
+  return encoder
+}
+
+// Aborting stops waiting for the result (the worker finishes the file regardless)
+function runInWorker(
+  request: WithoutId<EncoderRequest>,
+  signal?: AbortSignal,
+  onProgress?: (progress: EncodeProgress) => void
+): Promise<Result> {
+  return new Promise((resolve, reject) => {
+    const id = nextRequestId++
+    const abort = () => {
+      pending.delete(id)
+      reject(new DOMException('Encoding was cancelled', 'AbortError'))
+    }
+    if (signal?.aborted) return abort()
+    signal?.addEventListener('abort', abort, { once: true })
+    const settle = <T,>(done: (value: T) => void) => (value: T) => {
+      signal?.removeEventListener('abort', abort)
+      done(value)
+    }
+    pending.set(id, { resolve: settle(resolve), reject: settle(reject), onProgress })
+    getEncoder().postMessage({ ...request, id } as EncoderRequest)
+  })
+}
+
+/**
+ * Read a file as a base64 data URL, with its hash and optionally a preview,
+ * in a worker so large images don't freeze the page.
+ */
+export async function encodeImage(file: File, { onProgress, previewSize, signal }: EncodeOptions = {}): Promise<EncodedImage> {
+  if (typeof Worker === 'undefined') {
+    return { dataUrl: await readWithFileReader(file), sha256: '', preview: null }
+  }
+  const result = await runInWorker({ task: 'encode', file, previewSize }, signal, onProgress)
+  return (result as Extract<Result, { type: 'done' }>).image
+}
+
+/**
+ * The image scaled down as a JPEG so its longest side is at most
+ * `maxDimension` pixels, for a quicker and cheaper analysis. The file itself
+ * comes back when it is small enough already, `maxDimension` is 0, or the
+ * browser can't decode it (HEIC, RAW).
+ */
+export async function downscaleImage(file: File, maxDimension: number, signal?: AbortSignal): Promise<File> {
+  if (!maxDimension || typeof Worker === 'undefined') return file
+  const result = await runInWorker({ task: 'downscale', file, maxDimension }, signal)
+  const image = (result as Extract<Result, { type: 'downscaled' }>).image
+  return image ? new File([image], file.name.replace(/\.[^.]*$/, '') + '.jpg', { type: 'image/jpeg' }) : file
+}
+
+function readWithFileReader(file: File): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const reader = new FileReader()
+    reader.readAsDataURL(file)
+    reader.onload = () => resolve(reader.result as string)
+    reader.onerror = (error) => reject(error)
+  })
+}
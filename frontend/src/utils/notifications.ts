@@ -0,0 +1,28 @@
+/**
+ * Desktop notifications for work that finishes while the app isn't being
+ * looked at (another tab, a minimized window). Browsers only ask for
+ * permission in response to a user action, so ask when the work starts.
+ */
+
+const supported = () => typeof Notification !== 'undefined'
+
+/** Ask for permission once; later calls (or a refusal) don't ask again */
+export function requestNotificationPermission() {
+  if (supported() && Notification.permission === 'default') {
+    Notification.requestPermission().catch(() => undefined)
+  }
+}
+
+/**
+ * Notify if the page is hidden and notifications are allowed. Clicking
+ * the notification brings the app's window to the front and runs `onClick`.
+ */
+export function notifyIfHidden(title: string, body: string, onClick?: () => void) {
+  if (!supported() || Notification.permission !== 'granted' || !document.hidden) return
+  const notification = new Notification(title, { body })
+  notification.onclick = () => {
+    window.focus()
+    notification.close()
+    onClick?.()
+  }
+}
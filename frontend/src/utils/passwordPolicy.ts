@@ -0,0 +1,52 @@
+import { PasswordPolicy } from '../types'
+
+/**
+ * Client-side mirror of the backend's password rules (see
+ * MainApp/password_policy.py), used for live feedback while typing. The
+ * server still enforces them, and alone checks for breached passwords.
+ */
+
+// Backend defaults, used until GET /auth/password/policy answers
+export const DEFAULT_PASSWORD_POLICY: PasswordPolicy = {
+  min_length: 8,
+  max_length: 128,
+  require_uppercase: false,
+  require_lowercase: false,
+  require_digit: false,
+  require_symbol: false,
+  check_breached: true,
+}
+
+export interface PasswordRule {
+  label: string
+  met: (password: string) => boolean
+}
+
+export function passwordRules(policy: PasswordPolicy): PasswordRule[] {
+  const rules: PasswordRule[] = [
+    {
+      label: `${policy.min_length}-${policy.max_length} characters`,
+      met: (p) => p.length >= policy.min_length && p.length <= policy.max_length,
+    },
+  ]
+  if (policy.require_uppercase) rules.push({ label: 'An uppercase letter', met: (p) => /\p{Lu}/u.test(p) })
+  if (policy.require_lowercase) rules.push({ label: 'A lowercase letter', met: (p) => /\p{Ll}/u.test(p) })
+  if (policy.require_digit) rules.push({ label: 'A digit', met: (p) => /\p{Nd}/u.test(p) })
+  if (policy.require_symbol) rules.push({ label: 'A symbol', met: (p) => /[^\p{L}\p{N}\s]/u.test(p) })
+  return rules
+}
+
+/** Labels of the rules the password doesn't meet yet */
+export const passwordProblems = (password: string, policy: PasswordPolicy) =>
+  passwordRules(policy)
+    .filter((rule) => !rule.met(password))
+    .map((rule) => rule.label.toLowerCase())
+
+/** Rough strength from 0 (empty) to 4, based on length and character variety */
+export function passwordStrength(password: string): number {
+  if (!password) return 0
+  const classes = [/[a-z]/, /[A-Z]/, /\d/, /[^A-Za-z0-9]/].filter((re) => re.test(password)).length
+  let score = password.length >= 16 ? 3 : password.length >= 12 ? 2 : password.length >= 8 ? 1 : 0
+  if (classes >= 3) score += 1
+  return Math.max(1, Math.min(4, score))
+}
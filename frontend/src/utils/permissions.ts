@@ -1,5 +1,13 @@
 import { UserRole } from '../types'
 
+// Display names for each role
+export const ROLE_LABELS: Record<UserRole, string> = {
+  user: 'User',
+  admin: 'Admin',
+  field: 'Field Engineer',
+  onsite: 'Lab Engineer',
+}
+
 /**
  * Check if a user role has a specific permission.
  * Admin role automatically has ALL permissions.
@@ -36,17 +44,44 @@ export function canAccess(userRole: UserRole | undefined, feature: string): bool
   // Define feature permissions for each role
   const permissions: Record<string, UserRole[]> = {
     'upload': ['user', 'field', 'admin'],
+    'batch-upload': ['field', 'admin'],
     'gallery': ['user', 'field', 'onsite', 'admin'],
     'edit': ['onsite', 'admin'],
+    'delete': ['onsite', 'admin'],
     'verify': ['onsite', 'admin'],
+    'all-artifacts': ['onsite', 'admin'],
+    'moderation': ['onsite', 'admin'],
     'user-management': ['admin'],
     'audit-logs': ['admin'],
+    'tag-management': ['admin'],
   }
   
   const allowedRoles = permissions[feature] || []
   return allowedRoles.includes(userRole)
 }
 
+/**
+ * Error raised when the client refuses to issue a request the current
+ * user's role isn't allowed to make.
+ */
+export class PermissionDeniedError extends Error {
+  constructor(public feature: string) {
+    super(`You don't have permission to use ${feature}`)
+    this.name = 'PermissionDeniedError'
+  }
+}
+
+/**
+ * Throw PermissionDeniedError unless the role can access the feature.
+ * The backend must still enforce its own checks; this only stops the UI
+ * from sending requests that are bound to be rejected.
+ */
+export function requirePermission(userRole: UserRole | undefined, feature: string): void {
+  if (!canAccess(userRole, feature)) {
+    throw new PermissionDeniedError(feature)
+  }
+}
+
 /**
  * Get all roles that have a specific permission.
  */
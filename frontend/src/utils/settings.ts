@@ -0,0 +1,64 @@
+import { settingsApi } from '../services/api'
+import { session } from '../services/session'
+
+/**
+ * User-adjustable application settings. Kept in localStorage for instant
+ * reads and saved to the signed-in user's account so they follow them to
+ * other machines.
+ */
+export interface AppSettings {
+  // How many files a batch upload analyzes at the same time
+  batchConcurrency: number
+  // Analysis tier preselected on the upload pages
+  defaultTier: string
+  // Photos are scaled down to this longest side (pixels) before analysis; 0 sends them full size.
+  // The archive always keeps the original.
+  analysisMaxDimension: number
+}
+
+const STORAGE_KEY = 'settings'
+
+export const MAX_BATCH_CONCURRENCY = 8
+
+// Choices for analysisMaxDimension, with 0 meaning full size
+export const ANALYSIS_SIZES = [0, 4096, 2048, 1024]
+
+export const analysisSizeLabel = (size: number) => (size ? `Up to ${size} px` : 'Full size')
+
+const defaults: AppSettings = {
+  batchConcurrency: 3,
+  defaultTier: 'fast',
+  analysisMaxDimension: 2048,
+}
+
+export function getSettings(): AppSettings {
+  try {
+    const stored = localStorage.getItem(STORAGE_KEY)
+    return stored ? { ...defaults, ...JSON.parse(stored) } : { ...defaults }
+  } catch (e) {
+    return { ...defaults }
+  }
+}
+
+export function updateSettings(changes: Partial<AppSettings>): AppSettings {
+  const settings = { ...getSettings(), ...changes }
+  localStorage.setItem(STORAGE_KEY, JSON.stringify(settings))
+  if (session.getUser()) {
+    settingsApi.save({ ...settings }).catch((error) => console.warn('Could not save settings to your account:', error))
+  }
+  return settings
+}
+
+/** Replace this browser's settings with the ones saved to the signed-in account */
+export async function loadAccountSettings(): Promise<AppSettings> {
+  const saved = await settingsApi.get()
+  if (Object.keys(saved).length === 0) {
+    // Nothing saved yet: start the account off with this browser's settings
+    const settings = getSettings()
+    await settingsApi.save({ ...settings })
+    return settings
+  }
+  const settings = { ...defaults, ...(saved as Partial<AppSettings>) }
+  localStorage.setItem(STORAGE_KEY, JSON.stringify(settings))
+  return settings
+}
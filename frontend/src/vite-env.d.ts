@@ -0,0 +1,11 @@
+/// <reference types="vite/client" />
+
+interface ImportMetaEnv {
+  readonly VITE_API_URL?: string
+  readonly VITE_DEMO_MODE?: string
+  readonly VITE_AUTH_MODE?: string
+}
+
+interface ImportMeta {
+  readonly env: ImportMetaEnv
+}
@@ -0,0 +1,73 @@
+// Base64-encodes, hashes and downscales images for utils/files.ts, off the main thread
+import type { EncoderMessage, EncoderRequest } from '../utils/files'
+
+// A multiple of 3, so each chunk encodes to base64 on its own
+const CHUNK_BYTES = 3 * 1024 * 1024
+// String.fromCharCode takes its arguments on the stack
+const CHAR_CODES_AT_ONCE = 0x8000
+
+const post = (message: EncoderMessage) => self.postMessage(message)
+
+const toBase64 = (bytes: Uint8Array) => {
+  let binary = ''
+  for (let i = 0; i < bytes.length; i += CHAR_CODES_AT_ONCE) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + CHAR_CODES_AT_ONCE))
+  }
+  return btoa(binary)
+}
+
+const toHex = (buffer: ArrayBuffer) =>
+  Array.from(new Uint8Array(buffer), (byte) => byte.toString(16).padStart(2, '0')).join('')
+
+// A JPEG of the image with its longest side at most `size`; null if the browser can't decode it
+const scaledJpeg = async (file: File, size: number, onlyIfLarger: boolean): Promise<Blob | null> => {
+  let bitmap: ImageBitmap
+  try {
+    bitmap = await createImageBitmap(file)
+  } catch {
+    // HEIC and RAW: browsers can't decode them, the server converts them on upload
+    return null
+  }
+  const scale = Math.min(1, size / Math.max(bitmap.width, bitmap.height))
+  if (scale === 1 && onlyIfLarger) {
+    bitmap.close()
+    return null
+  }
+  const canvas = new OffscreenCanvas(Math.round(bitmap.width * scale), Math.round(bitmap.height * scale))
+  canvas.getContext('2d')?.drawImage(bitmap, 0, 0, canvas.width, canvas.height)
+  bitmap.close()
+  return canvas.convertToBlob({ type: 'image/jpeg', quality: 0.9 })
+}
+
+const encode = async (id: number, file: File, previewSize?: number) => {
+  const buffer = await file.arrayBuffer()
+  const bytes = new Uint8Array(buffer)
+  const parts: string[] = []
+  for (let offset = 0; offset < bytes.length; offset += CHUNK_BYTES) {
+    parts.push(toBase64(bytes.subarray(offset, offset + CHUNK_BYTES)))
+    post({ id, type: 'progress', loaded: Math.min(offset + CHUNK_BYTES, bytes.length), total: bytes.length })
+  }
+  // crypto.subtle only exists on https:// and localhost
+  const sha256 = crypto.subtle ? toHex(await crypto.subtle.digest('SHA-256', buffer)) : ''
+  const preview = previewSize ? await scaledJpeg(file, previewSize, false) : null
+  // As FileReader.readAsDataURL writes it
+  const dataUrl = `data:${file.type || 'application/octet-stream'};base64,${parts.join('')}`
+  post({
+    id,
+    type: 'done',
+    image: { dataUrl, sha256, preview: preview && `data:image/jpeg;base64,${toBase64(new Uint8Array(await preview.arrayBuffer()))}` },
+  })
+}
+
+self.onmessage = async (event: MessageEvent<EncoderRequest>) => {
+  const request = event.data
+  try {
+    if (request.task === 'encode') {
+      await encode(request.id, request.file, request.previewSize)
+    } else {
+      post({ id: request.id, type: 'downscaled', image: await scaledJpeg(request.file, request.maxDimension, true) })
+    }
+  } catch (error) {
+    post({ id: request.id, type: 'error', message: error instanceof Error ? error.message : String(error) })
+  }
+}